@@ -1,7 +1,9 @@
-use crate::cdp::CdpClient;
+use crate::cdp::{CdpClient, CdpClientBackend, WebSocketBackend};
 use crate::error::{ChromeMcpError, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use tracing::debug;
 
 /// Represents an accessibility tree node
@@ -18,6 +20,9 @@ pub struct AccessibilityNode {
     pub focusable: bool,
     pub focused: bool,
     pub clickable: bool,
+    /// DOM node the accessibility node was computed for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend_node_id: Option<u64>,
 }
 
 /// Bounding box for accessibility nodes
@@ -29,45 +34,183 @@ pub struct Bounds {
     pub height: f64,
 }
 
+/// Output format of `AccessibilityManager::export_tree`: the full tree as JSON, an indented
+/// `role: name` outline, an outline of the nodes with a WAI-ARIA role only, Markdown
+/// (headings, links, buttons and text) or a flat `node_id,role,name,x,y,width,height,clickable` CSV table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeExportFormat {
+    Json,
+    TextOutline,
+    AriaRoles,
+    Markdown,
+    Csv,
+}
+
+/// WAI-ARIA roles kept by `TreeExportFormat::AriaRoles`. Chrome-internal roles
+/// (`StaticText`, `RootWebArea`, ...) and `generic`/`none` carry no ARIA semantics.
+const ARIA_ROLES: &[&str] = &[
+    "alert", "alertdialog", "application", "article", "banner", "blockquote", "button", "caption",
+    "cell", "checkbox", "code", "columnheader", "combobox", "complementary", "contentinfo",
+    "definition", "deletion", "dialog", "document", "emphasis", "feed", "figure", "form", "grid",
+    "gridcell", "group", "heading", "img", "insertion", "link", "list", "listbox", "listitem", "log",
+    "main", "marquee", "math", "menu", "menubar", "menuitem", "menuitemcheckbox", "menuitemradio",
+    "meter", "navigation", "note", "option", "paragraph", "progressbar", "radio", "radiogroup",
+    "region", "row", "rowgroup", "rowheader", "scrollbar", "search", "searchbox", "separator",
+    "slider", "spinbutton", "status", "strong", "subscript", "superscript", "switch", "tab", "table",
+    "tablist", "tabpanel", "term", "textbox", "time", "timer", "toolbar", "tooltip", "tree", "treegrid",
+    "treeitem",
+];
+
 /// Accessibility tree manager
-pub struct AccessibilityManager {
-    cdp: CdpClient,
-    cached_tree: Option<AccessibilityNode>,
+///
+/// Whole-tree queries fetch the tree in one `Accessibility.getFullAXTree` call. Depth-limited
+/// queries load it lazily: the root comes from `Accessibility.getRootAXNode` and the children
+/// of a node are only fetched (`Accessibility.getChildAXNodes`) when it is expanded.
+#[derive(Clone)]
+pub struct AccessibilityManager<B = WebSocketBackend> {
+    cdp: CdpClient<B>,
+    /// Root node, without children
+    root: Option<AccessibilityNode>,
+    /// Children of each expanded node, themselves without children
+    expanded: HashMap<String, Vec<AccessibilityNode>>,
+    /// Child ids Chrome reported for each fetched node, so leaves are never expanded
+    child_ids: HashMap<String, Vec<String>>,
+    /// Whether every node is expanded, as after `get_full_tree`
+    complete: bool,
 }
 
-impl AccessibilityManager {
-    pub fn new(cdp: CdpClient) -> Self {
+impl<B: CdpClientBackend> AccessibilityManager<B> {
+    pub fn new(cdp: CdpClient<B>) -> Self {
         Self {
             cdp,
-            cached_tree: None,
+            root: None,
+            expanded: HashMap::new(),
+            child_ids: HashMap::new(),
+            complete: false,
         }
     }
 
-    /// Get the full accessibility tree
+    /// Get the full accessibility tree, freshly fetched in one call
     pub async fn get_full_tree(&mut self) -> Result<AccessibilityNode> {
         debug!("Fetching full accessibility tree");
-        
-        let raw_tree = self.cdp.get_accessibility_tree().await?;
-        let root_node = self.parse_accessibility_tree(raw_tree)?;
-        
-        self.cached_tree = Some(root_node.clone());
-        Ok(root_node)
+
+        self.clear_cache();
+        let response = self.cdp.get_full_ax_tree().await.map_err(|e| match e.root_cause() {
+            // A result without `nodes`, rather than Chrome failing the command
+            ChromeMcpError::CdpProtocol(message) if message.starts_with("Unexpected Accessibility.getFullAXTree result") => {
+                ChromeMcpError::accessibility_error(format!("Invalid accessibility tree format: {}", message))
+            }
+            _ => e,
+        })?;
+        let mut nodes = Vec::with_capacity(response.nodes.len());
+        for node_raw in &response.nodes {
+            nodes.push(self.parse_shallow_node(node_raw)?);
+        }
+
+        // Chrome lists the root first
+        let root = nodes
+            .first()
+            .cloned()
+            .ok_or_else(|| ChromeMcpError::accessibility_error("Empty accessibility tree"))?;
+        let by_id: HashMap<String, AccessibilityNode> = nodes.into_iter().map(|node| (node.node_id.clone(), node)).collect();
+        for (node_id, child_ids) in &self.child_ids {
+            let children = child_ids.iter().filter_map(|id| by_id.get(id).cloned()).collect();
+            self.expanded.insert(node_id.clone(), children);
+        }
+        self.root = Some(root.clone());
+        self.complete = true;
+
+        Ok(self.assemble_tree(root, u32::MAX))
+    }
+
+    /// Full tree from the cache when `get_full_tree` loaded it, else freshly fetched
+    async fn full_tree(&mut self) -> Result<AccessibilityNode> {
+        match self.root.clone() {
+            Some(root) if self.complete => Ok(self.assemble_tree(root, u32::MAX)),
+            _ => self.get_full_tree().await,
+        }
+    }
+
+    /// Root node of the tree, without children
+    pub async fn get_root(&mut self) -> Result<AccessibilityNode> {
+        if let Some(ref root) = self.root {
+            return Ok(root.clone());
+        }
+
+        let response = self.cdp.get_root_ax_node().await?;
+        let root = self.parse_shallow_node(&response.node)?;
+        self.root = Some(root.clone());
+        Ok(root)
+    }
+
+    /// Children of a node, fetched on first use and cached. The children are returned
+    /// without their own children; expand them in turn to go deeper.
+    pub async fn expand_node(&mut self, node_id: &str) -> Result<Vec<AccessibilityNode>> {
+        if let Some(children) = self.expanded.get(node_id) {
+            return Ok(children.clone());
+        }
+
+        let mut children = Vec::new();
+        if self.child_ids.get(node_id).is_none_or(|ids| !ids.is_empty()) {
+            let response = self.cdp.get_child_ax_nodes(node_id).await?;
+            for node_raw in &response.nodes {
+                children.push(self.parse_shallow_node(node_raw)?);
+            }
+
+            // Keep the document order Chrome reported for the parent
+            if let Some(order) = self.child_ids.get(node_id) {
+                children.sort_by_key(|child| order.iter().position(|id| *id == child.node_id).unwrap_or(usize::MAX));
+            }
+        }
+
+        self.expanded.insert(node_id.to_string(), children.clone());
+        Ok(children)
     }
 
-    /// Parse raw CDP accessibility tree into structured nodes
-    fn parse_accessibility_tree(&self, raw_tree: Value) -> Result<AccessibilityNode> {
-        let nodes = raw_tree
-            .get("nodes")
-            .and_then(|n| n.as_array())
-            .ok_or_else(|| ChromeMcpError::accessibility_error("Invalid accessibility tree format"))?;
+    /// Tree with every node down to `depth` levels below the root expanded (0 = the root
+    /// alone). Nodes expanded before are served from the cache.
+    pub async fn expand_to_depth(&mut self, depth: u32) -> Result<AccessibilityNode> {
+        let root = self.get_root().await?;
+
+        let mut level = vec![root.node_id.clone()];
+        for _ in 0..depth {
+            let mut next_level = Vec::new();
+            for node_id in &level {
+                next_level.extend(self.expand_node(node_id).await?.into_iter().map(|child| child.node_id));
+            }
+            if next_level.is_empty() {
+                break;
+            }
+            level = next_level;
+        }
+
+        Ok(self.assemble_tree(root, depth))
+    }
 
-        if nodes.is_empty() {
-            return Err(ChromeMcpError::accessibility_error("Empty accessibility tree"));
+    /// Attach the cached children of `node`, down to `depth` levels
+    fn assemble_tree(&self, mut node: AccessibilityNode, depth: u32) -> AccessibilityNode {
+        if depth > 0 {
+            if let Some(children) = self.expanded.get(&node.node_id) {
+                node.children = children
+                    .iter()
+                    .map(|child| self.assemble_tree(child.clone(), depth - 1))
+                    .collect();
+            }
         }
+        node
+    }
 
-        // Find root node (usually the first one or one with no parent)
-        let root_raw = &nodes[0];
-        self.parse_node(root_raw, nodes)
+    /// Parse a node without its children, remembering its child ids
+    fn parse_shallow_node(&mut self, node_raw: &Value) -> Result<AccessibilityNode> {
+        let node = self.parse_node(node_raw, &[])?;
+        let child_ids = node_raw
+            .get("childIds")
+            .and_then(|c| c.as_array())
+            .map(|ids| ids.iter().filter_map(|id| id.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+        self.child_ids.insert(node.node_id.clone(), child_ids);
+        Ok(node)
     }
 
     /// Parse a single accessibility node
@@ -146,6 +289,7 @@ impl AccessibilityManager {
             focusable,
             focused,
             clickable,
+            backend_node_id: node_raw.get("backendDOMNodeId").and_then(|id| id.as_u64()),
         })
     }
 
@@ -181,50 +325,50 @@ impl AccessibilityManager {
         }
     }
 
-    /// Find nodes by role
+    /// Find nodes by role in the full tree
     pub async fn find_by_role(&mut self, role: &str) -> Result<Vec<AccessibilityNode>> {
-        let tree = if let Some(ref cached) = self.cached_tree {
-            cached.clone()
-        } else {
-            self.get_full_tree().await?
-        };
+        let tree = self.full_tree().await?;
 
         Ok(self.search_nodes_by_role(&tree, role))
     }
 
     /// Find nodes by name (text content)
     pub async fn find_by_name(&mut self, name: &str) -> Result<Vec<AccessibilityNode>> {
-        let tree = if let Some(ref cached) = self.cached_tree {
-            cached.clone()
-        } else {
-            self.get_full_tree().await?
-        };
+        let tree = self.full_tree().await?;
 
         Ok(self.search_nodes_by_name(&tree, name))
     }
 
     /// Find nodes by description
     pub async fn find_by_description(&mut self, description: &str) -> Result<Vec<AccessibilityNode>> {
-        let tree = if let Some(ref cached) = self.cached_tree {
-            cached.clone()
-        } else {
-            self.get_full_tree().await?
-        };
+        let tree = self.full_tree().await?;
 
         Ok(self.search_nodes_by_description(&tree, description))
     }
 
     /// Find clickable elements containing text
     pub async fn find_clickable_by_text(&mut self, text: &str) -> Result<Vec<AccessibilityNode>> {
-        let tree = if let Some(ref cached) = self.cached_tree {
-            cached.clone()
-        } else {
-            self.get_full_tree().await?
-        };
+        let tree = self.full_tree().await?;
 
         Ok(self.search_clickable_by_text(&tree, text))
     }
 
+    /// Node that has keyboard focus, from a freshly fetched tree. The document itself reports
+    /// focus when no element has it, so the deepest focused node wins.
+    pub async fn get_focused_node(&mut self) -> Result<Option<AccessibilityNode>> {
+        let tree = self.get_full_tree().await?;
+
+        Ok(self.search_focused_node(&tree))
+    }
+
+    /// Recursive search for the deepest focused node
+    fn search_focused_node(&self, node: &AccessibilityNode) -> Option<AccessibilityNode> {
+        node.children
+            .iter()
+            .find_map(|child| self.search_focused_node(child))
+            .or_else(|| node.focused.then(|| node.clone()))
+    }
+
     /// Recursive search for nodes by role
     fn search_nodes_by_role(&self, node: &AccessibilityNode, target_role: &str) -> Vec<AccessibilityNode> {
         let mut results = Vec::new();
@@ -319,15 +463,123 @@ impl AccessibilityManager {
 
     /// Clear cached tree (force refresh on next access)
     pub fn clear_cache(&mut self) {
-        self.cached_tree = None;
+        self.root = None;
+        self.expanded.clear();
+        self.child_ids.clear();
+        self.complete = false;
     }
 
-    /// Get a summary of the accessibility tree
-    pub async fn get_tree_summary(&mut self) -> Result<Vec<String>> {
-        let tree = self.get_full_tree().await?;
-        let mut summary = Vec::new();
-        self.collect_node_summaries(&tree, &mut summary, 0);
-        Ok(summary)
+    /// Fetch the tree, down to `depth` levels if given, and render it in the given format
+    pub async fn export_tree(&mut self, format: TreeExportFormat, depth: Option<u32>) -> Result<String> {
+        let tree = match depth {
+            Some(depth) => {
+                self.clear_cache();
+                self.expand_to_depth(depth).await?
+            }
+            None => self.get_full_tree().await?,
+        };
+        self.render_tree(&tree, format)
+    }
+
+    /// Render a parsed tree in the given format
+    fn render_tree(&self, tree: &AccessibilityNode, format: TreeExportFormat) -> Result<String> {
+        let mut lines = Vec::new();
+        match format {
+            TreeExportFormat::Json => return Ok(serde_json::to_string_pretty(tree)?),
+            TreeExportFormat::TextOutline => self.collect_node_summaries(tree, &mut lines, 0),
+            TreeExportFormat::AriaRoles => self.collect_aria_roles(tree, &mut lines, 0),
+            TreeExportFormat::Markdown => self.collect_markdown(tree, &mut lines),
+            TreeExportFormat::Csv => {
+                lines.push("node_id,role,name,x,y,width,height,clickable".to_string());
+                self.collect_csv_rows(tree, &mut lines);
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Recursively collect an outline of the nodes with a WAI-ARIA role
+    fn collect_aria_roles(&self, node: &AccessibilityNode, lines: &mut Vec<String>, depth: usize) {
+        let aria_role = node.role.as_deref().filter(|role| ARIA_ROLES.contains(role));
+
+        let child_depth = match aria_role {
+            Some(role) => {
+                let name = node.name.as_deref().unwrap_or("(no name)");
+                lines.push(format!("{}{}: {}", "  ".repeat(depth), role, name));
+                depth + 1
+            }
+            None => depth,
+        };
+
+        for child in &node.children {
+            self.collect_aria_roles(child, lines, child_depth);
+        }
+    }
+
+    /// Recursively collect Markdown lines for headings, links, buttons and text.
+    /// Nodes that produce a line are not descended into.
+    fn collect_markdown(&self, node: &AccessibilityNode, lines: &mut Vec<String>) {
+        let name = node.name.as_deref().map(str::trim).unwrap_or("");
+
+        // Headings, links, buttons and text carry their content in the name
+        let line = match node.role.as_deref() {
+            Some("heading") => {
+                let level = self.get_property_value(node, "level").and_then(|l| l.as_u64()).unwrap_or(1);
+                Some(format!("{} {}", "#".repeat(level.clamp(1, 6) as usize), name))
+            }
+            Some("link") => {
+                let url = self.get_property_value(node, "url").and_then(|u| u.as_str()).unwrap_or("");
+                Some(format!("[{}]({})", name, url))
+            }
+            Some("button") => Some(format!("- [Button] {}", name)),
+            Some("StaticText") if !name.is_empty() => Some(name.to_string()),
+            _ => None,
+        };
+
+        match line {
+            Some(line) => lines.push(line),
+            None => {
+                for child in &node.children {
+                    self.collect_markdown(child, lines);
+                }
+            }
+        }
+    }
+
+    /// Recursively collect CSV rows, one per node
+    fn collect_csv_rows(&self, node: &AccessibilityNode, rows: &mut Vec<String>) {
+        let (x, y, width, height) = match node.bounds {
+            Some(ref b) => (b.x.to_string(), b.y.to_string(), b.width.to_string(), b.height.to_string()),
+            None => Default::default(),
+        };
+
+        rows.push([
+            csv_field(&node.node_id),
+            csv_field(node.role.as_deref().unwrap_or("")),
+            csv_field(node.name.as_deref().unwrap_or("")),
+            x,
+            y,
+            width,
+            height,
+            node.clickable.to_string(),
+        ].join(","));
+
+        for child in &node.children {
+            self.collect_csv_rows(child, rows);
+        }
+    }
+
+    /// Value of a parsed node property (e.g. heading `level`, link `url`)
+    fn get_property_value<'a>(&self, node: &'a AccessibilityNode, property: &str) -> Option<&'a Value> {
+        node.properties
+            .as_ref()
+            .and_then(|props| props.as_array())
+            .and_then(|props_array| {
+                props_array.iter().find(|prop| {
+                    prop.get("name").and_then(|n| n.as_str()) == Some(property)
+                })
+            })
+            .and_then(|prop| prop.get("value"))
+            .and_then(|v| v.get("value"))
     }
 
     /// Recursively collect node summaries for debugging
@@ -351,10 +603,21 @@ impl AccessibilityManager {
     }
 }
 
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cdp::CdpClient;
+    use crate::error::ChromeMcpError;
+    use crate::testing::MockCdpClient;
     use serde_json::json;
 
     fn create_test_node(
@@ -376,6 +639,7 @@ mod tests {
             focusable: false,
             focused: false,
             clickable,
+            backend_node_id: None,
         }
     }
 
@@ -419,35 +683,31 @@ mod tests {
         let cdp = CdpClient::new("localhost", 9222);
         let manager = AccessibilityManager::new(cdp);
         
-        assert!(manager.cached_tree.is_none());
+        assert!(manager.root.is_none());
+        assert!(manager.expanded.is_empty());
     }
 
-    #[test]
-    fn test_parse_accessibility_tree_empty() {
-        let cdp = CdpClient::new("localhost", 9222);
-        let manager = AccessibilityManager::new(cdp);
-        
-        let empty_tree = json!({
-            "nodes": []
-        });
+    #[tokio::test]
+    async fn test_get_root_invalid_format() {
+        let mock = MockCdpClient::new(vec![("Accessibility.getRootAXNode", json!({ "nodes": [] }))]);
+        let mut manager = AccessibilityManager::new(mock.client());
         
-        let result = manager.parse_accessibility_tree(empty_tree);
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ChromeMcpError::Accessibility(_)));
+        let error = manager.get_root().await.unwrap_err();
+        assert!(matches!(error.root_cause(), ChromeMcpError::CdpProtocol(_)));
+        assert!(manager.root.is_none());
     }
 
     #[test]
-    fn test_parse_accessibility_tree_invalid_format() {
+    fn test_parse_shallow_node_records_child_ids() {
         let cdp = CdpClient::new("localhost", 9222);
-        let manager = AccessibilityManager::new(cdp);
-        
-        let invalid_tree = json!({
-            "not_nodes": []
-        });
-        
-        let result = manager.parse_accessibility_tree(invalid_tree);
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ChromeMcpError::Accessibility(_)));
+        let mut manager = AccessibilityManager::new(cdp);
+
+        let root = manager
+            .parse_shallow_node(&json!({ "nodeId": "1", "role": { "value": "RootWebArea" }, "childIds": ["2", "3"] }))
+            .unwrap();
+        assert_eq!(root.node_id, "1");
+        assert!(root.children.is_empty());
+        assert_eq!(manager.child_ids["1"], vec!["2", "3"]);
     }
 
     #[test]
@@ -581,6 +841,7 @@ mod tests {
             focusable: false,
             focused: false,
             clickable: true,
+            backend_node_id: None,
         };
         let non_clickable = create_test_node("4", Some("text"), Some("Click me"), false, None);
         
@@ -593,6 +854,47 @@ mod tests {
         assert_eq!(results_specific.len(), 1);
     }
 
+    fn create_export_tree() -> AccessibilityNode {
+        let mut root = create_test_node("1", Some("RootWebArea"), Some("Shop"), false, None);
+        let mut heading = create_test_node("2", Some("heading"), Some("Products"), false, Some((0.0, 0.0, 200.0, 40.0)));
+        heading.properties = Some(json!([{ "name": "level", "value": { "type": "integer", "value": 2 } }]));
+        heading.children = vec![create_test_node("3", Some("StaticText"), Some("Products"), false, None)];
+        let mut link = create_test_node("4", Some("link"), Some("Docs, FAQ"), true, Some((10.0, 50.0, 80.0, 20.0)));
+        link.properties = Some(json!([{ "name": "url", "value": { "type": "string", "value": "https://example.com/docs" } }]));
+        let mut generic = create_test_node("5", Some("generic"), None, false, None);
+        generic.children = vec![
+            create_test_node("6", Some("StaticText"), Some("In stock"), false, None),
+            create_test_node("7", Some("button"), Some("Buy"), true, Some((10.0, 80.0, 60.0, 24.0))),
+        ];
+        root.children = vec![heading, link, generic];
+        root
+    }
+
+    #[test]
+    fn test_render_tree_formats() {
+        let manager = AccessibilityManager::new(CdpClient::new("localhost", 9222));
+        let tree = create_export_tree();
+
+        let markdown = manager.render_tree(&tree, TreeExportFormat::Markdown).unwrap();
+        assert_eq!(markdown, "## Products\n[Docs, FAQ](https://example.com/docs)\nIn stock\n- [Button] Buy");
+
+        let aria = manager.render_tree(&tree, TreeExportFormat::AriaRoles).unwrap();
+        assert_eq!(aria, "heading: Products\nlink: Docs, FAQ\nbutton: Buy");
+
+        let csv = manager.render_tree(&tree, TreeExportFormat::Csv).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "node_id,role,name,x,y,width,height,clickable");
+        assert_eq!(rows.len(), 8);
+        assert_eq!(rows[1], "1,RootWebArea,Shop,,,,,false");
+        assert_eq!(rows[4], "4,link,\"Docs, FAQ\",10,50,80,20,true");
+
+        let outline = manager.render_tree(&tree, TreeExportFormat::TextOutline).unwrap();
+        assert!(outline.starts_with("RootWebArea: Shop\n  heading: Products @(0,0)"));
+
+        let exported: AccessibilityNode = serde_json::from_str(&manager.render_tree(&tree, TreeExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(exported.children.len(), 3);
+    }
+
     #[test]
     fn test_get_center_coords() {
         let cdp = CdpClient::new("localhost", 9222);
@@ -646,16 +948,18 @@ mod tests {
         let mut manager = AccessibilityManager::new(cdp);
         
         // Initially no cache
-        assert!(manager.cached_tree.is_none());
+        assert!(manager.root.is_none());
         
         // Set a cached tree
         let test_tree = create_test_node("1", Some("document"), Some("Test"), false, None);
-        manager.cached_tree = Some(test_tree);
-        assert!(manager.cached_tree.is_some());
+        manager.root = Some(test_tree);
+        manager.expanded.insert("1".to_string(), vec![create_test_node("2", Some("button"), None, true, None)]);
+        assert!(manager.root.is_some());
         
         // Clear cache
         manager.clear_cache();
-        assert!(manager.cached_tree.is_none());
+        assert!(manager.root.is_none());
+        assert!(manager.expanded.is_empty());
     }
 
     #[test]
@@ -690,4 +994,188 @@ mod tests {
         let results_name = manager.search_nodes_by_name(&node, "submit");
         assert_eq!(results_name.len(), 1);
     }
-}
\ No newline at end of file
+
+    fn lazy_tree_mock() -> MockCdpClient {
+        MockCdpClient::new(vec![
+            ("Accessibility.getRootAXNode", json!({
+                "node": {
+                    "nodeId": "1",
+                    "ignored": false,
+                    "role": { "type": "role", "value": "RootWebArea" },
+                    "name": { "type": "computedString", "value": "Sign in" },
+                    "childIds": ["2", "3"]
+                }
+            })),
+            ("Accessibility.getChildAXNodes", json!({
+                "nodes": [
+                    {
+                        "nodeId": "3",
+                        "ignored": false,
+                        "role": { "type": "role", "value": "form" },
+                        "childIds": ["4"]
+                    },
+                    {
+                        "nodeId": "2",
+                        "ignored": false,
+                        "role": { "type": "role", "value": "textbox" },
+                        "name": { "type": "computedString", "value": "Email" },
+                        "properties": [
+                            { "name": "focusable", "value": { "type": "booleanOrUndefined", "booleanValue": true } },
+                            { "name": "focused", "value": { "type": "booleanOrUndefined", "booleanValue": true } }
+                        ],
+                        "childIds": []
+                    }
+                ]
+            })),
+            ("Accessibility.getChildAXNodes", json!({
+                "nodes": [
+                    {
+                        "nodeId": "4",
+                        "ignored": false,
+                        "role": { "type": "role", "value": "button" },
+                        "name": { "type": "computedString", "value": "Continue" },
+                        "childIds": []
+                    }
+                ]
+            })),
+        ])
+    }
+
+    fn full_tree_mock() -> MockCdpClient {
+        MockCdpClient::new(vec![
+            ("Accessibility.getFullAXTree", json!({
+                "nodes": [
+                    {
+                        "nodeId": "1",
+                        "ignored": false,
+                        "role": { "type": "role", "value": "RootWebArea" },
+                        "name": { "type": "computedString", "value": "Sign in" },
+                        "childIds": ["2", "3"]
+                    },
+                    {
+                        "nodeId": "3",
+                        "ignored": false,
+                        "role": { "type": "role", "value": "form" },
+                        "childIds": ["4"]
+                    },
+                    {
+                        "nodeId": "4",
+                        "ignored": false,
+                        "role": { "type": "role", "value": "button" },
+                        "name": { "type": "computedString", "value": "Continue" },
+                        "childIds": []
+                    },
+                    {
+                        "nodeId": "2",
+                        "ignored": false,
+                        "role": { "type": "role", "value": "textbox" },
+                        "name": { "type": "computedString", "value": "Email" },
+                        "properties": [
+                            { "name": "focusable", "value": { "type": "booleanOrUndefined", "booleanValue": true } },
+                            { "name": "focused", "value": { "type": "booleanOrUndefined", "booleanValue": true } }
+                        ],
+                        "childIds": []
+                    }
+                ]
+            })),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_parse_accessibility_tree_empty() {
+        let mock = MockCdpClient::new(vec![("Accessibility.getFullAXTree", json!({ "nodes": [] }))]);
+        let mut manager = AccessibilityManager::new(mock.client());
+
+        let result = manager.get_full_tree().await;
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ChromeMcpError::Accessibility(_)));
+        assert!(manager.root.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_parse_accessibility_tree_invalid_format() {
+        let mock = MockCdpClient::new(vec![("Accessibility.getFullAXTree", json!({ "not_nodes": [] }))]);
+        let mut manager = AccessibilityManager::new(mock.client());
+
+        let result = manager.get_full_tree().await;
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ChromeMcpError::Accessibility(_)));
+    }
+
+    #[tokio::test]
+    async fn test_get_full_tree_with_mock_cdp() {
+        let mock = full_tree_mock();
+        let mut manager = AccessibilityManager::new(mock.client());
+
+        let tree = manager.get_full_tree().await.unwrap();
+        assert_eq!(tree.name.as_deref(), Some("Sign in"));
+        assert_eq!(tree.children.len(), 2);
+        assert!(tree.children[0].focused);
+        assert_eq!(tree.children[1].children[0].name.as_deref(), Some("Continue"));
+        assert_eq!(mock.calls().len(), 1);
+
+        // Served from the cached tree, no further CDP calls
+        let buttons = manager.find_by_role("button").await.unwrap();
+        assert_eq!(buttons.len(), 1);
+        assert!(buttons[0].clickable);
+        assert_eq!(manager.expand_node("3").await.unwrap()[0].node_id, "4");
+        assert!(manager.expand_node("4").await.unwrap().is_empty());
+        assert_eq!(mock.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_focused_node_with_mock_cdp() {
+        let mock = full_tree_mock();
+        let mut manager = AccessibilityManager::new(mock.client());
+
+        let focused = manager.get_focused_node().await.unwrap().unwrap();
+        assert_eq!(focused.node_id, "2");
+        assert_eq!(focused.name.as_deref(), Some("Email"));
+    }
+
+    #[test]
+    fn test_search_focused_node_prefers_deepest() {
+        let manager = AccessibilityManager::new(CdpClient::new("localhost", 9222));
+        let node = |id: &str, focused: bool, children: Vec<AccessibilityNode>| AccessibilityNode {
+            node_id: id.to_string(),
+            role: None,
+            name: None,
+            description: None,
+            value: None,
+            properties: None,
+            children,
+            bounds: None,
+            focusable: focused,
+            focused,
+            clickable: false,
+            backend_node_id: None,
+        };
+
+        let tree = node("1", true, vec![node("2", false, vec![node("3", true, vec![])]), node("4", false, vec![])]);
+        assert_eq!(manager.search_focused_node(&tree).unwrap().node_id, "3");
+        let tree = node("1", true, vec![node("2", false, vec![])]);
+        assert_eq!(manager.search_focused_node(&tree).unwrap().node_id, "1");
+        assert!(manager.search_focused_node(&node("1", false, vec![])).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expand_to_depth_loads_only_requested_levels() {
+        let mock = lazy_tree_mock();
+        let mut manager = AccessibilityManager::new(mock.client());
+
+        let root = manager.expand_to_depth(0).await.unwrap();
+        assert!(root.children.is_empty());
+        assert_eq!(mock.calls().len(), 1);
+
+        let tree = manager.expand_to_depth(1).await.unwrap();
+        assert_eq!(tree.children.len(), 2);
+        assert!(tree.children[1].children.is_empty());
+        assert_eq!(mock.calls().len(), 2);
+
+        let children = manager.expand_node("3").await.unwrap();
+        assert_eq!(children[0].node_id, "4");
+        assert_eq!(manager.expand_node("3").await.unwrap().len(), 1);
+        assert_eq!(mock.calls().len(), 3);
+        assert_eq!(mock.remaining(), 0);
+    }
+}
@@ -1,26 +1,993 @@
 use crate::accessibility::{AccessibilityManager, AccessibilityNode};
-use crate::cdp::{CdpClient, TabInfo};
+use crate::cdp::{CdpClient, CdpClientBackend, ConnectionMetrics, TabInfo, WebSocketBackend};
+use crate::cdp_types::{CaptureScreenshotRequest, EvaluateRequest, NavigateRequest, SetCookieRequest};
+use crate::dom_diff::DomSnapshot;
 use crate::error::{ChromeMcpError, Result};
-use crate::native_input::NativeInputManager;
-use crate::screenshot::{ScreenshotManager};
+use crate::humanize::{jitter_point, typing_delay, HumanRng};
+use crate::markdown::html_to_markdown;
+use crate::native_input::{NativeAxElement, NativeInputManager};
+use crate::screenshot::{ScreenshotManager, DEFAULT_SCREENSHOT_NAME_TEMPLATE};
+use crate::timezones::is_valid_timezone;
 pub use crate::screenshot::PdfOptions;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use regex::{Regex, RegexBuilder};
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::{AbortHandle, JoinSet, LocalSet};
 use tokio::time::{sleep, timeout};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// pdf.js build loaded on demand for PDF text extraction
+const PDFJS_SCRIPT_URL: &str = "https://cdnjs.cloudflare.com/ajax/libs/pdf.js/3.11.174/pdf.min.js";
+const PDFJS_WORKER_URL: &str = "https://cdnjs.cloudflare.com/ajax/libs/pdf.js/3.11.174/pdf.worker.min.js";
+
+/// tesseract.js build loaded on demand for OCR, and the language it recognizes
+const TESSERACT_SCRIPT_URL: &str = "https://cdn.jsdelivr.net/npm/tesseract.js@5.1.1/dist/tesseract.min.js";
+const OCR_LANGUAGE: &str = "eng";
+
+/// JavaScript function building a CSS selector path for an element
+const CSS_PATH_JS: &str = r#"
+(el) => {
+    const parts = [];
+    while (el && el.nodeType === 1 && el !== document.documentElement) {
+        if (el.id) {
+            parts.unshift('#' + CSS.escape(el.id));
+            break;
+        }
+        let part = el.tagName.toLowerCase();
+        const parent = el.parentElement;
+        if (parent) {
+            const siblings = Array.from(parent.children).filter(c => c.tagName === el.tagName);
+            if (siblings.length > 1) part += ':nth-of-type(' + (siblings.indexOf(el) + 1) + ')';
+        }
+        parts.unshift(part);
+        el = parent;
+    }
+    return parts.join(' > ');
+}
+"#;
+
+/// Expression describing `document.activeElement` as an `ElementRef`, `null` when no element
+/// but the body has focus; `{css_path}` is `CSS_PATH_JS`
+const FOCUSED_ELEMENT_JS: &str = r#"
+(() => {
+    const cssPath = {css_path};
+    const el = document.activeElement;
+    if (!el || el === document.body || el === document.documentElement) return null;
+    const rect = el.getBoundingClientRect();
+    return {
+        id: 'focused-' + el.tagName.toLowerCase(),
+        selector: cssPath(el),
+        accessibility_id: null,
+        bounds: [rect.x, rect.y, rect.width, rect.height],
+        text: (el.innerText || el.value || el.getAttribute('aria-label') || '').trim().slice(0, 200) || null,
+        role: el.getAttribute('role')
+    };
+})()
+"#;
+
+/// Function run on an element (`this`) returning a selector that survives markup and styling
+/// changes: the first unique one of `data-testid` (and similar test attributes), `aria-label`,
+/// `id` and `role` + `aria-label`, else a CSS path; `{css_path}` is `CSS_PATH_JS`
+const STABLE_SELECTOR_JS: &str = r#"
+function() {
+    const el = this;
+    const cssPath = {css_path};
+    const unique = (selector) => {
+        try {
+            const matches = document.querySelectorAll(selector);
+            return matches.length === 1 && matches[0] === el;
+        } catch (e) {
+            return false;
+        }
+    };
+    const attr = (name, value) => `[${name}="${value.replace(/["\\]/g, '\\$&')}"]`;
+    const tag = el.tagName.toLowerCase();
+
+    const candidates = [];
+    for (const name of ['data-testid', 'data-test-id', 'data-test', 'data-cy']) {
+        const value = el.getAttribute(name);
+        if (value) candidates.push(attr(name, value), tag + attr(name, value));
+    }
+    const label = el.getAttribute('aria-label');
+    if (label) candidates.push(attr('aria-label', label), tag + attr('aria-label', label));
+    if (el.id) candidates.push('#' + CSS.escape(el.id));
+    const role = el.getAttribute('role');
+    if (role && label) candidates.push(attr('role', role) + attr('aria-label', label));
+    return candidates.find(unique) || cssPath(el);
+}
+"#;
+
+/// Smallest number of uncaught exceptions kept by the page-side error buffer
+const MIN_JS_ERROR_BUFFER_CAPACITY: usize = 50;
+
+/// Page-side capture of uncaught exceptions and unhandled rejections into
+/// `window.__chromeMcpJsErrors`; `{capacity}` is replaced with the buffer size.
+const JS_ERROR_CAPTURE_JS: &str = r#"
+(() => {
+    window.__chromeMcpJsErrorCapacity = {capacity};
+    if (window.__chromeMcpJsErrors) return;
+    window.__chromeMcpJsErrors = [];
+    const push = (message) => {
+        const buffer = window.__chromeMcpJsErrors;
+        buffer.push(message);
+        while (buffer.length > window.__chromeMcpJsErrorCapacity) buffer.shift();
+    };
+    window.addEventListener('error', (e) => {
+        push(e.message + (e.filename ? ' (' + e.filename + ':' + e.lineno + ')' : ''));
+    });
+    window.addEventListener('unhandledrejection', (e) => {
+        const reason = e.reason && e.reason.message ? e.reason.message : String(e.reason);
+        push('Unhandled promise rejection: ' + reason);
+    });
+})()
+"#;
+
+/// JavaScript function returning the fraction (0..1) of an element inside the viewport
+const VISIBILITY_RATIO_JS: &str = r#"
+(el) => {
+    const style = getComputedStyle(el);
+    if (style.display === 'none' || style.visibility === 'hidden') return 0;
+    const rect = el.getBoundingClientRect();
+    const area = rect.width * rect.height;
+    if (area <= 0) return 0;
+    const width = Math.max(0, Math.min(rect.right, window.innerWidth) - Math.max(rect.left, 0));
+    const height = Math.max(0, Math.min(rect.bottom, window.innerHeight) - Math.max(rect.top, 0));
+    return Math.min(1, (width * height) / area);
+}
+"#;
+
+/// Page-side `<title>` observer reporting each change through the runtime binding named in
+/// `window.__chromeMcpTitleBinding`
+const TITLE_WATCH_JS: &str = r#"
+(() => {
+    window.__chromeMcpTitleBinding = '{binding}';
+    if (window.__chromeMcpTitleObserver) return;
+    let last = document.title;
+    const report = () => {
+        if (document.title === last) return;
+        const change = { old_title: last, new_title: document.title, timestamp_ms: Date.now() };
+        last = document.title;
+        const binding = window[window.__chromeMcpTitleBinding];
+        if (typeof binding === 'function') binding(JSON.stringify(change));
+    };
+    window.__chromeMcpTitleObserver = new MutationObserver(report);
+    const observe = () => window.__chromeMcpTitleObserver.observe(document.head || document.documentElement, {
+        subtree: true, childList: true, characterData: true
+    });
+    if (document.documentElement) observe();
+    else document.addEventListener('DOMContentLoaded', observe);
+})()
+"#;
+
+/// Common SQL injection payloads for `FuzzStrategy::Sql`
+const SQL_FUZZ_PAYLOADS: &[&str] = &[
+    "' OR '1'='1",
+    "' OR 1=1 --",
+    "\" OR \"\"=\"",
+    "'; DROP TABLE users; --",
+    "1' UNION SELECT NULL, NULL --",
+    "admin'--",
+    "1; WAITFOR DELAY '0:0:5' --",
+    "' AND SLEEP(5) --",
+];
+
+/// Common XSS payloads for `FuzzStrategy::Xss`
+const XSS_FUZZ_PAYLOADS: &[&str] = &[
+    "<script>alert(1)</script>",
+    "\"><script>alert(1)</script>",
+    "<img src=x onerror=alert(1)>",
+    "<svg onload=alert(1)>",
+    "javascript:alert(1)",
+    "'><iframe src=javascript:alert(1)>",
+    "<body onload=alert(1)>",
+    "{{constructor.constructor('alert(1)')()}}",
+];
+
+/// How long to let a fuzzed form submission settle before checking its outcome
+const FUZZ_SETTLE_MS: u64 = 500;
+
+/// Milliseconds between the two clicks of `Browser::double_click`
+const DOUBLE_CLICK_DELAY_MS: u64 = 100;
+
+/// How many times `navigate_menu` looks for a menu item before giving up
+const MENU_ITEM_ATTEMPTS: usize = 5;
+
+/// Fixed and sticky elements starting this close to the top of the viewport count as headers,
+/// and clicks on elements starting above it are checked for header overlap
+const FIXED_HEADER_ZONE_PX: f64 = 200.0;
+
+/// Gap left between a fixed header and an element scrolled out from under it
+const FIXED_HEADER_MARGIN_PX: f64 = 8.0;
+
+/// How long a history traversal gets to reach its entry, before waiting for the page to load
+const HISTORY_NAVIGATION_TIMEOUT_MS: u64 = 30000;
+
+/// Expression returning the session history length, the current entry's state and the URL
+const HISTORY_STATE_JS: &str = "({ length: window.history.length, state: window.history.state, url: window.location.href })";
+
+/// Bottom edge of the fixed and sticky elements starting above `{threshold}`, i.e. how much of
+/// the viewport top they cover.
+///
+/// For a `{selector}` or `{target}` box, only the header on top of the target's center counts:
+/// the fixed or sticky ancestor of the element hit there, if it overlaps the target horizontally
+/// and is not part of the target (containing the `{selector}` element or the `{target}` box).
+/// Without either, every fixed and sticky element of the page is measured.
+const FIXED_HEADER_HEIGHT_JS: &str = r#"
+(() => {
+    const threshold = {threshold};
+    const selector = {selector};
+    const element = selector ? document.querySelector(selector) : null;
+    const target = element ? element.getBoundingClientRect() : {target};
+    const pinned = el => ['fixed', 'sticky'].includes(getComputedStyle(el).position);
+    const isHeader = rect => rect.top < threshold && rect.bottom > 0 && rect.width > 0 && rect.height > 0;
+    if (!target) {
+        let bottom = 0;
+        for (const el of document.querySelectorAll('*')) {
+            const rect = el.getBoundingClientRect();
+            if (pinned(el) && isHeader(rect)) bottom = Math.max(bottom, rect.bottom);
+        }
+        return bottom;
+    }
+    let hit = document.elementFromPoint(target.x + target.width / 2, target.y + target.height / 2);
+    if (!hit || (element && (element === hit || element.contains(hit)))) return 0;
+    while (hit && !pinned(hit)) hit = hit.parentElement;
+    if (!hit || (element && hit.contains(element))) return 0;
+    const rect = hit.getBoundingClientRect();
+    if (!isHeader(rect) || rect.right <= target.x || rect.left >= target.x + target.width) return 0;
+    if (!element && rect.left <= target.x && rect.top <= target.y
+        && rect.right >= target.x + target.width && rect.bottom >= target.y + target.height) return 0;
+    return rect.bottom;
+})()
+"#;
+
+/// Scroll the page by `{delta}` pixels and return how far it actually moved
+const SCROLL_BY_JS: &str = "(() => { const before = window.scrollY; window.scrollBy(0, {delta}); return window.scrollY - before; })()";
+
+/// Events recorded by `start_console_capture`
+const CONSOLE_API_CALLED: &str = "Runtime.consoleAPICalled";
+const EXCEPTION_THROWN: &str = "Runtime.exceptionThrown";
+
+/// Console entries `start_console_capture` keeps by default
+pub const DEFAULT_CONSOLE_MAX_ENTRIES: usize = 1000;
+
+/// Requests `start_network_capture` keeps by default
+pub const DEFAULT_NETWORK_MAX_ENTRIES: usize = 1000;
+
+/// Largest response body `start_network_capture` fetches by default (1 MiB)
+pub const DEFAULT_NETWORK_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Requests `WaitCondition::NetworkIdle` tolerates in flight by default, like puppeteer's networkidle2
+pub const DEFAULT_NETWORK_IDLE_MAX_INFLIGHT: usize = 2;
+
+/// Name of the isolated worlds `switch_to_frame` creates in iframes
+const FRAME_WORLD_NAME: &str = "chrome-mcp";
+
+/// Viewport box of the element matching `{selector}`, scrolled into view first; null if missing
+const FRAME_ELEMENT_RECT_JS: &str = r#"
+(() => {
+    const el = document.querySelector({selector});
+    if (!el) return null;
+    el.scrollIntoView({ block: 'center', inline: 'center' });
+    const rect = el.getBoundingClientRect();
+    return { timestamp_ms: 0, x: rect.x, y: rect.y, width: rect.width, height: rect.height };
+})()
+"#;
+
+/// Viewport position of the content of the iframe matching `{selector}`, i.e. where the
+/// iframe's own viewport starts; null if missing
+const FRAME_CONTENT_OFFSET_JS: &str = r#"
+(() => {
+    const frame = document.querySelector({selector});
+    if (!frame) return null;
+    const rect = frame.getBoundingClientRect();
+    const style = getComputedStyle(frame);
+    return {
+        x: rect.x + frame.clientLeft + parseFloat(style.paddingLeft),
+        y: rect.y + frame.clientTop + parseFloat(style.paddingTop)
+    };
+})()
+"#;
+
+/// Mouse moves `drag_and_drop` makes on the way from the source to the target
+const DRAG_STEPS: u32 = 10;
+
+/// HTML5 drag and drop events for a drag from the `{source}` point to the `{target}` point,
+/// which pages see no matter how the mouse moved. Only fired when the source is
+/// `draggable`; returns whether they were.
+const DRAG_EVENTS_JS: &str = r#"
+(() => {
+    const from = {source};
+    const to = {target};
+    const start = document.elementFromPoint(from.x, from.y);
+    const source = start && start.closest('[draggable="true"]');
+    const target = document.elementFromPoint(to.x, to.y);
+    if (!source || !target) return false;
+    const dataTransfer = new DataTransfer();
+    const fire = (element, type, point) => element.dispatchEvent(new DragEvent(type, {
+        bubbles: true, cancelable: true, composed: true, dataTransfer, clientX: point.x, clientY: point.y
+    }));
+    fire(source, 'dragstart', from);
+    fire(source, 'drag', to);
+    fire(target, 'dragenter', to);
+    // Drop targets accept the drop by cancelling dragover
+    if (!fire(target, 'dragover', to)) fire(target, 'drop', to);
+    fire(source, 'dragend', to);
+    return true;
+})()
+"#;
+
+/// Page-side assignment of `{value}` to the field matching `{selector}` (through the native
+/// value setter, so framework-controlled inputs notice), followed by a form submission.
+/// Returns null if the field is missing and whether a form was submitted otherwise.
+const FUZZ_SUBMIT_JS: &str = r#"
+(() => {
+    if (window.__chromeMcpJsErrors) window.__chromeMcpJsErrors.length = 0;
+    const el = document.querySelector({selector});
+    if (!el) return null;
+    const proto = el instanceof HTMLTextAreaElement ? HTMLTextAreaElement.prototype
+        : el instanceof HTMLInputElement ? HTMLInputElement.prototype : null;
+    const descriptor = proto && Object.getOwnPropertyDescriptor(proto, 'value');
+    if (descriptor && descriptor.set) descriptor.set.call(el, {value});
+    else el.value = {value};
+    el.dispatchEvent(new Event('input', { bubbles: true }));
+    el.dispatchEvent(new Event('change', { bubbles: true }));
+    if (!el.form) return false;
+    if (el.form.requestSubmit) el.form.requestSubmit();
+    else el.form.submit();
+    return true;
+})()
+"#;
+
+/// Head scripts that block parsing and stylesheets that apply to every medium
+const RENDER_BLOCKING_SELECTOR: &str =
+    r#"head script[src]:not([async]):not([defer]):not([type="module"]), link[rel="stylesheet"]:not([media])"#;
+
+/// Page-side collection of render-blocking resources: the elements matching `{selector}`
+/// plus resources whose timing entry reports `renderBlockingStatus: "blocking"`
+const RENDER_BLOCKING_JS: &str = r#"
+(() => {
+    const timings = new Map(performance.getEntriesByType('resource').map(entry => [entry.name, entry]));
+    const found = new Map();
+    const add = (url, type) => {
+        if (!url || found.has(url)) return;
+        const entry = timings.get(url);
+        found.set(url, {
+            url,
+            type,
+            load_time_ms: entry ? entry.duration : null,
+            critical_path: !!entry && entry.renderBlockingStatus === 'blocking'
+        });
+    };
+    document.querySelectorAll({selector}).forEach(el => {
+        if (el.tagName === 'SCRIPT') add(el.src, 'script');
+        else add(el.href, 'stylesheet');
+    });
+    timings.forEach((entry, url) => {
+        if (entry.renderBlockingStatus === 'blocking') add(url, entry.initiatorType === 'script' ? 'script' : 'stylesheet');
+    });
+    return Array.from(found.values());
+})()
+"#;
+
+/// Page-side collection for `analyze_js_bundles`: versions exposed by well-known library
+/// globals, the `src` of every external script (repeats included) with its size from the
+/// resource timing entry, and the byte size of inline scripts
+const JS_BUNDLES_JS: &str = r#"
+(() => {
+    const globals = [];
+    const add = (name, global, version) => {
+        if (version !== undefined && version !== null) globals.push({ name, global, version: String(version) });
+    };
+    add('jquery', 'jQuery', window.jQuery?.fn?.jquery);
+    if (window.$ !== window.jQuery) add('jquery', '$', window.$?.fn?.jquery);
+    add('react', 'React', window.React?.version);
+    add('react-dom', 'ReactDOM', window.ReactDOM?.version);
+    add('vue', 'Vue', window.Vue?.version);
+    add('angular', 'angular', window.angular?.version?.full);
+    add('angular', 'ng-version', document.querySelector('[ng-version]')?.getAttribute('ng-version'));
+    add('backbone', 'Backbone', window.Backbone?.VERSION);
+    if (window.__NEXT_DATA__) add('next', '__NEXT_DATA__', window.next?.version ?? '');
+
+    const timings = new Map(performance.getEntriesByType('resource').map(entry => [entry.name, entry]));
+    const scripts = [];
+    let inlineBytes = 0;
+    for (const script of document.scripts) {
+        if (script.src) {
+            const entry = timings.get(script.src);
+            scripts.push({ url: script.src, size: entry ? (entry.encodedBodySize || entry.transferSize || 0) : 0 });
+        } else {
+            inlineBytes += new TextEncoder().encode(script.text).length;
+        }
+    }
+    return { globals, scripts, inlineBytes };
+})()
+"#;
+
+/// Page-side facts for `run_seo_audit`
+const SEO_FACTS_JS: &str = r#"
+(() => {
+    const meta = (name) => Array.from(document.querySelectorAll(`meta[name="${name}" i]`)).map(m => m.getAttribute('content') || '');
+    const images = Array.from(document.images);
+    return {
+        title: document.querySelector('title') ? document.title : null,
+        descriptions: meta('description'),
+        robots: meta('robots'),
+        viewport: meta('viewport'),
+        canonicals: Array.from(document.querySelectorAll('link[rel~="canonical" i]')).map(l => l.getAttribute('href') || ''),
+        headings: Array.from(document.querySelectorAll('h1, h2, h3, h4, h5, h6')).map(h => Number(h.tagName[1])),
+        images: images.length,
+        images_without_alt: images.filter(img => !img.hasAttribute('alt')).length
+    };
+})()
+"#;
+
+/// Page-side label facts of the visible `<input>`s without an accessible name in the form
+/// matching `{form_selector}`, returning `null` when there is no such form; `{css_path}` is
+/// `CSS_PATH_JS`. `label_issue` turns each into a `LabelIssue`.
+const FORM_LABELS_JS: &str = r#"
+(() => {
+    const cssPath = {css_path};
+    const form = document.querySelector({form_selector});
+    if (!form) return null;
+    const text = (el) => (el ? el.textContent : '').trim();
+    const unlabeled = [];
+    form.querySelectorAll('input:not([type="hidden"])').forEach(input => {
+        const labels = Array.from(input.labels || []).map(text).join(' ').trim();
+        const ariaLabel = input.getAttribute('aria-label');
+        const labelledBy = input.getAttribute('aria-labelledby');
+        const ids = labelledBy ? labelledBy.split(/\s+/).filter(Boolean) : [];
+        const labelledByText = ids.map(id => text(document.getElementById(id))).join(' ').trim();
+        const buttonText = ['submit', 'reset', 'button'].includes(input.type) ? input.value
+            : input.type === 'image' ? input.alt : '';
+        const title = input.getAttribute('title');
+        if (labels || (ariaLabel && ariaLabel.trim()) || labelledByText || (title && title.trim()) || (buttonText && buttonText.trim())) return;
+
+        const previous = input.previousElementSibling;
+        const stray = Array.from(form.querySelectorAll('label[for]')).find(label =>
+            label.control !== input && (label.htmlFor === input.name || (input.id && label.htmlFor === input.id)))
+            || (previous && previous.tagName === 'LABEL' && previous.htmlFor && !previous.control ? previous : null);
+        unlabeled.push({
+            input_selector: cssPath(input),
+            id: input.id || null,
+            aria_label: ariaLabel,
+            aria_labelledby: labelledBy,
+            missing_labelledby_ids: ids.filter(id => !document.getElementById(id)),
+            stray_label_for: stray ? stray.htmlFor : null
+        });
+    });
+    return unlabeled;
+})()
+"#;
+
+/// Binding that `STORAGE_LISTEN_JS` reports each storage change through
+const STORAGE_BINDING: &str = "__chromeMcpStorage";
+
+/// Page-side `storage` event listener reporting each change through the `{binding}` runtime
+/// binding; resolves once it has listened for `{timeout}` ms
+const STORAGE_LISTEN_JS: &str = r#"
+new Promise(resolve => {
+    const listener = (event) => {
+        window['{binding}'](JSON.stringify({
+            key: event.key,
+            old_value: event.oldValue,
+            new_value: event.newValue,
+            url: event.url,
+            storage_type: event.storageArea === sessionStorage ? 'session' : 'local',
+            timestamp_ms: Date.now()
+        }));
+    };
+    window.addEventListener('storage', listener);
+    setTimeout(() => {
+        window.removeEventListener('storage', listener);
+        resolve();
+    }, {timeout});
+})
+"#;
+
+/// Page-side `EventSource` on `{url}` collecting `{types}` events until `{max}` have arrived or
+/// `{timeout}` ms have passed (or the connection is given up); the source is closed before
+/// resolving
+const SSE_CAPTURE_JS: &str = r#"
+new Promise(resolve => {
+    const events = [];
+    const started = Date.now();
+    const source = new EventSource({url});
+    let timer;
+    const finish = () => {
+        clearTimeout(timer);
+        source.close();
+        resolve(events);
+    };
+    const listener = (event) => {
+        if (events.length >= {max}) return;
+        const captured = {
+            event_type: event.type,
+            data: event.data,
+            id: event.lastEventId || null,
+            received_at_ms: Date.now() - started
+        };
+        events.push(captured);
+        if (events.length >= {max}) finish();
+    };
+    for (const type of {types}) source.addEventListener(type, listener);
+    source.addEventListener('error', () => {
+        if (source.readyState === EventSource.CLOSED) finish();
+    });
+    timer = setTimeout(finish, {timeout});
+})
+"#;
+
+/// Name of the runtime binding reporting when an observed sentinel enters the viewport
+const INTERSECTION_BINDING: &str = "__chromeMcpIntersection";
+
+/// Page-side `IntersectionObserver` on the `{selector}` sentinel, scrolling down step by step
+/// (or the sentinel into view once the page cannot scroll further) until it intersects the
+/// viewport or `{timeout}` ms pass. Reports through the `{binding}` runtime binding and
+/// resolves null when there is no sentinel.
+const OBSERVE_INTERSECTION_JS: &str = r#"
+new Promise(resolve => {
+    const sentinel = document.querySelector({selector});
+    if (!sentinel) {
+        resolve(null);
+        return;
+    }
+    const started = performance.now();
+    let timer, scroller;
+    const finish = (result) => {
+        clearTimeout(timer);
+        clearInterval(scroller);
+        observer.disconnect();
+        resolve(result);
+    };
+    const observer = new IntersectionObserver((entries) => {
+        const entry = entries.find(e => e.isIntersecting);
+        if (!entry) return;
+        const result = {
+            intersected: true,
+            intersection_ratio: entry.intersectionRatio,
+            time_ms: Math.round(performance.now() - started)
+        };
+        if (typeof window['{binding}'] === 'function') window['{binding}'](JSON.stringify(result));
+        finish(result);
+    }, { threshold: [0, 0.25, 0.5, 0.75, 1] });
+    observer.observe(sentinel);
+    scroller = setInterval(() => {
+        const before = window.scrollY;
+        window.scrollBy(0, window.innerHeight * 0.8);
+        if (window.scrollY === before) sentinel.scrollIntoView({ block: 'end' });
+    }, 250);
+    timer = setTimeout(() => finish({ intersected: false, intersection_ratio: 0, time_ms: {timeout} }), {timeout});
+})
+"#;
+
+/// Page-side tracking of instantiated WebAssembly modules in `window.__chromeMcpWasm`, since
+/// the page cannot enumerate them: wraps `WebAssembly.instantiate`, `instantiateStreaming` and
+/// the `Instance` constructor. Instances are held weakly and dropped once collected.
+const WASM_TRACKER_JS: &str = r#"
+(() => {
+    if (window.__chromeMcpWasm || typeof WebAssembly !== 'object') return;
+    const tracked = [];
+    window.__chromeMcpWasm = tracked;
+    const registry = new FinalizationRegistry(entry => {
+        const index = tracked.indexOf(entry);
+        if (index >= 0) tracked.splice(index, 1);
+    });
+    const track = (module, instance, name) => {
+        const entry = { name: name || null, exports: WebAssembly.Module.exports(module), instance: new WeakRef(instance) };
+        tracked.push(entry);
+        registry.register(instance, entry);
+    };
+    const sourceName = (source) => (source && typeof source.url === 'string' && source.url) || null;
+
+    const { instantiate, instantiateStreaming, Instance } = WebAssembly;
+    WebAssembly.instantiate = function (source, ...rest) {
+        return instantiate.call(this, source, ...rest).then(result => {
+            if (result instanceof Instance) track(source, result, null);
+            else track(result.module, result.instance, null);
+            return result;
+        });
+    };
+    if (instantiateStreaming) {
+        WebAssembly.instantiateStreaming = function (source, ...rest) {
+            return Promise.resolve(source).then(response =>
+                instantiateStreaming.call(this, response, ...rest).then(result => {
+                    track(result.module, result.instance, sourceName(response));
+                    return result;
+                }));
+        };
+    }
+    WebAssembly.Instance = new Proxy(Instance, {
+        construct(target, args, newTarget) {
+            const instance = Reflect.construct(target, args, newTarget);
+            track(args[0], instance, null);
+            return instance;
+        }
+    });
+})()
+"#;
+
+/// Exports of the live tracked WebAssembly modules
+const WASM_MODULES_JS: &str = r#"
+(window.__chromeMcpWasm || [])
+    .filter(entry => entry.instance.deref())
+    .map(entry => ({ name: entry.name, exports: entry.exports.map(e => ({ name: e.name, kind: e.kind })) }))
+"#;
+
+/// Call the `{export}` function of the first live tracked module (named `{module}` if not
+/// null) that has it, with the `{args}` array; BigInt results are returned as strings
+const WASM_CALL_JS: &str = r#"
+(() => {
+    const entry = (window.__chromeMcpWasm || []).find(entry => {
+        const instance = entry.instance.deref();
+        return instance && ({module} === null || entry.name === {module})
+            && typeof instance.exports[{export}] === 'function';
+    });
+    if (!entry) return { found: false };
+    const result = entry.instance.deref().exports[{export}](...{args});
+    return { found: true, result: typeof result === 'bigint' ? result.toString() : result };
+})()
+"#;
+
+/// Name of the runtime binding reporting `BroadcastChannel` messages as they arrive
+const BROADCAST_BINDING: &str = "__chromeMcpBroadcast";
+
+/// Page-side `BroadcastChannel` listener on `{channel}` collecting messages until `{max}` have
+/// arrived or `{timeout}` ms have passed, also reporting each one through the `{binding}`
+/// runtime binding; the channel is closed before resolving
+const BROADCAST_LISTEN_JS: &str = r#"
+new Promise(resolve => {
+    const messages = [];
+    const channel = new BroadcastChannel({channel});
+    let timer;
+    const finish = () => {
+        clearTimeout(timer);
+        channel.close();
+        resolve(messages);
+    };
+    channel.onmessage = (event) => {
+        if (messages.length >= {max}) return;
+        messages.push(event.data);
+        if (typeof window['{binding}'] === 'function') window['{binding}'](JSON.stringify(event.data));
+        if (messages.length >= {max}) finish();
+    };
+    timer = setTimeout(finish, {timeout});
+})
+"#;
+
+/// Query the `{descriptors}` permissions in parallel; resolves to `{ name: state }`, without
+/// the permissions this Chrome does not support
+const QUERY_PERMISSIONS_JS: &str = r#"
+(async () => {
+    const states = {};
+    await Promise.all({descriptors}.map(async (descriptor) => {
+        try {
+            states[descriptor.name] = (await navigator.permissions.query(descriptor)).state;
+        } catch (e) {}
+    }));
+    return states;
+})()
+"#;
+
+/// Read every item on the clipboard; resolves to `[{ type, data_b64 }]`, one entry per MIME
+/// type of each item
+const CLIPBOARD_ITEMS_JS: &str = r#"
+(async () => {
+    const toBase64 = (blob) => new Promise((resolve, reject) => {
+        const reader = new FileReader();
+        reader.onload = () => resolve(reader.result.slice(reader.result.indexOf(',') + 1));
+        reader.onerror = () => reject(reader.error);
+        reader.readAsDataURL(blob);
+    });
+    const entries = [];
+    for (const item of await navigator.clipboard.read()) {
+        for (const type of item.types) {
+            entries.push({ type, data_b64: await toBase64(await item.getType(type)) });
+        }
+    }
+    return entries;
+})()
+"#;
+
+/// Copy `{text}` through a temporary textarea and `document.execCommand('copy')`, for pages
+/// where `navigator.clipboard.writeText` is refused. Restores the selection and focus;
+/// returns whether the copy went through.
+const CLIPBOARD_COPY_JS: &str = r#"
+(() => {
+    const active = document.activeElement;
+    const selection = document.getSelection();
+    const ranges = [];
+    for (let i = 0; i < selection.rangeCount; i++) ranges.push(selection.getRangeAt(i));
+
+    const area = document.createElement('textarea');
+    area.value = {text};
+    area.setAttribute('readonly', '');
+    area.style.position = 'fixed';
+    area.style.top = '0';
+    area.style.opacity = '0';
+    document.body.appendChild(area);
+    area.select();
+    let copied = false;
+    try {
+        copied = document.execCommand('copy');
+    } catch (e) {}
+    area.remove();
+
+    selection.removeAllRanges();
+    ranges.forEach((range) => selection.addRange(range));
+    if (active && active.focus) active.focus();
+    return copied;
+})()
+"#;
+
+/// Page-side patch that removes WebRTC peer connection constructors
+const DISABLE_WEBRTC_JS: &str = r#"
+(() => {
+    for (const name of ['RTCPeerConnection', 'webkitRTCPeerConnection', 'mozRTCPeerConnection', 'RTCDataChannel']) {
+        try {
+            Object.defineProperty(window, name, { value: undefined, writable: false, configurable: false });
+        } catch (e) {}
+    }
+})();
+"#;
+
+/// Outer HTML of the element `{element}` evaluates to, with the base URL to resolve its links
+const CONTENT_HTML_JS: &str = r#"
+(() => {
+    const el = {element};
+    if (!el) return null;
+    return { html: el.outerHTML, baseUrl: document.baseURI };
+})()
+"#;
+
+/// Page origin and the absolute http(s) URLs of the links inside the elements matching
+/// `{selectors}` (the whole document when empty), without fragments or duplicates
+const LINKS_JS: &str = r#"
+(() => {
+    const selectors = {selectors};
+    const roots = selectors.length ? selectors.flatMap(s => Array.from(document.querySelectorAll(s))) : [document];
+    const links = [];
+    for (const root of roots) {
+        const anchors = root.matches && root.matches('a[href]') ? [root] : Array.from(root.querySelectorAll('a[href]'));
+        for (const a of anchors) {
+            let url;
+            try { url = new URL(a.href, document.baseURI); } catch (e) { continue; }
+            if (url.protocol !== 'http:' && url.protocol !== 'https:') continue;
+            url.hash = '';
+            if (!links.includes(url.href)) links.push(url.href);
+        }
+    }
+    return { origin: location.origin, links };
+})()
+"#;
+
+/// Request fullscreen for `{element}`, resolving to `null` when there is no such element,
+/// otherwise to the reason fullscreen was refused (empty when it was granted)
+const ENTER_FULLSCREEN_JS: &str = r#"
+(async () => {
+    const el = {element};
+    if (!el) return null;
+    if (!document.fullscreenEnabled) return 'Fullscreen is disabled in this document (blocked by policy or iframe permissions)';
+    try {
+        await el.requestFullscreen();
+        return '';
+    } catch (e) {
+        return `${e.name}: ${e.message}`;
+    }
+})()
+"#;
+
+/// Custom properties declared in the page's stylesheets (and inline on the element), with
+/// their computed value on `{element}` and the URL of the stylesheet declaring them first
+/// (`null` for `<style>` blocks and inline styles). `null` when there is no such element.
+const CSS_VARIABLES_JS: &str = r#"
+(() => {
+    const el = {element};
+    if (!el) return null;
+    const sources = new Map();
+    const declare = (style, source) => {
+        for (let i = 0; i < style.length; i++) {
+            const name = style[i];
+            if (name.startsWith('--') && !sources.has(name)) sources.set(name, source);
+        }
+    };
+    const walk = (rules, source) => {
+        for (const rule of rules) {
+            if (rule.style) declare(rule.style, source);
+            if (rule.cssRules) walk(rule.cssRules, source);
+            if (rule.styleSheet) visit(rule.styleSheet);
+        }
+    };
+    const visit = (sheet) => {
+        let rules;
+        // Cross-origin stylesheets without CORS cannot be read
+        try { rules = sheet.cssRules; } catch (e) { return; }
+        walk(rules, sheet.href || null);
+    };
+    for (const sheet of document.styleSheets) visit(sheet);
+    for (const sheet of document.adoptedStyleSheets || []) visit(sheet);
+    if (el.style) declare(el.style, null);
+    const style = getComputedStyle(el);
+    return Array.from(sources, ([name, source]) => ({ name, value: style.getPropertyValue(name).trim(), source }))
+        .sort((a, b) => a.name.localeCompare(b.name));
+})()
+"#;
+
+/// Fetch each of `{urls}` from the page, giving up after `{timeout}` ms. A readable response
+/// gives its status; otherwise a `no-cors` retry tells an answering server (opaque response)
+/// from a closed or unreachable port (network error or timeout).
+const PROBE_PORTS_JS: &str = r#"
+(async () => {
+    const probe = async (url) => {
+        try {
+            const response = await fetch(url, { signal: AbortSignal.timeout({timeout}), cache: 'no-store' });
+            return { open: true, status: response.status };
+        } catch (e) {
+            if (e.name === 'TimeoutError' || e.name === 'AbortError') return { open: false, status: null };
+        }
+        try {
+            await fetch(url, { mode: 'no-cors', signal: AbortSignal.timeout({timeout}), cache: 'no-store' });
+            return { open: true, status: null };
+        } catch (e) {
+            return { open: false, status: null };
+        }
+    };
+    const results = await Promise.allSettled({urls}.map(probe));
+    return results.map(r => r.status === 'fulfilled' ? r.value : { open: false, status: null });
+})()
+"#;
+
+/// Title, URL and favicon of the page; without an icon link, http(s) pages fall back to
+/// the origin's `/favicon.ico`
+const PAGE_INFO_JS: &str = r#"
+(() => {
+    const link = document.querySelector('link[rel~="icon" i]');
+    let favicon = link && link.href ? link.href : null;
+    if (!favicon && /^https?:$/.test(location.protocol)) favicon = location.origin + '/favicon.ico';
+    return { title: document.title, url: location.href, favicon };
+})()
+"#;
+
+/// `fetch` `{request}` (`{ url, init }`) from the page and read back the response. The
+/// response counts as served from the HTTP cache when its resource timing entry shows no
+/// bytes transferred for a non-empty body (cross-origin entries without
+/// `Timing-Allow-Origin` report zero sizes and never count).
+const FETCH_JS: &str = r#"
+(async () => {
+    const { url, init } = {request};
+    const started = performance.now();
+    const response = await fetch(url, init);
+    const body = await response.text();
+    const headers = {};
+    response.headers.forEach((value, name) => { headers[name] = value; });
+    const entry = performance.getEntriesByType('resource')
+        .filter(e => e.initiatorType === 'fetch' && e.startTime >= started - 1
+            && (e.name === response.url || e.name === new URL(url, location.href).href))
+        .pop();
+    return {
+        status: response.status,
+        url: response.url,
+        redirected: response.redirected,
+        headers,
+        body,
+        fromCache: !!entry && entry.transferSize === 0 && entry.decodedBodySize > 0,
+    };
+})()
+"#;
+
+/// JavaScript function converting a `<table>` element into `TableData`.
+/// Spanned cells are repeated in every column/row they cover; the last leading
+/// row made of `<th>` cells (or inside `<thead>`) provides the headers.
+const TABLE_EXTRACTOR_JS: &str = r#"
+(table) => {
+    const clean = (el) => (el.textContent || '').replace(/\s+/g, ' ').trim();
+    const grid = [];
+    let headerCount = 0;
+    let inHeader = true;
+    Array.from(table.rows).forEach((row, r) => {
+        grid[r] = grid[r] || [];
+        const cells = Array.from(row.cells);
+        const isHeader = (row.parentElement && row.parentElement.tagName === 'THEAD')
+            || (cells.length > 0 && cells.every(cell => cell.tagName === 'TH'));
+        if (inHeader && isHeader) {
+            headerCount = r + 1;
+        } else {
+            inHeader = false;
+        }
+        let c = 0;
+        for (const cell of cells) {
+            while (grid[r][c] !== undefined) c++;
+            const text = clean(cell);
+            const colspan = Math.max(1, cell.colSpan || 1);
+            const rowspan = Math.max(1, cell.rowSpan || 1);
+            for (let dr = 0; dr < rowspan; dr++) {
+                grid[r + dr] = grid[r + dr] || [];
+                for (let dc = 0; dc < colspan; dc++) {
+                    grid[r + dr][c + dc] = text;
+                }
+            }
+            c += colspan;
+        }
+    });
+    const rows = grid.slice(0, table.rows.length).map(row => Array.from(row, v => v === undefined ? '' : v));
+    return {
+        headers: headerCount > 0 ? rows[headerCount - 1] : [],
+        rows: rows.slice(headerCount),
+        caption: table.caption ? clean(table.caption) : null
+    };
+}
+"#;
 
 /// High-level browser automation interface
+///
+/// Clones share the CDP connection settings but keep their own state, and can be connected to
+/// another tab without affecting the original.
 #[allow(dead_code)]
-pub struct Browser {
-    cdp: CdpClient,
-    accessibility: AccessibilityManager,
-    screenshot: ScreenshotManager,
-    native_input: NativeInputManager,
+#[derive(Clone)]
+pub struct Browser<B = WebSocketBackend> {
+    cdp: CdpClient<B>,
+    accessibility: AccessibilityManager<B>,
+    screenshot: ScreenshotManager<B>,
+    /// `None` when no native input tool was found at startup
+    native_input: Option<NativeInputManager>,
+    /// X display native input goes to instead of `DISPLAY`, see `set_native_input_display`
+    native_input_display: Option<String>,
     current_tab_id: Option<String>,
-    network_events: Vec<NetworkEvent>,
+    /// Also filled by the task `start_network_capture` spawns
+    network_events: Arc<Mutex<Vec<NetworkEvent>>>,
+    /// Task of `start_network_capture`
+    network_capture: Option<AbortHandle>,
     cookies: HashMap<String, Vec<Cookie>>,
+    /// Shared with the task `sync_fetch_interception` starts
+    mocks: Arc<Mutex<Vec<MockResponse>>>,
+    /// Task answering the requests paused by `Fetch` interception
+    fetch_handler: Option<AbortHandle>,
+    /// Shared with the task `sync_fetch_interception` starts
+    http_auth: Arc<Mutex<HashMap<(String, String), HttpAuthCredentials>>>,
+    last_cpu_profile: Option<CpuProfile>,
+    popup_opener_tab_id: Option<String>,
+    /// Iframe that `evaluate`, `click` and `type_text` act in, see `switch_to_frame`
+    frame: Option<FrameContext>,
+    js_error_budget: Option<usize>,
+    js_error_capture_script: Option<String>,
+    /// Tag of the running `watch_title`, to restart it on tab switches
+    title_watch_tag: Option<String>,
+    /// Filled by the task `watch_title` starts
+    title_history: Arc<Mutex<Vec<TitleChange>>>,
+    /// Task of `watch_title`
+    title_watch: Option<AbortHandle>,
+    /// Filled by the task `start_console_capture` spawns
+    console_entries: Arc<Mutex<Vec<ConsoleEntry>>>,
+    /// Task of `start_console_capture`
+    console_capture: Option<AbortHandle>,
+    checkpoint_file: Option<PathBuf>,
+    /// CDP domains enabled on demand, on top of the ones `CdpClient` enables on connect
+    enabled_domains: Vec<String>,
+    /// Scripts added with `Page.addScriptToEvaluateOnNewDocument`, as (identifier, source)
+    injected_scripts: Vec<(String, String)>,
+    /// Set with `set_download_directory`
+    download_dir: Option<PathBuf>,
+    /// Downloads by GUID, filled by the task `set_download_directory` starts
+    downloads: Arc<Mutex<HashMap<String, TrackedDownload>>>,
+    /// Task of `set_download_directory` following download events
+    download_watch: Option<AbortHandle>,
+    /// Maximum offset of every `click` from the element center, when enabled
+    click_jitter: Option<u32>,
+    human_rng: HumanRng,
+    /// Flattened sessions of the extension DevTools targets attached so far, by target ID
+    devtools_sessions: HashMap<String, String>,
+    /// Target `evaluate_in_devtools_target` runs in unless told otherwise
+    devtools_target: Option<String>,
 }
 
 /// Network event information
@@ -33,6 +1000,19 @@ pub struct NetworkEvent {
     pub timestamp: f64,
     pub status_code: Option<u32>,
     pub response_headers: Option<HashMap<String, String>>,
+    /// Response body as text, or base64 for binary responses
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Response body decoded according to its content type: parsed JSON,
+    /// text as-is, or a base64 string for binary content
+    #[serde(default)]
+    pub body_decoded: Value,
+    /// Whether the response finished loading (`Network.loadingFinished`)
+    #[serde(default)]
+    pub finished: bool,
+    /// Why the request failed (`Network.loadingFailed`), e.g. `net::ERR_CONNECTION_REFUSED`
+    #[serde(default)]
+    pub error_text: Option<String>,
 }
 
 /// Cookie information
@@ -48,721 +1028,9417 @@ pub struct Cookie {
     pub expires: Option<f64>,
 }
 
-/// Element reference for consistent targeting
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ElementRef {
-    pub id: String,
-    pub selector: Option<String>,
-    pub accessibility_id: Option<String>,
-    pub bounds: Option<(f64, f64, f64, f64)>, // x, y, width, height
-    pub text: Option<String>,
-    pub role: Option<String>,
+/// Saved browser session (cookies, web storage and current URL)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionState {
+    pub url: Option<String>,
+    pub cookies: Vec<Cookie>,
+    /// localStorage entries keyed by origin
+    pub local_storage: HashMap<String, HashMap<String, String>>,
+    /// sessionStorage entries keyed by origin
+    pub session_storage: HashMap<String, HashMap<String, String>>,
 }
 
-/// Wait conditions
-#[derive(Debug, Clone)]
-pub enum WaitCondition {
-    /// Wait for element to be present
-    ElementPresent(String),
-    /// Wait for element to be visible
-    ElementVisible(String),
-    /// Wait for element to be clickable
-    ElementClickable(String),
-    /// Wait for text to be present
-    TextPresent(String),
-    /// Wait for URL to match pattern
-    UrlMatches(String),
-    /// Wait for URL to contain text
-    UrlContains(String),
-    /// Wait for page load to complete
-    PageLoad,
-    /// Wait for network idle (no requests for specified duration)
-    NetworkIdle(u64), // milliseconds
+/// Browser state needed to resume a session after Chrome restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionCheckpoint {
+    pub tab_id: String,
+    pub enabled_domains: Vec<String>,
+    pub injected_scripts: Vec<String>,
+    pub current_url: String,
+    pub cookie_state: Vec<Cookie>,
 }
 
-impl Browser {
-    /// Create a new Browser instance
-    pub fn new(chrome_host: &str, chrome_port: u16) -> Result<Self> {
-        let cdp = CdpClient::new(chrome_host, chrome_port);
-        let accessibility = AccessibilityManager::new(cdp.clone());
-        let screenshot = ScreenshotManager::new(cdp.clone());
-        let native_input = NativeInputManager::new()?;
+impl SessionCheckpoint {
+    /// Load a checkpoint file, returning `None` if it does not exist
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
 
-        Ok(Self {
-            cdp,
-            accessibility,
-            screenshot,
-            native_input,
-            current_tab_id: None,
-            network_events: Vec::new(),
-            cookies: HashMap::new(),
-        })
+    /// Write the checkpoint, replacing any previous one atomically
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
     }
+}
 
-    /// Connect to Chrome and select a tab
-    pub async fn connect(&mut self, tab_id: Option<&str>) -> Result<String> {
-        info!("Connecting to Chrome browser");
+/// Type of a loaded page resource as reported by `Page.getResourceTree`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ResourceType {
+    Document,
+    Stylesheet,
+    Image,
+    Media,
+    Font,
+    Script,
+    #[serde(rename = "XHR")]
+    Xhr,
+    Fetch,
+    Other,
+}
 
-        let tab = if let Some(id) = tab_id {
-            // Connect to specific tab
-            self.cdp.connect_to_tab(id).await?;
-            id.to_string()
-        } else {
-            // Find an existing tab or create a new one
-            let tabs = self.cdp.list_tabs().await?;
-            let tab_id = if let Some(tab) = tabs.first() {
-                tab.id.clone()
-            } else {
-                // Create new tab
-                let new_tab = self.cdp.create_tab(None).await?;
-                new_tab.id
-            };
+/// Resource loaded by the page or one of its frames
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageResource {
+    pub url: String,
+    #[serde(rename = "type")]
+    pub type_: ResourceType,
+    pub mime_type: String,
+    pub content_size: Option<u64>,
+    pub frame_id: String,
+}
 
-            self.cdp.connect_to_tab(&tab_id).await?;
-            tab_id
-        };
+/// Text search match on the page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextMatch {
+    pub frame_id: String,
+    pub matched_text: String,
+    /// Match with up to 20 characters of context on each side
+    pub surrounding_text: String,
+    /// 1-based line in the frame document source (for matches inside child frames)
+    pub line_number: Option<u32>,
+    /// Selector of the element containing the text (for matches in the main document)
+    pub node_selector: Option<String>,
+}
 
-        self.current_tab_id = Some(tab.clone());
-        info!("Connected to tab: {}", tab);
-        Ok(tab)
-    }
+/// Structured contents of an HTML table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableData {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub caption: Option<String>,
+}
 
-    /// List all available tabs
-    pub async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
-        self.cdp.list_tabs().await
-    }
+/// Mocked API response served through `Fetch.fulfillRequest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockResponse {
+    /// URL pattern, `*` matches any sequence and `?` a single character
+    pub url_pattern: String,
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
 
-    /// Create a new tab
-    pub async fn create_tab(&mut self, url: Option<&str>) -> Result<String> {
-        let tab = self.cdp.create_tab(url).await?;
-        info!("Created new tab: {} ({})", tab.title, tab.id);
-        Ok(tab.id)
-    }
+/// Entry of a mock fixture file (`{ "<url pattern>": { status, headers, body } }`)
+#[derive(Debug, Clone, Deserialize)]
+struct MockFixtureEntry {
+    #[serde(default = "default_mock_status")]
+    status: u16,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: Value,
+}
 
-    /// Switch to a different tab
-    pub async fn switch_to_tab(&mut self, tab_id: &str) -> Result<()> {
-        self.cdp.connect_to_tab(tab_id).await?;
-        self.current_tab_id = Some(tab_id.to_string());
-        info!("Switched to tab: {}", tab_id);
-        Ok(())
+fn default_mock_status() -> u16 {
+    200
+}
+
+/// Credentials answered to HTTP authentication challenges
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpAuthCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Current state of a (form) element
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ElementState {
+    pub checked: bool,
+    pub disabled: bool,
+    pub read_only: bool,
+    pub required: bool,
+    /// Current value, for elements that have one
+    pub value: Option<String>,
+    pub valid: bool,
+    pub validation_message: String,
+    pub focused: bool,
+    pub indeterminate: bool,
+    /// Fraction of the element inside the viewport (0.0 to 1.0)
+    #[serde(default)]
+    pub visibility_ratio: f64,
+}
+
+/// Iframe selected with `switch_to_frame`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameContext {
+    /// Selector of the iframe element in the top-level document
+    pub selector: String,
+    pub frame_id: String,
+    /// Isolated world created in the frame for chrome-mcp's scripts
+    pub context_id: u64,
+    /// Flattened session of a cross-origin iframe, which runs in its own target (whose id is
+    /// the frame id); `context_id` belongs to that session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+/// Start or end of a `drag_and_drop`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DragEndpoint {
+    /// Center of the element found for a CSS selector or text
+    Element(String),
+    /// Viewport point in CSS pixels
+    Point(f64, f64),
+}
+
+/// Bounding box of an element at a point in time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PositionSample {
+    /// Milliseconds since tracking started
+    pub timestamp_ms: f64,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// CPU profile as returned by `Profiler.stop`.
+/// Serializes to the `.cpuprofile` format loaded by Chrome DevTools.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CpuProfile {
+    /// Call tree nodes (`Profiler.ProfileNode`)
+    pub nodes: Vec<Value>,
+    /// Start time in microseconds
+    pub start_time: f64,
+    /// End time in microseconds
+    pub end_time: f64,
+    /// Node id of each sample
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub samples: Option<Vec<u64>>,
+    /// Microseconds between consecutive samples
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_deltas: Option<Vec<i64>>,
+}
+
+impl CpuProfile {
+    /// Profiled duration in milliseconds
+    pub fn duration_ms(&self) -> f64 {
+        (self.end_time - self.start_time) / 1000.0
     }
+}
 
-    /// Close a tab
-    pub async fn close_tab(&self, tab_id: &str) -> Result<()> {
-        self.cdp.close_tab(tab_id).await?;
-        info!("Closed tab: {}", tab_id);
-        Ok(())
+/// Popup window: a page target other than the tab that opened it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PopupInfo {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    /// Only reported for popups opened without `noopener`
+    pub opener_id: Option<String>,
+}
+
+/// DevTools context of a browser extension, e.g. its DevTools panel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevToolsTarget {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    /// ID of the extension, the host of its `chrome-extension://` URL
+    pub extension_id: String,
+}
+
+/// Failed HTML5 constraint validation on a form field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationError {
+    pub selector: String,
+    pub field_name: Option<String>,
+    pub message: String,
+    /// `ValidityState` flags of the field (`valueMissing`, `typeMismatch`, ...)
+    pub validity_state: HashMap<String, bool>,
+}
+
+/// Problem found by `Browser::verify_form_labels`: the input has no accessible name at all,
+/// a `<label for>` meant for it does not reach it (wrong or duplicate id), or its
+/// `aria-label`/`aria-labelledby` is empty or points at missing elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelIssueType {
+    NoLabel,
+    InvalidForAttribute,
+    MissingAriaLabel,
+}
+
+/// Form field screen readers cannot announce with a label
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelIssue {
+    pub input_selector: String,
+    pub issue: LabelIssueType,
+    pub recommendation: String,
+}
+
+/// Input without an accessible name, as found by `FORM_LABELS_JS`
+#[derive(Debug, Clone, Deserialize)]
+struct UnlabeledInput {
+    input_selector: String,
+    id: Option<String>,
+    aria_label: Option<String>,
+    aria_labelledby: Option<String>,
+    missing_labelledby_ids: Vec<String>,
+    /// `for` of a nearby `<label>` that was meant for this input but does not reach it
+    stray_label_for: Option<String>,
+}
+
+/// Inputs tried by `Browser::fuzz_input`
+#[derive(Debug, Clone, PartialEq)]
+pub enum FuzzStrategy {
+    /// Random strings mixing ASCII, control and non-ASCII characters
+    Random,
+    /// Empty, whitespace, max-length, over-length, numeric edge and special-character values
+    Boundary,
+    /// Common SQL injection patterns
+    Sql,
+    /// Common XSS payloads
+    Xss,
+    Custom(Vec<String>),
+}
+
+/// Classification of a fuzzed submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FuzzStatus {
+    /// No JavaScript error and no navigation
+    Passed,
+    /// The page threw uncaught exceptions
+    JsErrors,
+    /// The page navigated away without JavaScript errors
+    Navigated,
+}
+
+/// Outcome of one `Browser::fuzz_input` iteration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzResult {
+    pub input: String,
+    pub js_errors: Vec<String>,
+    pub url_changed: bool,
+    pub status: FuzzStatus,
+}
+
+/// Outcome of a CORS preflight check
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CorsResult {
+    pub allowed: bool,
+    pub allowed_origin: Option<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// Why the request would be blocked, if it would be
+    pub error: Option<String>,
+}
+
+/// Viewport screenshot taken by `screenshot_at_breakpoints`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakpointScreenshot {
+    /// Viewport width in CSS pixels
+    pub width: u32,
+    pub screenshot_b64: String,
+}
+
+/// Whether a port answered a `probe_local_ports` request from the page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortProbeResult {
+    pub port: u16,
+    pub open: bool,
+    /// HTTP status, when the response was readable from the page
+    pub response_code: Option<u16>,
+}
+
+/// Basic facts about the current page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageInfo {
+    pub title: String,
+    pub url: String,
+    /// Icon declared with `<link rel="icon">`, or `/favicon.ico` of the page's origin
+    pub favicon_url: Option<String>,
+    /// Height of the viewport top covered by fixed or sticky headers, in CSS pixels
+    pub fixed_header_height: f64,
+}
+
+/// Response to a `fetch_with_headers` request made from the page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FetchResult {
+    /// HTTP status; 0 for a redirect that was not followed
+    pub status: u16,
+    /// Final URL, after any followed redirects
+    pub url: String,
+    pub redirected: bool,
+    /// Response headers readable from the page, with lowercase names
+    pub response_headers: HashMap<String, String>,
+    pub body_text: String,
+    /// Served from the HTTP cache without a network transfer
+    pub from_cache: bool,
+}
+
+/// Outcome of checking one link with `check_links`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkCheckResult {
+    pub url: String,
+    /// HTTP status, when the server answered and it could be read
+    pub status_code: Option<u16>,
+    /// Why the link could not be loaded, or that its status is unknown
+    pub error: Option<String>,
+    pub is_broken: bool,
+}
+
+impl LinkCheckResult {
+    /// Result for a loaded link; without a status it is reported as unknown, not broken
+    fn with_status(url: &str, status_code: Option<u16>) -> Self {
+        Self {
+            url: url.to_string(),
+            status_code,
+            error: status_code.is_none().then(|| "HTTP status unknown".to_string()),
+            is_broken: status_code.is_some_and(|status| status >= 400),
+        }
     }
 
-    /// Navigate to a URL
-    pub async fn navigate(&mut self, url: &str) -> Result<()> {
-        info!("Navigating to: {}", url);
-        self.cdp.navigate(url).await?;
-        
-        // Wait for navigation to complete
-        self.wait_for_condition(WaitCondition::PageLoad, 30000).await?;
-        
-        // Clear accessibility cache after navigation
-        self.accessibility.clear_cache();
-        
+    fn failed(url: &str, error: impl Into<String>) -> Self {
+        Self {
+            url: url.to_string(),
+            status_code: None,
+            error: Some(error.into()),
+            is_broken: true,
+        }
+    }
+}
+
+/// A CSS custom property and its computed value on an element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CssVariable {
+    pub name: String,
+    pub value: String,
+    /// URL of the stylesheet declaring it; `None` for `<style>` blocks and inline styles
+    pub source: Option<String>,
+}
+
+/// Progress of a download, as reported by Chrome or else as the download directory shows it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    pub bytes_downloaded: u64,
+    /// Expected size; not known from a partial file alone
+    pub total_bytes: Option<u64>,
+    pub percent: Option<f64>,
+    pub complete: bool,
+}
+
+/// Download followed through `Browser.downloadWillBegin` and `Browser.downloadProgress`
+#[derive(Debug, Clone)]
+struct TrackedDownload {
+    filename: String,
+    begun: std::time::SystemTime,
+    received_bytes: u64,
+    /// 0 when the server sent no length
+    total_bytes: u64,
+    /// `inProgress`, `completed` or `canceled`
+    state: String,
+}
+
+impl TrackedDownload {
+    fn progress(&self) -> DownloadProgress {
+        let complete = self.state == "completed";
+        let total_bytes = (self.total_bytes > 0).then_some(self.total_bytes).or(complete.then_some(self.received_bytes));
+        DownloadProgress {
+            bytes_downloaded: self.received_bytes,
+            total_bytes,
+            percent: total_bytes.filter(|total| *total > 0).map(|total| self.received_bytes as f64 * 100.0 / total as f64),
+            complete,
+        }
+    }
+}
+
+/// A finished download
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadResult {
+    pub filename: String,
+    pub size_bytes: u64,
+    pub duration_ms: u64,
+}
+
+/// Shape or text element inside an inline SVG
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SvgElement {
+    pub tag: String,
+    pub id: Option<String>,
+    pub class: Option<String>,
+    /// `getBBox()` in SVG user units: x, y, width, height
+    pub bounds: Option<(f64, f64, f64, f64)>,
+    pub text: Option<String>,
+    /// `d` attribute of `<path>` elements
+    pub path_data: Option<String>,
+}
+
+/// Phases of the main document load, from its `PerformanceNavigationTiming` entry
+///
+/// Phase durations are zero when the phase did not happen (e.g. a reused connection or a
+/// plain HTTP page has no SSL handshake); `*_ms` milestones are relative to navigation start
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NavigationTiming {
+    pub url: String,
+    /// `navigate`, `reload`, `back_forward` or `prerender`
+    pub navigation_type: String,
+    pub redirect_count: u32,
+    pub redirect_ms: f64,
+    pub dns_lookup_ms: f64,
+    pub tcp_connect_ms: f64,
+    pub ssl_handshake_ms: f64,
+    pub ttfb_ms: f64,
+    pub response_ms: f64,
+    pub dom_interactive_ms: f64,
+    pub dom_content_loaded_ms: f64,
+    pub dom_complete_ms: f64,
+    pub load_event_ms: f64,
+    pub duration_ms: f64,
+    pub transfer_size: u64,
+    pub encoded_body_size: u64,
+    pub decoded_body_size: u64,
+}
+
+/// JavaScript library found on the page, by a global variable or a script URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryInfo {
+    /// Library name, e.g. `jquery` or `react-dom`
+    pub name: String,
+    pub version: Option<String>,
+    /// Global variable (or attribute) the version was read from
+    pub global: Option<String>,
+    /// Script the library was loaded from, recognized by its URL
+    pub script_url: Option<String>,
+}
+
+/// Library (or script) loaded more than once, or present in conflicting versions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateLibrary {
+    pub name: String,
+    /// Distinct versions seen
+    pub versions: Vec<String>,
+    /// Scripts loading it, repeats included
+    pub script_urls: Vec<String>,
+}
+
+/// Result of `analyze_js_bundles`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BundleAnalysis {
+    pub detected_libraries: Vec<LibraryInfo>,
+    pub duplicates: Vec<DuplicateLibrary>,
+    /// Size of the external scripts (as transferred, when the browser can tell) and inline scripts
+    pub total_script_size_bytes: u64,
+}
+
+/// How bad a failed SEO check is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeoSeverity {
+    Error,
+    Warning,
+    Pass,
+}
+
+/// One check of `run_seo_audit`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeoCheck {
+    pub name: String,
+    pub passed: bool,
+    pub severity: SeoSeverity,
+    /// What was found, e.g. the title text
+    pub value: Option<String>,
+    pub recommendation: String,
+}
+
+/// Result of `run_seo_audit`, errors first, then warnings, then passed checks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SeoAudit {
+    pub checks: Vec<SeoCheck>,
+}
+
+/// What `SEO_FACTS_JS` reports about the page
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SeoFacts {
+    title: Option<String>,
+    descriptions: Vec<String>,
+    robots: Vec<String>,
+    viewport: Vec<String>,
+    canonicals: Vec<String>,
+    headings: Vec<u8>,
+    images: usize,
+    images_without_alt: usize,
+}
+
+/// Kind of render-blocking resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderBlockingType {
+    Script,
+    Stylesheet,
+}
+
+/// Resource delaying first paint, as found by `Browser::get_render_blocking_resources`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderBlockingResource {
+    pub url: String,
+    #[serde(rename = "type")]
+    pub type_: RenderBlockingType,
+    /// Fetch duration from the resource timing entry, if the resource has one
+    pub load_time_ms: Option<f64>,
+    /// Chrome reported the resource as render-blocking (`renderBlockingStatus`)
+    #[serde(default)]
+    pub critical_path: bool,
+    #[serde(default)]
+    pub recommendation: String,
+}
+
+/// Timing of a subresource fetch, from its `PerformanceResourceTiming` entry
+///
+/// Cross-origin resources without `Timing-Allow-Origin` report zero for everything but
+/// `start_time_ms` and `duration_ms`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceTiming {
+    pub url: String,
+    /// What started the fetch: `script`, `img`, `fetch`, `xmlhttprequest`, `css`, ...
+    pub initiator_type: String,
+    pub start_time_ms: f64,
+    pub duration_ms: f64,
+    pub dns_lookup_ms: f64,
+    pub tcp_connect_ms: f64,
+    pub ssl_handshake_ms: f64,
+    pub ttfb_ms: f64,
+    pub response_ms: f64,
+    pub transfer_size: u64,
+    pub encoded_body_size: u64,
+    pub decoded_body_size: u64,
+}
+
+/// WebAssembly module instantiated by the page, as listed by `Browser::get_wasm_modules`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WasmModuleInfo {
+    /// URL the module was streamed from, when known
+    pub name: Option<String>,
+    pub exports: Vec<WasmExport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WasmExport {
+    pub name: String,
+    pub kind: WasmExportKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WasmExportKind {
+    Function,
+    Memory,
+    Table,
+    Global,
+    /// Exception tag
+    Tag,
+}
+
+/// Console message or uncaught exception, as recorded by `Browser::start_console_capture`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsoleEntry {
+    /// `log`, `warn`, `error`, `debug` or `info`; other console methods keep their name
+    /// (e.g. `table`), and uncaught exceptions are `error`s
+    pub level: String,
+    pub text: String,
+    /// Unix timestamp in milliseconds
+    pub timestamp: f64,
+}
+
+impl ConsoleEntry {
+    /// Entry for a `Runtime.consoleAPICalled` or `Runtime.exceptionThrown` event
+    pub fn from_event(method: &str, params: &Value) -> Option<Self> {
+        let timestamp = params.get("timestamp").and_then(|t| t.as_f64()).unwrap_or(0.0);
+        match method {
+            CONSOLE_API_CALLED => {
+                let level = match params.get("type").and_then(|t| t.as_str()).unwrap_or("log") {
+                    "warning" => "warn",
+                    level => level,
+                };
+                let args = params.get("args").and_then(|a| a.as_array()).map(Vec::as_slice).unwrap_or_default();
+                let text = args.iter().map(remote_object_text).collect::<Vec<_>>().join(" ");
+                Some(ConsoleEntry { level: level.to_string(), text, timestamp })
+            }
+            EXCEPTION_THROWN => {
+                let details = params.get("exceptionDetails")?;
+                let text = details
+                    .get("exception")
+                    .and_then(|e| e.get("description"))
+                    .or_else(|| details.get("text"))
+                    .and_then(|t| t.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                Some(ConsoleEntry { level: "error".to_string(), text, timestamp })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How the console prints a `Runtime.RemoteObject` argument, roughly
+fn remote_object_text(object: &Value) -> String {
+    match object.get("value") {
+        Some(Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+        None => object
+            .get("unserializableValue")
+            .or_else(|| object.get("description"))
+            .and_then(|d| d.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| object.get("type").and_then(|t| t.as_str()).unwrap_or_default().to_string()),
+    }
+}
+
+/// Performance timeline mark, as added by `Browser::console_mark`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeMark {
+    pub name: String,
+    /// Milliseconds since the navigation started (`performance.now()` time base)
+    pub start_time: f64,
+}
+
+/// Web Storage area
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageType {
+    Local,
+    Session,
+}
+
+impl StorageType {
+    /// Name of the storage object on `window`
+    pub fn js_object(&self) -> &'static str {
+        match self {
+            StorageType::Local => "localStorage",
+            StorageType::Session => "sessionStorage",
+        }
+    }
+}
+
+/// Permission of the Permissions API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum BrowserPermission {
+    Camera,
+    Microphone,
+    Notifications,
+    Push,
+    Geolocation,
+    ClipboardRead,
+    ClipboardWrite,
+    Midi,
+    BackgroundSync,
+    PersistentStorage,
+}
+
+impl BrowserPermission {
+    pub const ALL: [BrowserPermission; 10] = [
+        BrowserPermission::Camera,
+        BrowserPermission::Microphone,
+        BrowserPermission::Notifications,
+        BrowserPermission::Push,
+        BrowserPermission::Geolocation,
+        BrowserPermission::ClipboardRead,
+        BrowserPermission::ClipboardWrite,
+        BrowserPermission::Midi,
+        BrowserPermission::BackgroundSync,
+        BrowserPermission::PersistentStorage,
+    ];
+
+    /// Permission name, as used by `navigator.permissions` and `Browser.setPermission`
+    pub fn name(&self) -> &'static str {
+        match self {
+            BrowserPermission::Camera => "camera",
+            BrowserPermission::Microphone => "microphone",
+            BrowserPermission::Notifications => "notifications",
+            BrowserPermission::Push => "push",
+            BrowserPermission::Geolocation => "geolocation",
+            BrowserPermission::ClipboardRead => "clipboard-read",
+            BrowserPermission::ClipboardWrite => "clipboard-write",
+            BrowserPermission::Midi => "midi",
+            BrowserPermission::BackgroundSync => "background-sync",
+            BrowserPermission::PersistentStorage => "persistent-storage",
+        }
+    }
+
+    /// Permission descriptor; Chrome only supports push permissions for visible notifications
+    fn descriptor(&self) -> Value {
+        match self {
+            BrowserPermission::Push => json!({ "name": "push", "userVisibleOnly": true }),
+            permission => json!({ "name": permission.name() }),
+        }
+    }
+}
+
+/// State of a permission for the page's origin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    Prompt,
+}
+
+/// One representation of a clipboard item, as read by `Browser::read_clipboard_items`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardItem {
+    /// MIME type, e.g. `text/plain` or `image/png`
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Content, base64-encoded (UTF-8 for text)
+    pub data_b64: String,
+    #[serde(default)]
+    pub kind: ClipboardContentKind,
+}
+
+/// What a clipboard item holds, derived from its MIME type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardContentKind {
+    Text,
+    Html,
+    Image,
+    #[default]
+    Other,
+}
+
+impl ClipboardContentKind {
+    pub fn from_mime_type(mime_type: &str) -> Self {
+        match mime_type {
+            "text/plain" => ClipboardContentKind::Text,
+            "text/html" => ClipboardContentKind::Html,
+            t if t.starts_with("image/") => ClipboardContentKind::Image,
+            _ => ClipboardContentKind::Other,
+        }
+    }
+}
+
+/// `storage` event observed by `Browser::listen_for_storage_changes`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageChange {
+    /// Changed key, `None` when the storage was cleared
+    pub key: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    /// URL of the document whose storage changed
+    pub url: String,
+    pub storage_type: StorageType,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: f64,
+}
+
+/// Outcome of `Browser::observe_intersection`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntersectionResult {
+    /// Whether the sentinel entered the viewport before the timeout
+    pub intersected: bool,
+    /// Visible fraction of the sentinel (0.0 to 1.0) when it intersected
+    pub intersection_ratio: f64,
+    /// Milliseconds until the sentinel intersected, or the timeout
+    pub time_ms: u64,
+}
+
+/// Server-sent event received by `Browser::capture_sse_events`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SseEvent {
+    /// Event name, `message` for events without an `event:` field
+    pub event_type: String,
+    pub data: String,
+    /// Last event id (`id:` field) seen on the stream
+    pub id: Option<String>,
+    /// Milliseconds since the `EventSource` was opened
+    pub received_at_ms: u64,
+}
+
+/// Key combination such as `Ctrl+Shift+T`, as sent with `Input.dispatchKeyEvent`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyShortcut {
+    /// Bitmask of the held modifiers: 1 = Alt, 2 = Ctrl, 4 = Meta/Cmd, 8 = Shift
+    pub modifiers: u32,
+    pub key: String,
+    pub code: String,
+    pub windows_virtual_key_code: u32,
+}
+
+impl KeyShortcut {
+    pub const ALT: u32 = 1;
+    pub const CTRL: u32 = 2;
+    pub const META: u32 = 4;
+    pub const SHIFT: u32 = 8;
+
+    /// Parse `+`-separated modifiers followed by a key name, e.g. `Cmd+Shift+T` or `Enter`.
+    /// Names are case-insensitive; unknown modifiers and keys are an `InvalidOperation` error.
+    pub fn parse(shortcut: &str) -> Result<Self> {
+        let parts: Vec<&str> = shortcut.split('+').map(str::trim).collect();
+        let (key_name, modifier_names) = parts.split_last().filter(|(key, _)| !key.is_empty()).ok_or_else(|| {
+            ChromeMcpError::invalid_operation(format!("No key in shortcut: {}", shortcut))
+        })?;
+
+        let mut modifiers = 0;
+        for name in modifier_names {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "alt" | "option" | "opt" => Self::ALT,
+                "ctrl" | "control" => Self::CTRL,
+                "meta" | "cmd" | "command" | "super" | "win" => Self::META,
+                "shift" => Self::SHIFT,
+                _ => return Err(ChromeMcpError::invalid_operation(format!("Unknown modifier '{}' in shortcut: {}", name, shortcut))),
+            };
+        }
+
+        let (key, code, windows_virtual_key_code) = key_definition(key_name, modifiers & Self::SHIFT != 0)
+            .ok_or_else(|| ChromeMcpError::invalid_operation(format!("Unknown key '{}' in shortcut: {}", key_name, shortcut)))?;
+        Ok(Self { modifiers, key, code, windows_virtual_key_code })
+    }
+
+    /// Text the key inserts, when no modifier other than Shift turns it into a command
+    fn text(&self) -> Option<&str> {
+        if self.modifiers & (Self::ALT | Self::CTRL | Self::META) != 0 {
+            return None;
+        }
+        match self.key.as_str() {
+            "Enter" => Some("\r"),
+            key if key.chars().count() == 1 => Some(key),
+            _ => None,
+        }
+    }
+}
+
+/// `key`, `code` and Windows virtual key code of a key name
+fn key_definition(name: &str, shift: bool) -> Option<(String, String, u32)> {
+    let named = |key: &str, code: u32| Some((key.to_string(), key.to_string(), code));
+    match name.to_ascii_lowercase().as_str() {
+        "enter" | "return" => named("Enter", 13),
+        "escape" | "esc" => named("Escape", 27),
+        "tab" => named("Tab", 9),
+        "backspace" => named("Backspace", 8),
+        "delete" | "del" => named("Delete", 46),
+        "insert" => named("Insert", 45),
+        "home" => named("Home", 36),
+        "end" => named("End", 35),
+        "pageup" => named("PageUp", 33),
+        "pagedown" => named("PageDown", 34),
+        "arrowleft" | "left" => named("ArrowLeft", 37),
+        "arrowup" | "up" => named("ArrowUp", 38),
+        "arrowright" | "right" => named("ArrowRight", 39),
+        "arrowdown" | "down" => named("ArrowDown", 40),
+        "space" => Some((" ".to_string(), "Space".to_string(), 32)),
+        lower => {
+            if let Some(number) = lower.strip_prefix('f').and_then(|n| n.parse::<u32>().ok()).filter(|n| (1..=12).contains(n)) {
+                return named(&format!("F{}", number), 111 + number);
+            }
+            let mut chars = lower.chars();
+            match (chars.next(), chars.next()) {
+                (Some(letter @ 'a'..='z'), None) => {
+                    let upper = letter.to_ascii_uppercase();
+                    let key = if shift { upper } else { letter };
+                    Some((key.to_string(), format!("Key{}", upper), upper as u32))
+                }
+                (Some(digit @ '0'..='9'), None) => Some((digit.to_string(), format!("Digit{}", digit), digit as u32)),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Scroll direction for snap-point navigation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Element reference for consistent targeting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementRef {
+    pub id: String,
+    pub selector: Option<String>,
+    pub accessibility_id: Option<String>,
+    pub bounds: Option<(f64, f64, f64, f64)>, // x, y, width, height
+    pub text: Option<String>,
+    pub role: Option<String>,
+    #[serde(default)]
+    pub clickable: bool,
+    /// Relevance to the query that found the element, see `relevance_score`
+    #[serde(default)]
+    pub score: f64,
+    /// DOM node behind an accessibility node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend_node_id: Option<u64>,
+    /// Selector built from stable attributes, see `generate_stable_selector`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stable_selector: Option<String>,
+}
+
+/// Change of `document.title`, as observed by `Browser::watch_title`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TitleChange {
+    pub old_title: String,
+    pub new_title: String,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: f64,
+}
+
+/// Wait conditions
+#[derive(Debug, Clone)]
+pub enum WaitCondition {
+    /// Wait for element to be present
+    ElementPresent(String),
+    /// Wait for element to be visible
+    ElementVisible(String),
+    /// Wait for at least `min_ratio` (0.0 to 1.0) of an element to be inside the viewport
+    ElementVisibleRatio(String, f64),
+    /// Wait for element to be clickable
+    ElementClickable(String),
+    /// Wait for text to be present
+    TextPresent(String),
+    /// Wait for URL to match pattern
+    UrlMatches(String),
+    /// Wait for URL to contain text
+    UrlContains(String),
+    /// Wait for the page title to contain text
+    TitleContains(String),
+    /// Wait for the page title to equal text
+    TitleEquals(String),
+    /// Wait for the page title to match a pattern: a wildcard pattern (`*` and `?`) when it
+    /// has wildcards, otherwise a prefix
+    TitleMatches(String),
+    /// Wait for the page title to differ from the given one
+    TitleChangedFrom(String),
+    /// Wait for page load to complete
+    PageLoad,
+    /// Wait until no more than `max_inflight` requests are in flight and none started or
+    /// finished for `idle_ms`; tolerating a few (like puppeteer's networkidle2) keeps
+    /// long-lived requests such as EventSource streams or long polls from blocking the wait
+    NetworkIdle { idle_ms: u64, max_inflight: usize },
+    /// Wait for a request whose URL matches a wildcard pattern (`*` and `?`), and whose
+    /// method matches if given, to finish loading
+    RequestCompleted { url_pattern: String, method: Option<String> },
+    /// Wait for a request whose URL matches a wildcard pattern (`*` and `?`) to fail
+    RequestFailed { url_pattern: String },
+}
+
+/// Events `wait_for_condition` and `start_network_capture` follow, requests first
+const NETWORK_EVENT_METHODS: [&str; 4] = [
+    "Network.requestWillBeSent",
+    "Network.responseReceived",
+    "Network.loadingFinished",
+    "Network.loadingFailed",
+];
+
+impl Browser {
+    /// Create a new Browser instance
+    pub fn new(chrome_host: &str, chrome_port: u16) -> Result<Self> {
+        Self::with_cdp_client(CdpClient::new(chrome_host, chrome_port))
+    }
+}
+
+impl<B: CdpClientBackend> Browser<B> {
+    /// Create a Browser on top of an existing CDP client, e.g. one with a mock backend
+    pub fn with_cdp_client(cdp: CdpClient<B>) -> Result<Self> {
+        let accessibility = AccessibilityManager::new(cdp.clone());
+        let screenshot = ScreenshotManager::new(cdp.clone());
+        let native_input = NativeInputManager::new()
+            .map_err(|e| warn!("Native input unavailable: {}", e))
+            .ok();
+
+        Ok(Self {
+            cdp,
+            accessibility,
+            screenshot,
+            native_input,
+            native_input_display: None,
+            current_tab_id: None,
+            network_events: Arc::new(Mutex::new(Vec::new())),
+            network_capture: None,
+            cookies: HashMap::new(),
+            mocks: Arc::new(Mutex::new(Vec::new())),
+            fetch_handler: None,
+            http_auth: Arc::new(Mutex::new(HashMap::new())),
+            last_cpu_profile: None,
+            popup_opener_tab_id: None,
+            frame: None,
+            js_error_budget: None,
+            js_error_capture_script: None,
+            title_watch_tag: None,
+            title_history: Arc::new(Mutex::new(Vec::new())),
+            title_watch: None,
+            console_entries: Arc::new(Mutex::new(Vec::new())),
+            console_capture: None,
+            checkpoint_file: None,
+            enabled_domains: Vec::new(),
+            injected_scripts: Vec::new(),
+            download_dir: None,
+            downloads: Arc::new(Mutex::new(HashMap::new())),
+            download_watch: None,
+            click_jitter: None,
+            human_rng: HumanRng::from_time(),
+            devtools_sessions: HashMap::new(),
+            devtools_target: None,
+        })
+    }
+
+    /// Save a session checkpoint to this file after every navigation and resume from it on connect
+    pub fn with_checkpoint_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint_file = Some(path.into());
+        self
+    }
+
+    /// Auto-save screenshots to `output_dir`, keeping at most `max_files` of them there
+    pub fn with_screenshot_auto_save(mut self, output_dir: impl Into<PathBuf>, max_files: Option<usize>) -> Self {
+        self.screenshot.set_auto_save(output_dir, max_files);
+        self
+    }
+
+    /// Connect to Chrome and select a tab
+    pub async fn connect(&mut self, tab_id: Option<&str>) -> Result<String> {
+        info!("Connecting to Chrome browser");
+
+        if tab_id.is_none() {
+            if let Some(tab) = self.resume_from_checkpoint().await {
+                return Ok(tab);
+            }
+        }
+
+        let tab = if let Some(id) = tab_id {
+            // Connect to specific tab
+            self.connect_cdp(id).await?;
+            id.to_string()
+        } else {
+            // Find an existing tab or create a new one
+            let tabs = self.cdp.list_tabs().await?;
+            let tab_id = if let Some(tab) = tabs.first() {
+                tab.id.clone()
+            } else {
+                // Create new tab
+                let new_tab = self.cdp.create_tab(None).await?;
+                new_tab.id
+            };
+
+            self.connect_cdp(&tab_id).await?;
+            tab_id
+        };
+
+        self.current_tab_id = Some(tab.clone());
+        info!("Connected to tab: {}", tab);
+        Ok(tab)
+    }
+
+    /// Connect the CDP client, and the managers sending commands through it, to a tab
+    async fn connect_cdp(&mut self, tab_id: &str) -> Result<()> {
+        self.cdp.connect_to_tab(tab_id).await?;
+        self.share_cdp_connection();
         Ok(())
     }
 
-    /// Click on an element
-    pub async fn click(&mut self, selector_or_text: &str) -> Result<()> {
-        debug!("Attempting to click: {}", selector_or_text);
+    /// Hand the current CDP connection to the accessibility and screenshot managers, which
+    /// would otherwise keep using the one from before a tab switch
+    fn share_cdp_connection(&mut self) {
+        // Execution contexts belong to the previous target
+        self.frame = None;
+        self.accessibility = AccessibilityManager::new(self.cdp.clone());
+        self.screenshot.set_cdp_client(self.cdp.clone());
+    }
+
+    /// Restore the session from the checkpoint file, if one is configured and present
+    async fn resume_from_checkpoint(&mut self) -> Option<String> {
+        let path = self.checkpoint_file.clone()?;
+        let checkpoint = match SessionCheckpoint::load(&path) {
+            Ok(checkpoint) => checkpoint?,
+            Err(e) => {
+                warn!("Ignoring unreadable checkpoint {}: {}", path.display(), e);
+                return None;
+            }
+        };
+
+        // Only resume once Chrome is reachable again
+        if let Err(e) = self.cdp.list_tabs().await {
+            debug!("Chrome not available, not resuming checkpoint: {}", e);
+            return None;
+        }
+
+        match self.restore_session(checkpoint).await {
+            Ok(tab) => Some(tab),
+            Err(e) => {
+                warn!("Failed to restore session from {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Reconnect to (or recreate) the checkpointed tab and restore domains, scripts and cookies
+    pub async fn restore_session(&mut self, checkpoint: SessionCheckpoint) -> Result<String> {
+        info!("Restoring session at {}", checkpoint.current_url);
+
+        let tabs = self.cdp.list_tabs().await?;
+        let existing = tabs.iter().any(|t| t.id == checkpoint.tab_id);
+        let tab = if existing {
+            checkpoint.tab_id.clone()
+        } else {
+            // The URL is loaded only once cookies and scripts are back in place
+            self.cdp.create_tab(None).await?.id
+        };
+
+        self.connect_cdp(&tab).await?;
+        self.current_tab_id = Some(tab.clone());
+
+        for cookie in checkpoint.cookie_state {
+            self.set_cookie(cookie).await?;
+        }
+
+        // Script registrations belong to the previous CDP session, so add them again
+        let error_capture_source = self.js_error_capture_script.take().and_then(|id| {
+            self.injected_scripts.iter().find(|(i, _)| *i == id).map(|(_, source)| source.clone())
+        });
+        self.injected_scripts.clear();
+        for source in &checkpoint.injected_scripts {
+            let identifier = self.add_init_script(source).await?;
+            if error_capture_source.as_deref() == Some(source.as_str()) {
+                self.js_error_capture_script = Some(identifier);
+            }
+        }
+
+        self.enabled_domains.clear();
+        for domain in &checkpoint.enabled_domains {
+            self.enable_domain(domain, None).await?;
+        }
+
+        if !existing && !checkpoint.current_url.is_empty() {
+            self.navigate(&checkpoint.current_url).await?;
+        }
+
+        info!("Restored session in tab: {}", tab);
+        Ok(tab)
+    }
+
+    /// Capture the state needed to resume this session
+    pub async fn create_checkpoint(&mut self) -> Result<SessionCheckpoint> {
+        let tab_id = self.current_tab_id.clone()
+            .ok_or_else(|| ChromeMcpError::invalid_operation("Not connected to a tab"))?;
+
+        Ok(SessionCheckpoint {
+            tab_id,
+            enabled_domains: self.enabled_domains.clone(),
+            injected_scripts: self.injected_scripts.iter().map(|(_, source)| source.clone()).collect(),
+            current_url: self.current_url().await?,
+            cookie_state: self.get_cookies().await?,
+        })
+    }
+
+    /// Write a checkpoint to the configured file; failures are logged, not returned
+    async fn save_checkpoint(&mut self) {
+        let Some(path) = self.checkpoint_file.clone() else {
+            return;
+        };
+
+        let result = match self.create_checkpoint().await {
+            Ok(checkpoint) => checkpoint.save(&path),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("Failed to write checkpoint {}: {}", path.display(), e);
+        }
+    }
+
+    /// Enable a CDP domain and remember it for session restore
+    async fn enable_domain(&mut self, domain: &str, params: Option<Value>) -> Result<()> {
+        self.cdp.send_command(&format!("{}.enable", domain), params).await?;
+        if !self.enabled_domains.iter().any(|d| d == domain) {
+            self.enabled_domains.push(domain.to_string());
+        }
+        Ok(())
+    }
+
+    /// Disable a CDP domain enabled with `enable_domain`
+    async fn disable_domain(&mut self, domain: &str) -> Result<()> {
+        self.cdp.send_command(&format!("{}.disable", domain), None).await?;
+        self.enabled_domains.retain(|d| d != domain);
+        Ok(())
+    }
+
+    /// Run a script on every new document and remember it for session restore; returns its identifier
+    async fn add_init_script(&mut self, source: &str) -> Result<String> {
+        let result = self.cdp.send_command("Page.addScriptToEvaluateOnNewDocument", Some(json!({
+            "source": source
+        }))).await?;
+        let identifier = result.get("identifier").and_then(|i| i.as_str()).unwrap_or_default().to_string();
+        self.injected_scripts.push((identifier.clone(), source.to_string()));
+        Ok(identifier)
+    }
+
+    /// Remove a script added with `add_init_script`
+    async fn remove_init_script(&mut self, identifier: &str) -> Result<()> {
+        self.cdp.send_command("Page.removeScriptToEvaluateOnNewDocument", Some(json!({
+            "identifier": identifier
+        }))).await?;
+        self.injected_scripts.retain(|(i, _)| i != identifier);
+        Ok(())
+    }
+
+    /// List all available tabs
+    pub async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
+        self.cdp.list_tabs().await
+    }
+
+    /// Create a new tab
+    pub async fn create_tab(&mut self, url: Option<&str>) -> Result<String> {
+        let tab = self.cdp.create_tab(url).await?;
+        info!("Created new tab: {} ({})", tab.title, tab.id);
+        Ok(tab.id)
+    }
+
+    /// Switch to a different tab
+    pub async fn switch_to_tab(&mut self, tab_id: &str) -> Result<()> {
+        self.connect_cdp(tab_id).await?;
+        self.current_tab_id = Some(tab_id.to_string());
+        // Interception and the title watch belong to sessions on the previous tab
+        if self.fetch_handler.is_some() {
+            self.sync_fetch_interception().await?;
+        }
+        if let Some(tag) = self.title_watch_tag.clone() {
+            self.watch_title(&tag).await?;
+        }
+        info!("Switched to tab: {}", tab_id);
+        Ok(())
+    }
+
+    /// Close a tab
+    pub async fn close_tab(&self, tab_id: &str) -> Result<()> {
+        self.cdp.close_tab(tab_id).await?;
+        info!("Closed tab: {}", tab_id);
+        Ok(())
+    }
+
+    /// List open popup windows, i.e. the page targets other than the main tab
+    pub async fn list_popups(&mut self) -> Result<Vec<PopupInfo>> {
+        let result = self.cdp.send_command("Target.getTargets", None).await?;
+        let main_tab_id = self.popup_opener_tab_id.as_deref().or(self.current_tab_id.as_deref());
+        Ok(parse_popup_targets(&result, main_tab_id))
+    }
+
+    /// Wait for a popup opened from now on whose URL matches a wildcard pattern and return
+    /// its tab ID; popups already open are ignored
+    pub async fn wait_for_popup(&mut self, url_pattern: &str, timeout_ms: u64) -> Result<String> {
+        debug!("Waiting for popup matching {} (timeout: {}ms)", url_pattern, timeout_ms);
+
+        // Target discovery lasts as long as the session that enabled it
+        let mut session = self.cdp.open_session().await?;
+        let targets = session.send_command("Target.getTargets", None).await?;
+        let existing: HashSet<String> = parse_popup_targets(&targets, None).into_iter().map(|t| t.id).collect();
+
+        // Discovery first reports the targets already open, including any opened since the snapshot
+        session.send_command("Target.setDiscoverTargets", Some(json!({ "discover": true }))).await?;
+
+        let main_tab_id = self.popup_opener_tab_id.clone().or(self.current_tab_id.clone());
+        let result = timeout(Duration::from_millis(timeout_ms), async {
+            loop {
+                let (method, params) = session.next_event().await?;
+                // Popups usually start at about:blank before redirecting, reported as info changes
+                if method != "Target.targetCreated" && method != "Target.targetInfoChanged" {
+                    continue;
+                }
+
+                let popup = params.get("targetInfo").and_then(|t| popup_from_target(t, main_tab_id.as_deref()));
+                if let Some(popup) = popup {
+                    if !existing.contains(&popup.id) && url_pattern_matches(url_pattern, &popup.url) {
+                        return Ok(popup.id);
+                    }
+                }
+            }
+        }).await;
+
+        match result {
+            Ok(popup_id) => popup_id,
+            Err(_) => Err(ChromeMcpError::Timeout { timeout: timeout_ms }),
+        }
+    }
+
+    /// Switch to a popup, remembering the opener tab to return to
+    pub async fn switch_to_popup(&mut self, popup_tab_id: &str) -> Result<()> {
+        if self.popup_opener_tab_id.is_none() {
+            self.popup_opener_tab_id = self.current_tab_id.clone();
+        }
+        self.switch_to_tab(popup_tab_id).await
+    }
+
+    /// Close the current popup and switch back to the tab that opened it
+    pub async fn close_popup_and_return(&mut self) -> Result<String> {
+        let opener = self.popup_opener_tab_id.take()
+            .ok_or_else(|| ChromeMcpError::invalid_operation("Not switched to a popup"))?;
+
+        if let Some(popup) = self.current_tab_id.clone() {
+            if popup != opener {
+                // The popup may already have closed itself (e.g. after completing an OAuth flow)
+                if let Err(e) = self.close_tab(&popup).await {
+                    debug!("Could not close popup {}: {}", popup, e);
+                }
+            }
+        }
+
+        self.switch_to_tab(&opener).await?;
+        Ok(opener)
+    }
+
+    /// Screenshot the viewport of a tab, scaled down to fit `max_width` x `max_height`
+    /// (aspect ratio preserved), as base64 PNG. The browser switches back to the current tab afterwards.
+    pub async fn capture_tab_thumbnail(&mut self, tab_id: &str, max_width: u32, max_height: u32) -> Result<String> {
+        debug!("Capturing thumbnail of tab {}", tab_id);
+
+        let previous_tab_id = self.current_tab_id.clone();
+        self.switch_to_tab(tab_id).await?;
+        let screenshot = self.cdp.capture_screenshot_typed(CaptureScreenshotRequest {
+            format: Some("png".to_string()),
+            ..Default::default()
+        }).await;
+
+        if let Some(previous_tab_id) = previous_tab_id.filter(|id| id != tab_id) {
+            self.switch_to_tab(&previous_tab_id).await?;
+        }
+        png_thumbnail(&screenshot?.data, max_width, max_height)
+    }
+
+    /// Thumbnails of every open page, in tab order
+    pub async fn capture_all_tab_thumbnails(&mut self, max_width: u32, max_height: u32) -> Result<Vec<(TabInfo, String)>> {
+        let tabs: Vec<TabInfo> = self.list_tabs().await?
+            .into_iter()
+            .filter(|tab| matches!(tab.target_type.as_deref(), None | Some("page")))
+            .collect();
+
+        let mut thumbnails = Vec::new();
+        for tab in tabs {
+            let thumbnail = self.capture_tab_thumbnail(&tab.id, max_width, max_height).await?;
+            thumbnails.push((tab, thumbnail));
+        }
+        Ok(thumbnails)
+    }
+
+    /// Run `f` concurrently on every open page, each with its own clone of this browser
+    /// connected to that page. Results are in tab order; the first error cancels the rest.
+    pub async fn map_tabs<F, Fut, T>(&self, f: F) -> Result<Vec<T>>
+    where
+        F: Fn(Browser<B>) -> Fut + 'static,
+        Fut: Future<Output = Result<T>> + 'static,
+        T: 'static,
+    {
+        let tab_ids: Vec<String> = self.list_tabs().await?
+            .into_iter()
+            .filter(|tab| matches!(tab.target_type.as_deref(), None | Some("page")))
+            .map(|tab| tab.id)
+            .collect();
+        debug!("Mapping over {} tabs", tab_ids.len());
+
+        let f = Rc::new(f);
+        // Tasks share this thread: they are CDP round trips, and `Browser` is not `Send` on every platform
+        LocalSet::new().run_until(async {
+            let mut tasks = JoinSet::new();
+            for (index, tab_id) in tab_ids.into_iter().enumerate() {
+                let mut browser = self.clone();
+                // Only the original browser writes session checkpoints
+                browser.checkpoint_file = None;
+                let f = Rc::clone(&f);
+
+                tasks.spawn_local(async move {
+                    browser.switch_to_tab(&tab_id).await?;
+                    f(browser).await.map(|result| (index, result))
+                });
+            }
+
+            let mut results = Vec::with_capacity(tasks.len());
+            while let Some(joined) = tasks.join_next().await {
+                let result = joined.map_err(|e| ChromeMcpError::invalid_operation(format!("Tab task failed: {}", e)))??;
+                results.push(result);
+            }
+            results.sort_by_key(|(index, _)| *index);
+            Ok(results.into_iter().map(|(_, result)| result).collect())
+        }).await
+    }
+
+    /// ID of the tab commands are sent to
+    pub fn current_tab_id(&self) -> Option<&str> {
+        self.current_tab_id.as_deref()
+    }
+
+    /// Traffic and round-trip times of the CDP commands sent so far
+    pub fn get_connection_metrics(&self) -> ConnectionMetrics {
+        self.cdp.connection_metrics()
+    }
+
+    /// Start counting CDP traffic from zero again
+    pub fn reset_connection_metrics(&self) {
+        self.cdp.reset_connection_metrics();
+    }
+
+    /// Extension DevTools contexts (`other` targets with a `chrome-extension://` URL), such as
+    /// the DevTools panels of installed extensions
+    pub async fn list_devtools_targets(&mut self) -> Result<Vec<DevToolsTarget>> {
+        let result = self.cdp.send_command("Target.getTargets", None).await?;
+        Ok(parse_devtools_targets(&result))
+    }
+
+    /// Run `evaluate_in_devtools_target` in an extension DevTools context from
+    /// `list_devtools_targets` by default; the current tab stays the target of every other command
+    pub async fn switch_to_devtools_target(&mut self, target_id: &str) -> Result<()> {
+        self.devtools_session(target_id)
+            .await
+            .map_err(|e| e.in_frame("browser", "switch_to_devtools_target").with_context("target_id", target_id))?;
+        self.devtools_target = Some(target_id.to_string());
+        info!("Switched to DevTools target: {}", target_id);
+        Ok(())
+    }
+
+    /// Evaluate JavaScript in an extension DevTools context, `target_id` or else the one
+    /// `switch_to_devtools_target` picked, over a flattened session of the current tab's connection
+    pub async fn evaluate_in_devtools_target(&mut self, javascript: &str, target_id: Option<&str>) -> Result<Value> {
+        let target_id = target_id
+            .or(self.devtools_target.as_deref())
+            .ok_or_else(|| ChromeMcpError::invalid_operation("No DevTools target: pass target_id or switch to one first"))?
+            .to_string();
+        let in_frame = |e: ChromeMcpError| e.in_frame("browser", "evaluate_in_devtools_target").with_context("target_id", target_id.as_str());
+
+        let session_id = self.devtools_session(&target_id).await.map_err(in_frame)?;
+        match self.cdp.evaluate_js_in_session(&session_id, javascript, None).await {
+            // Sessions end with the connection they were attached on, e.g. after a tab switch
+            Err(e) if matches!(e.root_cause(), ChromeMcpError::CdpProtocol(message) if message.contains("Session with given id not found")) => {
+                self.devtools_sessions.remove(&target_id);
+                let session_id = self.devtools_session(&target_id).await.map_err(in_frame)?;
+                self.cdp.evaluate_js_in_session(&session_id, javascript, None).await.map_err(in_frame)
+            }
+            result => result.map_err(in_frame),
+        }
+    }
+
+    /// Flattened session of a DevTools target, attaching to it the first time
+    async fn devtools_session(&mut self, target_id: &str) -> Result<String> {
+        if let Some(session_id) = self.devtools_sessions.get(target_id) {
+            return Ok(session_id.clone());
+        }
+        let session_id = self.cdp.attach_to_target(target_id).await?;
+        self.devtools_sessions.insert(target_id.to_string(), session_id.clone());
+        Ok(session_id)
+    }
+
+    /// Navigate to a URL
+    pub async fn navigate(&mut self, url: &str) -> Result<()> {
+        info!("Navigating to: {}", url);
+        self.cdp.navigate_typed(NavigateRequest::new(url))
+            .await
+            .map_err(|e| e.in_frame("browser", "navigate").with_context("url", url))?;
+        
+        // Wait for navigation to complete
+        self.wait_for_condition(WaitCondition::PageLoad, 30000)
+            .await
+            .map_err(|e| e.in_frame("browser", "navigate").with_context("url", url))?;
+        
+        // Clear accessibility cache after navigation
+        self.accessibility.clear_cache();
+        self.frame = None;
+        debug!("CDP connection metrics after navigating to {}: {:?}", url, self.get_connection_metrics());
+
+        self.save_checkpoint().await;
+        
+        Ok(())
+    }
+
+    /// Navigate to a URL and, once the page has loaded, scroll to (`x`, `y`)
+    pub async fn navigate_and_scroll(&mut self, url: &str, x: i32, y: i32) -> Result<()> {
+        self.navigate(url).await?;
+        self.scroll_to(x, y).await
+    }
+
+    /// Reload the current page, optionally bypassing the cache
+    pub async fn reload(&mut self, ignore_cache: bool) -> Result<()> {
+        self.reload_page(ignore_cache, None).await
+    }
+
+    /// Reload the current page, running `script` once in every frame of the reloaded document
+    pub async fn reload_and_inject(&mut self, ignore_cache: bool, script: &str) -> Result<()> {
+        self.reload_page(ignore_cache, Some(script)).await
+    }
+
+    async fn reload_page(&mut self, ignore_cache: bool, script: Option<&str>) -> Result<()> {
+        info!("Reloading page (ignore cache: {})", ignore_cache);
+
+        let mut params = json!({ "ignoreCache": ignore_cache });
+        if let Some(script) = script {
+            params["scriptToEvaluateOnLoad"] = json!(script);
+        }
+        self.cdp.send_command("Page.reload", Some(params))
+            .await
+            .map_err(|e| e.in_frame("browser", "reload"))?;
+
+        self.wait_for_condition(WaitCondition::PageLoad, 30000)
+            .await
+            .map_err(|e| e.in_frame("browser", "reload"))?;
+
+        // The page-side JS error buffer starts empty with the new document, and the
+        // isolated world of a selected frame is gone with the old one
+        self.accessibility.clear_cache();
+        self.frame = None;
+        Ok(())
+    }
+
+    /// Go back one entry in the session history, like the browser's back button
+    pub async fn go_back(&mut self) -> Result<()> {
+        self.traverse_history(-1).await
+    }
+
+    /// Go forward one entry in the session history
+    pub async fn go_forward(&mut self) -> Result<()> {
+        self.traverse_history(1).await
+    }
+
+    /// Move `delta` entries through the session history (negative goes back, 0 reloads)
+    pub async fn navigate_to_index(&mut self, delta: i32) -> Result<()> {
+        if delta == 0 {
+            return self.reload(false).await;
+        }
+        self.traverse_history(delta).await
+    }
+
+    /// Session history `length`, the current entry's `state` and its `url`
+    pub async fn get_history_state(&mut self) -> Result<Value> {
+        let result = self.cdp.evaluate_js(HISTORY_STATE_JS)
+            .await
+            .map_err(|e| e.in_frame("browser", "get_history_state"))?;
+        Ok(result.get("value").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Go to the session history entry `delta` entries from the current one and wait for the
+    /// page it lands on to load. Fails when there is no such entry, where `history.go()` would
+    /// silently do nothing.
+    async fn traverse_history(&mut self, delta: i32) -> Result<()> {
+        info!("History navigation by {} entries", delta);
+        let in_frame = |e: ChromeMcpError| e.in_frame("browser", "traverse_history").with_context("delta", delta);
+
+        let history = self.cdp.send_command("Page.getNavigationHistory", None).await.map_err(in_frame)?;
+        let current = history["currentIndex"].as_i64().ok_or_else(|| {
+            in_frame(ChromeMcpError::cdp_protocol("No currentIndex in Page.getNavigationHistory response"))
+        })?;
+        let entries = history["entries"].as_array().map(Vec::as_slice).unwrap_or_default();
+        let entry_id = usize::try_from(current + i64::from(delta))
+            .ok()
+            .and_then(|index| entries.get(index))
+            .and_then(|entry| entry["id"].as_i64())
+            .ok_or_else(|| {
+                in_frame(ChromeMcpError::invalid_operation(format!(
+                    "No history entry {} from the current one (entry {} of {})",
+                    delta,
+                    current + 1,
+                    entries.len()
+                )))
+            })?;
+
+        let mut frame_navigated = self.cdp.subscribe("Page.frameNavigated");
+        let mut within_document = self.cdp.subscribe("Page.navigatedWithinDocument");
+        self.cdp.send_command("Page.navigateToHistoryEntry", Some(json!({ "entryId": entry_id })))
+            .await
+            .map_err(in_frame)?;
+
+        // The traversal starts asynchronously: wait for the main frame to reach the entry
+        // (another document or the same one) before waiting for it to load
+        timeout(Duration::from_millis(HISTORY_NAVIGATION_TIMEOUT_MS), async {
+            loop {
+                tokio::select! {
+                    Some(params) = frame_navigated.recv() => {
+                        // Subframe navigations don't move the page to another entry
+                        if params["frame"].get("parentId").is_none() {
+                            break;
+                        }
+                    }
+                    Some(_) = within_document.recv() => break,
+                    else => std::future::pending::<()>().await,
+                }
+            }
+        })
+        .await
+        .map_err(|_| in_frame(ChromeMcpError::Timeout { timeout: HISTORY_NAVIGATION_TIMEOUT_MS }))?;
+
+        self.wait_for_condition(WaitCondition::PageLoad, 30000).await.map_err(in_frame)?;
+
+        self.accessibility.clear_cache();
+        self.frame = None;
+        Ok(())
+    }
+
+    /// Click on an element
+    pub async fn click(&mut self, selector_or_text: &str) -> Result<()> {
+        if let Some(frame) = self.frame.clone() {
+            return self.click_in_frame(&frame, selector_or_text).await;
+        }
+        let element_ref = self.find_click_target(selector_or_text).await?;
+        self.click_element_ref(&element_ref, self.click_jitter).await
+    }
+
+    /// Click on an element at a random point near its center (within `jitter_px` on each axis
+    /// and the inner 80 % of its box), rather than exactly on the center
+    pub async fn click_with_jitter(&mut self, selector_or_text: &str, jitter_px: u32) -> Result<()> {
+        let element_ref = self.find_click_target(selector_or_text).await?;
+        self.click_element_ref(&element_ref, Some(jitter_px)).await
+    }
+
+    /// Click the element matching `selector` below any fixed or sticky header covering it:
+    /// on the uncovered part of a partly covered element, after scrolling a fully covered one
+    /// out from under the header
+    pub async fn click_compensating_for_fixed_headers(&mut self, selector: &str) -> Result<()> {
+        let bounds = self.get_element_bounds(selector).await?;
+        let bounds = (bounds.x, bounds.y, bounds.width, bounds.height);
+        let header_height = self.fixed_header_height(Some(selector), None).await?;
+        let (x, y, width, height) = self.avoid_fixed_header(bounds, header_height).await?;
+        self.cdp.click_at(x + width / 2.0, y + height / 2.0).await
+    }
+
+    /// Height of the viewport top covered by fixed or sticky headers
+    pub async fn get_fixed_header_height(&mut self) -> Result<f64> {
+        self.fixed_header_height(None, None).await
+    }
+
+    /// Fixed header height, not counting the header the target element is part of
+    async fn fixed_header_height(&mut self, selector: Option<&str>, target: Option<(f64, f64, f64, f64)>) -> Result<f64> {
+        let target = target.map(|(x, y, width, height)| json!({ "x": x, "y": y, "width": width, "height": height }));
+        let script = FIXED_HEADER_HEIGHT_JS
+            .replace("{threshold}", &FIXED_HEADER_ZONE_PX.to_string())
+            .replace("{selector}", &serde_json::to_string(&selector)?)
+            .replace("{target}", &serde_json::to_string(&target)?);
+        let result = self.cdp.evaluate_js(&script).await?;
+        Ok(result.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0))
+    }
+
+    /// Bounds of the part of an element below a header `header_height` tall, scrolling the
+    /// page first if the header covers all of it
+    async fn avoid_fixed_header(&mut self, bounds: (f64, f64, f64, f64), header_height: f64) -> Result<(f64, f64, f64, f64)> {
+        let (x, y, width, height) = bounds;
+        if y >= header_height {
+            return Ok(bounds);
+        }
+        if y + height > header_height {
+            debug!("Clicking below a {}px fixed header", header_height);
+            return Ok((x, header_height, width, y + height - header_height));
+        }
+
+        let delta = y - header_height - FIXED_HEADER_MARGIN_PX;
+        debug!("Scrolling by {}px to move the element out from under a {}px fixed header", delta, header_height);
+        let result = self.cdp.evaluate_js(&SCROLL_BY_JS.replace("{delta}", &delta.to_string())).await?;
+        // At the top of the page there is nothing to scroll back to
+        let scrolled = result.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        if scrolled == 0.0 {
+            debug!("The page did not scroll, the element stays under the fixed header");
+        }
+        Ok((x, y - scrolled, width, height))
+    }
+
+    /// Randomize the point of every `click` by up to `max_px`, as with `click_with_jitter`
+    pub fn set_click_jitter_enabled(&mut self, enabled: bool, max_px: u32) {
+        self.click_jitter = enabled.then_some(max_px);
+    }
+
+    /// Find the element to click, trying `selector_or_text` as a CSS selector, then as
+    /// accessibility text, then as an accessibility role
+    async fn find_click_target(&mut self, selector_or_text: &str) -> Result<ElementRef> {
+        debug!("Attempting to click: {}", selector_or_text);
+
+        // Strategy 1: Try as CSS selector
+        if let Ok(element_ref) = self.find_element_by_selector(selector_or_text).await {
+            return Ok(element_ref);
+        }
+
+        // Strategy 2: Try as accessibility text
+        if let Ok(element_ref) = self.find_element_by_text(selector_or_text).await {
+            return Ok(element_ref);
+        }
+
+        // Strategy 3: Try as accessibility role
+        if let Ok(element_ref) = self.find_element_by_role(selector_or_text).await {
+            return Ok(element_ref);
+        }
+
+        Err(ChromeMcpError::element_not_found(format!(
+            "Could not find element to click: {}", selector_or_text
+        ))
+        .in_frame("browser", "click")
+        .with_context("target", selector_or_text)
+        .with_context("strategies", json!(["css", "text", "role"])))
+    }
+
+    /// Native input manager, looking for the input tool again if it was missing at startup
+    fn native_input(&self) -> Result<NativeInputManager> {
+        match &self.native_input {
+            Some(manager) => Ok(manager.clone()),
+            None => NativeInputManager::for_display(self.native_input_display.as_deref()),
+        }
+    }
+
+    /// Send native input to the X display `display` (e.g. `:99`) instead of the one in `DISPLAY`
+    pub fn set_native_input_display(&mut self, display: &str) {
+        self.native_input_display = Some(display.to_string());
+        self.native_input = NativeInputManager::for_display(Some(display))
+            .map_err(|e| warn!("Native input unavailable: {}", e))
+            .ok();
+    }
+
+    /// Click at specific coordinates using native input
+    pub async fn native_click(&self, x: f64, y: f64) -> Result<()> {
+        info!("Native click at ({}, {})", x, y);
+        self.native_input()?.click_at(x, y)
+    }
+
+    /// macOS accessibility element at screen coordinates, e.g. a toolbar button
+    pub async fn native_accessibility_element(&self, x: f64, y: f64) -> Result<NativeAxElement> {
+        self.native_input()?.get_native_accessibility_element(x, y)
+    }
+
+    /// Type text into an element or the focused element
+    pub async fn type_text(&mut self, text: &str, selector: Option<&str>) -> Result<()> {
+        info!("Typing text: {}", text);
+
+        if let Some(sel) = selector {
+            // Click on the element first to focus it
+            self.click(sel).await?;
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        // Type the text using CDP
+        self.cdp.type_text(text)
+            .await
+            .map_err(|e| e.in_frame("browser", "type_text").with_context("length", text.chars().count()))?;
+        
+        Ok(())
+    }
+
+    /// Type text into the focused element one character at a time, waiting a random
+    /// `min_delay_ms`..=`max_delay_ms` between characters like a person typing
+    pub async fn type_text_humanized(&mut self, text: &str, min_delay_ms: u64, max_delay_ms: u64) -> Result<()> {
+        info!("Typing text with {}-{}ms delays: {}", min_delay_ms, max_delay_ms, text);
+
+        let mut buffer = [0; 4];
+        for (index, ch) in text.chars().enumerate() {
+            if index > 0 {
+                sleep(typing_delay(min_delay_ms, max_delay_ms, &mut self.human_rng)).await;
+            }
+            self.cdp
+                .send_command("Input.dispatchKeyEvent", Some(json!({ "type": "char", "text": ch.encode_utf8(&mut buffer) })))
+                .await
+                .map_err(|e| e.in_frame("browser", "type_text_humanized").with_context("index", index))?;
+        }
+
+        Ok(())
+    }
+
+    /// Type text using native input
+    pub async fn native_type(&self, text: &str) -> Result<()> {
+        info!("Native typing: {}", text);
+        self.native_input()?.type_text(text)
+    }
+
+    /// Take a screenshot
+    pub async fn screenshot(&mut self, format: Option<&str>, quality: Option<u32>) -> Result<String> {
+        let format = format.unwrap_or("png");
+        self.screenshot.capture_with_options(format, quality, false).await
+    }
+
+    /// Take a full-page screenshot
+    pub async fn screenshot_full_page(&mut self, format: Option<&str>, quality: Option<u32>) -> Result<String> {
+        let format = format.unwrap_or("png");
+        self.screenshot.capture_with_options(format, quality, true).await
+    }
+
+    /// Take a full-page screenshot in viewport-sized chunks, `chunk_height` CSS pixels tall
+    /// (default: the viewport height)
+    pub async fn screenshot_full_page_chunked(&mut self, format: Option<&str>, quality: Option<u32>, chunk_height: Option<u32>) -> Result<Vec<String>> {
+        let format = format.unwrap_or("png");
+        self.screenshot.capture_full_page_chunked(format, quality, chunk_height.unwrap_or(u32::MAX)).await
+    }
+
+    /// Take a viewport screenshot at each of the `breakpoints` widths, `height` pixels tall,
+    /// giving the layout 300ms to reflow after each resize. The window's own viewport size
+    /// is restored afterwards.
+    pub async fn screenshot_at_breakpoints(&mut self, breakpoints: Vec<u32>, height: u32, format: Option<&str>) -> Result<Vec<BreakpointScreenshot>> {
+        let format = format.unwrap_or("png");
+        info!("Taking screenshots at breakpoints {:?}", breakpoints);
+
+        let previous_viewport = self.screenshot.viewport_override();
+        let mut screenshots = Vec::with_capacity(breakpoints.len());
+        let mut result = Ok(());
+        for width in breakpoints {
+            let capture = async {
+                self.screenshot.set_viewport_size(width, height).await?;
+                sleep(Duration::from_millis(300)).await;
+                self.screenshot.capture_with_options(format, None, false).await
+            };
+            match capture.await {
+                Ok(screenshot_b64) => screenshots.push(BreakpointScreenshot { width, screenshot_b64 }),
+                Err(e) => {
+                    result = Err(e.with_context("width", width));
+                    break;
+                }
+            }
+        }
+
+        self.screenshot.restore_viewport(previous_viewport).await?;
+        result.map(|_| screenshots)
+    }
+
+    /// Save a screenshot of the viewport, or of `element` (PNG, all of it with `full_element`),
+    /// to the auto-save directory, named after `name_template` (default
+    /// `{timestamp}-{url_slug}-{counter}`), and return its path
+    pub async fn auto_save_screenshot(
+        &mut self,
+        format: Option<&str>,
+        quality: Option<u32>,
+        name_template: Option<&str>,
+        element: Option<&str>,
+        full_element: bool,
+    ) -> Result<String> {
+        let (screenshot_data, format) = match element {
+            Some(selector) if full_element => (self.screenshot.capture_element_full(selector).await?, "png"),
+            Some(selector) => (self.screenshot.capture_element(selector).await?, "png"),
+            None => {
+                let format = format.unwrap_or("png");
+                (self.screenshot.capture_with_options(format, quality, false).await?, format)
+            }
+        };
+        let output_dir = self.screenshot.auto_save_dir().display().to_string();
+        let name_template = name_template.unwrap_or(DEFAULT_SCREENSHOT_NAME_TEMPLATE);
+        self.screenshot.auto_save(&screenshot_data, format, &output_dir, name_template).await
+    }
+
+    /// Screenshot a specific element
+    pub async fn screenshot_element(&mut self, selector: &str) -> Result<String> {
+        self.screenshot.capture_element(selector).await
+    }
+
+    /// Screenshot the whole of an element, even where it extends beyond the viewport (PNG)
+    pub async fn screenshot_element_full(&mut self, selector: &str) -> Result<String> {
+        self.screenshot.capture_element_full(selector).await
+    }
+
+    /// Evaluate JavaScript
+    pub async fn evaluate(&mut self, javascript: &str) -> Result<Value> {
+        debug!("Evaluating JavaScript: {}", javascript);
+        match self.frame.clone() {
+            Some(frame) => self.evaluate_in_frame(&frame, javascript).await,
+            None => self.cdp.evaluate_js_in_context(javascript, None).await,
+        }
+    }
+
+    /// Evaluate JavaScript in the isolated world of `frame`, over its session if it is cross-origin
+    async fn evaluate_in_frame(&mut self, frame: &FrameContext, javascript: &str) -> Result<Value> {
+        match &frame.session_id {
+            Some(session_id) => self.cdp.evaluate_js_in_session(session_id, javascript, Some(frame.context_id)).await,
+            None => self.cdp.evaluate_js_in_context(javascript, Some(frame.context_id)).await,
+        }
+    }
+
+    /// Run `evaluate`, `click` and `type_text` in the iframe matching `selector` in the
+    /// top-level document, until `switch_to_main_frame`. Cross-origin iframes are entered over
+    /// a session attached to their own target.
+    pub async fn switch_to_frame(&mut self, selector: &str) -> Result<FrameContext> {
+        debug!("Switching to frame: {}", selector);
+        let in_frame = |e: ChromeMcpError| e.in_frame("browser", "switch_to_frame").with_context("selector", selector);
+
+        let node_id = *self.cdp.query_selector_all(selector).await.map_err(in_frame)?.node_ids.first().ok_or_else(|| {
+            in_frame(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector)))
+        })?;
+        let described = self.cdp.send_command("DOM.describeNode", Some(json!({ "nodeId": node_id }))).await.map_err(in_frame)?;
+        let node = described.get("node").unwrap_or(&Value::Null);
+        let is_frame = node
+            .get("nodeName")
+            .and_then(|n| n.as_str())
+            .is_some_and(|n| n.eq_ignore_ascii_case("iframe") || n.eq_ignore_ascii_case("frame"));
+        let frame_id = node.get("frameId").and_then(|f| f.as_str()).filter(|_| is_frame).ok_or_else(|| {
+            in_frame(ChromeMcpError::invalid_operation(format!("Element is not an iframe: {}", selector)))
+        })?;
+
+        let world_params = json!({
+            "frameId": frame_id,
+            "worldName": FRAME_WORLD_NAME,
+            "grantUniveralAccess": true
+        });
+        let (world, session_id) = match self.cdp.send_command("Page.createIsolatedWorld", Some(world_params.clone())).await {
+            Ok(world) => (world, None),
+            // Cross-origin iframes are out of process: the page's session does not know their
+            // frame, which is the main frame of a target of its own
+            Err(error) => {
+                let session_id = self.cdp.attach_to_target(frame_id).await.map_err(|_| {
+                    in_frame(ChromeMcpError::invalid_operation(format!(
+                        "Cannot switch to frame {}: it is neither in the page's process nor an attachable cross-origin frame ({})",
+                        selector,
+                        error.root_cause()
+                    )))
+                })?;
+                let world = self.cdp.send_session_command(&session_id, "Page.createIsolatedWorld", Some(world_params)).await.map_err(in_frame)?;
+                (world, Some(session_id))
+            }
+        };
+        let context_id = world.get("executionContextId").and_then(|c| c.as_u64()).ok_or_else(|| {
+            in_frame(ChromeMcpError::cdp_protocol("No executionContextId in Page.createIsolatedWorld response"))
+        })?;
+
+        let frame = FrameContext { selector: selector.to_string(), frame_id: frame_id.to_string(), context_id, session_id };
+        self.frame = Some(frame.clone());
+        Ok(frame)
+    }
+
+    /// Go back to running scripts and input in the top-level document
+    pub fn switch_to_main_frame(&mut self) {
+        debug!("Switching to main frame");
+        self.frame = None;
+    }
+
+    /// Iframe selected with `switch_to_frame`, if any
+    pub fn current_frame(&self) -> Option<&FrameContext> {
+        self.frame.as_ref()
+    }
+
+    /// Click the element matching `selector` in the current iframe, at its center translated
+    /// from the iframe's viewport to the page's
+    async fn click_in_frame(&mut self, frame: &FrameContext, selector: &str) -> Result<()> {
+        let in_frame = |e: ChromeMcpError| e.in_frame("browser", "click_in_frame").with_context("selector", selector).with_context("frame", frame.frame_id.as_str());
+
+        let rect = self.evaluate_in_frame(frame, &FRAME_ELEMENT_RECT_JS.replace("{selector}", &json!(selector).to_string())).await.map_err(in_frame)?;
+        let rect: PositionSample = match rect.get("value") {
+            Some(value) if !value.is_null() => serde_json::from_value(value.clone())?,
+            _ => return Err(in_frame(ChromeMcpError::element_not_found(format!("No element found in frame for selector: {}", selector)))),
+        };
+
+        let offset = self.cdp.evaluate_js(&FRAME_CONTENT_OFFSET_JS.replace("{selector}", &json!(frame.selector).to_string())).await.map_err(in_frame)?;
+        let (offset_x, offset_y) = match offset.get("value") {
+            Some(value) if !value.is_null() => (value["x"].as_f64().unwrap_or(0.0), value["y"].as_f64().unwrap_or(0.0)),
+            _ => return Err(in_frame(ChromeMcpError::element_not_found(format!("Frame element is gone: {}", frame.selector)))),
+        };
+
+        self.cdp.click_at(offset_x + rect.x + rect.width / 2.0, offset_y + rect.y + rect.height / 2.0).await
+    }
+
+    /// Evaluate `expressions` one after the other, waiting `delay_ms` between them, e.g. for
+    /// pages that rate-limit the operations they trigger
+    pub async fn evaluate_throttled(&mut self, expressions: Vec<String>, delay_ms: u64) -> Result<Vec<Value>> {
+        debug!("Evaluating {} expressions {}ms apart", expressions.len(), delay_ms);
+
+        let mut results = Vec::with_capacity(expressions.len());
+        for (index, expression) in expressions.iter().enumerate() {
+            if index > 0 {
+                sleep(Duration::from_millis(delay_ms)).await;
+            }
+            let result = self.cdp.evaluate_js(expression)
+                .await
+                .map_err(|e| e.in_frame("browser", "evaluate_throttled").with_context("index", index))?;
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Evaluate `expressions` with up to `concurrency` of them in flight at once; results are
+    /// in the order of `expressions`
+    pub async fn evaluate_batch_parallel(&mut self, expressions: Vec<String>, concurrency: usize) -> Result<Vec<Value>> {
+        debug!("Evaluating {} expressions, {} at a time", expressions.len(), concurrency);
+
+        let semaphore = Rc::new(Semaphore::new(concurrency.max(1)));
+        // Same as `map_tabs`: CDP round trips sharing this thread
+        LocalSet::new().run_until(async {
+            let mut tasks = JoinSet::new();
+            for (index, expression) in expressions.into_iter().enumerate() {
+                let mut cdp = self.cdp.clone();
+                let semaphore = Rc::clone(&semaphore);
+                tasks.spawn_local(async move {
+                    let _permit = semaphore.acquire().await;
+                    let result = cdp.evaluate_js(&expression)
+                        .await
+                        .map_err(|e| e.in_frame("browser", "evaluate_batch_parallel").with_context("index", index));
+                    (index, result)
+                });
+            }
+
+            let mut results = Vec::with_capacity(tasks.len());
+            while let Some(joined) = tasks.join_next().await {
+                let (index, result) = joined.map_err(|e| ChromeMcpError::invalid_operation(format!("Evaluation task failed: {}", e)))?;
+                results.push((index, result?));
+            }
+            results.sort_by_key(|(index, _)| *index);
+            Ok(results.into_iter().map(|(_, result)| result).collect())
+        }).await
+    }
+
+    /// Scroll the page
+    pub async fn scroll(&mut self, x: i32, y: i32) -> Result<()> {
+        debug!("Scrolling by ({}, {})", x, y);
+        self.cdp.evaluate_typed(EvaluateRequest::new(format!("window.scrollBy({}, {})", x, y))).await?;
+        Ok(())
+    }
+
+    /// Scroll the page to an absolute position
+    pub async fn scroll_to(&mut self, x: i32, y: i32) -> Result<()> {
+        debug!("Scrolling to ({}, {})", x, y);
+        self.cdp.evaluate_js(&format!("window.scrollTo({}, {})", x, y)).await?;
+        Ok(())
+    }
+
+    /// Current scroll position of the page, to restore later with `restore_scroll`
+    pub async fn remember_scroll(&mut self) -> Result<(i32, i32)> {
+        let result = self.cdp.evaluate_js("[Math.round(window.scrollX), Math.round(window.scrollY)]").await?;
+        let (x, y): (i32, i32) = serde_json::from_value(result.get("value").cloned().unwrap_or(json!([0, 0])))?;
+        Ok((x, y))
+    }
+
+    /// Scroll back to a position returned by `remember_scroll`
+    pub async fn restore_scroll(&mut self, pos: (i32, i32)) -> Result<()> {
+        self.scroll_to(pos.0, pos.1).await
+    }
+
+    /// Scroll to element
+    pub async fn scroll_to_element(&mut self, selector: &str) -> Result<()> {
+        debug!("Scrolling to element: {}", selector);
+        self.cdp.evaluate_typed(EvaluateRequest::new(format!(
+            "document.querySelector('{}').scrollIntoView({{ behavior: 'smooth', block: 'center' }})",
+            selector.replace("'", "\\'")
+        ))).await?;
+        Ok(())
+    }
+
+    /// Scroll a `scroll-snap` container by one page so it lands on the adjacent snap point.
+    /// On a single-axis container, Right/Down both mean "next" and Left/Up both mean "previous".
+    pub async fn scroll_to_next_snap(&mut self, container_selector: &str, direction: ScrollDirection) -> Result<()> {
+        debug!("Scrolling {:?} to next snap point in: {}", direction, container_selector);
+
+        let escaped = container_selector.replace("'", "\\'");
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const container = document.querySelector('{}');
+                if (!container) return null;
+                const type = getComputedStyle(container).scrollSnapType;
+                if (!type || type === 'none') return 'none';
+                if (type.includes('both')) return 'both';
+                return type.includes('y') || type.includes('block') ? 'y' : 'x';
+            }})()
+            "#,
+            escaped
+        )).await?;
+
+        let snap_axis = match result.get("value").and_then(|v| v.as_str()) {
+            Some("none") => return Err(ChromeMcpError::invalid_operation(format!(
+                "Element is not a scroll-snap container: {}", container_selector
+            ))),
+            Some(axis) => axis.to_string(),
+            None => return Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", container_selector))),
+        };
+
+        let (axis, sign) = snap_scroll_axis(direction, &snap_axis);
+        let options = if axis == "x" {
+            format!("{{ left: {} * container.offsetWidth, behavior: 'smooth' }}", sign)
+        } else {
+            format!("{{ top: {} * container.offsetHeight, behavior: 'smooth' }}", sign)
+        };
+
+        self.cdp.evaluate_js(&format!(
+            "(() => {{ const container = document.querySelector('{}'); container.scrollBy({}); }})()",
+            escaped,
+            options
+        )).await?;
+
+        Ok(())
+    }
+
+    /// Find the snap child currently aligned in a `scroll-snap` container
+    pub async fn get_active_snap_item(&mut self, container_selector: &str) -> Result<ElementRef> {
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const cssPath = {};
+                const container = document.querySelector('{}');
+                if (!container) return null;
+                const box = container.getBoundingClientRect();
+                const items = Array.from(container.children)
+                    .filter(c => getComputedStyle(c).scrollSnapAlign !== 'none');
+                let best = null;
+                let bestDistance = Infinity;
+                items.forEach((item, index) => {{
+                    const rect = item.getBoundingClientRect();
+                    const distance = Math.abs(rect.left - box.left) + Math.abs(rect.top - box.top);
+                    if (distance < bestDistance) {{
+                        bestDistance = distance;
+                        best = {{
+                            id: 'snap-' + index,
+                            selector: cssPath(item),
+                            accessibility_id: null,
+                            bounds: [rect.x, rect.y, rect.width, rect.height],
+                            text: (item.innerText || '').trim().slice(0, 200) || null,
+                            role: item.getAttribute('role')
+                        }};
+                    }}
+                }});
+                return best || 'empty';
+            }})()
+            "#,
+            CSS_PATH_JS,
+            container_selector.replace("'", "\\'")
+        )).await?;
+
+        match result.get("value") {
+            Some(Value::String(_)) => Err(ChromeMcpError::element_not_found(format!("No snap items found in: {}", container_selector))),
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value.clone())?),
+            _ => Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", container_selector))),
+        }
+    }
+
+    /// Hover over an element
+    pub async fn hover(&mut self, selector_or_text: &str) -> Result<()> {
+        debug!("Hovering over: {}", selector_or_text);
+
+        let element_ref = self.find_element_any_strategy(selector_or_text).await?;
+        let bounds = match (element_ref.bounds, &element_ref.selector) {
+            (Some(bounds), _) => Some(bounds),
+            // CSS selector matches carry no bounds; read them from the page
+            (None, Some(selector)) => {
+                let rect = self.get_element_bounds(selector).await?;
+                Some((rect.x, rect.y, rect.width, rect.height))
+            }
+            (None, None) => None,
+        };
+
+        if let Some((x, y, width, height)) = bounds {
+            self.cdp.send_command("Input.dispatchMouseEvent", Some(json!({
+                "type": "mouseMoved",
+                "x": x + width / 2.0,
+                "y": y + height / 2.0
+            }))).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Drag from `source` to `target` with the left mouse button, moving over `duration_ms`,
+    /// then fire the HTML5 drag and drop events for pages listening to those instead
+    pub async fn drag_and_drop(&mut self, source: &DragEndpoint, target: &DragEndpoint, duration_ms: u64) -> Result<()> {
+        debug!("Dragging {:?} to {:?} over {}ms", source, target, duration_ms);
+
+        let (from_x, from_y) = self.drag_endpoint_point(source).await?;
+        let (to_x, to_y) = self.drag_endpoint_point(target).await?;
+        let in_frame = |e: ChromeMcpError| e.in_frame("browser", "drag_and_drop");
+        let mouse_event = |kind: &str, x: f64, y: f64| json!({
+            "type": kind,
+            "x": x,
+            "y": y,
+            "button": "left",
+            "buttons": 1,
+            "clickCount": 1
+        });
+
+        self.cdp.send_command("Input.dispatchMouseEvent", Some(mouse_event("mousePressed", from_x, from_y))).await.map_err(in_frame)?;
+        for step in 1..=DRAG_STEPS {
+            sleep(Duration::from_millis(duration_ms / DRAG_STEPS as u64)).await;
+            let progress = step as f64 / DRAG_STEPS as f64;
+            let (x, y) = (from_x + (to_x - from_x) * progress, from_y + (to_y - from_y) * progress);
+            self.cdp.send_command("Input.dispatchMouseEvent", Some(mouse_event("mouseMoved", x, y))).await.map_err(in_frame)?;
+        }
+        self.cdp.send_command("Input.dispatchMouseEvent", Some(mouse_event("mouseReleased", to_x, to_y))).await.map_err(in_frame)?;
+
+        let result = self.cdp.evaluate_js(
+            &DRAG_EVENTS_JS
+                .replace("{source}", &json!({ "x": from_x, "y": from_y }).to_string())
+                .replace("{target}", &json!({ "x": to_x, "y": to_y }).to_string()),
+        ).await.map_err(in_frame)?;
+        debug!("HTML5 drag events fired: {}", result.get("value").and_then(|v| v.as_bool()).unwrap_or(false));
+
+        Ok(())
+    }
+
+    /// Viewport point of a drag endpoint: the center of an element, or the point itself
+    async fn drag_endpoint_point(&mut self, endpoint: &DragEndpoint) -> Result<(f64, f64)> {
+        match endpoint {
+            DragEndpoint::Point(x, y) => Ok((*x, *y)),
+            DragEndpoint::Element(query) => self.element_center(query).await,
+        }
+    }
+
+    /// Viewport center of the element found for `query` by selector, text or role
+    async fn element_center(&mut self, query: &str) -> Result<(f64, f64)> {
+        let element_ref = self.find_element_any_strategy(query).await?;
+        let (x, y, width, height) = match (element_ref.bounds, &element_ref.selector) {
+            (Some(bounds), _) => bounds,
+            // CSS selector matches carry no bounds; read them from the page
+            (None, Some(selector)) => {
+                let rect = self.get_element_bounds(selector).await?;
+                (rect.x, rect.y, rect.width, rect.height)
+            }
+            (None, None) => {
+                return Err(ChromeMcpError::element_not_found(format!("No position for element: {}", query)))
+            }
+        };
+        Ok((x + width / 2.0, y + height / 2.0))
+    }
+
+    /// Right-click an element, found the same way as by `click`, to open its context menu
+    pub async fn right_click(&mut self, target: &str) -> Result<()> {
+        debug!("Right-clicking: {}", target);
+        let (x, y) = self.element_center(target).await.map_err(|e| e.in_frame("browser", "right_click"))?;
+        self.cdp.mouse_click(x, y, "right", 1).await
+    }
+
+    /// Double-click an element, found the same way as by `click`, waiting
+    /// `DOUBLE_CLICK_DELAY_MS` between the two clicks
+    pub async fn double_click(&mut self, target: &str) -> Result<()> {
+        self.double_click_with_delay(target, DOUBLE_CLICK_DELAY_MS).await
+    }
+
+    /// Double-click an element with `delay_ms` between the clicks; the second click carries
+    /// `clickCount: 2`, which is what fires `dblclick`
+    pub async fn double_click_with_delay(&mut self, target: &str, delay_ms: u64) -> Result<()> {
+        debug!("Double-clicking: {} ({}ms apart)", target, delay_ms);
+        let (x, y) = self.element_center(target).await.map_err(|e| e.in_frame("browser", "double_click"))?;
+        self.cdp.mouse_click(x, y, "left", 1).await?;
+        sleep(Duration::from_millis(delay_ms)).await;
+        self.cdp.mouse_click(x, y, "left", 2).await
+    }
+
+    /// Click the item named `name` of the context menu the page opened, e.g. after `right_click`.
+    /// Only menus built from page elements with menu item roles can be selected, not Chrome's own.
+    pub async fn select_context_menu_item(&mut self, name: &str) -> Result<()> {
+        let item = self
+            .wait_for_menu_item(name)
+            .await
+            .map_err(|e| e.in_frame("browser", "select_context_menu_item"))?;
+        self.click_element_ref(&element_ref_from_node(&item), None).await
+    }
+
+    /// Open the menu behind `trigger_selector` by hovering it, then follow `item_path`: each
+    /// entry names a visible menu item (exact accessible name first, then substring), which is
+    /// hovered to reveal its submenu, and the last one is clicked. If an item does not show up,
+    /// the open menus are dismissed with Escape and the error lists the items that were visible.
+    pub async fn navigate_menu(&mut self, trigger_selector: &str, item_path: Vec<&str>) -> Result<()> {
+        if item_path.is_empty() {
+            return Err(ChromeMcpError::invalid_operation("Menu path is empty"));
+        }
+        debug!("Navigating menu {} > {}", trigger_selector, item_path.join(" > "));
+
+        self.hover(trigger_selector).await?;
+
+        for (level, name) in item_path.iter().enumerate() {
+            let item = self.wait_for_menu_item(name).await.map_err(|e| {
+                e.in_frame("browser", "navigate_menu").with_context("path", json!(item_path[..=level]))
+            })?;
+
+            let element_ref = element_ref_from_node(&item);
+            if level + 1 == item_path.len() {
+                self.click_element_ref(&element_ref, None).await?;
+            } else if let Some((x, y, width, height)) = element_ref.bounds {
+                self.cdp.send_command("Input.dispatchMouseEvent", Some(json!({
+                    "type": "mouseMoved",
+                    "x": x + width / 2.0,
+                    "y": y + height / 2.0
+                }))).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Wait for a visible menu item named `name` (exact accessible name first, then substring).
+    /// If it does not show up, the open menus are dismissed with Escape and the error lists
+    /// the items that were visible.
+    async fn wait_for_menu_item(&mut self, name: &str) -> Result<AccessibilityNode> {
+        let mut visible = Vec::new();
+        for attempt in 0..MENU_ITEM_ATTEMPTS {
+            if attempt > 0 {
+                // Menus usually open with a transition
+                sleep(Duration::from_millis(100)).await;
+            }
+            visible = self.visible_menu_items().await?;
+            if let Some(item) = match_menu_item(&visible, name) {
+                return Ok(item.clone());
+            }
+        }
+
+        if let Err(e) = self.press_escape().await {
+            warn!("Could not dismiss menu: {}", e);
+        }
+        let available: Vec<String> = visible.iter().filter_map(|node| node.name.clone()).collect();
+        Err(ChromeMcpError::element_not_found(format!("Menu item not found: {}", name))
+            .in_frame("browser", "wait_for_menu_item")
+            .with_context("available", json!(available)))
+    }
+
+    /// Menu items (`menuitem`, `menuitemcheckbox`, `menuitemradio`) currently laid out on the page
+    async fn visible_menu_items(&mut self) -> Result<Vec<AccessibilityNode>> {
+        self.accessibility.clear_cache();
+        let items = self.accessibility.find_by_role("menuitem").await?;
+        Ok(items
+            .into_iter()
+            .filter(|node| node.bounds.as_ref().is_some_and(|b| b.width > 0.0 && b.height > 0.0))
+            .collect())
+    }
+
+    async fn press_escape(&mut self) -> Result<()> {
+        self.press_shortcut("Escape").await
+    }
+
+    /// Press a key combination such as `Ctrl+C`, `Cmd+Shift+T` or `Enter`: a `keyDown` then a
+    /// `keyUp` event with the modifiers held
+    pub async fn press_shortcut(&mut self, shortcut: &str) -> Result<()> {
+        let shortcut = KeyShortcut::parse(shortcut)?;
+        debug!("Pressing {:?}", shortcut);
+
+        for event_type in ["keyDown", "keyUp"] {
+            let mut params = json!({
+                "type": event_type,
+                "modifiers": shortcut.modifiers,
+                "key": shortcut.key,
+                "code": shortcut.code,
+                "windowsVirtualKeyCode": shortcut.windows_virtual_key_code
+            });
+            if let (Some(text), "keyDown") = (shortcut.text(), event_type) {
+                params["text"] = json!(text);
+            }
+            self.cdp.send_command("Input.dispatchKeyEvent", Some(params)).await?;
+        }
+        Ok(())
+    }
+
+    /// Select option from dropdown
+    pub async fn select_option(&mut self, selector: &str, option_value: &str) -> Result<()> {
+        debug!("Selecting option '{}' in element: {}", option_value, selector);
+        
+        self.cdp.evaluate_typed(EvaluateRequest::new(format!(
+            r#"
+            const select = document.querySelector('{}');
+            if (select) {{
+                select.value = '{}';
+                select.dispatchEvent(new Event('change', {{ bubbles: true }}));
+            }} else {{
+                throw new Error('Select element not found');
+            }}
+            "#,
+            selector.replace("'", "\\'"),
+            option_value.replace("'", "\\'")
+        ))).await?;
+        
+        Ok(())
+    }
+
+    /// Put a local file into the `<input type="file">` matching `selector`, as if picked in
+    /// the file dialog
+    pub async fn upload_file(&mut self, selector: &str, file_path: &str) -> Result<()> {
+        self.upload_files(selector, &[file_path.to_string()]).await
+    }
+
+    /// Put local files into the `<input type="file">` matching `selector`; more than one
+    /// needs a `multiple` input
+    pub async fn upload_files(&mut self, selector: &str, files: &[String]) -> Result<()> {
+        debug!("Uploading {:?} through {}", files, selector);
+        let in_frame = |e: ChromeMcpError| e.in_frame("browser", "upload_files").with_context("selector", selector);
+
+        if files.is_empty() {
+            return Err(in_frame(ChromeMcpError::invalid_operation("No files to upload")));
+        }
+        for file in files {
+            if !Path::new(file).is_absolute() {
+                return Err(in_frame(ChromeMcpError::invalid_operation(format!("File path must be absolute: {}", file))));
+            }
+            if !Path::new(file).exists() {
+                // Chrome may run on another machine than chrome-mcp
+                warn!("Upload file not found locally: {}", file);
+            }
+        }
+
+        let node_id = *self.cdp.query_selector_all(selector).await.map_err(in_frame)?.node_ids.first().ok_or_else(|| {
+            in_frame(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector)))
+        })?;
+
+        let described = self.cdp.send_command("DOM.describeNode", Some(json!({ "nodeId": node_id }))).await.map_err(in_frame)?;
+        let node = described.get("node").unwrap_or(&Value::Null);
+        let attributes = node_attributes(node);
+        let is_file_input = node.get("nodeName").and_then(|n| n.as_str()).is_some_and(|n| n.eq_ignore_ascii_case("input"))
+            && attributes.get("type").is_some_and(|t| t.eq_ignore_ascii_case("file"));
+        if !is_file_input {
+            return Err(in_frame(ChromeMcpError::invalid_operation(format!("Element is not a file input: {}", selector))));
+        }
+        if files.len() > 1 && !attributes.contains_key("multiple") {
+            return Err(in_frame(ChromeMcpError::invalid_operation(format!(
+                "File input does not accept multiple files: {}",
+                selector
+            ))));
+        }
+
+        self.cdp.send_command("DOM.setFileInputFiles", Some(json!({
+            "files": files,
+            "nodeId": node_id
+        }))).await.map_err(in_frame)?;
+
+        Ok(())
+    }
+
+    /// Get the checked/disabled/focus/validity state of an element
+    pub async fn get_element_state(&mut self, selector: &str) -> Result<ElementState> {
+        debug!("Getting element state: {}", selector);
+
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const el = document.querySelector('{}');
+                if (!el) return null;
+                return {{
+                    checked: !!el.checked,
+                    disabled: !!el.disabled,
+                    read_only: !!el.readOnly,
+                    required: !!el.required,
+                    value: 'value' in el && el.value != null ? String(el.value) : null,
+                    valid: el.validity ? el.validity.valid : true,
+                    validation_message: el.validationMessage || '',
+                    focused: document.activeElement === el,
+                    indeterminate: !!el.indeterminate,
+                    visibility_ratio: ({})(el)
+                }};
+            }})()
+            "#,
+            selector.replace("'", "\\'"),
+            VISIBILITY_RATIO_JS
+        )).await?;
+
+        match result.get("value") {
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value.clone())?),
+            _ => Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector))),
+        }
+    }
+
+    /// Fraction of an element inside the viewport, from 0.0 (hidden) to 1.0 (fully visible)
+    pub async fn get_visibility_ratio(&mut self, selector: &str) -> Result<f64> {
+        let result = self.cdp.evaluate_js(&format!(
+            "(() => {{ const el = document.querySelector('{}'); return el ? ({})(el) : null; }})()",
+            selector.replace("'", "\\'"),
+            VISIBILITY_RATIO_JS
+        )).await?;
+
+        result
+            .get("value")
+            .and_then(|v| v.as_f64())
+            .ok_or_else(|| ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector)))
+    }
+
+    /// Collect the constraint validation errors of every invalid field in a form
+    pub async fn get_form_validation_errors(&mut self, form_selector: &str) -> Result<Vec<ValidationError>> {
+        debug!("Collecting validation errors for form: {}", form_selector);
+
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const cssPath = {};
+                const form = document.querySelector('{}');
+                if (!form || !form.elements) return null;
+                const flags = ['valueMissing', 'typeMismatch', 'patternMismatch', 'tooLong', 'tooShort',
+                    'rangeUnderflow', 'rangeOverflow', 'stepMismatch', 'badInput', 'customError'];
+                return Array.from(form.elements)
+                    .filter(field => field.willValidate && field.validity && !field.validity.valid)
+                    .map(field => {{
+                        const validity_state = {{}};
+                        flags.forEach(flag => validity_state[flag] = !!field.validity[flag]);
+                        return {{
+                            selector: cssPath(field),
+                            field_name: field.name || field.id || null,
+                            message: field.validationMessage,
+                            validity_state
+                        }};
+                    }});
+            }})()
+            "#,
+            CSS_PATH_JS,
+            form_selector.replace("'", "\\'")
+        )).await?;
+
+        match result.get("value") {
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value.clone())?),
+            _ => Err(ChromeMcpError::element_not_found(format!("No form found for selector: {}", form_selector))),
+        }
+    }
+
+    /// Run `form.reportValidity()`, showing the browser's validation bubbles.
+    /// Returns whether the form is valid.
+    pub async fn trigger_form_validation(&mut self, form_selector: &str) -> Result<bool> {
+        let result = self.cdp.evaluate_js(&format!(
+            "(() => {{ const form = document.querySelector('{}'); return form && form.reportValidity ? form.reportValidity() : null; }})()",
+            form_selector.replace("'", "\\'")
+        )).await?;
+
+        result
+            .get("value")
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| ChromeMcpError::element_not_found(format!("No form found for selector: {}", form_selector)))
+    }
+
+    /// Check that every non-hidden `<input>` of a form has a label screen readers can announce:
+    /// an associated `<label for>`, a wrapping `<label>`, `aria-labelledby`, `aria-label` or `title`
+    pub async fn verify_form_labels(&mut self, form_selector: &str) -> Result<Vec<LabelIssue>> {
+        debug!("Auditing labels of form: {}", form_selector);
+
+        let script = FORM_LABELS_JS
+            .replace("{css_path}", CSS_PATH_JS.trim())
+            .replace("{form_selector}", &serde_json::to_string(form_selector)?);
+        let result = self.cdp.evaluate_js(&script).await?;
+
+        match result.get("value") {
+            Some(value) if !value.is_null() => {
+                let unlabeled: Vec<UnlabeledInput> = serde_json::from_value(value.clone())?;
+                Ok(unlabeled.into_iter().map(label_issue).collect())
+            }
+            _ => Err(ChromeMcpError::element_not_found(format!("No form found for selector: {}", form_selector))),
+        }
+    }
+
+    /// Fill a form field with `iterations` generated inputs, submitting its form after each one,
+    /// and record uncaught exceptions and navigations. The page is navigated back to the
+    /// starting URL whenever a submission leaves it.
+    pub async fn fuzz_input(&mut self, selector: &str, strategy: FuzzStrategy, iterations: u32) -> Result<Vec<FuzzResult>> {
+        info!("Fuzzing {} with {:?} inputs ({} iterations)", selector, strategy, iterations);
+
+        let start_url = self.current_url().await?;
+        let selector_js = serde_json::to_string(selector)?;
+
+        let max_length = self.cdp.evaluate_js(&format!(
+            "(() => {{ const el = document.querySelector({}); return el ? el.maxLength : null; }})()",
+            selector_js
+        )).await?;
+        let max_length = match max_length.get("value") {
+            Some(value) if !value.is_null() => value.as_i64().filter(|l| *l >= 0).map(|l| l as usize),
+            _ => return Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector))),
+        };
+
+        self.ensure_js_error_capture().await?;
+
+        let mut results = Vec::new();
+        for input in fuzz_inputs(&strategy, iterations, max_length, uuid::Uuid::new_v4().as_u128() as u64) {
+            let script = FUZZ_SUBMIT_JS
+                .replace("{selector}", &selector_js)
+                .replace("{value}", &serde_json::to_string(&input)?);
+            let submitted = self.cdp.evaluate_js(&script).await?;
+            if submitted.get("value").is_none_or(|v| v.is_null()) {
+                return Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector)));
+            }
+
+            sleep(Duration::from_millis(FUZZ_SETTLE_MS)).await;
+
+            let url_changed = self.current_url().await? != start_url;
+            let js_errors = self.get_js_errors().await?;
+            let status = if !js_errors.is_empty() {
+                FuzzStatus::JsErrors
+            } else if url_changed {
+                FuzzStatus::Navigated
+            } else {
+                FuzzStatus::Passed
+            };
+
+            results.push(FuzzResult { input, js_errors, url_changed, status });
+
+            if url_changed {
+                self.navigate(&start_url).await?;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Start capturing uncaught exceptions (without a budget) unless already capturing
+    async fn ensure_js_error_capture(&mut self) -> Result<()> {
+        if self.js_error_capture_script.is_some() {
+            return Ok(());
+        }
+
+        let script = JS_ERROR_CAPTURE_JS.replace("{capacity}", &MIN_JS_ERROR_BUFFER_CAPACITY.to_string());
+        self.js_error_capture_script = Some(self.add_init_script(&script).await?);
+        self.cdp.evaluate_js(&script).await?;
+        Ok(())
+    }
+
+    /// WebAssembly modules the page instantiated, with their exports. Modules are tracked from
+    /// the first call on, in the current and all later documents, so reload the page to include
+    /// the ones it instantiates while loading.
+    pub async fn get_wasm_modules(&mut self) -> Result<Vec<WasmModuleInfo>> {
+        self.ensure_wasm_tracking().await?;
+
+        let result = self.cdp.evaluate_js(WASM_MODULES_JS).await?;
+        Ok(serde_json::from_value(result.get("value").cloned().unwrap_or(json!([])))?)
+    }
+
+    /// Call the exported function `export` of a tracked WebAssembly module (the first one
+    /// exporting it, or the one streamed from `module`) with `args`
+    pub async fn call_wasm_export(&mut self, module: Option<&str>, export: &str, args: Vec<Value>) -> Result<Value> {
+        debug!("Calling WebAssembly export {} with {} argument(s)", export, args.len());
+        self.ensure_wasm_tracking().await?;
+
+        let script = WASM_CALL_JS
+            .replace("{module}", &serde_json::to_string(&module)?)
+            .replace("{args}", &serde_json::to_string(&args)?)
+            .replace("{export}", &serde_json::to_string(export)?);
+        let result = self.cdp.evaluate_js(&script).await?;
+        let value = result.get("value").cloned().unwrap_or_default();
+
+        if value.get("found").and_then(|f| f.as_bool()) != Some(true) {
+            return Err(ChromeMcpError::invalid_operation(format!("No tracked WebAssembly module exports a function named {}", export)));
+        }
+        Ok(value.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Start tracking WebAssembly instantiation unless already tracking
+    async fn ensure_wasm_tracking(&mut self) -> Result<()> {
+        if self.injected_scripts.iter().any(|(_, source)| source == WASM_TRACKER_JS) {
+            return Ok(());
+        }
+
+        self.add_init_script(WASM_TRACKER_JS).await?;
+        self.cdp.evaluate_js(WASM_TRACKER_JS).await?;
+        Ok(())
+    }
+
+    /// Check or uncheck a checkbox (or check a radio button)
+    pub async fn set_checked(&mut self, selector: &str, checked: bool) -> Result<()> {
+        debug!("Setting checked={} on element: {}", checked, selector);
+
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const el = document.querySelector('{}');
+                if (!el) return 'missing';
+                if (el.type !== 'checkbox' && el.type !== 'radio') return 'unsupported';
+                if (el.checked !== {}) {{
+                    el.checked = {};
+                    el.indeterminate = false;
+                    el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                    el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                }}
+                return 'ok';
+            }})()
+            "#,
+            selector.replace("'", "\\'"),
+            checked,
+            checked
+        )).await?;
+
+        match result.get("value").and_then(|v| v.as_str()) {
+            Some("ok") => Ok(()),
+            Some("unsupported") => Err(ChromeMcpError::invalid_operation(format!("Element is not a checkbox or radio button: {}", selector))),
+            _ => Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector))),
+        }
+    }
+
+    /// Wait for a condition to be met
+    pub async fn wait_for_condition(&mut self, condition: WaitCondition, timeout_ms: u64) -> Result<()> {
+        debug!("Waiting for condition: {:?} (timeout: {}ms)", condition, timeout_ms);
+
+        // Request conditions follow the network events arriving while waiting
+        let mut network_events = match &condition {
+            WaitCondition::NetworkIdle { .. } | WaitCondition::RequestCompleted { .. } | WaitCondition::RequestFailed { .. } => {
+                NETWORK_EVENT_METHODS.iter().map(|method| (*method, self.cdp.subscribe(method))).collect()
+            }
+            _ => Vec::new(),
+        };
+        let mut in_flight: HashSet<String> = HashSet::new();
+        let mut last_activity = Instant::now();
+
+        let result = timeout(Duration::from_millis(timeout_ms), async {
+            loop {
+                // Requests are drained before their completions, so none is counted as finished first
+                for (method, events) in network_events.iter_mut() {
+                    while let Ok(params) = events.try_recv() {
+                        self.handle_network_event(method, &params);
+                        last_activity = Instant::now();
+                        let request_id = params.get("requestId").and_then(|id| id.as_str()).unwrap_or_default();
+                        match *method {
+                            "Network.requestWillBeSent" => {
+                                in_flight.insert(request_id.to_string());
+                            }
+                            "Network.loadingFinished" | "Network.loadingFailed" => {
+                                in_flight.remove(request_id);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                match &condition {
+                    WaitCondition::ElementPresent(selector) => {
+                        if self.find_element_by_selector(selector).await.is_ok() {
+                            break;
+                        }
+                    }
+                    WaitCondition::ElementVisible(selector) => {
+                        if self.is_element_visible(selector).await? {
+                            break;
+                        }
+                    }
+                    WaitCondition::ElementVisibleRatio(selector, min_ratio) => {
+                        if self.get_visibility_ratio(selector).await.unwrap_or(0.0) >= *min_ratio {
+                            break;
+                        }
+                    }
+                    WaitCondition::ElementClickable(selector) => {
+                        if self.is_element_clickable(selector).await? {
+                            break;
+                        }
+                    }
+                    WaitCondition::TextPresent(text) => {
+                        if self.is_text_present(text).await? {
+                            break;
+                        }
+                    }
+                    WaitCondition::UrlMatches(pattern) => {
+                        if self.current_url().await?.contains(pattern) {
+                            break;
+                        }
+                    }
+                    WaitCondition::UrlContains(text) => {
+                        if self.current_url().await?.contains(text) {
+                            break;
+                        }
+                    }
+                    WaitCondition::TitleContains(text) => {
+                        if self.page_title().await?.contains(text.as_str()) {
+                            break;
+                        }
+                    }
+                    WaitCondition::TitleEquals(text) => {
+                        if self.page_title().await? == *text {
+                            break;
+                        }
+                    }
+                    WaitCondition::TitleMatches(pattern) => {
+                        if title_matches(pattern, &self.page_title().await?) {
+                            break;
+                        }
+                    }
+                    WaitCondition::TitleChangedFrom(original) => {
+                        if self.page_title().await? != *original {
+                            break;
+                        }
+                    }
+                    WaitCondition::PageLoad => {
+                        let ready_state = self.cdp.evaluate_typed(EvaluateRequest::new("document.readyState")).await?;
+
+                        if let Some(state) = ready_state.value().and_then(|v| v.as_str()) {
+                            if state == "complete" {
+                                break;
+                            }
+                        }
+                    }
+                    WaitCondition::RequestCompleted { url_pattern, method } => {
+                        let completed = self.network_events.lock().unwrap().iter().any(|event| {
+                            event.finished
+                                && url_pattern_matches(url_pattern, &event.url)
+                                && method.as_ref().is_none_or(|m| m.eq_ignore_ascii_case(&event.method))
+                        });
+                        if completed {
+                            break;
+                        }
+                    }
+                    WaitCondition::RequestFailed { url_pattern } => {
+                        let failed = self.network_events.lock().unwrap().iter().any(|event| {
+                            event.error_text.is_some() && url_pattern_matches(url_pattern, &event.url)
+                        });
+                        if failed {
+                            break;
+                        }
+                    }
+                    WaitCondition::NetworkIdle { idle_ms, max_inflight } => {
+                        if in_flight.len() <= *max_inflight && last_activity.elapsed() >= Duration::from_millis(*idle_ms) {
+                            break;
+                        }
+                    }
+                }
+
+                sleep(Duration::from_millis(100)).await;
+            }
+            Ok::<(), ChromeMcpError>(())
+        }).await;
+
+        match result {
+            Ok(_) => {
+                debug!("Wait condition satisfied");
+                Ok(())
+            }
+            Err(_) => Err(ChromeMcpError::Timeout { timeout: timeout_ms }),
+        }
+    }
+
+    /// Text of the element matching `selector`: its rendered `innerText`, or its `textContent`
+    /// including hidden descendants with `include_hidden`
+    pub async fn get_element_text(&mut self, selector: &str, include_hidden: bool) -> Result<String> {
+        let value = self.evaluate_on_elements(selector, false, text_property(include_hidden)).await?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
+    /// Text of every element matching `selector`, in document order, see `get_element_text`
+    pub async fn get_elements_text(&mut self, selector: &str, include_hidden: bool) -> Result<Vec<String>> {
+        let value = self.evaluate_on_elements(selector, true, text_property(include_hidden)).await?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// `outerHTML` of the element matching `selector`, or its `innerHTML` unless `outer`
+    pub async fn get_element_html(&mut self, selector: &str, outer: bool) -> Result<String> {
+        let property = if outer { "el.outerHTML" } else { "el.innerHTML" };
+        let value = self.evaluate_on_elements(selector, false, property).await?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
+    /// Value of an attribute of the element matching `selector`, `None` if the element lacks it
+    pub async fn get_element_attribute(&mut self, selector: &str, attribute: &str) -> Result<Option<String>> {
+        // Wrapped so that a missing attribute is told apart from a missing element
+        let expression = format!("({{ value: el.getAttribute({}) }})", serde_json::to_string(attribute)?);
+        let value = self.evaluate_on_elements(selector, false, &expression).await?;
+        Ok(value.get("value").and_then(|v| v.as_str()).map(str::to_string))
+    }
+
+    /// Evaluate `expression` with `el` bound to the first element matching `selector`, or to
+    /// each of them as an array with `all`
+    async fn evaluate_on_elements(&mut self, selector: &str, all: bool, expression: &str) -> Result<Value> {
+        let result = self.evaluate(&element_expression(selector, all, expression)?).await?;
+
+        match result.get("value") {
+            Some(Value::Null) | None => Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector))),
+            Some(value) => Ok(value.clone()),
+        }
+    }
+
+    /// Give keyboard focus to the element matching `selector`
+    pub async fn focus_element(&mut self, selector: &str) -> Result<()> {
+        debug!("Focusing: {}", selector);
+        self.evaluate_on_elements(selector, false, "(el.focus(), true)").await?;
+        Ok(())
+    }
+
+    /// Take keyboard focus away from the element matching `selector`
+    pub async fn blur_element(&mut self, selector: &str) -> Result<()> {
+        debug!("Blurring: {}", selector);
+        self.evaluate_on_elements(selector, false, "(el.blur(), true)").await?;
+        Ok(())
+    }
+
+    /// Element that has keyboard focus (`document.activeElement`), `None` when only the body has it
+    pub async fn get_focused_element(&mut self) -> Result<Option<ElementRef>> {
+        let result = self.evaluate(&FOCUSED_ELEMENT_JS.replace("{css_path}", CSS_PATH_JS.trim())).await?;
+
+        match result.get("value") {
+            Some(value) if !value.is_null() => Ok(Some(serde_json::from_value(value.clone())?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Read the current bounding box of an element
+    pub async fn get_element_bounds(&mut self, selector: &str) -> Result<PositionSample> {
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const el = document.querySelector('{}');
+                if (!el) return null;
+                const rect = el.getBoundingClientRect();
+                return {{ timestamp_ms: 0, x: rect.x, y: rect.y, width: rect.width, height: rect.height }};
+            }})()
+            "#,
+            selector.replace("'", "\\'")
+        )).await?;
+
+        match result.get("value") {
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value.clone())?),
+            _ => Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector))),
+        }
+    }
+
+    /// Sample an element's bounding box every `poll_interval_ms` for `duration_ms`.
+    /// Polling runs inside the page so intervals down to a single frame are honoured.
+    pub async fn track_element_position(&mut self, selector: &str, poll_interval_ms: u64, duration_ms: u64) -> Result<Vec<PositionSample>> {
+        debug!("Tracking position of {} every {}ms for {}ms", selector, poll_interval_ms, duration_ms);
+
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            new Promise((resolve) => {{
+                const selector = '{}';
+                if (!document.querySelector(selector)) {{
+                    resolve(null);
+                    return;
+                }}
+                const samples = [];
+                const start = performance.now();
+                const sample = () => {{
+                    const el = document.querySelector(selector);
+                    if (!el) return;
+                    const rect = el.getBoundingClientRect();
+                    samples.push({{
+                        timestamp_ms: performance.now() - start,
+                        x: rect.x,
+                        y: rect.y,
+                        width: rect.width,
+                        height: rect.height
+                    }});
+                }};
+                sample();
+                const timer = setInterval(sample, {});
+                setTimeout(() => {{
+                    clearInterval(timer);
+                    sample();
+                    resolve(samples);
+                }}, {});
+            }})
+            "#,
+            selector.replace("'", "\\'"),
+            poll_interval_ms.max(1),
+            duration_ms
+        )).await?;
+
+        match result.get("value") {
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value.clone())?),
+            _ => Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector))),
+        }
+    }
+
+    /// Hash the markup of `document.body` (or the element matching `scope_selector`)
+    pub async fn compute_dom_hash(&mut self, scope_selector: Option<&str>) -> Result<u64> {
+        let expression = match scope_selector {
+            Some(selector) => format!(
+                "(() => {{ const el = document.querySelector('{}'); return el ? el.innerHTML : null; }})()",
+                selector.replace("'", "\\'")
+            ),
+            None => "document.body ? document.body.innerHTML : document.documentElement.innerHTML".to_string(),
+        };
+
+        let result = self.cdp.evaluate_js(&expression).await?;
+        match result.get("value").and_then(|v| v.as_str()) {
+            Some(html) => Ok(fnv1a_hash(html.as_bytes())),
+            None => Err(ChromeMcpError::element_not_found(format!(
+                "No element found for selector: {}",
+                scope_selector.unwrap_or("body")
+            ))),
+        }
+    }
+
+    /// Fail if the DOM hash still equals `baseline_hash`
+    pub async fn assert_dom_changed(&mut self, baseline_hash: u64, scope_selector: Option<&str>) -> Result<()> {
+        let hash = self.compute_dom_hash(scope_selector).await?;
+        if hash == baseline_hash {
+            return Err(ChromeMcpError::invalid_operation(format!("DOM unchanged (hash {})", hash)));
+        }
+        Ok(())
+    }
+
+    /// Re-compute the DOM hash until it differs from `baseline_hash`, returning the new hash
+    pub async fn wait_for_dom_change(&mut self, baseline_hash: u64, scope_selector: Option<&str>, timeout_ms: u64) -> Result<u64> {
+        debug!("Waiting for DOM change (timeout: {}ms)", timeout_ms);
+
+        let result = timeout(Duration::from_millis(timeout_ms), async {
+            loop {
+                let hash = self.compute_dom_hash(scope_selector).await?;
+                if hash != baseline_hash {
+                    return Ok::<u64, ChromeMcpError>(hash);
+                }
+                sleep(Duration::from_millis(100)).await;
+            }
+        }).await;
+
+        match result {
+            Ok(hash) => hash,
+            Err(_) => Err(ChromeMcpError::Timeout { timeout: timeout_ms }),
+        }
+    }
+
+    /// Allow at most `max_errors` uncaught page exceptions and start capturing them
+    /// (on the current document and every document loaded afterwards)
+    pub async fn set_js_error_budget(&mut self, max_errors: usize) -> Result<()> {
+        debug!("Setting JavaScript error budget: {}", max_errors);
+
+        let capacity = max_errors.saturating_add(1).max(MIN_JS_ERROR_BUFFER_CAPACITY);
+        let script = JS_ERROR_CAPTURE_JS.replace("{capacity}", &capacity.to_string());
+
+        if let Some(identifier) = self.js_error_capture_script.take() {
+            self.remove_init_script(&identifier).await?;
+        }
+
+        self.js_error_capture_script = Some(self.add_init_script(&script).await?);
+        self.cdp.evaluate_js(&script).await?;
+
+        self.js_error_budget = Some(max_errors);
+        Ok(())
+    }
+
+    /// Configured JavaScript error budget, if any
+    pub fn js_error_budget(&self) -> Option<usize> {
+        self.js_error_budget
+    }
+
+    /// Uncaught exceptions captured on the current document
+    pub async fn get_js_errors(&mut self) -> Result<Vec<String>> {
+        let result = self.cdp.evaluate_js("window.__chromeMcpJsErrors || []").await?;
+        Ok(serde_json::from_value(result.get("value").cloned().unwrap_or(json!([])))?)
+    }
+
+    /// Fail if more uncaught exceptions were captured than the budget allows
+    pub async fn check_js_error_budget(&mut self) -> Result<()> {
+        let max_errors = self.js_error_budget.unwrap_or(0);
+        let errors = self.get_js_errors().await?;
+
+        if errors.len() > max_errors {
+            return Err(ChromeMcpError::JavaScriptError(format!(
+                "{} uncaught exception(s), budget is {}:\n{}",
+                errors.len(),
+                max_errors,
+                errors.join("\n")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get current URL
+    pub async fn current_url(&mut self) -> Result<String> {
+        let result = self.cdp.evaluate_typed(EvaluateRequest::new("window.location.href")).await?;
+
+        result
+            .value()
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| ChromeMcpError::cdp_protocol("Could not get current URL"))
+    }
+
+    /// Get page title
+    pub async fn page_title(&mut self) -> Result<String> {
+        let result = self.cdp.evaluate_typed(EvaluateRequest::new("document.title")).await?;
+
+        result
+            .value()
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| ChromeMcpError::cdp_protocol("Could not get page title"))
+    }
+
+    /// Record `document.title` changes (e.g. SPA navigations) through a runtime binding
+    /// named after `callback_tag`, on the current and all later documents of the tab.
+    /// The watch follows `switch_to_tab`.
+    pub async fn watch_title(&mut self, callback_tag: &str) -> Result<()> {
+        let tag: String = callback_tag.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '_').collect();
+        let binding = format!("__chromeMcpTitle_{}", tag);
+        debug!("Watching page title via binding {}", binding);
+
+        // Binding calls are only reported to the session that added the binding
+        let mut session = self.cdp.open_session().await?;
+        session.send_command("Runtime.enable", None).await?;
+        session.send_command("Runtime.addBinding", Some(json!({ "name": binding }))).await?;
+        let script = TITLE_WATCH_JS.replace("{binding}", &binding);
+        session.send_command("Page.addScriptToEvaluateOnNewDocument", Some(json!({ "source": script }))).await?;
+        session.send_command("Runtime.evaluate", Some(json!({ "expression": script }))).await?;
+
+        let history = self.title_history.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let (method, params) = match session.next_event().await {
+                    Ok(event) => event,
+                    Err(e) => {
+                        warn!("Page title watch stopped: {}", e);
+                        break;
+                    }
+                };
+                if method != "Runtime.bindingCalled" {
+                    continue;
+                }
+                if let Err(e) = record_title_change(&binding, &history, &params) {
+                    warn!("Invalid title change from binding {}: {}", binding, e);
+                }
+            }
+        });
+
+        if let Some(previous) = self.title_watch.replace(task.abort_handle()) {
+            previous.abort();
+        }
+        self.title_watch_tag = Some(tag);
+        Ok(())
+    }
+
+    /// Title changes recorded since `watch_title` was called
+    pub fn get_title_history(&self) -> Vec<TitleChange> {
+        self.title_history.lock().unwrap().clone()
+    }
+
+    /// Current `document.title`; same as `page_title`
+    pub async fn get_current_title(&mut self) -> Result<String> {
+        self.page_title().await
+    }
+
+    /// Title, URL and favicon of the current page
+    pub async fn page_info(&mut self) -> Result<PageInfo> {
+        let result = self.cdp.evaluate_js(PAGE_INFO_JS).await?;
+        let value = result.get("value").cloned().unwrap_or_default();
+        let field = |name: &str| value.get(name).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        Ok(PageInfo {
+            title: field("title").unwrap_or_default(),
+            url: field("url").unwrap_or_default(),
+            favicon_url: field("favicon"),
+            fixed_header_height: self.get_fixed_header_height().await?,
+        })
+    }
+
+    /// Record console messages and uncaught exceptions, dropping the oldest entries beyond
+    /// `max_entries`. Starts from an empty buffer, ending any earlier capture.
+    pub async fn start_console_capture(&mut self, max_entries: usize) -> Result<()> {
+        self.stop_console_capture();
+        self.console_entries.lock().unwrap().clear();
+        debug!("Capturing console output (at most {} entries)", max_entries);
+
+        let mut api_calls = self.cdp.subscribe(CONSOLE_API_CALLED);
+        let mut exceptions = self.cdp.subscribe(EXCEPTION_THROWN);
+        let entries = self.console_entries.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let (method, params) = tokio::select! {
+                    Some(params) = api_calls.recv() => (CONSOLE_API_CALLED, params),
+                    Some(params) = exceptions.recv() => (EXCEPTION_THROWN, params),
+                    else => break,
+                };
+                let Some(entry) = ConsoleEntry::from_event(method, &params) else {
+                    continue;
+                };
+                // The two subscriptions are read in no particular order
+                let mut entries = entries.lock().unwrap();
+                let index = entries.partition_point(|earlier| earlier.timestamp <= entry.timestamp);
+                entries.insert(index, entry);
+                if entries.len() > max_entries {
+                    let excess = entries.len() - max_entries;
+                    entries.drain(..excess);
+                }
+            }
+        });
+        self.console_capture = Some(task.abort_handle());
+        Ok(())
+    }
+
+    /// Stop recording console output; recorded entries are kept
+    pub fn stop_console_capture(&mut self) {
+        // Aborting the task drops its subscriptions, leaving other subscribers alone
+        if let Some(task) = self.console_capture.take() {
+            task.abort();
+        }
+    }
+
+    /// Console entries recorded so far, oldest first, optionally only those of `level`
+    pub fn get_console_entries(&self, level: Option<&str>) -> Vec<ConsoleEntry> {
+        self.console_entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| level.is_none_or(|level| entry.level == level))
+            .cloned()
+            .collect()
+    }
+
+    /// Value stored under `key`, or every entry as an object when `key` is `None`.
+    /// Missing keys read as `null`.
+    pub async fn storage_get(&mut self, storage_type: StorageType, key: Option<&str>) -> Result<Value> {
+        let storage = storage_type.js_object();
+        let script = match key {
+            Some(key) => format!("{}.getItem({})", storage, serde_json::to_string(key)?),
+            None => format!("Object.fromEntries(Object.keys({0}).map((key) => [key, {0}.getItem(key)]))", storage),
+        };
+        self.evaluate_storage(&script).await
+    }
+
+    /// Store `value` under `key`; strings are stored as they are, anything else as its JSON
+    pub async fn storage_set(&mut self, storage_type: StorageType, key: &str, value: &Value) -> Result<()> {
+        let script = format!(
+            "(() => {{ const value = {}; {}.setItem({}, typeof value === 'string' ? value : JSON.stringify(value)); }})()",
+            value,
+            storage_type.js_object(),
+            serde_json::to_string(key)?
+        );
+        self.evaluate_storage(&script).await?;
+        Ok(())
+    }
+
+    /// Remove `key`
+    pub async fn storage_remove(&mut self, storage_type: StorageType, key: &str) -> Result<()> {
+        let script = format!("{}.removeItem({})", storage_type.js_object(), serde_json::to_string(key)?);
+        self.evaluate_storage(&script).await?;
+        Ok(())
+    }
+
+    /// Remove every key
+    pub async fn storage_clear(&mut self, storage_type: StorageType) -> Result<()> {
+        self.evaluate_storage(&format!("{}.clear()", storage_type.js_object())).await?;
+        Ok(())
+    }
+
+    /// Keys currently stored
+    pub async fn storage_keys(&mut self, storage_type: StorageType) -> Result<Vec<String>> {
+        let keys = self.evaluate_storage(&format!("Object.keys({})", storage_type.js_object())).await?;
+        Ok(serde_json::from_value(keys)?)
+    }
+
+    async fn evaluate_storage(&mut self, script: &str) -> Result<Value> {
+        let result = self.cdp.evaluate_js(script)
+            .await
+            .map_err(|e| e.in_frame("browser", "storage").with_context("script", script))?;
+        Ok(result.get("value").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Dispatch a synthetic `storage` event on the page, as if another document changed `key`.
+    /// The storage itself is left unchanged.
+    pub async fn trigger_storage_event(&mut self, key: &str, old_value: Option<&str>, new_value: Option<&str>, storage_type: StorageType) -> Result<()> {
+        debug!("Triggering {:?} storage event for key {}", storage_type, key);
+
+        let script = format!(
+            "window.dispatchEvent(new StorageEvent('storage', {{ key: {}, oldValue: {}, newValue: {}, url: location.href, storageArea: {} }}))",
+            serde_json::to_string(key)?,
+            serde_json::to_string(&old_value)?,
+            serde_json::to_string(&new_value)?,
+            storage_type.js_object()
+        );
+        self.cdp.evaluate_js(&script).await?;
+        Ok(())
+    }
+
+    /// Collect the `storage` events the page receives during the next `timeout_ms` milliseconds
+    pub async fn listen_for_storage_changes(&mut self, timeout_ms: u64) -> Result<Vec<StorageChange>> {
+        debug!("Listening for storage changes for {}ms", timeout_ms);
+
+        let mut calls = self.cdp.subscribe("Runtime.bindingCalled");
+        self.cdp.send_command("Runtime.addBinding", Some(json!({ "name": STORAGE_BINDING }))).await?;
+
+        let script = STORAGE_LISTEN_JS
+            .replace("{binding}", STORAGE_BINDING)
+            .replace("{timeout}", &timeout_ms.to_string());
+        self.cdp.evaluate_js(&script).await?;
+        self.cdp.send_command("Runtime.removeBinding", Some(json!({ "name": STORAGE_BINDING }))).await?;
+
+        // Every call was delivered before the listening promise resolved
+        let mut changes = Vec::new();
+        while let Ok(params) = calls.try_recv() {
+            if params.get("name").and_then(|n| n.as_str()) != Some(STORAGE_BINDING) {
+                continue;
+            }
+            let payload = params.get("payload").and_then(|p| p.as_str()).unwrap_or_default();
+            changes.push(serde_json::from_str(payload)?);
+        }
+        Ok(changes)
+    }
+
+    /// Open an `EventSource` on `source_url` from the page and collect its `event_types` events
+    /// (default `message`) until `max_events` have arrived or `timeout_ms` has passed, then
+    /// close it. Each event is also reported through a runtime binding as it arrives.
+    pub async fn capture_sse_events(&mut self, source_url: &str, event_types: Vec<&str>, max_events: usize, timeout_ms: u64) -> Result<Vec<SseEvent>> {
+        let event_types = if event_types.is_empty() { vec!["message"] } else { event_types };
+        debug!("Capturing up to {} {:?} event(s) from {} for {}ms", max_events, event_types, source_url, timeout_ms);
+
+        let script = SSE_CAPTURE_JS
+            .replace("{types}", &serde_json::to_string(&event_types)?)
+            .replace("{max}", &max_events.max(1).to_string())
+            .replace("{timeout}", &timeout_ms.to_string())
+            .replace("{url}", &serde_json::to_string(source_url)?);
+        let result = self.cdp.evaluate_js(&script).await?;
+
+        Ok(serde_json::from_value(result.get("value").cloned().unwrap_or(json!([])))?)
+    }
+
+    /// Scroll the page until the element matching `sentinel_selector` (e.g. an infinite scroll
+    /// loader) enters the viewport, as reported by an `IntersectionObserver`, or `timeout_ms`
+    /// passes. Once it intersects, `on_intersect_js` is evaluated, e.g. to click "load more".
+    pub async fn observe_intersection(&mut self, sentinel_selector: &str, on_intersect_js: Option<&str>, timeout_ms: u64) -> Result<IntersectionResult> {
+        debug!("Scrolling until {} intersects the viewport (timeout: {}ms)", sentinel_selector, timeout_ms);
+
+        self.cdp.send_command("Runtime.addBinding", Some(json!({ "name": INTERSECTION_BINDING }))).await?;
+
+        let script = OBSERVE_INTERSECTION_JS
+            .replace("{binding}", INTERSECTION_BINDING)
+            .replace("{timeout}", &timeout_ms.to_string())
+            .replace("{selector}", &serde_json::to_string(sentinel_selector)?);
+        let result = self.cdp.evaluate_js(&script).await?;
+        let result: IntersectionResult = match result.get("value") {
+            Some(value) if !value.is_null() => serde_json::from_value(value.clone())?,
+            _ => return Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", sentinel_selector))),
+        };
+
+        if let (true, Some(js)) = (result.intersected, on_intersect_js) {
+            self.cdp.evaluate_js(js).await?;
+        }
+
+        Ok(result)
+    }
+
+    /// Collect the messages posted on the `channel_name` `BroadcastChannel` (by other tabs,
+    /// frames or workers of the page's origin) until `max_messages` have arrived or
+    /// `timeout_ms` has passed
+    pub async fn create_broadcast_listener(&mut self, channel_name: &str, max_messages: usize, timeout_ms: u64) -> Result<Vec<Value>> {
+        debug!("Listening on broadcast channel {} for {}ms", channel_name, timeout_ms);
+
+        self.cdp.send_command("Runtime.addBinding", Some(json!({ "name": BROADCAST_BINDING }))).await?;
+
+        let script = BROADCAST_LISTEN_JS
+            .replace("{binding}", BROADCAST_BINDING)
+            .replace("{max}", &max_messages.max(1).to_string())
+            .replace("{timeout}", &timeout_ms.to_string())
+            .replace("{channel}", &serde_json::to_string(channel_name)?);
+        let result = self.cdp.evaluate_js(&script).await?;
+
+        Ok(serde_json::from_value(result.get("value").cloned().unwrap_or(json!([])))?)
+    }
+
+    /// Post `message` on the `channel_name` `BroadcastChannel` of the page's origin
+    pub async fn post_broadcast_message(&mut self, channel_name: &str, message: Value) -> Result<()> {
+        debug!("Posting to broadcast channel {}", channel_name);
+
+        let script = format!(
+            "(() => {{ const channel = new BroadcastChannel({}); channel.postMessage({}); channel.close(); }})()",
+            serde_json::to_string(channel_name)?,
+            serde_json::to_string(&message)?
+        );
+        self.cdp.evaluate_js(&script).await?;
+        Ok(())
+    }
+
+    /// State of `permission` for the current page, as the page sees it
+    pub async fn query_permission(&mut self, permission: BrowserPermission) -> Result<PermissionState> {
+        self.query_permissions(&[permission])
+            .await?
+            .remove(permission.name())
+            .ok_or_else(|| ChromeMcpError::invalid_operation(format!("Permission not supported: {}", permission.name())))
+    }
+
+    /// States of all `BrowserPermission`s this Chrome supports, by permission name
+    pub async fn query_all_permissions(&mut self) -> Result<HashMap<String, PermissionState>> {
+        self.query_permissions(&BrowserPermission::ALL).await
+    }
+
+    async fn query_permissions(&mut self, permissions: &[BrowserPermission]) -> Result<HashMap<String, PermissionState>> {
+        let descriptors: Vec<Value> = permissions.iter().map(|p| p.descriptor()).collect();
+        let script = QUERY_PERMISSIONS_JS.replace("{descriptors}", &serde_json::to_string(&descriptors)?);
+        let result = self.cdp.evaluate_js(&script).await?;
+
+        Ok(serde_json::from_value(result.get("value").cloned().unwrap_or(json!({})))?)
+    }
+
+    /// Set `permission` to `state` for the current page's origin (`Browser.setPermission`)
+    pub async fn set_permission(&mut self, permission: BrowserPermission, state: PermissionState) -> Result<()> {
+        let url = self.current_url().await?;
+        let origin = Url::parse(&url)?.origin().ascii_serialization();
+        debug!("Setting {} permission to {:?} for {}", permission.name(), state, origin);
+
+        self.cdp.send_command("Browser.setPermission", Some(json!({
+            "permission": permission.descriptor(),
+            "setting": state,
+            "origin": origin
+        }))).await?;
+        Ok(())
+    }
+
+    /// Grant `permission` to the current page's origin
+    pub async fn grant_permission(&mut self, permission: BrowserPermission) -> Result<()> {
+        self.set_permission(permission, PermissionState::Granted).await
+    }
+
+    /// Deny `permission` to the current page's origin
+    pub async fn deny_permission(&mut self, permission: BrowserPermission) -> Result<()> {
+        self.set_permission(permission, PermissionState::Denied).await
+    }
+
+    /// Text on the clipboard (`navigator.clipboard.readText()`)
+    pub async fn clipboard_read(&mut self) -> Result<String> {
+        let result = self.evaluate_clipboard("navigator.clipboard.readText()").await?;
+        result.as_str().map(str::to_string).ok_or_else(|| {
+            ChromeMcpError::javascript_error(format!("navigator.clipboard.readText() returned {} instead of text", result))
+        })
+    }
+
+    /// Every item on the clipboard, one entry per MIME type
+    pub async fn read_clipboard_items(&mut self) -> Result<Vec<ClipboardItem>> {
+        let result = self.evaluate_clipboard(CLIPBOARD_ITEMS_JS).await?;
+        let mut items: Vec<ClipboardItem> = serde_json::from_value(result)?;
+        for item in &mut items {
+            item.kind = ClipboardContentKind::from_mime_type(&item.type_);
+        }
+        Ok(items)
+    }
+
+    /// PNG image on the clipboard, as base64
+    pub async fn read_clipboard_image(&mut self) -> Result<String> {
+        self.read_clipboard_items()
+            .await?
+            .into_iter()
+            .find(|item| item.type_ == "image/png")
+            .map(|item| item.data_b64)
+            .ok_or_else(|| ChromeMcpError::element_not_found("Clipboard holds no image/png item"))
+    }
+
+    /// Put `text` on the clipboard with `navigator.clipboard.writeText`, falling back to
+    /// `document.execCommand('copy')` when the page is refused clipboard access
+    pub async fn clipboard_write(&mut self, text: &str) -> Result<()> {
+        self.prepare_clipboard(BrowserPermission::ClipboardWrite).await?;
+        let written = self.write_clipboard(text).await;
+        let ended = self.end_focus_emulation().await;
+        // A failed write says more than failing to end focus emulation after it
+        written.and(ended)
+    }
+
+    async fn write_clipboard(&mut self, text: &str) -> Result<()> {
+        let text_json = json!(text).to_string();
+
+        let script = format!("navigator.clipboard.writeText({})", text_json);
+        let response = self.cdp.evaluate_typed(EvaluateRequest::new(script).with_user_gesture())
+            .await
+            .map_err(|e| e.in_frame("browser", "write_clipboard"))?;
+        let Some(exception_details) = response.exception_details else {
+            return Ok(());
+        };
+        debug!("navigator.clipboard.writeText failed, copying with execCommand: {}", exception_details);
+
+        let script = CLIPBOARD_COPY_JS.replace("{text}", &text_json);
+        let copied = self.cdp.evaluate_typed(EvaluateRequest::new(script).with_user_gesture())
+            .await
+            .map_err(|e| e.in_frame("browser", "write_clipboard"))?;
+        if copied.value().and_then(|v| v.as_bool()) != Some(true) {
+            return Err(ChromeMcpError::javascript_error(format!("Could not write the clipboard: {}", exception_details)));
+        }
+        Ok(())
+    }
+
+    /// Give the page focus, which clipboard access needs, and `permission` if the Permissions
+    /// API reports it denied; callers turn focus emulation off again with `end_focus_emulation`
+    async fn prepare_clipboard(&mut self, permission: BrowserPermission) -> Result<()> {
+        if self.query_permission(permission).await? == PermissionState::Denied {
+            self.grant_permission(permission).await?;
+        }
+        self.cdp.send_command("Emulation.setFocusEmulationEnabled", Some(json!({ "enabled": true }))).await?;
+        Ok(())
+    }
+
+    /// Give focus and blur back to the real window once clipboard access is done
+    async fn end_focus_emulation(&mut self) -> Result<()> {
+        self.cdp.send_command("Emulation.setFocusEmulationEnabled", Some(json!({ "enabled": false }))).await?;
+        Ok(())
+    }
+
+    /// Evaluate a clipboard read; the page needs the clipboard-read permission and focus
+    async fn evaluate_clipboard(&mut self, script: &str) -> Result<Value> {
+        self.prepare_clipboard(BrowserPermission::ClipboardRead).await?;
+
+        let response = self.cdp.evaluate_typed(EvaluateRequest::new(script).with_user_gesture()).await;
+        let ended = self.end_focus_emulation().await;
+        let response = response.map_err(|e| e.in_frame("browser", "read_clipboard"))?;
+        ended?;
+        if let Some(exception_details) = response.exception_details {
+            return Err(ChromeMcpError::javascript_error(format!("Could not read the clipboard: {}", exception_details)));
+        }
+        Ok(response.value().cloned().unwrap_or(Value::Null))
+    }
+
+    /// Add a timestamped marker to the console and the DevTools Performance timeline,
+    /// to correlate automation steps with page activity
+    pub async fn console_mark(&mut self, label: &str) -> Result<()> {
+        debug!("Adding console mark {}", label);
+
+        let label = serde_json::to_string(label)?;
+        let script = format!(
+            "console.timeStamp({label}); performance.mark({label}); console.log('[chrome-mcp]', {label}, performance.now())"
+        );
+        self.cdp.evaluate_js(&script).await?;
+        Ok(())
+    }
+
+    /// Performance marks of the current page, including those added by `console_mark`
+    pub async fn get_time_marks(&mut self) -> Result<Vec<TimeMark>> {
+        let result = self.cdp.evaluate_js(
+            "performance.getEntriesByType('mark').map(m => ({ name: m.name, start_time: m.startTime }))"
+        ).await?;
+
+        Ok(serde_json::from_value(result.get("value").cloned().unwrap_or(json!([])))?)
+    }
+
+    /// Get accessibility tree
+    pub async fn accessibility_tree(&mut self) -> Result<AccessibilityNode> {
+        self.accessibility.get_full_tree().await
+    }
+
+    /// Get accessibility manager
+    pub fn accessibility(&mut self) -> &mut AccessibilityManager<B> {
+        &mut self.accessibility
+    }
+
+    /// Find elements using various strategies, most relevant first
+    pub async fn find_elements(&mut self, query: &str) -> Result<Vec<ElementRef>> {
+        let mut results = Vec::new();
+
+        // Try CSS selector
+        if let Ok(elements) = self.find_elements_by_selector(query).await {
+            results.extend(elements);
+        }
+
+        // Try accessibility text and role
+        let mut nodes = self.accessibility.find_clickable_by_text(query).await.unwrap_or_default();
+        nodes.extend(self.accessibility.find_by_role(query).await.unwrap_or_default());
+        for node in &nodes {
+            let element = element_ref_from_node(node);
+            if !results.iter().any(|r: &ElementRef| r.id == element.id) {
+                results.push(element);
+            }
+        }
+
+        if results.is_empty() {
+            return Err(ChromeMcpError::element_not_found(format!("No elements found for: {}", query)));
+        }
+
+        let viewport = self.viewport_size().await;
+        for element in &mut results {
+            element.score = relevance_score(query, element, viewport);
+        }
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        Ok(results)
+    }
+
+    /// Selector for the element best matching `element_hint` (found as with `find_elements`)
+    /// that does not depend on layout or CSS framework classes: a test id, `aria-label`, `id`
+    /// or role and label, falling back to a minimal CSS path
+    pub async fn generate_stable_selector(&mut self, element_hint: &str) -> Result<String> {
+        let elements = self.find_elements(element_hint).await?;
+        self.stable_selector_for(&elements[0]).await
+    }
+
+    /// Fill in `stable_selector` for each element; elements that cannot be resolved to a DOM
+    /// node are left without one
+    pub async fn add_stable_selectors(&mut self, elements: &mut [ElementRef]) {
+        for element in elements {
+            match self.stable_selector_for(element).await {
+                Ok(selector) => element.stable_selector = Some(selector),
+                Err(e) => debug!("No stable selector for {}: {}", element.id, e),
+            }
+        }
+    }
+
+    async fn stable_selector_for(&mut self, element: &ElementRef) -> Result<String> {
+        let node = match (element.id.strip_prefix("dom-").and_then(|id| id.parse::<u64>().ok()), element.backend_node_id) {
+            (Some(node_id), _) => json!({ "nodeId": node_id }),
+            (None, Some(backend_node_id)) => json!({ "backendNodeId": backend_node_id }),
+            (None, None) => {
+                return Err(ChromeMcpError::element_not_found(format!("Element {} has no DOM node", element.id)));
+            }
+        };
+        let resolved = self.cdp.send_command("DOM.resolveNode", Some(node)).await?;
+        let object_id = resolved
+            .get("object")
+            .and_then(|o| o.get("objectId"))
+            .and_then(|id| id.as_str())
+            .ok_or_else(|| ChromeMcpError::element_not_found(format!("Element {} is no longer in the page", element.id)))?;
+
+        let result = self.cdp.send_command("Runtime.callFunctionOn", Some(json!({
+            "objectId": object_id,
+            "functionDeclaration": STABLE_SELECTOR_JS.replace("{css_path}", CSS_PATH_JS),
+            "returnByValue": true
+        }))).await?;
+        result
+            .get("result")
+            .and_then(|r| r.get("value"))
+            .and_then(|v| v.as_str())
+            .filter(|selector| !selector.is_empty())
+            .map(|selector| selector.to_string())
+            .ok_or_else(|| ChromeMcpError::javascript_error(format!("Could not build a selector for {}", element.id)))
+    }
+
+    /// Viewport width and height in CSS pixels, if the page can report them
+    pub async fn viewport_size(&mut self) -> Option<(f64, f64)> {
+        let result = self.cdp.evaluate_js("[window.innerWidth, window.innerHeight]").await.ok()?;
+        let size = result.get("value")?.as_array()?;
+        Some((size.first()?.as_f64()?, size.get(1)?.as_f64()?))
+    }
+
+    /// Get cookies for current domain
+    pub async fn get_cookies(&mut self) -> Result<Vec<Cookie>> {
+        self.fetch_cookies(None).await
+    }
+
+    /// Cookies that apply to `url`, following the browser's domain, path and `Secure` rules
+    pub async fn get_cookies_for_url(&mut self, url: &str) -> Result<Vec<Cookie>> {
+        Url::parse(url).map_err(|e| ChromeMcpError::invalid_operation(format!("Invalid URL {}: {}", url, e)))?;
+        self.fetch_cookies(Some(json!({ "urls": [url] }))).await
+    }
+
+    /// Cookies for the current domain named `name`
+    pub async fn get_cookies_by_name(&mut self, name: &str) -> Result<Vec<Cookie>> {
+        let mut cookies = self.get_cookies().await?;
+        cookies.retain(|cookie| cookie.name == name);
+        Ok(cookies)
+    }
+
+    async fn fetch_cookies(&mut self, params: Option<Value>) -> Result<Vec<Cookie>> {
+        let result = self.cdp.send_command("Network.getCookies", params).await?;
+
+        let cookies_json = result
+            .get("cookies")
+            .and_then(|c| c.as_array())
+            .ok_or_else(|| ChromeMcpError::network_error("Invalid cookies response"))?;
+
+        let cookies: Vec<Cookie> = cookies_json
+            .iter()
+            .filter_map(|cookie_json| {
+                Some(Cookie {
+                    name: cookie_json.get("name")?.as_str()?.to_string(),
+                    value: cookie_json.get("value")?.as_str()?.to_string(),
+                    domain: cookie_json.get("domain")?.as_str()?.to_string(),
+                    path: cookie_json.get("path")?.as_str()?.to_string(),
+                    secure: cookie_json.get("secure")?.as_bool().unwrap_or(false),
+                    http_only: cookie_json.get("httpOnly")?.as_bool().unwrap_or(false),
+                    same_site: cookie_json.get("sameSite").and_then(|s| s.as_str()).map(|s| s.to_string()),
+                    expires: cookie_json.get("expires").and_then(|e| e.as_f64()),
+                })
+            })
+            .collect();
+
+        Ok(cookies)
+    }
+
+    /// Set a cookie
+    pub async fn set_cookie(&mut self, cookie: Cookie) -> Result<()> {
+        self.cdp.set_cookie_typed(SetCookieRequest {
+            name: cookie.name,
+            value: cookie.value,
+            url: None,
+            domain: Some(cookie.domain),
+            path: Some(cookie.path),
+            secure: Some(cookie.secure),
+            http_only: Some(cookie.http_only),
+            same_site: cookie.same_site,
+            expires: cookie.expires,
+        }).await
+    }
+
+    /// Clear all cookies
+    pub async fn clear_cookies(&mut self) -> Result<()> {
+        self.cdp.send_command("Network.clearBrowserCookies", None).await?;
+        Ok(())
+    }
+
+    /// Export cookies, web storage of the current origin and the current URL
+    pub async fn export_session(&mut self) -> Result<SessionState> {
+        let cookies = self.get_cookies().await?;
+        let url = self.current_url().await.ok();
+
+        let result = self.cdp.evaluate_js(
+            r#"
+            (() => {
+                const dump = (storage) => {
+                    const entries = {};
+                    for (let i = 0; i < storage.length; i++) {
+                        const key = storage.key(i);
+                        entries[key] = storage.getItem(key);
+                    }
+                    return entries;
+                };
+                return {
+                    origin: window.location.origin,
+                    local: dump(window.localStorage),
+                    session: dump(window.sessionStorage)
+                };
+            })()
+            "#,
+        ).await?;
+
+        let value = result.get("value").cloned().unwrap_or(Value::Null);
+        let mut state = SessionState {
+            url,
+            cookies,
+            ..Default::default()
+        };
+
+        if let Some(origin) = value.get("origin").and_then(|o| o.as_str()) {
+            // Opaque origins (about:blank, data: URLs) have no usable storage
+            if origin != "null" {
+                let local: HashMap<String, String> =
+                    serde_json::from_value(value.get("local").cloned().unwrap_or(json!({})))?;
+                let session: HashMap<String, String> =
+                    serde_json::from_value(value.get("session").cloned().unwrap_or(json!({})))?;
+
+                if !local.is_empty() {
+                    state.local_storage.insert(origin.to_string(), local);
+                }
+                if !session.is_empty() {
+                    state.session_storage.insert(origin.to_string(), session);
+                }
+            }
+        }
+
+        info!(
+            "Exported session: {} cookies, {} storage origins",
+            state.cookies.len(),
+            state.local_storage.len() + state.session_storage.len()
+        );
+        Ok(state)
+    }
+
+    /// Restore a previously exported session
+    pub async fn import_session(&mut self, state: SessionState) -> Result<()> {
+        for cookie in state.cookies {
+            self.set_cookie(cookie).await?;
+        }
+
+        let mut origins: Vec<&String> = state
+            .local_storage
+            .keys()
+            .chain(state.session_storage.keys())
+            .collect();
+        origins.sort();
+        origins.dedup();
+
+        // Storage is only writable from a document of the same origin
+        for origin in origins {
+            self.navigate(origin).await?;
+
+            let local = state.local_storage.get(origin).cloned().unwrap_or_default();
+            let session = state.session_storage.get(origin).cloned().unwrap_or_default();
+
+            self.cdp.evaluate_js(&format!(
+                r#"
+                (() => {{
+                    const local = {};
+                    const session = {};
+                    for (const [key, value] of Object.entries(local)) {{
+                        window.localStorage.setItem(key, value);
+                    }}
+                    for (const [key, value] of Object.entries(session)) {{
+                        window.sessionStorage.setItem(key, value);
+                    }}
+                }})()
+                "#,
+                serde_json::to_string(&local)?,
+                serde_json::to_string(&session)?
+            )).await?;
+        }
+
+        if let Some(url) = state.url {
+            self.navigate(&url).await?;
+        }
+
+        info!("Imported session");
+        Ok(())
+    }
+
+    /// Generate PDF of current page
+    pub async fn pdf(&mut self, options: Option<PdfOptions>) -> Result<String> {
+        self.screenshot.capture_pdf(options).await
+    }
+
+    /// Check whether a server's CORS policy allows `method` requests from `origin`.
+    /// Browsers refuse to let page scripts set the `Origin` header, so the preflight
+    /// (`OPTIONS`) request is sent directly rather than through `fetch`.
+    pub async fn test_cors(&mut self, resource_url: &str, origin: &str, method: &str, request_headers: Vec<String>) -> Result<CorsResult> {
+        debug!("Testing CORS for {} from origin {} ({})", resource_url, origin, method);
+
+        let mut request = reqwest::Client::new()
+            .request(reqwest::Method::OPTIONS, resource_url)
+            .header("Origin", origin)
+            .header("Access-Control-Request-Method", method.to_uppercase());
+        if !request_headers.is_empty() {
+            request = request.header("Access-Control-Request-Headers", request_headers.join(", ").to_lowercase());
+        }
+
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                return Ok(CorsResult {
+                    error: Some(format!("Preflight request failed: {}", e)),
+                    ..Default::default()
+                })
+            }
+        };
+
+        let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+        let mut result = evaluate_cors_headers(
+            origin,
+            method,
+            &request_headers,
+            header("access-control-allow-origin").as_deref(),
+            header("access-control-allow-methods").as_deref(),
+            header("access-control-allow-headers").as_deref(),
+        );
+
+        if result.allowed && !response.status().is_success() {
+            result.allowed = false;
+            result.error = Some(format!("Preflight returned HTTP {}", response.status()));
+        }
+
+        Ok(result)
+    }
+
+    /// Probe which of `ports` on `host` the page can reach, by fetching `http://host:port/`
+    /// from it with a `timeout_ms` limit per port. Meant for security testing of internal
+    /// network exposure (what an SSRF or a malicious page could reach).
+    pub async fn probe_local_ports(&mut self, host: &str, ports: Vec<u16>, timeout_ms: u64) -> Result<Vec<PortProbeResult>> {
+        warn!(
+            "Probing {} port(s) on {} from the page; only scan hosts you are authorized to test",
+            ports.len(),
+            host
+        );
+
+        let mut urls = Vec::with_capacity(ports.len());
+        for port in &ports {
+            let url = Url::parse(&format!("http://{}:{}/", host, port))
+                .map_err(|e| ChromeMcpError::invalid_operation(format!("Invalid host {}: {}", host, e)))?;
+            if url.path() != "/" || url.query().is_some() || url.port_or_known_default() != Some(*port) {
+                return Err(ChromeMcpError::invalid_operation(format!("Invalid host: {}", host)));
+            }
+            urls.push(url.to_string());
+        }
+
+        let script = PROBE_PORTS_JS
+            .replace("{urls}", &serde_json::to_string(&urls)?)
+            .replace("{timeout}", &timeout_ms.max(1).to_string());
+        let result = self.cdp.evaluate_js(&script).await?;
+        let probes = result.get("value").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+
+        Ok(ports
+            .into_iter()
+            .zip(probes.iter().map(Some).chain(std::iter::repeat(None)))
+            .map(|(port, probe)| PortProbeResult {
+                port,
+                open: probe.and_then(|p| p.get("open")).and_then(|o| o.as_bool()).unwrap_or(false),
+                response_code: probe
+                    .and_then(|p| p.get("status"))
+                    .and_then(|s| s.as_u64())
+                    .and_then(|s| u16::try_from(s).ok()),
+            })
+            .collect())
+    }
+
+    /// Send a `method` request to `url` with `fetch` from the page, so it carries the page's
+    /// cookies and goes through the browser's HTTP cache, e.g. to check content negotiation
+    /// (`Accept`, `Accept-Encoding`) and caching (`Cache-Control`, `If-None-Match`) behavior.
+    /// With `follow_redirects` off a redirect comes back as an opaque response (status 0).
+    pub async fn fetch_with_headers(
+        &mut self,
+        url: &str,
+        headers: HashMap<String, String>,
+        method: &str,
+        body: Option<&str>,
+        follow_redirects: bool,
+    ) -> Result<FetchResult> {
+        let method = method.to_uppercase();
+        if body.is_some() && (method == "GET" || method == "HEAD") {
+            return Err(ChromeMcpError::invalid_operation(format!("A {} request cannot have a body", method)));
+        }
+        debug!("Fetching {} {} from the page", method, url);
+
+        let request = json!({
+            "url": url,
+            "init": {
+                "method": method,
+                "headers": headers,
+                "body": body,
+                "redirect": if follow_redirects { "follow" } else { "manual" },
+            },
+        });
+        let script = FETCH_JS.replace("{request}", &serde_json::to_string(&request)?);
+        let result = self.cdp.evaluate_js(&script).await?;
+        let value = result.get("value").cloned().unwrap_or_default();
+
+        Ok(FetchResult {
+            status: value.get("status").and_then(|s| s.as_u64()).and_then(|s| u16::try_from(s).ok()).unwrap_or(0),
+            url: value.get("url").and_then(|u| u.as_str()).unwrap_or(url).to_string(),
+            redirected: value.get("redirected").and_then(|r| r.as_bool()).unwrap_or(false),
+            response_headers: value
+                .get("headers")
+                .and_then(|h| h.as_object())
+                .map(|headers| {
+                    headers
+                        .iter()
+                        .filter_map(|(name, value)| value.as_str().map(|v| (name.clone(), v.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            body_text: value.get("body").and_then(|b| b.as_str()).unwrap_or_default().to_string(),
+            from_cache: value.get("fromCache").and_then(|c| c.as_bool()).unwrap_or(false),
+        })
+    }
+
+    /// Check the links of the page, or of the elements matching `selectors`, for broken
+    /// targets, `concurrent` at a time. Same-origin links are loaded in fresh tabs, so they
+    /// see the page's cookies; external links (only checked with `include_external`) get a
+    /// plain HEAD request. Same-origin results come first, each group in page order.
+    pub async fn check_links(
+        &mut self,
+        selectors: Option<Vec<&str>>,
+        concurrent: usize,
+        include_external: bool,
+        timeout_ms: u64,
+    ) -> Result<Vec<LinkCheckResult>> {
+        let concurrent = concurrent.max(1);
+        let script = LINKS_JS.replace("{selectors}", &serde_json::to_string(&selectors.unwrap_or_default())?);
+        let result = self.cdp.evaluate_js(&script).await?;
+
+        let page = result.get("value").cloned().unwrap_or(Value::Null);
+        let origin = page.get("origin").and_then(|o| o.as_str()).unwrap_or_default().to_string();
+        let links: Vec<String> = serde_json::from_value(page.get("links").cloned().unwrap_or(json!([])))?;
+        info!("Checking {} links, {} at a time", links.len(), concurrent);
+
+        let (same_origin, external): (Vec<String>, Vec<String>) = links
+            .into_iter()
+            .partition(|link| Url::parse(link).is_ok_and(|url| url.origin().ascii_serialization() == origin));
+
+        let mut results = self.check_links_in_tabs(&same_origin, concurrent, timeout_ms).await?;
+        if include_external {
+            results.extend(check_links_over_http(&external, concurrent, timeout_ms).await?);
+        }
+        Ok(results)
+    }
+
+    /// Load each URL in a fresh tab, `concurrent` tabs at a time
+    async fn check_links_in_tabs(&self, urls: &[String], concurrent: usize, timeout_ms: u64) -> Result<Vec<LinkCheckResult>> {
+        let mut results = Vec::with_capacity(urls.len());
+
+        for batch in urls.chunks(concurrent) {
+            // Same as `map_tabs`: CDP round trips sharing this thread
+            let batch_results = LocalSet::new().run_until(async {
+                let mut tasks = JoinSet::new();
+                for (index, url) in batch.iter().cloned().enumerate() {
+                    let mut browser = self.clone();
+                    browser.checkpoint_file = None;
+                    tasks.spawn_local(async move { (index, browser.check_link_in_fresh_tab(&url, timeout_ms).await) });
+                }
+
+                let mut batch_results = Vec::with_capacity(tasks.len());
+                while let Some(joined) = tasks.join_next().await {
+                    batch_results.push(joined.map_err(|e| ChromeMcpError::invalid_operation(format!("Link check task failed: {}", e)))?);
+                }
+                batch_results.sort_by_key(|(index, _)| *index);
+                Ok::<_, ChromeMcpError>(batch_results)
+            }).await?;
+
+            results.extend(batch_results.into_iter().map(|(_, result)| result));
+        }
+
+        Ok(results)
+    }
+
+    /// Let the browser download files into `path`, creating it if needed
+    pub async fn set_download_directory(&mut self, path: impl Into<PathBuf>) -> Result<()> {
+        let path = path.into();
+        std::fs::create_dir_all(&path)?;
+        self.cdp
+            .send_command(
+                "Browser.setDownloadBehavior",
+                Some(json!({
+                    "behavior": "allow",
+                    "downloadPath": path.to_string_lossy(),
+                    "eventsEnabled": true
+                })),
+            )
+            .await?;
+        info!("Downloading files to {}", path.display());
+        self.download_dir = Some(path);
+        self.track_downloads();
+        Ok(())
+    }
+
+    /// Follow the downloads Chrome reports now that `eventsEnabled` is set
+    fn track_downloads(&mut self) {
+        let mut begins = self.cdp.subscribe("Browser.downloadWillBegin");
+        let mut updates = self.cdp.subscribe("Browser.downloadProgress");
+        let downloads = self.downloads.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    // A download begins before its progress is reported
+                    biased;
+                    Some(params) = begins.recv() => {
+                        let guid = params.get("guid").and_then(|g| g.as_str());
+                        let filename = params.get("suggestedFilename").and_then(|f| f.as_str());
+                        if let (Some(guid), Some(filename)) = (guid, filename) {
+                            downloads.lock().unwrap().insert(guid.to_string(), TrackedDownload {
+                                filename: filename.to_string(),
+                                begun: std::time::SystemTime::now(),
+                                received_bytes: 0,
+                                total_bytes: 0,
+                                state: "inProgress".to_string(),
+                            });
+                        }
+                    }
+                    Some(params) = updates.recv() => {
+                        let guid = params.get("guid").and_then(|g| g.as_str()).unwrap_or_default();
+                        if let Some(download) = downloads.lock().unwrap().get_mut(guid) {
+                            let bytes = |key: &str| params.get(key).and_then(|b| b.as_f64()).unwrap_or_default() as u64;
+                            download.received_bytes = bytes("receivedBytes");
+                            download.total_bytes = bytes("totalBytes");
+                            if let Some(state) = params.get("state").and_then(|s| s.as_str()) {
+                                download.state = state.to_string();
+                            }
+                        }
+                    }
+                    else => break,
+                }
+            }
+        });
+
+        if let Some(previous) = self.download_watch.replace(task.abort_handle()) {
+            previous.abort();
+        }
+    }
+
+    /// Directory downloads are saved to: the one set with `set_download_directory`,
+    /// otherwise the user's Downloads folder
+    pub fn download_directory(&self) -> PathBuf {
+        self.download_dir.clone().unwrap_or_else(default_download_dir)
+    }
+
+    /// Current state of the download whose file name matches `filename_pattern` (`*` and `?`
+    /// wildcards), as Chrome reports it or else as read from the download directory
+    pub fn download_progress(&self, filename_pattern: &str) -> Result<Option<DownloadProgress>> {
+        let found = current_download(&self.downloads, &self.download_directory(), filename_pattern)?;
+        Ok(found.map(|(_, progress)| progress))
+    }
+
+    /// Poll every `poll_interval_ms` for the download matching `filename_pattern`. Emits a
+    /// `DownloadProgress` whenever the download is found and ends after the one reporting
+    /// completion.
+    pub fn monitor_download_progress(
+        &self,
+        filename_pattern: &str,
+        poll_interval_ms: u64,
+    ) -> impl futures_util::Stream<Item = DownloadProgress> {
+        let dir = self.download_directory();
+        let downloads = self.downloads.clone();
+        let pattern = filename_pattern.to_string();
+        let interval = Duration::from_millis(poll_interval_ms.max(1));
+
+        futures_util::stream::unfold((false, true), move |(finished, first)| {
+            let (dir, downloads, pattern) = (dir.clone(), downloads.clone(), pattern.clone());
+            async move {
+                if finished {
+                    return None;
+                }
+                if !first {
+                    sleep(interval).await;
+                }
+                loop {
+                    match current_download(&downloads, &dir, &pattern) {
+                        Ok(Some((_, progress))) => {
+                            let complete = progress.complete;
+                            return Some((progress, (complete, false)));
+                        }
+                        Ok(None) => {}
+                        Err(e) => debug!("Could not read download directory: {}", e),
+                    }
+                    sleep(interval).await;
+                }
+            }
+        })
+    }
+
+    /// Wait up to `timeout_ms` for a download matching `filename_pattern` that started after
+    /// this call to complete
+    pub async fn wait_for_download(&self, filename_pattern: &str, timeout_ms: u64) -> Result<DownloadResult> {
+        let dir = self.download_directory();
+        let started = std::time::SystemTime::now();
+        // File modification times can be coarser than the clock
+        let since = started - Duration::from_secs(1);
+
+        let result = timeout(Duration::from_millis(timeout_ms), async {
+            loop {
+                let tracked = find_tracked_download(&self.downloads.lock().unwrap(), filename_pattern, Some(started));
+                let found = match tracked {
+                    Some(download) if download.state == "canceled" => {
+                        return Err(ChromeMcpError::network_error(format!("Download of {} was canceled", download.filename)));
+                    }
+                    Some(download) => Some((download.filename.clone(), download.progress())),
+                    None => scan_download_dir(&dir, filename_pattern, Some(since))?,
+                };
+                match found {
+                    Some((filename, progress)) if progress.complete => {
+                        return Ok(DownloadResult {
+                            filename,
+                            size_bytes: progress.bytes_downloaded,
+                            duration_ms: started.elapsed().map(|d| d.as_millis() as u64).unwrap_or_default(),
+                        });
+                    }
+                    _ => {}
+                }
+                sleep(Duration::from_millis(200)).await;
+            }
+        })
+        .await;
+
+        match result {
+            Ok(download) => download,
+            Err(_) => Err(ChromeMcpError::Timeout { timeout: timeout_ms }),
+        }
+    }
+
+    /// Make the element matching `selector` (the whole page when `None`) fullscreen
+    pub async fn enter_fullscreen(&mut self, selector: Option<&str>) -> Result<()> {
+        let element = match selector {
+            Some(selector) => format!("document.querySelector({})", serde_json::to_string(selector)?),
+            None => "document.documentElement".to_string(),
+        };
+        let request = EvaluateRequest::new(ENTER_FULLSCREEN_JS.replace("{element}", &element)).with_user_gesture();
+        let response = self.cdp.evaluate_typed(request).await?;
+        if let Some(exception_details) = response.exception_details {
+            return Err(ChromeMcpError::javascript_error(format!("JS Exception: {}", exception_details)));
+        }
+
+        match response.value() {
+            Some(Value::String(refused)) if refused.is_empty() => Ok(()),
+            Some(Value::String(refused)) => Err(ChromeMcpError::invalid_operation(format!("Fullscreen request refused: {}", refused))),
+            _ => Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector.unwrap_or_default()))),
+        }
+    }
+
+    /// Leave fullscreen; does nothing when the page is not fullscreen
+    pub async fn exit_fullscreen(&mut self) -> Result<()> {
+        self.cdp
+            .evaluate_js("document.fullscreenElement ? document.exitFullscreen() : undefined")
+            .await?;
+        Ok(())
+    }
+
+    /// Whether an element of the page is fullscreen
+    pub async fn is_fullscreen(&mut self) -> Result<bool> {
+        let result = self.cdp.evaluate_js("!!document.fullscreenElement").await?;
+        Ok(result.get("value").and_then(|v| v.as_bool()).unwrap_or(false))
+    }
+
+    /// CSS custom properties declared in the page's stylesheets, with their computed value on
+    /// the element matching `selector` (the root element when `None`) and the stylesheet
+    /// declaring each of them, sorted by name
+    pub async fn list_css_variables(&mut self, selector: Option<&str>) -> Result<Vec<CssVariable>> {
+        let element = match selector {
+            Some(selector) => format!("document.querySelector({})", serde_json::to_string(selector)?),
+            None => "document.documentElement".to_string(),
+        };
+        let result = self.cdp.evaluate_js(&CSS_VARIABLES_JS.replace("{element}", &element)).await?;
+
+        match result.get("value").filter(|v| !v.is_null()) {
+            Some(variables) => Ok(serde_json::from_value(variables.clone())?),
+            None => Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector.unwrap_or_default()))),
+        }
+    }
+
+    /// Computed values of the CSS custom properties on the element matching `selector`
+    /// (the root element when `None`), by name
+    pub async fn get_css_variables(&mut self, selector: Option<&str>) -> Result<HashMap<String, String>> {
+        let variables = self.list_css_variables(selector).await?;
+        Ok(variables.into_iter().map(|v| (v.name, v.value)).collect())
+    }
+
+    /// Override a CSS custom property inline on the element matching `selector`
+    pub async fn set_css_variable(&mut self, selector: &str, variable: &str, value: &str) -> Result<()> {
+        if !variable.starts_with("--") {
+            return Err(ChromeMcpError::invalid_operation(format!(
+                "Not a CSS custom property: {} (names start with --)",
+                variable
+            )));
+        }
+
+        let result = self.cdp.evaluate_js(&format!(
+            "(() => {{ const el = document.querySelector({}); if (!el) return false; el.style.setProperty({}, {}); return true; }})()",
+            serde_json::to_string(selector)?,
+            serde_json::to_string(variable)?,
+            serde_json::to_string(value)?,
+        )).await?;
+
+        match result.get("value").and_then(|v| v.as_bool()) {
+            Some(true) => Ok(()),
+            _ => Err(ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector))),
+        }
+    }
+
+    /// Open a tab, load `url` in it and read the HTTP status of the navigation
+    async fn check_link_in_fresh_tab(&mut self, url: &str, timeout_ms: u64) -> LinkCheckResult {
+        let tab_id = match self.create_tab(None).await {
+            Ok(tab_id) => tab_id,
+            Err(e) => return LinkCheckResult::failed(url, e.root_cause().to_string()),
+        };
+
+        let checked = timeout(Duration::from_millis(timeout_ms), async {
+            self.switch_to_tab(&tab_id).await?;
+            self.cdp.send_command("Network.enable", None).await?;
+            self.cdp.send_command("Page.enable", None).await?;
+            let mut responses = self.cdp.subscribe("Network.responseReceived");
+            let mut loads = self.cdp.subscribe("Page.loadEventFired");
+            let navigation = self.cdp.navigate_typed(NavigateRequest::new(url)).await?;
+            if let Some(error_text) = navigation.error_text {
+                return Ok(LinkCheckResult::failed(url, error_text));
+            }
+
+            // Status of the main document response; the load event ends the wait when none
+            // is reported, e.g. for a page restored from the back/forward cache
+            let mut status = None;
+            loop {
+                tokio::select! {
+                    Some(event) = responses.recv() => {
+                        let is_document = event.get("type").and_then(|t| t.as_str()) == Some("Document")
+                            && event.get("frameId").and_then(|f| f.as_str()) == Some(navigation.frame_id.as_str());
+                        if is_document {
+                            status = event.pointer("/response/status").and_then(|s| s.as_f64()).map(|s| s as u16);
+                            break;
+                        }
+                    }
+                    Some(_) = loads.recv() => break,
+                    else => break,
+                }
+            }
+            if status.is_none() {
+                let entry = self.cdp.evaluate_js(
+                    "performance.getEntriesByType('navigation').map(entry => entry.responseStatus)[0] || null"
+                ).await?;
+                status = entry.get("value").and_then(|v| v.as_u64()).map(|s| s as u16);
+            }
+            Ok::<_, ChromeMcpError>(LinkCheckResult::with_status(url, status))
+        }).await;
+
+        if let Err(e) = self.close_tab(&tab_id).await {
+            warn!("Could not close link check tab {}: {}", tab_id, e);
+        }
+
+        match checked {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => LinkCheckResult::failed(url, e.root_cause().to_string()),
+            Err(_) => LinkCheckResult::failed(url, format!("Timed out after {}ms", timeout_ms)),
+        }
+    }
+
+    /// List every resource loaded by the page and its frames
+    pub async fn get_resource_tree(&mut self) -> Result<Vec<PageResource>> {
+        let result = self.cdp.send_command("Page.getResourceTree", None).await?;
+
+        let frame_tree = result
+            .get("frameTree")
+            .ok_or_else(|| ChromeMcpError::cdp_protocol("No frame tree in Page.getResourceTree response"))?;
+
+        let mut resources = Vec::new();
+        collect_frame_resources(frame_tree, &mut resources);
+        Ok(resources)
+    }
+
+    /// Fetch the raw content of a loaded resource. Binary resources are returned base64-encoded.
+    pub async fn get_resource_content(&mut self, url: &str) -> Result<String> {
+        let resources = self.get_resource_tree().await?;
+        let resource = resources
+            .iter()
+            .find(|r| r.url == url)
+            .ok_or_else(|| ChromeMcpError::network_error(format!("Resource not loaded by page: {}", url)))?;
+
+        let result = self.cdp.send_command("Page.getResourceContent", Some(json!({
+            "frameId": resource.frame_id,
+            "url": url
+        }))).await?;
+
+        let content = result
+            .get("content")
+            .and_then(|c| c.as_str())
+            .ok_or_else(|| ChromeMcpError::network_error("No content in Page.getResourceContent response"))?;
+
+        let base64_encoded = result.get("base64Encoded").and_then(|b| b.as_bool()).unwrap_or(false);
+        if base64_encoded {
+            // Decode text resources that Chrome still chose to base64-encode
+            if let Some(text) = BASE64.decode(content).ok().and_then(|bytes| String::from_utf8(bytes).ok()) {
+                return Ok(text);
+            }
+        }
+
+        Ok(content.to_string())
+    }
+
+    /// HTML of the page as currently in the DOM (`DOM.getOuterHTML` of the document), including
+    /// changes made by JavaScript since it loaded
+    pub async fn get_page_source(&mut self) -> Result<String> {
+        let root = self.cdp.get_document_typed().await?.root;
+        let result = self.cdp.send_command("DOM.getOuterHTML", Some(json!({
+            "nodeId": root.node_id
+        }))).await?;
+
+        result
+            .get("outerHTML")
+            .and_then(|html| html.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| ChromeMcpError::cdp_protocol("No outerHTML in DOM.getOuterHTML response"))
+    }
+
+    /// HTML of the main document as the server sent it, before any script ran. The request
+    /// of a frame's document has the ID of the frame's loader, which `Page.getFrameTree` gives.
+    pub async fn get_network_source(&mut self) -> Result<String> {
+        let tree = self.cdp.send_command("Page.getFrameTree", None).await?;
+        let loader_id = tree
+            .pointer("/frameTree/frame/loaderId")
+            .and_then(|id| id.as_str())
+            .ok_or_else(|| ChromeMcpError::cdp_protocol("No main frame loader in Page.getFrameTree response"))?;
+
+        let result = self.cdp.send_command("Network.getResponseBody", Some(json!({
+            "requestId": loader_id
+        }))).await.map_err(|e| e.in_frame("browser", "get_network_source").with_context("request_id", loader_id))?;
+
+        let body = result
+            .get("body")
+            .and_then(|b| b.as_str())
+            .ok_or_else(|| ChromeMcpError::network_error("No body in Network.getResponseBody response"))?;
+        if result.get("base64Encoded").and_then(|b| b.as_bool()).unwrap_or(false) {
+            let bytes = BASE64
+                .decode(body)
+                .map_err(|e| ChromeMcpError::network_error(format!("Invalid base64 document body: {}", e)))?;
+            // Known when the document's response was captured
+            let content_type = self.network_events.lock().unwrap().iter().find(|e| e.request_id == loader_id).and_then(|event| {
+                event.response_headers.as_ref()?.iter().find(|(name, _)| name.eq_ignore_ascii_case("content-type")).map(|(_, value)| value.clone())
+            });
+            return Ok(decode_html_bytes(&bytes, content_type.as_deref()));
+        }
+        Ok(body.to_string())
+    }
+
+    /// Record a `Network.*` event in the request log read by `get_response` and the request
+    /// wait conditions, which feed it while waiting. Returns whether the event was a network
+    /// request event.
+    pub fn handle_network_event(&mut self, method: &str, params: &Value) -> bool {
+        record_network_event(&mut self.network_events.lock().unwrap(), method, params)
+    }
+
+    /// Fetch the response body of a captured request and decode it by content type
+    pub async fn get_response(&mut self, request_id: &str) -> Result<NetworkEvent> {
+        debug!("Getting response body for request {}", request_id);
+        fetch_response_body(&mut self.cdp, &self.network_events, request_id, None).await
+    }
+
+    /// Record every network request and response of the connected tab, replacing what was
+    /// captured before and dropping the oldest requests beyond `max_entries`. With
+    /// `capture_body`, response bodies of at most `max_body_bytes` are fetched as soon as they
+    /// finish loading; larger ones are left to `get_response`.
+    pub async fn start_network_capture(&mut self, capture_body: bool, max_entries: usize, max_body_bytes: usize) -> Result<()> {
+        self.stop_network_capture();
+        self.network_events.lock().unwrap().clear();
+        debug!("Capturing network traffic (at most {} requests, bodies: {})", max_entries, capture_body);
+
+        let mut cdp = self.cdp.clone();
+        let [mut requests, mut responses, mut finished, mut failed] = NETWORK_EVENT_METHODS.map(|method| cdp.subscribe(method));
+        let events = self.network_events.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                // Polled in order so that a request is recorded before its response and completion
+                let (method, params) = tokio::select! {
+                    biased;
+                    Some(params) = requests.recv() => (NETWORK_EVENT_METHODS[0], params),
+                    Some(params) = responses.recv() => (NETWORK_EVENT_METHODS[1], params),
+                    Some(params) = finished.recv() => (NETWORK_EVENT_METHODS[2], params),
+                    Some(params) = failed.recv() => (NETWORK_EVENT_METHODS[3], params),
+                    else => break,
+                };
+                let recorded = {
+                    let mut events = events.lock().unwrap();
+                    let recorded = record_network_event(&mut events, method, &params);
+                    if events.len() > max_entries {
+                        let excess = events.len() - max_entries;
+                        events.drain(..excess);
+                    }
+                    recorded
+                };
+                if !(recorded && capture_body && method == "Network.loadingFinished") {
+                    continue;
+                }
+                let request_id = params.get("requestId").and_then(|id| id.as_str()).unwrap_or_default();
+                // Transfer size, which compressed bodies exceed once decoded; checked again after fetching
+                let encoded_length = params.get("encodedDataLength").and_then(|length| length.as_f64()).unwrap_or(0.0);
+                if encoded_length > max_body_bytes as f64 {
+                    debug!("Not capturing response body of request {} ({} bytes)", request_id, encoded_length);
+                    continue;
+                }
+                if let Err(e) = fetch_response_body(&mut cdp, &events, request_id, Some(max_body_bytes)).await {
+                    warn!("Could not capture response body of request {}: {}", request_id, e);
+                }
+            }
+        });
+        self.network_capture = Some(task.abort_handle());
+        Ok(())
+    }
+
+    /// Stop recording network traffic; captured requests are kept
+    pub fn stop_network_capture(&mut self) {
+        // Aborting the task drops its subscriptions, leaving other subscribers alone
+        if let Some(task) = self.network_capture.take() {
+            task.abort();
+        }
+    }
+
+    /// Captured requests in the order they were sent, optionally only those whose URL matches
+    /// the regular expression `url_pattern`
+    pub fn get_network_events(&self, url_pattern: Option<&str>) -> Result<Vec<NetworkEvent>> {
+        let regex = url_pattern
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| ChromeMcpError::invalid_operation(format!("Invalid URL pattern: {}", e)))?;
+        Ok(self.network_events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| regex.as_ref().is_none_or(|regex| regex.is_match(&event.url)))
+            .cloned()
+            .collect())
+    }
+
+    /// Collect the IP addresses WebRTC exposes through ICE candidates
+    pub async fn detect_webrtc_ips(&mut self) -> Result<Vec<String>> {
+        debug!("Detecting WebRTC-exposed IP addresses");
+
+        let result = self.cdp.evaluate_js(
+            r#"
+            new Promise((resolve, reject) => {
+                const Peer = window.RTCPeerConnection || window.webkitRTCPeerConnection;
+                if (!Peer) {
+                    resolve([]);
+                    return;
+                }
+                const candidates = [];
+                const pc = new Peer({ iceServers: [{ urls: 'stun:stun.l.google.com:19302' }] });
+                const finish = () => {
+                    pc.close();
+                    resolve(candidates);
+                };
+                pc.onicecandidate = (event) => {
+                    if (event.candidate && event.candidate.candidate) {
+                        candidates.push(event.candidate.candidate);
+                    }
+                };
+                pc.onicegatheringstatechange = () => {
+                    if (pc.iceGatheringState === 'complete') finish();
+                };
+                setTimeout(finish, 5000);
+                pc.createDataChannel('chrome-mcp');
+                pc.createOffer()
+                    .then(offer => pc.setLocalDescription(offer))
+                    .catch(reject);
+            })
+            "#,
+        ).await?;
+
+        let candidates: Vec<String> = serde_json::from_value(result.get("value").cloned().unwrap_or(json!([])))?;
+
+        let mut ips: Vec<String> = candidates.iter().flat_map(|c| parse_ice_candidate_ips(c)).collect();
+        ips.sort();
+        ips.dedup();
+        Ok(ips)
+    }
+
+    /// Remove the WebRTC APIs from the current page and every page loaded afterwards
+    pub async fn disable_webrtc(&mut self) -> Result<()> {
+        info!("Disabling WebRTC");
+        self.add_init_script(DISABLE_WEBRTC_JS).await?;
+        self.cdp.evaluate_js(DISABLE_WEBRTC_JS).await?;
+        Ok(())
+    }
+
+    /// Override the page timezone with an IANA identifier (e.g. `America/New_York`)
+    pub async fn set_timezone(&mut self, tz: &str) -> Result<()> {
+        if !is_valid_timezone(tz) {
+            return Err(ChromeMcpError::invalid_operation(format!("Unknown IANA timezone: {}", tz)));
+        }
+
+        info!("Setting timezone override: {}", tz);
+        self.cdp.send_command("Emulation.setTimezoneOverride", Some(json!({
+            "timezoneId": tz
+        }))).await?;
+        Ok(())
+    }
+
+    /// Override the page locale (e.g. `de-DE`)
+    pub async fn set_locale(&mut self, locale: &str) -> Result<()> {
+        info!("Setting locale override: {}", locale);
+        self.cdp.send_command("Emulation.setLocaleOverride", Some(json!({
+            "locale": locale
+        }))).await?;
+        Ok(())
+    }
+
+    /// Restore the system locale and timezone
+    pub async fn reset_locale_and_timezone(&mut self) -> Result<()> {
+        // An empty timezone id and a missing locale clear the respective overrides
+        self.cdp.send_command("Emulation.setTimezoneOverride", Some(json!({
+            "timezoneId": ""
+        }))).await?;
+        self.cdp.send_command("Emulation.setLocaleOverride", Some(json!({}))).await?;
+        Ok(())
+    }
+
+    /// Report (`lat`, `lon`) with `accuracy` meters to the Geolocation API
+    pub async fn set_geolocation(&mut self, lat: f64, lon: f64, accuracy: f64) -> Result<()> {
+        if !(-90.0..=90.0).contains(&lat) {
+            return Err(ChromeMcpError::invalid_operation(format!("Latitude {} is outside [-90, 90]", lat)));
+        }
+        if !(-180.0..=180.0).contains(&lon) {
+            return Err(ChromeMcpError::invalid_operation(format!("Longitude {} is outside [-180, 180]", lon)));
+        }
+        if accuracy.is_nan() || accuracy < 0.0 {
+            return Err(ChromeMcpError::invalid_operation(format!("Accuracy {} must not be negative", accuracy)));
+        }
+
+        info!("Setting geolocation override: {}, {} (±{}m)", lat, lon, accuracy);
+        self.cdp.send_command("Emulation.setGeolocationOverride", Some(json!({
+            "latitude": lat,
+            "longitude": lon,
+            "accuracy": accuracy
+        }))).await?;
+        Ok(())
+    }
+
+    /// Report the real position again
+    pub async fn clear_geolocation(&mut self) -> Result<()> {
+        self.cdp.send_command("Emulation.clearGeolocationOverride", None).await?;
+        Ok(())
+    }
+
+    /// Grant geolocation to every origin, so pages get positions without a prompt
+    /// (unlike `grant_permission`, this also covers pages navigated to later)
+    pub async fn grant_geolocation_permission(&mut self) -> Result<()> {
+        self.cdp.send_command("Browser.setPermission", Some(json!({
+            "permission": { "name": BrowserPermission::Geolocation.name() },
+            "setting": "granted"
+        }))).await?;
+        Ok(())
+    }
+
+    /// Start CPU profiling with the given sampling interval
+    pub async fn start_cpu_profile(&mut self, sample_interval_us: u32) -> Result<()> {
+        debug!("Starting CPU profile (sampling every {}us)", sample_interval_us);
+
+        self.enable_domain("Profiler", None).await?;
+        self.cdp.send_command("Profiler.setSamplingInterval", Some(json!({
+            "interval": sample_interval_us
+        }))).await?;
+        self.cdp.send_command("Profiler.start", None).await?;
+        Ok(())
+    }
+
+    /// Stop CPU profiling and return the recorded profile
+    pub async fn stop_cpu_profile(&mut self) -> Result<CpuProfile> {
+        let result = self.cdp.send_command("Profiler.stop", None).await?;
+        self.disable_domain("Profiler").await?;
+
+        let profile: CpuProfile = serde_json::from_value(
+            result.get("profile").cloned()
+                .ok_or_else(|| ChromeMcpError::cdp_protocol("Profiler.stop returned no profile"))?
+        )?;
+
+        self.last_cpu_profile = Some(profile.clone());
+        Ok(profile)
+    }
+
+    /// Most recent profile returned by `stop_cpu_profile`
+    pub fn last_cpu_profile(&self) -> Option<&CpuProfile> {
+        self.last_cpu_profile.as_ref()
+    }
+
+    /// Write a CPU profile to disk as a `.cpuprofile` JSON file
+    pub fn save_cpu_profile(&self, profile: &CpuProfile, filename: &str) -> Result<()> {
+        std::fs::write(filename, serde_json::to_string(profile)?)?;
+        Ok(())
+    }
+
+    /// Search the page text with a regular expression.
+    /// Text of the main document is searched in the live DOM; child frames are
+    /// searched in their document source via `Page.searchInResource`.
+    pub async fn search_page_text(&mut self, pattern: &str, case_sensitive: bool) -> Result<Vec<TextMatch>> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|e| ChromeMcpError::invalid_operation(format!("Invalid search pattern: {}", e)))?;
+
+        let documents: Vec<PageResource> = self
+            .get_resource_tree()
+            .await?
+            .into_iter()
+            .filter(|r| r.type_ == ResourceType::Document)
+            .collect();
+
+        let main_frame_id = documents.first().map(|d| d.frame_id.clone()).unwrap_or_default();
+        let mut matches = Vec::new();
+
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const cssPath = {};
+                const root = document.body || document.documentElement;
+                const walker = document.createTreeWalker(root, NodeFilter.SHOW_TEXT, {{
+                    acceptNode: (node) => {{
+                        const tag = node.parentElement && node.parentElement.tagName;
+                        if (tag === 'SCRIPT' || tag === 'STYLE' || tag === 'NOSCRIPT') return NodeFilter.FILTER_REJECT;
+                        return node.textContent.trim() ? NodeFilter.FILTER_ACCEPT : NodeFilter.FILTER_REJECT;
+                    }}
+                }});
+                const nodes = [];
+                while (walker.nextNode()) {{
+                    nodes.push({{ text: walker.currentNode.textContent, selector: cssPath(walker.currentNode.parentElement) }});
+                }}
+                return nodes;
+            }})()
+            "#,
+            CSS_PATH_JS
+        )).await?;
+
+        if let Some(nodes) = result.get("value").and_then(|v| v.as_array()) {
+            for node in nodes {
+                let text = node.get("text").and_then(|t| t.as_str()).unwrap_or("");
+                let selector = node.get("selector").and_then(|s| s.as_str()).map(|s| s.to_string());
+                matches.extend(find_text_matches(&regex, text, &main_frame_id, None, selector));
+            }
+        }
+
+        for document in documents.iter().skip(1) {
+            let result = self.cdp.send_command("Page.searchInResource", Some(json!({
+                "frameId": document.frame_id,
+                "url": document.url,
+                "query": pattern,
+                "caseSensitive": case_sensitive,
+                "isRegex": true
+            }))).await;
+
+            // Frames can disappear or refuse the search; skip them rather than failing the whole search
+            let lines = match result {
+                Ok(result) => result.get("result").and_then(|r| r.as_array()).cloned().unwrap_or_default(),
+                Err(e) => {
+                    debug!("Could not search frame {}: {}", document.frame_id, e);
+                    continue;
+                }
+            };
+
+            for line in lines {
+                let content = line.get("lineContent").and_then(|c| c.as_str()).unwrap_or("");
+                let line_number = line.get("lineNumber").and_then(|n| n.as_f64()).map(|n| n as u32 + 1);
+                matches.extend(find_text_matches(&regex, content, &document.frame_id, line_number, None));
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// List the `rect`, `circle`, `path`, `text` and `g` elements of an inline SVG
+    pub async fn get_svg_elements(&mut self, svg_selector: &str) -> Result<Vec<SvgElement>> {
+        debug!("Getting SVG elements: {}", svg_selector);
+
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const svg = document.querySelector('{}');
+                if (!svg || !(svg instanceof SVGElement)) return null;
+                return Array.from(svg.querySelectorAll('rect, circle, path, text, g')).map(el => {{
+                    let bounds = null;
+                    try {{
+                        const box = el.getBBox();
+                        bounds = [box.x, box.y, box.width, box.height];
+                    }} catch (e) {{}}
+                    const text = (el.textContent || '').trim();
+                    return {{
+                        tag: el.tagName.toLowerCase(),
+                        id: el.id || null,
+                        class: el.getAttribute('class'),
+                        bounds,
+                        text: text || null,
+                        path_data: el.tagName.toLowerCase() === 'path' ? el.getAttribute('d') : null
+                    }};
+                }});
+            }})()
+            "#,
+            svg_selector.replace("'", "\\'")
+        )).await?;
+
+        match result.get("value") {
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value.clone())?),
+            _ => Err(ChromeMcpError::element_not_found(format!("No SVG found for selector: {}", svg_selector))),
+        }
+    }
+
+    /// Extract the text of every `<text>` element (including `<tspan>` content) in an inline SVG
+    pub async fn get_svg_text_content(&mut self, svg_selector: &str) -> Result<Vec<String>> {
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const svg = document.querySelector('{}');
+                if (!svg || !(svg instanceof SVGElement)) return null;
+                return Array.from(svg.querySelectorAll('text'))
+                    .map(el => el.textContent.replace(/\s+/g, ' ').trim())
+                    .filter(text => text);
+            }})()
+            "#,
+            svg_selector.replace("'", "\\'")
+        )).await?;
+
+        match result.get("value") {
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value.clone())?),
+            _ => Err(ChromeMcpError::element_not_found(format!("No SVG found for selector: {}", svg_selector))),
+        }
+    }
+
+    /// Read the RGBA pixels of a canvas region, row by row
+    pub async fn read_canvas_pixels(&mut self, selector: &str, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<[u8; 4]>> {
+        debug!("Reading {}x{} canvas pixels at ({}, {}) from {}", width, height, x, y, selector);
+
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const canvas = document.querySelector('{}');
+                if (!canvas || canvas.tagName !== 'CANVAS') return null;
+                let ctx = canvas.getContext('2d');
+                if (!ctx) {{
+                    // WebGL canvases have no 2d context; copy their content onto one
+                    const copy = document.createElement('canvas');
+                    copy.width = canvas.width;
+                    copy.height = canvas.height;
+                    ctx = copy.getContext('2d');
+                    ctx.drawImage(canvas, 0, 0);
+                }}
+                return Array.from(ctx.getImageData({}, {}, {}, {}).data);
+            }})()
+            "#,
+            selector.replace("'", "\\'"),
+            x, y, width.max(1), height.max(1)
+        )).await?;
+
+        let data: Vec<u8> = match result.get("value") {
+            Some(value) if !value.is_null() => serde_json::from_value(value.clone())?,
+            _ => return Err(ChromeMcpError::element_not_found(format!("No canvas found for selector: {}", selector))),
+        };
+
+        Ok(rgba_pixels(&data))
+    }
+
+    /// Export a canvas as a PNG data URL
+    pub async fn get_canvas_data_url(&mut self, selector: &str) -> Result<String> {
+        let result = self.cdp.evaluate_js(&format!(
+            "(() => {{ const canvas = document.querySelector('{}'); return canvas && canvas.toDataURL ? canvas.toDataURL() : null; }})()",
+            selector.replace("'", "\\'")
+        )).await?;
+
+        result
+            .get("value")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| ChromeMcpError::element_not_found(format!("No canvas found for selector: {}", selector)))
+    }
+
+    /// Timing breakdown of the current document's navigation
+    pub async fn get_navigation_timing(&mut self) -> Result<NavigationTiming> {
+        let result = self.cdp.evaluate_js(
+            "(() => { const entry = performance.getEntriesByType('navigation')[0]; return entry ? entry.toJSON() : null; })()"
+        ).await?;
+
+        match result.get("value") {
+            Some(entry) if !entry.is_null() => Ok(navigation_timing_from_entry(entry)),
+            _ => Err(ChromeMcpError::invalid_operation("No navigation timing entry for the current page")),
+        }
+    }
+
+    /// Timing of every resource fetched by the current document, in fetch order
+    pub async fn get_resource_timings(&mut self) -> Result<Vec<ResourceTiming>> {
+        let result = self.cdp.evaluate_js(
+            "performance.getEntriesByType('resource').map(entry => entry.toJSON())"
+        ).await?;
+
+        Ok(result
+            .get("value")
+            .and_then(|v| v.as_array())
+            .map(|entries| entries.iter().map(resource_timing_from_entry).collect())
+            .unwrap_or_default())
+    }
+
+    /// Find the scripts and stylesheets that block the first render of the page
+    pub async fn get_render_blocking_resources(&mut self) -> Result<Vec<RenderBlockingResource>> {
+        debug!("Looking for render-blocking resources");
+
+        let script = RENDER_BLOCKING_JS.replace("{selector}", &serde_json::to_string(RENDER_BLOCKING_SELECTOR)?);
+        let result = self.cdp.evaluate_js(&script).await?;
+
+        let mut resources: Vec<RenderBlockingResource> = serde_json::from_value(result.get("value").cloned().unwrap_or(json!([])))?;
+        for resource in &mut resources {
+            resource.recommendation = match resource.type_ {
+                RenderBlockingType::Script => "Add `defer` (or `async` if no other script depends on it), or load it at the end of <body>",
+                RenderBlockingType::Stylesheet => "Inline the critical CSS and load the rest asynchronously, or add a `media` query if it only applies to some devices",
+            }.to_string();
+        }
+        Ok(resources)
+    }
+
+    /// Add `defer` to the parser-blocking head scripts of the current DOM and return how many
+    /// were changed. Scripts that already ran are not re-executed; this only affects the live
+    /// markup, e.g. to inspect or export a fixed version of the page.
+    pub async fn defer_render_blocking_scripts(&mut self) -> Result<usize> {
+        let result = self.cdp.evaluate_js(
+            r#"(() => {
+                const scripts = document.querySelectorAll('head script[src]:not([async]):not([defer]):not([type="module"])');
+                scripts.forEach(script => script.defer = true);
+                return scripts.length;
+            })()"#
+        ).await?;
+
+        Ok(result.get("value").and_then(|v| v.as_u64()).unwrap_or(0) as usize)
+    }
+
+    /// Find the JavaScript libraries on the page, from their globals and script URLs, and
+    /// flag the ones loaded twice or in conflicting versions
+    pub async fn analyze_js_bundles(&mut self) -> Result<BundleAnalysis> {
+        debug!("Analyzing JavaScript bundles");
+
+        let result = self.cdp.evaluate_js(JS_BUNDLES_JS).await?;
+        let value = result.get("value").cloned().unwrap_or(Value::Null);
+
+        let mut detected_libraries = Vec::new();
+        for global in value.get("globals").and_then(|g| g.as_array()).into_iter().flatten() {
+            detected_libraries.push(LibraryInfo {
+                name: global.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string(),
+                version: global.get("version").and_then(|v| v.as_str()).filter(|v| !v.is_empty()).map(|v| v.to_string()),
+                global: global.get("global").and_then(|g| g.as_str()).map(|g| g.to_string()),
+                script_url: None,
+            });
+        }
+
+        let mut script_urls = Vec::new();
+        let mut sizes = HashMap::new();
+        let mut total_script_size_bytes = value.get("inlineBytes").and_then(|b| b.as_u64()).unwrap_or(0);
+        for script in value.get("scripts").and_then(|s| s.as_array()).into_iter().flatten() {
+            let Some(url) = script.get("url").and_then(|u| u.as_str()) else { continue };
+            script_urls.push(url.to_string());
+            if !sizes.contains_key(url) {
+                let size = script.get("size").and_then(|s| s.as_u64()).unwrap_or(0);
+                total_script_size_bytes += size;
+                sizes.insert(url.to_string(), size);
+            }
+            if let Some((name, version)) = library_from_script_url(url) {
+                detected_libraries.push(LibraryInfo { name, version, global: None, script_url: Some(url.to_string()) });
+            }
+        }
+
+        let duplicates = find_duplicate_libraries(&detected_libraries, &script_urls);
+        Ok(BundleAnalysis { detected_libraries, duplicates, total_script_size_bytes })
+    }
+
+    /// Check the page against common SEO rules: title and meta description length, heading
+    /// structure, image alt texts, robots directives, canonical link, mobile viewport and
+    /// load speed (from `Performance.getMetrics`)
+    pub async fn run_seo_audit(&mut self) -> Result<SeoAudit> {
+        debug!("Running SEO audit");
+
+        let result = self.cdp.evaluate_js(SEO_FACTS_JS).await?;
+        let facts: SeoFacts = serde_json::from_value(result.get("value").cloned().unwrap_or(Value::Null))?;
+
+        self.enable_domain("Performance", None).await?;
+        let metrics = self.cdp.send_command("Performance.getMetrics", None).await;
+        self.disable_domain("Performance").await?;
+        let metric = |name: &str| {
+            metrics.as_ref().ok()?
+                .get("metrics")?
+                .as_array()?
+                .iter()
+                .find(|m| m.get("name").and_then(|n| n.as_str()) == Some(name))?
+                .get("value")?
+                .as_f64()
+        };
+        // Both are timestamps in seconds; DomContentLoaded is 0 until the event fires
+        let dom_content_loaded_ms = match (metric("NavigationStart"), metric("DomContentLoaded")) {
+            (Some(start), Some(loaded)) if loaded > 0.0 => Some((loaded - start) * 1000.0),
+            _ => None,
+        };
+
+        let mut checks = seo_checks(&facts, dom_content_loaded_ms);
+        checks.sort_by_key(|check| check.severity);
+        Ok(SeoAudit { checks })
+    }
+
+    /// Main content of the page as Markdown: the `<main>` element, else the first
+    /// `<article>`, else the body
+    pub async fn get_page_as_markdown(&mut self, include_images: bool) -> Result<String> {
+        self.content_as_markdown(
+            "document.querySelector('main') || document.querySelector('article') || document.body",
+            include_images,
+        )
+        .await
+        .map(|markdown| markdown.unwrap_or_default())
+    }
+
+    /// Content of the element matching a selector as Markdown
+    pub async fn get_element_as_markdown(&mut self, selector: &str, include_images: bool) -> Result<String> {
+        debug!("Converting {} to Markdown", selector);
+
+        let element = format!("document.querySelector({})", serde_json::to_string(selector)?);
+        self.content_as_markdown(&element, include_images)
+            .await?
+            .ok_or_else(|| ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector)))
+    }
+
+    /// Markdown of the element a JavaScript expression evaluates to, `None` if it is null
+    async fn content_as_markdown(&mut self, element: &str, include_images: bool) -> Result<Option<String>> {
+        let result = self.cdp.evaluate_js(&CONTENT_HTML_JS.replace("{element}", element)).await?;
+
+        let Some(content) = result.get("value").filter(|v| !v.is_null()) else {
+            return Ok(None);
+        };
+        let html = content.get("html").and_then(|h| h.as_str()).unwrap_or_default();
+        let base_url = content.get("baseUrl").and_then(|u| u.as_str());
+        Ok(Some(html_to_markdown(html, base_url, include_images)))
+    }
+
+    /// Snapshot of the `outerHTML` of the element matching `scope_selector`, or of the whole
+    /// document, to diff against a later one with `DomSnapshot::diff`
+    pub async fn take_dom_snapshot(&mut self, scope_selector: Option<&str>) -> Result<DomSnapshot> {
+        let element = match scope_selector {
+            Some(selector) => format!("document.querySelector({})", serde_json::to_string(selector)?),
+            None => "document.documentElement".to_string(),
+        };
+        let result = self.cdp.evaluate_js(&CONTENT_HTML_JS.replace("{element}", &element)).await?;
+
+        result.get("value")
+            .and_then(|content| content.get("html"))
+            .and_then(|html| html.as_str())
+            .map(DomSnapshot::from_outer_html)
+            .ok_or_else(|| match scope_selector {
+                Some(selector) => ChromeMcpError::element_not_found(format!("No element found for selector: {}", selector)),
+                None => ChromeMcpError::element_not_found("The page has no document element"),
+            })
+    }
+
+    /// Extract the contents of the table matching a selector
+    pub async fn extract_table(&mut self, selector: &str) -> Result<TableData> {
+        debug!("Extracting table: {}", selector);
+
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (() => {{
+                const table = document.querySelector('{}');
+                if (!table || table.tagName !== 'TABLE') return null;
+                return ({})(table);
+            }})()
+            "#,
+            selector.replace("'", "\\'"),
+            TABLE_EXTRACTOR_JS
+        )).await?;
+
+        match result.get("value") {
+            Some(value) if !value.is_null() => Ok(serde_json::from_value(value.clone())?),
+            _ => Err(ChromeMcpError::element_not_found(format!("No table found for selector: {}", selector))),
+        }
+    }
+
+    /// Extract the contents of every table on the page
+    pub async fn extract_tables(&mut self) -> Result<Vec<TableData>> {
+        let result = self.cdp.evaluate_js(&format!(
+            "Array.from(document.querySelectorAll('table')).map({})",
+            TABLE_EXTRACTOR_JS
+        )).await?;
+
+        Ok(serde_json::from_value(result.get("value").cloned().unwrap_or(json!([])))?)
+    }
+
+    /// Serve a canned response for every request matching `url_pattern`
+    pub async fn mock_api_response(&mut self, url_pattern: &str, status_code: u16, response_body: &str, content_type: &str) -> Result<()> {
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), content_type.to_string());
+
+        self.add_mock(MockResponse {
+            url_pattern: url_pattern.to_string(),
+            status: status_code,
+            headers,
+            body: response_body.to_string(),
+        });
+        self.sync_fetch_interception().await
+    }
+
+    /// Register all mocks from a JSON fixture file mapping URL patterns to `{status, headers, body}`
+    pub async fn mock_api_responses_from_fixture(&mut self, fixture_file: &str) -> Result<usize> {
+        let content = std::fs::read_to_string(fixture_file)?;
+        let fixture: HashMap<String, MockFixtureEntry> = serde_json::from_str(&content)?;
+        let count = fixture.len();
+
+        for (url_pattern, entry) in fixture {
+            let body = match entry.body {
+                Value::String(text) => text,
+                Value::Null => String::new(),
+                other => serde_json::to_string(&other)?,
+            };
+
+            self.add_mock(MockResponse {
+                url_pattern,
+                status: entry.status,
+                headers: entry.headers,
+                body,
+            });
+        }
+
+        info!("Loaded {} mocks from {}", count, fixture_file);
+        self.sync_fetch_interception().await?;
+        Ok(count)
+    }
+
+    /// Remove the mock registered for `url_pattern`, returns whether one existed
+    pub async fn clear_mock(&mut self, url_pattern: &str) -> Result<bool> {
+        let removed = {
+            let mut mocks = self.mocks.lock().unwrap();
+            let before = mocks.len();
+            mocks.retain(|m| m.url_pattern != url_pattern);
+            mocks.len() != before
+        };
+
+        if removed {
+            self.sync_fetch_interception().await?;
+        }
+        Ok(removed)
+    }
+
+    /// Remove all registered mocks and stop request interception
+    pub async fn clear_all_mocks(&mut self) -> Result<()> {
+        self.mocks.lock().unwrap().clear();
+        self.sync_fetch_interception().await
+    }
+
+    /// Currently registered mocks
+    pub fn list_mocks(&self) -> Vec<MockResponse> {
+        self.mocks.lock().unwrap().clone()
+    }
+
+    /// Store credentials for HTTP authentication challenges, keyed by `(host, realm)`.
+    /// `None` for host or realm matches any host/realm.
+    pub async fn set_http_auth_credentials(&mut self, username: &str, password: &str, host: Option<&str>, realm: Option<&str>) -> Result<()> {
+        debug!("Setting HTTP auth credentials for host {:?}, realm {:?}", host, realm);
+
+        self.http_auth.lock().unwrap().insert(
+            (host.map_or("*", unbracket_host).to_string(), realm.unwrap_or("*").to_string()),
+            HttpAuthCredentials {
+                username: username.to_string(),
+                password: password.to_string(),
+            },
+        );
+        self.sync_fetch_interception().await
+    }
+
+    /// Forget all stored HTTP authentication credentials
+    pub async fn clear_http_auth_credentials(&mut self) -> Result<()> {
+        self.http_auth.lock().unwrap().clear();
+        self.sync_fetch_interception().await
+    }
+
+    /// Text in the element matching `selector`, read from a screenshot of it with tesseract.js
+    pub async fn ocr_element(&mut self, selector: &str) -> Result<String> {
+        debug!("Running OCR on element: {}", selector);
+        let png_b64 = self.screenshot.capture_element(selector).await?;
+        self.ocr_image(&png_b64)
+            .await
+            .map_err(|e| e.in_frame("browser", "ocr_element").with_context("selector", selector))
+    }
+
+    /// Text in a rectangle of the page (CSS pixels), read from a screenshot of it with tesseract.js
+    pub async fn ocr_region(&mut self, x: f64, y: f64, width: f64, height: f64) -> Result<String> {
+        debug!("Running OCR on region {}x{} at ({}, {})", width, height, x, y);
+        let png_b64 = self.screenshot.capture_region(x, y, width, height).await?;
+        self.ocr_image(&png_b64).await.map_err(|e| e.in_frame("browser", "ocr_region"))
+    }
+
+    /// Recognize the text of a base64 PNG in the page, loading tesseract.js first if needed
+    async fn ocr_image(&mut self, png_b64: &str) -> Result<String> {
+        let loader = format!(
+            r#"
+            new Promise((resolve, reject) => {{
+                if (window.Tesseract) {{
+                    resolve(true);
+                    return;
+                }}
+                const script = document.createElement('script');
+                script.src = '{}';
+                script.onload = () => resolve(true);
+                script.onerror = () => reject(new Error('Failed to load tesseract.js'));
+                (document.head || document.documentElement).appendChild(script);
+            }})
+            "#,
+            TESSERACT_SCRIPT_URL
+        );
+
+        let loaded = self.cdp.evaluate_js("typeof window.Tesseract !== 'undefined'").await?;
+        if loaded.get("value").and_then(|v| v.as_bool()) != Some(true) {
+            // Keep tesseract.js available on documents loaded later, then load it into the current one
+            if !self.injected_scripts.iter().any(|(_, source)| *source == loader) {
+                self.add_init_script(&loader).await?;
+            }
+            self.cdp.evaluate_js(&loader).await?;
+        }
+
+        let result = self.cdp.evaluate_js(&format!(
+            "Tesseract.recognize('data:image/png;base64,{}', '{}').then(result => result.data.text)",
+            png_b64.trim(),
+            OCR_LANGUAGE
+        )).await?;
+
+        result
+            .get("value")
+            .and_then(|v| v.as_str())
+            .map(|text| text.trim().to_string())
+            .ok_or_else(|| ChromeMcpError::javascript_error("tesseract.js returned no text"))
+    }
+
+    /// Extract the text content of a base64-encoded PDF using pdf.js
+    pub async fn extract_pdf_text(&mut self, pdf_b64: &str, page_separator: Option<&str>) -> Result<String> {
+        debug!("Extracting text from PDF ({} base64 chars)", pdf_b64.len());
+
+        let loader = format!(
+            r#"
+            new Promise((resolve, reject) => {{
+                if (window.pdfjsLib) {{
+                    resolve(true);
+                    return;
+                }}
+                const script = document.createElement('script');
+                script.src = '{}';
+                script.onload = () => resolve(true);
+                script.onerror = () => reject(new Error('Failed to load pdf.js'));
+                (document.head || document.documentElement).appendChild(script);
+            }})
+            "#,
+            PDFJS_SCRIPT_URL
+        );
+
+        let loaded = self.cdp.evaluate_js("typeof window.pdfjsLib !== 'undefined'").await?;
+        if loaded.get("value").and_then(|v| v.as_bool()) != Some(true) {
+            // Keep pdf.js available on documents loaded later, then load it into the current one
+            self.add_init_script(&loader).await?;
+            self.cdp.evaluate_js(&loader).await?;
+        }
+
+        let separator = page_separator.unwrap_or("\n\n");
+        let result = self.cdp.evaluate_js(&format!(
+            r#"
+            (async () => {{
+                pdfjsLib.GlobalWorkerOptions.workerSrc = '{}';
+                const binary = atob('{}');
+                const bytes = new Uint8Array(binary.length);
+                for (let i = 0; i < binary.length; i++) {{
+                    bytes[i] = binary.charCodeAt(i);
+                }}
+                const url = URL.createObjectURL(new Blob([bytes], {{ type: 'application/pdf' }}));
+                try {{
+                    const pdf = await pdfjsLib.getDocument(url).promise;
+                    const pages = [];
+                    for (let n = 1; n <= pdf.numPages; n++) {{
+                        const page = await pdf.getPage(n);
+                        const content = await page.getTextContent();
+                        pages.push(content.items.map(item => item.str).join(' '));
+                    }}
+                    return pages.join({});
+                }} finally {{
+                    URL.revokeObjectURL(url);
+                }}
+            }})()
+            "#,
+            PDFJS_WORKER_URL,
+            pdf_b64.trim(),
+            serde_json::to_string(separator)?
+        )).await?;
+
+        result
+            .get("value")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| ChromeMcpError::javascript_error("pdf.js returned no text"))
+    }
+
+    // Private helper methods
+
+    fn add_mock(&mut self, mock: MockResponse) {
+        let mut mocks = self.mocks.lock().unwrap();
+        mocks.retain(|m| m.url_pattern != mock.url_pattern);
+        mocks.push(mock);
+    }
+
+    /// Intercept the requests matching a registered mock, and answer authentication
+    /// challenges while credentials are stored, on a dedicated session whose events a
+    /// background task answers. Interception ends with its session, so each change
+    /// replaces the session.
+    async fn sync_fetch_interception(&mut self) -> Result<()> {
+        let mocks = self.list_mocks();
+        let handle_auth = !self.http_auth.lock().unwrap().is_empty();
+        if mocks.is_empty() && !handle_auth {
+            if let Some(task) = self.fetch_handler.take() {
+                task.abort();
+            }
+            return Ok(());
+        }
+
+        // Auth challenges are only reported for intercepted requests, so intercept everything
+        let patterns: Vec<Value> = if !handle_auth {
+            mocks
+                .iter()
+                .map(|m| json!({ "urlPattern": m.url_pattern, "requestStage": "Request" }))
+                .collect()
+        } else {
+            vec![json!({ "urlPattern": "*", "requestStage": "Request" })]
+        };
+
+        let mut session = self.cdp.open_session().await?;
+        session.send_command("Fetch.enable", Some(json!({
+            "patterns": patterns,
+            "handleAuthRequests": handle_auth
+        }))).await?;
+
+        let mocks = self.mocks.clone();
+        let http_auth = self.http_auth.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let (method, params) = match session.next_event().await {
+                    Ok(event) => event,
+                    Err(e) => {
+                        warn!("Request interception stopped: {}", e);
+                        break;
+                    }
+                };
+
+                let reply = match method.as_str() {
+                    "Fetch.requestPaused" => paused_request_reply(&mocks.lock().unwrap(), &params),
+                    "Fetch.authRequired" => auth_challenge_reply(&http_auth.lock().unwrap(), &params)
+                        .map(|reply| ("Fetch.continueWithAuth", reply)),
+                    _ => continue,
+                };
+                let result = match reply {
+                    Ok((command, reply)) => session.send_command(command, Some(reply)).await.map(|_| ()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    warn!("Could not answer intercepted request: {}", e);
+                }
+            }
+        });
+
+        // Replaced only now, so requests are not let through while the new session starts
+        if let Some(previous) = self.fetch_handler.replace(task.abort_handle()) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    async fn find_element_any_strategy(&mut self, query: &str) -> Result<ElementRef> {
+        // Try CSS selector first
+        if let Ok(element) = self.find_element_by_selector(query).await {
+            return Ok(element);
+        }
+
+        // Try accessibility text
+        if let Ok(element) = self.find_element_by_text(query).await {
+            return Ok(element);
+        }
+
+        // Try accessibility role
+        if let Ok(element) = self.find_element_by_role(query).await {
+            return Ok(element);
+        }
+
+        Err(ChromeMcpError::element_not_found(format!("Element not found: {}", query)))
+    }
+
+    async fn find_element_by_selector(&mut self, selector: &str) -> Result<ElementRef> {
+        // Use the first found element
+        let mut elements = self.find_elements_by_selector(selector).await?;
+        Ok(elements.remove(0))
+    }
+
+    async fn find_elements_by_selector(&mut self, selector: &str) -> Result<Vec<ElementRef>> {
+        let in_frame = |e: ChromeMcpError| e.in_frame("browser", "find_element_by_selector").with_context("selector", selector);
+        let node_ids = self.cdp.query_selector_all(selector).await.map_err(in_frame)?.node_ids;
+
+        if node_ids.is_empty() {
+            return Err(in_frame(ChromeMcpError::element_not_found(format!("No elements found for selector: {}", selector))));
+        }
+
+        let mut elements = Vec::with_capacity(node_ids.len());
+        for node_id in node_ids {
+            elements.push(ElementRef {
+                id: format!("dom-{}", node_id),
+                selector: Some(selector.to_string()),
+                accessibility_id: None,
+                bounds: None, // TODO: Get bounds from DOM
+                text: None,
+                role: None,
+                clickable: false,
+                score: 0.0,
+                backend_node_id: None,
+                stable_selector: None,
+            });
+        }
+        Ok(elements)
+    }
+
+    async fn find_element_by_text(&mut self, text: &str) -> Result<ElementRef> {
+        let nodes = self.accessibility.find_clickable_by_text(text).await?;
+        nodes
+            .first()
+            .map(element_ref_from_node)
+            .ok_or_else(|| ChromeMcpError::element_not_found(format!("No clickable element found with text: {}", text)))
+    }
+
+    async fn find_element_by_role(&mut self, role: &str) -> Result<ElementRef> {
+        let nodes = self.accessibility.find_by_role(role).await?;
+        nodes
+            .first()
+            .map(element_ref_from_node)
+            .ok_or_else(|| ChromeMcpError::element_not_found(format!("No element found with role: {}", role)))
+    }
+
+    async fn click_element_ref(&mut self, element_ref: &ElementRef, jitter_px: Option<u32>) -> Result<()> {
+        if let Some(bounds) = element_ref.bounds {
+            let (x, y, width, height) = if bounds.1 < FIXED_HEADER_ZONE_PX {
+                match self.fixed_header_height(None, Some(bounds)).await {
+                    Ok(header_height) => self.avoid_fixed_header(bounds, header_height).await?,
+                    Err(e) => {
+                        debug!("Could not measure fixed headers: {}", e);
+                        bounds
+                    }
+                }
+            } else {
+                bounds
+            };
+            let (click_x, click_y) = match jitter_px {
+                Some(jitter_px) => jitter_point((x, y, width, height), jitter_px, &mut self.human_rng),
+                // Click at center of element
+                None => (x + width / 2.0, y + height / 2.0),
+            };
+            self.cdp.click_at(click_x, click_y).await
+        } else if let Some(ref selector) = element_ref.selector {
+            // Try to click using JavaScript
+            self.cdp.evaluate_typed(EvaluateRequest::new(format!(
+                "document.querySelector('{}').click()",
+                selector.replace("'", "\\'")
+            ))).await?;
+            Ok(())
+        } else {
+            Err(ChromeMcpError::invalid_operation("Cannot click element: no bounds or selector"))
+        }
+    }
+
+    async fn is_element_visible(&mut self, selector: &str) -> Result<bool> {
+        let result = self.cdp.evaluate_typed(EvaluateRequest::new(format!(
+            r#"
+            const el = document.querySelector('{}');
+            el && el.offsetParent !== null && 
+            getComputedStyle(el).visibility !== 'hidden' && 
+            getComputedStyle(el).display !== 'none'
+            "#,
+            selector.replace("'", "\\'")
+        ))).await?;
+
+        Ok(result
+            .value()
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false))
+    }
+
+    async fn is_element_clickable(&mut self, selector: &str) -> Result<bool> {
+        let result = self.cdp.evaluate_typed(EvaluateRequest::new(format!(
+            r#"
+            const el = document.querySelector('{}');
+            el && el.offsetParent !== null && 
+            !el.disabled &&
+            getComputedStyle(el).pointerEvents !== 'none'
+            "#,
+            selector.replace("'", "\\'")
+        ))).await?;
+
+        Ok(result
+            .value()
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false))
+    }
+
+    async fn is_text_present(&mut self, text: &str) -> Result<bool> {
+        let result = self.cdp.evaluate_typed(EvaluateRequest::new(format!(
+            "document.body.textContent.includes('{}')",
+            text.replace("'", "\\'")
+        ))).await?;
+
+        Ok(result
+            .value()
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false))
+    }
+}
+
+/// Command answering a `Fetch.requestPaused` event: fulfill the request from the last
+/// registered mock matching its URL, or let it continue to the network
+fn paused_request_reply(mocks: &[MockResponse], params: &Value) -> Result<(&'static str, Value)> {
+    let request_id = params
+        .get("requestId")
+        .and_then(|id| id.as_str())
+        .ok_or_else(|| ChromeMcpError::network_error("Paused request has no requestId"))?;
+
+    let url = params
+        .get("request")
+        .and_then(|r| r.get("url"))
+        .and_then(|u| u.as_str())
+        .unwrap_or("");
+
+    match mocks.iter().rev().find(|m| url_pattern_matches(&m.url_pattern, url)) {
+        Some(mock) => {
+            debug!("Fulfilling {} from mock {}", url, mock.url_pattern);
+            let headers: Vec<Value> = mock
+                .headers
+                .iter()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect();
+
+            Ok(("Fetch.fulfillRequest", json!({
+                "requestId": request_id,
+                "responseCode": mock.status,
+                "responseHeaders": headers,
+                "body": BASE64.encode(mock.body.as_bytes())
+            })))
+        }
+        None => Ok(("Fetch.continueRequest", json!({ "requestId": request_id }))),
+    }
+}
+
+/// `Fetch.continueWithAuth` parameters answering a `Fetch.authRequired` event with the
+/// stored credentials for its host and realm, or cancelling the challenge
+fn auth_challenge_reply(credentials: &HashMap<(String, String), HttpAuthCredentials>, params: &Value) -> Result<Value> {
+    let request_id = params
+        .get("requestId")
+        .and_then(|id| id.as_str())
+        .ok_or_else(|| ChromeMcpError::network_error("Auth challenge has no requestId"))?;
+
+    let challenge = params.get("authChallenge");
+    let origin = challenge.and_then(|c| c.get("origin")).and_then(|o| o.as_str()).unwrap_or("");
+    let realm = challenge.and_then(|c| c.get("realm")).and_then(|r| r.as_str()).unwrap_or("");
+
+    let response = match lookup_auth_credentials(credentials, &origin_host(origin), realm) {
+        Some(credentials) => json!({
+            "response": "ProvideCredentials",
+            "username": credentials.username,
+            "password": credentials.password
+        }),
+        None => json!({ "response": "CancelAuth" }),
+    };
+
+    Ok(json!({
+        "requestId": request_id,
+        "authChallengeResponse": response
+    }))
+}
+
+/// Host of an origin such as `https://intranet.example:8443`, IPv6 addresses without brackets
+fn origin_host(origin: &str) -> String {
+    Url::parse(origin)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| unbracket_host(host).to_string()))
+        .unwrap_or_default()
+}
+
+/// IPv6 host without the brackets it is written with in URLs (`[::1]`)
+fn unbracket_host(host: &str) -> &str {
+    host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host)
+}
+
+/// Find credentials for a challenge, preferring exact host/realm matches over wildcards
+fn lookup_auth_credentials<'a>(credentials: &'a HashMap<(String, String), HttpAuthCredentials>, host: &str, realm: &str) -> Option<&'a HttpAuthCredentials> {
+    [(host, realm), (host, "*"), ("*", realm), ("*", "*")]
+        .iter()
+        .find_map(|(h, r)| credentials.get(&(h.to_string(), r.to_string())))
+}
+
+/// Record the title change a `Runtime.bindingCalled` event of `binding` reports; returns
+/// whether the event belonged to `binding`
+fn record_title_change(binding: &str, history: &Mutex<Vec<TitleChange>>, params: &Value) -> Result<bool> {
+    if params.get("name").and_then(|n| n.as_str()) != Some(binding) {
+        return Ok(false);
+    }
+
+    let payload = params.get("payload").and_then(|p| p.as_str()).unwrap_or("{}");
+    history.lock().unwrap().push(serde_json::from_str(payload)?);
+    Ok(true)
+}
+
+/// Match a URL against a CDP-style wildcard pattern (`*` and `?`)
+fn url_pattern_matches(pattern: &str, url: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let url: Vec<char> = url.chars().collect();
+    let (mut p, mut u) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while u < url.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == url[u]) {
+            p += 1;
+            u += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, u));
+            p += 1;
+        } else if let Some((star_p, star_u)) = backtrack {
+            p = star_p + 1;
+            u = star_u + 1;
+            backtrack = Some((star_p, star_u + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Flatten a `Page.getResourceTree` frame tree into a resource list
+fn collect_frame_resources(frame_tree: &Value, resources: &mut Vec<PageResource>) {
+    let frame = frame_tree.get("frame");
+    let frame_id = frame
+        .and_then(|f| f.get("id"))
+        .and_then(|id| id.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    // The frame document itself is not part of its resource list
+    if let Some(url) = frame.and_then(|f| f.get("url")).and_then(|u| u.as_str()) {
+        resources.push(PageResource {
+            url: url.to_string(),
+            type_: ResourceType::Document,
+            mime_type: frame
+                .and_then(|f| f.get("mimeType"))
+                .and_then(|m| m.as_str())
+                .unwrap_or("text/html")
+                .to_string(),
+            content_size: None,
+            frame_id: frame_id.clone(),
+        });
+    }
+
+    if let Some(frame_resources) = frame_tree.get("resources").and_then(|r| r.as_array()) {
+        for resource in frame_resources {
+            let url = match resource.get("url").and_then(|u| u.as_str()) {
+                Some(url) => url,
+                None => continue,
+            };
+
+            resources.push(PageResource {
+                url: url.to_string(),
+                type_: resource
+                    .get("type")
+                    .cloned()
+                    .and_then(|t| serde_json::from_value(t).ok())
+                    .unwrap_or(ResourceType::Other),
+                mime_type: resource
+                    .get("mimeType")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                content_size: resource.get("contentSize").and_then(|c| c.as_f64()).map(|c| c as u64),
+                frame_id: frame_id.clone(),
+            });
+        }
+    }
+
+    if let Some(children) = frame_tree.get("childFrames").and_then(|c| c.as_array()) {
+        for child in children {
+            collect_frame_resources(child, resources);
+        }
+    }
+}
+
+/// Extract the IP addresses from an ICE candidate line
+/// (`candidate:<foundation> <component> <protocol> <priority> <address> <port> typ ... [raddr <address>]`).
+/// mDNS-obfuscated `.local` hosts and unspecified addresses are skipped since they do not leak an IP.
+fn parse_ice_candidate_ips(candidate: &str) -> Vec<String> {
+    let fields: Vec<&str> = candidate.split_whitespace().collect();
+    let mut addresses: Vec<&str> = fields.get(4).copied().into_iter().collect();
+
+    if let Some(pos) = fields.iter().position(|&f| f == "raddr") {
+        addresses.extend(fields.get(pos + 1).copied());
+    }
+
+    addresses
+        .into_iter()
+        .filter(|a| !a.ends_with(".local"))
+        .filter_map(|a| a.parse::<std::net::IpAddr>().ok())
+        .filter(|ip| !ip.is_unspecified())
+        .map(|ip| ip.to_string())
+        .collect()
+}
+
+/// Number of characters of context kept on each side of a text match
+const MATCH_CONTEXT_CHARS: usize = 20;
+
+/// Run a regex over a text block and build a `TextMatch` for each hit
+fn find_text_matches(regex: &Regex, text: &str, frame_id: &str, line_number: Option<u32>, node_selector: Option<String>) -> Vec<TextMatch> {
+    regex
+        .find_iter(text)
+        .filter(|m| !m.as_str().is_empty())
+        .map(|m| {
+            let before: String = text[..m.start()]
+                .chars()
+                .rev()
+                .take(MATCH_CONTEXT_CHARS)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .collect();
+            let after: String = text[m.end()..].chars().take(MATCH_CONTEXT_CHARS).collect();
+
+            TextMatch {
+                frame_id: frame_id.to_string(),
+                matched_text: m.as_str().to_string(),
+                surrounding_text: format!("{}{}{}", before, m.as_str(), after),
+                line_number,
+                node_selector: node_selector.clone(),
+            }
+        })
+        .collect()
+}
+
+/// 64-bit FNV-1a hash, stable across runs and platforms
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| (hash ^ *byte as u64).wrapping_mul(PRIME))
+}
+
+/// Pick the popup windows out of a `Target.getTargets` result
+fn parse_popup_targets(result: &Value, main_tab_id: Option<&str>) -> Vec<PopupInfo> {
+    result
+        .get("targetInfos")
+        .and_then(|t| t.as_array())
+        .map(|targets| targets.iter().filter_map(|t| popup_from_target(t, main_tab_id)).collect())
+        .unwrap_or_default()
+}
+
+/// Popup described by a `TargetInfo`, `None` unless it is a page other than the main tab.
+/// `openerId` is not required: popups opened with `noopener` have none.
+fn popup_from_target(target: &Value, main_tab_id: Option<&str>) -> Option<PopupInfo> {
+    if target.get("type").and_then(|v| v.as_str()) != Some("page") {
+        return None;
+    }
+    let id = target.get("targetId").and_then(|v| v.as_str())?;
+    if Some(id) == main_tab_id {
+        return None;
+    }
+    Some(PopupInfo {
+        id: id.to_string(),
+        url: target.get("url").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        title: target.get("title").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        opener_id: target.get("openerId").and_then(|v| v.as_str()).map(|id| id.to_string()),
+    })
+}
+
+/// Pick the extension DevTools contexts out of a `Target.getTargets` result
+fn parse_devtools_targets(result: &Value) -> Vec<DevToolsTarget> {
+    result
+        .get("targetInfos")
+        .and_then(|t| t.as_array())
+        .map(|targets| {
+            targets
+                .iter()
+                .filter(|t| t.get("type").and_then(|v| v.as_str()) == Some("other"))
+                .filter_map(|t| {
+                    let url = t.get("url").and_then(|v| v.as_str())?;
+                    let extension_id = url.strip_prefix("chrome-extension://")?.split('/').next()?;
+                    Some(DevToolsTarget {
+                        id: t.get("targetId").and_then(|v| v.as_str())?.to_string(),
+                        url: url.to_string(),
+                        title: t.get("title").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                        extension_id: extension_id.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Resolve the scroll axis (`"x"` or `"y"`) and sign for a snap scroll.
+/// Directions across a single-axis container are mapped onto its axis.
+fn snap_scroll_axis(direction: ScrollDirection, snap_axis: &str) -> (&'static str, i32) {
+    let sign = match direction {
+        ScrollDirection::Right | ScrollDirection::Down => 1,
+        ScrollDirection::Left | ScrollDirection::Up => -1,
+    };
+
+    let axis = match snap_axis {
+        "x" => "x",
+        "y" => "y",
+        _ => match direction {
+            ScrollDirection::Left | ScrollDirection::Right => "x",
+            ScrollDirection::Up | ScrollDirection::Down => "y",
+        },
+    };
+
+    (axis, sign)
+}
+
+/// Classify why an input has no accessible name: a broken ARIA label takes precedence over a
+/// `<label for>` that misses the input, and anything else has no label at all
+fn label_issue(input: UnlabeledInput) -> LabelIssue {
+    let (issue, recommendation) = if input.aria_label.is_some() || input.aria_labelledby.is_some() {
+        let recommendation = if input.missing_labelledby_ids.is_empty() {
+            "The ARIA label is empty; set aria-label to a descriptive name or aria-labelledby to a labelled element".to_string()
+        } else {
+            let missing: Vec<String> = input.missing_labelledby_ids.iter().map(|id| format!("#{}", id)).collect();
+            format!("aria-labelledby references missing element(s) {}; point it at the id of the visible label", missing.join(", "))
+        };
+        (LabelIssueType::MissingAriaLabel, recommendation)
+    } else if let Some(label_for) = &input.stray_label_for {
+        let recommendation = match &input.id {
+            Some(id) => format!("label for=\"{}\" does not reach this input; make its id \"{}\" unique and set for=\"{}\"", label_for, id, id),
+            None => format!("label for=\"{}\" does not match any id; give the input id=\"{}\"", label_for, label_for),
+        };
+        (LabelIssueType::InvalidForAttribute, recommendation)
+    } else {
+        (LabelIssueType::NoLabel, "Add a <label for=\"...\">, wrap the input in a <label>, or set aria-label".to_string())
+    };
+
+    LabelIssue {
+        input_selector: input.input_selector,
+        issue,
+        recommendation,
+    }
+}
+
+/// Apply the CORS preflight rules to the `Access-Control-Allow-*` response headers
+fn evaluate_cors_headers(
+    origin: &str,
+    method: &str,
+    request_headers: &[String],
+    allow_origin: Option<&str>,
+    allow_methods: Option<&str>,
+    allow_headers: Option<&str>,
+) -> CorsResult {
+    let split = |value: Option<&str>| -> Vec<String> {
+        value
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    };
+
+    let mut result = CorsResult {
+        allowed_origin: allow_origin.map(|o| o.trim().to_string()),
+        allowed_methods: split(allow_methods),
+        allowed_headers: split(allow_headers),
+        ..Default::default()
+    };
+
+    let method = method.to_uppercase();
+    // GET, HEAD and POST and the safelisted headers never need explicit permission
+    let simple_method = matches!(method.as_str(), "GET" | "HEAD" | "POST");
+    let safelisted_headers = ["accept", "accept-language", "content-language", "content-type"];
+
+    result.error = match result.allowed_origin.as_deref() {
+        None => Some("No Access-Control-Allow-Origin header".to_string()),
+        Some(allowed) if allowed != "*" && allowed != origin => {
+            Some(format!("Origin {} not allowed (server allows {})", origin, allowed))
+        }
+        _ if !simple_method && !result.allowed_methods.iter().any(|m| m == "*" || m.eq_ignore_ascii_case(&method)) => {
+            Some(format!("Method {} not allowed", method))
+        }
+        _ => request_headers
+            .iter()
+            .find(|h| {
+                !safelisted_headers.contains(&h.to_lowercase().as_str())
+                    && !result.allowed_headers.iter().any(|a| a == "*" || a.eq_ignore_ascii_case(h))
+            })
+            .map(|h| format!("Header {} not allowed", h)),
+    };
+    result.allowed = result.error.is_none();
+
+    result
+}
+
+/// Generate `iterations` fuzz inputs; payload lists are repeated as needed.
+/// `max_length` is the field's `maxLength`, if it has one.
+fn fuzz_inputs(strategy: &FuzzStrategy, iterations: u32, max_length: Option<usize>, seed: u64) -> Vec<String> {
+    let payloads: Vec<String> = match strategy {
+        FuzzStrategy::Random => {
+            const CHARS: &[char] = &['a', 'Z', '0', '9', ' ', '\'', '"', '<', '>', '&', '%', '\\', '/', ';', '\n', '\t', '\0', 'é', 'ß', '中', '🚀', '\u{202e}'];
+            let mut state = seed | 1;
+            let mut next = move || {
+                // xorshift64
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            };
+            return (0..iterations)
+                .map(|_| {
+                    let length = (next() % 64) as usize + 1;
+                    (0..length).map(|_| CHARS[(next() % CHARS.len() as u64) as usize]).collect()
+                })
+                .collect();
+        }
+        FuzzStrategy::Boundary => {
+            let long = max_length.unwrap_or(1024);
+            vec![
+                String::new(),
+                " ".to_string(),
+                "A".repeat(long),
+                "A".repeat(long + 1),
+                "-1".to_string(),
+                "0".to_string(),
+                "99999999999999999999".to_string(),
+                "!@#$%^&*()_+-=[]{}|;':\",./<>?`~".to_string(),
+                "\n\r\t\0".to_string(),
+                "é中🚀\u{202e}".to_string(),
+            ]
+        }
+        FuzzStrategy::Sql => SQL_FUZZ_PAYLOADS.iter().map(|p| p.to_string()).collect(),
+        FuzzStrategy::Xss => XSS_FUZZ_PAYLOADS.iter().map(|p| p.to_string()).collect(),
+        FuzzStrategy::Custom(inputs) => inputs.clone(),
+    };
+
+    payloads.into_iter().cycle().take(iterations as usize).collect()
+}
+
+/// Send a HEAD request to each URL, `concurrent` at a time
+async fn check_links_over_http(urls: &[String], concurrent: usize, timeout_ms: u64) -> Result<Vec<LinkCheckResult>> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout_ms))
+        .build()
+        .map_err(|e| ChromeMcpError::network_error(format!("Could not create HTTP client: {}", e)))?;
+
+    let mut results = Vec::with_capacity(urls.len());
+    for batch in urls.chunks(concurrent) {
+        results.extend(futures_util::future::join_all(batch.iter().map(|url| check_link_over_http(&client, url))).await);
+    }
+    Ok(results)
+}
+
+async fn check_link_over_http(client: &reqwest::Client, url: &str) -> LinkCheckResult {
+    let mut response = client.head(url).send().await;
+
+    // Some servers do not implement HEAD
+    if response.as_ref().is_ok_and(|r| matches!(r.status().as_u16(), 405 | 501)) {
+        response = client.get(url).send().await;
+    }
+
+    match response {
+        Ok(response) => LinkCheckResult::with_status(url, Some(response.status().as_u16())),
+        Err(e) => LinkCheckResult::failed(url, e.to_string()),
+    }
+}
+
+/// Suffix Chrome gives files while they download
+const PARTIAL_DOWNLOAD_SUFFIX: &str = ".crdownload";
+
+/// The user's Downloads folder, where Chrome saves files by default
+fn default_download_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join("Downloads")
+}
+
+/// Newest download Chrome reported whose file name matches `pattern`, begun after `since`
+fn find_tracked_download(
+    downloads: &HashMap<String, TrackedDownload>,
+    pattern: &str,
+    since: Option<std::time::SystemTime>,
+) -> Option<TrackedDownload> {
+    downloads
+        .values()
+        .filter(|download| url_pattern_matches(pattern, &download.filename))
+        .filter(|download| since.is_none_or(|since| download.begun >= since))
+        .max_by_key(|download| download.begun)
+        .cloned()
+}
+
+/// The download matching `pattern`: the newest one Chrome reported, else the one found in `dir`
+fn current_download(
+    downloads: &Mutex<HashMap<String, TrackedDownload>>,
+    dir: &Path,
+    pattern: &str,
+) -> Result<Option<(String, DownloadProgress)>> {
+    let tracked = find_tracked_download(&downloads.lock().unwrap(), pattern, None);
+    match tracked {
+        Some(download) => Ok(Some((download.filename.clone(), download.progress()))),
+        None => scan_download_dir(dir, pattern, None),
+    }
+}
+
+/// Find the download matching `pattern` in `dir`: a file still downloading if there is one,
+/// otherwise the newest complete file. With `since`, older files are ignored.
+fn scan_download_dir(dir: &Path, pattern: &str, since: Option<std::time::SystemTime>) -> Result<Option<(String, DownloadProgress)>> {
+    let mut partial: Option<(String, u64)> = None;
+    let mut newest: Option<(std::time::SystemTime, String, u64)> = None;
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let modified = metadata.modified()?;
+        if since.is_some_and(|since| modified < since) {
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().into_owned();
+        match name.strip_suffix(PARTIAL_DOWNLOAD_SUFFIX) {
+            Some(target) if url_pattern_matches(pattern, target) => partial = Some((target.to_string(), metadata.len())),
+            Some(_) => {}
+            None if url_pattern_matches(pattern, &name) && newest.as_ref().is_none_or(|(time, _, _)| modified > *time) => {
+                newest = Some((modified, name, metadata.len()));
+            }
+            None => {}
+        }
+    }
+
+    let found = match (partial, newest) {
+        (Some((name, size)), _) => Some((name, size, false)),
+        (None, Some((_, name, size))) => Some((name, size, true)),
+        (None, None) => None,
+    };
+    Ok(found.map(|(filename, bytes_downloaded, complete)| {
+        let progress = DownloadProgress {
+            bytes_downloaded,
+            total_bytes: complete.then_some(bytes_downloaded),
+            percent: complete.then_some(100.0),
+            complete,
+        };
+        (filename, progress)
+    }))
+}
+
+/// Libraries recognized from script file names, longest names first so `react-dom.js` is not
+/// taken for React
+const KNOWN_SCRIPT_LIBRARIES: &[&str] =
+    &["react-dom", "underscore", "backbone", "angular", "jquery", "lodash", "moment", "react", "vue"];
+
+/// Library name and version of a script, from its URL: `jquery-3.6.0.min.js`,
+/// `/react@18.2.0/umd/react.production.min.js`, `/angularjs/1.8.2/angular.min.js`, ...
+fn library_from_script_url(url: &str) -> Option<(String, Option<String>)> {
+    let path = match Url::parse(url) {
+        Ok(url) => url.path().to_lowercase(),
+        Err(_) => url.split(['?', '#']).next().unwrap_or_default().to_lowercase(),
+    };
+    let file_name = path.rsplit('/').next()?.strip_suffix(".js")?;
+    let (name, rest) = KNOWN_SCRIPT_LIBRARIES.iter().find_map(|name| {
+        let rest = file_name.strip_prefix(name)?;
+        (rest.is_empty() || rest.starts_with(['.', '-', '_', '@'])).then_some((*name, rest))
+    })?;
+
+    let version_regex = Regex::new(r"^[.\-_@]v?(\d+\.\d+(?:\.\d+)?)").ok()?;
+    let path_version_regex = Regex::new(r"(?:@|/v?)(\d+\.\d+(?:\.\d+)?)(?:/|$)").ok()?;
+    let version = version_regex
+        .captures(rest)
+        .or_else(|| path_version_regex.captures(&path))
+        .map(|c| c[1].to_string());
+    Some((name.to_string(), version))
+}
+
+/// Libraries loaded from several scripts or seen in several versions, then other scripts
+/// included more than once
+fn find_duplicate_libraries(libraries: &[LibraryInfo], script_urls: &[String]) -> Vec<DuplicateLibrary> {
+    let mut names: Vec<&str> = Vec::new();
+    for library in libraries {
+        if !names.contains(&library.name.as_str()) {
+            names.push(&library.name);
+        }
+    }
+
+    let mut duplicates = Vec::new();
+    let mut library_urls: Vec<&str> = Vec::new();
+    for name in names {
+        let mut versions: Vec<String> = Vec::new();
+        let mut urls = Vec::new();
+        for library in libraries.iter().filter(|l| l.name == name) {
+            if let Some(version) = &library.version {
+                if !versions.contains(version) {
+                    versions.push(version.clone());
+                }
+            }
+            if let Some(url) = &library.script_url {
+                urls.push(url.clone());
+                library_urls.push(url);
+            }
+        }
+        if urls.len() > 1 || versions.len() > 1 {
+            duplicates.push(DuplicateLibrary { name: name.to_string(), versions, script_urls: urls });
+        }
+    }
+
+    let mut seen: Vec<&str> = Vec::new();
+    for url in script_urls {
+        if library_urls.contains(&url.as_str()) || seen.contains(&url.as_str()) {
+            continue;
+        }
+        seen.push(url);
+        let repeats: Vec<String> = script_urls.iter().filter(|u| *u == url).cloned().collect();
+        if repeats.len() > 1 {
+            let name = url.rsplit('/').next().unwrap_or(url).to_string();
+            duplicates.push(DuplicateLibrary { name, versions: Vec::new(), script_urls: repeats });
+        }
+    }
+    duplicates
+}
+
+/// SEO checks of `run_seo_audit`, in page order
+fn seo_checks(facts: &SeoFacts, dom_content_loaded_ms: Option<f64>) -> Vec<SeoCheck> {
+    let check = |name: &str, severity: SeoSeverity, value: Option<String>, recommendation: &str| SeoCheck {
+        name: name.to_string(),
+        passed: severity == SeoSeverity::Pass,
+        severity,
+        value,
+        recommendation: if severity == SeoSeverity::Pass { String::new() } else { recommendation.to_string() },
+    };
+    let mut checks = Vec::new();
+
+    let title = facts.title.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    checks.push(match title.map(|t| t.chars().count()) {
+        None => check("title", SeoSeverity::Error, None, "Add a <title> describing the page"),
+        Some(length) if !(10..=60).contains(&length) => {
+            check("title", SeoSeverity::Warning, title.map(String::from), "Keep the title between 10 and 60 characters so search results show it whole")
+        }
+        Some(_) => check("title", SeoSeverity::Pass, title.map(String::from), ""),
+    });
+
+    let description = facts.descriptions.first().map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    checks.push(match description.as_ref().map(|d| d.chars().count()) {
+        None => check("meta_description", SeoSeverity::Error, None, "Add a <meta name=\"description\"> summarizing the page"),
+        Some(_) if facts.descriptions.len() > 1 => {
+            check("meta_description", SeoSeverity::Warning, description, "Keep a single meta description")
+        }
+        Some(length) if !(50..=160).contains(&length) => {
+            check("meta_description", SeoSeverity::Warning, description, "Keep the meta description between 50 and 160 characters")
+        }
+        Some(_) => check("meta_description", SeoSeverity::Pass, description, ""),
+    });
+
+    let h1_count = facts.headings.iter().filter(|&&level| level == 1).count();
+    let h1_severity = match h1_count {
+        0 => SeoSeverity::Error,
+        1 => SeoSeverity::Pass,
+        _ => SeoSeverity::Warning,
+    };
+    checks.push(check("h1", h1_severity, Some(h1_count.to_string()), "Use exactly one <h1> stating the page topic"));
+
+    let skipped = facts.headings.windows(2).find(|pair| pair[1] > pair[0] + 1);
+    checks.push(match skipped {
+        Some(pair) => check(
+            "heading_hierarchy",
+            SeoSeverity::Warning,
+            Some(format!("h{} followed by h{}", pair[0], pair[1])),
+            "Do not skip heading levels, so the outline of the page stays clear",
+        ),
+        None => check("heading_hierarchy", SeoSeverity::Pass, None, ""),
+    });
+
+    let alt_severity = if facts.images_without_alt > 0 { SeoSeverity::Error } else { SeoSeverity::Pass };
+    checks.push(check(
+        "image_alt",
+        alt_severity,
+        Some(format!("{} of {} images without alt", facts.images_without_alt, facts.images)),
+        "Give every image an alt attribute (empty for decorative images)",
+    ));
+
+    let robots = facts.robots.join(", ").to_lowercase();
+    let robots_value = (!facts.robots.is_empty()).then(|| facts.robots.join(", "));
+    checks.push(if robots.contains("noindex") || robots.contains("none") {
+        check("robots", SeoSeverity::Error, robots_value, "Remove noindex from the robots meta tag if the page should appear in search results")
+    } else if robots.contains("nofollow") {
+        check("robots", SeoSeverity::Warning, robots_value, "Remove nofollow from the robots meta tag so links on the page are crawled")
+    } else {
+        check("robots", SeoSeverity::Pass, robots_value, "")
+    });
+
+    checks.push(match facts.canonicals.as_slice() {
+        [] => check("canonical", SeoSeverity::Warning, None, "Add a <link rel=\"canonical\"> pointing at the preferred URL of the page"),
+        [href] if !href.trim().is_empty() => check("canonical", SeoSeverity::Pass, Some(href.clone()), ""),
+        [href] => check("canonical", SeoSeverity::Error, Some(href.clone()), "Give the canonical link an href"),
+        hrefs => check("canonical", SeoSeverity::Error, Some(hrefs.join(", ")), "Keep a single canonical link; search engines ignore conflicting ones"),
+    });
+
+    let viewport = facts.viewport.first().cloned();
+    checks.push(match &viewport {
+        None => check("viewport", SeoSeverity::Error, None, "Add <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\"> for mobile devices"),
+        Some(content) if !content.replace(' ', "").contains("width=device-width") => {
+            check("viewport", SeoSeverity::Warning, viewport, "Set width=device-width in the viewport meta tag")
+        }
+        Some(_) => check("viewport", SeoSeverity::Pass, viewport, ""),
+    });
+
+    checks.push(match dom_content_loaded_ms {
+        None => check("page_speed", SeoSeverity::Warning, None, "DOMContentLoaded has not fired; check again once the page has loaded"),
+        Some(ms) => {
+            let severity = if ms <= 2500.0 {
+                SeoSeverity::Pass
+            } else if ms <= 4000.0 {
+                SeoSeverity::Warning
+            } else {
+                SeoSeverity::Error
+            };
+            check("page_speed", severity, Some(format!("DOMContentLoaded after {:.0}ms", ms)), "Reduce render-blocking resources and script work so DOMContentLoaded fires within 2.5s")
+        }
+    });
+
+    checks
+}
+
+/// Scale a base64 PNG down to fit within `max_width` x `max_height`, keeping its aspect ratio.
+/// Images that already fit are returned unchanged.
+fn png_thumbnail(png_base64: &str, max_width: u32, max_height: u32) -> Result<String> {
+    if max_width == 0 || max_height == 0 {
+        return Err(ChromeMcpError::invalid_operation("Thumbnail dimensions must be positive"));
+    }
+
+    let bytes = BASE64.decode(png_base64)
+        .map_err(|e| ChromeMcpError::screenshot_error(format!("Invalid screenshot data: {}", e)))?;
+    let image = image::load_from_memory_with_format(&bytes, image::ImageFormat::Png)
+        .map_err(|e| ChromeMcpError::screenshot_error(format!("Could not decode screenshot: {}", e)))?;
+
+    if image.width() <= max_width && image.height() <= max_height {
+        return Ok(png_base64.to_string());
+    }
+
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    image
+        .thumbnail(max_width, max_height)
+        .write_to(&mut encoded, image::ImageFormat::Png)
+        .map_err(|e| ChromeMcpError::screenshot_error(format!("Could not encode thumbnail: {}", e)))?;
+    Ok(BASE64.encode(encoded.into_inner()))
+}
+
+/// Element area (CSS px²) from which a larger element no longer ranks higher
+const RELEVANCE_FULL_SIZE_AREA: f64 = 10_000.0;
+
+/// Attributes of a `DOM.describeNode` node, which lists them as `[name, value, name, value, ...]`
+fn node_attributes(node: &Value) -> HashMap<String, String> {
+    let values: Vec<&str> = node
+        .get("attributes")
+        .and_then(|a| a.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+    values.chunks_exact(2).map(|pair| (pair[0].to_lowercase(), pair[1].to_string())).collect()
+}
+
+/// Element reference for an accessibility node
+fn element_ref_from_node(node: &AccessibilityNode) -> ElementRef {
+    ElementRef {
+        id: format!("ax-{}", node.node_id),
+        selector: None,
+        accessibility_id: Some(node.node_id.clone()),
+        bounds: node.bounds.as_ref().map(|b| (b.x, b.y, b.width, b.height)),
+        text: node.name.clone(),
+        role: node.role.clone(),
+        clickable: node.clickable,
+        score: 0.0,
+        backend_node_id: node.backend_node_id,
+        stable_selector: None,
+    }
+}
+
+/// Menu item named `name`: an exact (case-insensitive) accessible name match, or else the
+/// first name containing it
+fn match_menu_item<'a>(items: &'a [AccessibilityNode], name: &str) -> Option<&'a AccessibilityNode> {
+    let name = name.trim().to_lowercase();
+    let item_name = |node: &AccessibilityNode| node.name.as_deref().unwrap_or_default().trim().to_lowercase();
+    items
+        .iter()
+        .find(|node| item_name(node) == name)
+        .or_else(|| items.iter().find(|node| item_name(node).contains(&name)))
+}
+
+/// How well `element` matches a `find_elements` query: exact name match +10, name containing
+/// the query +5, exact role match +3, clickable +2, inside the `(width, height)` viewport +1,
+/// plus up to +1 for its size, since larger elements are more likely intended targets
+pub fn relevance_score(query: &str, element: &ElementRef, viewport: Option<(f64, f64)>) -> f64 {
+    let query = query.trim().to_lowercase();
+    let mut score = 0.0;
+
+    if let Some(name) = element.text.as_deref().map(|t| t.trim().to_lowercase()) {
+        if name == query {
+            score += 10.0;
+        } else if !query.is_empty() && name.contains(&query) {
+            score += 5.0;
+        }
+    }
+    if element.role.as_deref().is_some_and(|role| role.eq_ignore_ascii_case(&query)) {
+        score += 3.0;
+    }
+    if element.clickable {
+        score += 2.0;
+    }
+    if let Some((x, y, width, height)) = element.bounds {
+        if let Some((viewport_width, viewport_height)) = viewport {
+            if width > 0.0 && height > 0.0 && x < viewport_width && y < viewport_height && x + width > 0.0 && y + height > 0.0 {
+                score += 1.0;
+            }
+        }
+        score += (width.max(0.0) * height.max(0.0) / RELEVANCE_FULL_SIZE_AREA).min(1.0);
+    }
+
+    score
+}
+
+/// Fetch the response body of the captured request `request_id` into `events`, decoded by content type
+async fn fetch_response_body<B: CdpClientBackend>(
+    cdp: &mut CdpClient<B>,
+    events: &Mutex<Vec<NetworkEvent>>,
+    request_id: &str,
+    max_bytes: Option<usize>,
+) -> Result<NetworkEvent> {
+    let content_type = {
+        let events = events.lock().unwrap();
+        let event = events
+            .iter()
+            .find(|e| e.request_id == request_id)
+            .ok_or_else(|| ChromeMcpError::network_error(format!("No captured request with id: {}", request_id)))?;
+        event.response_headers.as_ref().and_then(|headers| {
+            headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+                .map(|(_, value)| value.clone())
+        })
+    };
+
+    let result = cdp.send_command("Network.getResponseBody", Some(json!({
+        "requestId": request_id
+    }))).await?;
+
+    let body = result
+        .get("body")
+        .and_then(|b| b.as_str())
+        .ok_or_else(|| ChromeMcpError::network_error("No body in Network.getResponseBody response"))?;
+    let base64_encoded = result.get("base64Encoded").and_then(|b| b.as_bool()).unwrap_or(false);
+    if let Some(max_bytes) = max_bytes.filter(|&max_bytes| body.len() > max_bytes) {
+        return Err(ChromeMcpError::network_error(format!(
+            "Response body of {} bytes exceeds the {} byte capture limit",
+            body.len(),
+            max_bytes
+        )));
+    }
+    let (raw, decoded) = decode_response_body(content_type.as_deref(), body, base64_encoded);
+
+    let mut events = events.lock().unwrap();
+    let event = events
+        .iter_mut()
+        .find(|e| e.request_id == request_id)
+        .ok_or_else(|| ChromeMcpError::network_error(format!("No captured request with id: {}", request_id)))?;
+    event.body = Some(raw);
+    event.body_decoded = decoded;
+    Ok(event.clone())
+}
+
+/// Script evaluating `expression` on the element matching `selector` (bound to `el`), or on
+/// each matching element with `all`; it returns `null` when nothing matches
+fn element_expression(selector: &str, all: bool, expression: &str) -> Result<String> {
+    let selector = serde_json::to_string(selector)?;
+    Ok(if all {
+        format!("(() => {{ const els = document.querySelectorAll({}); return els.length ? Array.from(els, el => {}) : null; }})()", selector, expression)
+    } else {
+        format!("(() => {{ const el = document.querySelector({}); return el ? {} : null; }})()", selector, expression)
+    })
+}
+
+/// Element text property for `Browser::get_element_text`; `innerText` only exists on HTML elements
+fn text_property(include_hidden: bool) -> &'static str {
+    if include_hidden {
+        "el.textContent"
+    } else {
+        "(el.innerText ?? el.textContent)"
+    }
+}
+
+/// Document bytes decoded like a browser would: by their byte order mark, the charset of
+/// `content_type`, or the one a `<meta>` tag in the first 1024 bytes names, falling back to
+/// UTF-8 and then to windows-1252 (where every byte is a character)
+fn decode_html_bytes(bytes: &[u8], content_type: Option<&str>) -> String {
+    let charset = Regex::new(r#"(?i)charset\s*=\s*["']?([\w.:-]+)"#).unwrap();
+    let label = |text: &str| charset.captures(text).and_then(|captures| Encoding::for_label(captures[1].as_bytes()));
+    let prescan: String = bytes.iter().take(1024).copied().map(char::from).collect();
+    let meta = Regex::new(r"(?is)<meta\b[^>]*>").unwrap();
+
+    let encoding = content_type
+        .and_then(label)
+        .or_else(|| meta.find_iter(&prescan).find_map(|tag| label(tag.as_str())))
+        // A document cannot name UTF-16 in itself, as it would not be ASCII-compatible
+        .filter(|encoding| *encoding != UTF_16LE && *encoding != UTF_16BE)
+        .unwrap_or_else(|| if std::str::from_utf8(bytes).is_ok() { UTF_8 } else { WINDOWS_1252 });
+    // A byte order mark overrides the encoding
+    encoding.decode(bytes).0.into_owned()
+}
+
+/// Record a `Network.*` event in `events`, see `Browser::handle_network_event`
+fn record_network_event(events: &mut Vec<NetworkEvent>, method: &str, params: &Value) -> bool {
+    let Some(request_id) = params.get("requestId").and_then(|id| id.as_str()) else {
+        return false;
+    };
+    let str_map = |value: Option<&Value>| -> HashMap<String, String> {
+        value
+            .and_then(|v| v.as_object())
+            .map(|map| map.iter().filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string()))).collect())
+            .unwrap_or_default()
+    };
+
+    if method == "Network.requestWillBeSent" {
+        let request = params.get("request");
+        let field = |name: &str| request.and_then(|r| r.get(name)).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        // A redirect reuses the request id for the next hop
+        events.retain(|event| event.request_id != request_id);
+        events.push(NetworkEvent {
+            request_id: request_id.to_string(),
+            url: field("url"),
+            method: field("method"),
+            headers: str_map(request.and_then(|r| r.get("headers"))),
+            timestamp: params.get("timestamp").and_then(|t| t.as_f64()).unwrap_or(0.0),
+            status_code: None,
+            response_headers: None,
+            body: None,
+            body_decoded: Value::Null,
+            finished: false,
+            error_text: None,
+        });
+        return true;
+    }
+
+    let Some(event) = events.iter_mut().find(|event| event.request_id == request_id) else {
+        return false;
+    };
+    match method {
+        "Network.responseReceived" => {
+            let response = params.get("response");
+            event.status_code = response.and_then(|r| r.get("status")).and_then(|s| s.as_u64()).map(|s| s as u32);
+            event.response_headers = Some(str_map(response.and_then(|r| r.get("headers"))));
+        }
+        "Network.loadingFinished" => event.finished = true,
+        "Network.loadingFailed" => {
+            let error_text = params.get("errorText").and_then(|e| e.as_str()).unwrap_or("Failed");
+            event.error_text = Some(error_text.to_string());
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Decode a `Network.getResponseBody` body into its raw text and a content-type aware value.
+/// JSON is parsed, binary types (octet-stream, images, audio, video, fonts) are kept as base64,
+/// everything else is text.
+fn decode_response_body(content_type: Option<&str>, body: &str, base64_encoded: bool) -> (String, Value) {
+    let mime = content_type
+        .and_then(|c| c.split(';').next())
+        .map(|m| m.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let binary = mime == "application/octet-stream"
+        || ["image/", "audio/", "video/", "font/"].iter().any(|prefix| mime.starts_with(prefix));
+    if binary {
+        let encoded = if base64_encoded { body.to_string() } else { BASE64.encode(body.as_bytes()) };
+        return (encoded.clone(), Value::String(encoded));
+    }
+
+    let text = if base64_encoded {
+        match BASE64.decode(body).ok().and_then(|bytes| String::from_utf8(bytes).ok()) {
+            Some(text) => text,
+            // Not valid UTF-8 after all, keep it base64-encoded
+            None => return (body.to_string(), Value::String(body.to_string())),
+        }
+    } else {
+        body.to_string()
+    };
+
+    let decoded = if mime == "application/json" || mime.ends_with("+json") {
+        serde_json::from_str(&text).unwrap_or_else(|_| Value::String(text.clone()))
+    } else {
+        Value::String(text.clone())
+    };
+    (text, decoded)
+}
+
+/// Duration between two timestamps of a performance entry, zero if either was not recorded
+fn timing_phase_ms(entry: &Value, start: &str, end: &str) -> f64 {
+    let start = entry.get(start).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let end = entry.get(end).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    if start <= 0.0 || end <= 0.0 {
+        0.0
+    } else {
+        (end - start).max(0.0)
+    }
+}
+
+fn timing_ms(entry: &Value, key: &str) -> f64 {
+    entry.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0)
+}
+
+fn timing_size(entry: &Value, key: &str) -> u64 {
+    entry.get(key).and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+fn timing_str(entry: &Value, key: &str) -> String {
+    entry.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+}
+
+/// Map a `PerformanceNavigationTiming.toJSON()` object to `NavigationTiming`
+fn navigation_timing_from_entry(entry: &Value) -> NavigationTiming {
+    NavigationTiming {
+        url: timing_str(entry, "name"),
+        navigation_type: timing_str(entry, "type"),
+        redirect_count: timing_size(entry, "redirectCount") as u32,
+        redirect_ms: timing_phase_ms(entry, "redirectStart", "redirectEnd"),
+        dns_lookup_ms: timing_phase_ms(entry, "domainLookupStart", "domainLookupEnd"),
+        tcp_connect_ms: timing_phase_ms(entry, "connectStart", "connectEnd"),
+        ssl_handshake_ms: timing_phase_ms(entry, "secureConnectionStart", "connectEnd"),
+        // Navigation entries start at 0, so milestones are already relative to navigation start
+        ttfb_ms: timing_ms(entry, "responseStart"),
+        response_ms: timing_phase_ms(entry, "responseStart", "responseEnd"),
+        dom_interactive_ms: timing_ms(entry, "domInteractive"),
+        dom_content_loaded_ms: timing_ms(entry, "domContentLoadedEventEnd"),
+        dom_complete_ms: timing_ms(entry, "domComplete"),
+        load_event_ms: timing_phase_ms(entry, "loadEventStart", "loadEventEnd"),
+        duration_ms: timing_ms(entry, "duration"),
+        transfer_size: timing_size(entry, "transferSize"),
+        encoded_body_size: timing_size(entry, "encodedBodySize"),
+        decoded_body_size: timing_size(entry, "decodedBodySize"),
+    }
+}
+
+/// Map a `PerformanceResourceTiming.toJSON()` object to `ResourceTiming`
+fn resource_timing_from_entry(entry: &Value) -> ResourceTiming {
+    ResourceTiming {
+        url: timing_str(entry, "name"),
+        initiator_type: timing_str(entry, "initiatorType"),
+        start_time_ms: timing_ms(entry, "startTime"),
+        duration_ms: timing_ms(entry, "duration"),
+        dns_lookup_ms: timing_phase_ms(entry, "domainLookupStart", "domainLookupEnd"),
+        tcp_connect_ms: timing_phase_ms(entry, "connectStart", "connectEnd"),
+        ssl_handshake_ms: timing_phase_ms(entry, "secureConnectionStart", "connectEnd"),
+        ttfb_ms: timing_phase_ms(entry, "startTime", "responseStart"),
+        response_ms: timing_phase_ms(entry, "responseStart", "responseEnd"),
+        transfer_size: timing_size(entry, "transferSize"),
+        encoded_body_size: timing_size(entry, "encodedBodySize"),
+        decoded_body_size: timing_size(entry, "decodedBodySize"),
+    }
+}
+
+/// Split flat `ImageData` bytes into RGBA pixels
+fn rgba_pixels(data: &[u8]) -> Vec<[u8; 4]> {
+    data.chunks_exact(4).map(|p| [p[0], p[1], p[2], p[3]]).collect()
+}
+
+/// Parse a `#rgb`, `#rrggbb` or `#rrggbbaa` color into RGBA
+pub fn parse_hex_color(color: &str) -> Option<[u8; 4]> {
+    let hex = color.trim().trim_start_matches('#');
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+    match hex.len() {
+        3 => {
+            let mut rgba = [255; 4];
+            for (i, c) in hex.chars().enumerate() {
+                let v = c.to_digit(16)? as u8;
+                rgba[i] = v * 16 + v;
+            }
+            Some(rgba)
+        }
+        6 => Some([channel(0)?, channel(2)?, channel(4)?, 255]),
+        8 => Some([channel(0)?, channel(2)?, channel(4)?, channel(6)?]),
+        _ => None,
+    }
+}
+
+/// Whether every channel of `pixel` is within `tolerance` of `expected`
+pub fn pixel_matches(pixel: [u8; 4], expected: [u8; 4], tolerance: u8) -> bool {
+    pixel.iter().zip(expected.iter()).all(|(a, b)| a.abs_diff(*b) <= tolerance)
+}
+
+/// Match a title against a wildcard pattern (`*` and `?`), or a prefix when it has no wildcards
+fn title_matches(pattern: &str, title: &str) -> bool {
+    if pattern.contains(['*', '?']) {
+        url_pattern_matches(pattern, title)
+    } else {
+        title.starts_with(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{evaluate_response, MockCdpClient, MOCK_TAB_ID};
+
+    #[test]
+    fn test_browser_creation() {
+        let result = Browser::new("localhost", 9222);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_network_event_structure() {
+        let event = NetworkEvent {
+            request_id: "req_123".to_string(),
+            url: "https://example.com".to_string(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            timestamp: 1640995200.0,
+            status_code: Some(200),
+            response_headers: None,
+            body: None,
+            body_decoded: Value::Null,
+            finished: false,
+            error_text: None,
+        };
+
+        assert_eq!(event.request_id, "req_123");
+        assert_eq!(event.url, "https://example.com");
+        assert_eq!(event.method, "GET");
+        assert_eq!(event.status_code, Some(200));
+        assert!(event.response_headers.is_none());
+    }
+
+    #[test]
+    fn test_network_event_serialization() {
+        let mut headers = HashMap::new();
+        headers.insert("User-Agent".to_string(), "chrome-mcp/0.1.0".to_string());
+        headers.insert("Accept".to_string(), "application/json".to_string());
+
+        let event = NetworkEvent {
+            request_id: "req_456".to_string(),
+            url: "https://api.example.com/data".to_string(),
+            method: "POST".to_string(),
+            headers,
+            timestamp: 1640995260.5,
+            status_code: Some(201),
+            response_headers: Some(HashMap::new()),
+            body: None,
+            body_decoded: Value::Null,
+            finished: false,
+            error_text: None,
+        };
+
+        let json_str = serde_json::to_string(&event).unwrap();
+        let parsed: NetworkEvent = serde_json::from_str(&json_str).unwrap();
+
+        assert_eq!(event.request_id, parsed.request_id);
+        assert_eq!(event.url, parsed.url);
+        assert_eq!(event.method, parsed.method);
+        assert_eq!(event.status_code, parsed.status_code);
+    }
+
+    #[test]
+    fn test_decode_response_body() {
+        let (raw, decoded) = decode_response_body(Some("application/json; charset=utf-8"), r#"{"id":1}"#, false);
+        assert_eq!(raw, r#"{"id":1}"#);
+        assert_eq!(decoded, json!({ "id": 1 }));
+
+        let (_, decoded) = decode_response_body(Some("application/problem+json"), "not json", false);
+        assert_eq!(decoded, json!("not json"));
+
+        let (raw, decoded) = decode_response_body(Some("text/html"), &BASE64.encode("<p>hi</p>"), true);
+        assert_eq!(raw, "<p>hi</p>");
+        assert_eq!(decoded, json!("<p>hi</p>"));
+
+        let (raw, decoded) = decode_response_body(Some("image/png"), "iVBORw0KGgo=", true);
+        assert_eq!(raw, "iVBORw0KGgo=");
+        assert_eq!(decoded, json!("iVBORw0KGgo="));
+
+        let (_, decoded) = decode_response_body(Some("application/octet-stream"), "abc", false);
+        assert_eq!(decoded, json!(BASE64.encode("abc")));
+
+        let (_, decoded) = decode_response_body(None, "plain", false);
+        assert_eq!(decoded, json!("plain"));
+    }
+
+    #[tokio::test]
+    async fn test_wasm_modules() {
+        let mock = MockCdpClient::new(vec![
+            ("Page.addScriptToEvaluateOnNewDocument", json!({ "identifier": "1" })),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.evaluate", evaluate_response(json!([{
+                "name": "https://example.com/app.wasm",
+                "exports": [
+                    { "name": "add", "kind": "function" },
+                    { "name": "memory", "kind": "memory" },
+                    { "name": "counter", "kind": "global" },
+                ]
+            }]))),
+            ("Runtime.evaluate", evaluate_response(json!({ "found": true, "result": 5 }))),
+            ("Runtime.evaluate", evaluate_response(json!({ "found": false }))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let modules = browser.get_wasm_modules().await.unwrap();
+        assert_eq!(modules[0].name.as_deref(), Some("https://example.com/app.wasm"));
+        assert_eq!(modules[0].exports[1], WasmExport { name: "memory".to_string(), kind: WasmExportKind::Memory });
+
+        // Tracking is only installed once
+        let result = browser.call_wasm_export(None, "add", vec![json!(2), json!(3)]).await.unwrap();
+        assert_eq!(result, json!(5));
+        let expression = mock.calls()[3].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"exports["add"](...[2,3])"#));
+        assert!(expression.contains("(null === null || entry.name === null)"));
+
+        let error = browser.call_wasm_export(Some("other.wasm"), "add", vec![]).await.unwrap_err();
+        assert!(error.to_string().contains("No tracked WebAssembly module exports a function named add"));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_selector_errors_carry_trace() {
+        let mock = MockCdpClient::new(vec![("DOM.getDocument", json!({ "root": { "nodeId": 1 } }))]);
+        mock.push_error("DOM.querySelectorAll", "DOMException: not a valid selector");
+        mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+        mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [] }));
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let error = browser.find_element_by_selector("div[").await.unwrap_err();
+        let trace = error.trace();
+        assert_eq!((trace[0].module.as_str(), trace[0].operation.as_str()), ("browser", "find_element_by_selector"));
+        assert_eq!(trace[0].context["selector"], "div[");
+        assert_eq!((trace[1].module.as_str(), trace[1].operation.as_str()), ("cdp", "send_command"));
+        assert_eq!(trace[1].context["method"], "DOM.querySelectorAll");
+
+        let error = browser.find_element_by_selector("#missing").await.unwrap_err();
+        assert!(matches!(error.root_cause(), ChromeMcpError::ElementNotFound(_)));
+        assert_eq!(error.trace()[0].context["selector"], "#missing");
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_request_conditions() {
+        let mock = MockCdpClient::new(vec![]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let page = mock.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            let request = |id: &str, method: &str, url: &str| json!({
+                "requestId": id,
+                "timestamp": 1.5,
+                "request": { "url": url, "method": method, "headers": { "Accept": "application/json" } }
+            });
+            page.emit_event("Network.requestWillBeSent", request("1", "POST", "https://api.example.com/orders?page=1"));
+            page.emit_event("Network.requestWillBeSent", request("2", "GET", "https://cdn.example.com/app.js"));
+            page.emit_event("Network.responseReceived", json!({
+                "requestId": "1",
+                "response": { "status": 201, "headers": { "Content-Type": "application/json" } }
+            }));
+            page.emit_event("Network.loadingFinished", json!({ "requestId": "1" }));
+            page.emit_event("Network.loadingFailed", json!({ "requestId": "2", "errorText": "net::ERR_BLOCKED_BY_CLIENT" }));
+        });
+
+        let completed = |method: Option<&str>| WaitCondition::RequestCompleted {
+            url_pattern: "*/orders*".to_string(),
+            method: method.map(|m| m.to_string()),
+        };
+        browser.wait_for_condition(completed(Some("post")), 2000).await.unwrap();
+        assert_eq!(browser.network_events.lock().unwrap()[0].status_code, Some(201));
+        assert!(browser.wait_for_condition(completed(Some("GET")), 300).await.is_err());
+
+        let failed = |url_pattern: &str| WaitCondition::RequestFailed { url_pattern: url_pattern.to_string() };
+        browser.wait_for_condition(failed("https://cdn.example.com/*.js"), 2000).await.unwrap();
+        assert!(browser.wait_for_condition(failed("*/orders*"), 300).await.is_err());
+        assert!(!browser.handle_network_event("Network.loadingFinished", &json!({ "requestId": "unknown" })));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_network_idle() {
+        let mock = MockCdpClient::new(vec![]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let page = mock.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            page.emit_event("Network.requestWillBeSent", json!({
+                "requestId": "1",
+                "request": { "url": "https://api.example.com/slow", "method": "GET" }
+            }));
+            sleep(Duration::from_millis(400)).await;
+            page.emit_event("Network.loadingFinished", json!({ "requestId": "1" }));
+        });
+
+        // Idle only once the request has finished, not during the gap before it finishes
+        let started = Instant::now();
+        browser.wait_for_condition(WaitCondition::NetworkIdle { idle_ms: 200, max_inflight: 0 }, 2000).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(650), "idle after {:?}", started.elapsed());
+        assert!(browser.network_events.lock().unwrap()[0].finished);
+
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            mock.emit_event("Network.requestWillBeSent", json!({ "requestId": "2", "request": { "url": "https://api.example.com/hung" } }));
+        });
+        let error = browser.wait_for_condition(WaitCondition::NetworkIdle { idle_ms: 100, max_inflight: 0 }, 300).await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::Timeout { timeout: 300 }));
+
+        // The hung request is tolerated as a long-lived one
+        browser.wait_for_condition(WaitCondition::NetworkIdle { idle_ms: 100, max_inflight: 2 }, 1000).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_response_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Network.getResponseBody", json!({ "body": r#"{"users":[]}"#, "base64Encoded": false })),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        browser.network_events.lock().unwrap().push(NetworkEvent {
+            request_id: "req_1".to_string(),
+            url: "https://api.example.com/users".to_string(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            timestamp: 0.0,
+            status_code: Some(200),
+            response_headers: Some(HashMap::from([("Content-Type".to_string(), "application/json".to_string())])),
+            body: None,
+            body_decoded: Value::Null,
+            finished: false,
+            error_text: None,
+        });
+
+        let event = browser.get_response("req_1").await.unwrap();
+        assert_eq!(event.body.as_deref(), Some(r#"{"users":[]}"#));
+        assert_eq!(event.body_decoded, json!({ "users": [] }));
+        assert_eq!(browser.network_events.lock().unwrap()[0].body_decoded, json!({ "users": [] }));
+
+        let error = browser.get_response("req_2").await.unwrap_err();
+        assert!(error.to_string().contains("No captured request with id: req_2"));
+    }
+
+    #[tokio::test]
+    async fn test_network_capture() {
+        let mock = MockCdpClient::new(vec![
+            ("Network.getResponseBody", json!({ "body": r#"{"id":7}"#, "base64Encoded": false })),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.start_network_capture(true, DEFAULT_NETWORK_MAX_ENTRIES, DEFAULT_NETWORK_MAX_BODY_BYTES).await.unwrap();
+        mock.emit_event("Network.requestWillBeSent", json!({
+            "requestId": "1",
+            "timestamp": 3.25,
+            "request": { "url": "https://api.example.com/orders/7", "method": "GET", "headers": { "Accept": "application/json" } }
+        }));
+        mock.emit_event("Network.requestWillBeSent", json!({
+            "requestId": "2",
+            "request": { "url": "https://cdn.example.com/logo.png", "method": "GET" }
+        }));
+        mock.emit_event("Network.responseReceived", json!({
+            "requestId": "1",
+            "response": { "status": 200, "headers": { "content-type": "application/json" } }
+        }));
+        mock.emit_event("Network.loadingFinished", json!({ "requestId": "1" }));
+        mock.emit_event("Network.loadingFailed", json!({ "requestId": "2", "errorText": "net::ERR_ABORTED" }));
+        sleep(Duration::from_millis(50)).await;
+
+        let events = browser.get_network_events(None).unwrap();
+        assert_eq!(events.len(), 2);
+        let order = &events[0];
+        assert_eq!((order.url.as_str(), order.method.as_str(), order.timestamp), ("https://api.example.com/orders/7", "GET", 3.25));
+        assert_eq!(order.headers.get("Accept").map(String::as_str), Some("application/json"));
+        assert_eq!(order.status_code, Some(200));
+        assert!(order.finished);
+        assert_eq!(order.body.as_deref(), Some(r#"{"id":7}"#));
+        assert_eq!(order.body_decoded, json!({ "id": 7 }));
+        assert_eq!(events[1].error_text.as_deref(), Some("net::ERR_ABORTED"));
+        assert_eq!(events[1].body, None);
+
+        let orders = browser.get_network_events(Some(r"/orders/\d+$")).unwrap();
+        assert_eq!(orders.iter().map(|e| e.request_id.as_str()).collect::<Vec<_>>(), vec!["1"]);
+        assert!(browser.get_network_events(Some(r"\.css$")).unwrap().is_empty());
+        let error = browser.get_network_events(Some("orders/(")).unwrap_err();
+        assert!(error.to_string().contains("Invalid URL pattern"));
+
+        browser.stop_network_capture();
+        mock.emit_event("Network.requestWillBeSent", json!({ "requestId": "3", "request": { "url": "https://api.example.com/late" } }));
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(browser.get_network_events(None).unwrap().len(), 2);
+        assert_eq!(mock.calls().len(), 1);
+
+        // The oldest requests are dropped, and large bodies are not fetched or not kept
+        mock.push_response("Network.getResponseBody", json!({ "body": "x".repeat(20), "base64Encoded": false }));
+        browser.start_network_capture(true, 2, 10).await.unwrap();
+        for id in ["4", "5", "6"] {
+            mock.emit_event("Network.requestWillBeSent", json!({ "requestId": id, "request": { "url": format!("https://example.com/{}", id) } }));
+        }
+        mock.emit_event("Network.loadingFinished", json!({ "requestId": "5", "encodedDataLength": 11 }));
+        mock.emit_event("Network.loadingFinished", json!({ "requestId": "6", "encodedDataLength": 8 }));
+        sleep(Duration::from_millis(50)).await;
+        let events = browser.get_network_events(None).unwrap();
+        assert_eq!(events.iter().map(|e| e.request_id.as_str()).collect::<Vec<_>>(), vec!["5", "6"]);
+        assert!(events.iter().all(|event| event.finished && event.body.is_none()));
+        assert_eq!(mock.calls().len(), 2);
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[test]
+    fn test_key_shortcut_parsing() {
+        let shortcut = KeyShortcut::parse("Cmd+Shift+T").unwrap();
+        assert_eq!(shortcut.modifiers, KeyShortcut::META | KeyShortcut::SHIFT);
+        assert_eq!((shortcut.key.as_str(), shortcut.code.as_str(), shortcut.windows_virtual_key_code), ("T", "KeyT", 84));
+
+        let copy = KeyShortcut::parse("ctrl + c").unwrap();
+        assert_eq!(copy.modifiers, 2);
+        assert_eq!((copy.key.as_str(), copy.code.as_str()), ("c", "KeyC"));
+        assert_eq!(KeyShortcut::parse("Control+Alt+Meta+Shift+1").unwrap().modifiers, 15);
+
+        let keys = [
+            ("Alt+F4", "F4", 115),
+            ("F12", "F12", 123),
+            ("Enter", "Enter", 13),
+            ("Esc", "Escape", 27),
+            ("Shift+Tab", "Tab", 9),
+            ("Backspace", "Backspace", 8),
+            ("Delete", "Delete", 46),
+            ("Left", "ArrowLeft", 37),
+            ("ArrowDown", "ArrowDown", 40),
+            ("7", "Digit7", 55),
+        ];
+        for (name, code, key_code) in keys {
+            let shortcut = KeyShortcut::parse(name).unwrap();
+            assert_eq!((shortcut.code.as_str(), shortcut.windows_virtual_key_code), (code, key_code), "{}", name);
+        }
+
+        for invalid in ["Ctrl+F13", "Hyper+A", "Ctrl+", "", "Ctrl+Shift+Tab+X+Y"] {
+            let error = KeyShortcut::parse(invalid).unwrap_err();
+            assert!(matches!(error, ChromeMcpError::InvalidOperation(_)), "{}: {}", invalid, error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_press_shortcut_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Input.dispatchKeyEvent", json!({})),
+            ("Input.dispatchKeyEvent", json!({})),
+            ("Input.dispatchKeyEvent", json!({})),
+            ("Input.dispatchKeyEvent", json!({})),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.press_shortcut("Ctrl+A").await.unwrap();
+        browser.press_shortcut("Enter").await.unwrap();
+        assert!(browser.press_shortcut("Ctrl+Launch").await.is_err());
+
+        let calls = mock.calls();
+        assert_eq!(calls.len(), 4);
+        assert_eq!(calls[0].1, Some(json!({ "type": "keyDown", "modifiers": 2, "key": "a", "code": "KeyA", "windowsVirtualKeyCode": 65 })));
+        assert_eq!(calls[1].1.as_ref().unwrap()["type"], "keyUp");
+        assert_eq!(calls[2].1.as_ref().unwrap()["text"], "\r");
+        assert!(calls[3].1.as_ref().unwrap().get("text").is_none());
+    }
+
+    #[test]
+    fn test_cookie_structure() {
+        let cookie = Cookie {
+            name: "session_id".to_string(),
+            value: "abc123".to_string(),
+            domain: "example.com".to_string(),
+            path: "/".to_string(),
+            secure: true,
+            http_only: false,
+            same_site: Some("Lax".to_string()),
+            expires: Some(1672531200.0), // 2023-01-01
+        };
+
+        assert_eq!(cookie.name, "session_id");
+        assert_eq!(cookie.value, "abc123");
+        assert_eq!(cookie.domain, "example.com");
+        assert_eq!(cookie.path, "/");
+        assert!(cookie.secure);
+        assert!(!cookie.http_only);
+        assert_eq!(cookie.same_site, Some("Lax".to_string()));
+        assert!(cookie.expires.is_some());
+    }
+
+    #[test]
+    fn test_cookie_serialization() {
+        let cookie = Cookie {
+            name: "test_cookie".to_string(),
+            value: "test_value".to_string(),
+            domain: "localhost".to_string(),
+            path: "/test".to_string(),
+            secure: false,
+            http_only: true,
+            same_site: Some("Strict".to_string()),
+            expires: None,
+        };
+
+        let json_str = serde_json::to_string(&cookie).unwrap();
+        let parsed: Cookie = serde_json::from_str(&json_str).unwrap();
+
+        assert_eq!(cookie.name, parsed.name);
+        assert_eq!(cookie.value, parsed.value);
+        assert_eq!(cookie.domain, parsed.domain);
+        assert_eq!(cookie.path, parsed.path);
+        assert_eq!(cookie.secure, parsed.secure);
+        assert_eq!(cookie.http_only, parsed.http_only);
+        assert_eq!(cookie.same_site, parsed.same_site);
+        assert_eq!(cookie.expires, parsed.expires);
+    }
+
+    #[test]
+    fn test_session_state_serialization() {
+        let mut local = HashMap::new();
+        local.insert("token".to_string(), "abc123".to_string());
+
+        let mut state = SessionState {
+            url: Some("https://example.com/dashboard".to_string()),
+            cookies: vec![Cookie {
+                name: "sid".to_string(),
+                value: "xyz".to_string(),
+                domain: "example.com".to_string(),
+                path: "/".to_string(),
+                secure: true,
+                http_only: true,
+                same_site: None,
+                expires: None,
+            }],
+            ..Default::default()
+        };
+        state.local_storage.insert("https://example.com".to_string(), local);
+
+        let json_str = serde_json::to_string(&state).unwrap();
+        let parsed: SessionState = serde_json::from_str(&json_str).unwrap();
+
+        assert_eq!(parsed.url, state.url);
+        assert_eq!(parsed.cookies.len(), 1);
+        assert_eq!(parsed.cookies[0].name, "sid");
+        assert_eq!(parsed.local_storage["https://example.com"]["token"], "abc123");
+        assert!(parsed.session_storage.is_empty());
+    }
+
+    #[test]
+    fn test_table_data_deserialization() {
+        let value = json!({
+            "headers": ["Name", "Age"],
+            "rows": [["Alice", "30"], ["Bob", "30"]],
+            "caption": null
+        });
+
+        let table: TableData = serde_json::from_value(value).unwrap();
+        assert_eq!(table.headers, vec!["Name", "Age"]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[1], vec!["Bob", "30"]);
+        assert!(table.caption.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_extract_table_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({
+                "headers": ["Name", "Age"],
+                "rows": [["Alice", "30"], ["Bob", "30"]],
+                "caption": "People"
+            }))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.evaluate", evaluate_response(json!([{ "headers": [], "rows": [["1", "2"]], "caption": null }]))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let table = browser.extract_table("#people").await.unwrap();
+        assert_eq!(table.headers, vec!["Name", "Age"]);
+        assert_eq!(table.rows, vec![vec!["Alice", "30"], vec!["Bob", "30"]]);
+        assert_eq!(table.caption.as_deref(), Some("People"));
+
+        let error = browser.extract_table("#missing").await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::ElementNotFound(_)));
+
+        let tables = browser.extract_tables().await.unwrap();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].rows[0], vec!["1", "2"]);
+
+        let calls = mock.calls();
+        assert!(calls.iter().all(|(method, _)| method == "Runtime.evaluate"));
+        let expression = |index: usize| calls[index].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression(0).contains("document.querySelector('#people')"));
+        assert!(expression(0).contains(TABLE_EXTRACTOR_JS));
+        assert!(expression(2).contains("document.querySelectorAll('table')"));
+    }
+
+    #[test]
+    fn test_collect_frame_resources() {
+        let frame_tree = json!({
+            "frame": { "id": "main", "url": "https://example.com/", "mimeType": "text/html" },
+            "resources": [
+                { "url": "https://example.com/app.js", "type": "Script", "mimeType": "application/javascript", "contentSize": 1024.0 },
+                { "url": "https://example.com/api", "type": "XHR", "mimeType": "application/json" },
+                { "url": "https://example.com/site.webmanifest", "type": "Manifest", "mimeType": "application/manifest+json" }
+            ],
+            "childFrames": [{
+                "frame": { "id": "child", "url": "https://ads.example.net/frame", "mimeType": "text/html" },
+                "resources": [
+                    { "url": "https://ads.example.net/font.woff2", "type": "Font", "mimeType": "font/woff2" }
+                ]
+            }]
+        });
+
+        let mut resources = Vec::new();
+        collect_frame_resources(&frame_tree, &mut resources);
+
+        assert_eq!(resources.len(), 6);
+        assert_eq!(resources[0].type_, ResourceType::Document);
+        assert_eq!(resources[1].type_, ResourceType::Script);
+        assert_eq!(resources[1].content_size, Some(1024));
+        assert_eq!(resources[2].type_, ResourceType::Xhr);
+        assert_eq!(resources[3].type_, ResourceType::Other);
+        assert_eq!(resources[5].type_, ResourceType::Font);
+        assert_eq!(resources[5].frame_id, "child");
+    }
+
+    #[test]
+    fn test_parse_ice_candidate_ips() {
+        assert_eq!(
+            parse_ice_candidate_ips("candidate:842163049 1 udp 1677729535 203.0.113.7 54321 typ srflx raddr 192.168.1.20 rport 50000"),
+            vec!["203.0.113.7".to_string(), "192.168.1.20".to_string()]
+        );
+        assert_eq!(
+            parse_ice_candidate_ips("candidate:842163049 1 udp 1677729535 203.0.113.7 54321 typ srflx raddr 0.0.0.0 rport 0"),
+            vec!["203.0.113.7".to_string()]
+        );
+        assert_eq!(
+            parse_ice_candidate_ips("candidate:1 1 udp 2113937151 2001:db8::1 50000 typ host"),
+            vec!["2001:db8::1".to_string()]
+        );
+        assert!(parse_ice_candidate_ips("candidate:1 1 udp 2113937151 3c1b1f2e-1234-4bcd-9f00-abcdef012345.local 50000 typ host").is_empty());
+        assert!(parse_ice_candidate_ips("garbage").is_empty());
+    }
+
+    #[test]
+    fn test_find_text_matches() {
+        let regex = RegexBuilder::new("order #\\d+").case_insensitive(true).build().unwrap();
+        let text = "Thank you! Your Order #12345 has been placed and will ship soon.";
+
+        let matches = find_text_matches(&regex, text, "main", None, Some("#confirmation".to_string()));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].matched_text, "Order #12345");
+        assert_eq!(matches[0].surrounding_text, "Thank you! Your Order #12345 has been placed and");
+        assert_eq!(matches[0].node_selector.as_deref(), Some("#confirmation"));
+        assert!(matches[0].line_number.is_none());
+    }
+
+    #[test]
+    fn test_find_text_matches_literal_pattern() {
+        let regex = RegexBuilder::new(&regex::escape("$9.99 (USD)")).build().unwrap();
+        let matches = find_text_matches(&regex, "Price: $9.99 (USD) or $9.99 (USD)", "frame", Some(12), None);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[1].line_number, Some(12));
+        assert_eq!(matches[1].surrounding_text, "ice: $9.99 (USD) or $9.99 (USD)");
+    }
+
+    #[test]
+    fn test_element_state_deserialization() {
+        let state: ElementState = serde_json::from_value(json!({
+            "checked": true,
+            "disabled": false,
+            "read_only": false,
+            "required": true,
+            "value": "on",
+            "valid": false,
+            "validation_message": "Please check this box if you want to proceed.",
+            "focused": true,
+            "indeterminate": false
+        })).unwrap();
+
+        assert!(state.checked);
+        assert!(state.required);
+        assert!(!state.valid);
+        assert_eq!(state.value.as_deref(), Some("on"));
+        assert!(state.validation_message.starts_with("Please check"));
+    }
+
+    #[test]
+    fn test_fnv1a_hash() {
+        // Reference values of the 64-bit FNV-1a specification
+        assert_eq!(fnv1a_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a_hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(fnv1a_hash(b"<p>before</p>"), fnv1a_hash(b"<p>after</p>"));
+    }
+
+    #[test]
+    fn test_position_sample_trace_deserialization() {
+        let trace: Vec<PositionSample> = serde_json::from_value(json!([
+            { "timestamp_ms": 0.0, "x": 10.0, "y": 20.0, "width": 100.0, "height": 50.0 },
+            { "timestamp_ms": 16.7, "x": 14.5, "y": 20.0, "width": 100.0, "height": 50.0 }
+        ])).unwrap();
+
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace[1].x, 14.5);
+        assert!(trace[1].timestamp_ms > trace[0].timestamp_ms);
+    }
+
+    #[test]
+    fn test_cpu_profile_roundtrip() {
+        let profile: CpuProfile = serde_json::from_value(json!({
+            "nodes": [{
+                "id": 1,
+                "callFrame": { "functionName": "(root)", "scriptId": "0", "url": "", "lineNumber": -1, "columnNumber": -1 },
+                "children": [2]
+            }],
+            "startTime": 1000.0,
+            "endTime": 251000.0,
+            "samples": [1, 2, 2],
+            "timeDeltas": [100, 250, 250]
+        })).unwrap();
+
+        assert_eq!(profile.nodes.len(), 1);
+        assert_eq!(profile.duration_ms(), 250.0);
+
+        // Field names must stay in the DevTools .cpuprofile format
+        let value = serde_json::to_value(&profile).unwrap();
+        assert!(value.get("startTime").is_some());
+        assert!(value.get("timeDeltas").is_some());
+    }
+
+    #[test]
+    fn test_snap_scroll_axis() {
+        assert_eq!(snap_scroll_axis(ScrollDirection::Right, "x"), ("x", 1));
+        assert_eq!(snap_scroll_axis(ScrollDirection::Left, "x"), ("x", -1));
+        // Vertical carousels map left/right onto their own axis
+        assert_eq!(snap_scroll_axis(ScrollDirection::Right, "y"), ("y", 1));
+        assert_eq!(snap_scroll_axis(ScrollDirection::Up, "both"), ("y", -1));
+        assert_eq!(serde_json::to_value(ScrollDirection::Down).unwrap(), json!("down"));
+    }
+
+    #[test]
+    fn test_js_error_capture_script() {
+        let script = JS_ERROR_CAPTURE_JS.replace("{capacity}", "50");
+        assert!(script.contains("window.__chromeMcpJsErrorCapacity = 50;"));
+        assert!(!script.contains("{capacity}"));
+        assert!(script.contains("unhandledrejection"));
+    }
+
+    #[test]
+    fn test_validation_error_deserialization() {
+        let errors: Vec<ValidationError> = serde_json::from_value(json!([{
+            "selector": "#signup > input:nth-of-type(2)",
+            "field_name": "email",
+            "message": "Please include an '@' in the email address.",
+            "validity_state": { "valueMissing": false, "typeMismatch": true }
+        }])).unwrap();
+
+        assert_eq!(errors[0].field_name.as_deref(), Some("email"));
+        assert_eq!(errors[0].validity_state.get("typeMismatch"), Some(&true));
+        assert_eq!(errors[0].validity_state.get("valueMissing"), Some(&false));
+    }
+
+    #[test]
+    fn test_evaluate_cors_headers() {
+        let headers = vec!["Authorization".to_string(), "Content-Type".to_string()];
+
+        let result = evaluate_cors_headers(
+            "https://app.example",
+            "put",
+            &headers,
+            Some("https://app.example"),
+            Some("GET, PUT, DELETE"),
+            Some("authorization"),
+        );
+        assert!(result.allowed);
+        assert_eq!(result.allowed_methods, vec!["GET", "PUT", "DELETE"]);
+
+        let result = evaluate_cors_headers("https://evil.example", "GET", &[], Some("https://app.example"), None, None);
+        assert!(!result.allowed);
+        assert!(result.error.unwrap().contains("https://evil.example"));
+
+        let result = evaluate_cors_headers("https://app.example", "PATCH", &[], Some("*"), Some("GET, PUT"), None);
+        assert_eq!(result.error.as_deref(), Some("Method PATCH not allowed"));
+
+        let result = evaluate_cors_headers("https://app.example", "GET", &headers, Some("*"), None, None);
+        assert_eq!(result.error.as_deref(), Some("Header Authorization not allowed"));
+    }
+
+    #[test]
+    fn test_svg_element_deserialization() {
+        let elements: Vec<SvgElement> = serde_json::from_value(json!([
+            { "tag": "rect", "id": "bar-1", "class": "bar", "bounds": [10.0, 40.0, 20.0, 60.0], "text": null, "path_data": null },
+            { "tag": "path", "id": null, "class": null, "bounds": null, "text": null, "path_data": "M0 0 L10 10" }
+        ])).unwrap();
+
+        assert_eq!(elements[0].bounds, Some((10.0, 40.0, 20.0, 60.0)));
+        assert_eq!(elements[1].path_data.as_deref(), Some("M0 0 L10 10"));
+    }
+
+    #[test]
+    fn test_canvas_pixel_helpers() {
+        let pixels = rgba_pixels(&[0, 128, 0, 255, 255, 255, 255, 255, 7]);
+        assert_eq!(pixels, vec![[0, 128, 0, 255], [255, 255, 255, 255]]);
+
+        assert_eq!(parse_hex_color("#008000"), Some([0, 128, 0, 255]));
+        assert_eq!(parse_hex_color("#0f08"), None);
+        assert_eq!(parse_hex_color("#fff"), Some([255, 255, 255, 255]));
+        assert_eq!(parse_hex_color("00800080"), Some([0, 128, 0, 128]));
+        assert_eq!(parse_hex_color("#zz0000"), None);
+
+        assert!(pixel_matches([2, 126, 0, 255], [0, 128, 0, 255], 2));
+        assert!(!pixel_matches([2, 126, 0, 255], [0, 128, 0, 255], 1));
+    }
+
+    #[test]
+    fn test_session_checkpoint_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.json");
+        assert!(SessionCheckpoint::load(&path).unwrap().is_none());
+
+        let checkpoint = SessionCheckpoint {
+            tab_id: "ABC123".to_string(),
+            enabled_domains: vec!["Fetch".to_string()],
+            injected_scripts: vec!["window.__injected = true;".to_string()],
+            current_url: "https://example.com/dashboard".to_string(),
+            cookie_state: vec![Cookie {
+                name: "session".to_string(),
+                value: "xyz".to_string(),
+                domain: "example.com".to_string(),
+                path: "/".to_string(),
+                secure: true,
+                http_only: true,
+                same_site: Some("Lax".to_string()),
+                expires: None,
+            }],
+        };
+        checkpoint.save(&path).unwrap();
+        checkpoint.save(&path).unwrap();
+
+        let loaded = SessionCheckpoint::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.tab_id, "ABC123");
+        assert_eq!(loaded.enabled_domains, vec!["Fetch"]);
+        assert_eq!(loaded.injected_scripts.len(), 1);
+        assert_eq!(loaded.current_url, "https://example.com/dashboard");
+        assert_eq!(loaded.cookie_state[0].name, "session");
+        assert!(!dir.path().join("checkpoint.json.tmp").exists());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(SessionCheckpoint::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_performance_timing_from_entries() {
+        let navigation = navigation_timing_from_entry(&json!({
+            "name": "https://example.com/",
+            "type": "navigate",
+            "startTime": 0,
+            "duration": 812.4,
+            "redirectCount": 0,
+            "redirectStart": 0,
+            "redirectEnd": 0,
+            "domainLookupStart": 5.0,
+            "domainLookupEnd": 25.0,
+            "connectStart": 25.0,
+            "secureConnectionStart": 40.0,
+            "connectEnd": 70.0,
+            "requestStart": 71.0,
+            "responseStart": 180.5,
+            "responseEnd": 230.5,
+            "domInteractive": 400.0,
+            "domContentLoadedEventEnd": 420.0,
+            "domComplete": 800.0,
+            "loadEventStart": 800.0,
+            "loadEventEnd": 812.4,
+            "transferSize": 15300,
+            "encodedBodySize": 15000,
+            "decodedBodySize": 48000
+        }));
+
+        assert_eq!(navigation.url, "https://example.com/");
+        assert_eq!(navigation.navigation_type, "navigate");
+        assert_eq!(navigation.redirect_ms, 0.0);
+        assert_eq!(navigation.dns_lookup_ms, 20.0);
+        assert_eq!(navigation.tcp_connect_ms, 45.0);
+        assert_eq!(navigation.ssl_handshake_ms, 30.0);
+        assert_eq!(navigation.ttfb_ms, 180.5);
+        assert_eq!(navigation.response_ms, 50.0);
+        assert_eq!(navigation.dom_interactive_ms, 400.0);
+        assert_eq!(navigation.dom_complete_ms, 800.0);
+        assert!((navigation.load_event_ms - 12.4).abs() < 1e-9);
+        assert_eq!(navigation.decoded_body_size, 48000);
+
+        // Cross-origin resource without Timing-Allow-Origin
+        let resource = resource_timing_from_entry(&json!({
+            "name": "https://cdn.example.net/app.js",
+            "initiatorType": "script",
+            "startTime": 250.0,
+            "duration": 90.0,
+            "domainLookupStart": 0,
+            "domainLookupEnd": 0,
+            "connectStart": 0,
+            "secureConnectionStart": 0,
+            "connectEnd": 0,
+            "responseStart": 0,
+            "responseEnd": 340.0,
+            "transferSize": 0
+        }));
+
+        assert_eq!(resource.initiator_type, "script");
+        assert_eq!(resource.start_time_ms, 250.0);
+        assert_eq!(resource.duration_ms, 90.0);
+        assert_eq!(resource.ttfb_ms, 0.0);
+        assert_eq!(resource.response_ms, 0.0);
+        assert_eq!(resource.transfer_size, 0);
+    }
+
+    #[tokio::test]
+    async fn test_browser_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({
+                "name": "https://example.com/",
+                "type": "reload",
+                "startTime": 0,
+                "duration": 350.0,
+                "responseStart": 120.0,
+                "responseEnd": 150.0,
+                "domInteractive": 200.0,
+                "domComplete": 340.0
+            }))),
+            ("Network.getCookies", json!({
+                "cookies": [{
+                    "name": "session",
+                    "value": "abc",
+                    "domain": "example.com",
+                    "path": "/",
+                    "expires": -1,
+                    "httpOnly": true,
+                    "secure": true,
+                    "sameSite": "Lax"
+                }]
+            })),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        assert_eq!(browser.connect(None).await.unwrap(), MOCK_TAB_ID);
+
+        let timing = browser.get_navigation_timing().await.unwrap();
+        assert_eq!(timing.navigation_type, "reload");
+        assert_eq!(timing.ttfb_ms, 120.0);
+        assert_eq!(timing.response_ms, 30.0);
+
+        let cookies = browser.get_cookies().await.unwrap();
+        assert_eq!(cookies.len(), 1);
+        assert!(cookies[0].http_only);
+
+        assert_eq!(mock.remaining(), 0);
+        assert!(browser.get_navigation_timing().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cookie_filters() {
+        let cookie = |name: &str, domain: &str| json!({
+            "name": name,
+            "value": "1",
+            "domain": domain,
+            "path": "/",
+            "httpOnly": false,
+            "secure": false
+        });
+        let mock = MockCdpClient::new(vec![
+            ("Network.getCookies", json!({ "cookies": [cookie("session", "shop.example.com"), cookie("theme", ".example.com")] })),
+            ("Network.getCookies", json!({ "cookies": [cookie("session", "shop.example.com"), cookie("theme", ".example.com")] })),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let cookies = browser.get_cookies_for_url("https://shop.example.com/cart").await.unwrap();
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(mock.calls()[0].1, Some(json!({ "urls": ["https://shop.example.com/cart"] })));
+
+        let cookies = browser.get_cookies_by_name("theme").await.unwrap();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].domain, ".example.com");
+
+        assert!(browser.get_cookies_for_url("not a url").await.is_err());
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_map_tabs_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!(1))),
+            ("Runtime.evaluate", evaluate_response(json!(1))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        browser.connect(None).await.unwrap();
+        let second_tab = browser.create_tab(Some("https://example.com")).await.unwrap();
+
+        let tab_ids = browser.map_tabs(|mut tab_browser| async move {
+            tab_browser.evaluate("1").await?;
+            Ok(tab_browser.current_tab_id().unwrap().to_string())
+        }).await.unwrap();
+        assert_eq!(tab_ids, vec![MOCK_TAB_ID.to_string(), second_tab]);
+        assert_eq!(mock.remaining(), 0);
+
+        // The original browser stays on its tab
+        assert_eq!(browser.current_tab_id(), Some(MOCK_TAB_ID));
+
+        let error = browser.map_tabs(|tab_browser| async move {
+            match tab_browser.current_tab_id() {
+                Some(MOCK_TAB_ID) => Err(ChromeMcpError::invalid_operation("tab failed")),
+                _ => Ok(()),
+            }
+        }).await.unwrap_err();
+        assert!(error.to_string().contains("tab failed"));
+    }
+
+    #[test]
+    fn test_console_entry_from_event() {
+        let entry = ConsoleEntry::from_event(CONSOLE_API_CALLED, &json!({
+            "type": "warning",
+            "timestamp": 1700000000000.0,
+            "args": [
+                { "type": "string", "value": "retrying" },
+                { "type": "number", "value": 3 },
+                { "type": "number", "unserializableValue": "NaN" },
+                { "type": "object", "className": "Object", "description": "Object" }
+            ]
+        })).unwrap();
+        assert_eq!(entry, ConsoleEntry { level: "warn".to_string(), text: "retrying 3 NaN Object".to_string(), timestamp: 1700000000000.0 });
+
+        let entry = ConsoleEntry::from_event(EXCEPTION_THROWN, &json!({
+            "timestamp": 1700000000001.0,
+            "exceptionDetails": { "text": "Uncaught", "exception": { "description": "TypeError: x is undefined\n    at app.js:3" } }
+        })).unwrap();
+        assert_eq!(entry.level, "error");
+        assert!(entry.text.starts_with("TypeError: x is undefined"));
+
+        assert!(ConsoleEntry::from_event("Runtime.bindingCalled", &json!({})).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_console_capture() {
+        let mock = MockCdpClient::new(vec![]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.start_console_capture(2).await.unwrap();
+        let log = |level: &str, text: &str| json!({ "type": level, "timestamp": 1.0, "args": [{ "type": "string", "value": text }] });
+        mock.emit_event(CONSOLE_API_CALLED, log("log", "dropped"));
+        mock.emit_event(CONSOLE_API_CALLED, log("info", "loaded"));
+        mock.emit_event(EXCEPTION_THROWN, json!({ "timestamp": 2.0, "exceptionDetails": { "text": "Uncaught boom" } }));
+        sleep(Duration::from_millis(50)).await;
+
+        // Capped at the two latest entries
+        let texts: Vec<String> = browser.get_console_entries(None).into_iter().map(|entry| entry.text).collect();
+        assert_eq!(texts, vec!["loaded", "Uncaught boom"]);
+        let errors = browser.get_console_entries(Some("error"));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].timestamp, 2.0);
+
+        browser.stop_console_capture();
+        mock.emit_event(CONSOLE_API_CALLED, log("error", "after stop"));
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(browser.get_console_entries(None).len(), 2);
+        assert!(mock.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_storage_access() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!("dark"))),
+            ("Runtime.evaluate", evaluate_response(json!({ "theme": "dark", "it's": "[1,2]" }))),
+            ("Runtime.evaluate", json!({ "result": { "type": "undefined" } })),
+            ("Runtime.evaluate", json!({ "result": { "type": "undefined" } })),
+            ("Runtime.evaluate", json!({ "result": { "type": "undefined" } })),
+            ("Runtime.evaluate", json!({ "result": { "type": "undefined" } })),
+            ("Runtime.evaluate", evaluate_response(json!(["theme", "it's"]))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        assert_eq!(browser.storage_get(StorageType::Local, Some("theme")).await.unwrap(), json!("dark"));
+        assert_eq!(browser.storage_get(StorageType::Session, None).await.unwrap()["it's"], "[1,2]");
+        browser.storage_set(StorageType::Local, "it's", &json!([1, 2])).await.unwrap();
+        browser.storage_set(StorageType::Local, "theme", &json!("light")).await.unwrap();
+        browser.storage_remove(StorageType::Session, "it's").await.unwrap();
+        browser.storage_clear(StorageType::Session).await.unwrap();
+        assert_eq!(browser.storage_keys(StorageType::Local).await.unwrap(), vec!["theme", "it's"]);
+
+        let expressions: Vec<String> = mock.calls().iter().map(|(_, params)| params.as_ref().unwrap()["expression"].as_str().unwrap().to_string()).collect();
+        assert_eq!(expressions, vec![
+            r#"localStorage.getItem("theme")"#,
+            "Object.fromEntries(Object.keys(sessionStorage).map((key) => [key, sessionStorage.getItem(key)]))",
+            r#"(() => { const value = [1,2]; localStorage.setItem("it's", typeof value === 'string' ? value : JSON.stringify(value)); })()"#,
+            r#"(() => { const value = "light"; localStorage.setItem("theme", typeof value === 'string' ? value : JSON.stringify(value)); })()"#,
+            r#"sessionStorage.removeItem("it's")"#,
+            "sessionStorage.clear()",
+            "Object.keys(localStorage)",
+        ]);
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_storage_events_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.addBinding", json!({})),
+        ]);
+        let change = json!({
+            "key": "cart",
+            "old_value": null,
+            "new_value": "[1]",
+            "url": "https://shop.example.com/",
+            "storage_type": "session",
+            "timestamp_ms": 1700000000000.0
+        });
+        mock.push_response_with_events("Runtime.evaluate", evaluate_response(Value::Null), vec![
+            ("Runtime.bindingCalled", json!({ "name": "__chromeMcpTitle_console", "payload": "{}" })),
+            ("Runtime.bindingCalled", json!({ "name": STORAGE_BINDING, "payload": change.to_string() })),
+        ]);
+        mock.push_response("Runtime.removeBinding", json!({}));
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.trigger_storage_event("cart", None, Some("[1]"), StorageType::Session).await.unwrap();
+        let expression = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"key: "cart", oldValue: null, newValue: "[1]""#));
+        assert!(expression.contains("storageArea: sessionStorage"));
+
+        let changes = browser.listen_for_storage_changes(500).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].key.as_deref(), Some("cart"));
+        assert_eq!(changes[0].old_value, None);
+        assert_eq!(changes[0].storage_type, StorageType::Session);
+
+        let calls = mock.calls();
+        assert_eq!(calls[1].1, Some(json!({ "name": STORAGE_BINDING })));
+        assert!(calls[2].1.as_ref().unwrap()["expression"].as_str().unwrap().contains("}, 500);"));
+        assert_eq!(calls[3], ("Runtime.removeBinding".to_string(), Some(json!({ "name": STORAGE_BINDING }))));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_observe_intersection() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.addBinding", json!({})),
+            ("Runtime.evaluate", evaluate_response(json!({ "intersected": true, "intersection_ratio": 0.75, "time_ms": 820 }))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.addBinding", json!({})),
+            ("Runtime.evaluate", evaluate_response(json!({ "intersected": false, "intersection_ratio": 0, "time_ms": 300 }))),
+            ("Runtime.addBinding", json!({})),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let result = browser.observe_intersection("#sentinel", Some("document.querySelector('.load-more').click()"), 5000).await.unwrap();
+        assert_eq!(result, IntersectionResult { intersected: true, intersection_ratio: 0.75, time_ms: 820 });
+        let calls = mock.calls();
+        assert_eq!(calls[0].1, Some(json!({ "name": INTERSECTION_BINDING })));
+        let expression = calls[1].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r##"document.querySelector("#sentinel")"##));
+        assert!(expression.contains("time_ms: 5000 }), 5000)"));
+        assert_eq!(calls[2].1.as_ref().unwrap()["expression"], "document.querySelector('.load-more').click()");
+
+        // The callback only runs once the sentinel intersected
+        let result = browser.observe_intersection("#sentinel", Some("loadMore()"), 300).await.unwrap();
+        assert!(!result.intersected);
+
+        let error = browser.observe_intersection("#missing", None, 300).await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::ElementNotFound(_)));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_capture_sse_events() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!([
+                { "event_type": "message", "data": "hello", "id": null, "received_at_ms": 12 },
+                { "event_type": "price", "data": "{\"usd\":3}", "id": "42", "received_at_ms": 250 },
+            ]))),
+            ("Runtime.evaluate", evaluate_response(json!([]))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let events = browser.capture_sse_events("/stream?topic=a", vec!["message", "price"], 2, 5000).await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].id, None);
+        assert_eq!(
+            events[1],
+            SseEvent { event_type: "price".to_string(), data: r#"{"usd":3}"#.to_string(), id: Some("42".to_string()), received_at_ms: 250 }
+        );
+
+        let expression = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"new EventSource("/stream?topic=a")"#));
+        assert!(expression.contains(r#"for (const type of ["message","price"])"#));
+        assert!(expression.contains("events.length >= 2"));
+        assert!(expression.contains("setTimeout(finish, 5000)"));
+
+        assert!(browser.capture_sse_events("/stream", vec![], 10, 100).await.unwrap().is_empty());
+        let expression = mock.calls()[1].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"["message"]"#));
+    }
+
+    #[tokio::test]
+    async fn test_connection_metrics() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!(1))),
+            ("Runtime.evaluate", evaluate_response(json!(2))),
+        ]);
+        mock.push_error("Runtime.evaluate", "Cannot find context");
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        assert_eq!(browser.get_connection_metrics(), ConnectionMetrics::default());
+
+        browser.evaluate("1").await.unwrap();
+        browser.evaluate("2").await.unwrap();
+        assert!(browser.evaluate("3").await.is_err());
+
+        let metrics = browser.get_connection_metrics();
+        assert_eq!(metrics.commands_sent, 3);
+        assert_eq!(metrics.commands_received, 3);
+        assert!(metrics.bytes_sent > 0 && metrics.bytes_received > 0);
+        assert!(metrics.max_round_trip_ms >= metrics.avg_round_trip_ms);
+
+        // Shared with the clones the accessibility and screenshot managers use
+        let _ = browser.accessibility_tree().await;
+        assert!(browser.get_connection_metrics().commands_sent > 3);
+
+        browser.reset_connection_metrics();
+        assert_eq!(browser.get_connection_metrics(), ConnectionMetrics::default());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_throttled_and_parallel() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!(1))),
+            ("Runtime.evaluate", evaluate_response(json!(2))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let started = std::time::Instant::now();
+        let results = browser.evaluate_throttled(vec!["1".to_string(), "1 + 1".to_string()], 100).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(results.iter().map(|r| r["value"].clone()).collect::<Vec<_>>(), vec![json!(1), json!(2)]);
+
+        for value in 0..5 {
+            mock.push_response("Runtime.evaluate", evaluate_response(json!(value)));
+        }
+        let expressions: Vec<String> = (0..5).map(|i| format!("{} * 10", i)).collect();
+        let results = browser.evaluate_batch_parallel(expressions, 2).await.unwrap();
+        assert_eq!(results.len(), 5);
+        let mut evaluated: Vec<String> = mock.calls()[2..]
+            .iter()
+            .map(|(_, params)| params.as_ref().unwrap()["expression"].as_str().unwrap().to_string())
+            .collect();
+        evaluated.sort();
+        assert_eq!(evaluated, vec!["0 * 10", "1 * 10", "2 * 10", "3 * 10", "4 * 10"]);
+
+        mock.push_error("Runtime.evaluate", "Execution context was destroyed");
+        let error = browser.evaluate_throttled(vec!["1".to_string(), "2".to_string()], 0).await.unwrap_err();
+        assert_eq!(error.trace()[0].context["index"], 0);
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_permissions() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({ "camera": "granted" }))),
+            ("Runtime.evaluate", evaluate_response(json!({}))),
+            ("Runtime.evaluate", evaluate_response(json!({ "notifications": "denied", "push": "prompt", "geolocation": "prompt" }))),
+            ("Runtime.evaluate", json!({ "result": { "value": "https://app.example.com/settings?tab=1" } })),
+            ("Browser.setPermission", json!({})),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        assert_eq!(browser.query_permission(BrowserPermission::Camera).await.unwrap(), PermissionState::Granted);
+        let expression = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"[{"name":"camera"}].map"#));
+
+        let error = browser.query_permission(BrowserPermission::Midi).await.unwrap_err();
+        assert!(error.to_string().contains("Permission not supported: midi"));
+
+        let states = browser.query_all_permissions().await.unwrap();
+        assert_eq!(states.len(), 3);
+        assert_eq!(states["notifications"], PermissionState::Denied);
+        let expression = mock.calls()[2].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"{"name":"push","userVisibleOnly":true}"#));
+        assert!(expression.contains(r#"{"name":"persistent-storage"}"#));
+
+        browser.grant_permission(BrowserPermission::Notifications).await.unwrap();
+        assert_eq!(mock.calls()[4].1, Some(json!({
+            "permission": { "name": "notifications" },
+            "setting": "granted",
+            "origin": "https://app.example.com"
+        })));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_clipboard() {
+        let mock = MockCdpClient::new(vec![]);
+        for items in [json!([]), json!([
+            { "type": "text/plain", "data_b64": "aGk=" },
+            { "type": "text/html", "data_b64": "PGI+aGk8L2I+" },
+            { "type": "image/png", "data_b64": "iVBORw0KGgo=" }
+        ])] {
+            mock.push_response("Runtime.evaluate", evaluate_response(json!({ "clipboard-read": "denied" })));
+            mock.push_response("Runtime.evaluate", json!({ "result": { "value": "https://app.example.com/" } }));
+            mock.push_response("Browser.setPermission", json!({}));
+            mock.push_response("Emulation.setFocusEmulationEnabled", json!({}));
+            mock.push_response("Runtime.evaluate", evaluate_response(items));
+            mock.push_response("Emulation.setFocusEmulationEnabled", json!({}));
+        }
+        // Already granted, so nothing is granted again
+        for text in [json!("copied"), json!({})] {
+            mock.push_response("Runtime.evaluate", evaluate_response(json!({ "clipboard-read": "granted" })));
+            mock.push_response("Emulation.setFocusEmulationEnabled", json!({}));
+            mock.push_response("Runtime.evaluate", evaluate_response(text));
+            mock.push_response("Emulation.setFocusEmulationEnabled", json!({}));
+        }
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let error = browser.read_clipboard_image().await.unwrap_err();
+        assert!(error.to_string().contains("no image/png item"));
+        assert!(mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().contains(r#"[{"name":"clipboard-read"}]"#));
+        assert_eq!(mock.calls()[2].1.as_ref().unwrap()["permission"], json!({ "name": "clipboard-read" }));
+        assert_eq!(mock.calls()[4].1.as_ref().unwrap()["userGesture"], json!(true));
+        // Focus emulation is turned back off after the read
+        assert_eq!(mock.calls()[5].1, Some(json!({ "enabled": false })));
+
+        let items = browser.read_clipboard_items().await.unwrap();
+        let kinds: Vec<ClipboardContentKind> = items.iter().map(|item| item.kind).collect();
+        assert_eq!(kinds, vec![ClipboardContentKind::Text, ClipboardContentKind::Html, ClipboardContentKind::Image]);
+        assert_eq!(items[2].data_b64, "iVBORw0KGgo=");
+
+        assert_eq!(browser.clipboard_read().await.unwrap(), "copied");
+        let error = browser.clipboard_read().await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::JavaScriptError(_)));
+        assert!(!mock.calls()[12..].iter().any(|(method, _)| method == "Browser.setPermission"));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_write_clipboard() {
+        let mock = MockCdpClient::new(vec![]);
+        for (write, copy) in [(Some(evaluate_response(Value::Null)), None), (None, Some(true)), (None, Some(false))] {
+            mock.push_response("Runtime.evaluate", evaluate_response(json!({ "clipboard-write": "denied" })));
+            mock.push_response("Runtime.evaluate", json!({ "result": { "value": "https://app.example.com/" } }));
+            mock.push_response("Browser.setPermission", json!({}));
+            mock.push_response("Emulation.setFocusEmulationEnabled", json!({}));
+            mock.push_response("Runtime.evaluate", write.unwrap_or_else(|| json!({
+                "result": { "type": "object", "subtype": "error" },
+                "exceptionDetails": { "text": "Uncaught (in promise) NotAllowedError: Write permission denied." }
+            })));
+            if let Some(copied) = copy {
+                mock.push_response("Runtime.evaluate", evaluate_response(json!(copied)));
+            }
+            mock.push_response("Emulation.setFocusEmulationEnabled", json!({}));
+        }
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.clipboard_write("it's \"quoted\"").await.unwrap();
+        let calls = mock.calls();
+        assert_eq!(calls[2].1.as_ref().unwrap()["permission"], json!({ "name": "clipboard-write" }));
+        assert_eq!(calls[4].1.as_ref().unwrap()["expression"], r#"navigator.clipboard.writeText("it's \"quoted\"")"#);
+        assert_eq!(calls[4].1.as_ref().unwrap()["userGesture"], json!(true));
+        assert_eq!(calls[5].1, Some(json!({ "enabled": false })));
+        assert_eq!(calls.len(), 6);
+
+        // Refused by the Clipboard API, copied through a textarea instead
+        browser.clipboard_write("fallback").await.unwrap();
+        let fallback = mock.calls()[11].1.clone().unwrap();
+        let expression = fallback["expression"].as_str().unwrap();
+        assert!(expression.contains(r#"area.value = "fallback";"#));
+        assert!(expression.contains("document.execCommand('copy')"));
+
+        let error = browser.clipboard_write("nowhere").await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::JavaScriptError(_)));
+        assert!(error.to_string().contains("Write permission denied"));
+        assert_eq!(mock.remaining(), 0);
+
+        // The write error is reported even when ending focus emulation fails too
+        mock.push_response("Runtime.evaluate", evaluate_response(json!({ "clipboard-write": "granted" })));
+        mock.push_response("Emulation.setFocusEmulationEnabled", json!({}));
+        mock.push_response("Runtime.evaluate", json!({
+            "result": { "type": "object", "subtype": "error" },
+            "exceptionDetails": { "text": "Uncaught (in promise) NotAllowedError: Write permission denied." }
+        }));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!(false)));
+        mock.push_error("Emulation.setFocusEmulationEnabled", "Target closed");
+        let error = browser.clipboard_write("unfocused").await.unwrap_err();
+        assert!(error.to_string().contains("Write permission denied"), "{}", error);
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_geolocation_override() {
+        let mock = MockCdpClient::new(vec![
+            ("Browser.setPermission", json!({})),
+            ("Emulation.setGeolocationOverride", json!({})),
+            ("Emulation.clearGeolocationOverride", json!({})),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        for (lat, lon, accuracy) in [(90.5, 0.0, 10.0), (0.0, -180.1, 10.0), (0.0, 0.0, -1.0), (f64::NAN, 0.0, 10.0), (0.0, 0.0, f64::NAN)] {
+            let error = browser.set_geolocation(lat, lon, accuracy).await.unwrap_err();
+            assert!(matches!(error, ChromeMcpError::InvalidOperation(_)), "{} {} {}", lat, lon, accuracy);
+        }
+        assert!(mock.calls().is_empty());
+
+        browser.grant_geolocation_permission().await.unwrap();
+        browser.set_geolocation(52.52, 13.405, 25.0).await.unwrap();
+        browser.clear_geolocation().await.unwrap();
+
+        let calls = mock.calls();
+        assert_eq!(calls[0].1, Some(json!({ "permission": { "name": "geolocation" }, "setting": "granted" })));
+        assert_eq!(calls[1].1, Some(json!({ "latitude": 52.52, "longitude": 13.405, "accuracy": 25.0 })));
+        assert_eq!(calls[2], ("Emulation.clearGeolocationOverride".to_string(), None));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_take_dom_snapshot() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({ "html": "<ul><li>One</li></ul>", "baseUrl": "https://example.com/" }))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let snapshot = browser.take_dom_snapshot(Some("#list")).await.unwrap();
+        assert_eq!(snapshot, DomSnapshot("<ul>\n<li>One</li>\n</ul>".to_string()));
+        let expression = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r##"document.querySelector("#list")"##));
+
+        let error = browser.take_dom_snapshot(Some("#missing")).await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::ElementNotFound(_)));
+        assert!(error.to_string().contains("#missing"));
+
+        let error = browser.take_dom_snapshot(None).await.unwrap_err();
+        assert!(error.to_string().contains("no document element"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_ocr_region() {
+        let mock = MockCdpClient::new(vec![
+            ("Page.captureScreenshot", json!({ "data": "iVBORw0KGgo=" })),
+            ("Runtime.evaluate", evaluate_response(json!(false))),
+            ("Page.addScriptToEvaluateOnNewDocument", json!({ "identifier": "1" })),
+            ("Runtime.evaluate", evaluate_response(json!(true))),
+            ("Runtime.evaluate", evaluate_response(json!("SPRING SALE\n-20%\n"))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        assert_eq!(browser.ocr_region(10.0, 20.0, 300.0, 80.0).await.unwrap(), "SPRING SALE\n-20%");
+        assert_eq!(mock.calls()[0].1.as_ref().unwrap()["clip"], json!({ "x": 10.0, "y": 20.0, "width": 300.0, "height": 80.0, "scale": 1.0 }));
+        let expression = mock.calls()[4].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.starts_with("Tesseract.recognize('data:image/png;base64,iVBORw0KGgo=', 'eng')"));
+
+        let error = browser.ocr_region(0.0, 0.0, 0.0, 80.0).await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::InvalidOperation(_)));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_devtools_targets() {
+        let mock = MockCdpClient::new(vec![]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        let target_id = browser.create_tab(None).await.unwrap();
+
+        mock.push_response("Target.getTargets", json!({ "targetInfos": [
+            { "targetId": "PAGE", "type": "page", "url": "https://example.com/", "title": "Example" },
+            { "targetId": target_id, "type": "other", "url": "chrome-extension://abcdef/panel.html", "title": "My Panel" },
+            { "targetId": "DEVTOOLS", "type": "other", "url": "devtools://devtools/bundled/devtools_app.html", "title": "DevTools" },
+        ]}));
+        mock.push_response("Target.attachToTarget", json!({ "sessionId": "SESSION" }));
+
+        let targets = browser.list_devtools_targets().await.unwrap();
+        assert_eq!(targets, vec![DevToolsTarget {
+            id: target_id.clone(),
+            url: "chrome-extension://abcdef/panel.html".to_string(),
+            title: "My Panel".to_string(),
+            extension_id: "abcdef".to_string(),
+        }]);
+
+        let error = browser.evaluate_in_devtools_target("chrome.runtime.id", None).await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::InvalidOperation(_)));
+
+        // Attaching keeps the current tab; evaluations reuse the session
+        let tab_id = browser.current_tab_id().map(str::to_string);
+        browser.switch_to_devtools_target(&target_id).await.unwrap();
+        assert_eq!(mock.calls()[1].1, Some(json!({ "targetId": target_id, "flatten": true })));
+        assert_eq!(browser.current_tab_id(), tab_id.as_deref());
+        mock.push_response("Runtime.evaluate", evaluate_response(json!("abcdef")));
+        let result = browser.evaluate_in_devtools_target("chrome.runtime.id", None).await.unwrap();
+        assert_eq!(result["value"], "abcdef");
+
+        // A session gone with its connection is attached again
+        mock.push_error("Runtime.evaluate", "CDP error -32001: Session with given id not found.");
+        mock.push_response("Target.attachToTarget", json!({ "sessionId": "SESSION2" }));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!("abcdef")));
+        browser.evaluate_in_devtools_target("chrome.runtime.id", Some(&target_id)).await.unwrap();
+        assert_eq!(browser.devtools_sessions[&target_id], "SESSION2");
+        assert_eq!(browser.current_tab_id(), tab_id.as_deref());
+
+        mock.push_error("Target.attachToTarget", "No target with given id found");
+        let error = browser.switch_to_devtools_target("GONE").await.unwrap_err();
+        assert_eq!(error.trace()[0].context["target_id"], "GONE");
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_switch_to_frame() {
+        let mock = MockCdpClient::new(vec![
+            ("DOM.getDocument", json!({ "root": { "nodeId": 1 } })),
+            ("DOM.querySelectorAll", json!({ "nodeIds": [5] })),
+            ("DOM.describeNode", json!({ "node": { "nodeId": 5, "nodeName": "IFRAME", "frameId": "F2" } })),
+            ("Page.createIsolatedWorld", json!({ "executionContextId": 42 })),
+            ("Runtime.evaluate", evaluate_response(json!(2))),
+            // Element in the frame, then the frame content in the page
+            ("Runtime.evaluate", evaluate_response(json!({ "timestamp_ms": 0, "x": 10.0, "y": 20.0, "width": 40.0, "height": 10.0 }))),
+            ("Runtime.evaluate", evaluate_response(json!({ "x": 100.0, "y": 200.0 }))),
+            ("Input.dispatchMouseEvent", json!({})),
+            ("Input.dispatchMouseEvent", json!({})),
+            ("Runtime.evaluate", evaluate_response(json!("top"))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let frame = browser.switch_to_frame("#checkout").await.unwrap();
+        assert_eq!(frame, FrameContext { selector: "#checkout".to_string(), frame_id: "F2".to_string(), context_id: 42, session_id: None });
+        assert_eq!(browser.current_frame(), Some(&frame));
+
+        browser.evaluate("1 + 1").await.unwrap();
+        browser.click("#pay").await.unwrap();
+        browser.switch_to_main_frame();
+        assert_eq!(browser.evaluate("window.name").await.unwrap()["value"], "top");
+
+        let calls = mock.calls();
+        assert_eq!(calls[3].1, Some(json!({ "frameId": "F2", "worldName": "chrome-mcp", "grantUniveralAccess": true })));
+        let evaluations: Vec<&Value> = calls.iter().filter(|(method, _)| method == "Runtime.evaluate").map(|(_, params)| params.as_ref().unwrap()).collect();
+        assert_eq!(evaluations[0]["contextId"], 42);
+        assert_eq!(evaluations[1]["contextId"], 42);
+        assert!(evaluations[1]["expression"].as_str().unwrap().contains(r##"document.querySelector("#pay")"##));
+        // The frame's position comes from the top-level document
+        assert!(evaluations[2].get("contextId").is_none());
+        assert!(evaluations[2]["expression"].as_str().unwrap().contains(r##"document.querySelector("#checkout")"##));
+        assert!(evaluations[3].get("contextId").is_none());
+        let press = calls.iter().find(|(method, _)| method == "Input.dispatchMouseEvent").unwrap().1.as_ref().unwrap();
+        assert_eq!((press["x"].as_f64(), press["y"].as_f64()), (Some(130.0), Some(225.0)));
+        assert_eq!(mock.remaining(), 0);
+
+        mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+        mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [6] }));
+        mock.push_response("DOM.describeNode", json!({ "node": { "nodeId": 6, "nodeName": "DIV" } }));
+        let error = browser.switch_to_frame("#sidebar").await.unwrap_err();
+        assert!(matches!(error.root_cause(), ChromeMcpError::InvalidOperation(_)));
+        assert_eq!(browser.current_frame(), None);
+
+        // A cross-origin iframe is entered over a session attached to its own target
+        let select_frame = |mock: &MockCdpClient| {
+            mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+            mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [8] }));
+            mock.push_response("DOM.describeNode", json!({ "node": { "nodeId": 8, "nodeName": "IFRAME", "frameId": "OOPIF" } }));
+            mock.push_error("Page.createIsolatedWorld", "No frame for given id found");
+        };
+        select_frame(&mock);
+        mock.push_response("Target.attachToTarget", json!({ "sessionId": "S1" }));
+        mock.push_response("Page.createIsolatedWorld", json!({ "executionContextId": 7 }));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!("Pay now")));
+        let frame = browser.switch_to_frame("#payment").await.unwrap();
+        assert_eq!(frame.session_id.as_deref(), Some("S1"));
+        assert_eq!(browser.get_element_text("button", false).await.unwrap(), "Pay now");
+        let calls = mock.calls();
+        let attach = calls.iter().rev().find(|(method, _)| method == "Target.attachToTarget").unwrap();
+        assert_eq!(attach.1, Some(json!({ "targetId": "OOPIF", "flatten": true })));
+        assert_eq!(calls.last().unwrap().1.as_ref().unwrap()["contextId"], 7);
+
+        select_frame(&mock);
+        mock.push_error("Target.attachToTarget", "No target with given id found");
+        let error = browser.switch_to_frame("#ad").await.unwrap_err();
+        assert!(matches!(error.root_cause(), ChromeMcpError::InvalidOperation(message) if message.contains("cross-origin")));
+        assert_eq!(mock.remaining(), 0);
+
+        // Reloading destroys the frame's isolated world, so scripts go back to the page
+        mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+        mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [5] }));
+        mock.push_response("DOM.describeNode", json!({ "node": { "nodeId": 5, "nodeName": "IFRAME", "frameId": "F2" } }));
+        mock.push_response("Page.createIsolatedWorld", json!({ "executionContextId": 43 }));
+        mock.push_response("Page.reload", json!({}));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!("complete")));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!("top")));
+        browser.switch_to_frame("#checkout").await.unwrap();
+        browser.reload(false).await.unwrap();
+        assert_eq!(browser.current_frame(), None);
+        assert_eq!(browser.evaluate("window.name").await.unwrap()["value"], "top");
+        assert!(mock.calls().last().unwrap().1.as_ref().unwrap().get("contextId").is_none());
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_upload_files() {
+        let file_input = |attributes: Value| json!({ "node": { "nodeId": 7, "nodeName": "INPUT", "attributes": attributes } });
+        let mock = MockCdpClient::new(vec![]);
+        for node in [
+            file_input(json!(["type", "file", "name", "avatar"])),
+            file_input(json!(["type", "FILE", "multiple", ""])),
+            file_input(json!(["type", "file"])),
+            file_input(json!(["type", "text"])),
+            json!({ "node": { "nodeId": 7, "nodeName": "DIV", "attributes": ["type", "file"] } }),
+        ] {
+            mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+            mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [7, 8] }));
+            mock.push_response("DOM.describeNode", node);
+        }
+        mock.push_response("DOM.setFileInputFiles", json!({}));
+        mock.push_response("DOM.setFileInputFiles", json!({}));
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        let set_files = |mock: &MockCdpClient| mock.calls().into_iter().filter(|(method, _)| method == "DOM.setFileInputFiles").map(|(_, params)| params.unwrap()).collect::<Vec<Value>>();
+
+        browser.upload_file("#avatar", "/tmp/avatar.png").await.unwrap();
+        assert_eq!(mock.calls()[2].1, Some(json!({ "nodeId": 7 })));
+        assert_eq!(set_files(&mock), vec![json!({ "files": ["/tmp/avatar.png"], "nodeId": 7 })]);
+
+        let files = vec!["/tmp/a.pdf".to_string(), "/tmp/b.pdf".to_string()];
+        browser.upload_files("#attachments", &files).await.unwrap();
+        assert_eq!(set_files(&mock)[1], json!({ "files": ["/tmp/a.pdf", "/tmp/b.pdf"], "nodeId": 7 }));
+
+        // A single file input, then elements that are no file inputs
+        for selector in ["#avatar", "#name", "#dropzone"] {
+            let error = browser.upload_files(selector, &files[..if selector == "#avatar" { 2 } else { 1 }]).await.unwrap_err();
+            assert!(matches!(error.root_cause(), ChromeMcpError::InvalidOperation(_)), "{}: {:?}", selector, error);
+            assert_eq!(error.trace()[0].context["selector"], selector);
+        }
+        assert_eq!(set_files(&mock).len(), 2);
+
+        let error = browser.upload_file("#avatar", "avatar.png").await.unwrap_err();
+        assert!(error.to_string().contains("must be absolute"));
+        assert!(browser.upload_files("#avatar", &[]).await.is_err());
+
+        mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+        mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [] }));
+        let error = browser.upload_file("#missing", "/tmp/avatar.png").await.unwrap_err();
+        assert!(matches!(error.root_cause(), ChromeMcpError::ElementNotFound(_)));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[test]
+    fn test_node_attributes() {
+        let attributes = node_attributes(&json!({ "attributes": ["TYPE", "file", "multiple", "", "dangling"] }));
+        assert_eq!(attributes.get("type").map(String::as_str), Some("file"));
+        assert_eq!(attributes.get("multiple").map(String::as_str), Some(""));
+        assert_eq!(attributes.len(), 2);
+        assert!(node_attributes(&json!({ "nodeName": "#text" })).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drag_and_drop() {
+        let mock = MockCdpClient::new(vec![]);
+        for (node_id, x) in [(3, 10.0), (4, 210.0)] {
+            mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+            mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [node_id] }));
+            mock.push_response("Runtime.evaluate", evaluate_response(json!({ "timestamp_ms": 0, "x": x, "y": 100.0, "width": 40.0, "height": 20.0 })));
+        }
+        for _ in 0..2 * (DRAG_STEPS + 2) {
+            mock.push_response("Input.dispatchMouseEvent", json!({}));
+        }
+        mock.push_response("Runtime.evaluate", evaluate_response(json!(true)));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!(false)));
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        let mouse_events = |calls: &[(String, Option<Value>)]| -> Vec<(String, f64, f64)> {
+            calls
+                .iter()
+                .filter(|(method, _)| method == "Input.dispatchMouseEvent")
+                .map(|(_, params)| {
+                    let params = params.as_ref().unwrap();
+                    (params["type"].as_str().unwrap().to_string(), params["x"].as_f64().unwrap(), params["y"].as_f64().unwrap())
+                })
+                .collect()
+        };
+
+        // Element centers
+        let source = DragEndpoint::Element("#card".to_string());
+        let target = DragEndpoint::Element("#done-column".to_string());
+        browser.drag_and_drop(&source, &target, 0).await.unwrap();
+        let calls = mock.calls();
+        let events = mouse_events(&calls);
+        assert_eq!(events.len(), DRAG_STEPS as usize + 2);
+        assert_eq!(events[0], ("mousePressed".to_string(), 30.0, 110.0));
+        assert_eq!(events[1], ("mouseMoved".to_string(), 50.0, 110.0));
+        assert_eq!(events[DRAG_STEPS as usize], ("mouseMoved".to_string(), 230.0, 110.0));
+        assert_eq!(events[DRAG_STEPS as usize + 1], ("mouseReleased".to_string(), 230.0, 110.0));
+        let expression = calls.last().unwrap().1.as_ref().unwrap()["expression"].as_str().unwrap();
+        assert!(expression.contains(r#"const from = {"x":30.0,"y":110.0};"#));
+        assert!(expression.contains(r#"const to = {"x":230.0,"y":110.0};"#));
+
+        // Points
+        browser.drag_and_drop(&DragEndpoint::Point(0.0, 0.0), &DragEndpoint::Point(100.0, 50.0), 0).await.unwrap();
+        let calls = mock.calls();
+        let events = mouse_events(&calls[calls.len() - DRAG_STEPS as usize - 3..]);
+        let moves: Vec<(f64, f64)> = events.iter().filter(|e| e.0 == "mouseMoved").map(|e| (e.1, e.2)).collect();
+        assert_eq!(moves.first(), Some(&(10.0, 5.0)));
+        assert_eq!(moves.get(4), Some(&(50.0, 25.0)));
+        assert_eq!(moves.last(), Some(&(100.0, 50.0)));
+        assert_eq!(events.last().unwrap().0, "mouseReleased");
+        assert!(calls.iter().filter(|(method, _)| method == "DOM.querySelectorAll").count() == 2);
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_click_compensating_for_fixed_headers() {
+        let mock = MockCdpClient::new(vec![]);
+        // Partly covered by a 70px header, then fully covered by it
+        for (y, height) in [(50.0, 40.0), (20.0, 30.0)] {
+            mock.push_response("Runtime.evaluate", evaluate_response(json!({ "timestamp_ms": 0, "x": 10.0, "y": y, "width": 100.0, "height": height })));
+            mock.push_response("Runtime.evaluate", evaluate_response(json!(70.0)));
+        }
+        mock.push_response("Runtime.evaluate", evaluate_response(json!(-20.0)));
+        for _ in 0..4 {
+            mock.push_response("Input.dispatchMouseEvent", json!({}));
+        }
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.click_compensating_for_fixed_headers("#save").await.unwrap();
+        let calls = mock.calls();
+        let expression = calls[1].1.as_ref().unwrap()["expression"].as_str().unwrap();
+        assert!(expression.contains(r##"const selector = "#save";"##));
+        assert_eq!((calls[2].1.as_ref().unwrap()["x"].as_f64(), calls[2].1.as_ref().unwrap()["y"].as_f64()), (Some(60.0), Some(80.0)));
+
+        browser.click_compensating_for_fixed_headers("#save").await.unwrap();
+        let calls = mock.calls();
+        let expression = calls[6].1.as_ref().unwrap()["expression"].as_str().unwrap();
+        assert!(expression.contains("window.scrollBy(0, -58)"));
+        // Only 20px of the 58 were left to scroll
+        assert_eq!(calls[7].1.as_ref().unwrap()["y"].as_f64(), Some(55.0));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_channel() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.addBinding", json!({})),
+            ("Runtime.evaluate", evaluate_response(json!([{ "type": "logout" }, "ping"]))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.post_broadcast_message("auth", json!({ "type": "logout" })).await.unwrap();
+        let messages = browser.create_broadcast_listener("auth", 2, 1000).await.unwrap();
+        assert_eq!(messages, vec![json!({ "type": "logout" }), json!("ping")]);
+
+        let calls = mock.calls();
+        assert_eq!(
+            calls[0].1.as_ref().unwrap()["expression"],
+            r#"(() => { const channel = new BroadcastChannel("auth"); channel.postMessage({"type":"logout"}); channel.close(); })()"#
+        );
+        assert_eq!(calls[1].1, Some(json!({ "name": BROADCAST_BINDING })));
+        let expression = calls[2].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"new BroadcastChannel("auth")"#));
+        assert!(expression.contains("messages.length >= 2"));
+        assert!(expression.contains("setTimeout(finish, 1000)"));
+    }
+
+    #[tokio::test]
+    async fn test_console_marks_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.evaluate", evaluate_response(json!([
+                { "name": "step 'login'", "start_time": 1234.5 }
+            ]))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.console_mark("step 'login'").await.unwrap();
+        let expression = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"performance.mark("step 'login'")"#));
+        assert!(expression.contains(r#"console.log('[chrome-mcp]', "step 'login'", performance.now())"#));
+
+        let marks = browser.get_time_marks().await.unwrap();
+        assert_eq!(marks, vec![TimeMark { name: "step 'login'".to_string(), start_time: 1234.5 }]);
+    }
+
+    #[tokio::test]
+    async fn test_reload_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Page.reload", json!({})),
+            ("Runtime.evaluate", evaluate_response(json!("complete"))),
+            ("Page.reload", json!({})),
+            ("Runtime.evaluate", evaluate_response(json!("complete"))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.reload(false).await.unwrap();
+        browser.reload_and_inject(true, "window.injected = true").await.unwrap();
+
+        let calls = mock.calls();
+        assert_eq!(calls[0].1, Some(json!({ "ignoreCache": false })));
+        assert_eq!(calls[2].1, Some(json!({ "ignoreCache": true, "scriptToEvaluateOnLoad": "window.injected = true" })));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_history_navigation() {
+        let history = |current: u64| json!({
+            "currentIndex": current,
+            "entries": [{ "id": 11, "url": "https://example.com/0" }, { "id": 12, "url": "https://example.com/1" }, { "id": 13, "url": "https://example.com/2" }]
+        });
+        let mock = MockCdpClient::new(vec![
+            ("Page.getNavigationHistory", history(1)),
+            ("Page.navigateToHistoryEntry", json!({})),
+            ("Runtime.evaluate", evaluate_response(json!("complete"))),
+            ("Page.getNavigationHistory", history(0)),
+            ("Page.navigateToHistoryEntry", json!({})),
+            ("Runtime.evaluate", evaluate_response(json!("complete"))),
+            ("Runtime.evaluate", evaluate_response(json!({ "length": 3, "state": { "page": 2 }, "url": "https://example.com/2" }))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let page = mock.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            // A subframe navigating doesn't end the wait
+            page.emit_event("Page.frameNavigated", json!({ "frame": { "id": "F2", "parentId": "F1" } }));
+            page.emit_event("Page.frameNavigated", json!({ "frame": { "id": "F1" } }));
+        });
+        browser.go_back().await.unwrap();
+
+        let page = mock.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            page.emit_event("Page.navigatedWithinDocument", json!({ "frameId": "F1", "url": "https://example.com/2" }));
+        });
+        browser.navigate_to_index(2).await.unwrap();
+
+        let state = browser.get_history_state().await.unwrap();
+        assert_eq!(state, json!({ "length": 3, "state": { "page": 2 }, "url": "https://example.com/2" }));
+
+        let entries: Vec<Value> = mock.calls().into_iter().filter(|(method, _)| method == "Page.navigateToHistoryEntry").map(|(_, params)| params.unwrap()).collect();
+        assert_eq!(entries, vec![json!({ "entryId": 11 }), json!({ "entryId": 13 })]);
+        assert_eq!(mock.remaining(), 0);
+
+        // On the last entry there is nothing to go to
+        mock.push_response("Page.getNavigationHistory", history(2));
+        let error = browser.go_forward().await.unwrap_err();
+        assert!(matches!(error.root_cause(), ChromeMcpError::InvalidOperation(_)));
+        assert_eq!(error.trace()[0].context["delta"], 1);
+        assert_eq!(mock.calls().last().unwrap().0, "Page.getNavigationHistory");
+        assert_eq!(mock.remaining(), 0);
+    }
 
-        // Try different strategies to find and click the element
-        
-        // Strategy 1: Try as CSS selector
-        if let Ok(element_ref) = self.find_element_by_selector(selector_or_text).await {
-            return self.click_element_ref(&element_ref).await;
-        }
+    #[test]
+    fn test_fuzz_inputs() {
+        let sql = fuzz_inputs(&FuzzStrategy::Sql, 3, None, 1);
+        assert_eq!(sql, SQL_FUZZ_PAYLOADS[..3].to_vec());
 
-        // Strategy 2: Try as accessibility text
-        if let Ok(element_ref) = self.find_element_by_text(selector_or_text).await {
-            return self.click_element_ref(&element_ref).await;
-        }
+        let custom = fuzz_inputs(&FuzzStrategy::Custom(vec!["a".to_string(), "b".to_string()]), 5, None, 1);
+        assert_eq!(custom, vec!["a", "b", "a", "b", "a"]);
+        assert!(fuzz_inputs(&FuzzStrategy::Custom(Vec::new()), 5, None, 1).is_empty());
 
-        // Strategy 3: Try as accessibility role
-        if let Ok(element_ref) = self.find_element_by_role(selector_or_text).await {
-            return self.click_element_ref(&element_ref).await;
-        }
+        let boundary = fuzz_inputs(&FuzzStrategy::Boundary, 4, Some(8), 1);
+        assert_eq!(boundary[0], "");
+        assert_eq!(boundary[2].len(), 8);
+        assert_eq!(boundary[3].len(), 9);
 
-        Err(ChromeMcpError::element_not_found(format!(
-            "Could not find element to click: {}", selector_or_text
-        )))
+        let random = fuzz_inputs(&FuzzStrategy::Random, 20, None, 42);
+        assert_eq!(random.len(), 20);
+        assert!(random.iter().all(|input| (1..=64).contains(&input.chars().count())));
+        assert_eq!(random, fuzz_inputs(&FuzzStrategy::Random, 20, None, 42));
     }
 
-    /// Click at specific coordinates using native input
-    pub async fn native_click(&self, x: f64, y: f64) -> Result<()> {
-        info!("Native click at ({}, {})", x, y);
-        self.native_input.click_at(x, y)
+    #[tokio::test]
+    async fn test_fuzz_input_with_mock_cdp() {
+        let start_url = "https://example.com/search";
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", json!({ "result": { "value": start_url } })),
+            ("Runtime.evaluate", evaluate_response(json!(-1))),
+            ("Page.addScriptToEvaluateOnNewDocument", json!({ "identifier": "1" })),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            // First input: nothing happens
+            ("Runtime.evaluate", evaluate_response(json!(true))),
+            ("Runtime.evaluate", json!({ "result": { "value": start_url } })),
+            ("Runtime.evaluate", evaluate_response(json!([]))),
+            // Second input: the page throws and navigates, and is navigated back
+            ("Runtime.evaluate", evaluate_response(json!(true))),
+            ("Runtime.evaluate", json!({ "result": { "value": "https://example.com/error" } })),
+            ("Runtime.evaluate", evaluate_response(json!(["TypeError: x is undefined"]))),
+            ("Page.navigate", json!({ "frameId": "F1" })),
+            ("Runtime.evaluate", json!({ "result": { "value": "complete" } })),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let strategy = FuzzStrategy::Custom(vec!["hello".to_string(), "<b>".to_string()]);
+        let results = browser.fuzz_input("#q", strategy, 2).await.unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].status, FuzzStatus::Passed);
+        assert!(!results[0].url_changed);
+        assert_eq!(results[1].input, "<b>");
+        assert_eq!(results[1].status, FuzzStatus::JsErrors);
+        assert!(results[1].url_changed);
+        assert_eq!(results[1].js_errors, vec!["TypeError: x is undefined"]);
+        assert_eq!(mock.remaining(), 0);
+
+        let calls = mock.calls();
+        let submit_script = calls[4].1.as_ref().unwrap()["expression"].as_str().unwrap();
+        assert!(submit_script.contains(r##"document.querySelector("#q")"##));
+        assert!(submit_script.contains(r#"descriptor.set.call(el, "hello")"#));
+        assert_eq!(calls[10].1, Some(json!({ "url": start_url })));
     }
 
-    /// Type text into an element or the focused element
-    pub async fn type_text(&mut self, text: &str, selector: Option<&str>) -> Result<()> {
-        info!("Typing text: {}", text);
+    #[tokio::test]
+    async fn test_render_blocking_resources_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!([
+                { "url": "https://example.com/app.js", "type": "script", "load_time_ms": 120.5, "critical_path": true },
+                { "url": "https://example.com/site.css", "type": "stylesheet", "load_time_ms": null, "critical_path": false }
+            ]))),
+            ("Runtime.evaluate", evaluate_response(json!(1))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        if let Some(sel) = selector {
-            // Click on the element first to focus it
-            self.click(sel).await?;
-            sleep(Duration::from_millis(100)).await;
-        }
+        let resources = browser.get_render_blocking_resources().await.unwrap();
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[0].type_, RenderBlockingType::Script);
+        assert_eq!(resources[0].load_time_ms, Some(120.5));
+        assert!(resources[0].critical_path);
+        assert!(resources[0].recommendation.contains("defer"));
+        assert_eq!(resources[1].type_, RenderBlockingType::Stylesheet);
+        assert!(resources[1].recommendation.contains("critical CSS"));
 
-        // Type the text using CDP
-        self.cdp.type_text(text).await?;
-        
-        Ok(())
+        let expression = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"link[rel=\"stylesheet\"]:not([media])"#));
+
+        assert_eq!(browser.defer_render_blocking_scripts().await.unwrap(), 1);
     }
 
-    /// Type text using native input
-    pub async fn native_type(&self, text: &str) -> Result<()> {
-        info!("Native typing: {}", text);
-        self.native_input.type_text(text)
+    /// Base64 PNG of a solid image with the given dimensions
+    fn test_png(width: u32, height: u32) -> String {
+        let image = image::RgbaImage::from_pixel(width, height, image::Rgba([255, 0, 0, 255]));
+        let mut encoded = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut encoded, image::ImageFormat::Png).unwrap();
+        BASE64.encode(encoded.into_inner())
     }
 
-    /// Take a screenshot
-    pub async fn screenshot(&mut self, format: Option<&str>, quality: Option<u32>) -> Result<String> {
-        let format = format.unwrap_or("png");
-        self.screenshot.capture_with_options(format, quality, false).await
+    fn png_dimensions(png_base64: &str) -> (u32, u32) {
+        let image = image::load_from_memory(&BASE64.decode(png_base64).unwrap()).unwrap();
+        (image.width(), image.height())
     }
 
-    /// Take a full-page screenshot
-    pub async fn screenshot_full_page(&mut self, format: Option<&str>, quality: Option<u32>) -> Result<String> {
-        let format = format.unwrap_or("png");
-        self.screenshot.capture_with_options(format, quality, true).await
+    #[test]
+    fn test_png_thumbnail() {
+        let thumbnail = png_thumbnail(&test_png(200, 100), 50, 50).unwrap();
+        assert_eq!(png_dimensions(&thumbnail), (50, 25));
+
+        // Small images are not scaled up
+        let small = test_png(20, 10);
+        assert_eq!(png_thumbnail(&small, 50, 50).unwrap(), small);
+
+        assert!(png_thumbnail(&small, 0, 50).is_err());
+        assert!(png_thumbnail("not base64!", 50, 50).is_err());
     }
 
-    /// Screenshot a specific element
-    pub async fn screenshot_element(&mut self, selector: &str) -> Result<String> {
-        self.screenshot.capture_element(selector).await
+    #[tokio::test]
+    async fn test_tab_thumbnails_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Page.captureScreenshot", json!({ "data": test_png(160, 90) })),
+            ("Page.captureScreenshot", json!({ "data": test_png(90, 160) })),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        browser.connect(None).await.unwrap();
+        let second_tab = browser.create_tab(Some("https://example.com")).await.unwrap();
+
+        let thumbnails = browser.capture_all_tab_thumbnails(32, 32).await.unwrap();
+        assert_eq!(thumbnails.len(), 2);
+        assert_eq!(thumbnails[0].0.id, MOCK_TAB_ID);
+        assert_eq!(png_dimensions(&thumbnails[0].1), (32, 18));
+        assert_eq!(thumbnails[1].0.id, second_tab);
+        assert_eq!(png_dimensions(&thumbnails[1].1), (18, 32));
+
+        // Back on the tab it started on
+        assert_eq!(browser.current_tab_id(), Some(MOCK_TAB_ID));
     }
 
-    /// Evaluate JavaScript
-    pub async fn evaluate(&mut self, javascript: &str) -> Result<Value> {
-        debug!("Evaluating JavaScript: {}", javascript);
-        self.cdp.evaluate_js(javascript).await
+    #[tokio::test]
+    async fn test_scroll_restoration_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!([0, 1480]))),
+            ("Page.navigate", json!({ "frameId": "F1" })),
+            ("Runtime.evaluate", json!({ "result": { "value": "complete" } })),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let position = browser.remember_scroll().await.unwrap();
+        assert_eq!(position, (0, 1480));
+        browser.navigate_and_scroll("https://example.com/feed", position.0, position.1).await.unwrap();
+
+        let calls = mock.calls();
+        assert_eq!(calls[1].1, Some(json!({ "url": "https://example.com/feed" })));
+        assert_eq!(calls[3].1.as_ref().unwrap()["expression"], "window.scrollTo(0, 1480)");
+        assert_eq!(mock.remaining(), 0);
     }
 
-    /// Scroll the page
-    pub async fn scroll(&mut self, x: i32, y: i32) -> Result<()> {
-        debug!("Scrolling by ({}, {})", x, y);
-        self.cdp.send_command("Runtime.evaluate", Some(json!({
-            "expression": format!("window.scrollBy({}, {})", x, y)
-        }))).await?;
-        Ok(())
+    #[tokio::test]
+    async fn test_find_elements_ranked_by_relevance() {
+        let mock = MockCdpClient::new(vec![]);
+        mock.push_error("DOM.*", "DOM agent is not enabled");
+        mock.push_response("Accessibility.getFullAXTree", json!({
+            "nodes": [
+                {
+                    "nodeId": "1",
+                    "role": { "type": "role", "value": "RootWebArea" },
+                    "childIds": ["2", "3"]
+                },
+                {
+                    "nodeId": "2",
+                    "role": { "type": "role", "value": "link" },
+                    "name": { "type": "computedString", "value": "Continue reading" },
+                    "boundingRect": { "x": 10, "y": 2000, "width": 80, "height": 16 },
+                    "childIds": []
+                },
+                {
+                    "nodeId": "3",
+                    "role": { "type": "role", "value": "button" },
+                    "name": { "type": "computedString", "value": "Continue" },
+                    "boundingRect": { "x": 600, "y": 400, "width": 100, "height": 40 },
+                    "childIds": []
+                }
+            ]
+        }));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!([1280, 720])));
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let elements = browser.find_elements("Continue").await.unwrap();
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].id, "ax-3");
+        assert!((elements[0].score - 13.4).abs() < 1e-9);
+        assert_eq!(elements[1].id, "ax-2");
+        assert!((elements[1].score - 7.128).abs() < 1e-9);
+
+        let serialized = serde_json::to_value(&elements[0]).unwrap();
+        assert_eq!(serialized["clickable"], json!(true));
+        assert!(serialized["score"].is_f64());
     }
 
-    /// Scroll to element
-    pub async fn scroll_to_element(&mut self, selector: &str) -> Result<()> {
-        debug!("Scrolling to element: {}", selector);
-        self.cdp.send_command("Runtime.evaluate", Some(json!({
-            "expression": format!(
-                "document.querySelector('{}').scrollIntoView({{ behavior: 'smooth', block: 'center' }})", 
-                selector.replace("'", "\\'")
-            )
-        }))).await?;
-        Ok(())
+    #[test]
+    fn test_relevance_score() {
+        let element = ElementRef {
+            id: "ax-1".to_string(),
+            selector: None,
+            accessibility_id: Some("1".to_string()),
+            bounds: Some((0.0, 0.0, 200.0, 100.0)),
+            text: Some("Search".to_string()),
+            role: Some("button".to_string()),
+            clickable: true,
+            score: 0.0,
+            backend_node_id: None,
+            stable_selector: None,
+        };
+        // Exact name, clickable, in viewport, full size bonus
+        assert_eq!(relevance_score("search", &element, Some((800.0, 600.0))), 14.0);
+        // Outside the viewport
+        assert_eq!(relevance_score("search", &element, Some((-1.0, -1.0))), 13.0);
+        // Role match, name does not contain the query
+        assert_eq!(relevance_score("Button", &element, None), 6.0);
+
+        let selector_match = ElementRef { bounds: None, text: None, role: None, clickable: false, ..element };
+        assert_eq!(relevance_score("#search", &selector_match, None), 0.0);
     }
 
-    /// Hover over an element
-    pub async fn hover(&mut self, selector_or_text: &str) -> Result<()> {
-        debug!("Hovering over: {}", selector_or_text);
+    #[tokio::test]
+    async fn test_verify_form_labels() {
+        let unlabeled = |selector: &str, facts: Value| {
+            let mut input = json!({
+                "input_selector": selector,
+                "id": null,
+                "aria_label": null,
+                "aria_labelledby": null,
+                "missing_labelledby_ids": [],
+                "stray_label_for": null
+            });
+            input.as_object_mut().unwrap().extend(facts.as_object().unwrap().clone());
+            input
+        };
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!([
+                unlabeled("#signup > input:nth-of-type(2)", json!({ "stray_label_for": "email" })),
+                unlabeled("#phone", json!({ "id": "phone", "aria_labelledby": "phone-label", "missing_labelledby_ids": ["phone-label"] })),
+                unlabeled("#zip", json!({ "id": "zip", "aria_label": "", "stray_label_for": "zip" })),
+                unlabeled("#city", json!({ "id": "city", "stray_label_for": "city" })),
+                unlabeled("#signup > input:nth-of-type(5)", json!({})),
+            ]))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        let element_ref = self.find_element_any_strategy(selector_or_text).await?;
-        
-        if let Some((x, y, _, _)) = element_ref.bounds {
-            let center_x = x + element_ref.bounds.unwrap().2 / 2.0;
-            let center_y = y + element_ref.bounds.unwrap().3 / 2.0;
-            
-            self.cdp.send_command("Input.dispatchMouseEvent", Some(json!({
-                "type": "mouseMoved",
-                "x": center_x,
-                "y": center_y
-            }))).await?;
-        }
+        let issues = browser.verify_form_labels("#signup").await.unwrap();
+        let kinds: Vec<LabelIssueType> = issues.iter().map(|issue| issue.issue).collect();
+        assert_eq!(kinds, vec![
+            LabelIssueType::InvalidForAttribute,
+            LabelIssueType::MissingAriaLabel,
+            // An empty aria-label is reported before the label that misses the input
+            LabelIssueType::MissingAriaLabel,
+            LabelIssueType::InvalidForAttribute,
+            LabelIssueType::NoLabel,
+        ]);
+        assert_eq!(issues[0].recommendation, r#"label for="email" does not match any id; give the input id="email""#);
+        assert_eq!(issues[1].recommendation, "aria-labelledby references missing element(s) #phone-label; point it at the id of the visible label");
+        assert!(issues[2].recommendation.starts_with("The ARIA label is empty"));
+        assert!(issues[3].recommendation.contains(r#"make its id "city" unique"#));
+        assert_eq!(issues[4].input_selector, "#signup > input:nth-of-type(5)");
 
-        Ok(())
+        let expression = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r##"document.querySelector("#signup")"##));
+        assert!(expression.contains("const cssPath = (el) =>"));
+
+        let error = browser.verify_form_labels("#missing").await.unwrap_err();
+        assert!(error.to_string().contains("No form found for selector: #missing"));
     }
 
-    /// Select option from dropdown
-    pub async fn select_option(&mut self, selector: &str, option_value: &str) -> Result<()> {
-        debug!("Selecting option '{}' in element: {}", option_value, selector);
-        
-        self.cdp.send_command("Runtime.evaluate", Some(json!({
-            "expression": format!(
-                r#"
-                const select = document.querySelector('{}');
-                if (select) {{
-                    select.value = '{}';
-                    select.dispatchEvent(new Event('change', {{ bubbles: true }}));
-                }} else {{
-                    throw new Error('Select element not found');
-                }}
-                "#,
-                selector.replace("'", "\\'"),
-                option_value.replace("'", "\\'")
-            )
-        }))).await?;
-        
-        Ok(())
+    #[tokio::test]
+    async fn test_get_element_as_markdown_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({
+                "html": "<article><h2>Title</h2><p>See <a href=\"more\">more</a> <img src=\"a.png\" alt=\"A\"></p></article>",
+                "baseUrl": "https://example.com/news/"
+            }))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let markdown = browser.get_element_as_markdown("#story", false).await.unwrap();
+        assert_eq!(markdown, "## Title\n\nSee [more](https://example.com/news/more)");
+        let script = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(script.contains("document.querySelector(\"#story\")"));
+
+        let error = browser.get_element_as_markdown("#missing", false).await.unwrap_err();
+        assert!(error.to_string().contains("No element found for selector: #missing"));
     }
 
-    /// Wait for a condition to be met
-    pub async fn wait_for_condition(&mut self, condition: WaitCondition, timeout_ms: u64) -> Result<()> {
-        debug!("Waiting for condition: {:?} (timeout: {}ms)", condition, timeout_ms);
+    #[test]
+    fn test_element_expression_escaping() {
+        let script = element_expression(r#"a[title="it's \"quoted\""]"#, false, "el.textContent").unwrap();
+        assert_eq!(
+            script,
+            r#"(() => { const el = document.querySelector("a[title=\"it's \\\"quoted\\\"\"]"); return el ? el.textContent : null; })()"#
+        );
 
-        let result = timeout(Duration::from_millis(timeout_ms), async {
-            loop {
-                match &condition {
-                    WaitCondition::ElementPresent(selector) => {
-                        if self.find_element_by_selector(selector).await.is_ok() {
-                            break;
-                        }
-                    }
-                    WaitCondition::ElementVisible(selector) => {
-                        if self.is_element_visible(selector).await? {
-                            break;
-                        }
-                    }
-                    WaitCondition::ElementClickable(selector) => {
-                        if self.is_element_clickable(selector).await? {
-                            break;
-                        }
-                    }
-                    WaitCondition::TextPresent(text) => {
-                        if self.is_text_present(text).await? {
-                            break;
-                        }
-                    }
-                    WaitCondition::UrlMatches(pattern) => {
-                        if self.current_url().await?.contains(pattern) {
-                            break;
-                        }
-                    }
-                    WaitCondition::UrlContains(text) => {
-                        if self.current_url().await?.contains(text) {
-                            break;
-                        }
-                    }
-                    WaitCondition::PageLoad => {
-                        let ready_state = self.cdp.send_command("Runtime.evaluate", Some(json!({
-                            "expression": "document.readyState",
-                            "returnByValue": true
-                        }))).await?;
-                        
-                        if let Some(state) = ready_state.get("result").and_then(|r| r.get("value")).and_then(|v| v.as_str()) {
-                            if state == "complete" {
-                                break;
-                            }
-                        }
-                    }
-                    WaitCondition::NetworkIdle(idle_time) => {
-                        // Simplified network idle detection
-                        sleep(Duration::from_millis(*idle_time)).await;
-                        break;
-                    }
-                }
+        let script = element_expression("li\n</script>", true, text_property(false)).unwrap();
+        assert!(script.contains(r#"document.querySelectorAll("li\n</script>")"#));
+        assert!(script.contains("Array.from(els, el => (el.innerText ?? el.textContent))"));
+    }
 
-                sleep(Duration::from_millis(100)).await;
-            }
-            Ok::<(), ChromeMcpError>(())
-        }).await;
+    #[tokio::test]
+    async fn test_get_element_content_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!("Total: $5"))),
+            ("Runtime.evaluate", evaluate_response(json!(["One", "Two"]))),
+            ("Runtime.evaluate", evaluate_response(json!("<b>Total</b>"))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.evaluate", evaluate_response(json!({ "value": "/docs?id=1" }))),
+            ("Runtime.evaluate", evaluate_response(json!({ "value": null }))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        match result {
-            Ok(_) => {
-                debug!("Wait condition satisfied");
-                Ok(())
-            }
-            Err(_) => Err(ChromeMcpError::Timeout { timeout: timeout_ms }),
-        }
+        assert_eq!(browser.get_element_text("#total", true).await.unwrap(), "Total: $5");
+        assert_eq!(browser.get_elements_text("li", false).await.unwrap(), vec!["One", "Two"]);
+        assert_eq!(browser.get_element_html(".price", false).await.unwrap(), "<b>Total</b>");
+        let error = browser.get_element_html("#missing", true).await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::ElementNotFound(_)), "{}", error);
+        assert_eq!(browser.get_element_attribute("a", "data-id").await.unwrap().as_deref(), Some("/docs?id=1"));
+        assert_eq!(browser.get_element_attribute("a", "download").await.unwrap(), None);
+
+        let expressions: Vec<String> = mock
+            .calls()
+            .into_iter()
+            .map(|(_, params)| params.unwrap()["expression"].as_str().unwrap().to_string())
+            .collect();
+        assert!(expressions[0].contains("el.textContent"));
+        assert!(expressions[1].contains("document.querySelectorAll(\"li\")"));
+        assert!(expressions[2].contains("el.innerHTML"));
+        assert!(expressions[3].contains("el.outerHTML"));
+        assert!(expressions[4].contains("el ? ({ value: el.getAttribute(\"data-id\") }) : null"));
     }
 
-    /// Get current URL
-    pub async fn current_url(&mut self) -> Result<String> {
-        let result = self.cdp.send_command("Runtime.evaluate", Some(json!({
-            "expression": "window.location.href",
-            "returnByValue": true
-        }))).await?;
+    #[tokio::test]
+    async fn test_page_source_dom_and_network() {
+        let mock = MockCdpClient::new(vec![
+            ("DOM.getDocument", json!({ "root": { "nodeId": 1 } })),
+            ("DOM.getOuterHTML", json!({ "outerHTML": "<!DOCTYPE html><html><body><ul><li>Added by script</li></ul></body></html>" })),
+            ("Page.getFrameTree", json!({ "frameTree": { "frame": { "id": "main", "loaderId": "LOADER1", "url": "https://example.com/" } } })),
+            ("Network.getResponseBody", json!({ "body": "<!DOCTYPE html><html><body><ul></ul></body></html>", "base64Encoded": false })),
+            ("Page.getFrameTree", json!({ "frameTree": { "frame": { "id": "main", "loaderId": "LOADER2" } } })),
+            ("Network.getResponseBody", json!({ "body": BASE64.encode(b"<p>caf\xe9</p>"), "base64Encoded": true })),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        result
-            .get("result")
-            .and_then(|r| r.get("value"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .ok_or_else(|| ChromeMcpError::cdp_protocol("Could not get current URL"))
+        // The DOM source has what the script added, the network source does not
+        let dom = browser.get_page_source().await.unwrap();
+        let network = browser.get_network_source().await.unwrap();
+        assert!(dom.contains("<li>Added by script</li>"));
+        assert_eq!(network, "<!DOCTYPE html><html><body><ul></ul></body></html>");
+
+        // Latin-1 documents come back base64-encoded
+        assert_eq!(browser.get_network_source().await.unwrap(), "<p>café</p>");
+
+        let calls = mock.calls();
+        assert_eq!(calls[1].1, Some(json!({ "nodeId": 1 })));
+        assert_eq!(calls[3].1, Some(json!({ "requestId": "LOADER1" })));
     }
 
-    /// Get page title
-    pub async fn page_title(&mut self) -> Result<String> {
-        let result = self.cdp.send_command("Runtime.evaluate", Some(json!({
-            "expression": "document.title",
-            "returnByValue": true
-        }))).await?;
+    #[tokio::test]
+    async fn test_focus_blur_and_get_focused_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!(true))),
+            ("Runtime.evaluate", evaluate_response(json!({
+                "id": "focused-input",
+                "selector": "#email",
+                "accessibility_id": null,
+                "bounds": [10, 20, 200, 30],
+                "text": "me@example.com",
+                "role": null
+            }))),
+            ("Runtime.evaluate", evaluate_response(json!(true))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        result
-            .get("result")
-            .and_then(|r| r.get("value"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .ok_or_else(|| ChromeMcpError::cdp_protocol("Could not get page title"))
+        browser.focus_element("#email").await.unwrap();
+        let focused = browser.get_focused_element().await.unwrap().unwrap();
+        assert_eq!(focused.selector.as_deref(), Some("#email"));
+        assert_eq!(focused.bounds, Some((10.0, 20.0, 200.0, 30.0)));
+        assert_eq!(focused.text.as_deref(), Some("me@example.com"));
+
+        browser.blur_element("#email").await.unwrap();
+        assert!(browser.get_focused_element().await.unwrap().is_none());
+        let error = browser.focus_element("#missing").await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::ElementNotFound(_)), "{}", error);
+
+        let expressions: Vec<String> = mock
+            .calls()
+            .into_iter()
+            .map(|(_, params)| params.unwrap()["expression"].as_str().unwrap().to_string())
+            .collect();
+        assert!(expressions[0].contains("document.querySelector(\"#email\"); return el ? (el.focus(), true) : null;"));
+        assert!(expressions[1].contains("document.activeElement"));
+        assert!(expressions[2].contains("el.blur()"));
     }
 
-    /// Get accessibility tree
-    pub async fn accessibility_tree(&mut self) -> Result<AccessibilityNode> {
-        self.accessibility.get_full_tree().await
+    #[test]
+    fn test_decode_html_bytes() {
+        assert_eq!(decode_html_bytes("<p>café ✓</p>".as_bytes(), None), "<p>café ✓</p>");
+        assert_eq!(decode_html_bytes(b"<p>na\xefve</p>", None), "<p>naïve</p>");
+        // Shift_JIS for "日本", named by the response, by a meta tag or overridden by a BOM
+        assert_eq!(decode_html_bytes(b"<p>\x93\xfa\x96\x7b</p>", Some("text/html; charset=Shift_JIS")), "<p>日本</p>");
+        assert_eq!(decode_html_bytes(b"<meta charset=\"shift_jis\"><p>\x93\xfa\x96\x7b</p>", None), "<meta charset=\"shift_jis\"><p>日本</p>");
+        let http_equiv = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1251\"><p>\xcc\xe8\xf0</p>";
+        assert!(decode_html_bytes(http_equiv, None).ends_with("<p>Мир</p>"));
+        assert_eq!(decode_html_bytes("\u{feff}<p>café</p>".as_bytes(), Some("text/html; charset=iso-8859-1")), "<p>café</p>");
+        assert_eq!(decode_html_bytes(b"<p>caf\xe9</p>", Some("text/html; charset=nonsense")), "<p>café</p>");
     }
 
-    /// Get accessibility manager
-    pub fn accessibility(&mut self) -> &mut AccessibilityManager {
-        &mut self.accessibility
+    /// HTTP server answering `/missing` with 404, HEAD requests to `/no-head` with 405 and
+    /// everything else with 200
+    async fn spawn_link_server() -> std::net::SocketAddr {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = [0u8; 1024];
+                let read = stream.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                let status = if request.starts_with("HEAD /no-head ") {
+                    "405 Method Not Allowed"
+                } else if request.contains(" /missing ") {
+                    "404 Not Found"
+                } else {
+                    "200 OK"
+                };
+                let response = format!("HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        addr
     }
 
-    /// Find elements using various strategies
-    pub async fn find_elements(&mut self, query: &str) -> Result<Vec<ElementRef>> {
-        let mut results = Vec::new();
+    #[tokio::test]
+    async fn test_check_links_with_mock_cdp() {
+        let server = spawn_link_server().await;
+        let external = |path: &str| format!("http://{}{}", server, path);
 
-        // Try CSS selector
-        if let Ok(element) = self.find_element_by_selector(query).await {
-            results.push(element);
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({
+                "origin": "https://example.com",
+                "links": [
+                    "https://example.com/about",
+                    external("/missing"),
+                    "https://example.com/gone",
+                    external("/no-head"),
+                    "https://example.com/offline",
+                    "https://example.com/cached"
+                ]
+            }))),
+        ]);
+        let document = |frame_id: &str, status: u16| ("Network.responseReceived", json!({
+            "type": "Document", "frameId": frame_id, "response": { "status": status }
+        }));
+        for _ in 0..4 {
+            mock.push_response("Network.enable", json!({}));
+            mock.push_response("Page.enable", json!({}));
         }
+        mock.push_response_with_events("Page.navigate", json!({ "frameId": "F1" }), vec![
+            ("Network.responseReceived", json!({ "type": "Image", "frameId": "F1", "response": { "status": 404 } })),
+            document("F1", 200),
+        ]);
+        mock.push_response_with_events("Page.navigate", json!({ "frameId": "F2" }), vec![document("F2", 404)]);
+        mock.push_response("Page.navigate", json!({ "frameId": "F3", "errorText": "net::ERR_CONNECTION_REFUSED" }));
+        // Restored without a response event and without a navigation entry status
+        mock.push_response_with_events("Page.navigate", json!({ "frameId": "F4" }), vec![("Page.loadEventFired", json!({}))]);
+        mock.push_response("Runtime.evaluate", evaluate_response(Value::Null));
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        browser.connect(None).await.unwrap();
 
-        // Try accessibility text
-        if let Ok(element) = self.find_element_by_text(query).await {
-            results.push(element);
-        }
+        let results = browser.check_links(Some(vec!["main"]), 1, true, 5000).await.unwrap();
+        let summary: Vec<(&str, Option<u16>, bool)> = results
+            .iter()
+            .map(|r| (r.url.as_str(), r.status_code, r.is_broken))
+            .collect();
+        assert_eq!(summary, vec![
+            ("https://example.com/about", Some(200), false),
+            ("https://example.com/gone", Some(404), true),
+            ("https://example.com/offline", None, true),
+            ("https://example.com/cached", None, false),
+            (external("/missing").as_str(), Some(404), true),
+            (external("/no-head").as_str(), Some(200), false),
+        ]);
+        assert_eq!(results[0].error, None);
+        assert_eq!(results[2].error.as_deref(), Some("net::ERR_CONNECTION_REFUSED"));
+        assert_eq!(results[3].error.as_deref(), Some("HTTP status unknown"));
 
-        // Try accessibility role
-        if let Ok(element) = self.find_element_by_role(query).await {
-            results.push(element);
-        }
+        let script = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(script.contains(r#"const selectors = ["main"];"#));
 
-        if results.is_empty() {
-            return Err(ChromeMcpError::element_not_found(format!("No elements found for: {}", query)));
-        }
+        // Every checking tab was closed again
+        assert_eq!(browser.list_tabs().await.unwrap().len(), 1);
+        assert_eq!(browser.current_tab_id(), Some(MOCK_TAB_ID));
+        assert_eq!(mock.remaining(), 0);
+    }
 
-        Ok(results)
+    #[tokio::test]
+    async fn test_generate_stable_selector() {
+        let mock = MockCdpClient::new(vec![
+            ("DOM.getDocument", json!({ "root": { "nodeId": 1 } })),
+            ("DOM.querySelectorAll", json!({ "nodeIds": [7] })),
+            ("DOM.resolveNode", json!({ "object": { "objectId": "obj-7" } })),
+            ("Runtime.callFunctionOn", json!({ "result": { "type": "string", "value": r#"[data-testid="submit"]"# } })),
+            ("DOM.resolveNode", json!({ "object": { "objectId": "obj-42" } })),
+            ("Runtime.callFunctionOn", json!({ "result": { "type": "string", "value": "#search" } })),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let selector = browser.generate_stable_selector("div.mui-btn-root > span").await.unwrap();
+        assert_eq!(selector, r#"[data-testid="submit"]"#);
+
+        let calls = mock.calls();
+        let resolve = calls.iter().find(|(method, _)| method == "DOM.resolveNode").unwrap();
+        assert_eq!(resolve.1, Some(json!({ "nodeId": 7 })));
+        let call = calls.iter().find(|(method, _)| method == "Runtime.callFunctionOn").unwrap().1.clone().unwrap();
+        assert_eq!(call["objectId"], "obj-7");
+        assert!(call["functionDeclaration"].as_str().unwrap().contains("data-testid"));
+
+        let mut node = AccessibilityNode {
+            node_id: "5".to_string(),
+            role: Some("searchbox".to_string()),
+            name: Some("Search".to_string()),
+            description: None,
+            value: None,
+            properties: None,
+            children: Vec::new(),
+            bounds: None,
+            focusable: true,
+            focused: false,
+            clickable: true,
+            backend_node_id: Some(42),
+        };
+        let mut elements = vec![element_ref_from_node(&node)];
+        node.backend_node_id = None;
+        elements.push(element_ref_from_node(&node));
+        browser.add_stable_selectors(&mut elements).await;
+        assert_eq!(elements[0].stable_selector.as_deref(), Some("#search"));
+        assert_eq!(elements[1].stable_selector, None);
+        assert_eq!(mock.calls().iter().rfind(|(method, _)| method == "DOM.resolveNode").unwrap().1, Some(json!({ "backendNodeId": 42 })));
     }
 
-    /// Get cookies for current domain
-    pub async fn get_cookies(&mut self) -> Result<Vec<Cookie>> {
-        let result = self.cdp.send_command("Network.getCookies", None).await?;
-        
-        let cookies_json = result
-            .get("cookies")
-            .and_then(|c| c.as_array())
-            .ok_or_else(|| ChromeMcpError::network_error("Invalid cookies response"))?;
+    #[tokio::test]
+    async fn test_run_seo_audit() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({
+                "title": "Acme Widgets - Durable widgets for every workshop",
+                "descriptions": [],
+                "robots": ["index, nofollow"],
+                "viewport": ["width=device-width, initial-scale=1"],
+                "canonicals": ["https://example.com/widgets"],
+                "headings": [1, 2, 4, 2],
+                "images": 4,
+                "images_without_alt": 1
+            }))),
+            ("Performance.enable", json!({})),
+            ("Performance.getMetrics", json!({ "metrics": [
+                { "name": "NavigationStart", "value": 100.0 },
+                { "name": "DomContentLoaded", "value": 101.2 },
+            ] })),
+            ("Performance.disable", json!({})),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        let cookies: Vec<Cookie> = cookies_json
-            .iter()
-            .filter_map(|cookie_json| {
-                Some(Cookie {
-                    name: cookie_json.get("name")?.as_str()?.to_string(),
-                    value: cookie_json.get("value")?.as_str()?.to_string(),
-                    domain: cookie_json.get("domain")?.as_str()?.to_string(),
-                    path: cookie_json.get("path")?.as_str()?.to_string(),
-                    secure: cookie_json.get("secure")?.as_bool().unwrap_or(false),
-                    http_only: cookie_json.get("httpOnly")?.as_bool().unwrap_or(false),
-                    same_site: cookie_json.get("sameSite").and_then(|s| s.as_str()).map(|s| s.to_string()),
-                    expires: cookie_json.get("expires").and_then(|e| e.as_f64()),
-                })
-            })
-            .collect();
+        let audit = browser.run_seo_audit().await.unwrap();
+        let summary: Vec<(&str, SeoSeverity)> = audit.checks.iter().map(|c| (c.name.as_str(), c.severity)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("meta_description", SeoSeverity::Error),
+                ("image_alt", SeoSeverity::Error),
+                ("heading_hierarchy", SeoSeverity::Warning),
+                ("robots", SeoSeverity::Warning),
+                ("title", SeoSeverity::Pass),
+                ("h1", SeoSeverity::Pass),
+                ("canonical", SeoSeverity::Pass),
+                ("viewport", SeoSeverity::Pass),
+                ("page_speed", SeoSeverity::Pass),
+            ]
+        );
+        let hierarchy = &audit.checks[2];
+        assert!(!hierarchy.passed);
+        assert_eq!(hierarchy.value.as_deref(), Some("h2 followed by h4"));
+        assert!(audit.checks[4].recommendation.is_empty());
+        assert_eq!(audit.checks[8].value.as_deref(), Some("DOMContentLoaded after 1200ms"));
+    }
 
-        Ok(cookies)
+    #[test]
+    fn test_seo_checks_flag_missing_basics() {
+        let checks = seo_checks(&SeoFacts::default(), None);
+        let failing: Vec<&str> = checks.iter().filter(|c| c.severity == SeoSeverity::Error).map(|c| c.name.as_str()).collect();
+        assert_eq!(failing, vec!["title", "meta_description", "h1", "viewport"]);
+
+        let facts = SeoFacts {
+            robots: vec!["noindex".to_string()],
+            canonicals: vec!["/a".to_string(), "/b".to_string()],
+            ..Default::default()
+        };
+        let checks = seo_checks(&facts, Some(5000.0));
+        for name in ["robots", "canonical", "page_speed"] {
+            assert_eq!(checks.iter().find(|c| c.name == name).unwrap().severity, SeoSeverity::Error, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_library_from_script_url() {
+        let library = |url| library_from_script_url(url);
+        assert_eq!(library("https://code.jquery.com/jquery-3.6.0.min.js"), Some(("jquery".to_string(), Some("3.6.0".to_string()))));
+        assert_eq!(
+            library("https://unpkg.com/react-dom@18.2.0/umd/react-dom.production.min.js"),
+            Some(("react-dom".to_string(), Some("18.2.0".to_string())))
+        );
+        assert_eq!(
+            library("https://ajax.googleapis.com/ajax/libs/angularjs/1.8.2/angular.min.js"),
+            Some(("angular".to_string(), Some("1.8.2".to_string())))
+        );
+        assert_eq!(library("/static/vue.global.prod.js?v=3"), Some(("vue".to_string(), None)));
+        assert_eq!(library("https://example.com/app.bundle.js"), None);
+        assert_eq!(library("https://example.com/reactive.js"), None);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_js_bundles() {
+        let mock = MockCdpClient::new(vec![(
+            "Runtime.evaluate",
+            evaluate_response(json!({
+                "globals": [
+                    { "name": "jquery", "global": "jQuery", "version": "3.6.0" },
+                    { "name": "jquery", "global": "$", "version": "1.12.4" },
+                    { "name": "react", "global": "React", "version": "18.2.0" },
+                ],
+                "scripts": [
+                    { "url": "https://cdn.example.com/jquery-1.12.4.min.js", "size": 1000 },
+                    { "url": "https://code.jquery.com/jquery-3.6.0.min.js", "size": 3000 },
+                    { "url": "https://example.com/app.js", "size": 500 },
+                    { "url": "https://example.com/app.js", "size": 500 },
+                ],
+                "inlineBytes": 20
+            })),
+        )]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let analysis = browser.analyze_js_bundles().await.unwrap();
+        assert_eq!(analysis.detected_libraries.len(), 5);
+        assert_eq!(analysis.total_script_size_bytes, 4520);
+        assert_eq!(
+            analysis.duplicates,
+            vec![
+                DuplicateLibrary {
+                    name: "jquery".to_string(),
+                    versions: vec!["3.6.0".to_string(), "1.12.4".to_string()],
+                    script_urls: vec![
+                        "https://cdn.example.com/jquery-1.12.4.min.js".to_string(),
+                        "https://code.jquery.com/jquery-3.6.0.min.js".to_string(),
+                    ],
+                },
+                DuplicateLibrary {
+                    name: "app.js".to_string(),
+                    versions: vec![],
+                    script_urls: vec!["https://example.com/app.js".to_string(), "https://example.com/app.js".to_string()],
+                },
+            ]
+        );
     }
 
-    /// Set a cookie
-    pub async fn set_cookie(&mut self, cookie: Cookie) -> Result<()> {
-        let mut params = json!({
-            "name": cookie.name,
-            "value": cookie.value,
-            "domain": cookie.domain,
-            "path": cookie.path,
-            "secure": cookie.secure,
-            "httpOnly": cookie.http_only,
-        });
+    #[tokio::test]
+    async fn test_auto_save_element_screenshot() {
+        let mock = MockCdpClient::new(vec![
+            ("DOM.getDocument", json!({ "root": { "nodeId": 1 } })),
+            ("DOM.querySelector", json!({ "nodeId": 7 })),
+            ("DOM.getBoxModel", json!({ "model": { "content": [10, 20, 110, 20, 110, 70, 10, 70] } })),
+            ("Page.captureScreenshot", json!({ "data": "iVBORw0KGgo=" })),
+            ("Runtime.evaluate", evaluate_response(json!("https://example.com/"))),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        browser.screenshot.set_auto_save(dir.path(), None);
 
-        if let Some(same_site) = cookie.same_site {
-            params["sameSite"] = json!(same_site);
-        }
+        let path = browser.auto_save_screenshot(Some("jpeg"), None, Some("card-{counter}"), Some("#card"), false).await.unwrap();
+        assert!(path.ends_with("card-0001.png"), "{}", path);
+        let capture = mock.calls()[3].1.clone().unwrap();
+        assert_eq!(capture["clip"], json!({ "x": 10.0, "y": 20.0, "width": 100.0, "height": 50.0, "scale": 1.0 }));
+        assert_eq!(mock.remaining(), 0);
+    }
 
-        if let Some(expires) = cookie.expires {
-            params["expires"] = json!(expires);
-        }
+    #[tokio::test]
+    async fn test_screenshot_at_breakpoints() {
+        let mock = MockCdpClient::new(vec![
+            ("Emulation.setDeviceMetricsOverride", json!({})),
+            ("Page.captureScreenshot", json!({ "data": "c20=" })),
+            ("Emulation.setDeviceMetricsOverride", json!({})),
+            ("Page.captureScreenshot", json!({ "data": "bGc=" })),
+            ("Emulation.clearDeviceMetricsOverride", json!({})),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        self.cdp.send_command("Network.setCookie", Some(params)).await?;
-        Ok(())
+        let screenshots = browser.screenshot_at_breakpoints(vec![640, 1024], 800, None).await.unwrap();
+        assert_eq!(
+            screenshots,
+            vec![
+                BreakpointScreenshot { width: 640, screenshot_b64: "c20=".to_string() },
+                BreakpointScreenshot { width: 1024, screenshot_b64: "bGc=".to_string() },
+            ]
+        );
+        let calls = mock.calls();
+        assert_eq!(calls[2].1, Some(json!({ "width": 1024, "height": 800, "deviceScaleFactor": 1.0, "mobile": false })));
+        assert_eq!(calls.last().unwrap().0, "Emulation.clearDeviceMetricsOverride");
+        assert_eq!(mock.remaining(), 0);
+
+        // An override set before is put back afterwards
+        mock.push_response("Emulation.setDeviceMetricsOverride", json!({}));
+        browser.screenshot.set_viewport_size(390, 844).await.unwrap();
+        mock.push_response("Emulation.setDeviceMetricsOverride", json!({}));
+        mock.push_response("Page.captureScreenshot", json!({ "data": "c20=" }));
+        mock.push_response("Emulation.setDeviceMetricsOverride", json!({}));
+        browser.screenshot_at_breakpoints(vec![640], 800, None).await.unwrap();
+        let calls = mock.calls();
+        assert_eq!(calls.last().unwrap().1, Some(json!({ "width": 390, "height": 844, "deviceScaleFactor": 1.0, "mobile": false })));
+        assert_eq!(mock.remaining(), 0);
     }
 
-    /// Clear all cookies
-    pub async fn clear_cookies(&mut self) -> Result<()> {
-        self.cdp.send_command("Network.clearBrowserCookies", None).await?;
-        Ok(())
+    #[tokio::test]
+    async fn test_type_text_humanized() {
+        let mock = MockCdpClient::new(vec![
+            ("Input.dispatchKeyEvent", json!({})),
+            ("Input.dispatchKeyEvent", json!({})),
+            ("Input.dispatchKeyEvent", json!({})),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let started = std::time::Instant::now();
+        browser.type_text_humanized("hé!", 20, 40).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(40));
+
+        let typed: Vec<Value> = mock.calls().into_iter().map(|(_, params)| params.unwrap()["text"].clone()).collect();
+        assert_eq!(typed, vec![json!("h"), json!("é"), json!("!")]);
     }
 
-    /// Generate PDF of current page
-    pub async fn pdf(&mut self, options: Option<PdfOptions>) -> Result<String> {
-        self.screenshot.capture_pdf(options).await
+    /// Accessibility tree with the given `(role, name, bounds)` nodes below the root
+    fn push_ax_tree(mock: &MockCdpClient, nodes: &[(&str, &str, Option<[f64; 4]>)]) {
+        let child_ids: Vec<String> = (0..nodes.len()).map(|i| (i + 2).to_string()).collect();
+        let mut tree = vec![json!({ "nodeId": "1", "role": { "type": "role", "value": "RootWebArea" }, "childIds": child_ids })];
+        for (i, (role, name, bounds)) in nodes.iter().enumerate() {
+            let mut node = json!({
+                "nodeId": (i + 2).to_string(),
+                "role": { "type": "role", "value": role },
+                "name": { "type": "computedString", "value": name },
+                "childIds": []
+            });
+            if let Some([x, y, width, height]) = bounds {
+                node["boundingRect"] = json!({ "x": x, "y": y, "width": width, "height": height });
+            }
+            tree.push(node);
+        }
+        mock.push_response("Accessibility.getFullAXTree", json!({ "nodes": tree }));
     }
 
-    // Private helper methods
+    #[tokio::test]
+    async fn test_navigate_menu() {
+        let mock = MockCdpClient::new(vec![]);
+        mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+        mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [7] }));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!({ "timestamp_ms": 0, "x": 10, "y": 10, "width": 60, "height": 20 })));
+        mock.push_response("Input.dispatchMouseEvent", json!({}));
+        push_ax_tree(&mock, &[
+            ("menuitem", "New", Some([10.0, 40.0, 100.0, 20.0])),
+            ("menuitem", "Export as…", Some([10.0, 60.0, 100.0, 20.0])),
+            ("menuitem", "Hidden", None),
+        ]);
+        mock.push_response("Input.dispatchMouseEvent", json!({}));
+        push_ax_tree(&mock, &[
+            ("menuitem", "Export as…", Some([10.0, 60.0, 100.0, 20.0])),
+            ("menuitem", "PDF", Some([110.0, 60.0, 80.0, 20.0])),
+        ]);
+        mock.push_response("Input.dispatchMouseEvent", json!({}));
+        mock.push_response("Input.dispatchMouseEvent", json!({}));
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-    async fn find_element_any_strategy(&mut self, query: &str) -> Result<ElementRef> {
-        // Try CSS selector first
-        if let Ok(element) = self.find_element_by_selector(query).await {
-            return Ok(element);
-        }
+        browser.navigate_menu("#file-menu", vec!["export", "PDF"]).await.unwrap();
+        assert_eq!(mock.remaining(), 0);
 
-        // Try accessibility text
-        if let Ok(element) = self.find_element_by_text(query).await {
-            return Ok(element);
-        }
+        let moves: Vec<Value> = mock
+            .calls()
+            .into_iter()
+            .filter(|(method, _)| method == "Input.dispatchMouseEvent")
+            .filter_map(|(_, params)| params)
+            .collect();
+        assert_eq!((moves[0]["x"].as_f64(), moves[0]["y"].as_f64()), (Some(40.0), Some(20.0)));
+        assert_eq!((moves[1]["x"].as_f64(), moves[1]["y"].as_f64()), (Some(60.0), Some(70.0)));
+        assert_eq!(moves[2]["type"], "mousePressed");
+        assert_eq!((moves[2]["x"].as_f64(), moves[2]["y"].as_f64()), (Some(150.0), Some(70.0)));
+    }
 
-        // Try accessibility role
-        if let Ok(element) = self.find_element_by_role(query).await {
-            return Ok(element);
+    #[tokio::test]
+    async fn test_navigate_menu_missing_item_dismisses_menu() {
+        let mock = MockCdpClient::new(vec![]);
+        mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+        mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [7] }));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!({ "timestamp_ms": 0, "x": 10, "y": 10, "width": 60, "height": 20 })));
+        mock.push_response("Input.dispatchMouseEvent", json!({}));
+        for _ in 0..MENU_ITEM_ATTEMPTS {
+            push_ax_tree(&mock, &[("menuitem", "New", Some([10.0, 40.0, 100.0, 20.0]))]);
         }
+        mock.push_response("Input.dispatchKeyEvent", json!({}));
+        mock.push_response("Input.dispatchKeyEvent", json!({}));
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        Err(ChromeMcpError::element_not_found(format!("Element not found: {}", query)))
+        let error = browser.navigate_menu("#file-menu", vec!["Delete"]).await.unwrap_err();
+        assert!(error.to_string().contains("Menu item not found: Delete"));
+        assert_eq!(mock.remaining(), 0);
+        let calls = mock.calls();
+        assert_eq!(calls.last().unwrap().1.as_ref().unwrap()["key"], "Escape");
+
+        let mut browser = Browser::with_cdp_client(MockCdpClient::new(vec![]).client()).unwrap();
+        assert!(browser.navigate_menu("#file-menu", vec![]).await.is_err());
     }
 
-    async fn find_element_by_selector(&mut self, selector: &str) -> Result<ElementRef> {
-        let nodes = self.cdp.query_selector_all(selector).await?;
-        let node_ids = nodes
-            .get("nodeIds")
-            .and_then(|ids| ids.as_array())
-            .ok_or_else(|| ChromeMcpError::element_not_found(format!("No elements found for selector: {}", selector)))?;
+    /// Responses locating `#row` at (100, 200) with a 50x20 box
+    fn push_element_at(mock: &MockCdpClient) {
+        mock.push_response("DOM.getDocument", json!({ "root": { "nodeId": 1 } }));
+        mock.push_response("DOM.querySelectorAll", json!({ "nodeIds": [7] }));
+        mock.push_response("Runtime.evaluate", evaluate_response(json!({ "timestamp_ms": 0, "x": 100, "y": 200, "width": 50, "height": 20 })));
+    }
 
-        if node_ids.is_empty() {
-            return Err(ChromeMcpError::element_not_found(format!("No elements found for selector: {}", selector)));
-        }
+    fn mouse_events(mock: &MockCdpClient) -> Vec<(String, String, u64)> {
+        mock.calls()
+            .into_iter()
+            .filter(|(method, _)| method == "Input.dispatchMouseEvent")
+            .filter_map(|(_, params)| params)
+            .map(|p| (p["type"].as_str().unwrap().to_string(), p["button"].as_str().unwrap().to_string(), p["clickCount"].as_u64().unwrap()))
+            .collect()
+    }
 
-        // Use the first found element
-        let node_id = node_ids[0]
-            .as_u64()
-            .ok_or_else(|| ChromeMcpError::cdp_protocol("Invalid node ID"))?;
-
-        Ok(ElementRef {
-            id: format!("dom-{}", node_id),
-            selector: Some(selector.to_string()),
-            accessibility_id: None,
-            bounds: None, // TODO: Get bounds from DOM
-            text: None,
-            role: None,
-        })
+    #[tokio::test]
+    async fn test_right_click_selects_context_menu_item() {
+        let mock = MockCdpClient::new(vec![]);
+        push_element_at(&mock);
+        mock.push_response("Input.dispatchMouseEvent", json!({}));
+        mock.push_response("Input.dispatchMouseEvent", json!({}));
+        push_ax_tree(&mock, &[
+            ("menuitem", "Rename", Some([120.0, 210.0, 80.0, 20.0])),
+            ("menuitem", "Delete", Some([120.0, 230.0, 80.0, 20.0])),
+        ]);
+        mock.push_response("Input.dispatchMouseEvent", json!({}));
+        mock.push_response("Input.dispatchMouseEvent", json!({}));
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.right_click("#row").await.unwrap();
+        browser.select_context_menu_item("delete").await.unwrap();
+        assert_eq!(mock.remaining(), 0);
+
+        let events = mouse_events(&mock);
+        let click = |kind: &str, button: &str| (kind.to_string(), button.to_string(), 1);
+        assert_eq!(events, vec![
+            click("mousePressed", "right"),
+            click("mouseReleased", "right"),
+            click("mousePressed", "left"),
+            click("mouseReleased", "left"),
+        ]);
+        let calls = mock.calls();
+        let right_press = calls.iter().find(|(method, _)| method == "Input.dispatchMouseEvent").unwrap().1.clone().unwrap();
+        assert_eq!((right_press["x"].as_f64(), right_press["y"].as_f64()), (Some(125.0), Some(210.0)));
+        let item_press = calls.iter().rev().find(|(method, _)| method == "Input.dispatchMouseEvent").unwrap().1.clone().unwrap();
+        assert_eq!(item_press["y"].as_f64(), Some(240.0));
     }
 
-    async fn find_element_by_text(&mut self, text: &str) -> Result<ElementRef> {
-        let nodes = self.accessibility.find_clickable_by_text(text).await?;
-        if let Some(node) = nodes.first() {
-            Ok(ElementRef {
-                id: format!("ax-{}", node.node_id),
-                selector: None,
-                accessibility_id: Some(node.node_id.clone()),
-                bounds: node.bounds.as_ref().map(|b| (b.x, b.y, b.width, b.height)),
-                text: node.name.clone(),
-                role: node.role.clone(),
-            })
-        } else {
-            Err(ChromeMcpError::element_not_found(format!("No clickable element found with text: {}", text)))
+    #[tokio::test]
+    async fn test_double_click_event_sequence() {
+        let mock = MockCdpClient::new(vec![]);
+        push_element_at(&mock);
+        for _ in 0..4 {
+            mock.push_response("Input.dispatchMouseEvent", json!({}));
         }
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let started = Instant::now();
+        browser.double_click_with_delay("#row", 150).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(150));
+
+        let events = mouse_events(&mock);
+        let expected = [("mousePressed", 1), ("mouseReleased", 1), ("mousePressed", 2), ("mouseReleased", 2)];
+        assert_eq!(events, expected.map(|(kind, count)| (kind.to_string(), "left".to_string(), count)).to_vec());
+
+        let mut browser = Browser::with_cdp_client(MockCdpClient::new(vec![]).client()).unwrap();
+        let error = browser.double_click("Missing button").await.unwrap_err();
+        assert!(matches!(error.root_cause(), ChromeMcpError::ElementNotFound(_)), "{}", error);
     }
 
-    async fn find_element_by_role(&mut self, role: &str) -> Result<ElementRef> {
-        let nodes = self.accessibility.find_by_role(role).await?;
-        if let Some(node) = nodes.first() {
-            Ok(ElementRef {
-                id: format!("ax-{}", node.node_id),
-                selector: None,
-                accessibility_id: Some(node.node_id.clone()),
-                bounds: node.bounds.as_ref().map(|b| (b.x, b.y, b.width, b.height)),
-                text: node.name.clone(),
-                role: node.role.clone(),
-            })
-        } else {
-            Err(ChromeMcpError::element_not_found(format!("No element found with role: {}", role)))
-        }
+    #[tokio::test]
+    async fn test_wait_for_title_conditions() {
+        let title = |t: &str| ("Runtime.evaluate", evaluate_response(json!(t)));
+        let mock = MockCdpClient::new(vec![
+            title("Processing…"),
+            title("Processing… 50%"),
+            title("Done! 3 files"),
+            title("Processing…"),
+            title("Uploaded"),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        browser.wait_for_condition(WaitCondition::TitleMatches("Done!*files".to_string()), 5000).await.unwrap();
+        browser.wait_for_condition(WaitCondition::TitleChangedFrom("Processing…".to_string()), 5000).await.unwrap();
+        assert_eq!(mock.remaining(), 0);
+
+        assert!(title_matches("Done", "Done! 3 files"));
+        assert!(!title_matches("Done", "Not Done"));
+        assert!(title_matches("*Done", "Not Done"));
+        assert!(title_matches("Step ? of 3", "Step 2 of 3"));
     }
 
-    async fn click_element_ref(&mut self, element_ref: &ElementRef) -> Result<()> {
-        if let Some((x, y, width, height)) = element_ref.bounds {
-            // Click at center of element
-            let center_x = x + width / 2.0;
-            let center_y = y + height / 2.0;
-            self.cdp.click_at(center_x, center_y).await
-        } else if let Some(ref selector) = element_ref.selector {
-            // Try to click using JavaScript
-            self.cdp.send_command("Runtime.evaluate", Some(json!({
-                "expression": format!("document.querySelector('{}').click()", selector.replace("'", "\\'"))
-            }))).await?;
-            Ok(())
-        } else {
-            Err(ChromeMcpError::invalid_operation("Cannot click element: no bounds or selector"))
-        }
+    #[tokio::test]
+    async fn test_page_info() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({
+                "title": "Example",
+                "url": "https://example.com/docs",
+                "favicon": "https://example.com/favicon.ico",
+            }))),
+            ("Runtime.evaluate", evaluate_response(json!(64.0))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+
+        let info = browser.page_info().await.unwrap();
+        assert_eq!(
+            info,
+            PageInfo {
+                title: "Example".to_string(),
+                url: "https://example.com/docs".to_string(),
+                favicon_url: Some("https://example.com/favicon.ico".to_string()),
+                fixed_header_height: 64.0,
+            }
+        );
+        assert!(mock.calls()[1].1.as_ref().unwrap()["expression"].as_str().unwrap().contains("position"));
     }
 
-    async fn is_element_visible(&mut self, selector: &str) -> Result<bool> {
-        let result = self.cdp.send_command("Runtime.evaluate", Some(json!({
-            "expression": format!(
-                r#"
-                const el = document.querySelector('{}');
-                el && el.offsetParent !== null && 
-                getComputedStyle(el).visibility !== 'hidden' && 
-                getComputedStyle(el).display !== 'none'
-                "#,
-                selector.replace("'", "\\'")
-            ),
-            "returnByValue": true
-        }))).await?;
+    #[tokio::test]
+    async fn test_fetch_with_headers() {
+        let mock = MockCdpClient::new(vec![(
+            "Runtime.evaluate",
+            evaluate_response(json!({
+                "status": 200,
+                "url": "https://api.example.com/items",
+                "redirected": false,
+                "headers": { "content-type": "application/json", "cache-control": "max-age=60" },
+                "body": "[1,2]",
+                "fromCache": true,
+            })),
+        )]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        Ok(result
-            .get("result")
-            .and_then(|r| r.get("value"))
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false))
+        let headers = HashMap::from([("Accept".to_string(), "application/json".to_string())]);
+        let result = browser.fetch_with_headers("https://api.example.com/items", headers, "get", None, false).await.unwrap();
+        assert_eq!(result.status, 200);
+        assert_eq!(result.response_headers["cache-control"], "max-age=60");
+        assert_eq!(result.body_text, "[1,2]");
+        assert!(result.from_cache);
+        assert!(!result.redirected);
+
+        let expression = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#""method":"GET""#));
+        assert!(expression.contains(r#""headers":{"Accept":"application/json"}"#));
+        assert!(expression.contains(r#""redirect":"manual""#));
+
+        let error = browser.fetch_with_headers("https://api.example.com/items", HashMap::new(), "GET", Some("x"), true).await;
+        assert!(matches!(error, Err(ChromeMcpError::InvalidOperation(_))));
+        assert_eq!(mock.remaining(), 0);
     }
 
-    async fn is_element_clickable(&mut self, selector: &str) -> Result<bool> {
-        let result = self.cdp.send_command("Runtime.evaluate", Some(json!({
-            "expression": format!(
-                r#"
-                const el = document.querySelector('{}');
-                el && el.offsetParent !== null && 
-                !el.disabled &&
-                getComputedStyle(el).pointerEvents !== 'none'
-                "#,
-                selector.replace("'", "\\'")
-            ),
-            "returnByValue": true
-        }))).await?;
+    #[tokio::test]
+    async fn test_probe_local_ports() {
+        let mock = MockCdpClient::new(vec![(
+            "Runtime.evaluate",
+            evaluate_response(json!([
+                { "open": true, "status": 200 },
+                { "open": true, "status": null },
+                { "open": false, "status": null },
+            ])),
+        )]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        Ok(result
-            .get("result")
-            .and_then(|r| r.get("value"))
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false))
+        let results = browser.probe_local_ports("127.0.0.1", vec![8080, 80, 9999], 500).await.unwrap();
+        assert_eq!(
+            results,
+            vec![
+                PortProbeResult { port: 8080, open: true, response_code: Some(200) },
+                PortProbeResult { port: 80, open: true, response_code: None },
+                PortProbeResult { port: 9999, open: false, response_code: None },
+            ]
+        );
+        let expression = mock.calls()[0].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"["http://127.0.0.1:8080/","http://127.0.0.1/","http://127.0.0.1:9999/"]"#));
+        assert!(expression.contains("AbortSignal.timeout(500)"));
+
+        assert!(browser.probe_local_ports("example.com/admin?x=", vec![80], 500).await.is_err());
     }
 
-    async fn is_text_present(&mut self, text: &str) -> Result<bool> {
-        let result = self.cdp.send_command("Runtime.evaluate", Some(json!({
-            "expression": format!(
-                "document.body.textContent.includes('{}')",
-                text.replace("'", "\\'")
-            ),
-            "returnByValue": true
-        }))).await?;
+    #[tokio::test]
+    async fn test_css_variables() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!([
+                { "name": "--font-size-base", "value": "16px", "source": null },
+                { "name": "--primary-color", "value": "#0055ff", "source": "https://example.com/tokens.css" },
+            ]))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.evaluate", evaluate_response(json!(true))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        Ok(result
-            .get("result")
-            .and_then(|r| r.get("value"))
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false))
+        let variables = browser.get_css_variables(None).await.unwrap();
+        assert_eq!(variables.len(), 2);
+        assert_eq!(variables["--primary-color"], "#0055ff");
+
+        let missing = browser.list_css_variables(Some(".missing")).await.unwrap_err();
+        assert!(matches!(missing, ChromeMcpError::ElementNotFound(_)));
+
+        browser.set_css_variable(":root", "--primary-color", "red").await.unwrap();
+        let expression = mock.calls()[2].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression.contains(r#"el.style.setProperty("--primary-color", "red")"#));
+
+        assert!(browser.set_css_variable(":root", "color", "red").await.is_err());
+        assert_eq!(mock.remaining(), 0);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn test_fullscreen() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!(""))),
+            ("Runtime.evaluate", evaluate_response(json!(true))),
+            ("Runtime.evaluate", evaluate_response(json!("NotAllowedError: Permissions check failed"))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-    #[test]
-    fn test_browser_creation() {
-        let result = Browser::new("localhost", 9222);
-        assert!(result.is_ok());
+        browser.enter_fullscreen(Some("#player")).await.unwrap();
+        assert!(browser.is_fullscreen().await.unwrap());
+
+        let calls = mock.calls();
+        let request = calls[0].1.as_ref().unwrap();
+        assert_eq!(request["userGesture"], json!(true));
+        assert!(request["expression"].as_str().unwrap().contains(r##"document.querySelector("#player")"##));
+
+        let refused = browser.enter_fullscreen(None).await.unwrap_err();
+        assert!(refused.to_string().contains("NotAllowedError"));
+        let missing = browser.enter_fullscreen(Some("#missing")).await.unwrap_err();
+        assert!(matches!(missing, ChromeMcpError::ElementNotFound(_)));
     }
 
     #[test]
-    fn test_network_event_structure() {
-        let event = NetworkEvent {
-            request_id: "req_123".to_string(),
-            url: "https://example.com".to_string(),
-            method: "GET".to_string(),
-            headers: HashMap::new(),
-            timestamp: 1640995200.0,
-            status_code: Some(200),
-            response_headers: None,
-        };
+    fn test_scan_download_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(scan_download_dir(dir.path(), "report*.pdf", None).unwrap().is_none());
 
-        assert_eq!(event.request_id, "req_123");
-        assert_eq!(event.url, "https://example.com");
-        assert_eq!(event.method, "GET");
-        assert_eq!(event.status_code, Some(200));
-        assert!(event.response_headers.is_none());
+        std::fs::write(dir.path().join("report-2023.pdf"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"x").unwrap();
+        let (filename, progress) = scan_download_dir(dir.path(), "report*.pdf", None).unwrap().unwrap();
+        assert_eq!(filename, "report-2023.pdf");
+        assert!(progress.complete);
+        assert_eq!(progress.total_bytes, Some(10));
+        assert_eq!(progress.percent, Some(100.0));
+
+        // A download in progress wins over finished ones
+        std::fs::write(dir.path().join("report-2024.pdf.crdownload"), vec![0u8; 4]).unwrap();
+        let (filename, progress) = scan_download_dir(dir.path(), "report*.pdf", None).unwrap().unwrap();
+        assert_eq!(filename, "report-2024.pdf");
+        assert_eq!(
+            progress,
+            DownloadProgress { bytes_downloaded: 4, total_bytes: None, percent: None, complete: false }
+        );
+
+        let future = std::time::SystemTime::now() + Duration::from_secs(60);
+        assert!(scan_download_dir(dir.path(), "*", Some(future)).unwrap().is_none());
     }
 
-    #[test]
-    fn test_network_event_serialization() {
-        let mut headers = HashMap::new();
-        headers.insert("User-Agent".to_string(), "chrome-mcp/0.1.0".to_string());
-        headers.insert("Accept".to_string(), "application/json".to_string());
+    #[tokio::test]
+    async fn test_wait_for_download() {
+        use futures_util::StreamExt;
 
-        let event = NetworkEvent {
-            request_id: "req_456".to_string(),
-            url: "https://api.example.com/data".to_string(),
-            method: "POST".to_string(),
-            headers,
-            timestamp: 1640995260.5,
-            status_code: Some(201),
-            response_headers: Some(HashMap::new()),
-        };
+        let dir = tempfile::tempdir().unwrap();
+        let mock = MockCdpClient::new(vec![("Browser.setDownloadBehavior", json!({}))]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        browser.set_download_directory(dir.path()).await.unwrap();
+        assert_eq!(browser.download_directory(), dir.path());
+        assert!(browser.download_progress("data.csv").unwrap().is_none());
 
-        let json_str = serde_json::to_string(&event).unwrap();
-        let parsed: NetworkEvent = serde_json::from_str(&json_str).unwrap();
+        let partial = dir.path().join("data.csv.crdownload");
+        std::fs::write(&partial, b"a,b").unwrap();
+        let finish = tokio::spawn(async move {
+            sleep(Duration::from_millis(300)).await;
+            std::fs::write(&partial, b"a,b\n1,2\n").unwrap();
+            std::fs::rename(&partial, partial.with_extension("")).unwrap();
+        });
 
-        assert_eq!(event.request_id, parsed.request_id);
-        assert_eq!(event.url, parsed.url);
-        assert_eq!(event.method, parsed.method);
-        assert_eq!(event.status_code, parsed.status_code);
+        let progress: Vec<DownloadProgress> = browser.monitor_download_progress("data.csv", 50).collect().await;
+        assert!(!progress[0].complete);
+        assert_eq!(progress[0].bytes_downloaded, 3);
+        assert!(progress.last().unwrap().complete);
+        finish.await.unwrap();
+
+        let download = browser.wait_for_download("data.csv", 2000).await.unwrap();
+        assert_eq!(download.filename, "data.csv");
+        assert_eq!(download.size_bytes, 8);
+
+        let err = browser.wait_for_download("missing.zip", 100).await.unwrap_err();
+        assert!(matches!(err, ChromeMcpError::Timeout { timeout: 100 }));
     }
 
-    #[test]
-    fn test_cookie_structure() {
-        let cookie = Cookie {
-            name: "session_id".to_string(),
-            value: "abc123".to_string(),
-            domain: "example.com".to_string(),
-            path: "/".to_string(),
-            secure: true,
-            http_only: false,
-            same_site: Some("Lax".to_string()),
-            expires: Some(1672531200.0), // 2023-01-01
+    #[tokio::test]
+    async fn test_download_progress_from_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let mock = MockCdpClient::new(vec![("Browser.setDownloadBehavior", json!({}))]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
+        browser.set_download_directory(dir.path()).await.unwrap();
+
+        mock.emit_event("Browser.downloadWillBegin", json!({ "guid": "g1", "suggestedFilename": "big.zip" }));
+        mock.emit_event("Browser.downloadProgress", json!({
+            "guid": "g1", "receivedBytes": 25, "totalBytes": 100, "state": "inProgress"
+        }));
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(
+            browser.download_progress("big*.zip").unwrap(),
+            Some(DownloadProgress { bytes_downloaded: 25, total_bytes: Some(100), percent: Some(25.0), complete: false })
+        );
+
+        let events = async {
+            sleep(Duration::from_millis(50)).await;
+            mock.emit_event("Browser.downloadProgress", json!({
+                "guid": "g1", "receivedBytes": 100, "totalBytes": 100, "state": "completed"
+            }));
+            mock.emit_event("Browser.downloadWillBegin", json!({ "guid": "g2", "suggestedFilename": "other.zip" }));
+            mock.emit_event("Browser.downloadProgress", json!({
+                "guid": "g2", "receivedBytes": 0, "totalBytes": 0, "state": "canceled"
+            }));
         };
+        let (canceled, _) = tokio::join!(browser.wait_for_download("other.zip", 2000), events);
+        assert!(canceled.unwrap_err().to_string().contains("Download of other.zip was canceled"));
 
-        assert_eq!(cookie.name, "session_id");
-        assert_eq!(cookie.value, "abc123");
-        assert_eq!(cookie.domain, "example.com");
-        assert_eq!(cookie.path, "/");
-        assert!(cookie.secure);
-        assert!(!cookie.http_only);
-        assert_eq!(cookie.same_site, Some("Lax".to_string()));
-        assert!(cookie.expires.is_some());
+        let progress = browser.download_progress("big.zip").unwrap().unwrap();
+        assert!(progress.complete);
+        assert_eq!(progress.percent, Some(100.0));
     }
 
-    #[test]
-    fn test_cookie_serialization() {
-        let cookie = Cookie {
-            name: "test_cookie".to_string(),
-            value: "test_value".to_string(),
-            domain: "localhost".to_string(),
-            path: "/test".to_string(),
-            secure: false,
-            http_only: true,
-            same_site: Some("Strict".to_string()),
-            expires: None,
-        };
+    #[tokio::test]
+    async fn test_visibility_ratio_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!(0.25))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.evaluate", evaluate_response(json!(0.2))),
+            ("Runtime.evaluate", evaluate_response(json!(0.75))),
+        ]);
+        let mut browser = Browser::with_cdp_client(mock.client()).unwrap();
 
-        let json_str = serde_json::to_string(&cookie).unwrap();
-        let parsed: Cookie = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(browser.get_visibility_ratio("img.lazy").await.unwrap(), 0.25);
+        let error = browser.get_visibility_ratio("#missing").await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::ElementNotFound(_)));
 
-        assert_eq!(cookie.name, parsed.name);
-        assert_eq!(cookie.value, parsed.value);
-        assert_eq!(cookie.domain, parsed.domain);
-        assert_eq!(cookie.path, parsed.path);
-        assert_eq!(cookie.secure, parsed.secure);
-        assert_eq!(cookie.http_only, parsed.http_only);
-        assert_eq!(cookie.same_site, parsed.same_site);
-        assert_eq!(cookie.expires, parsed.expires);
+        // Polls until the ratio reaches the minimum
+        browser.wait_for_condition(WaitCondition::ElementVisibleRatio("img.lazy".to_string(), 0.5), 2000).await.unwrap();
+        assert_eq!(mock.remaining(), 0);
+
+        let calls = mock.calls();
+        let expression = calls[0].1.as_ref().unwrap()["expression"].as_str().unwrap();
+        assert!(expression.contains("document.querySelector('img.lazy')"));
+        assert!(expression.contains(VISIBILITY_RATIO_JS));
+        assert_eq!(calls.len(), 4);
     }
 
     #[test]
@@ -772,9 +10448,10 @@ mod tests {
             WaitCondition::ElementClickable("#submit".to_string()),
             WaitCondition::TextPresent("Loading complete".to_string()),
             WaitCondition::UrlContains("success".to_string()),
+            WaitCondition::ElementVisibleRatio("img.lazy".to_string(), 0.5),
         ];
 
-        assert_eq!(conditions.len(), 4);
+        assert_eq!(conditions.len(), 5);
         
         match &conditions[0] {
             WaitCondition::ElementVisible(selector) => assert_eq!(selector, ".button"),
@@ -795,6 +10472,14 @@ mod tests {
             WaitCondition::UrlContains(url_part) => assert_eq!(url_part, "success"),
             _ => panic!("Expected UrlContains condition"),
         }
+
+        match &conditions[4] {
+            WaitCondition::ElementVisibleRatio(selector, min_ratio) => {
+                assert_eq!(selector, "img.lazy");
+                assert_eq!(*min_ratio, 0.5);
+            }
+            _ => panic!("Expected ElementVisibleRatio condition"),
+        }
     }
 
     #[test]
@@ -1020,4 +10705,135 @@ mod tests {
             assert!(!domain.ends_with("."));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_url_pattern_matching() {
+        assert!(url_pattern_matches("*", "https://example.com/api"));
+        assert!(url_pattern_matches("*/api/users*", "https://example.com/api/users?page=2"));
+        assert!(url_pattern_matches("https://example.com/api/v?/items", "https://example.com/api/v1/items"));
+        assert!(!url_pattern_matches("*/api/users", "https://example.com/api/orders"));
+        assert!(!url_pattern_matches("https://example.com/", "https://example.com/other"));
+    }
+
+    #[test]
+    fn test_paused_request_reply() {
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        let mocks = vec![
+            MockResponse { url_pattern: "*/api/*".to_string(), status: 500, headers: HashMap::new(), body: String::new() },
+            MockResponse { url_pattern: "*/api/users*".to_string(), status: 201, headers, body: r#"[{"id":1}]"#.to_string() },
+        ];
+
+        // The mock registered last wins
+        let paused = json!({ "requestId": "interception-1", "request": { "url": "https://example.com/api/users?page=2" } });
+        let (command, reply) = paused_request_reply(&mocks, &paused).unwrap();
+        assert_eq!(command, "Fetch.fulfillRequest");
+        assert_eq!(reply["requestId"], "interception-1");
+        assert_eq!(reply["responseCode"], 201);
+        assert_eq!(reply["responseHeaders"], json!([{ "name": "Content-Type", "value": "application/json" }]));
+        assert_eq!(BASE64.decode(reply["body"].as_str().unwrap()).unwrap(), br#"[{"id":1}]"#);
+
+        let paused = json!({ "requestId": "interception-2", "request": { "url": "https://example.com/app.js" } });
+        let (command, reply) = paused_request_reply(&mocks, &paused).unwrap();
+        assert_eq!((command, reply), ("Fetch.continueRequest", json!({ "requestId": "interception-2" })));
+
+        assert!(paused_request_reply(&mocks, &json!({ "request": { "url": "https://example.com/" } })).is_err());
+    }
+
+    #[test]
+    fn test_mock_fixture_parsing() {
+        let fixture = r#"{
+            "*/api/users": { "status": 201, "headers": { "Content-Type": "application/json" }, "body": [{"id": 1}] },
+            "*/api/ping": { "body": "pong" }
+        }"#;
+
+        let parsed: HashMap<String, MockFixtureEntry> = serde_json::from_str(fixture).unwrap();
+        assert_eq!(parsed["*/api/users"].status, 201);
+        assert_eq!(parsed["*/api/users"].headers["Content-Type"], "application/json");
+        assert!(parsed["*/api/users"].body.is_array());
+        assert_eq!(parsed["*/api/ping"].status, 200);
+        assert_eq!(parsed["*/api/ping"].body, json!("pong"));
+    }
+
+    #[test]
+    fn test_parse_popup_targets() {
+        let result = json!({
+            "targetInfos": [
+                { "targetId": "MAIN", "type": "page", "url": "https://shop.example/checkout", "title": "Checkout" },
+                { "targetId": "POPUP", "type": "page", "url": "https://accounts.google.com/o/oauth2/auth", "title": "Sign in", "openerId": "MAIN" },
+                { "targetId": "NOOPENER", "type": "page", "url": "https://pay.example/confirm", "title": "Pay" },
+                { "targetId": "WORKER", "type": "service_worker", "url": "https://shop.example/sw.js", "title": "", "openerId": "MAIN" }
+            ]
+        });
+
+        let popups = parse_popup_targets(&result, Some("MAIN"));
+        let ids: Vec<&str> = popups.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["POPUP", "NOOPENER"]);
+        assert_eq!(popups[0].opener_id.as_deref(), Some("MAIN"));
+        assert_eq!(popups[1].opener_id, None);
+        assert!(url_pattern_matches("*accounts.google.com*", &popups[0].url));
+
+        // Without a main tab every page counts, as used for the snapshot of open targets
+        assert_eq!(parse_popup_targets(&result, None).len(), 3);
+    }
+
+    #[test]
+    fn test_lookup_auth_credentials() {
+        let credentials = |user: &str| HttpAuthCredentials { username: user.to_string(), password: "secret".to_string() };
+        let mut map = HashMap::new();
+        map.insert(("intranet.example".to_string(), "*".to_string()), credentials("intranet"));
+        map.insert(("::1".to_string(), "*".to_string()), credentials("loopback"));
+        map.insert(("*".to_string(), "*".to_string()), credentials("fallback"));
+
+        assert_eq!(origin_host("https://intranet.example:8443"), "intranet.example");
+        assert_eq!(origin_host("http://[::1]:8080"), "::1");
+        assert_eq!(lookup_auth_credentials(&map, "intranet.example", "Staff Only").unwrap().username, "intranet");
+        assert_eq!(lookup_auth_credentials(&map, &origin_host("http://[::1]:8080"), "").unwrap().username, "loopback");
+        assert_eq!(lookup_auth_credentials(&map, "other.example", "").unwrap().username, "fallback");
+
+        map.remove(&("*".to_string(), "*".to_string()));
+        assert!(lookup_auth_credentials(&map, "other.example", "").is_none());
+    }
+
+    #[test]
+    fn test_auth_challenge_reply() {
+        let mut map = HashMap::new();
+        map.insert(
+            ("intranet.example".to_string(), "*".to_string()),
+            HttpAuthCredentials { username: "staff".to_string(), password: "secret".to_string() },
+        );
+
+        let reply = auth_challenge_reply(&map, &json!({
+            "requestId": "interception-1",
+            "authChallenge": { "source": "Server", "origin": "https://intranet.example", "scheme": "basic", "realm": "Staff Only" }
+        })).unwrap();
+        assert_eq!(reply, json!({
+            "requestId": "interception-1",
+            "authChallengeResponse": { "response": "ProvideCredentials", "username": "staff", "password": "secret" }
+        }));
+
+        let reply = auth_challenge_reply(&map, &json!({
+            "requestId": "interception-2",
+            "authChallenge": { "source": "Server", "origin": "https://other.example", "scheme": "basic", "realm": "" }
+        })).unwrap();
+        assert_eq!(reply["authChallengeResponse"], json!({ "response": "CancelAuth" }));
+    }
+
+    #[test]
+    fn test_record_title_change() {
+        let history = Mutex::new(Vec::new());
+
+        let recorded = record_title_change("__chromeMcpTitle_spa", &history, &json!({
+            "name": "__chromeMcpTitle_spa",
+            "payload": r#"{"old_title":"Inbox","new_title":"Inbox (3)","timestamp_ms":1700000000000}"#,
+            "executionContextId": 1
+        })).unwrap();
+        assert!(recorded);
+        assert!(!record_title_change("__chromeMcpTitle_spa", &history, &json!({ "name": "otherBinding", "payload": "x" })).unwrap());
+        assert!(record_title_change("__chromeMcpTitle_spa", &history, &json!({ "name": "__chromeMcpTitle_spa", "payload": "oops" })).is_err());
+
+        let history = history.into_inner().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!((history[0].old_title.as_str(), history[0].new_title.as_str()), ("Inbox", "Inbox (3)"));
+    }
+}
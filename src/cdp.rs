@@ -1,12 +1,22 @@
 use crate::error::{ChromeMcpError, Result};
+use crate::compatibility::{check_compatibility, required_version, ChromeVersion};
+use crate::cdp_types::{
+    CaptureScreenshotRequest, CaptureScreenshotResponse, EvaluateRequest, EvaluateResponse, GetChildAXNodesResponse, GetFullAXTreeResponse,
+    GetDocumentResponse, GetRootAXNodeResponse, NavigateRequest, NavigateResponse, QuerySelectorAllRequest,
+    QuerySelectorAllResponse, SetCookieRequest,
+};
+use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
-use tokio::time::{timeout, Duration};
+use tokio::time::{timeout, Duration, Instant};
 use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 use tracing::{debug, error, trace, warn};
 use url::Url;
@@ -20,6 +30,9 @@ pub struct CdpMessage {
     pub params: Option<Value>,
     pub result: Option<Value>,
     pub error: Option<CdpError>,
+    /// Session of a target attached in flattened mode, for its commands and events
+    #[serde(rename = "sessionId", default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +42,64 @@ pub struct CdpError {
     pub data: Option<Value>,
 }
 
+/// Traffic and latency of the commands a `CdpClient` has sent, shared by all its clones
+///
+/// Byte counts are the sizes of the WebSocket frames sent and received, events included.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionMetrics {
+    pub commands_sent: u64,
+    /// Commands Chrome answered, with a result or a protocol error
+    pub commands_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub avg_round_trip_ms: f64,
+    pub max_round_trip_ms: f64,
+}
+
+impl ConnectionMetrics {
+    /// Count a command going out
+    fn record_sent(&mut self) {
+        self.commands_sent += 1;
+    }
+
+    /// Count an answer that took `round_trip_ms` to arrive
+    fn record_received(&mut self, round_trip_ms: f64) {
+        self.commands_received += 1;
+        self.avg_round_trip_ms += (round_trip_ms - self.avg_round_trip_ms) / self.commands_received as f64;
+        self.max_round_trip_ms = self.max_round_trip_ms.max(round_trip_ms);
+    }
+}
+
+/// Bytes a backend has put on and taken off the wire, shared by its clones
+#[derive(Debug, Clone, Default)]
+pub struct FrameBytes {
+    sent: Arc<AtomicU64>,
+    received: Arc<AtomicU64>,
+}
+
+impl FrameBytes {
+    /// Count a frame of `bytes` bytes going out
+    pub fn record_sent(&self, bytes: usize) {
+        self.sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Count a frame of `bytes` bytes coming in
+    pub fn record_received(&self, bytes: usize) {
+        self.received.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Bytes sent and received so far
+    pub fn totals(&self) -> (u64, u64) {
+        (self.sent.load(Ordering::Relaxed), self.received.load(Ordering::Relaxed))
+    }
+
+    /// Start counting from zero again
+    pub fn reset(&self) {
+        self.sent.store(0, Ordering::Relaxed);
+        self.received.store(0, Ordering::Relaxed);
+    }
+}
+
 /// Chrome tab information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TabInfo {
@@ -38,26 +109,237 @@ pub struct TabInfo {
     pub description: String,
     #[serde(rename = "webSocketDebuggerUrl")]
     pub websocket_debugger_url: Option<String>,
+    /// Target type: `page`, `iframe`, `service_worker`, ...
+    #[serde(rename = "type", default)]
+    pub target_type: Option<String>,
 }
 
-/// CDP client for communicating with Chrome DevTools
-pub struct CdpClient {
-    websocket: Option<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+/// Transport that delivers CDP commands to a browser target
+///
+/// `CdpClient` builds every higher-level operation on top of these methods, so swapping the
+/// backend (e.g. for `testing::MockCdpClient`) changes where commands go without touching callers.
+pub trait CdpClientBackend: Clone + Send + Sync + 'static {
+    /// Send a command to the connected target and return its `result` object
+    fn send_command_inner(&mut self, method: &str, params: Option<Value>) -> impl Future<Output = Result<Value>> + Send;
+
+    /// List available tabs
+    fn list_tabs(&self) -> impl Future<Output = Result<Vec<TabInfo>>> + Send;
+
+    /// Create a new tab
+    fn create_tab(&self, url: Option<&str>) -> impl Future<Output = Result<TabInfo>> + Send;
+
+    /// Close a tab
+    fn close_tab(&self, tab_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Connect to a specific tab, making it the target of subsequent commands
+    fn connect_to_tab(&mut self, tab_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// ID of the connected tab
+    fn current_tab_id(&self) -> Option<&str>;
+
+    /// Send a command to a target attached in flattened mode (`Target.attachToTarget` with
+    /// `flatten`), identified by its `sessionId`, over the connection of the current tab
+    fn send_session_command_inner(&mut self, session_id: &str, method: &str, params: Option<Value>) -> impl Future<Output = Result<Value>> + Send;
+
+    /// Subscribers to the events of the connected target
+    fn events(&self) -> &EventSubscribers;
+
+    /// Bytes exchanged with the browser
+    fn frame_bytes(&self) -> &FrameBytes;
+}
+
+/// Channels of the subscribers to each event method, with their subscription IDs
+type Channels = Arc<Mutex<HashMap<String, Vec<(u64, mpsc::UnboundedSender<Value>)>>>>;
+
+/// Channels receiving the `params` of CDP events, by event method (e.g. `Page.loadEventFired`)
+#[derive(Debug, Clone, Default)]
+pub struct EventSubscribers {
+    channels: Channels,
+    next_id: Arc<AtomicU64>,
+}
+
+impl EventSubscribers {
+    /// Receive every `method` event from now on, until the subscription is dropped
+    pub fn subscribe(&self, method: &str) -> Subscription {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.channels.lock().unwrap().entry(method.to_string()).or_default().push((id, tx));
+        Subscription {
+            method: method.to_string(),
+            id,
+            receiver: rx,
+            channels: Arc::clone(&self.channels),
+        }
+    }
+
+    /// Send an event to the subscribers of its method, forgetting those that stopped listening
+    pub fn dispatch(&self, method: &str, params: &Value) {
+        let mut channels = self.channels.lock().unwrap();
+        if let Some(subscribers) = channels.get_mut(method) {
+            subscribers.retain(|(_, tx)| tx.send(params.clone()).is_ok());
+            if subscribers.is_empty() {
+                channels.remove(method);
+            }
+        }
+    }
+}
+
+/// One subscriber's feed of the `params` of an event method, from `EventSubscribers::subscribe`
+///
+/// Dropping it ends this subscription only; other subscribers of the method keep theirs.
+#[derive(Debug)]
+pub struct Subscription {
+    method: String,
+    id: u64,
+    receiver: mpsc::UnboundedReceiver<Value>,
+    channels: Channels,
+}
+
+impl Subscription {
+    /// Next event, or `None` once the connection's subscribers were replaced
+    pub async fn recv(&mut self) -> Option<Value> {
+        self.receiver.recv().await
+    }
+
+    /// Next event if one already arrived
+    pub fn try_recv(&mut self) -> std::result::Result<Value, mpsc::error::TryRecvError> {
+        self.receiver.try_recv()
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let mut channels = self.channels.lock().unwrap();
+        if let Some(subscribers) = channels.get_mut(&self.method) {
+            subscribers.retain(|(id, _)| *id != self.id);
+            if subscribers.is_empty() {
+                channels.remove(&self.method);
+            }
+        }
+    }
+}
+
+/// How long a command may wait for its response
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Responses still awaited on a connection, by message ID
+type PendingRequests = Arc<Mutex<HashMap<u64, mpsc::UnboundedSender<CdpMessage>>>>;
+
+/// Open WebSocket to one target, served by a writer and a reader task
+#[derive(Clone)]
+struct Connection {
+    ws_url: String,
+    /// Messages for the writer task to send
+    outgoing: mpsc::UnboundedSender<Message>,
+    pending_requests: PendingRequests,
+    /// Cleared once the socket is closed, so the next command reconnects
+    open: Arc<AtomicBool>,
+}
+
+/// CDP domains enabled on every connection
+const AUTOMATION_DOMAINS: [&str; 6] = ["Runtime", "Page", "DOM", "Input", "Network", "Accessibility"];
+
+/// Command whose effect lasts only as long as the connection it was sent on
+#[derive(Debug, Clone)]
+struct SessionCommand {
+    method: String,
+    params: Option<Value>,
+    /// For `Page.addScriptToEvaluateOnNewDocument`: the identifier callers know the script by,
+    /// and the one Chrome gave it on the current connection
+    identifier: Option<(String, String)>,
+}
+
+/// Domains, bindings and new-document scripts set up on a connection, to set them up again
+/// when it has to be re-established
+#[derive(Debug, Default)]
+struct SessionState {
+    commands: Vec<SessionCommand>,
+}
+
+impl SessionState {
+    /// Remember what a successful `method` command changed
+    fn record(&mut self, method: &str, params: &Option<Value>, result: &Value) {
+        let param = |name: &str| params.as_ref().and_then(|p| p.get(name)).cloned();
+        let command = SessionCommand { method: method.to_string(), params: params.clone(), identifier: None };
+        match method {
+            "Runtime.addBinding" | "Runtime.removeBinding" => {
+                let name = param("name");
+                self.commands.retain(|c| c.method != "Runtime.addBinding" || c.params.as_ref().and_then(|p| p.get("name")) != name.as_ref());
+                if method == "Runtime.addBinding" {
+                    self.commands.push(command);
+                }
+            }
+            "Page.addScriptToEvaluateOnNewDocument" => {
+                if let Some(identifier) = result.get("identifier").and_then(|i| i.as_str()) {
+                    let identifier = Some((identifier.to_string(), identifier.to_string()));
+                    self.commands.push(SessionCommand { identifier, ..command });
+                }
+            }
+            "Page.removeScriptToEvaluateOnNewDocument" => {
+                // `params` were translated, so they hold the current identifier
+                let identifier = param("identifier");
+                self.commands.retain(|c| c.identifier.as_ref().map(|(_, current)| json!(current)) != identifier);
+            }
+            _ => match method.split_once('.') {
+                Some((domain, "enable")) if !AUTOMATION_DOMAINS.contains(&domain) => {
+                    self.commands.retain(|c| c.method != method);
+                    self.commands.push(command);
+                }
+                Some((domain, "disable")) => {
+                    let enable = format!("{}.enable", domain);
+                    self.commands.retain(|c| c.method != enable);
+                }
+                _ => {}
+            },
+        }
+    }
+
+    /// `params` of `method` with the script identifier callers know replaced by the current one
+    fn translate(&self, method: &str, params: Option<Value>) -> Option<Value> {
+        let mut params = params;
+        if method != "Page.removeScriptToEvaluateOnNewDocument" {
+            return params;
+        }
+        if let Some(identifier) = params.as_mut().and_then(|p| p.get_mut("identifier")) {
+            let current = self.commands.iter().filter_map(|c| c.identifier.as_ref()).find(|(original, _)| identifier == original.as_str());
+            if let Some((_, current)) = current {
+                *identifier = json!(current);
+            }
+        }
+        params
+    }
+}
+
+/// WebSocket connection to Chrome's DevTools endpoint
+///
+/// One connection per tab is kept open and shared by every command: responses are routed
+/// back to their callers by message ID. Clones share the connection until they connect to
+/// another tab. If the socket closes, the next command reconnects and sets up again the
+/// domains, bindings and new-document scripts the old connection had.
+pub struct WebSocketBackend {
+    connection: Arc<Mutex<Option<Connection>>>,
+    /// Held while re-establishing the connection, so concurrent commands reconnect only once
+    reconnecting: Arc<tokio::sync::Mutex<()>>,
+    session_state: Arc<Mutex<SessionState>>,
+    events: EventSubscribers,
+    frame_bytes: FrameBytes,
     message_id: Arc<Mutex<u64>>,
-    pending_requests: Arc<Mutex<HashMap<u64, mpsc::UnboundedSender<CdpMessage>>>>,
-    event_sender: Option<mpsc::UnboundedSender<CdpMessage>>,
     chrome_host: String,
     chrome_port: u16,
     tab_id: Option<String>,
 }
 
-impl Clone for CdpClient {
+impl Clone for WebSocketBackend {
     fn clone(&self) -> Self {
         Self {
-            websocket: None, // WebSocket connections aren't cloneable, create new ones as needed
+            connection: Arc::clone(&self.connection),
+            reconnecting: Arc::clone(&self.reconnecting),
+            session_state: Arc::clone(&self.session_state),
+            events: self.events.clone(),
+            frame_bytes: self.frame_bytes.clone(),
             message_id: Arc::clone(&self.message_id),
-            pending_requests: Arc::clone(&self.pending_requests),
-            event_sender: None,
             chrome_host: self.chrome_host.clone(),
             chrome_port: self.chrome_port,
             tab_id: self.tab_id.clone(),
@@ -65,21 +347,236 @@ impl Clone for CdpClient {
     }
 }
 
-impl CdpClient {
+impl WebSocketBackend {
     pub fn new(host: &str, port: u16) -> Self {
         Self {
-            websocket: None,
+            connection: Arc::new(Mutex::new(None)),
+            reconnecting: Arc::new(tokio::sync::Mutex::new(())),
+            session_state: Arc::new(Mutex::new(SessionState::default())),
+            events: EventSubscribers::default(),
+            frame_bytes: FrameBytes::default(),
             message_id: Arc::new(Mutex::new(1)),
-            pending_requests: Arc::new(Mutex::new(HashMap::new())),
-            event_sender: None,
             chrome_host: host.to_string(),
             chrome_port: port,
             tab_id: None,
         }
     }
 
+    /// Enable CDP domains required for automation
+    async fn enable_domains(&self, connection: &Connection) -> Result<()> {
+        for domain in AUTOMATION_DOMAINS {
+            self.request(connection, None, &format!("{}.enable", domain), None).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Open a WebSocket to `ws_url`, start its reader and writer tasks and enable the CDP
+    /// domains on it, replacing the connection shared with this backend's clones
+    async fn open_connection(&self, ws_url: &str) -> Result<Connection> {
+        debug!("Connecting to tab WebSocket: {}", ws_url);
+
+        let url = Url::parse(ws_url)
+            .map_err(|e| ChromeMcpError::cdp_connection(format!("Invalid WebSocket URL: {}", e)))?;
+
+        let (ws_stream, _) = connect_async(url.as_str())
+            .await
+            .map_err(|e| ChromeMcpError::cdp_connection(format!("WebSocket connection failed: {}", e)))?;
+
+        let (sink, stream) = ws_stream.split();
+        let (outgoing_tx, outgoing_rx) = mpsc::unbounded_channel();
+        let connection = Connection {
+            ws_url: ws_url.to_string(),
+            outgoing: outgoing_tx,
+            pending_requests: Arc::new(Mutex::new(HashMap::new())),
+            open: Arc::new(AtomicBool::new(true)),
+        };
+        tokio::spawn(write_messages(sink, outgoing_rx, self.frame_bytes.clone(), Arc::clone(&connection.open)));
+        tokio::spawn(read_messages(
+            stream,
+            Arc::clone(&connection.pending_requests),
+            self.events.clone(),
+            self.frame_bytes.clone(),
+            Arc::clone(&connection.open),
+        ));
+
+        *self.connection.lock().unwrap() = Some(connection.clone());
+        self.enable_domains(&connection).await?;
+        Ok(connection)
+    }
+
+    /// The shared connection, re-established (with its domains enabled again) if it was closed
+    async fn live_connection(&self) -> Result<Connection> {
+        if let Some(connection) = self.open_connection_if_any()? {
+            return Ok(connection);
+        }
+
+        let _reconnecting = self.reconnecting.lock().await;
+        // Another command may have reconnected while this one waited
+        if let Some(connection) = self.open_connection_if_any()? {
+            return Ok(connection);
+        }
+        let ws_url = self.connection.lock().unwrap().as_ref().map(|c| c.ws_url.clone()).unwrap_or_default();
+        warn!("WebSocket connection was closed, reconnecting to {}", ws_url);
+        let connection = self.open_connection(&ws_url).await?;
+        self.restore_session_state(&connection).await?;
+        Ok(connection)
+    }
+
+    /// The shared connection if it is open, `None` if it was closed
+    fn open_connection_if_any(&self) -> Result<Option<Connection>> {
+        match self.connection.lock().unwrap().as_ref() {
+            Some(connection) if connection.open.load(Ordering::SeqCst) => Ok(Some(connection.clone())),
+            Some(_) => Ok(None),
+            None => Err(ChromeMcpError::cdp_connection("Not connected to a tab".to_string())),
+        }
+    }
+
+    /// Replay the domains, bindings and new-document scripts of the previous connection on
+    /// `connection`; scripts keep the identifiers callers know them by
+    async fn restore_session_state(&self, connection: &Connection) -> Result<()> {
+        let commands = self.session_state.lock().unwrap().commands.clone();
+        let mut restored = Vec::with_capacity(commands.len());
+        for mut command in commands {
+            let result = self.request(connection, None, &command.method, command.params.clone()).await.map_err(|e| {
+                ChromeMcpError::cdp_connection(format!("Reconnected, but could not restore {}: {}", command.method, e))
+            })?;
+            if let (Some((_, current)), Some(identifier)) = (command.identifier.as_mut(), result.get("identifier").and_then(|i| i.as_str())) {
+                *current = identifier.to_string();
+            }
+            restored.push(command);
+        }
+        self.session_state.lock().unwrap().commands = restored;
+        Ok(())
+    }
+
+    /// Send a command over `connection`, to the attached target of `session_id` if given, and
+    /// wait for its response
+    async fn request(&self, connection: &Connection, session_id: Option<&str>, method: &str, params: Option<Value>) -> Result<Value> {
+        let id = {
+            let mut counter = self.message_id.lock().unwrap();
+            let current = *counter;
+            *counter += 1;
+            current
+        };
+
+        let message = CdpMessage {
+            id: Some(id),
+            method: Some(method.to_string()),
+            params,
+            result: None,
+            error: None,
+            session_id: session_id.map(str::to_string),
+        };
+
+        let (response_tx, mut response_rx) = mpsc::unbounded_channel();
+        connection.pending_requests.lock().unwrap().insert(id, response_tx);
+
+        // Send the message
+        let json_msg = serde_json::to_string(&message)?;
+        trace!("Sending CDP message: {}", json_msg);
+        if connection.outgoing.send(Message::Text(json_msg)).is_err() {
+            connection.pending_requests.lock().unwrap().remove(&id);
+            return Err(ChromeMcpError::cdp_connection("WebSocket connection closed".to_string()));
+        }
+
+        // Wait for response with timeout
+        let response = match timeout(COMMAND_TIMEOUT, response_rx.recv()).await {
+            Ok(response) => response.ok_or_else(|| ChromeMcpError::cdp_connection("WebSocket connection closed".to_string()))?,
+            Err(_) => {
+                connection.pending_requests.lock().unwrap().remove(&id);
+                return Err(ChromeMcpError::Timeout { timeout: COMMAND_TIMEOUT.as_millis() as u64 });
+            }
+        };
+
+        if let Some(error) = response.error {
+            return Err(ChromeMcpError::cdp_protocol(format!(
+                "CDP error {}: {}", error.code, error.message
+            )));
+        }
+
+        Ok(response.result.unwrap_or(Value::Null))
+    }
+}
+
+/// Write the messages queued for a connection to its socket, closing the socket once every
+/// sender is gone
+async fn write_messages(
+    mut sink: SplitSink<WsStream, Message>,
+    mut outgoing: mpsc::UnboundedReceiver<Message>,
+    frame_bytes: FrameBytes,
+    open: Arc<AtomicBool>,
+) {
+    while let Some(message) = outgoing.recv().await {
+        frame_bytes.record_sent(message.len());
+        if let Err(e) = sink.send(message).await {
+            error!("WebSocket write failed: {}", e);
+            open.store(false, Ordering::SeqCst);
+            return;
+        }
+    }
+    let _ = sink.close().await;
+}
+
+/// Route the responses arriving on a connection to the commands waiting for them, and its
+/// events to their subscribers, until the socket closes; commands still waiting then fail
+/// instead of timing out
+async fn read_messages(
+    mut stream: SplitStream<WsStream>,
+    pending_requests: PendingRequests,
+    events: EventSubscribers,
+    frame_bytes: FrameBytes,
+    open: Arc<AtomicBool>,
+) {
+    while let Some(msg) = stream.next().await {
+        if let Ok(message) = &msg {
+            frame_bytes.record_received(message.len());
+        }
+        match msg {
+            Ok(Message::Text(text)) => {
+                trace!("Received CDP message: {}", text);
+                match serde_json::from_str::<CdpMessage>(&text) {
+                    Ok(cdp_msg) => {
+                        if let Some(id) = cdp_msg.id {
+                            // This is a response to a request
+                            if let Some(sender) = pending_requests.lock().unwrap().remove(&id) {
+                                if sender.send(cdp_msg).is_err() {
+                                    warn!("Failed to send response to waiting request {}", id);
+                                }
+                            }
+                        } else if let Some(session_id) = &cdp_msg.session_id {
+                            // Events of attached targets are not the connected tab's
+                            trace!("CDP event of session {}: {:?}", session_id, cdp_msg.method);
+                        } else if let Some(method) = &cdp_msg.method {
+                            // This is an event
+                            trace!("CDP Event: {}", method);
+                            events.dispatch(method, cdp_msg.params.as_ref().unwrap_or(&Value::Null));
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to parse CDP message: {}", e);
+                    }
+                }
+            }
+            Ok(Message::Close(_)) => {
+                warn!("WebSocket connection closed");
+                break;
+            }
+            Err(e) => {
+                error!("WebSocket error: {}", e);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    open.store(false, Ordering::SeqCst);
+    pending_requests.lock().unwrap().clear();
+}
+
+impl CdpClientBackend for WebSocketBackend {
     /// List available tabs
-    pub async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
+    async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
         let url = format!("http://{}:{}/json", self.chrome_host, self.chrome_port);
         debug!("Fetching tabs from: {}", url);
 
@@ -96,7 +593,7 @@ impl CdpClient {
     }
 
     /// Create a new tab
-    pub async fn create_tab(&self, url: Option<&str>) -> Result<TabInfo> {
+    async fn create_tab(&self, url: Option<&str>) -> Result<TabInfo> {
         let mut endpoint = format!("http://{}:{}/json/new", self.chrome_host, self.chrome_port);
         if let Some(u) = url {
             endpoint.push_str(&format!("?{}", u));
@@ -115,7 +612,7 @@ impl CdpClient {
     }
 
     /// Close a tab
-    pub async fn close_tab(&self, tab_id: &str) -> Result<()> {
+    async fn close_tab(&self, tab_id: &str) -> Result<()> {
         let url = format!("http://{}:{}/json/close/{}", self.chrome_host, self.chrome_port, tab_id);
         
         let response = reqwest::get(&url)
@@ -130,7 +627,7 @@ impl CdpClient {
     }
 
     /// Connect to a specific tab
-    pub async fn connect_to_tab(&mut self, tab_id: &str) -> Result<()> {
+    async fn connect_to_tab(&mut self, tab_id: &str) -> Result<()> {
         let tabs = self.list_tabs().await?;
         let tab = tabs
             .iter()
@@ -142,174 +639,221 @@ impl CdpClient {
             .as_ref()
             .ok_or_else(|| ChromeMcpError::cdp_protocol("Tab has no WebSocket debugger URL".to_string()))?;
 
-        debug!("Connecting to tab WebSocket: {}", ws_url);
-        
-        let url = Url::parse(ws_url)
-            .map_err(|e| ChromeMcpError::cdp_connection(format!("Invalid WebSocket URL: {}", e)))?;
+        // Clones still connected to the previous tab keep their connection and subscribers;
+        // byte counts stay shared, they cover every tab
+        self.connection = Arc::new(Mutex::new(None));
+        self.reconnecting = Arc::new(tokio::sync::Mutex::new(()));
+        self.session_state = Arc::new(Mutex::new(SessionState::default()));
+        self.events = EventSubscribers::default();
+        self.tab_id = Some(tab_id.to_string());
+        self.open_connection(ws_url).await?;
 
-        let (ws_stream, _) = connect_async(url.as_str())
-            .await
-            .map_err(|e| ChromeMcpError::cdp_connection(format!("WebSocket connection failed: {}", e)))?;
+        Ok(())
+    }
 
-        self.websocket = Some(ws_stream);
-        self.tab_id = Some(tab_id.to_string());
+    async fn send_command_inner(&mut self, method: &str, params: Option<Value>) -> Result<Value> {
+        let connection = self.live_connection().await?;
+        let params = self.session_state.lock().unwrap().translate(method, params);
+        let result = self.request(&connection, None, method, params.clone()).await?;
+        self.session_state.lock().unwrap().record(method, &params, &result);
+        Ok(result)
+    }
 
-        // Start message handling loop
-        self.start_message_loop().await?;
+    async fn send_session_command_inner(&mut self, session_id: &str, method: &str, params: Option<Value>) -> Result<Value> {
+        let connection = self.live_connection().await?;
+        self.request(&connection, Some(session_id), method, params).await
+    }
 
-        // Enable necessary CDP domains
-        self.enable_domains().await?;
+    fn current_tab_id(&self) -> Option<&str> {
+        self.tab_id.as_deref()
+    }
 
-        Ok(())
+    fn events(&self) -> &EventSubscribers {
+        &self.events
     }
 
-    /// Enable CDP domains required for automation
-    async fn enable_domains(&mut self) -> Result<()> {
-        let domains = vec![
-            "Runtime",
-            "Page",
-            "DOM",
-            "Input",
-            "Network",
-            "Accessibility",
-        ];
+    fn frame_bytes(&self) -> &FrameBytes {
+        &self.frame_bytes
+    }
+}
 
-        for domain in domains {
-            self.send_command(&format!("{}.enable", domain), None).await?;
+/// Prefix of the protocol error returned for methods the connected Chrome does not implement
+const METHOD_NOT_FOUND: &str = "CDP error -32601";
+
+/// CDP client for communicating with Chrome DevTools
+#[derive(Clone)]
+pub struct CdpClient<B = WebSocketBackend> {
+    backend: B,
+    /// Fetched on the first protocol error, to explain failures caused by an old Chrome
+    chrome_version: Option<ChromeVersion>,
+    metrics: Arc<Mutex<ConnectionMetrics>>,
+}
+
+impl CdpClient {
+    pub fn new(host: &str, port: u16) -> Self {
+        Self::with_backend(WebSocketBackend::new(host, port))
+    }
+}
+
+impl<B: CdpClientBackend> CdpClient<B> {
+    /// Create a client that sends commands through the given backend
+    pub fn with_backend(backend: B) -> Self {
+        Self {
+            backend,
+            chrome_version: None,
+            metrics: Arc::new(Mutex::new(ConnectionMetrics::default())),
         }
+    }
 
-        Ok(())
+    /// List available tabs
+    pub async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
+        self.backend.list_tabs().await
     }
 
-    /// Start the message handling loop
-    async fn start_message_loop(&mut self) -> Result<()> {
-        let (event_tx, _event_rx) = mpsc::unbounded_channel();
-        self.event_sender = Some(event_tx);
-
-        if let Some(ws) = self.websocket.take() {
-            let (_sink, mut stream) = ws.split();
-            let pending_requests = Arc::clone(&self.pending_requests);
-
-            // Spawn task to handle incoming messages
-            tokio::spawn(async move {
-                while let Some(msg) = stream.next().await {
-                    match msg {
-                        Ok(Message::Text(text)) => {
-                            trace!("Received CDP message: {}", text);
-                            match serde_json::from_str::<CdpMessage>(&text) {
-                                Ok(cdp_msg) => {
-                                    if let Some(id) = cdp_msg.id {
-                                        // This is a response to a request
-                                        if let Some(sender) = pending_requests.lock().unwrap().remove(&id) {
-                                            if sender.send(cdp_msg).is_err() {
-                                                warn!("Failed to send response to waiting request {}", id);
-                                            }
-                                        }
-                                    } else {
-                                        // This is an event
-                                        // For now, we'll just log events
-                                        debug!("CDP Event: {:?}", cdp_msg);
-                                    }
-                                }
-                                Err(e) => {
-                                    error!("Failed to parse CDP message: {}", e);
-                                }
-                            }
-                        }
-                        Ok(Message::Close(_)) => {
-                            warn!("WebSocket connection closed");
-                            break;
-                        }
-                        Err(e) => {
-                            error!("WebSocket error: {}", e);
-                            break;
-                        }
-                        _ => {}
-                    }
-                }
-            });
+    /// Create a new tab
+    pub async fn create_tab(&self, url: Option<&str>) -> Result<TabInfo> {
+        self.backend.create_tab(url).await
+    }
 
-            // Store the sink for sending messages
-            // Note: In a real implementation, we'd need to store this properly
-            // For now, we'll create a new connection when needed
-        }
+    /// Close a tab
+    pub async fn close_tab(&self, tab_id: &str) -> Result<()> {
+        self.backend.close_tab(tab_id).await
+    }
 
-        Ok(())
+    /// Open a dedicated session on the current tab, see `CdpSession`
+    pub async fn open_session(&self) -> Result<CdpSession> {
+        let tab_id = self
+            .current_tab_id()
+            .ok_or_else(|| ChromeMcpError::invalid_operation("Not connected to a tab"))?;
+
+        let tabs = self.list_tabs().await?;
+        let ws_url = tabs
+            .iter()
+            .find(|t| t.id == tab_id)
+            .and_then(|t| t.websocket_debugger_url.as_deref())
+            .ok_or_else(|| ChromeMcpError::tab_not_found(format!("Tab {} has no WebSocket debugger URL", tab_id)))?;
+
+        CdpSession::connect(ws_url).await
     }
 
-    /// Send a CDP command and wait for response
-    pub async fn send_command(&mut self, method: &str, params: Option<Value>) -> Result<Value> {
-        let id = {
-            let mut counter = self.message_id.lock().unwrap();
-            let current = *counter;
-            *counter += 1;
-            current
-        };
+    /// Connect to a specific tab
+    pub async fn connect_to_tab(&mut self, tab_id: &str) -> Result<()> {
+        self.backend.connect_to_tab(tab_id).await
+    }
 
-        let message = CdpMessage {
-            id: Some(id),
-            method: Some(method.to_string()),
-            params,
-            result: None,
-            error: None,
-        };
+    /// Attach to a non-page target such as an extension's DevTools panel, returning the session ID
+    ///
+    /// The target is attached with `Target.attachToTarget` (flattened), which fails for unknown
+    /// or detached targets; `send_session_command` then reaches it over the current tab's
+    /// connection, which stays the target of every other command.
+    pub async fn attach_to_target(&mut self, target_id: &str) -> Result<String> {
+        let result = self.send_command("Target.attachToTarget", Some(json!({
+            "targetId": target_id,
+            "flatten": true
+        }))).await?;
+        let session_id = result
+            .get("sessionId")
+            .and_then(|s| s.as_str())
+            .ok_or_else(|| ChromeMcpError::cdp_protocol(format!("No session ID attaching to target {}", target_id)))?
+            .to_string();
+        Ok(session_id)
+    }
 
-        let (response_tx, mut response_rx) = mpsc::unbounded_channel();
-        self.pending_requests.lock().unwrap().insert(id, response_tx);
+    /// Send a command to a target attached with `attach_to_target`
+    pub async fn send_session_command(&mut self, session_id: &str, method: &str, params: Option<Value>) -> Result<Value> {
+        self.metrics.lock().unwrap().record_sent();
+        let started = Instant::now();
 
-        // Send the message
-        self.send_message(message).await?;
+        let result = self.backend.send_session_command_inner(session_id, method, params).await;
+        if matches!(result, Ok(_) | Err(ChromeMcpError::CdpProtocol(_))) {
+            self.metrics.lock().unwrap().record_received(started.elapsed().as_secs_f64() * 1000.0);
+        }
+        result.map_err(|e| e.in_frame("cdp", "send_session_command").with_context("method", method))
+    }
 
-        // Wait for response with timeout
-        let response = timeout(Duration::from_secs(30), response_rx.recv())
+    /// Evaluate JavaScript in a target attached with `attach_to_target`, in its execution
+    /// context `context_id` or else the default one, returning the remote object as JSON
+    pub async fn evaluate_js_in_session(&mut self, session_id: &str, expression: &str, context_id: Option<u64>) -> Result<Value> {
+        let mut request = EvaluateRequest::new(expression);
+        request.context_id = context_id;
+        let request = serde_json::to_value(request)?;
+        let result = self.send_session_command(session_id, "Runtime.evaluate", Some(request)).await?;
+        let response: EvaluateResponse = serde_json::from_value(result)
+            .map_err(|e| ChromeMcpError::cdp_protocol(format!("Unexpected Runtime.evaluate result: {}", e)))?;
+        evaluation_result(response)
+    }
+
+    /// Receive the `params` of every `method` event (e.g. `Network.requestWillBeSent`) from now
+    /// on; dropping the subscription ends it
+    pub fn subscribe(&mut self, method: &str) -> Subscription {
+        self.backend.events().subscribe(method)
+    }
+
+    /// Wait for the next `method` event and return its `params`
+    #[cfg(test)]
+    pub async fn wait_for_event(&mut self, method: &str, timeout_ms: u64) -> Result<Value> {
+        let mut events = self.subscribe(method);
+        timeout(Duration::from_millis(timeout_ms), events.recv())
             .await
-            .map_err(|_| ChromeMcpError::Timeout { timeout: 30000 })?
-            .ok_or_else(|| ChromeMcpError::cdp_protocol("Response channel closed".to_string()))?;
+            .map_err(|_| ChromeMcpError::Timeout { timeout: timeout_ms })?
+            .ok_or_else(|| ChromeMcpError::cdp_connection(format!("Subscription to {} ended", method)))
+    }
 
-        if let Some(error) = response.error {
-            return Err(ChromeMcpError::cdp_protocol(format!(
-                "CDP error {}: {}", error.code, error.message
-            )));
+    /// Send a CDP command and wait for response
+    pub async fn send_command(&mut self, method: &str, params: Option<Value>) -> Result<Value> {
+        self.metrics.lock().unwrap().record_sent();
+        let started = Instant::now();
+
+        let result = self.backend.send_command_inner(method, params).await;
+        let round_trip_ms = started.elapsed().as_secs_f64() * 1000.0;
+        let error = match result {
+            Ok(result) => {
+                self.metrics.lock().unwrap().record_received(round_trip_ms);
+                return Ok(result);
+            }
+            Err(error) => error,
+        };
+        if matches!(error, ChromeMcpError::CdpProtocol(_)) {
+            self.metrics.lock().unwrap().record_received(round_trip_ms);
         }
 
-        Ok(response.result.unwrap_or(Value::Null))
+        // Chrome answers methods it does not implement with "method not found"
+        let method_not_found = matches!(&error, ChromeMcpError::CdpProtocol(message) if message.starts_with(METHOD_NOT_FOUND));
+        let error = match required_version(method) {
+            Some(_) if method_not_found => match self.version().await {
+                Ok(version) => check_compatibility(error, method, &version),
+                Err(_) => error,
+            },
+            _ => error,
+        };
+        Err(error.in_frame("cdp", "send_command").with_context("method", method))
     }
 
-    /// Send a message to Chrome
-    async fn send_message(&mut self, message: CdpMessage) -> Result<()> {
-        // In a real implementation, we'd need to properly manage the WebSocket connection
-        // For now, this is a simplified version
-        
-        // Create a new connection for each message (not efficient, but works for demo)
-        if let Some(tab_id) = &self.tab_id {
-            let tabs = self.list_tabs().await?;
-            let tab = tabs
-                .iter()
-                .find(|t| t.id == *tab_id)
-                .ok_or_else(|| ChromeMcpError::tab_not_found(format!("Tab {} not found", tab_id)))?;
-
-            if let Some(ws_url) = &tab.websocket_debugger_url {
-                let url = Url::parse(ws_url)?;
-                let (mut ws_stream, _) = connect_async(url.as_str()).await?;
-
-                let json_msg = serde_json::to_string(&message)?;
-                trace!("Sending CDP message: {}", json_msg);
-                
-                ws_stream.send(Message::Text(json_msg)).await?;
-                
-                // Read the response
-                if let Some(msg) = ws_stream.next().await {
-                    if let Message::Text(text) = msg? {
-                        let response: CdpMessage = serde_json::from_str(&text)?;
-                        if let Some(sender) = self.pending_requests.lock().unwrap().remove(&message.id.unwrap_or(0)) {
-                            let _ = sender.send(response);
-                        }
-                    }
-                }
-            }
+    /// Send a CDP command with typed parameters and parse its result
+    pub async fn send_typed<P: Serialize, R: DeserializeOwned>(&mut self, method: &str, params: Option<&P>) -> Result<R> {
+        let params = params.map(serde_json::to_value).transpose()?;
+        let result = self.send_command(method, params).await?;
+
+        serde_json::from_value(result).map_err(|e| {
+            ChromeMcpError::cdp_protocol(format!("Unexpected {} result: {}", method, e))
+                .in_frame("cdp", "send_typed")
+                .with_context("method", method)
+        })
+    }
+
+    /// Version of the connected Chrome, queried once with `Browser.getVersion`
+    pub async fn version(&mut self) -> Result<ChromeVersion> {
+        if let Some(version) = &self.chrome_version {
+            return Ok(version.clone());
         }
 
-        Ok(())
+        let result = self.backend.send_command_inner("Browser.getVersion", None).await?;
+        let product = result.get("product").and_then(|p| p.as_str()).unwrap_or_default();
+        let version = ChromeVersion::parse(product)
+            .ok_or_else(|| ChromeMcpError::cdp_protocol(format!("Unrecognized Chrome version: {}", product)))?;
+
+        self.chrome_version = Some(version.clone());
+        Ok(version)
     }
 
     /// Navigate to a URL
@@ -317,19 +861,48 @@ impl CdpClient {
         self.send_command("Page.navigate", Some(json!({ "url": url }))).await
     }
 
-    /// Evaluate JavaScript
+    /// Navigate to a URL (`Page.navigate`)
+    pub async fn navigate_typed(&mut self, request: NavigateRequest) -> Result<NavigateResponse> {
+        self.send_typed("Page.navigate", Some(&request)).await
+    }
+
+    /// Evaluate JavaScript, returning the remote object as JSON
     pub async fn evaluate_js(&mut self, expression: &str) -> Result<Value> {
-        let result = self.send_command("Runtime.evaluate", Some(json!({
-            "expression": expression,
-            "returnByValue": true,
-            "awaitPromise": true
-        }))).await?;
+        self.evaluate_js_in_context(expression, None).await
+    }
 
-        if let Some(exception_details) = result.get("exceptionDetails") {
-            return Err(ChromeMcpError::javascript_error(format!("JS Exception: {}", exception_details)));
-        }
+    /// Evaluate JavaScript in an execution context, or the top frame's for `None`
+    pub async fn evaluate_js_in_context(&mut self, expression: &str, context_id: Option<u64>) -> Result<Value> {
+        let mut request = EvaluateRequest::new(expression);
+        request.context_id = context_id;
+        let response = self.evaluate_typed(request).await?;
+        evaluation_result(response)
+    }
+
+    /// Evaluate JavaScript (`Runtime.evaluate`); exceptions are left to the caller
+    pub async fn evaluate_typed(&mut self, request: EvaluateRequest) -> Result<EvaluateResponse> {
+        self.send_typed("Runtime.evaluate", Some(&request)).await
+    }
+
+    /// Document root node (`DOM.getDocument`)
+    pub async fn get_document_typed(&mut self) -> Result<GetDocumentResponse> {
+        self.send_typed::<(), _>("DOM.getDocument", None).await
+    }
+
+    /// Elements matching a selector below a node (`DOM.querySelectorAll`)
+    pub async fn query_selector_all_typed(&mut self, request: QuerySelectorAllRequest) -> Result<QuerySelectorAllResponse> {
+        self.send_typed("DOM.querySelectorAll", Some(&request)).await
+    }
 
-        Ok(result.get("result").unwrap_or(&Value::Null).clone())
+    /// Take a screenshot (`Page.captureScreenshot`)
+    pub async fn capture_screenshot_typed(&mut self, request: CaptureScreenshotRequest) -> Result<CaptureScreenshotResponse> {
+        self.send_typed("Page.captureScreenshot", Some(&request)).await
+    }
+
+    /// Set a cookie (`Network.setCookie`)
+    pub async fn set_cookie_typed(&mut self, request: SetCookieRequest) -> Result<()> {
+        self.send_typed::<_, Value>("Network.setCookie", Some(&request)).await?;
+        Ok(())
     }
 
     /// Take a screenshot
@@ -355,13 +928,19 @@ impl CdpClient {
 
     /// Click at coordinates
     pub async fn click_at(&mut self, x: f64, y: f64) -> Result<()> {
+        self.mouse_click(x, y, "left", 1).await
+    }
+
+    /// Press and release `button` (`left`, `middle` or `right`) at coordinates; `click_count`
+    /// is 2 for the second click of a double click
+    pub async fn mouse_click(&mut self, x: f64, y: f64, button: &str, click_count: u32) -> Result<()> {
         // Mouse down
         self.send_command("Input.dispatchMouseEvent", Some(json!({
             "type": "mousePressed",
             "x": x,
             "y": y,
-            "button": "left",
-            "clickCount": 1
+            "button": button,
+            "clickCount": click_count
         }))).await?;
 
         // Small delay
@@ -372,8 +951,8 @@ impl CdpClient {
             "type": "mouseReleased",
             "x": x,
             "y": y,
-            "button": "left",
-            "clickCount": 1
+            "button": button,
+            "clickCount": click_count
         }))).await?;
 
         Ok(())
@@ -392,31 +971,157 @@ impl CdpClient {
         Ok(())
     }
 
-    /// Get accessibility tree
-    pub async fn get_accessibility_tree(&mut self) -> Result<Value> {
-        self.send_command("Accessibility.getFullAXTree", None).await
+    /// Get every node of the accessibility tree
+    pub async fn get_full_ax_tree(&mut self) -> Result<GetFullAXTreeResponse> {
+        self.send_typed::<(), _>("Accessibility.getFullAXTree", None).await
+    }
+
+    /// Get the root node of the accessibility tree
+    pub async fn get_root_ax_node(&mut self) -> Result<GetRootAXNodeResponse> {
+        self.send_typed::<(), _>("Accessibility.getRootAXNode", None).await
+    }
+
+    /// Get the direct children of an accessibility node
+    pub async fn get_child_ax_nodes(&mut self, node_id: &str) -> Result<GetChildAXNodesResponse> {
+        self.send_typed("Accessibility.getChildAXNodes", Some(&json!({ "id": node_id }))).await
     }
 
     /// Find elements by selector
-    pub async fn query_selector_all(&mut self, selector: &str) -> Result<Value> {
-        // Get document root
-        let doc_result = self.send_command("DOM.getDocument", None).await?;
-        let root_node_id = doc_result
-            .get("root")
-            .and_then(|r| r.get("nodeId"))
-            .and_then(|id| id.as_u64())
-            .ok_or_else(|| ChromeMcpError::cdp_protocol("Could not get document root"))?;
-
-        // Query for elements
-        self.send_command("DOM.querySelectorAll", Some(json!({
-            "nodeId": root_node_id,
-            "selector": selector
-        }))).await
+    pub async fn query_selector_all(&mut self, selector: &str) -> Result<QuerySelectorAllResponse> {
+        let document = self.get_document_typed().await?;
+        self.query_selector_all_typed(QuerySelectorAllRequest {
+            node_id: document.root.node_id,
+            selector: selector.to_string(),
+        })
+        .await
     }
 
     /// Get the current tab ID
     pub fn current_tab_id(&self) -> Option<&str> {
-        self.tab_id.as_deref()
+        self.backend.current_tab_id()
+    }
+
+    /// Traffic and latency of the commands sent so far
+    pub fn connection_metrics(&self) -> ConnectionMetrics {
+        let (bytes_sent, bytes_received) = self.backend.frame_bytes().totals();
+        ConnectionMetrics {
+            bytes_sent,
+            bytes_received,
+            ..self.metrics.lock().unwrap().clone()
+        }
+    }
+
+    /// Start counting from zero again
+    pub fn reset_connection_metrics(&self) {
+        *self.metrics.lock().unwrap() = ConnectionMetrics::default();
+        self.backend.frame_bytes().reset();
+    }
+}
+
+/// Remote object an evaluation returned, as JSON, or its exception as an error
+fn evaluation_result(response: EvaluateResponse) -> Result<Value> {
+    if let Some(exception_details) = response.exception_details {
+        return Err(ChromeMcpError::javascript_error(format!("JS Exception: {}", exception_details)));
+    }
+
+    Ok(response.result.map(serde_json::to_value).transpose()?.unwrap_or(Value::Null))
+}
+
+/// Dedicated WebSocket connection to a tab, with a CDP session of its own.
+///
+/// Session state such as `Fetch` interception lasts only as long as the connection,
+/// so features that answer events keep a session open in a background task.
+pub struct CdpSession {
+    websocket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    next_id: u64,
+    /// Events received while waiting for a command response
+    events: VecDeque<(String, Value)>,
+}
+
+impl CdpSession {
+    /// Open a session on a WebSocket debugger URL
+    pub async fn connect(ws_url: &str) -> Result<Self> {
+        debug!("Opening CDP session: {}", ws_url);
+        let (websocket, _) = connect_async(ws_url)
+            .await
+            .map_err(|e| ChromeMcpError::cdp_connection(format!("WebSocket connection failed: {}", e)))?;
+
+        Ok(Self {
+            websocket,
+            next_id: 1,
+            events: VecDeque::new(),
+        })
+    }
+
+    /// Send a command on this session and wait for its response
+    pub async fn send_command(&mut self, method: &str, params: Option<Value>) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let message = CdpMessage {
+            id: Some(id),
+            method: Some(method.to_string()),
+            params,
+            result: None,
+            error: None,
+            session_id: None,
+        };
+        let json_msg = serde_json::to_string(&message)?;
+        trace!("Sending CDP session message: {}", json_msg);
+        self.websocket.send(Message::Text(json_msg)).await?;
+
+        let response = timeout(Duration::from_secs(30), async {
+            loop {
+                let message = self.read_message().await?;
+                if message.id == Some(id) {
+                    return Ok::<_, ChromeMcpError>(message);
+                }
+                if let Some(event) = message.method {
+                    self.events.push_back((event, message.params.unwrap_or(Value::Null)));
+                }
+            }
+        })
+        .await
+        .map_err(|_| ChromeMcpError::Timeout { timeout: 30000 })??;
+
+        if let Some(error) = response.error {
+            return Err(ChromeMcpError::cdp_protocol(format!(
+                "CDP error {}: {}", error.code, error.message
+            )));
+        }
+
+        Ok(response.result.unwrap_or(Value::Null))
+    }
+
+    /// Wait for the next event of this session, as `(method, params)`.
+    /// Fails once the connection is closed.
+    pub async fn next_event(&mut self) -> Result<(String, Value)> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(event);
+        }
+
+        loop {
+            let message = self.read_message().await?;
+            if let (None, Some(method)) = (message.id, message.method) {
+                return Ok((method, message.params.unwrap_or(Value::Null)));
+            }
+        }
+    }
+
+    async fn read_message(&mut self) -> Result<CdpMessage> {
+        loop {
+            match self.websocket.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    trace!("Received CDP session message: {}", text);
+                    return Ok(serde_json::from_str(&text)?);
+                }
+                Some(Ok(Message::Close(_))) | None => {
+                    return Err(ChromeMcpError::cdp_connection("CDP session closed"));
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            }
+        }
     }
 }
 
@@ -433,6 +1138,7 @@ mod tests {
             params: Some(json!({"url": "https://example.com"})),
             result: None,
             error: None,
+            session_id: None,
         };
 
         assert_eq!(message.id, Some(123));
@@ -450,6 +1156,7 @@ mod tests {
             params: Some(json!({"expression": "console.log('test')"})),
             result: None,
             error: None,
+            session_id: None,
         };
 
         let json_str = serde_json::to_string(&message).unwrap();
@@ -496,10 +1203,10 @@ mod tests {
     fn test_cdp_client_creation() {
         let client = CdpClient::new("localhost", 9222);
         
-        assert_eq!(client.chrome_host, "localhost");
-        assert_eq!(client.chrome_port, 9222);
+        assert_eq!(client.backend.chrome_host, "localhost");
+        assert_eq!(client.backend.chrome_port, 9222);
         assert!(client.current_tab_id().is_none());
-        assert!(client.websocket.is_none());
+        assert!(client.backend.connection.lock().unwrap().is_none());
     }
 
     #[test]
@@ -507,9 +1214,9 @@ mod tests {
         let client = CdpClient::new("localhost", 9222);
         let cloned = client.clone();
         
-        assert_eq!(client.chrome_host, cloned.chrome_host);
-        assert_eq!(client.chrome_port, cloned.chrome_port);
-        assert!(cloned.websocket.is_none()); // WebSocket shouldn't be cloned
+        assert_eq!(client.backend.chrome_host, cloned.backend.chrome_host);
+        assert_eq!(client.backend.chrome_port, cloned.backend.chrome_port);
+        assert!(Arc::ptr_eq(&client.backend.connection, &cloned.backend.connection)); // Clones share the connection
     }
 
     #[test]
@@ -519,8 +1226,8 @@ mod tests {
         
         // We can't easily test the actual HTTP call without mocking,
         // but we can test URL construction logic
-        assert_eq!(client.chrome_host, "localhost");
-        assert_eq!(client.chrome_port, 9222);
+        assert_eq!(client.backend.chrome_host, "localhost");
+        assert_eq!(client.backend.chrome_port, 9222);
     }
 
     #[test]
@@ -528,7 +1235,7 @@ mod tests {
         let client = CdpClient::new("localhost", 9222);
         
         // Test base URL construction
-        let base_url = format!("http://{}:{}/json/new", client.chrome_host, client.chrome_port);
+        let base_url = format!("http://{}:{}/json/new", client.backend.chrome_host, client.backend.chrome_port);
         assert_eq!(base_url, "http://localhost:9222/json/new");
         
         // Test URL with parameter
@@ -542,7 +1249,7 @@ mod tests {
         let client = CdpClient::new("localhost", 9222);
         let tab_id = "test_tab_123";
         
-        let close_url = format!("http://{}:{}/json/close/{}", client.chrome_host, client.chrome_port, tab_id);
+        let close_url = format!("http://{}:{}/json/close/{}", client.backend.chrome_host, client.backend.chrome_port, tab_id);
         assert_eq!(close_url, "http://localhost:9222/json/close/test_tab_123");
     }
 
@@ -552,14 +1259,14 @@ mod tests {
         
         // Test that message ID starts at 1 and increments
         let id1 = {
-            let mut counter = client.message_id.lock().unwrap();
+            let mut counter = client.backend.message_id.lock().unwrap();
             let current = *counter;
             *counter += 1;
             current
         };
         
         let id2 = {
-            let mut counter = client.message_id.lock().unwrap();
+            let mut counter = client.backend.message_id.lock().unwrap();
             let current = *counter;
             *counter += 1;
             current
@@ -577,6 +1284,7 @@ mod tests {
             params: Some(json!({"url": "https://example.com"})),
             result: None,
             error: None,
+            session_id: None,
         };
 
         assert_eq!(message.method.unwrap(), "Page.navigate");
@@ -689,14 +1397,7 @@ mod tests {
 
     #[test]
     fn test_cdp_domains_list() {
-        let expected_domains = vec![
-            "Runtime",
-            "Page", 
-            "DOM",
-            "Input",
-            "Network",
-            "Accessibility",
-        ];
+        let expected_domains = AUTOMATION_DOMAINS;
 
         // Test that we have all required domains
         assert!(expected_domains.contains(&"Runtime"));
@@ -747,4 +1448,173 @@ mod tests {
         let result = response.result.unwrap();
         assert_eq!(result["data"], "base64_encoded_screenshot_data");
     }
-}
\ No newline at end of file
+
+    /// Minimal DevTools WebSocket endpoint answering every command with its method name.
+    /// Returns its address, the number of connections accepted and the commands received;
+    /// with `close_after`, each connection is closed after answering that many commands.
+    async fn serve_cdp(close_after: Option<usize>) -> (std::net::SocketAddr, Arc<std::sync::atomic::AtomicUsize>, Arc<Mutex<Vec<CdpMessage>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let connections = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let (accepted, commands) = (Arc::clone(&connections), Arc::clone(&received));
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let commands = Arc::clone(&commands);
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    let mut answered = 0;
+                    while let Some(Ok(Message::Text(text))) = ws.next().await {
+                        let request: CdpMessage = serde_json::from_str(&text).unwrap();
+                        let response = json!({ "id": request.id, "result": { "method": request.method, "identifier": request.id.map(|id| id.to_string()) } });
+                        let reload = request.method.as_deref() == Some("Page.reload");
+                        commands.lock().unwrap().push(request);
+                        ws.send(Message::Text(response.to_string())).await.unwrap();
+                        if reload {
+                            let event = json!({ "method": "Page.loadEventFired", "params": { "timestamp": 1.5 } });
+                            ws.send(Message::Text(event.to_string())).await.unwrap();
+                        }
+
+                        answered += 1;
+                        if Some(answered) == close_after {
+                            let _ = ws.close(None).await;
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        (address, connections, received)
+    }
+
+    #[tokio::test]
+    async fn test_commands_share_one_connection() {
+        let (address, connections, received) = serve_cdp(None).await;
+        let backend = WebSocketBackend::new("127.0.0.1", address.port());
+        backend.open_connection(&format!("ws://{}/devtools/page/TAB", address)).await.unwrap();
+        let mut client = CdpClient::with_backend(backend);
+
+        for _ in 0..10 {
+            let result = client.send_command("Runtime.evaluate", Some(json!({ "expression": "1" }))).await.unwrap();
+            assert_eq!(result["method"], "Runtime.evaluate");
+        }
+
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+        // 6 domains enabled on connect, then the 10 commands, without gaps
+        let ids: Vec<u64> = received.lock().unwrap().iter().filter_map(|m| m.id).collect();
+        assert_eq!(ids, (1..=16).collect::<Vec<u64>>());
+
+        // Bytes are those of the frames actually exchanged
+        client.reset_connection_metrics();
+        client.send_command("Page.reload", None).await.unwrap();
+        let request = received.lock().unwrap().last().cloned().unwrap();
+        let response = json!({ "id": 17, "result": { "method": "Page.reload" } });
+        let metrics = client.connection_metrics();
+        assert_eq!(metrics.bytes_sent, serde_json::to_string(&request).unwrap().len() as u64);
+        assert!(metrics.bytes_received >= response.to_string().len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_session_commands() {
+        let (address, connections, received) = serve_cdp(None).await;
+        let backend = WebSocketBackend::new("127.0.0.1", address.port());
+        backend.open_connection(&format!("ws://{}/devtools/page/TAB", address)).await.unwrap();
+        let mut client = CdpClient::with_backend(backend);
+
+        let result = client.send_session_command("SESSION", "Runtime.evaluate", Some(json!({ "expression": "1" }))).await.unwrap();
+        assert_eq!(result["method"], "Runtime.evaluate");
+        client.send_command("Page.reload", None).await.unwrap();
+
+        // Sent over the tab's connection, addressed to the session
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+        let sessions: Vec<Option<String>> = received.lock().unwrap()[6..].iter().map(|m| m.session_id.clone()).collect();
+        assert_eq!(sessions, vec![Some("SESSION".to_string()), None]);
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_close() {
+        // The server hangs up after the domains and one command
+        let (address, connections, received) = serve_cdp(Some(7)).await;
+        let backend = WebSocketBackend::new("127.0.0.1", address.port());
+        backend.open_connection(&format!("ws://{}/devtools/page/TAB", address)).await.unwrap();
+        let mut client = CdpClient::with_backend(backend);
+
+        client.send_command("Page.reload", None).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        client.send_command("Page.reload", None).await.unwrap();
+
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+        let methods: Vec<String> = received.lock().unwrap().iter().filter_map(|m| m.method.clone()).collect();
+        assert_eq!(methods.iter().filter(|m| *m == "Runtime.enable").count(), 2);
+        assert_eq!(methods.last().map(String::as_str), Some("Page.reload"));
+    }
+
+    #[tokio::test]
+    async fn test_restores_session_state_after_reconnect() {
+        // The server hangs up after the domains and five commands
+        let (address, connections, received) = serve_cdp(Some(11)).await;
+        let backend = WebSocketBackend::new("127.0.0.1", address.port());
+        backend.open_connection(&format!("ws://{}/devtools/page/TAB", address)).await.unwrap();
+        let mut client = CdpClient::with_backend(backend);
+
+        client.send_command("Runtime.addBinding", Some(json!({ "name": "notify" }))).await.unwrap();
+        let script = client.send_command("Page.addScriptToEvaluateOnNewDocument", Some(json!({ "source": "1" }))).await.unwrap();
+        assert_eq!(script["identifier"], "8");
+        client.send_command("Fetch.enable", Some(json!({ "patterns": [{ "urlPattern": "*" }] }))).await.unwrap();
+        client.send_command("Runtime.evaluate", Some(json!({ "expression": "1" }))).await.unwrap();
+        client.send_command("Runtime.evaluate", Some(json!({ "expression": "2" }))).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Commands racing into the closed connection reconnect once
+        let mut other = client.clone();
+        let (first, second) = tokio::join!(
+            client.send_command("Page.removeScriptToEvaluateOnNewDocument", Some(json!({ "identifier": "8" }))),
+            other.send_command("Page.reload", None),
+        );
+        first.unwrap();
+        second.unwrap();
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+        // Past the first connection's 11 messages and the domains enabled on the new one
+        let replayed: Vec<CdpMessage> = received.lock().unwrap()[17..].to_vec();
+        let methods: Vec<&str> = replayed.iter().filter_map(|m| m.method.as_deref()).collect();
+        assert_eq!(&methods[..3], ["Runtime.addBinding", "Page.addScriptToEvaluateOnNewDocument", "Fetch.enable"]);
+        assert_eq!(replayed[2].params, Some(json!({ "patterns": [{ "urlPattern": "*" }] })));
+        // The script is removed under the identifier it got on the new connection
+        let removal = replayed.iter().find(|m| m.method.as_deref() == Some("Page.removeScriptToEvaluateOnNewDocument")).unwrap();
+        assert_eq!(removal.params, Some(json!({ "identifier": replayed[1].id.unwrap().to_string() })));
+    }
+
+    #[tokio::test]
+    async fn test_event_subscriptions() {
+        let (address, _, _) = serve_cdp(None).await;
+        let backend = WebSocketBackend::new("127.0.0.1", address.port());
+        backend.open_connection(&format!("ws://{}/devtools/page/TAB", address)).await.unwrap();
+        let mut client = CdpClient::with_backend(backend);
+
+        let mut first = client.subscribe("Page.loadEventFired");
+        let mut second = client.subscribe("Page.loadEventFired");
+        client.send_command("Page.reload", None).await.unwrap();
+        assert_eq!(first.recv().await.unwrap(), json!({ "timestamp": 1.5 }));
+        assert_eq!(second.recv().await.unwrap(), json!({ "timestamp": 1.5 }));
+
+        // Dropping one subscription leaves the other
+        drop(first);
+        client.send_command("Page.reload", None).await.unwrap();
+        assert_eq!(second.recv().await.unwrap(), json!({ "timestamp": 1.5 }));
+        drop(second);
+        assert!(client.backend.events().channels.lock().unwrap().is_empty());
+
+        let mut waiting = client.clone();
+        let event = tokio::spawn(async move { waiting.wait_for_event("Page.loadEventFired", 2000).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.send_command("Page.reload", None).await.unwrap();
+        assert_eq!(event.await.unwrap().unwrap(), json!({ "timestamp": 1.5 }));
+
+        let error = client.wait_for_event("Target.targetCrashed", 50).await.unwrap_err();
+        assert!(matches!(error, ChromeMcpError::Timeout { timeout: 50 }));
+    }
+}
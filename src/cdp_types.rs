@@ -0,0 +1,272 @@
+//! Typed requests and responses of the CDP commands chrome-mcp sends most often
+//!
+//! `CdpClient::send_command` stays available for everything else, and for `chrome_cdp_raw`.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// `Page.navigate` parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigateRequest {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referrer: Option<String>,
+}
+
+impl NavigateRequest {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            referrer: None,
+        }
+    }
+}
+
+/// `Page.navigate` result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigateResponse {
+    pub frame_id: String,
+    pub loader_id: Option<String>,
+    /// Set when the navigation failed, e.g. `net::ERR_NAME_NOT_RESOLVED`
+    pub error_text: Option<String>,
+}
+
+/// `Runtime.evaluate` parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateRequest {
+    pub expression: String,
+    pub return_by_value: bool,
+    pub await_promise: bool,
+    /// Treat the evaluation as initiated by the user, as APIs like fullscreen require
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_gesture: Option<bool>,
+    /// Execution context to evaluate in, e.g. an iframe's; the top frame's by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_id: Option<u64>,
+}
+
+impl EvaluateRequest {
+    /// Evaluate `expression`, returning its value by value once any promise resolves
+    pub fn new(expression: impl Into<String>) -> Self {
+        Self {
+            expression: expression.into(),
+            return_by_value: true,
+            await_promise: true,
+            user_gesture: None,
+            context_id: None,
+        }
+    }
+
+    /// Run as if triggered by a user gesture
+    pub fn with_user_gesture(mut self) -> Self {
+        self.user_gesture = Some(true);
+        self
+    }
+}
+
+/// `Runtime.evaluate` result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponse {
+    #[serde(default)]
+    pub result: Option<RemoteObject>,
+    /// Set when the expression threw
+    pub exception_details: Option<Value>,
+}
+
+impl EvaluateResponse {
+    /// Value of the result, if it has one
+    pub fn value(&self) -> Option<&Value> {
+        self.result.as_ref().and_then(|r| r.value.as_ref())
+    }
+}
+
+/// `Runtime.RemoteObject`, the result of an evaluation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteObject {
+    #[serde(rename = "type", default, skip_serializing_if = "String::is_empty")]
+    pub object_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class_name: Option<String>,
+    /// Present (possibly `null`) for values returned by value
+    #[serde(default, deserialize_with = "present_value", skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unserializable_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<String>,
+}
+
+/// Keep a `null` value apart from a missing one
+fn present_value<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// `DOM.getDocument` result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetDocumentResponse {
+    pub root: DomNode,
+}
+
+/// `DOM.Node`, limited to the fields chrome-mcp reads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomNode {
+    pub node_id: u64,
+    pub backend_node_id: Option<u64>,
+    pub node_name: Option<String>,
+}
+
+/// `DOM.querySelectorAll` parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuerySelectorAllRequest {
+    pub node_id: u64,
+    pub selector: String,
+}
+
+/// `DOM.querySelectorAll` result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuerySelectorAllResponse {
+    pub node_ids: Vec<u64>,
+}
+
+/// `Page.captureScreenshot` parameters
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureScreenshotRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clip: Option<ScreenshotClip>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_beyond_viewport: Option<bool>,
+}
+
+/// `Page.Viewport`, the area of a clipped screenshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScreenshotClip {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub scale: f64,
+}
+
+/// `Page.captureScreenshot` result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureScreenshotResponse {
+    /// Base64-encoded image
+    pub data: String,
+}
+
+/// `Accessibility.getRootAXNode` result. Nodes are kept raw for `AccessibilityManager`,
+/// which reads their many optional properties.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetRootAXNodeResponse {
+    pub node: Value,
+}
+
+/// `Accessibility.getFullAXTree` result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetFullAXTreeResponse {
+    pub nodes: Vec<Value>,
+}
+
+/// `Accessibility.getChildAXNodes` result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetChildAXNodesResponse {
+    pub nodes: Vec<Value>,
+}
+
+/// `Network.setCookie` parameters
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetCookieRequest {
+    pub name: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secure: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub same_site: Option<String>,
+    /// Expiration as seconds since the epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_serialization() {
+        assert_eq!(
+            serde_json::to_value(EvaluateRequest::new("1 + 1")).unwrap(),
+            json!({ "expression": "1 + 1", "returnByValue": true, "awaitPromise": true })
+        );
+        assert_eq!(
+            serde_json::to_value(EvaluateRequest::new("1 + 1").with_user_gesture()).unwrap()["userGesture"],
+            json!(true)
+        );
+        assert_eq!(
+            serde_json::to_value(CaptureScreenshotRequest {
+                format: Some("png".to_string()),
+                capture_beyond_viewport: Some(false),
+                ..Default::default()
+            })
+            .unwrap(),
+            json!({ "format": "png", "captureBeyondViewport": false })
+        );
+        assert_eq!(
+            serde_json::to_value(SetCookieRequest {
+                name: "session".to_string(),
+                value: "abc".to_string(),
+                http_only: Some(true),
+                ..Default::default()
+            })
+            .unwrap(),
+            json!({ "name": "session", "value": "abc", "httpOnly": true })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_response_keeps_null_values() {
+        let null: EvaluateResponse =
+            serde_json::from_value(json!({ "result": { "type": "object", "subtype": "null", "value": null } })).unwrap();
+        assert_eq!(null.value(), Some(&Value::Null));
+        assert_eq!(
+            serde_json::to_value(null.result.unwrap()).unwrap(),
+            json!({ "type": "object", "subtype": "null", "value": null })
+        );
+
+        let undefined: EvaluateResponse = serde_json::from_value(json!({ "result": { "type": "undefined" } })).unwrap();
+        assert_eq!(undefined.value(), None);
+
+        let thrown: EvaluateResponse = serde_json::from_value(json!({
+            "result": { "type": "object", "subtype": "error", "description": "Error: boom" },
+            "exceptionDetails": { "text": "Uncaught" }
+        }))
+        .unwrap();
+        assert!(thrown.exception_details.is_some());
+    }
+}
@@ -0,0 +1,129 @@
+//! Minimum Chrome versions of the CDP methods used by chrome-mcp
+
+use crate::error::ChromeMcpError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use tracing::warn;
+
+/// Minimum Chrome major version supporting each CDP method the crate sends
+pub static COMPATIBILITY_MAP: LazyLock<HashMap<&'static str, u32>> = LazyLock::new(|| {
+    HashMap::from([
+        ("Accessibility.getChildAXNodes", 89),
+        ("Accessibility.getFullAXTree", 74),
+        ("Accessibility.getRootAXNode", 91),
+        ("DOM.getBoxModel", 60),
+        ("DOM.getDocument", 60),
+        ("DOM.querySelector", 60),
+        ("DOM.querySelectorAll", 60),
+        ("Emulation.setDeviceMetricsOverride", 60),
+        ("Emulation.setLocaleOverride", 86),
+        ("Emulation.setTimezoneOverride", 78),
+        ("Fetch.enable", 74),
+        ("Fetch.disable", 74),
+        ("Fetch.continueRequest", 74),
+        ("Fetch.continueWithAuth", 74),
+        ("Fetch.fulfillRequest", 74),
+        ("Input.dispatchKeyEvent", 60),
+        ("Input.dispatchMouseEvent", 60),
+        ("Network.clearBrowserCookies", 60),
+        ("Network.getCookies", 60),
+        ("Network.getResponseBody", 60),
+        ("Network.setCookie", 63),
+        ("Page.addScriptToEvaluateOnNewDocument", 61),
+        ("Page.captureScreenshot", 60),
+        ("Page.getResourceContent", 60),
+        ("Page.getResourceTree", 60),
+        ("Page.navigate", 60),
+        ("Page.navigateToHistoryEntry", 60),
+        ("Page.printToPDF", 60),
+        ("Page.reload", 60),
+        ("Page.removeScriptToEvaluateOnNewDocument", 61),
+        ("Page.searchInResource", 60),
+        ("Profiler.setSamplingInterval", 60),
+        ("Profiler.start", 60),
+        ("Profiler.stop", 60),
+        ("Runtime.addBinding", 71),
+        ("Runtime.evaluate", 60),
+        ("Target.getTargets", 60),
+    ])
+});
+
+/// Chrome version reported by `Browser.getVersion`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChromeVersion {
+    /// Product string, e.g. `HeadlessChrome/120.0.6099.109`
+    pub product: String,
+    pub major: u32,
+}
+
+impl ChromeVersion {
+    /// Parse a `Browser.getVersion` product string
+    pub fn parse(product: &str) -> Option<Self> {
+        let version = product.rsplit('/').next()?;
+        let major = version.split('.').next()?.parse().ok()?;
+        Some(Self {
+            product: product.to_string(),
+            major,
+        })
+    }
+}
+
+/// Minimum Chrome major version supporting `method`, if known
+pub fn required_version(method: &str) -> Option<u32> {
+    COMPATIBILITY_MAP.get(method).copied()
+}
+
+/// Replace a CDP protocol error with `IncompatibleChromeVersion` when the connected
+/// Chrome is older than `method` requires; other errors are returned unchanged
+pub fn check_compatibility(error: ChromeMcpError, method: &str, version: &ChromeVersion) -> ChromeMcpError {
+    let required = match required_version(method) {
+        Some(required) if version.major < required => required,
+        _ => return error,
+    };
+
+    warn!(
+        "{} failed ({}): it requires Chrome {} or newer but {} is connected, upgrade Chrome to use this feature",
+        method, error, required, version.product
+    );
+    ChromeMcpError::IncompatibleChromeVersion {
+        method: method.to_string(),
+        required,
+        actual: version.major,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chrome_version() {
+        let version = ChromeVersion::parse("HeadlessChrome/120.0.6099.109").unwrap();
+        assert_eq!(version.major, 120);
+        assert_eq!(version.product, "HeadlessChrome/120.0.6099.109");
+
+        assert_eq!(ChromeVersion::parse("Chrome/72.0.3626.121").unwrap().major, 72);
+        assert!(ChromeVersion::parse("Chrome/unknown").is_none());
+    }
+
+    #[test]
+    fn test_check_compatibility() {
+        let old = ChromeVersion::parse("Chrome/72.0.3626.121").unwrap();
+        let error = check_compatibility(ChromeMcpError::cdp_protocol("CDP error -32601: not found"), "Accessibility.getRootAXNode", &old);
+        assert!(matches!(
+            error,
+            ChromeMcpError::IncompatibleChromeVersion { required: 91, actual: 72, .. }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Accessibility.getRootAXNode requires Chrome 91 or newer, but the connected Chrome is version 72"
+        );
+
+        // Supported methods and unknown methods keep the original error
+        let error = check_compatibility(ChromeMcpError::cdp_protocol("boom"), "Runtime.evaluate", &old);
+        assert!(matches!(error, ChromeMcpError::CdpProtocol(_)));
+        let error = check_compatibility(ChromeMcpError::cdp_protocol("boom"), "Custom.method", &old);
+        assert!(matches!(error, ChromeMcpError::CdpProtocol(_)));
+    }
+}
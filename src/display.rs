@@ -0,0 +1,127 @@
+//! Virtual X display (Xvfb) for running a headed Chrome on CI machines without a screen
+
+use crate::error::{ChromeMcpError, Result};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Display number used by `--use-xvfb`
+pub const DEFAULT_DISPLAY_NUM: u32 = 99;
+
+/// Screen size used by `--use-xvfb`
+pub const DEFAULT_SCREEN_SIZE: (u32, u32) = (1920, 1080);
+
+/// How long Xvfb gets to create its socket before `start` gives up
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Running `Xvfb` server; `display` is the `DISPLAY` value for the processes that should use
+/// it. Dropping it stops the server.
+#[derive(Debug)]
+pub struct VirtualDisplay {
+    display_num: u32,
+    process: Child,
+}
+
+impl VirtualDisplay {
+    /// Start `Xvfb :<display_num>` with a `width`x`height` 24-bit screen
+    pub fn start(display_num: u32, width: u32, height: u32) -> Result<VirtualDisplay> {
+        if width == 0 || height == 0 {
+            return Err(ChromeMcpError::invalid_operation(format!("Invalid screen size {}x{}", width, height)));
+        }
+        if lock_file(display_num).exists() {
+            return Err(ChromeMcpError::invalid_operation(format!("Display :{} is already in use", display_num)));
+        }
+
+        info!("Starting Xvfb on :{} ({}x{})", display_num, width, height);
+        let mut process = Command::new("Xvfb")
+            .args(xvfb_args(display_num, width, height))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| ChromeMcpError::invalid_operation(format!("Could not start Xvfb (is it installed?): {}", e)))?;
+
+        // The server is ready once it listens on its socket
+        let started = Instant::now();
+        while !socket_file(display_num).exists() {
+            if let Some(status) = process.try_wait()? {
+                return Err(ChromeMcpError::invalid_operation(format!("Xvfb exited on startup ({})", status)));
+            }
+            if started.elapsed() > STARTUP_TIMEOUT {
+                let _ = process.kill();
+                let _ = process.wait();
+                return Err(ChromeMcpError::Timeout { timeout: STARTUP_TIMEOUT.as_millis() as u64 });
+            }
+            sleep(Duration::from_millis(50));
+        }
+
+        debug!("Xvfb ready after {:?}", started.elapsed());
+        Ok(VirtualDisplay { display_num, process })
+    }
+
+    /// `DISPLAY` value of the server, e.g. `:99`
+    pub fn display(&self) -> String {
+        format!(":{}", self.display_num)
+    }
+}
+
+impl Drop for VirtualDisplay {
+    fn drop(&mut self) {
+        debug!("Stopping Xvfb on {}", self.display());
+        if let Err(e) = self.process.kill().and_then(|_| self.process.wait()) {
+            warn!("Could not stop Xvfb on {}: {}", self.display(), e);
+        }
+    }
+}
+
+/// Command line arguments of `Xvfb` for a display
+fn xvfb_args(display_num: u32, width: u32, height: u32) -> Vec<String> {
+    vec![
+        format!(":{}", display_num),
+        "-screen".to_string(),
+        "0".to_string(),
+        format!("{}x{}x24", width, height),
+        "-nolisten".to_string(),
+        "tcp".to_string(),
+    ]
+}
+
+/// Lock file an X server holds while it serves `display_num`
+fn lock_file(display_num: u32) -> PathBuf {
+    PathBuf::from(format!("/tmp/.X{}-lock", display_num))
+}
+
+/// Unix socket an X server listens on for `display_num`
+fn socket_file(display_num: u32) -> PathBuf {
+    PathBuf::from(format!("/tmp/.X11-unix/X{}", display_num))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xvfb_args() {
+        assert_eq!(xvfb_args(99, 1280, 720), vec![":99", "-screen", "0", "1280x720x24", "-nolisten", "tcp"]);
+        assert_eq!(lock_file(99), PathBuf::from("/tmp/.X99-lock"));
+        assert_eq!(socket_file(99), PathBuf::from("/tmp/.X11-unix/X99"));
+    }
+
+    #[test]
+    fn test_invalid_screen_size() {
+        let error = VirtualDisplay::start(DEFAULT_DISPLAY_NUM, 0, 720).unwrap_err();
+        assert!(matches!(error, ChromeMcpError::InvalidOperation(_)));
+    }
+
+    #[test]
+    #[ignore] // Requires Xvfb
+    fn test_start_and_stop_xvfb() {
+        let display = VirtualDisplay::start(97, 800, 600).unwrap();
+        assert_eq!(display.display(), ":97");
+        // This process keeps its own DISPLAY
+        assert_ne!(std::env::var("DISPLAY").ok().as_deref(), Some(":97"));
+        drop(display);
+    }
+}
@@ -0,0 +1,86 @@
+//! Line diffs of HTML snapshots, to check what an operation changed in the DOM
+
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+/// `outerHTML` of an element at one point in time, with a line break between adjacent tags
+/// so that line diffs point at the changed elements instead of one long line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DomSnapshot(pub String);
+
+impl DomSnapshot {
+    /// Snapshot of `outer_html`, split into one line per tag boundary
+    pub fn from_outer_html(outer_html: &str) -> Self {
+        DomSnapshot(outer_html.replace("><", ">\n<"))
+    }
+
+    /// Line diff from `self` to `other`
+    ///
+    /// Removed lines are numbered as in `self`, added and unchanged lines as in `other`.
+    pub fn diff(&self, other: &DomSnapshot) -> Vec<HtmlDiff> {
+        TextDiff::from_lines(&self.0, &other.0)
+            .iter_all_changes()
+            .map(|change| {
+                let (kind, index) = match change.tag() {
+                    ChangeTag::Insert => (DiffKind::Add, change.new_index()),
+                    ChangeTag::Delete => (DiffKind::Remove, change.old_index()),
+                    ChangeTag::Equal => (DiffKind::Equal, change.new_index()),
+                };
+                HtmlDiff {
+                    kind,
+                    line_number: index.unwrap_or_default() + 1,
+                    content: change.value().trim_end_matches('\n').to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Whether a line was added, removed or kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    Add,
+    Remove,
+    Equal,
+}
+
+/// One line of a `DomSnapshot::diff`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HtmlDiff {
+    pub kind: DiffKind,
+    /// 1-based line number
+    pub line_number: usize,
+    pub content: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_splits_tags() {
+        let snapshot = DomSnapshot::from_outer_html("<ul><li>One</li><li>Two</li></ul>");
+        assert_eq!(snapshot.0, "<ul>\n<li>One</li>\n<li>Two</li>\n</ul>");
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = DomSnapshot::from_outer_html("<ul><li>One</li><li>Two</li></ul>");
+        let after = DomSnapshot::from_outer_html("<ul><li>One</li><li class=\"done\">Two</li><li>Three</li></ul>");
+
+        let diff = before.diff(&after);
+        let changes: Vec<(DiffKind, usize, &str)> = diff
+            .iter()
+            .filter(|d| d.kind != DiffKind::Equal)
+            .map(|d| (d.kind, d.line_number, d.content.as_str()))
+            .collect();
+        assert_eq!(changes, vec![
+            (DiffKind::Remove, 3, "<li>Two</li>"),
+            (DiffKind::Add, 3, "<li class=\"done\">Two</li>"),
+            (DiffKind::Add, 4, "<li>Three</li>"),
+        ]);
+
+        assert!(before.diff(&before).iter().all(|d| d.kind == DiffKind::Equal));
+    }
+}
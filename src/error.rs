@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 use thiserror::Error;
 
 /// Main error type for chrome-mcp
@@ -36,8 +39,9 @@ pub enum ChromeMcpError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    /// Boxed, as the tungstenite error would otherwise make every `Result` this large
     #[error("WebSocket error: {0}")]
-    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
 
     #[error("JSON serialization error: {0}")]
     Json(#[from] serde_json::Error),
@@ -53,10 +57,38 @@ pub enum ChromeMcpError {
 
     #[error("Timeout: operation timed out after {timeout}ms")]
     Timeout { timeout: u64 },
+
+    #[error("{method} requires Chrome {required} or newer, but the connected Chrome is version {actual}")]
+    IncompatibleChromeVersion {
+        method: String,
+        required: u32,
+        actual: u32,
+    },
+
+    /// Another error annotated with the layers it passed through
+    #[error("{error}")]
+    WithContext {
+        error: Box<ChromeMcpError>,
+        trace: Vec<TraceFrame>,
+    },
+}
+
+/// One layer (tool, browser, cdp) an error passed through, with its context
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TraceFrame {
+    pub module: String,
+    pub operation: String,
+    pub context: HashMap<String, Value>,
 }
 
 pub type Result<T> = std::result::Result<T, ChromeMcpError>;
 
+impl From<tokio_tungstenite::tungstenite::Error> for ChromeMcpError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(error))
+    }
+}
+
 impl ChromeMcpError {
     pub fn cdp_connection(msg: impl Into<String>) -> Self {
         Self::CdpConnection(msg.into())
@@ -105,6 +137,58 @@ impl ChromeMcpError {
     pub fn tab_not_found(msg: impl Into<String>) -> Self {
         Self::TabNotFound(msg.into())
     }
+
+    /// Record that the error passed through `module::operation`.
+    /// Frames are kept outermost first, so the trace reads tool → browser → cdp.
+    pub fn in_frame(self, module: &str, operation: &str) -> Self {
+        let frame = TraceFrame {
+            module: module.to_string(),
+            operation: operation.to_string(),
+            context: HashMap::new(),
+        };
+
+        match self {
+            Self::WithContext { error, mut trace } => {
+                trace.insert(0, frame);
+                Self::WithContext { error, trace }
+            }
+            error => Self::WithContext {
+                error: Box::new(error),
+                trace: vec![frame],
+            },
+        }
+    }
+
+    /// Attach a context value to the outermost trace frame
+    pub fn with_context(self, key: &str, value: impl Into<Value>) -> Self {
+        let (error, mut trace) = match self {
+            Self::WithContext { error, trace } => (error, trace),
+            error => (Box::new(error), Vec::new()),
+        };
+
+        if trace.is_empty() {
+            trace.push(TraceFrame::default());
+        }
+        trace[0].context.insert(key.to_string(), value.into());
+
+        Self::WithContext { error, trace }
+    }
+
+    /// Trace frames recorded for this error, outermost first
+    pub fn trace(&self) -> &[TraceFrame] {
+        match self {
+            Self::WithContext { trace, .. } => trace,
+            _ => &[],
+        }
+    }
+
+    /// The underlying error, without trace annotations
+    pub fn root_cause(&self) -> &ChromeMcpError {
+        match self {
+            Self::WithContext { error, .. } => error.root_cause(),
+            error => error,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -173,6 +257,11 @@ mod tests {
         let url_error = url::Url::parse("not_a_url").unwrap_err();
         let chrome_error: ChromeMcpError = url_error.into();
         assert!(matches!(chrome_error, ChromeMcpError::Url(_)));
+
+        // Test WebSocket error conversion
+        let chrome_error: ChromeMcpError = tokio_tungstenite::tungstenite::Error::ConnectionClosed.into();
+        assert!(matches!(chrome_error, ChromeMcpError::WebSocket(_)));
+        assert_eq!(format!("{}", chrome_error), "WebSocket error: Connection closed normally");
     }
 
     #[test]
@@ -197,6 +286,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_error_trace_context() {
+        let error = ChromeMcpError::cdp_protocol("CDP error -32000: Cannot navigate to invalid URL")
+            .in_frame("cdp", "send_command")
+            .with_context("method", "Page.navigate")
+            .in_frame("browser", "navigate")
+            .with_context("url", "htp://broken")
+            .in_frame("mcp", "tools/call")
+            .with_context("tool", "chrome_navigate");
+
+        // Display is unchanged by the trace
+        assert_eq!(format!("{}", error), "CDP protocol error: CDP error -32000: Cannot navigate to invalid URL");
+        assert!(matches!(error.root_cause(), ChromeMcpError::CdpProtocol(_)));
+
+        let modules: Vec<&str> = error.trace().iter().map(|f| f.module.as_str()).collect();
+        assert_eq!(modules, vec!["mcp", "browser", "cdp"]);
+        assert_eq!(error.trace()[1].context["url"], "htp://broken");
+        assert_eq!(error.trace()[2].operation, "send_command");
+    }
+
     #[test]
     fn test_error_debug_formatting() {
         let error = ChromeMcpError::cdp_connection("test");
@@ -0,0 +1,106 @@
+//! Randomized, human-looking input: click points spread over an element instead of its
+//! exact center, and uneven delays between typed characters
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Share of an element's bounding box that jittered clicks may land in, keeping them
+/// clear of the borders
+const CLICK_AREA: f64 = 0.8;
+
+/// Small xorshift64 generator; good enough for input timing, not for anything secret
+#[derive(Debug, Clone)]
+pub struct HumanRng {
+    state: u64,
+}
+
+impl HumanRng {
+    /// Generator with a fixed seed, producing the same sequence every time
+    pub fn with_seed(seed: u64) -> Self {
+        Self { state: seed | 1 }
+    }
+
+    /// Generator seeded from the clock
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or_default();
+        Self::with_seed(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Uniform in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `[min, max]`
+    pub fn range(&mut self, min: u64, max: u64) -> u64 {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        match (max - min).checked_add(1) {
+            Some(span) => min + self.next_u64() % span,
+            None => self.next_u64(),
+        }
+    }
+}
+
+/// Random point inside the inner 80 % of the `(x, y, width, height)` box, at most `jitter_px`
+/// away from its center on each axis
+pub fn jitter_point(bounds: (f64, f64, f64, f64), jitter_px: u32, rng: &mut HumanRng) -> (f64, f64) {
+    let (x, y, width, height) = bounds;
+    let mut offset = |size: f64| {
+        let reach = (size * CLICK_AREA / 2.0).min(jitter_px as f64).max(0.0);
+        (rng.next_f64() * 2.0 - 1.0) * reach
+    };
+    let dx = offset(width);
+    let dy = offset(height);
+    (x + width / 2.0 + dx, y + height / 2.0 + dy)
+}
+
+/// Random delay between two typed characters
+pub fn typing_delay(min_delay_ms: u64, max_delay_ms: u64, rng: &mut HumanRng) -> Duration {
+    Duration::from_millis(rng.range(min_delay_ms, max_delay_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jitter_point_stays_inside_click_area() {
+        let mut rng = HumanRng::with_seed(42);
+        let bounds = (100.0, 200.0, 50.0, 20.0);
+
+        let points: Vec<(f64, f64)> = (0..500).map(|_| jitter_point(bounds, 100, &mut rng)).collect();
+        for &(x, y) in &points {
+            assert!((105.0..=145.0).contains(&x), "x = {}", x);
+            assert!((202.0..=218.0).contains(&y), "y = {}", y);
+        }
+        // Not always the center
+        assert!(points.iter().any(|&(x, _)| (x - 125.0).abs() > 5.0));
+
+        for _ in 0..100 {
+            let (x, y) = jitter_point(bounds, 3, &mut rng);
+            assert!((x - 125.0).abs() <= 3.0 && (y - 210.0).abs() <= 3.0);
+        }
+        assert_eq!(jitter_point(bounds, 0, &mut rng), (125.0, 210.0));
+    }
+
+    #[test]
+    fn test_seeded_rng_is_deterministic() {
+        let mut a = HumanRng::with_seed(7);
+        let mut b = HumanRng::with_seed(7);
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+
+        for _ in 0..200 {
+            let delay = typing_delay(30, 120, &mut a);
+            assert!((30..=120).contains(&(delay.as_millis() as u64)));
+        }
+        assert_eq!(typing_delay(50, 50, &mut a), Duration::from_millis(50));
+    }
+}
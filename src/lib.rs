@@ -1,9 +1,21 @@
 pub mod accessibility;
 pub mod browser;
+pub mod compatibility;
 pub mod cdp;
+pub mod cdp_types;
+pub mod display;
+pub mod dom_diff;
 pub mod error;
+pub mod humanize;
+pub mod markdown;
 pub mod mcp;
 pub mod native_input;
+pub mod proxy;
+pub mod replay;
 pub mod screenshot;
+pub mod testing;
+pub mod timezones;
+pub mod tool_params;
+pub mod tool_registry;
 
 pub use error::{ChromeMcpError, Result};
\ No newline at end of file
@@ -1,15 +1,33 @@
 mod accessibility;
 mod browser;
+mod compatibility;
 mod cdp;
+mod cdp_types;
+mod display;
+mod dom_diff;
 mod error;
+mod humanize;
+mod markdown;
 mod mcp;
 mod native_input;
+mod proxy;
+mod replay;
 mod screenshot;
+mod testing;
+mod timezones;
+mod tool_params;
+mod tool_registry;
 
-use clap::Parser;
-use mcp::McpServer;
+use browser::Browser;
+use cdp::{CdpClient, CdpClientBackend, WebSocketBackend};
+use clap::{Parser, Subcommand};
+use display::{VirtualDisplay, DEFAULT_DISPLAY_NUM, DEFAULT_SCREEN_SIZE};
+use mcp::{LogLevelHandle, McpServer};
+use replay::ReplaySession;
+use std::path::{Path, PathBuf};
+use testing::{MockCdpClient, RecordingCdpClient};
 use tracing::{error, info};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, reload, util::SubscriberInitExt};
 
 /// Chrome browser automation via MCP – click anywhere
 #[derive(Parser, Debug)]
@@ -30,6 +48,69 @@ struct Args {
     /// Run server over stdio (MCP protocol)
     #[arg(long, default_value_t = true)]
     stdio: bool,
+
+    /// Include error traces (tool → browser → cdp) in tool error responses
+    #[arg(long)]
+    verbose_errors: bool,
+
+    /// Save a session checkpoint to this file after each navigation and resume it on startup
+    #[arg(long)]
+    checkpoint_file: Option<PathBuf>,
+
+    /// Record every CDP command and its result to this file (newline-delimited JSON)
+    #[arg(long)]
+    record_session: Option<PathBuf>,
+
+    /// Serve MCP over TCP on this port (on 127.0.0.1) or address instead of stdio, as a proxy upstream
+    #[arg(long)]
+    listen: Option<String>,
+
+    /// Token a proxy must send with `initialize`; required to --listen on a non-loopback address
+    #[arg(long, requires = "listen")]
+    listen_token: Option<String>,
+
+    /// Forward tool calls to the upstream chrome-mcp servers of this JSON route file instead of driving Chrome
+    #[arg(long, conflicts_with = "record_session")]
+    proxy_config: Option<PathBuf>,
+
+    /// Directory screenshots taken with `auto_save` are written to (default ./screenshots)
+    #[arg(long)]
+    screenshot_dir: Option<PathBuf>,
+
+    /// Keep at most this many screenshots in the screenshot directory, deleting the oldest
+    #[arg(long)]
+    screenshot_max_files: Option<usize>,
+
+    /// Click at a random point up to this many pixels from the element center, like a person
+    #[arg(long)]
+    click_jitter: Option<u32>,
+
+    /// Start an Xvfb virtual display (:99, 1920x1080) for the session and send native input to it,
+    /// e.g. to run a headed Chrome on CI machines without a screen
+    #[arg(long)]
+    use_xvfb: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve MCP without Chrome, answering CDP commands from a session recorded with --record-session,
+    /// or with --live replay its tool calls against Chrome and print their results
+    Replay {
+        /// Recorded session file
+        #[arg(long)]
+        session: PathBuf,
+
+        /// Replay the recorded tool calls against a live Chrome instead of serving MCP
+        #[arg(long)]
+        live: bool,
+
+        /// Fail with a diff when a replayed tool result differs from the recorded one
+        #[arg(long, requires = "live")]
+        assert_results: bool,
+    },
 }
 
 #[tokio::main]
@@ -46,7 +127,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         _ => tracing::Level::INFO,
     };
 
+    // The level filter can be changed at runtime through logging/setLevel
+    let (level_filter, log_level_handle) = reload::Layer::new(LevelFilter::from_level(log_level));
     tracing_subscriber::registry()
+        .with(level_filter)
         .with(
             tracing_subscriber::fmt::layer()
                 .with_writer(std::io::stderr) // Write logs to stderr to avoid interfering with stdio MCP protocol
@@ -55,7 +139,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .with_thread_ids(false)
                 .compact(),
         )
-        .with(tracing_subscriber::filter::LevelFilter::from_level(log_level))
         .init();
 
     info!("Starting chrome-mcp server");
@@ -63,9 +146,105 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Chrome port: {}", args.chrome_port);
     info!("Log level: {}", args.log_level);
 
-    // Create MCP server
-    let mut server = match McpServer::new(&args.chrome_host, args.chrome_port) {
-        Ok(server) => server,
+    // Stopped when dropped at shutdown
+    let virtual_display = if args.use_xvfb {
+        let (width, height) = DEFAULT_SCREEN_SIZE;
+        let virtual_display = VirtualDisplay::start(DEFAULT_DISPLAY_NUM, width, height)?;
+        info!("Virtual display: {}", virtual_display.display());
+        Some(virtual_display)
+    } else {
+        None
+    };
+
+    // Create MCP server on top of the live, recording or replayed CDP connection
+    let log_level = (log_level, log_level_handle);
+    let display = virtual_display.as_ref().map(|virtual_display| virtual_display.display());
+    match (&args.command, &args.record_session, &args.proxy_config) {
+        (Some(Command::Replay { session, live: true, assert_results }), _, _) => {
+            info!("Replaying tool calls from {} against Chrome", session.display());
+            replay_live(&args, session, *assert_results).await?;
+        }
+        (Some(Command::Replay { session, .. }), _, _) => {
+            info!("Replaying CDP session from {}", session.display());
+            let backend = MockCdpClient::from_recording(session)?;
+            serve(Browser::with_cdp_client(backend.client()).map(McpServer::with_browser), &args, log_level, display.as_deref()).await?;
+        }
+        (None, _, Some(path)) => {
+            info!("Proxying tool calls to the routes in {}", path.display());
+            serve(proxy::load_routes(path).map(McpServer::new_proxy), &args, log_level, display.as_deref()).await?;
+        }
+        (None, Some(path), None) => {
+            info!("Recording CDP session to {}", path.display());
+            let backend = RecordingCdpClient::create(WebSocketBackend::new(&args.chrome_host, args.chrome_port), path)?;
+            let writer = backend.session_writer();
+            let browser = Browser::with_cdp_client(CdpClient::with_backend(backend));
+            let server = browser.map(|browser| McpServer::with_browser(browser).with_tool_recording(writer));
+            serve(server, &args, log_level, display.as_deref()).await?;
+        }
+        (None, None, None) => serve(McpServer::new(&args.chrome_host, args.chrome_port), &args, log_level, display.as_deref()).await?,
+    }
+
+    info!("chrome-mcp server shutting down");
+    Ok(())
+}
+
+/// Replay the tool calls of a recorded session against Chrome, printing each result
+async fn replay_live(args: &Args, session: &Path, assert_results: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let steps = ReplaySession::load(session)?;
+    let mut server = McpServer::new(&args.chrome_host, args.chrome_port)?.with_verbose_errors(args.verbose_errors);
+    let outcomes = replay::replay(&mut server, &steps).await?;
+
+    let mut mismatches = 0;
+    for (index, outcome) in outcomes.iter().enumerate() {
+        println!("[{}] {}: {}", index + 1, outcome.tool_name, outcome.result);
+        if assert_results && !outcome.matches() {
+            mismatches += 1;
+            println!("[{}] {} result differs from the recording:\n{}", index + 1, outcome.tool_name, outcome.diff());
+        }
+    }
+
+    if mismatches > 0 {
+        return Err(format!("{} of {} replayed tool results differ from the recording", mismatches, outcomes.len()).into());
+    }
+    Ok(())
+}
+
+/// Configure and run the MCP server, whatever CDP backend it uses
+async fn serve<B: CdpClientBackend>(
+    server: error::Result<McpServer<B>>,
+    args: &Args,
+    (log_level, log_level_handle): (tracing::Level, LogLevelHandle),
+    display: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = match server {
+        Ok(server) => {
+            let server = server
+                .with_verbose_errors(args.verbose_errors)
+                .with_log_level(log_level, log_level_handle);
+            let server = match (&args.screenshot_dir, args.screenshot_max_files) {
+                (None, None) => server,
+                (dir, max_files) => {
+                    let dir = dir.clone().unwrap_or_else(|| PathBuf::from(screenshot::DEFAULT_SCREENSHOT_DIR));
+                    info!("Screenshot directory: {}", dir.display());
+                    server.with_screenshot_auto_save(dir, max_files)
+                }
+            };
+            let server = match args.click_jitter {
+                Some(max_px) => server.with_click_jitter(max_px),
+                None => server,
+            };
+            let server = match display {
+                Some(display) => server.with_native_input_display(display),
+                None => server,
+            };
+            match &args.checkpoint_file {
+                Some(path) => {
+                    info!("Session checkpoint file: {}", path.display());
+                    server.with_checkpoint_file(path)
+                }
+                None => server,
+            }
+        }
         Err(e) => {
             error!("Failed to create MCP server: {}", e);
             return Err(e.into());
@@ -76,7 +255,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Checking Chrome connection...");
     // We'll handle connection errors gracefully in the initialize handler
 
-    if args.stdio {
+    if let Some(listen) = &args.listen {
+        let listener = tokio::net::TcpListener::bind(listen_address(listen)).await?;
+        let address = listener.local_addr()?;
+        if !address.ip().is_loopback() && args.listen_token.is_none() {
+            error!("Refusing to serve MCP on {} without --listen-token", address);
+            return Err(format!("--listen on non-loopback address {} requires --listen-token", address).into());
+        }
+        if let Err(e) = server.run_tcp(listener, args.listen_token.as_deref()).await {
+            error!("MCP server error: {}", e);
+            return Err(e.into());
+        }
+    } else if args.stdio {
         info!("Running MCP server over stdio");
         if let Err(e) = server.run_stdio().await {
             error!("MCP server error: {}", e);
@@ -87,10 +277,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Err("Only stdio mode is currently supported".into());
     }
 
-    info!("chrome-mcp server shutting down");
     Ok(())
 }
 
+/// Address to bind for `--listen`: a bare port is served on loopback only
+fn listen_address(listen: &str) -> String {
+    match listen.parse::<u16>() {
+        Ok(port) => format!("127.0.0.1:{}", port),
+        Err(_) => listen.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,5 +319,73 @@ mod tests {
         assert_eq!(args.chrome_port, 9222);
         assert_eq!(args.log_level, "info");
         assert!(args.stdio);
+        assert!(!args.verbose_errors);
+        assert!(args.checkpoint_file.is_none());
+    }
+
+    #[test]
+    fn test_verbose_errors_flag() {
+        use clap::Parser;
+
+        let args = Args::parse_from(["chrome-mcp", "--verbose-errors"]);
+        assert!(args.verbose_errors);
+    }
+
+    #[test]
+    fn test_record_session_and_replay() {
+        use clap::Parser;
+
+        let args = Args::parse_from(["chrome-mcp", "--record-session", "session.ndjson"]);
+        assert_eq!(args.record_session, Some(PathBuf::from("session.ndjson")));
+        assert!(args.command.is_none());
+
+        let args = Args::parse_from(["chrome-mcp", "replay", "--session", "session.ndjson"]);
+        match args.command {
+            Some(Command::Replay { session, live, assert_results }) => {
+                assert_eq!(session, PathBuf::from("session.ndjson"));
+                assert!(!live && !assert_results);
+            }
+            other => panic!("Expected replay command, got {:?}", other),
+        }
+
+        let args = Args::parse_from(["chrome-mcp", "replay", "--session", "session.ndjson", "--live", "--assert-results"]);
+        assert!(matches!(args.command, Some(Command::Replay { live: true, assert_results: true, .. })));
+
+        assert!(Args::try_parse_from(["chrome-mcp", "replay"]).is_err());
+        assert!(Args::try_parse_from(["chrome-mcp", "replay", "--session", "s.ndjson", "--assert-results"]).is_err());
+    }
+
+    #[test]
+    fn test_proxy_flags() {
+        use clap::Parser;
+
+        let args = Args::parse_from(["chrome-mcp", "--listen", "0.0.0.0:7000", "--listen-token", "secret"]);
+        assert_eq!(args.listen.as_deref(), Some("0.0.0.0:7000"));
+        assert_eq!(args.listen_token.as_deref(), Some("secret"));
+        assert!(args.proxy_config.is_none());
+        assert_eq!(listen_address("0.0.0.0:7000"), "0.0.0.0:7000");
+        assert_eq!(listen_address("7000"), "127.0.0.1:7000");
+        assert!(Args::try_parse_from(["chrome-mcp", "--listen-token", "secret"]).is_err());
+
+        let args = Args::parse_from(["chrome-mcp", "--proxy-config", "routes.json"]);
+        assert_eq!(args.proxy_config, Some(PathBuf::from("routes.json")));
+        assert!(Args::try_parse_from(["chrome-mcp", "--proxy-config", "routes.json", "--record-session", "s.ndjson"]).is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_file_flag() {
+        use clap::Parser;
+
+        let args = Args::parse_from(["chrome-mcp", "--checkpoint-file", "/tmp/chrome-mcp.json"]);
+        assert_eq!(args.checkpoint_file, Some(PathBuf::from("/tmp/chrome-mcp.json")));
+    }
+
+    #[test]
+    fn test_click_jitter_flag() {
+        use clap::Parser;
+
+        let args = Args::parse_from(["chrome-mcp", "--click-jitter", "5"]);
+        assert_eq!(args.click_jitter, Some(5));
+        assert_eq!(Args::parse_from(["chrome-mcp"]).click_jitter, None);
     }
 }
\ No newline at end of file
@@ -0,0 +1,322 @@
+//! HTML to Markdown conversion of page content, for agents that read Markdown more easily
+//! than raw HTML
+
+use ego_tree::NodeRef;
+use scraper::{Html, Node};
+use url::Url;
+
+/// Elements whose content is never part of the readable text
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style", "noscript", "template", "head", "svg", "iframe", "object"];
+
+/// Elements rendered as separate paragraphs
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "body", "dd", "details", "dialog", "div", "dl", "dt", "fieldset",
+    "figcaption", "figure", "footer", "form", "header", "main", "nav", "p", "section", "summary", "table",
+];
+
+/// Convert an HTML fragment to Markdown
+///
+/// Relative link and image URLs are resolved against `base_url` when given. Images are
+/// dropped unless `include_images` is set.
+pub fn html_to_markdown(html: &str, base_url: Option<&str>, include_images: bool) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut converter = Converter {
+        base_url: base_url.and_then(|url| Url::parse(url).ok()),
+        include_images,
+        out: String::new(),
+    };
+    converter.children(*fragment.root_element());
+    converter.finish()
+}
+
+struct Converter {
+    base_url: Option<Url>,
+    include_images: bool,
+    out: String,
+}
+
+impl Converter {
+    /// Converter for nested content, rendered separately and then inserted
+    fn nested(&self) -> Self {
+        Self {
+            base_url: self.base_url.clone(),
+            include_images: self.include_images,
+            out: String::new(),
+        }
+    }
+
+    /// Render the children of `node` into a fresh converter and return the Markdown
+    fn render_children(&self, node: NodeRef<Node>) -> String {
+        let mut nested = self.nested();
+        nested.children(node);
+        nested.finish()
+    }
+
+    fn children(&mut self, node: NodeRef<Node>) {
+        for child in node.children() {
+            self.node(child);
+        }
+    }
+
+    fn node(&mut self, node: NodeRef<Node>) {
+        let element = match node.value() {
+            Node::Text(text) => return self.text(text),
+            Node::Element(element) => element,
+            _ => return,
+        };
+
+        let name = element.name();
+        match name {
+            _ if SKIPPED_ELEMENTS.contains(&name) => {}
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                let text = self.render_children(node).replace('\n', " ");
+                if !text.is_empty() {
+                    self.block(&format!("{} {}", "#".repeat(level), text));
+                }
+            }
+            "br" => {
+                self.trim_trailing_spaces();
+                self.out.push('\n');
+            }
+            "hr" => self.block("---"),
+            "a" => {
+                let text = self.render_children(node);
+                match element.attr("href").map(|href| self.resolve(href)) {
+                    Some(href) if !text.is_empty() && !href.starts_with("javascript:") => {
+                        self.inline(&format!("[{}]({})", text, href))
+                    }
+                    _ => self.inline(&text),
+                }
+            }
+            "img" => {
+                let src = element.attr("src").unwrap_or_default();
+                if self.include_images && !src.is_empty() {
+                    let alt = element.attr("alt").unwrap_or_default();
+                    self.inline(&format!("![{}]({})", alt, self.resolve(src)));
+                }
+            }
+            "strong" | "b" => self.wrap_inline(node, "**"),
+            "em" | "i" => self.wrap_inline(node, "*"),
+            "code" | "kbd" | "samp" => {
+                let code = text_content(node);
+                if !code.trim().is_empty() {
+                    self.inline(&format!("`{}`", code.trim()));
+                }
+            }
+            "pre" => {
+                let language = code_language(node);
+                let code = text_content(node);
+                let fence = if code.contains("```") { "````" } else { "```" };
+                self.block(&format!("{}{}\n{}\n{}", fence, language, code.trim_matches('\n'), fence));
+            }
+            "ul" | "ol" => self.list(node, name == "ol"),
+            "blockquote" => {
+                let quote = self.render_children(node);
+                if !quote.is_empty() {
+                    let quoted: Vec<String> = quote
+                        .lines()
+                        .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {}", line) })
+                        .collect();
+                    self.block(&quoted.join("\n"));
+                }
+            }
+            "tr" | "li" => {
+                self.children(node);
+                self.trim_trailing_spaces();
+                self.out.push('\n');
+            }
+            "td" | "th" => {
+                self.children(node);
+                self.inline(" ");
+            }
+            _ if BLOCK_ELEMENTS.contains(&name) => {
+                let content = self.render_children(node);
+                self.block(&content);
+            }
+            _ => self.children(node),
+        }
+    }
+
+    /// Text with whitespace collapsed, as the browser renders it
+    fn text(&mut self, text: &str) {
+        let mut collapsed = String::with_capacity(text.len());
+        for (index, word) in text.split_whitespace().enumerate() {
+            if index > 0 {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+
+        if text.starts_with(char::is_whitespace) && !self.at_word_boundary() {
+            self.out.push(' ');
+        }
+        self.out.push_str(&collapsed);
+        if !collapsed.is_empty() && text.ends_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+    }
+
+    fn inline(&mut self, markdown: &str) {
+        if markdown.trim().is_empty() {
+            if !self.at_word_boundary() {
+                self.out.push(' ');
+            }
+            return;
+        }
+        self.out.push_str(markdown);
+    }
+
+    fn wrap_inline(&mut self, node: NodeRef<Node>, marker: &str) {
+        let text = self.render_children(node);
+        if !text.is_empty() {
+            self.inline(&format!("{}{}{}", marker, text, marker));
+        }
+    }
+
+    /// Separate `markdown` from the surrounding content by blank lines
+    fn block(&mut self, markdown: &str) {
+        if markdown.is_empty() {
+            return;
+        }
+        self.trim_trailing_spaces();
+        if !self.out.is_empty() {
+            while !self.out.ends_with("\n\n") {
+                self.out.push('\n');
+            }
+        }
+        self.out.push_str(markdown);
+        self.out.push_str("\n\n");
+    }
+
+    fn list(&mut self, node: NodeRef<Node>, ordered: bool) {
+        let mut items = Vec::new();
+        let items_iter = node
+            .children()
+            .filter(|child| child.value().as_element().is_some_and(|e| e.name() == "li"));
+        for (index, item) in items_iter.enumerate() {
+            let marker = if ordered { format!("{}. ", index + 1) } else { "- ".to_string() };
+            let indent = " ".repeat(marker.len());
+            let content = self.render_children(item);
+
+            let mut lines = content.lines();
+            let mut rendered = format!("{}{}", marker, lines.next().unwrap_or_default());
+            for line in lines {
+                rendered.push('\n');
+                if !line.is_empty() {
+                    rendered.push_str(&indent);
+                    rendered.push_str(line);
+                }
+            }
+            items.push(rendered);
+        }
+        self.block(&items.join("\n"));
+    }
+
+    fn resolve(&self, url: &str) -> String {
+        match &self.base_url {
+            Some(base) => base.join(url).map(|u| u.to_string()).unwrap_or_else(|_| url.to_string()),
+            None => url.to_string(),
+        }
+    }
+
+    fn at_word_boundary(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with([' ', '\n'])
+    }
+
+    fn trim_trailing_spaces(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+    }
+
+    /// The Markdown with trailing spaces and extra blank lines removed
+    fn finish(self) -> String {
+        let mut markdown = String::with_capacity(self.out.len());
+        let mut blank_lines = 0;
+        let mut in_fence = false;
+
+        for line in self.out.lines() {
+            let line = if in_fence { line } else { line.trim_end() };
+            if line.starts_with("```") {
+                in_fence = !in_fence;
+            }
+            if line.is_empty() && !in_fence {
+                blank_lines += 1;
+                continue;
+            }
+            if !markdown.is_empty() {
+                markdown.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+            }
+            blank_lines = 0;
+            markdown.push_str(line);
+        }
+
+        markdown
+    }
+}
+
+/// Raw text of all descendants, whitespace preserved
+fn text_content(node: NodeRef<Node>) -> String {
+    node.descendants()
+        .filter_map(|n| n.value().as_text().map(|t| t.to_string()))
+        .collect()
+}
+
+/// Language of a `<pre>` block from a `language-*` class on it or its `<code>` child
+fn code_language(pre: NodeRef<Node>) -> String {
+    pre.descendants()
+        .filter_map(|n| n.value().as_element())
+        .filter(|e| e.name() == "pre" || e.name() == "code")
+        .flat_map(|e| e.classes())
+        .find_map(|class| class.strip_prefix("language-").or_else(|| class.strip_prefix("lang-")))
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = r#"
+            <main>
+                <h1>Release <em>notes</em></h1>
+                <p>Read the <a href="/docs/intro">intro</a> or <strong>skip   ahead</strong>.<br>Run <code>cargo test</code>.</p>
+                <script>console.log("hidden")</script>
+                <img src="logo.png" alt="Logo">
+                <ul>
+                    <li>First</li>
+                    <li>Second
+                        <ol><li>Nested</li></ol>
+                    </li>
+                </ul>
+                <blockquote><p>Quoted</p></blockquote>
+                <pre><code class="language-rust">fn main() {
+    println!("hi");
+}</code></pre>
+            </main>
+        "#;
+
+        let markdown = html_to_markdown(html, Some("https://example.com/blog/post"), true);
+        assert_eq!(
+            markdown,
+            "# Release *notes*\n\n\
+             Read the [intro](https://example.com/docs/intro) or **skip ahead**.\nRun `cargo test`.\n\n\
+             ![Logo](https://example.com/blog/logo.png)\n\n\
+             - First\n- Second\n\n  1. Nested\n\n\
+             > Quoted\n\n\
+             ```rust\nfn main() {\n    println!(\"hi\");\n}\n```"
+        );
+
+        let without_images = html_to_markdown(html, None, false);
+        assert!(!without_images.contains("!["));
+        assert!(without_images.contains("[intro](/docs/intro)"));
+    }
+
+    #[test]
+    fn test_html_to_markdown_skips_empty_and_script_links() {
+        let html = r#"<p><a href="javascript:void(0)">Menu</a> <a href="/x"></a> <b> </b>text</p>"#;
+        assert_eq!(html_to_markdown(html, None, false), "Menu text");
+    }
+}
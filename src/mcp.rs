@@ -1,15 +1,67 @@
-use crate::browser::{Browser, Cookie, PdfOptions, WaitCondition};
-use crate::error::{ChromeMcpError, Result};
-use serde::{Deserialize, Serialize};
+use crate::accessibility::TreeExportFormat;
+use crate::browser::{parse_hex_color, pixel_matches, Browser, Cookie, DEFAULT_CONSOLE_MAX_ENTRIES, DEFAULT_NETWORK_IDLE_MAX_INFLIGHT, DEFAULT_NETWORK_MAX_BODY_BYTES, DEFAULT_NETWORK_MAX_ENTRIES, DragEndpoint, FuzzStrategy, PageResource, PdfOptions, ScrollDirection, SessionState, StorageType, WaitCondition};
+use crate::cdp::{CdpClientBackend, WebSocketBackend};
+use crate::dom_diff::{DiffKind, DomSnapshot, HtmlDiff};
+use crate::error::{ChromeMcpError, Result, TraceFrame};
+use crate::proxy::{ProxyRoute, ProxyRouter};
+use crate::replay::ReplayStep;
+use crate::screenshot::{breakpoint_preset, BREAKPOINT_PRESETS};
+use crate::testing::SessionWriter;
+use crate::tool_registry::{AsyncToolHandler, ToolFuture, ToolRegistry};
+use crate::tool_params::{
+    AccessibilityTreeParams, AuditAction, AuditParams, AuthAction, AuthParams, BroadcastAction, BroadcastParams,
+    CanvasAction, CanvasParams, CheckLinksParams, ClickParams, ClipboardAction, ClipboardParams, ConsoleAction,
+    ConsoleParams, ContentParams, CookiesAction, CookiesParams, CorsTestParams, CpuProfileAction, CpuProfileParams,
+    CssVarsAction, CssVarsParams, DomDiffAction, DomDiffParams, DomHashAction, DomHashParams, DoubleClickParams,
+    DownloadAction, DownloadParams, DragAndDropParams, ElementBoundsParams, ElementStateParams, EvaluateParams,
+    ExtensionDebugAction, ExtensionDebugParams, ExtractAction, ExtractParams, FetchParams, FindParams, FindTextParams,
+    FocusParams, FormAction, FormParams, FrameAction, FrameParams, FullscreenAction, FullscreenParams, FuzzParams,
+    FuzzStrategyKind, GeolocationAction, GeolocationParams, GetAttributeParams, GetFocusedParams, GetHtmlParams,
+    GetTextParams, HistoryAction, HistoryParams, HoverParams, InfiniteScrollParams, KeyboardShortcutParams,
+    LocaleAction, LocaleParams, MarkAction, MarkParams, MenuParams, MockAction, MockParams, NativeAccessibilityParams,
+    NativeClickParams, NavigateParams, NetworkAction, NetworkParams, NoParams, OcrParams, PageSourceKind,
+    PageSourceParams, PdfParams, PdfTextParams, PermissionsAction, PermissionsParams, PopupAction, PopupParams,
+    ProbePortsParams, ReloadParams, ResourcesParams, RightClickParams, ScreenshotAction, ScreenshotParams, ScrollParams,
+    SelectParams, SessionAction, SessionParams, SnapAction, SseParams, StatusAction, StatusParams, StorageAction,
+    StorageParams, SvgAction, SvgParams, TabsAction, TabsParams, TimingAction, TimingParams, ToolParams,
+    TrackPositionParams, TypeParams, UploadFileParams, WaitConditionKind, WaitParams, WasmAction, WasmParams,
+    WebRtcAction, WebRtcParams,
+};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::{json, Value};
 // use std::collections::HashMap;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tracing::{debug, error, info, warn};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tracing::{debug, error, info, warn, Level};
+use tracing_subscriber::{filter::LevelFilter, reload, Registry};
+
+/// Handle to the log level filter of the installed tracing subscriber
+pub type LogLevelHandle = reload::Handle<LevelFilter, Registry>;
+
+/// Batch expressions `chrome_evaluate` runs at once when `parallel` is set
+const EVALUATE_CONCURRENCY: usize = 4;
+
+/// Longest a tool may hold a `Runtime.evaluate` response while the page waits, safely below
+/// the CDP command timeout
+const MAX_PAGE_WAIT_MS: u64 = 25000;
+
+/// Where an `McpServer` runs tool calls
+pub enum ServerMode<B = WebSocketBackend> {
+    /// Against its own browser
+    Local(Box<Browser<B>>),
+    /// Forwarded to downstream chrome-mcp servers, chosen by the active tab URL
+    Proxy(ProxyRouter),
+}
 
 /// MCP Server implementation for Chrome automation
-pub struct McpServer {
-    browser: Browser,
+pub struct McpServer<B = WebSocketBackend> {
+    mode: ServerMode<B>,
     capabilities: ServerCapabilities,
+    verbose_errors: bool,
+    tools: ToolRegistry<B>,
+    log_level: Level,
+    log_level_handle: Option<LogLevelHandle>,
+    tool_recorder: Option<SessionWriter>,
 }
 
 /// MCP Server capabilities
@@ -49,6 +101,9 @@ pub struct ResourcesCapability {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpMessage {
     pub jsonrpc: String,
+    /// Request id, echoed unchanged in the response; `None` for notifications, which have no
+    /// id, as opposed to `Some(Value::Null)` for an explicit `"id": null`
+    #[serde(default, deserialize_with = "deserialize_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<Value>,
     pub method: Option<String>,
     pub params: Option<Value>,
@@ -56,12 +111,44 @@ pub struct McpMessage {
     pub error: Option<McpError>,
 }
 
+/// Keep an explicit `"id": null` apart from a missing id
+fn deserialize_id<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// JSON-RPC request id as it must be echoed back: string and number ids unchanged (a string
+/// id that looks like a number stays a string), `null` as `null`, no id for notifications.
+/// Ids of any other type are invalid and answered with a `null` id.
+pub fn normalize_id(id: Option<Value>) -> Option<Value> {
+    match id {
+        Some(id @ (Value::String(_) | Value::Number(_) | Value::Null)) => Some(id),
+        Some(_) => Some(Value::Null),
+        None => None,
+    }
+}
+
 /// MCP Error
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpError {
     pub code: i32,
     pub message: String,
     pub data: Option<Value>,
+    /// Layers the error passed through; sent to clients inside `data`
+    #[serde(skip)]
+    pub trace: Option<Vec<TraceFrame>>,
+}
+
+impl ChromeMcpError {
+    /// Convert into an MCP error, keeping the trace frames when `include_trace` is set
+    pub fn into_mcp_error_with_trace(self, include_trace: bool) -> McpError {
+        let trace = include_trace.then(|| self.trace().to_vec());
+        McpError {
+            code: -32603, // Internal error
+            message: self.to_string(),
+            data: trace.as_ref().map(|t| json!({ "trace": t })),
+            trace,
+        }
+    }
 }
 
 /// Tool definition
@@ -75,7 +162,22 @@ pub struct Tool {
 impl McpServer {
     /// Create a new MCP server
     pub fn new(chrome_host: &str, chrome_port: u16) -> Result<Self> {
-        let browser = Browser::new(chrome_host, chrome_port)?;
+        Ok(Self::with_browser(Browser::new(chrome_host, chrome_port)?))
+    }
+
+    /// Create an MCP server forwarding tool calls to the downstream servers of `routes`
+    pub fn new_proxy(routes: Vec<ProxyRoute>) -> Self {
+        Self::with_mode(ServerMode::Proxy(ProxyRouter::new(routes)))
+    }
+}
+
+impl<B: CdpClientBackend> McpServer<B> {
+    /// Create an MCP server driving an existing browser, e.g. one with a recording or replay backend
+    pub fn with_browser(browser: Browser<B>) -> Self {
+        Self::with_mode(ServerMode::Local(Box::new(browser)))
+    }
+
+    fn with_mode(mode: ServerMode<B>) -> Self {
         let capabilities = ServerCapabilities {
             tools: Some(ToolsCapability {
                 list_changed: Some(true),
@@ -87,19 +189,128 @@ impl McpServer {
             resources: None,
         };
 
-        Ok(Self {
-            browser,
+        let mut server = Self {
+            mode,
             capabilities,
-        })
+            verbose_errors: false,
+            tools: ToolRegistry::new(),
+            log_level: Level::INFO,
+            log_level_handle: None,
+            tool_recorder: None,
+        };
+        for tool in builtin_tools() {
+            let handler = Box::new(BuiltinTool { name: tool.name.clone() });
+            if let Err(e) = server.register_tool(tool, handler) {
+                warn!("Skipping built-in tool: {}", e);
+            }
+        }
+        server
+    }
+
+    /// Include error trace frames in tool error responses
+    pub fn with_verbose_errors(mut self, verbose_errors: bool) -> Self {
+        self.verbose_errors = verbose_errors;
+        self
+    }
+
+    /// Start at `level` and let `logging/setLevel` change the filter behind `handle`
+    pub fn with_log_level(mut self, level: Level, handle: LogLevelHandle) -> Self {
+        self.log_level_handle = Some(handle);
+        self.log_level = level;
+        if let Some(logging) = self.capabilities.logging.as_mut() {
+            logging.level = Some(mcp_log_level_name(level).to_string());
+        }
+        self
+    }
+
+    /// Record every tool call and a hash of its result, for `chrome-mcp replay --live`
+    pub fn with_tool_recording(mut self, writer: SessionWriter) -> Self {
+        self.tool_recorder = Some(writer);
+        self
+    }
+
+    /// Checkpoint the browser session to a file so it can be resumed after Chrome restarts
+    pub fn with_checkpoint_file(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.mode = match self.mode {
+            ServerMode::Local(browser) => ServerMode::Local(Box::new(browser.with_checkpoint_file(path))),
+            proxy => {
+                warn!("Session checkpoints are not supported in proxy mode");
+                proxy
+            }
+        };
+        self
+    }
+
+    /// Click at a random point up to `max_px` from the element center on every `chrome_click`
+    pub fn with_click_jitter(mut self, max_px: u32) -> Self {
+        match &mut self.mode {
+            ServerMode::Local(browser) => browser.set_click_jitter_enabled(true, max_px),
+            ServerMode::Proxy(_) => warn!("Click jitter is not supported in proxy mode"),
+        }
+        self
+    }
+
+    /// Send native input to the X display `display`, e.g. the one `--use-xvfb` starts
+    pub fn with_native_input_display(mut self, display: &str) -> Self {
+        match &mut self.mode {
+            ServerMode::Local(browser) => browser.set_native_input_display(display),
+            ServerMode::Proxy(_) => warn!("Native input is not supported in proxy mode"),
+        }
+        self
+    }
+
+    /// Auto-save screenshots taken with `auto_save` to `output_dir`, keeping at most `max_files`
+    pub fn with_screenshot_auto_save(mut self, output_dir: impl Into<std::path::PathBuf>, max_files: Option<usize>) -> Self {
+        self.mode = match self.mode {
+            ServerMode::Local(browser) => ServerMode::Local(Box::new(browser.with_screenshot_auto_save(output_dir, max_files))),
+            proxy => {
+                warn!("Screenshot auto-save settings are not supported in proxy mode");
+                proxy
+            }
+        };
+        self
     }
 
     /// Run the MCP server over stdio
     pub async fn run_stdio(&mut self) -> Result<()> {
         info!("Starting chrome-mcp server over stdio");
+        self.serve_lines(BufReader::new(tokio::io::stdin()), tokio::io::stdout()).await
+    }
+
+    /// Serve MCP over TCP, one newline-delimited JSON-RPC connection at a time, e.g. as
+    /// the upstream of a proxy. With a `token`, a connection must open with an `initialize`
+    /// request carrying it in `params.token`, or it is closed.
+    pub async fn run_tcp(&mut self, listener: TcpListener, token: Option<&str>) -> Result<()> {
+        info!("Starting chrome-mcp server on {}", listener.local_addr()?);
+
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            info!("Accepted MCP connection from {}", peer);
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+
+            let mut first_line = String::new();
+            if let Some(token) = token {
+                if let Err(e) = authenticate(&mut reader, &mut writer, &mut first_line, token).await {
+                    warn!("Rejected MCP connection from {}: {}", peer, e);
+                    continue;
+                }
+            }
+            // The authenticated `initialize` is still answered as usual
+            let reader = std::io::Cursor::new(first_line.into_bytes()).chain(reader);
+            if let Err(e) = self.serve_lines(reader, writer).await {
+                warn!("MCP connection from {} failed: {}", peer, e);
+            }
+            info!("MCP connection from {} closed", peer);
+        }
+    }
 
-        let stdin = tokio::io::stdin();
-        let mut stdout = tokio::io::stdout();
-        let mut reader = BufReader::new(stdin);
+    /// Answer newline-delimited JSON-RPC messages from `reader` until it is closed
+    async fn serve_lines<R, W>(&mut self, mut reader: R, mut stdout: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
         let mut buffer = String::new();
 
         loop {
@@ -108,7 +319,7 @@ impl McpServer {
             match reader.read_line(&mut buffer).await {
                 Ok(0) => {
                     // EOF reached
-                    info!("stdin closed, shutting down");
+                    info!("Input closed, shutting down");
                     break;
                 }
                 Ok(_) => {
@@ -134,11 +345,12 @@ impl McpServer {
                         Err(e) => {
                             error!("Error handling message: {}", e);
                             
-                            // Send error response if we can parse the message ID
-                            if let Ok(msg) = serde_json::from_str::<McpMessage>(line) {
+                            // Send error response if we can parse the message ID; notifications get none
+                            let request_id = serde_json::from_str::<McpMessage>(line).ok().and_then(|msg| normalize_id(msg.id));
+                            if let Some(id) = request_id {
                                 let error_response = McpMessage {
                                     jsonrpc: "2.0".to_string(),
-                                    id: msg.id,
+                                    id: Some(id),
                                     method: None,
                                     params: None,
                                     result: None,
@@ -146,6 +358,7 @@ impl McpServer {
                                         code: -32603, // Internal error
                                         message: e.to_string(),
                                         data: None,
+                                        trace: None,
                                     }),
                                 };
 
@@ -158,7 +371,7 @@ impl McpServer {
                     }
                 }
                 Err(e) => {
-                    error!("Error reading from stdin: {}", e);
+                    error!("Error reading MCP input: {}", e);
                     break;
                 }
             }
@@ -169,14 +382,25 @@ impl McpServer {
 
     /// Handle an incoming MCP message
     async fn handle_message(&mut self, message: &str) -> Result<Option<McpMessage>> {
-        let msg: McpMessage = serde_json::from_str(message)
+        let mut msg: McpMessage = serde_json::from_str(message)
             .map_err(|e| ChromeMcpError::mcp_protocol_error(format!("Invalid JSON: {}", e)))?;
+        msg.id = normalize_id(msg.id);
+
+        // Notifications (e.g. notifications/initialized) have no id and must not be answered
+        if msg.id.is_none() {
+            if let Some(method) = &msg.method {
+                debug!("Received notification: {}", method);
+                return Ok(None);
+            }
+        }
 
         match msg.method.as_deref() {
             Some("initialize") => self.handle_initialize(&msg).await,
             Some("tools/list") => self.handle_tools_list(&msg).await,
             Some("tools/call") => self.handle_tools_call(&msg).await,
             Some("ping") => self.handle_ping(&msg).await,
+            Some("logging/setLevel") => self.handle_logging_set_level(&msg).await,
+            Some("logging/getLevel") => self.handle_logging_get_level(&msg).await,
             Some(method) => {
                 warn!("Unknown method: {}", method);
                 Ok(Some(McpMessage {
@@ -189,6 +413,7 @@ impl McpServer {
                         code: -32601, // Method not found
                         message: format!("Method not found: {}", method),
                         data: None,
+                        trace: None,
                     }),
                 }))
             }
@@ -198,665 +423,2182 @@ impl McpServer {
                 Ok(None)
             }
         }
-    }
+    }
+
+    /// Handle initialize request
+    async fn handle_initialize(&mut self, msg: &McpMessage) -> Result<Option<McpMessage>> {
+        info!("Handling initialize request");
+
+        // Connect to Chrome; upstreams of a proxy are connected on their first tool call
+        match self.connect().await {
+            Ok(_) if matches!(self.mode, ServerMode::Proxy(_)) => {}
+            Ok(tab_id) => {
+                info!("Connected to Chrome tab: {}", tab_id);
+            }
+            Err(e) => {
+                warn!("Failed to connect to Chrome: {}", e);
+                // Continue anyway - connection can be retried
+            }
+        }
+
+        Ok(Some(McpMessage {
+            jsonrpc: "2.0".to_string(),
+            id: msg.id.clone(),
+            method: None,
+            params: None,
+            result: Some(json!({
+                "protocolVersion": "1.0.0",
+                "serverInfo": {
+                    "name": "chrome-mcp",
+                    "version": "0.1.0"
+                },
+                "capabilities": self.capabilities
+            })),
+            error: None,
+        }))
+    }
+
+    /// Handle tools/list request
+    async fn handle_tools_list(&self, msg: &McpMessage) -> Result<Option<McpMessage>> {
+        debug!("Handling tools/list request");
+
+        let tools = self.get_available_tools();
+
+        Ok(Some(McpMessage {
+            jsonrpc: "2.0".to_string(),
+            id: msg.id.clone(),
+            method: None,
+            params: None,
+            result: Some(json!({
+                "tools": tools
+            })),
+            error: None,
+        }))
+    }
+
+    /// Handle tools/call request
+    async fn handle_tools_call(&mut self, msg: &McpMessage) -> Result<Option<McpMessage>> {
+        let params = msg.params.as_ref()
+            .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing params in tools/call"))?;
+
+        let name = params.get("name")
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing tool name"))?;
+
+        let default_args = json!({});
+        let arguments = params.get("arguments").unwrap_or(&default_args);
+
+        debug!("Calling tool: {} with args: {}", name, arguments);
+
+        let result = self.call_tool(name, arguments).await;
+        if let Some(recorder) = &self.tool_recorder {
+            if let Err(e) = recorder.write(&ReplayStep::new(name, arguments, &result)) {
+                warn!("Failed to record tool call {}: {}", name, e);
+            }
+        }
+
+        match result {
+            Ok(tool_result) => {
+                Ok(Some(McpMessage {
+                    jsonrpc: "2.0".to_string(),
+                    id: msg.id.clone(),
+                    method: None,
+                    params: None,
+                    result: Some(json!({
+                        "content": [{
+                            "type": "text",
+                            "text": tool_result
+                        }]
+                    })),
+                    error: None,
+                }))
+            }
+            Err(e) => {
+                debug!("Tool {} failed: {:?}", name, e.root_cause());
+                let mut error = e
+                    .in_frame("mcp", "tools/call")
+                    .with_context("tool", name)
+                    .into_mcp_error_with_trace(self.verbose_errors);
+
+                error.message = format!("Tool execution failed: {}", error.message);
+                let mut data = json!({ "tool": name, "arguments": arguments });
+                if let Some(trace) = &error.trace {
+                    data["trace"] = serde_json::to_value(trace)?;
+                }
+                error.data = Some(data);
+
+                Ok(Some(McpMessage {
+                    jsonrpc: "2.0".to_string(),
+                    id: msg.id.clone(),
+                    method: None,
+                    params: None,
+                    result: None,
+                    error: Some(error),
+                }))
+            }
+        }
+    }
+
+    /// Handle ping request
+    async fn handle_ping(&self, msg: &McpMessage) -> Result<Option<McpMessage>> {
+        Ok(Some(McpMessage {
+            jsonrpc: "2.0".to_string(),
+            id: msg.id.clone(),
+            method: None,
+            params: None,
+            result: Some(json!({})),
+            error: None,
+        }))
+    }
+
+    /// Handle logging/setLevel request
+    async fn handle_logging_set_level(&mut self, msg: &McpMessage) -> Result<Option<McpMessage>> {
+        let requested = msg.params.as_ref().and_then(|p| p.get("level")).and_then(|l| l.as_str());
+        let level = match requested.and_then(parse_mcp_log_level) {
+            Some(level) => level,
+            None => {
+                return Ok(Some(McpMessage {
+                    jsonrpc: "2.0".to_string(),
+                    id: msg.id.clone(),
+                    method: None,
+                    params: None,
+                    result: None,
+                    error: Some(McpError {
+                        code: -32602, // Invalid params
+                        message: format!("Invalid log level: {}", requested.unwrap_or("(missing)")),
+                        data: None,
+                        trace: None,
+                    }),
+                }));
+            }
+        };
+
+        if let Some(handle) = &self.log_level_handle {
+            handle
+                .reload(LevelFilter::from_level(level))
+                .map_err(|e| ChromeMcpError::invalid_operation(format!("Could not change log level: {}", e)))?;
+        }
+        self.log_level = level;
+        if let Some(logging) = self.capabilities.logging.as_mut() {
+            logging.level = Some(mcp_log_level_name(level).to_string());
+        }
+        info!("Log level set to {}", level);
+
+        Ok(Some(McpMessage {
+            jsonrpc: "2.0".to_string(),
+            id: msg.id.clone(),
+            method: None,
+            params: None,
+            result: Some(json!({})),
+            error: None,
+        }))
+    }
+
+    /// Handle logging/getLevel request
+    async fn handle_logging_get_level(&self, msg: &McpMessage) -> Result<Option<McpMessage>> {
+        Ok(Some(McpMessage {
+            jsonrpc: "2.0".to_string(),
+            id: msg.id.clone(),
+            method: None,
+            params: None,
+            result: Some(json!({ "level": mcp_log_level_name(self.log_level) })),
+            error: None,
+        }))
+    }
+
+    /// Get list of available tools
+    fn get_available_tools(&self) -> Vec<Tool> {
+        self.tools.tools()
+    }
+
+    /// Register a custom tool, listed by `tools/list` and callable through `tools/call`
+    pub fn register_tool(&mut self, tool: Tool, handler: Box<dyn AsyncToolHandler<B>>) -> Result<()> {
+        self.tools.register(tool, handler)
+    }
+
+    /// Connect the browser to its first tab, as `initialize` does
+    pub async fn connect(&mut self) -> Result<String> {
+        match &mut self.mode {
+            ServerMode::Local(browser) => browser.connect(None).await,
+            ServerMode::Proxy(_) => Ok(String::new()),
+        }
+    }
+
+    /// Execute a tool call
+    pub async fn call_tool(&mut self, name: &str, arguments: &Value) -> Result<String> {
+        let browser = match &mut self.mode {
+            ServerMode::Local(browser) => browser,
+            ServerMode::Proxy(router) => return router.call_tool(name, arguments).await,
+        };
+        let handler = self.tools.handler(name)
+            .ok_or_else(|| ChromeMcpError::mcp_protocol_error(format!("Unknown tool: {}", name)))?;
+        handler.call(arguments, browser).await
+    }
+}
+
+/// Read the first message of a TCP connection into `line` and check that it is an
+/// `initialize` request with `token`; otherwise answer with an error
+async fn authenticate<R, W>(reader: &mut R, writer: &mut W, line: &mut String, token: &str) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    reader.read_line(line).await?;
+    let request: Value = serde_json::from_str(line.trim()).unwrap_or_default();
+    let authorized = request.get("method").and_then(|m| m.as_str()) == Some("initialize")
+        && request.get("params").and_then(|p| p.get("token")).and_then(|t| t.as_str()) == Some(token);
+    if authorized {
+        return Ok(());
+    }
+
+    let response = json!({
+        "jsonrpc": "2.0",
+        "id": request.get("id").cloned().unwrap_or(Value::Null),
+        "error": { "code": -32001, "message": "Unauthorized: initialize with the server's --listen-token" }
+    });
+    writer.write_all(format!("{}\n", response).as_bytes()).await?;
+    writer.flush().await?;
+    Err(ChromeMcpError::mcp_protocol_error("Missing or wrong token in initialize"))
+}
+
+/// Tool implemented by this crate, dispatched by name to `call_builtin_tool`
+struct BuiltinTool {
+    name: String,
+}
+
+impl<B: CdpClientBackend> AsyncToolHandler<B> for BuiltinTool {
+    fn call<'a>(&'a self, arguments: &'a Value, browser: &'a mut Browser<B>) -> ToolFuture<'a> {
+        Box::pin(call_builtin_tool(&self.name, arguments, browser))
+    }
+}
+
+/// Definitions of the tools implemented by this crate
+fn builtin_tools() -> Vec<Tool> {
+    vec![
+        Tool {
+            name: "chrome_navigate".to_string(),
+            description: "Navigate to a URL".to_string(),
+            input_schema: NavigateParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_click".to_string(),
+            description: "Click on an element by CSS selector, text content, or accessibility label".to_string(),
+            input_schema: ClickParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_type".to_string(),
+            description: "Type text into an element or the currently focused element".to_string(),
+            input_schema: TypeParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_screenshot".to_string(),
+            description: "Take a screenshot of the current page".to_string(),
+            input_schema: ScreenshotParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_evaluate".to_string(),
+            description: "Execute JavaScript in the browser, or a batch of expressions sequentially with a delay or in parallel".to_string(),
+            input_schema: EvaluateParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_tabs".to_string(),
+            description: "List, create, or switch between browser tabs, run a tool on every open tab in parallel, or capture tab thumbnails".to_string(),
+            input_schema: TabsParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_scroll".to_string(),
+            description: "Scroll the page or scroll to an element".to_string(),
+            input_schema: ScrollParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_infinite_scroll".to_string(),
+            description: "Scroll until an infinite scroll sentinel element becomes visible, optionally running JavaScript (e.g. clicking \"load more\") when it does".to_string(),
+            input_schema: InfiniteScrollParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_hover".to_string(),
+            description: "Hover over an element".to_string(),
+            input_schema: HoverParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_right_click".to_string(),
+            description: "Right-click an element to open its context menu, optionally selecting a context menu item by name".to_string(),
+            input_schema: RightClickParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_double_click".to_string(),
+            description: "Double-click an element by CSS selector, text content, or accessibility label".to_string(),
+            input_schema: DoubleClickParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_keyboard_shortcut".to_string(),
+            description: "Press a key combination such as Ctrl+C, Cmd+Shift+T, Alt+F4 or Enter".to_string(),
+            input_schema: KeyboardShortcutParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_drag_and_drop".to_string(),
+            description: "Drag an element or point onto another element or point with the mouse, also firing HTML5 drag and drop events".to_string(),
+            input_schema: DragAndDropParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_menu".to_string(),
+            description: "Open a hover or dropdown menu and click an item by following a path of menu item names through submenus".to_string(),
+            input_schema: MenuParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_select".to_string(),
+            description: "Select an option from a dropdown".to_string(),
+            input_schema: SelectParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_upload_file".to_string(),
+            description: "Upload local files through a file input element, without opening the file dialog".to_string(),
+            input_schema: UploadFileParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_wait".to_string(),
+            description: "Wait for a condition to be met".to_string(),
+            input_schema: WaitParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_page_info".to_string(),
+            description: "Get the title, URL and favicon URL of the current page, and the height of any fixed header".to_string(),
+            input_schema: NoParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_cookies".to_string(),
+            description: "Get, set, or clear browser cookies".to_string(),
+            input_schema: CookiesParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_pdf".to_string(),
+            description: "Generate a PDF of the current page".to_string(),
+            input_schema: PdfParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_ocr".to_string(),
+            description: "Read the text in an element or page region (e.g. text rendered into an image) with OCR".to_string(),
+            input_schema: OcrParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_pdf_text".to_string(),
+            description: "Extract the text content of a PDF using pdf.js in the browser".to_string(),
+            input_schema: PdfTextParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_accessibility_tree".to_string(),
+            description: "Get the accessibility tree of the current page".to_string(),
+            input_schema: AccessibilityTreeParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_native_click".to_string(),
+            description: "Click at screen coordinates using native input (for browser chrome)".to_string(),
+            input_schema: NativeClickParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_native_accessibility".to_string(),
+            description: "Inspect the macOS accessibility element at screen coordinates (browser UI, VoiceOver attributes)".to_string(),
+            input_schema: NativeAccessibilityParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_find".to_string(),
+            description: "Find elements by text, role, or selector and return references, most relevant first".to_string(),
+            input_schema: FindParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_content".to_string(),
+            description: "Get the content of the page or an element as Markdown".to_string(),
+            input_schema: ContentParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_check_links".to_string(),
+            description: "Check the links of the page for broken targets".to_string(),
+            input_schema: CheckLinksParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_sse".to_string(),
+            description: "Open a server-sent events stream from the page and collect its events until a count or timeout is reached".to_string(),
+            input_schema: SseParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_probe_ports".to_string(),
+            description: "Security testing: check which ports of a host the page can reach with fetch (internal network / SSRF exposure)".to_string(),
+            input_schema: ProbePortsParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_fetch".to_string(),
+            description: "Send an HTTP request with fetch from the page and return status, response headers, body and whether it came from the HTTP cache".to_string(),
+            input_schema: FetchParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_extract".to_string(),
+            description: "Extract structured data (HTML tables) from the page".to_string(),
+            input_schema: ExtractParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_mock".to_string(),
+            description: "Mock API responses for requests matching a URL pattern".to_string(),
+            input_schema: MockParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_auth".to_string(),
+            description: "Answer HTTP Basic Auth challenges with stored credentials".to_string(),
+            input_schema: AuthParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_resources".to_string(),
+            description: "List resources loaded by the page, or fetch the content of one".to_string(),
+            input_schema: ResourcesParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_webrtc".to_string(),
+            description: "Detect IP addresses leaked through WebRTC, or disable WebRTC".to_string(),
+            input_schema: WebRtcParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_locale".to_string(),
+            description: "Override the page timezone and locale for internationalization testing".to_string(),
+            input_schema: LocaleParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_geolocation".to_string(),
+            description: "Override the position pages get from the Geolocation API (granting them the permission), or restore the real one".to_string(),
+            input_schema: GeolocationParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_find_text".to_string(),
+            description: "Search the page text (including frames) and return match locations".to_string(),
+            input_schema: FindTextParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_element_state".to_string(),
+            description: "Get the checked/disabled/readonly/focus/validity state of an element, optionally setting its checked state first".to_string(),
+            input_schema: ElementStateParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_dom_hash".to_string(),
+            description: "Hash the page structure, or assert it changed since a baseline hash".to_string(),
+            input_schema: DomHashParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_dom_diff".to_string(),
+            description: "Snapshot an element's HTML, or line-diff two snapshots to see what an operation changed".to_string(),
+            input_schema: DomDiffParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_element_bounds".to_string(),
+            description: "Get the current bounding box of an element".to_string(),
+            input_schema: ElementBoundsParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_focus".to_string(),
+            description: "Give keyboard focus to an element".to_string(),
+            input_schema: FocusParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_blur".to_string(),
+            description: "Take keyboard focus away from an element".to_string(),
+            input_schema: FocusParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_get_focused".to_string(),
+            description: "Get the element that has keyboard focus, optionally with its accessibility node (e.g. to check tab order)".to_string(),
+            input_schema: GetFocusedParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_get_text".to_string(),
+            description: "Get the text of an element, or of every element matching a selector".to_string(),
+            input_schema: GetTextParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_get_html".to_string(),
+            description: "Get the outer or inner HTML of an element".to_string(),
+            input_schema: GetHtmlParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_get_attribute".to_string(),
+            description: "Get the value of an element attribute such as href or data-id (null if the element lacks it)".to_string(),
+            input_schema: GetAttributeParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_get_page_source".to_string(),
+            description: "Get the page HTML: the current DOM including JavaScript changes, or the original document as the server sent it".to_string(),
+            input_schema: PageSourceParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_track_position".to_string(),
+            description: "Record an element's bounding box over time (e.g. during animations or layout shifts)".to_string(),
+            input_schema: TrackPositionParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_cpu_profile".to_string(),
+            description: "Record a JavaScript CPU profile and save it for the DevTools Performance tab".to_string(),
+            input_schema: CpuProfileParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_popup".to_string(),
+            description: "Handle popup windows (OAuth, payment flows): wait for, switch to, close, or list popups".to_string(),
+            input_schema: PopupParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_extension_debug".to_string(),
+            description: "List the DevTools panels and other DevTools contexts of browser extensions, switch to one, or run JavaScript in it".to_string(),
+            input_schema: ExtensionDebugParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_form".to_string(),
+            description: "Inspect HTML5 form validation and accessibility: list invalid fields, trigger the browser's validation, or find inputs without a label".to_string(),
+            input_schema: FormParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_cors_test".to_string(),
+            description: "Check whether a server's CORS policy allows requests from an origin".to_string(),
+            input_schema: CorsTestParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_svg".to_string(),
+            description: "Inspect inline SVGs (charts, icons, diagrams): shapes with bounds, text, or path data".to_string(),
+            input_schema: SvgParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_canvas".to_string(),
+            description: "Inspect <canvas> output: read pixels, export as data URL, or compare a region against a color".to_string(),
+            input_schema: CanvasParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_console".to_string(),
+            description: "Observe page activity: record console messages and uncaught exceptions, and the page title history (SPA navigations)".to_string(),
+            input_schema: ConsoleParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_timing".to_string(),
+            description: "Get page load performance timing: navigation phases (DNS, TCP, SSL, TTFB, DOM) or per-resource timings".to_string(),
+            input_schema: TimingParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_network".to_string(),
+            description: "Capture network traffic: start/stop recording requests and responses (optionally with bodies), get them filtered by a URL regex, or get one response with its body decoded by content type (JSON parsed, text as-is, binary as base64)".to_string(),
+            input_schema: NetworkParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_storage".to_string(),
+            description: "Read and write localStorage or sessionStorage, dispatch a synthetic storage event, or listen for storage events".to_string(),
+            input_schema: StorageParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_mark".to_string(),
+            description: "Add timestamped markers to the console and DevTools Performance timeline, or list the page's performance marks".to_string(),
+            input_schema: MarkParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_reload".to_string(),
+            description: "Reload the current page, optionally bypassing the cache or injecting a script into the reloaded page".to_string(),
+            input_schema: ReloadParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_fuzz".to_string(),
+            description: "Fuzz a form field: fill it with random, boundary, SQL injection, XSS or custom inputs, submit its form after each one and report JavaScript errors and navigations".to_string(),
+            input_schema: FuzzParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_audit".to_string(),
+            description: "Audit the current page: find render-blocking scripts and stylesheets that delay the first paint, or JavaScript libraries loaded twice or in conflicting versions".to_string(),
+            input_schema: AuditParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_seo_audit".to_string(),
+            description: "Run an SEO checklist on the current page (title, meta description, headings, image alt texts, robots, canonical, viewport, load speed), errors first".to_string(),
+            input_schema: NoParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_download".to_string(),
+            description: "Choose where downloads are saved, check a download's progress, or wait for it to finish".to_string(),
+            input_schema: DownloadParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_fullscreen".to_string(),
+            description: "Enter or exit fullscreen for the page or an element, or check whether the page is fullscreen".to_string(),
+            input_schema: FullscreenParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_css_vars".to_string(),
+            description: "List CSS custom properties (design tokens) with their computed values and source stylesheets, or override one at runtime".to_string(),
+            input_schema: CssVarsParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_permissions".to_string(),
+            description: "Read the Permissions API state of the page (camera, microphone, notifications, push, geolocation, ...), or grant or deny a permission to its origin".to_string(),
+            input_schema: PermissionsParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_broadcast".to_string(),
+            description: "Test cross-tab messaging: listen for or post BroadcastChannel messages".to_string(),
+            input_schema: BroadcastParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_wasm".to_string(),
+            description: "List the WebAssembly modules the page instantiated with their exports, or call an exported function".to_string(),
+            input_schema: WasmParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_status".to_string(),
+            description: "Show or reset CDP connection metrics: commands and bytes sent and received, and command round-trip times".to_string(),
+            input_schema: StatusParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_clipboard".to_string(),
+            description: "Read the clipboard as text, as a PNG image, or as every item it holds (base64, with text, html and image told apart), or write text to it; grants the page the clipboard permission it needs".to_string(),
+            input_schema: ClipboardParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_history".to_string(),
+            description: "Go back or forward in the session history, or read it (length, current URL and state)".to_string(),
+            input_schema: HistoryParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_frame".to_string(),
+            description: "Switch chrome_evaluate, chrome_click and chrome_type into an iframe, or back to the main frame".to_string(),
+            input_schema: FrameParams::input_schema(),
+        },
+        Tool {
+            name: "chrome_session".to_string(),
+            description: "Export or restore browser session state (cookies, localStorage, sessionStorage, URL)".to_string(),
+            input_schema: SessionParams::input_schema(),
+        },
+    ]
+}
+
+/// Map an MCP (syslog) log level to the closest tracing level
+fn parse_mcp_log_level(level: &str) -> Option<Level> {
+    match level {
+        "debug" => Some(Level::DEBUG),
+        "info" | "notice" => Some(Level::INFO),
+        "warning" => Some(Level::WARN),
+        "error" | "critical" | "alert" | "emergency" => Some(Level::ERROR),
+        _ => None,
+    }
+}
+
+/// MCP log level name of a tracing level
+fn mcp_log_level_name(level: Level) -> &'static str {
+    match level {
+        Level::TRACE | Level::DEBUG => "debug",
+        Level::INFO => "info",
+        Level::WARN => "warning",
+        Level::ERROR => "error",
+    }
+}
+
+/// Start capturing page exceptions when a tool call asks for `fail_on_js_errors`,
+/// using a zero-error budget unless one was configured. Returns whether to check afterwards.
+async fn prepare_js_error_check<B: CdpClientBackend>(browser: &mut Browser<B>, fail_on_js_errors: bool) -> Result<bool> {
+    if !fail_on_js_errors {
+        return Ok(false);
+    }
+
+    if browser.js_error_budget().is_none() {
+        browser.set_js_error_budget(0).await?;
+    }
+    Ok(true)
+}
+
+/// Run one of the tools implemented by this crate
+async fn call_builtin_tool<B: CdpClientBackend>(name: &str, arguments: &Value, browser: &mut Browser<B>) -> Result<String> {
+    match name {
+        "chrome_navigate" => {
+            let params = NavigateParams::from_arguments(arguments)?;
+
+            let check_js_errors = prepare_js_error_check(browser, params.fail_on_js_errors).await?;
+            match params.restore_scroll {
+                Some(pos) => browser.navigate_and_scroll(&params.url, pos.x, pos.y).await?,
+                None if params.keep_scroll => {
+                    let position = browser.remember_scroll().await?;
+                    browser.navigate(&params.url).await?;
+                    browser.restore_scroll(position).await?;
+                }
+                None => browser.navigate(&params.url).await?,
+            }
+            if check_js_errors {
+                browser.check_js_error_budget().await?;
+            }
+            Ok(format!("Navigated to: {}", params.url))
+        }
+
+        "chrome_click" => {
+            let params = ClickParams::from_arguments(arguments)?;
+
+            let check_js_errors = prepare_js_error_check(browser, params.fail_on_js_errors).await?;
+            match params.jitter_px {
+                Some(jitter_px) => browser.click_with_jitter(&params.target, jitter_px).await?,
+                None => browser.click(&params.target).await?,
+            }
+            if check_js_errors {
+                browser.check_js_error_budget().await?;
+            }
+            Ok(format!("Clicked on: {}", params.target))
+        }
+
+        "chrome_type" => {
+            let params = TypeParams::from_arguments(arguments)?;
+
+            let check_js_errors = prepare_js_error_check(browser, params.fail_on_js_errors).await?;
+            match (params.min_delay_ms, params.max_delay_ms) {
+                (None, None) => browser.type_text(&params.text, params.selector.as_deref()).await?,
+                (min_delay_ms, max_delay_ms) => {
+                    if let Some(selector) = &params.selector {
+                        browser.click(selector).await?;
+                    }
+                    let min_delay_ms = min_delay_ms.or(max_delay_ms).unwrap_or_default();
+                    let max_delay_ms = max_delay_ms.unwrap_or(min_delay_ms);
+                    browser.type_text_humanized(&params.text, min_delay_ms, max_delay_ms).await?;
+                }
+            }
+            if check_js_errors {
+                browser.check_js_error_budget().await?;
+            }
+            Ok(format!("Typed text: {}", params.text))
+        }
+
+        "chrome_screenshot" => {
+            let params = ScreenshotParams::from_arguments(arguments)?;
+            let format = params.format.map(|f| f.as_str());
+
+            if params.action == Some(ScreenshotAction::Breakpoints) {
+                let mut widths = params.widths.clone().unwrap_or_default();
+                for preset in params.presets.iter().flatten() {
+                    let width = breakpoint_preset(preset).ok_or_else(|| {
+                        let known: Vec<&str> = BREAKPOINT_PRESETS.iter().map(|(name, _)| *name).collect();
+                        ChromeMcpError::mcp_protocol_error(format!("Unknown breakpoint preset: {} (expected one of {})", preset, known.join(", ")))
+                    })?;
+                    widths.push(width);
+                }
+                if widths.is_empty() {
+                    return Err(ChromeMcpError::mcp_protocol_error("Missing widths or presets parameter"));
+                }
+                widths.sort_unstable();
+                widths.dedup();
+
+                let height = match params.height {
+                    Some(height) => height,
+                    None => browser.viewport_size().await.map(|(_, height)| height as u32).unwrap_or(1080),
+                };
+                let screenshots = browser.screenshot_at_breakpoints(widths, height, format).await?;
+                let results: Vec<Value> = screenshots
+                    .iter()
+                    .map(|s| json!({
+                        "width": s.width,
+                        "screenshot": format!("data:image/{};base64,{}", format.unwrap_or("png"), s.screenshot_b64)
+                    }))
+                    .collect();
+                return Ok(serde_json::to_string_pretty(&results)?);
+            }
+
+            if params.auto_save {
+                let path = browser.auto_save_screenshot(
+                    format,
+                    params.quality,
+                    params.name_template.as_deref(),
+                    params.element.as_deref(),
+                    params.full_element,
+                ).await?;
+                return Ok(serde_json::to_string_pretty(&json!({ "path": path }))?);
+            }
+
+            if let Some(selector) = &params.element {
+                if params.full_element {
+                    let screenshot_data = browser.screenshot_element_full(selector).await?;
+                    return Ok(format!("data:image/png;base64,{}", screenshot_data));
+                }
+                let screenshot_data = browser.screenshot_element(selector).await?;
+                return Ok(format!("data:image/png;base64,{}", screenshot_data));
+            }
+
+            if params.chunked {
+                let chunks = browser.screenshot_full_page_chunked(format, params.quality, params.chunk_height).await?;
+                let data_urls: Vec<String> = chunks
+                    .iter()
+                    .map(|chunk| format!("data:image/{};base64,{}", format.unwrap_or("png"), chunk))
+                    .collect();
+                return Ok(serde_json::to_string_pretty(&data_urls)?);
+            }
+
+            let screenshot_data = if params.full_page {
+                browser.screenshot_full_page(format, params.quality).await?
+            } else {
+                browser.screenshot(format, params.quality).await?
+            };
+            
+            Ok(format!("data:image/{};base64,{}", format.unwrap_or("png"), screenshot_data))
+        }
+
+        "chrome_evaluate" => {
+            let params = EvaluateParams::from_arguments(arguments)?;
+
+            let result = match (params.batch, params.javascript) {
+                (Some(batch), _) if params.parallel => json!(browser.evaluate_batch_parallel(batch, EVALUATE_CONCURRENCY).await?),
+                (Some(batch), _) => json!(browser.evaluate_throttled(batch, params.delay_ms.unwrap_or(0)).await?),
+                (None, Some(javascript)) => browser.evaluate(&javascript).await?,
+                (None, None) => return Err(ChromeMcpError::mcp_protocol_error("Missing javascript or batch parameter")),
+            };
+            Ok(serde_json::to_string_pretty(&result)?)
+        }
+
+        "chrome_tabs" => {
+            let params = TabsParams::from_arguments(arguments)?;
+
+            match params.action {
+                TabsAction::List => {
+                    let tabs = browser.list_tabs().await?;
+                    Ok(serde_json::to_string_pretty(&tabs)?)
+                }
+                TabsAction::Create => {
+                    let tab_id = browser.create_tab(params.url.as_deref()).await?;
+                    Ok(format!("Created tab: {}", tab_id))
+                }
+                TabsAction::Switch => {
+                    let tab_id = params.tab_id
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing tab_id parameter"))?;
+
+                    browser.switch_to_tab(&tab_id).await?;
+                    Ok(format!("Switched to tab: {}", tab_id))
+                }
+                TabsAction::Close => {
+                    let tab_id = params.tab_id
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing tab_id parameter"))?;
+
+                    browser.close_tab(&tab_id).await?;
+                    Ok(format!("Closed tab: {}", tab_id))
+                }
+                TabsAction::Map => {
+                    let tool_name = params.tool_name
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing tool_name parameter"))?;
+                    if tool_name == "chrome_tabs" {
+                        return Err(ChromeMcpError::mcp_protocol_error("chrome_tabs cannot be mapped over tabs"));
+                    }
+                    let tool_arguments = Value::Object(params.tool_arguments.unwrap_or_default());
+
+                    // A failing tab is reported in its result instead of cancelling the other tabs
+                    let results = browser.map_tabs(move |mut tab_browser| {
+                        let tool_name = tool_name.clone();
+                        let tool_arguments = tool_arguments.clone();
+                        async move {
+                            let tab_id = tab_browser.current_tab_id().unwrap_or_default().to_string();
+                            Ok(match call_builtin_tool(&tool_name, &tool_arguments, &mut tab_browser).await {
+                                Ok(result) => json!({ "tab_id": tab_id, "result": result }),
+                                Err(e) => json!({ "tab_id": tab_id, "error": e.to_string() }),
+                            })
+                        }
+                    }).await?;
+                    Ok(serde_json::to_string_pretty(&results)?)
+                }
+                TabsAction::Thumbnails => {
+                    let max_width = params.max_width.unwrap_or(320);
+                    let max_height = params.max_height.unwrap_or(200);
+
+                    let thumbnails: Vec<Value> = browser.capture_all_tab_thumbnails(max_width, max_height).await?
+                        .into_iter()
+                        .map(|(tab, thumbnail)| json!({
+                            "tab_id": tab.id,
+                            "title": tab.title,
+                            "url": tab.url,
+                            "thumbnail": thumbnail
+                        }))
+                        .collect();
+                    Ok(serde_json::to_string_pretty(&thumbnails)?)
+                }
+            }
+        }
+
+        "chrome_scroll" => {
+            let params = ScrollParams::from_arguments(arguments)?;
+
+            if let Some(action) = params.action {
+                let container = params.container
+                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing container parameter"))?;
+
+                let direction = match action {
+                    SnapAction::NextSnap => ScrollDirection::Right,
+                    SnapAction::PrevSnap => ScrollDirection::Left,
+                };
+
+                browser.scroll_to_next_snap(&container, direction).await?;
+                // Let the smooth scroll settle before reading the active item
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                let active = browser.get_active_snap_item(&container).await?;
+                Ok(serde_json::to_string_pretty(&active)?)
+            } else if let Some(selector) = params.selector {
+                browser.scroll_to_element(&selector).await?;
+                Ok(format!("Scrolled to element: {}", selector))
+            } else {
+                let x = params.x.unwrap_or(0);
+                let y = params.y.unwrap_or(0);
+
+                browser.scroll(x, y).await?;
+                Ok(format!("Scrolled by: ({}, {})", x, y))
+            }
+        }
+
+        "chrome_infinite_scroll" => {
+            let params = InfiniteScrollParams::from_arguments(arguments)?;
+
+            let result = browser.observe_intersection(
+                &params.selector,
+                params.on_intersect_js.as_deref(),
+                params.timeout_ms.unwrap_or(10_000).min(MAX_PAGE_WAIT_MS),
+            ).await?;
+            Ok(serde_json::to_string_pretty(&result)?)
+        }
+
+        "chrome_hover" => {
+            let params = HoverParams::from_arguments(arguments)?;
+
+            browser.hover(&params.target).await?;
+            Ok(format!("Hovered over: {}", params.target))
+        }
+
+        "chrome_right_click" => {
+            let params = RightClickParams::from_arguments(arguments)?;
+
+            browser.right_click(&params.target).await?;
+            match params.context_menu_item {
+                Some(item) => {
+                    browser.select_context_menu_item(&item).await?;
+                    Ok(format!("Right-clicked {} and selected: {}", params.target, item))
+                }
+                None => Ok(format!("Right-clicked: {}", params.target)),
+            }
+        }
+
+        "chrome_double_click" => {
+            let params = DoubleClickParams::from_arguments(arguments)?;
+
+            match params.delay_ms {
+                Some(delay_ms) => browser.double_click_with_delay(&params.target, delay_ms).await?,
+                None => browser.double_click(&params.target).await?,
+            }
+            Ok(format!("Double-clicked: {}", params.target))
+        }
+
+        "chrome_keyboard_shortcut" => {
+            let params = KeyboardShortcutParams::from_arguments(arguments)?;
+
+            browser.press_shortcut(&params.shortcut).await?;
+            Ok(format!("Pressed: {}", params.shortcut))
+        }
+
+        "chrome_drag_and_drop" => {
+            let params = DragAndDropParams::from_arguments(arguments)?;
+
+            let endpoint = |selector: &Option<String>, x: Option<f64>, y: Option<f64>| match (selector, x, y) {
+                (Some(selector), None, None) => Some(DragEndpoint::Element(selector.clone())),
+                (None, Some(x), Some(y)) => Some(DragEndpoint::Point(x, y)),
+                _ => None,
+            };
+            let source = endpoint(&params.source_selector, params.source_x, params.source_y)
+                .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Provide either source_selector or source_x and source_y"))?;
+            let target = endpoint(&params.target_selector, params.target_x, params.target_y)
+                .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Provide either target_selector or target_x and target_y"))?;
+
+            browser.drag_and_drop(&source, &target, params.duration_ms.unwrap_or(500)).await?;
+            let describe = |endpoint: &DragEndpoint| match endpoint {
+                DragEndpoint::Element(query) => query.clone(),
+                DragEndpoint::Point(x, y) => format!("({}, {})", x, y),
+            };
+            Ok(format!("Dragged {} to {}", describe(&source), describe(&target)))
+        }
+
+        "chrome_select" => {
+            let params = SelectParams::from_arguments(arguments)?;
+
+            browser.select_option(&params.selector, &params.value).await?;
+            Ok(format!("Selected '{}' in {}", params.value, params.selector))
+        }
+
+        "chrome_upload_file" => {
+            let params = UploadFileParams::from_arguments(arguments)?;
+
+            let files: Vec<String> = params.file_path.into_iter().chain(params.files.unwrap_or_default()).collect();
+            if files.is_empty() {
+                return Err(ChromeMcpError::mcp_protocol_error("Provide file_path or files"));
+            }
+            browser.upload_files(&params.selector, &files).await?;
+            Ok(format!("Uploaded {} to {}", files.join(", "), params.selector))
+        }
+
+        "chrome_menu" => {
+            let params = MenuParams::from_arguments(arguments)?;
+
+            browser.navigate_menu(&params.trigger, params.path.iter().map(|p| p.as_str()).collect()).await?;
+            Ok(format!("Clicked menu item {} > {}", params.trigger, params.path.join(" > ")))
+        }
+
+        "chrome_wait" => {
+            let params = WaitParams::from_arguments(arguments)?;
+            let condition_name = json!(params.condition);
+
+            let target = params.target.clone().unwrap_or_default();
+            let condition = match params.condition {
+                WaitConditionKind::ElementPresent => WaitCondition::ElementPresent(target),
+                WaitConditionKind::ElementVisible => WaitCondition::ElementVisible(target),
+                WaitConditionKind::ElementVisibleRatio => {
+                    WaitCondition::ElementVisibleRatio(target, params.min_ratio.unwrap_or(0.5))
+                }
+                WaitConditionKind::ElementClickable => WaitCondition::ElementClickable(target),
+                WaitConditionKind::TextPresent => WaitCondition::TextPresent(target),
+                WaitConditionKind::UrlMatches => WaitCondition::UrlMatches(target),
+                WaitConditionKind::TitleContains => WaitCondition::TitleContains(target),
+                WaitConditionKind::TitleEquals => WaitCondition::TitleEquals(target),
+                WaitConditionKind::TitleMatches => WaitCondition::TitleMatches(target),
+                WaitConditionKind::TitleChangedFrom => {
+                    let original = match params.target {
+                        Some(original) => original,
+                        None => browser.get_current_title().await?,
+                    };
+                    WaitCondition::TitleChangedFrom(original)
+                }
+                WaitConditionKind::PageLoad => WaitCondition::PageLoad,
+                WaitConditionKind::NetworkIdle => WaitCondition::NetworkIdle {
+                    idle_ms: 1000,
+                    max_inflight: params.max_inflight.unwrap_or(DEFAULT_NETWORK_IDLE_MAX_INFLIGHT),
+                },
+                WaitConditionKind::RequestCompleted => WaitCondition::RequestCompleted {
+                    url_pattern: target,
+                    method: params.method,
+                },
+                WaitConditionKind::RequestFailed => WaitCondition::RequestFailed { url_pattern: target },
+            };
+
+            browser.wait_for_condition(condition, params.timeout.unwrap_or(10000)).await?;
+            Ok(format!("Wait condition '{}' satisfied", condition_name.as_str().unwrap_or_default()))
+        }
+
+        "chrome_page_info" => {
+            NoParams::from_arguments(arguments)?;
+            let info = browser.page_info().await?;
+            Ok(serde_json::to_string_pretty(&info)?)
+        }
+
+        "chrome_cookies" => {
+            let params = CookiesParams::from_arguments(arguments)?;
+
+            match params.action {
+                CookiesAction::Get => {
+                    let url = match params.url {
+                        Some(url) => url,
+                        None => browser.current_url().await?,
+                    };
+
+                    let mut cookies = browser.get_cookies_for_url(&url).await?;
+                    if let Some(name) = params.name {
+                        cookies.retain(|cookie| cookie.name == name);
+                    }
+                    Ok(serde_json::to_string_pretty(&cookies)?)
+                }
+                CookiesAction::Set => {
+                    let name = params.name
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing name parameter"))?;
+
+                    let value = params.value
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing value parameter"))?;
+
+                    let cookie = Cookie {
+                        name: name.clone(),
+                        value: value.clone(),
+                        domain: params.domain.unwrap_or_else(|| "localhost".to_string()),
+                        path: params.path.unwrap_or_else(|| "/".to_string()),
+                        secure: false,
+                        http_only: false,
+                        same_site: None,
+                        expires: None,
+                    };
+                    
+                    browser.set_cookie(cookie).await?;
+                    Ok(format!("Set cookie: {} = {}", name, value))
+                }
+                CookiesAction::Clear => {
+                    browser.clear_cookies().await?;
+                    Ok("Cleared all cookies".to_string())
+                }
+            }
+        }
+
+        "chrome_pdf" => {
+            let params = PdfParams::from_arguments(arguments)?;
+
+            let options = if params.landscape.is_some() || params.print_background.is_some() || params.scale.is_some() {
+                Some(PdfOptions {
+                    landscape: params.landscape,
+                    print_background: params.print_background,
+                    scale: params.scale,
+                    ..Default::default()
+                })
+            } else {
+                None
+            };
+            
+            let pdf_data = browser.pdf(options).await?;
+            Ok(format!("data:application/pdf;base64,{}", pdf_data))
+        }
+
+        "chrome_ocr" => {
+            let params = OcrParams::from_arguments(arguments)?;
+
+            let text = match (params.selector, params.region) {
+                (Some(selector), None) => browser.ocr_element(&selector).await?,
+                (None, Some(region)) => browser.ocr_region(region.x, region.y, region.width, region.height).await?,
+                _ => return Err(ChromeMcpError::mcp_protocol_error("Provide either selector or region")),
+            };
+            Ok(serde_json::to_string_pretty(&json!({ "text": text }))?)
+        }
+
+        "chrome_pdf_text" => {
+            let params = PdfTextParams::from_arguments(arguments)?;
+
+            // Accept data URLs as returned by chrome_pdf
+            let data = params.data.strip_prefix("data:application/pdf;base64,").unwrap_or(&params.data);
+            browser.extract_pdf_text(data, params.page_separator.as_deref()).await
+        }
+
+        "chrome_accessibility_tree" => {
+            let params = AccessibilityTreeParams::from_arguments(arguments)?;
+
+            let format = match params.format {
+                Some(format) => format,
+                None if params.summary => TreeExportFormat::TextOutline,
+                None => TreeExportFormat::Json,
+            };
+            browser.accessibility().export_tree(format, params.depth).await
+        }
+
+        "chrome_native_click" => {
+            let params = NativeClickParams::from_arguments(arguments)?;
+
+            browser.native_click(params.x, params.y).await?;
+            Ok(format!("Native click at ({}, {})", params.x, params.y))
+        }
+
+        "chrome_native_accessibility" => {
+            let params = NativeAccessibilityParams::from_arguments(arguments)?;
+
+            let element = browser.native_accessibility_element(params.x, params.y).await?;
+            Ok(serde_json::to_string_pretty(&element)?)
+        }
+
+        "chrome_find" => {
+            let params = FindParams::from_arguments(arguments)?;
+
+            let mut elements = browser.find_elements(&params.query).await?;
+            elements.truncate(params.max_results.unwrap_or(10));
+            if params.generate_selector {
+                browser.add_stable_selectors(&mut elements).await;
+            }
+            Ok(serde_json::to_string_pretty(&elements)?)
+        }
+
+        "chrome_content" => {
+            let params = ContentParams::from_arguments(arguments)?;
+
+            match params.selector.as_deref() {
+                Some(selector) => browser.get_element_as_markdown(selector, params.include_images).await,
+                None => browser.get_page_as_markdown(params.include_images).await,
+            }
+        }
+
+        "chrome_check_links" => {
+            let params = CheckLinksParams::from_arguments(arguments)?;
+
+            let selectors: Option<Vec<&str>> = params.selectors.as_ref().map(|s| s.iter().map(|s| s.as_str()).collect());
+            let results = browser.check_links(
+                selectors,
+                params.concurrent.unwrap_or(5),
+                params.include_external,
+                params.timeout_ms_per_link.unwrap_or(10_000),
+            ).await?;
+            Ok(serde_json::to_string_pretty(&results)?)
+        }
+
+        "chrome_sse" => {
+            let params = SseParams::from_arguments(arguments)?;
+            let event_types: Vec<&str> = params.events.iter().flatten().map(|e| e.as_str()).collect();
+
+            let events = browser.capture_sse_events(
+                &params.url,
+                event_types,
+                params.max_events.unwrap_or(10),
+                params.timeout_ms.unwrap_or(10_000).min(MAX_PAGE_WAIT_MS),
+            ).await?;
+            Ok(serde_json::to_string_pretty(&events)?)
+        }
+
+        "chrome_probe_ports" => {
+            let params = ProbePortsParams::from_arguments(arguments)?;
+
+            let results = browser.probe_local_ports(&params.host, params.ports, params.timeout_ms.unwrap_or(2000)).await?;
+            Ok(serde_json::to_string_pretty(&results)?)
+        }
+
+        "chrome_fetch" => {
+            let params = FetchParams::from_arguments(arguments)?;
+
+            let result = browser.fetch_with_headers(
+                &params.url,
+                params.headers.unwrap_or_default(),
+                params.method.as_deref().unwrap_or("GET"),
+                params.body.as_deref(),
+                params.follow_redirects.unwrap_or(true),
+            ).await?;
+            Ok(serde_json::to_string_pretty(&result)?)
+        }
+
+        "chrome_extract" => {
+            let params = ExtractParams::from_arguments(arguments)?;
+
+            match params.action {
+                ExtractAction::Table => {
+                    let selector = params.selector
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing selector parameter"))?;
+
+                    let table = browser.extract_table(&selector).await?;
+                    Ok(serde_json::to_string_pretty(&table)?)
+                }
+                ExtractAction::Tables => {
+                    let tables = browser.extract_tables().await?;
+                    Ok(serde_json::to_string_pretty(&tables)?)
+                }
+            }
+        }
+
+        "chrome_mock" => {
+            let params = MockParams::from_arguments(arguments)?;
+            let missing_pattern = || ChromeMcpError::mcp_protocol_error("Missing url_pattern parameter");
+
+            match params.action {
+                MockAction::Add => {
+                    let url_pattern = params.url_pattern.as_deref().ok_or_else(missing_pattern)?;
+                    let status = params.status.unwrap_or(200);
+                    let body = params.body.as_deref().unwrap_or("");
+                    let content_type = params.content_type.as_deref().unwrap_or("application/json");
+
+                    browser.mock_api_response(url_pattern, status, body, content_type).await?;
+                    Ok(format!("Mocking {} with status {}", url_pattern, status))
+                }
+                MockAction::AddFromFile => {
+                    let path = params.path
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing path parameter"))?;
+
+                    let count = browser.mock_api_responses_from_fixture(&path).await?;
+                    Ok(format!("Registered {} mocks from {}", count, path))
+                }
+                MockAction::Remove => {
+                    let url_pattern = params.url_pattern.as_deref().ok_or_else(missing_pattern)?;
+
+                    if browser.clear_mock(url_pattern).await? {
+                        Ok(format!("Removed mock: {}", url_pattern))
+                    } else {
+                        Ok(format!("No mock registered for: {}", url_pattern))
+                    }
+                }
+                MockAction::List => {
+                    Ok(serde_json::to_string_pretty(&browser.list_mocks())?)
+                }
+                MockAction::Clear => {
+                    browser.clear_all_mocks().await?;
+                    Ok("Cleared all mocks".to_string())
+                }
+            }
+        }
+
+        "chrome_auth" => {
+            let params = AuthParams::from_arguments(arguments)?;
+
+            match params.action {
+                AuthAction::SetBasicAuth => {
+                    let username = params.username
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing username parameter"))?;
+                    let password = params.password
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing password parameter"))?;
+                    let host = params.host.as_deref();
+
+                    browser.set_http_auth_credentials(&username, &password, host, params.realm.as_deref()).await?;
+                    Ok(format!("Basic auth credentials set for host: {}", host.unwrap_or("*")))
+                }
+                AuthAction::ClearAuth => {
+                    browser.clear_http_auth_credentials().await?;
+                    Ok("Cleared all auth credentials".to_string())
+                }
+            }
+        }
+
+        "chrome_resources" => {
+            let params = ResourcesParams::from_arguments(arguments)?;
+
+            if let Some(url) = params.url {
+                return browser.get_resource_content(&url).await;
+            }
+
+            let resources: Vec<PageResource> = browser.get_resource_tree().await?
+                .into_iter()
+                .filter(|r| params.type_filter.is_none_or(|t| r.type_ == t))
+                .collect();
+
+            Ok(serde_json::to_string_pretty(&resources)?)
+        }
+
+        "chrome_webrtc" => {
+            let params = WebRtcParams::from_arguments(arguments)?;
+
+            match params.action {
+                WebRtcAction::DetectIps => {
+                    let ips = browser.detect_webrtc_ips().await?;
+                    Ok(serde_json::to_string_pretty(&ips)?)
+                }
+                WebRtcAction::Disable => {
+                    browser.disable_webrtc().await?;
+                    Ok("WebRTC disabled".to_string())
+                }
+            }
+        }
+
+        "chrome_locale" => {
+            let params = LocaleParams::from_arguments(arguments)?;
+
+            match params.action {
+                LocaleAction::SetTimezone => {
+                    let timezone = params.timezone
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing timezone parameter"))?;
+
+                    browser.set_timezone(&timezone).await?;
+                    Ok(format!("Timezone set to: {}", timezone))
+                }
+                LocaleAction::SetLocale => {
+                    let locale = params.locale
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing locale parameter"))?;
+
+                    browser.set_locale(&locale).await?;
+                    Ok(format!("Locale set to: {}", locale))
+                }
+                LocaleAction::Reset => {
+                    browser.reset_locale_and_timezone().await?;
+                    Ok("Locale and timezone reset".to_string())
+                }
+            }
+        }
+
+        "chrome_geolocation" => {
+            let params = GeolocationParams::from_arguments(arguments)?;
+
+            match params.action {
+                GeolocationAction::Set => {
+                    let latitude = params.latitude
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing latitude parameter"))?;
+                    let longitude = params.longitude
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing longitude parameter"))?;
+                    let accuracy = params.accuracy.unwrap_or(100.0);
+
+                    browser.set_geolocation(latitude, longitude, accuracy).await?;
+                    browser.grant_geolocation_permission().await?;
+                    Ok(format!("Geolocation set to {}, {} (accuracy {}m)", latitude, longitude, accuracy))
+                }
+                GeolocationAction::Clear => {
+                    browser.clear_geolocation().await?;
+                    Ok("Geolocation override cleared".to_string())
+                }
+            }
+        }
+
+        "chrome_find_text" => {
+            let params = FindTextParams::from_arguments(arguments)?;
+
+            let pattern = if params.use_regex {
+                params.pattern
+            } else {
+                regex::escape(&params.pattern)
+            };
+
+            let matches = browser.search_page_text(&pattern, params.case_sensitive).await?;
+            Ok(serde_json::to_string_pretty(&matches)?)
+        }
+
+        "chrome_element_state" => {
+            let params = ElementStateParams::from_arguments(arguments)?;
+
+            if let Some(checked) = params.checked {
+                browser.set_checked(&params.selector, checked).await?;
+            }
+
+            let state = browser.get_element_state(&params.selector).await?;
+            Ok(serde_json::to_string_pretty(&state)?)
+        }
+
+        "chrome_dom_diff" => {
+            let params = DomDiffParams::from_arguments(arguments)?;
+
+            match params.action.unwrap_or(DomDiffAction::Diff) {
+                DomDiffAction::Snapshot => {
+                    let snapshot = browser.take_dom_snapshot(params.selector.as_deref()).await?;
+                    Ok(serde_json::to_string_pretty(&json!({ "label": params.label, "snapshot": snapshot }))?)
+                }
+                DomDiffAction::Diff => {
+                    let before = params.before.map(DomSnapshot)
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing before parameter"))?;
+                    let after = params.after.map(DomSnapshot)
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing after parameter"))?;
+
+                    let diff: Vec<HtmlDiff> = before
+                        .diff(&after)
+                        .into_iter()
+                        .filter(|d| params.include_equal || d.kind != DiffKind::Equal)
+                        .collect();
+                    Ok(serde_json::to_string_pretty(&diff)?)
+                }
+            }
+        }
+
+        "chrome_dom_hash" => {
+            let params = DomHashParams::from_arguments(arguments)?;
+            let selector = params.selector.as_deref();
+
+            match params.action {
+                DomHashAction::Compute => {
+                    let hash = browser.compute_dom_hash(selector).await?;
+                    // Hashes are returned as strings since they exceed the JSON safe integer range
+                    Ok(serde_json::to_string_pretty(&json!({ "hash": hash.to_string() }))?)
+                }
+                DomHashAction::AssertChanged => {
+                    let baseline_hash = params.baseline_hash.as_deref()
+                        .and_then(|h| h.parse::<u64>().ok())
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing or invalid baseline_hash parameter"))?;
+
+                    let hash = match params.timeout_ms {
+                        Some(timeout_ms) => browser.wait_for_dom_change(baseline_hash, selector, timeout_ms).await?,
+                        None => {
+                            browser.assert_dom_changed(baseline_hash, selector).await?;
+                            browser.compute_dom_hash(selector).await?
+                        }
+                    };
+
+                    Ok(serde_json::to_string_pretty(&json!({ "changed": true, "hash": hash.to_string() }))?)
+                }
+            }
+        }
+
+        "chrome_focus" => {
+            let params = FocusParams::from_arguments(arguments)?;
+
+            browser.focus_element(&params.selector).await?;
+            Ok(format!("Focused: {}", params.selector))
+        }
+
+        "chrome_blur" => {
+            let params = FocusParams::from_arguments(arguments)?;
+
+            browser.blur_element(&params.selector).await?;
+            Ok(format!("Blurred: {}", params.selector))
+        }
+
+        "chrome_get_focused" => {
+            let params = GetFocusedParams::from_arguments(arguments)?;
+
+            let element = browser.get_focused_element().await?;
+            let mut result = json!({ "element": element });
+            if params.include_accessibility.unwrap_or(false) {
+                result["accessibility_node"] = json!(browser.accessibility().get_focused_node().await?);
+            }
+            Ok(serde_json::to_string_pretty(&result)?)
+        }
+
+        "chrome_get_text" => {
+            let params = GetTextParams::from_arguments(arguments)?;
+            let include_hidden = params.include_hidden.unwrap_or(false);
+
+            if params.all.unwrap_or(false) {
+                let texts = browser.get_elements_text(&params.selector, include_hidden).await?;
+                Ok(serde_json::to_string_pretty(&texts)?)
+            } else {
+                browser.get_element_text(&params.selector, include_hidden).await
+            }
+        }
+
+        "chrome_get_html" => {
+            let params = GetHtmlParams::from_arguments(arguments)?;
+
+            browser.get_element_html(&params.selector, params.outer.unwrap_or(true)).await
+        }
+
+        "chrome_get_attribute" => {
+            let params = GetAttributeParams::from_arguments(arguments)?;
+
+            let value = browser.get_element_attribute(&params.selector, &params.attribute).await?;
+            Ok(serde_json::to_string_pretty(&json!({
+                "selector": params.selector,
+                "attribute": params.attribute,
+                "value": value
+            }))?)
+        }
+
+        "chrome_get_page_source" => {
+            let params = PageSourceParams::from_arguments(arguments)?;
+
+            match params.source.unwrap_or(PageSourceKind::Dom) {
+                PageSourceKind::Dom => browser.get_page_source().await,
+                PageSourceKind::Network => browser.get_network_source().await,
+            }
+        }
+
+        "chrome_element_bounds" => {
+            let params = ElementBoundsParams::from_arguments(arguments)?;
+
+            let bounds = browser.get_element_bounds(&params.selector).await?;
+            Ok(serde_json::to_string_pretty(&json!({
+                "x": bounds.x,
+                "y": bounds.y,
+                "width": bounds.width,
+                "height": bounds.height
+            }))?)
+        }
+
+        "chrome_track_position" => {
+            let params = TrackPositionParams::from_arguments(arguments)?;
+
+            let interval_ms = params.interval_ms.unwrap_or(16);
+            let duration_ms = params.duration_ms.unwrap_or(1000);
+
+            let samples = browser.track_element_position(&params.selector, interval_ms, duration_ms).await?;
+            Ok(serde_json::to_string_pretty(&samples)?)
+        }
+
+        "chrome_cpu_profile" => {
+            let params = CpuProfileParams::from_arguments(arguments)?;
+
+            match params.action {
+                CpuProfileAction::Start => {
+                    let interval = params.sample_interval_us.unwrap_or(1000);
+                    browser.start_cpu_profile(interval).await?;
+                    Ok(format!("CPU profiling started (sampling every {}us)", interval))
+                }
+                CpuProfileAction::Stop => {
+                    let profile = browser.stop_cpu_profile().await?;
+                    Ok(serde_json::to_string_pretty(&json!({
+                        "duration_ms": profile.duration_ms(),
+                        "node_count": profile.nodes.len(),
+                        "sample_count": profile.samples.as_ref().map_or(0, |s| s.len())
+                    }))?)
+                }
+                CpuProfileAction::Save => {
+                    let filename = params.filename
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing filename parameter"))?;
+
+                    let profile = browser.last_cpu_profile()
+                        .ok_or_else(|| ChromeMcpError::invalid_operation("No CPU profile recorded; stop a profile first"))?;
+
+                    browser.save_cpu_profile(profile, &filename)?;
+                    Ok(format!("CPU profile saved to: {}", filename))
+                }
+            }
+        }
+
+        "chrome_extension_debug" => {
+            let params = ExtensionDebugParams::from_arguments(arguments)?;
+
+            match params.action {
+                ExtensionDebugAction::List => {
+                    let targets = browser.list_devtools_targets().await?;
+                    Ok(serde_json::to_string_pretty(&targets)?)
+                }
+                ExtensionDebugAction::Switch => {
+                    let target_id = params.target_id
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing target_id parameter"))?;
+                    browser.switch_to_devtools_target(&target_id).await?;
+                    Ok(format!("Switched to DevTools target: {}", target_id))
+                }
+                ExtensionDebugAction::Evaluate => {
+                    let javascript = params.javascript
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing javascript parameter"))?;
+                    let result = browser.evaluate_in_devtools_target(&javascript, params.target_id.as_deref()).await?;
+                    Ok(serde_json::to_string_pretty(&result)?)
+                }
+            }
+        }
+
+        "chrome_popup" => {
+            let params = PopupParams::from_arguments(arguments)?;
+
+            match params.action {
+                PopupAction::Wait => {
+                    let url_pattern = params.url_pattern.as_deref().unwrap_or("*");
+                    let timeout_ms = params.timeout_ms.unwrap_or(10000);
+
+                    let popup_id = browser.wait_for_popup(url_pattern, timeout_ms).await?;
+                    if params.switch {
+                        browser.switch_to_popup(&popup_id).await?;
+                        Ok(format!("Switched to popup: {}", popup_id))
+                    } else {
+                        Ok(format!("Popup opened: {}", popup_id))
+                    }
+                }
+                PopupAction::Switch => {
+                    let tab_id = params.tab_id
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing tab_id parameter"))?;
+
+                    browser.switch_to_popup(&tab_id).await?;
+                    Ok(format!("Switched to popup: {}", tab_id))
+                }
+                PopupAction::Close => {
+                    let opener = browser.close_popup_and_return().await?;
+                    Ok(format!("Closed popup, returned to tab: {}", opener))
+                }
+                PopupAction::ListPopups => {
+                    let popups = browser.list_popups().await?;
+                    Ok(serde_json::to_string_pretty(&popups)?)
+                }
+            }
+        }
+
+        "chrome_form" => {
+            let params = FormParams::from_arguments(arguments)?;
+            let form_selector = params.form_selector.as_str();
+
+            match params.action {
+                FormAction::GetErrors => {
+                    let errors = browser.get_form_validation_errors(form_selector).await?;
+                    Ok(serde_json::to_string_pretty(&errors)?)
+                }
+                FormAction::Validate => {
+                    let valid = browser.trigger_form_validation(form_selector).await?;
+                    Ok(serde_json::to_string_pretty(&json!({ "valid": valid }))?)
+                }
+                FormAction::AuditLabels => {
+                    let issues = browser.verify_form_labels(form_selector).await?;
+                    Ok(serde_json::to_string_pretty(&issues)?)
+                }
+            }
+        }
+
+        "chrome_cors_test" => {
+            let params = CorsTestParams::from_arguments(arguments)?;
+
+            let method = params.method.as_deref().unwrap_or("GET");
+            let headers = params.headers.unwrap_or_default();
+
+            let result = browser.test_cors(&params.url, &params.origin, method, headers).await?;
+            Ok(serde_json::to_string_pretty(&result)?)
+        }
+
+        "chrome_svg" => {
+            let params = SvgParams::from_arguments(arguments)?;
+            let selector = params.selector.as_str();
+
+            match params.action {
+                SvgAction::Elements => {
+                    let elements = browser.get_svg_elements(selector).await?;
+                    Ok(serde_json::to_string_pretty(&elements)?)
+                }
+                SvgAction::Text => {
+                    let text = browser.get_svg_text_content(selector).await?;
+                    Ok(serde_json::to_string_pretty(&text)?)
+                }
+                SvgAction::PathData => {
+                    let paths: Vec<Value> = browser.get_svg_elements(selector).await?
+                        .into_iter()
+                        .filter_map(|e| e.path_data.map(|d| json!({ "id": e.id, "class": e.class, "d": d })))
+                        .collect();
+                    Ok(serde_json::to_string_pretty(&paths)?)
+                }
+            }
+        }
+
+        "chrome_canvas" => {
+            let params = CanvasParams::from_arguments(arguments)?;
+            let selector = params.selector.as_str();
+
+            let (x, y) = (params.x.unwrap_or(0), params.y.unwrap_or(0));
+            let (width, height) = (params.width.unwrap_or(1), params.height.unwrap_or(1));
+
+            match params.action {
+                CanvasAction::ReadPixels => {
+                    let pixels = browser.read_canvas_pixels(selector, x, y, width, height).await?;
+                    Ok(serde_json::to_string(&pixels)?)
+                }
+                CanvasAction::DataUrl => browser.get_canvas_data_url(selector).await,
+                CanvasAction::CompareRegion => {
+                    let expected_color = params.expected_color.as_deref()
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing expected_color parameter"))?;
+                    let expected = parse_hex_color(expected_color)
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error(format!("Invalid hex color: {}", expected_color)))?;
+                    let tolerance = params.tolerance.unwrap_or(0);
+
+                    let pixels = browser.read_canvas_pixels(selector, x, y, width, height).await?;
+                    let matching = pixels.iter().filter(|p| pixel_matches(**p, expected, tolerance)).count();
+
+                    Ok(serde_json::to_string_pretty(&json!({
+                        "matches": matching == pixels.len(),
+                        "matching_pixels": matching,
+                        "total_pixels": pixels.len(),
+                        "match_ratio": if pixels.is_empty() { 0.0 } else { matching as f64 / pixels.len() as f64 }
+                    }))?)
+                }
+            }
+        }
+
+        "chrome_console" => {
+            let params = ConsoleParams::from_arguments(arguments)?;
+
+            match params.action {
+                ConsoleAction::Start => {
+                    let max_entries = params.max_entries.unwrap_or(DEFAULT_CONSOLE_MAX_ENTRIES);
+                    browser.start_console_capture(max_entries).await?;
+                    browser.watch_title("console").await?;
+                    Ok("Started recording console output and watching page title".to_string())
+                }
+                ConsoleAction::Stop => {
+                    browser.stop_console_capture();
+                    Ok("Stopped recording console output".to_string())
+                }
+                ConsoleAction::Get => {
+                    let level = params.level.map(|l| l.as_str());
+                    Ok(serde_json::to_string_pretty(&json!({
+                        "console": browser.get_console_entries(level),
+                        "title_history": browser.get_title_history()
+                    }))?)
+                }
+            }
+        }
+
+        "chrome_timing" => {
+            let params = TimingParams::from_arguments(arguments)?;
+
+            match params.action {
+                TimingAction::Navigation => {
+                    let timing = browser.get_navigation_timing().await?;
+                    Ok(serde_json::to_string_pretty(&timing)?)
+                }
+                TimingAction::Resources => {
+                    let timings = browser.get_resource_timings().await?;
+                    Ok(serde_json::to_string_pretty(&timings)?)
+                }
+            }
+        }
+
+        "chrome_network" => {
+            let params = NetworkParams::from_arguments(arguments)?;
+            let pretty = params.pretty.unwrap_or(true);
+
+            match params.action {
+                NetworkAction::Start => {
+                    browser.start_network_capture(
+                        params.capture_body.unwrap_or(false),
+                        params.max_entries.unwrap_or(DEFAULT_NETWORK_MAX_ENTRIES),
+                        params.max_body_bytes.unwrap_or(DEFAULT_NETWORK_MAX_BODY_BYTES),
+                    ).await?;
+                    Ok("Started capturing network requests".to_string())
+                }
+                NetworkAction::Stop => {
+                    browser.stop_network_capture();
+                    Ok("Stopped capturing network requests".to_string())
+                }
+                NetworkAction::Get => {
+                    let events = browser.get_network_events(params.url_pattern.as_deref())?;
+                    if pretty {
+                        Ok(serde_json::to_string_pretty(&events)?)
+                    } else {
+                        Ok(serde_json::to_string(&events)?)
+                    }
+                }
+                NetworkAction::GetResponse => {
+                    let request_id = params.request_id
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing request_id parameter"))?;
+                    let event = browser.get_response(&request_id).await?;
+                    if pretty {
+                        Ok(serde_json::to_string_pretty(&event)?)
+                    } else {
+                        Ok(serde_json::to_string(&event)?)
+                    }
+                }
+            }
+        }
+
+        "chrome_storage" => {
+            let params = StorageParams::from_arguments(arguments)?;
+
+            let storage_type = params.storage_type.unwrap_or(StorageType::Local);
+            let key = params.key.as_deref();
+            let missing_key = || ChromeMcpError::mcp_protocol_error("Missing key parameter");
+
+            match params.action {
+                StorageAction::Get => {
+                    let value = browser.storage_get(storage_type, key).await?;
+                    Ok(serde_json::to_string_pretty(&value)?)
+                }
+                StorageAction::Set => {
+                    let key = key.ok_or_else(missing_key)?;
+                    let value = params.value.as_ref()
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing value parameter"))?;
+                    browser.storage_set(storage_type, key, value).await?;
+                    Ok(serde_json::to_string_pretty(&json!({ "key": key, "value": value }))?)
+                }
+                StorageAction::Remove => {
+                    let key = key.ok_or_else(missing_key)?;
+                    browser.storage_remove(storage_type, key).await?;
+                    Ok(serde_json::to_string_pretty(&json!({ "removed": key }))?)
+                }
+                StorageAction::Clear => {
+                    browser.storage_clear(storage_type).await?;
+                    Ok(serde_json::to_string_pretty(&json!({ "cleared": storage_type }))?)
+                }
+                StorageAction::Keys => {
+                    let keys = browser.storage_keys(storage_type).await?;
+                    Ok(serde_json::to_string_pretty(&keys)?)
+                }
+                StorageAction::TriggerEvent => {
+                    let key = key.ok_or_else(missing_key)?;
+                    let old_value = params.old_value.as_deref();
+                    let new_value = params.new_value.as_deref();
+
+                    browser.trigger_storage_event(key, old_value, new_value, storage_type).await?;
+                    Ok(format!("Dispatched storage event for key {}", key))
+                }
+                StorageAction::Listen => {
+                    // The page holds the Runtime.evaluate response until listening ends
+                    let timeout_ms = params.timeout_ms.unwrap_or(5000).min(MAX_PAGE_WAIT_MS);
+
+                    let changes = browser.listen_for_storage_changes(timeout_ms).await?;
+                    Ok(serde_json::to_string_pretty(&changes)?)
+                }
+            }
+        }
+
+        "chrome_mark" => {
+            let params = MarkParams::from_arguments(arguments)?;
+
+            match params.action {
+                MarkAction::Add => {
+                    let label = params.label
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing label parameter"))?;
+                    browser.console_mark(&label).await?;
+                    Ok(format!("Added mark: {}", label))
+                }
+                MarkAction::GetMarks => {
+                    let marks = browser.get_time_marks().await?;
+                    Ok(serde_json::to_string_pretty(&marks)?)
+                }
+            }
+        }
+
+        "chrome_reload" => {
+            let params = ReloadParams::from_arguments(arguments)?;
+
+            match params.inject_script {
+                Some(script) => browser.reload_and_inject(params.ignore_cache, &script).await?,
+                None => browser.reload(params.ignore_cache).await?,
+            }
+            Ok(if params.ignore_cache { "Reloaded page (cache bypassed)" } else { "Reloaded page" }.to_string())
+        }
+
+        "chrome_fuzz" => {
+            let params = FuzzParams::from_arguments(arguments)?;
+            let iterations = params.iterations.unwrap_or(10);
+            if !(1..=100).contains(&iterations) {
+                return Err(ChromeMcpError::mcp_protocol_error("iterations must be between 1 and 100"));
+            }
+
+            let strategy = match params.strategy {
+                FuzzStrategyKind::Random => FuzzStrategy::Random,
+                FuzzStrategyKind::Boundary => FuzzStrategy::Boundary,
+                FuzzStrategyKind::Sql => FuzzStrategy::Sql,
+                FuzzStrategyKind::Xss => FuzzStrategy::Xss,
+                FuzzStrategyKind::Custom => {
+                    let inputs = params.inputs
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing inputs parameter"))?;
+                    FuzzStrategy::Custom(inputs)
+                }
+            };
+
+            let results = browser.fuzz_input(&params.selector, strategy, iterations).await?;
+            Ok(serde_json::to_string_pretty(&results)?)
+        }
+
+        "chrome_audit" => {
+            let params = AuditParams::from_arguments(arguments)?;
+
+            match params.action {
+                AuditAction::RenderBlocking => {
+                    let resources = browser.get_render_blocking_resources().await?;
+                    if !params.fix {
+                        return Ok(serde_json::to_string_pretty(&resources)?);
+                    }
+
+                    let deferred_scripts = browser.defer_render_blocking_scripts().await?;
+                    Ok(serde_json::to_string_pretty(&json!({
+                        "resources": resources,
+                        "deferred_scripts": deferred_scripts
+                    }))?)
+                }
+                AuditAction::JsBundles => {
+                    let analysis = browser.analyze_js_bundles().await?;
+                    Ok(serde_json::to_string_pretty(&analysis)?)
+                }
+            }
+        }
+
+        "chrome_seo_audit" => {
+            NoParams::from_arguments(arguments)?;
+            let audit = browser.run_seo_audit().await?;
+            Ok(serde_json::to_string_pretty(&audit)?)
+        }
+
+        "chrome_download" => {
+            let params = DownloadParams::from_arguments(arguments)?;
+            let missing_pattern = || ChromeMcpError::mcp_protocol_error("Missing pattern parameter");
+
+            match params.action {
+                DownloadAction::SetDirectory => {
+                    let path = params.path
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing path parameter"))?;
+
+                    browser.set_download_directory(&path).await?;
+                    Ok(format!("Downloading to: {}", path))
+                }
+                DownloadAction::Progress => {
+                    let pattern = params.pattern.ok_or_else(missing_pattern)?;
+
+                    match browser.download_progress(&pattern)? {
+                        Some(progress) => Ok(serde_json::to_string_pretty(&progress)?),
+                        None => Ok(format!("No download matching: {}", pattern)),
+                    }
+                }
+                DownloadAction::Wait => {
+                    let pattern = params.pattern.ok_or_else(missing_pattern)?;
+                    let timeout_ms = params.timeout_ms.unwrap_or(30000);
+
+                    let download = browser.wait_for_download(&pattern, timeout_ms).await?;
+                    Ok(serde_json::to_string_pretty(&download)?)
+                }
+            }
+        }
 
-    /// Handle initialize request
-    async fn handle_initialize(&mut self, msg: &McpMessage) -> Result<Option<McpMessage>> {
-        info!("Handling initialize request");
+        "chrome_fullscreen" => {
+            let params = FullscreenParams::from_arguments(arguments)?;
 
-        // Connect to Chrome
-        match self.browser.connect(None).await {
-            Ok(tab_id) => {
-                info!("Connected to Chrome tab: {}", tab_id);
-            }
-            Err(e) => {
-                warn!("Failed to connect to Chrome: {}", e);
-                // Continue anyway - connection can be retried
+            match params.action {
+                FullscreenAction::Enter => {
+                    let selector = params.selector.as_deref();
+                    browser.enter_fullscreen(selector).await?;
+                    Ok(format!("Entered fullscreen: {}", selector.unwrap_or("page")))
+                }
+                FullscreenAction::Exit => {
+                    browser.exit_fullscreen().await?;
+                    Ok("Exited fullscreen".to_string())
+                }
+                FullscreenAction::Status => Ok(json!({ "fullscreen": browser.is_fullscreen().await? }).to_string()),
             }
         }
 
-        Ok(Some(McpMessage {
-            jsonrpc: "2.0".to_string(),
-            id: msg.id.clone(),
-            method: None,
-            params: None,
-            result: Some(json!({
-                "protocolVersion": "1.0.0",
-                "serverInfo": {
-                    "name": "chrome-mcp",
-                    "version": "0.1.0"
-                },
-                "capabilities": self.capabilities
-            })),
-            error: None,
-        }))
-    }
+        "chrome_css_vars" => {
+            let params = CssVarsParams::from_arguments(arguments)?;
+            let selector = params.selector.as_deref();
 
-    /// Handle tools/list request
-    async fn handle_tools_list(&self, msg: &McpMessage) -> Result<Option<McpMessage>> {
-        debug!("Handling tools/list request");
+            match params.action {
+                CssVarsAction::List => {
+                    let variables = browser.list_css_variables(selector).await?;
+                    Ok(serde_json::to_string_pretty(&variables)?)
+                }
+                CssVarsAction::Set => {
+                    let variable = params.variable
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing variable parameter"))?;
+                    let value = params.value
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing value parameter"))?;
+
+                    browser.set_css_variable(selector.unwrap_or(":root"), &variable, &value).await?;
+                    Ok(format!("Set {} to: {}", variable, value))
+                }
+            }
+        }
 
-        let tools = self.get_available_tools();
+        "chrome_permissions" => {
+            let params = PermissionsParams::from_arguments(arguments)?;
+            let permission = params.permission;
+            let missing_permission = || ChromeMcpError::mcp_protocol_error("Missing permission parameter");
 
-        Ok(Some(McpMessage {
-            jsonrpc: "2.0".to_string(),
-            id: msg.id.clone(),
-            method: None,
-            params: None,
-            result: Some(json!({
-                "tools": tools
-            })),
-            error: None,
-        }))
-    }
+            match params.action {
+                PermissionsAction::Query => {
+                    let state = browser.query_permission(permission.ok_or_else(missing_permission)?).await?;
+                    Ok(serde_json::to_string_pretty(&state)?)
+                }
+                PermissionsAction::QueryAll => {
+                    let states = browser.query_all_permissions().await?;
+                    Ok(serde_json::to_string_pretty(&states)?)
+                }
+                PermissionsAction::Grant => {
+                    let permission = permission.ok_or_else(missing_permission)?;
+                    browser.grant_permission(permission).await?;
+                    Ok(format!("Granted {} permission", permission.name()))
+                }
+                PermissionsAction::Deny => {
+                    let permission = permission.ok_or_else(missing_permission)?;
+                    browser.deny_permission(permission).await?;
+                    Ok(format!("Denied {} permission", permission.name()))
+                }
+            }
+        }
 
-    /// Handle tools/call request
-    async fn handle_tools_call(&mut self, msg: &McpMessage) -> Result<Option<McpMessage>> {
-        let params = msg.params.as_ref()
-            .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing params in tools/call"))?;
+        "chrome_broadcast" => {
+            let params = BroadcastParams::from_arguments(arguments)?;
+            let channel = params.channel.as_str();
 
-        let name = params.get("name")
-            .and_then(|n| n.as_str())
-            .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing tool name"))?;
+            match params.action {
+                BroadcastAction::Listen => {
+                    let max_messages = params.max_messages.unwrap_or(10);
+                    let timeout_ms = params.timeout_ms.unwrap_or(5000).min(MAX_PAGE_WAIT_MS);
 
-        let default_args = json!({});
-        let arguments = params.get("arguments").unwrap_or(&default_args);
+                    let messages = browser.create_broadcast_listener(channel, max_messages, timeout_ms).await?;
+                    Ok(serde_json::to_string_pretty(&messages)?)
+                }
+                BroadcastAction::Post => {
+                    let message = params.message
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing message parameter"))?;
 
-        debug!("Calling tool: {} with args: {}", name, arguments);
+                    browser.post_broadcast_message(channel, message).await?;
+                    Ok(format!("Posted message on broadcast channel {}", channel))
+                }
+            }
+        }
 
-        let result = self.call_tool(name, arguments).await;
+        "chrome_wasm" => {
+            let params = WasmParams::from_arguments(arguments)?;
 
-        match result {
-            Ok(tool_result) => {
-                Ok(Some(McpMessage {
-                    jsonrpc: "2.0".to_string(),
-                    id: msg.id.clone(),
-                    method: None,
-                    params: None,
-                    result: Some(json!({
-                        "content": [{
-                            "type": "text",
-                            "text": tool_result
-                        }]
-                    })),
-                    error: None,
-                }))
-            }
-            Err(e) => {
-                Ok(Some(McpMessage {
-                    jsonrpc: "2.0".to_string(),
-                    id: msg.id.clone(),
-                    method: None,
-                    params: None,
-                    result: None,
-                    error: Some(McpError {
-                        code: -32603,
-                        message: format!("Tool execution failed: {}", e),
-                        data: Some(json!({ "tool": name, "arguments": arguments })),
-                    }),
-                }))
+            match params.action {
+                WasmAction::List => {
+                    let modules = browser.get_wasm_modules().await?;
+                    Ok(serde_json::to_string_pretty(&modules)?)
+                }
+                WasmAction::CallExport => {
+                    let function = params.function
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing function parameter"))?;
+                    let args = params.args.unwrap_or_default().into_iter().map(Value::from).collect();
+
+                    let result = browser.call_wasm_export(params.module.as_deref(), &function, args).await?;
+                    Ok(serde_json::to_string_pretty(&result)?)
+                }
             }
         }
-    }
-
-    /// Handle ping request
-    async fn handle_ping(&self, msg: &McpMessage) -> Result<Option<McpMessage>> {
-        Ok(Some(McpMessage {
-            jsonrpc: "2.0".to_string(),
-            id: msg.id.clone(),
-            method: None,
-            params: None,
-            result: Some(json!({})),
-            error: None,
-        }))
-    }
 
-    /// Get list of available tools
-    fn get_available_tools(&self) -> Vec<Tool> {
-        vec![
-            Tool {
-                name: "chrome_navigate".to_string(),
-                description: "Navigate to a URL".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "url": {
-                            "type": "string",
-                            "description": "The URL to navigate to"
-                        }
-                    },
-                    "required": ["url"]
-                }),
-            },
-            Tool {
-                name: "chrome_click".to_string(),
-                description: "Click on an element by CSS selector, text content, or accessibility label".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "target": {
-                            "type": "string",
-                            "description": "CSS selector, text content, or accessibility label of element to click"
-                        }
-                    },
-                    "required": ["target"]
-                }),
-            },
-            Tool {
-                name: "chrome_type".to_string(),
-                description: "Type text into an element or the currently focused element".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "text": {
-                            "type": "string",
-                            "description": "Text to type"
-                        },
-                        "selector": {
-                            "type": "string",
-                            "description": "Optional CSS selector to focus first"
-                        }
-                    },
-                    "required": ["text"]
-                }),
-            },
-            Tool {
-                name: "chrome_screenshot".to_string(),
-                description: "Take a screenshot of the current page".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "format": {
-                            "type": "string",
-                            "description": "Image format: png or jpeg",
-                            "enum": ["png", "jpeg"]
-                        },
-                        "quality": {
-                            "type": "integer",
-                            "description": "JPEG quality (1-100)",
-                            "minimum": 1,
-                            "maximum": 100
-                        },
-                        "full_page": {
-                            "type": "boolean",
-                            "description": "Capture full page or just viewport"
-                        }
-                    }
-                }),
-            },
-            Tool {
-                name: "chrome_evaluate".to_string(),
-                description: "Execute JavaScript in the browser".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "javascript": {
-                            "type": "string",
-                            "description": "JavaScript code to execute"
-                        }
-                    },
-                    "required": ["javascript"]
-                }),
-            },
-            Tool {
-                name: "chrome_tabs".to_string(),
-                description: "List, create, or switch between browser tabs".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "action": {
-                            "type": "string",
-                            "description": "Action to perform",
-                            "enum": ["list", "create", "switch", "close"]
-                        },
-                        "tab_id": {
-                            "type": "string",
-                            "description": "Tab ID (for switch/close actions)"
-                        },
-                        "url": {
-                            "type": "string",
-                            "description": "URL for new tab (create action)"
-                        }
-                    },
-                    "required": ["action"]
-                }),
-            },
-            Tool {
-                name: "chrome_scroll".to_string(),
-                description: "Scroll the page or scroll to an element".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "x": {
-                            "type": "integer",
-                            "description": "Horizontal scroll amount in pixels"
-                        },
-                        "y": {
-                            "type": "integer",
-                            "description": "Vertical scroll amount in pixels"
-                        },
-                        "selector": {
-                            "type": "string",
-                            "description": "CSS selector of element to scroll to"
-                        }
-                    }
-                }),
-            },
-            Tool {
-                name: "chrome_hover".to_string(),
-                description: "Hover over an element".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "target": {
-                            "type": "string",
-                            "description": "CSS selector or text of element to hover over"
-                        }
-                    },
-                    "required": ["target"]
-                }),
-            },
-            Tool {
-                name: "chrome_select".to_string(),
-                description: "Select an option from a dropdown".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "selector": {
-                            "type": "string",
-                            "description": "CSS selector of the select element"
-                        },
-                        "value": {
-                            "type": "string",
-                            "description": "Value of the option to select"
-                        }
-                    },
-                    "required": ["selector", "value"]
-                }),
-            },
-            Tool {
-                name: "chrome_wait".to_string(),
-                description: "Wait for a condition to be met".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "condition": {
-                            "type": "string",
-                            "description": "Condition type",
-                            "enum": ["element_present", "element_visible", "element_clickable", "text_present", "url_matches", "page_load", "network_idle"]
-                        },
-                        "target": {
-                            "type": "string",
-                            "description": "Target for the condition (selector, text, URL pattern)"
-                        },
-                        "timeout": {
-                            "type": "integer",
-                            "description": "Timeout in milliseconds",
-                            "default": 10000
-                        }
-                    },
-                    "required": ["condition"]
-                }),
-            },
-            Tool {
-                name: "chrome_cookies".to_string(),
-                description: "Get, set, or clear browser cookies".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "action": {
-                            "type": "string",
-                            "description": "Cookie action",
-                            "enum": ["get", "set", "clear"]
-                        },
-                        "name": {
-                            "type": "string",
-                            "description": "Cookie name (for set action)"
-                        },
-                        "value": {
-                            "type": "string",
-                            "description": "Cookie value (for set action)"
-                        },
-                        "domain": {
-                            "type": "string",
-                            "description": "Cookie domain (for set action)"
-                        },
-                        "path": {
-                            "type": "string",
-                            "description": "Cookie path (for set action)"
-                        }
-                    },
-                    "required": ["action"]
-                }),
-            },
-            Tool {
-                name: "chrome_pdf".to_string(),
-                description: "Generate a PDF of the current page".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "landscape": {
-                            "type": "boolean",
-                            "description": "Landscape orientation"
-                        },
-                        "print_background": {
-                            "type": "boolean",
-                            "description": "Include background graphics"
-                        },
-                        "scale": {
-                            "type": "number",
-                            "description": "Scale factor (0.1 to 2.0)"
-                        }
-                    }
-                }),
-            },
-            Tool {
-                name: "chrome_accessibility_tree".to_string(),
-                description: "Get the accessibility tree of the current page".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "summary": {
-                            "type": "boolean",
-                            "description": "Return a text summary instead of full tree"
-                        }
-                    }
-                }),
-            },
-            Tool {
-                name: "chrome_native_click".to_string(),
-                description: "Click at screen coordinates using native input (for browser chrome)".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "x": {
-                            "type": "number",
-                            "description": "X coordinate on screen"
-                        },
-                        "y": {
-                            "type": "number",
-                            "description": "Y coordinate on screen"
-                        }
-                    },
-                    "required": ["x", "y"]
-                }),
-            },
-            Tool {
-                name: "chrome_find".to_string(),
-                description: "Find elements by text, role, or selector and return references".to_string(),
-                input_schema: json!({
-                    "type": "object",
-                    "properties": {
-                        "query": {
-                            "type": "string",
-                            "description": "Search query (text, role, or CSS selector)"
-                        }
-                    },
-                    "required": ["query"]
-                }),
-            },
-        ]
-    }
+        "chrome_status" => {
+            let params = StatusParams::from_arguments(arguments)?;
 
-    /// Execute a tool call
-    async fn call_tool(&mut self, name: &str, arguments: &Value) -> Result<String> {
-        match name {
-            "chrome_navigate" => {
-                let url = arguments.get("url")
-                    .and_then(|u| u.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing url parameter"))?;
-                
-                self.browser.navigate(url).await?;
-                Ok(format!("Navigated to: {}", url))
-            }
-
-            "chrome_click" => {
-                let target = arguments.get("target")
-                    .and_then(|t| t.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing target parameter"))?;
-                
-                self.browser.click(target).await?;
-                Ok(format!("Clicked on: {}", target))
-            }
-
-            "chrome_type" => {
-                let text = arguments.get("text")
-                    .and_then(|t| t.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing text parameter"))?;
-                
-                let selector = arguments.get("selector").and_then(|s| s.as_str());
-                
-                self.browser.type_text(text, selector).await?;
-                Ok(format!("Typed text: {}", text))
-            }
-
-            "chrome_screenshot" => {
-                let format = arguments.get("format").and_then(|f| f.as_str());
-                let quality = arguments.get("quality").and_then(|q| q.as_u64()).map(|q| q as u32);
-                let full_page = arguments.get("full_page").and_then(|f| f.as_bool()).unwrap_or(false);
-                
-                let screenshot_data = if full_page {
-                    self.browser.screenshot_full_page(format, quality).await?
-                } else {
-                    self.browser.screenshot(format, quality).await?
-                };
-                
-                Ok(format!("data:image/{};base64,{}", format.unwrap_or("png"), screenshot_data))
-            }
-
-            "chrome_evaluate" => {
-                let javascript = arguments.get("javascript")
-                    .and_then(|j| j.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing javascript parameter"))?;
-                
-                let result = self.browser.evaluate(javascript).await?;
-                Ok(serde_json::to_string_pretty(&result)?)
-            }
-
-            "chrome_tabs" => {
-                let action = arguments.get("action")
-                    .and_then(|a| a.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing action parameter"))?;
-                
-                match action {
-                    "list" => {
-                        let tabs = self.browser.list_tabs().await?;
-                        Ok(serde_json::to_string_pretty(&tabs)?)
-                    }
-                    "create" => {
-                        let url = arguments.get("url").and_then(|u| u.as_str());
-                        let tab_id = self.browser.create_tab(url).await?;
-                        Ok(format!("Created tab: {}", tab_id))
-                    }
-                    "switch" => {
-                        let tab_id = arguments.get("tab_id")
-                            .and_then(|t| t.as_str())
-                            .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing tab_id parameter"))?;
-                        
-                        self.browser.switch_to_tab(tab_id).await?;
-                        Ok(format!("Switched to tab: {}", tab_id))
-                    }
-                    "close" => {
-                        let tab_id = arguments.get("tab_id")
-                            .and_then(|t| t.as_str())
-                            .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing tab_id parameter"))?;
-                        
-                        self.browser.close_tab(tab_id).await?;
-                        Ok(format!("Closed tab: {}", tab_id))
-                    }
-                    _ => Err(ChromeMcpError::mcp_protocol_error(format!("Unknown tabs action: {}", action)))
+            match params.action {
+                StatusAction::Metrics => Ok(serde_json::to_string_pretty(&browser.get_connection_metrics())?),
+                StatusAction::ResetMetrics => {
+                    browser.reset_connection_metrics();
+                    Ok("Connection metrics reset".to_string())
                 }
             }
+        }
 
-            "chrome_scroll" => {
-                if let Some(selector) = arguments.get("selector").and_then(|s| s.as_str()) {
-                    self.browser.scroll_to_element(selector).await?;
-                    Ok(format!("Scrolled to element: {}", selector))
-                } else {
-                    let x = arguments.get("x").and_then(|x| x.as_i64()).unwrap_or(0) as i32;
-                    let y = arguments.get("y").and_then(|y| y.as_i64()).unwrap_or(0) as i32;
-                    
-                    self.browser.scroll(x, y).await?;
-                    Ok(format!("Scrolled by: ({}, {})", x, y))
-                }
-            }
-
-            "chrome_hover" => {
-                let target = arguments.get("target")
-                    .and_then(|t| t.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing target parameter"))?;
-                
-                self.browser.hover(target).await?;
-                Ok(format!("Hovered over: {}", target))
-            }
-
-            "chrome_select" => {
-                let selector = arguments.get("selector")
-                    .and_then(|s| s.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing selector parameter"))?;
-                
-                let value = arguments.get("value")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing value parameter"))?;
-                
-                self.browser.select_option(selector, value).await?;
-                Ok(format!("Selected '{}' in {}", value, selector))
-            }
-
-            "chrome_wait" => {
-                let condition_str = arguments.get("condition")
-                    .and_then(|c| c.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing condition parameter"))?;
-                
-                let target = arguments.get("target").and_then(|t| t.as_str()).unwrap_or("");
-                let timeout = arguments.get("timeout").and_then(|t| t.as_u64()).unwrap_or(10000);
-                
-                let condition = match condition_str {
-                    "element_present" => WaitCondition::ElementPresent(target.to_string()),
-                    "element_visible" => WaitCondition::ElementVisible(target.to_string()),
-                    "element_clickable" => WaitCondition::ElementClickable(target.to_string()),
-                    "text_present" => WaitCondition::TextPresent(target.to_string()),
-                    "url_matches" => WaitCondition::UrlMatches(target.to_string()),
-                    "page_load" => WaitCondition::PageLoad,
-                    "network_idle" => WaitCondition::NetworkIdle(1000),
-                    _ => return Err(ChromeMcpError::mcp_protocol_error(format!("Unknown condition: {}", condition_str)))
-                };
-                
-                self.browser.wait_for_condition(condition, timeout).await?;
-                Ok(format!("Wait condition '{}' satisfied", condition_str))
-            }
-
-            "chrome_cookies" => {
-                let action = arguments.get("action")
-                    .and_then(|a| a.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing action parameter"))?;
-                
-                match action {
-                    "get" => {
-                        let cookies = self.browser.get_cookies().await?;
-                        Ok(serde_json::to_string_pretty(&cookies)?)
-                    }
-                    "set" => {
-                        let name = arguments.get("name")
-                            .and_then(|n| n.as_str())
-                            .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing name parameter"))?;
-                        
-                        let value = arguments.get("value")
-                            .and_then(|v| v.as_str())
-                            .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing value parameter"))?;
-                        
-                        let domain = arguments.get("domain")
-                            .and_then(|d| d.as_str())
-                            .unwrap_or("localhost");
-                        
-                        let path = arguments.get("path")
-                            .and_then(|p| p.as_str())
-                            .unwrap_or("/");
-                        
-                        let cookie = Cookie {
-                            name: name.to_string(),
-                            value: value.to_string(),
-                            domain: domain.to_string(),
-                            path: path.to_string(),
-                            secure: false,
-                            http_only: false,
-                            same_site: None,
-                            expires: None,
-                        };
-                        
-                        self.browser.set_cookie(cookie).await?;
-                        Ok(format!("Set cookie: {} = {}", name, value))
-                    }
-                    "clear" => {
-                        self.browser.clear_cookies().await?;
-                        Ok("Cleared all cookies".to_string())
-                    }
-                    _ => Err(ChromeMcpError::mcp_protocol_error(format!("Unknown cookies action: {}", action)))
-                }
-            }
-
-            "chrome_pdf" => {
-                let landscape = arguments.get("landscape").and_then(|l| l.as_bool());
-                let print_background = arguments.get("print_background").and_then(|p| p.as_bool());
-                let scale = arguments.get("scale").and_then(|s| s.as_f64());
-                
-                let options = if landscape.is_some() || print_background.is_some() || scale.is_some() {
-                    Some(PdfOptions {
-                        landscape,
-                        print_background,
-                        scale,
-                        ..Default::default()
-                    })
-                } else {
-                    None
-                };
-                
-                let pdf_data = self.browser.pdf(options).await?;
-                Ok(format!("data:application/pdf;base64,{}", pdf_data))
+        "chrome_clipboard" => {
+            let params = ClipboardParams::from_arguments(arguments)?;
+
+            match params.action {
+                ClipboardAction::Read => browser.clipboard_read().await,
+                ClipboardAction::ReadImage => Ok(json!({ "image_b64": browser.read_clipboard_image().await? }).to_string()),
+                ClipboardAction::ReadItems => Ok(serde_json::to_string_pretty(&browser.read_clipboard_items().await?)?),
+                ClipboardAction::Write => {
+                    let text = params.text
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing text parameter"))?;
+                    browser.clipboard_write(&text).await?;
+                    Ok(format!("Wrote {} characters to the clipboard", text.chars().count()))
+                }
             }
+        }
 
-            "chrome_accessibility_tree" => {
-                let summary = arguments.get("summary").and_then(|s| s.as_bool()).unwrap_or(false);
-                
-                if summary {
-                    let summary = self.browser.accessibility().get_tree_summary().await?;
-                    Ok(summary.join("\n"))
-                } else {
-                    let tree = self.browser.accessibility_tree().await?;
-                    Ok(serde_json::to_string_pretty(&tree)?)
+        "chrome_history" => {
+            let params = HistoryParams::from_arguments(arguments)?;
+
+            match params.action {
+                HistoryAction::Back => {
+                    browser.go_back().await?;
+                    Ok("Went back".to_string())
+                }
+                HistoryAction::Forward => {
+                    browser.go_forward().await?;
+                    Ok("Went forward".to_string())
+                }
+                HistoryAction::Go => {
+                    let delta = params.delta
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing delta parameter"))?;
+                    browser.navigate_to_index(delta).await?;
+                    Ok(format!("Moved {} entries in history", delta))
+                }
+                HistoryAction::Get => {
+                    let history = browser.get_history_state().await?;
+                    Ok(serde_json::to_string_pretty(&json!({
+                        "length": history["length"],
+                        "current_url": history["url"],
+                        "state": history["state"],
+                    }))?)
                 }
             }
+        }
+
+        "chrome_frame" => {
+            let params = FrameParams::from_arguments(arguments)?;
 
-            "chrome_native_click" => {
-                let x = arguments.get("x")
-                    .and_then(|x| x.as_f64())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing x parameter"))?;
-                
-                let y = arguments.get("y")
-                    .and_then(|y| y.as_f64())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing y parameter"))?;
-                
-                self.browser.native_click(x, y).await?;
-                Ok(format!("Native click at ({}, {})", x, y))
+            match params.action {
+                FrameAction::Switch => {
+                    let selector = params.selector
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing selector parameter"))?;
+                    let frame = browser.switch_to_frame(&selector).await?;
+                    Ok(format!("Switched to frame {} ({})", frame.frame_id, frame.selector))
+                }
+                FrameAction::Main => {
+                    browser.switch_to_main_frame();
+                    Ok("Switched to main frame".to_string())
+                }
             }
+        }
+
+        "chrome_session" => {
+            let params = SessionParams::from_arguments(arguments)?;
+            let missing_path = || ChromeMcpError::mcp_protocol_error("Missing path parameter");
+
+            match params.action {
+                SessionAction::Export => {
+                    let state = browser.export_session().await?;
+                    Ok(serde_json::to_string_pretty(&state)?)
+                }
+                SessionAction::Import => {
+                    let state_json = params.state
+                        .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing state parameter"))?;
+
+                    let state: SessionState = serde_json::from_value(Value::Object(state_json))?;
+                    browser.import_session(state).await?;
+                    Ok("Session imported".to_string())
+                }
+                SessionAction::SaveFile => {
+                    let path = params.path.ok_or_else(missing_path)?;
 
-            "chrome_find" => {
-                let query = arguments.get("query")
-                    .and_then(|q| q.as_str())
-                    .ok_or_else(|| ChromeMcpError::mcp_protocol_error("Missing query parameter"))?;
-                
-                let elements = self.browser.find_elements(query).await?;
-                Ok(serde_json::to_string_pretty(&elements)?)
+                    let state = browser.export_session().await?;
+                    std::fs::write(&path, serde_json::to_string_pretty(&state)?)?;
+                    Ok(format!("Session saved to: {}", path))
+                }
+                SessionAction::LoadFile => {
+                    let path = params.path.ok_or_else(missing_path)?;
+
+                    let state: SessionState = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+                    browser.import_session(state).await?;
+                    Ok(format!("Session loaded from: {}", path))
+                }
             }
 
-            _ => Err(ChromeMcpError::mcp_protocol_error(format!("Unknown tool: {}", name)))
         }
+
+        _ => Err(ChromeMcpError::mcp_protocol_error(format!("Unknown tool: {}", name)))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{evaluate_response, MockCdpClient};
     use serde_json::json;
 
     #[test]
@@ -909,6 +2651,7 @@ mod tests {
             code: -32602,
             message: "Invalid params".to_string(),
             data: Some(json!({"details": "Missing required parameter"})),
+            trace: None,
         };
 
         assert_eq!(error.code, -32602);
@@ -1050,6 +2793,7 @@ mod tests {
         assert_eq!(schema["type"], "object");
         assert!(schema["properties"]["target"].is_object());
         assert_eq!(schema["properties"]["target"]["type"], "string");
+        assert_eq!(schema["properties"]["jitter_px"]["type"], "integer");
         assert_eq!(schema["required"][0], "target");
     }
 
@@ -1076,6 +2820,302 @@ mod tests {
         assert!(format_enum.as_array().unwrap().contains(&json!("jpeg")));
     }
 
+    #[test]
+    fn test_into_mcp_error_with_trace() {
+        let error = ChromeMcpError::element_not_found("#missing")
+            .in_frame("browser", "click")
+            .with_context("target", "#missing")
+            .in_frame("mcp", "tools/call");
+
+        let quiet = ChromeMcpError::element_not_found("#missing").into_mcp_error_with_trace(false);
+        assert!(quiet.trace.is_none());
+        assert!(quiet.data.is_none());
+
+        let verbose = error.into_mcp_error_with_trace(true);
+        assert_eq!(verbose.message, "Element not found: #missing");
+        let data = verbose.data.clone().unwrap();
+        assert_eq!(data["trace"][0]["module"], "mcp");
+        assert_eq!(data["trace"][1]["context"]["target"], "#missing");
+
+        // The trace is only sent inside `data`
+        assert!(serde_json::to_value(&verbose).unwrap().get("trace").is_none());
+    }
+
+    struct PageTitleTool;
+
+    impl AsyncToolHandler for PageTitleTool {
+        fn call<'a>(&'a self, arguments: &'a Value, _browser: &'a mut Browser) -> ToolFuture<'a> {
+            Box::pin(async move {
+                let prefix = arguments.get("prefix").and_then(|p| p.as_str()).unwrap_or_default();
+                Ok(format!("{}Example Domain", prefix))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_custom_tool() {
+        let mut server = McpServer::new("localhost", 9222).unwrap();
+        let builtin_count = server.get_available_tools().len();
+
+        let tool = Tool {
+            name: "custom_page_title".to_string(),
+            description: "Return the page title".to_string(),
+            input_schema: json!({ "type": "object", "properties": { "prefix": { "type": "string" } } }),
+        };
+        server.register_tool(tool.clone(), Box::new(PageTitleTool)).unwrap();
+        assert!(server.register_tool(tool, Box::new(PageTitleTool)).is_err());
+
+        let tools = server.get_available_tools();
+        assert_eq!(tools.len(), builtin_count + 1);
+        assert_eq!(tools.last().unwrap().name, "custom_page_title");
+
+        let result = server.call_tool("custom_page_title", &json!({ "prefix": "Title: " })).await.unwrap();
+        assert_eq!(result, "Title: Example Domain");
+
+        let error = server.call_tool("no_such_tool", &json!({})).await.unwrap_err();
+        assert!(error.to_string().contains("Unknown tool: no_such_tool"));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_forwards_tool_calls() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let mut upstream = McpServer::new("localhost", 9222).unwrap();
+        let tool = Tool {
+            name: "custom_page_title".to_string(),
+            description: "Return the page title".to_string(),
+            input_schema: json!({ "type": "object" }),
+        };
+        upstream.register_tool(tool, Box::new(PageTitleTool)).unwrap();
+
+        let mut proxy = McpServer::new_proxy(vec![ProxyRoute {
+            tab_url_pattern: "*".to_string(),
+            upstream_host: "127.0.0.1".to_string(),
+            upstream_port: port,
+            token: None,
+        }]);
+        let calls = async {
+            let title = proxy.call_tool("custom_page_title", &json!({ "prefix": "Title: " })).await;
+            let error = proxy.call_tool("no_such_tool", &json!({})).await;
+            (title, error)
+        };
+        let (title, error) = tokio::select! {
+            _ = upstream.run_tcp(listener, None) => unreachable!("the upstream serves until dropped"),
+            results = calls => results,
+        };
+
+        assert_eq!(title.unwrap(), "Title: Example Domain");
+        let error = error.unwrap_err().to_string();
+        assert!(error.contains(&format!("127.0.0.1:{}", port)));
+        assert!(error.contains("Unknown tool: no_such_tool"));
+        assert!(!error.contains("Tool execution failed"));
+    }
+
+    #[tokio::test]
+    async fn test_listen_token_required() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let mut upstream = McpServer::new("localhost", 9222).unwrap();
+        let tool = Tool {
+            name: "custom_page_title".to_string(),
+            description: "Return the page title".to_string(),
+            input_schema: json!({ "type": "object" }),
+        };
+        upstream.register_tool(tool, Box::new(PageTitleTool)).unwrap();
+
+        let route = |token: Option<&str>| ProxyRoute {
+            tab_url_pattern: "*".to_string(),
+            upstream_host: "127.0.0.1".to_string(),
+            upstream_port: port,
+            token: token.map(|t| t.to_string()),
+        };
+        let mut anonymous = McpServer::new_proxy(vec![route(None)]);
+        let mut wrong = McpServer::new_proxy(vec![route(Some("guess"))]);
+        let mut trusted = McpServer::new_proxy(vec![route(Some("secret"))]);
+        let calls = async {
+            let anonymous = anonymous.call_tool("custom_page_title", &json!({})).await;
+            let wrong = wrong.call_tool("custom_page_title", &json!({})).await;
+            let trusted = trusted.call_tool("custom_page_title", &json!({})).await;
+            (anonymous, wrong, trusted)
+        };
+        let (anonymous, wrong, trusted) = tokio::select! {
+            _ = upstream.run_tcp(listener, Some("secret")) => unreachable!("the upstream serves until dropped"),
+            results = calls => results,
+        };
+
+        assert!(anonymous.unwrap_err().to_string().contains("Unauthorized"));
+        assert!(wrong.unwrap_err().to_string().contains("Unauthorized"));
+        assert_eq!(trusted.unwrap(), "Example Domain");
+    }
+
+    #[test]
+    fn test_builtin_tool_names_unique() {
+        let tools = builtin_tools();
+        let mut names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), tools.len());
+    }
+
+    #[test]
+    fn test_chrome_network_tool_schema() {
+        let tools = builtin_tools();
+        let network_tool = tools.iter().find(|t| t.name == "chrome_network").unwrap();
+
+        let schema = &network_tool.input_schema;
+        assert_eq!(schema["properties"]["action"]["enum"], json!(["start", "stop", "get", "get_response"]));
+        assert_eq!(schema["properties"]["capture_body"]["type"], "boolean");
+        assert_eq!(schema["properties"]["pretty"]["type"], "boolean");
+        assert_eq!(schema["required"], json!(["action"]));
+    }
+
+    #[tokio::test]
+    async fn test_chrome_navigate_keep_scroll() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!([0, 1480]))),
+            ("Page.navigate", json!({ "frameId": "F1" })),
+            ("Runtime.evaluate", evaluate_response(json!("complete"))),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+        ]);
+        let mut server = McpServer::with_browser(Browser::with_cdp_client(mock.client()).unwrap());
+        server.call_tool("chrome_navigate", &json!({ "url": "https://example.com/feed?page=2", "keep_scroll": true })).await.unwrap();
+
+        let calls = mock.calls();
+        assert_eq!(calls[1].1, Some(json!({ "url": "https://example.com/feed?page=2" })));
+        assert_eq!(calls[3].1.as_ref().unwrap()["expression"], "window.scrollTo(0, 1480)");
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_chrome_content_defaults_to_main_content() {
+        let content = |html: &str| evaluate_response(json!({ "html": html, "baseUrl": "https://example.com/" }));
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", content("<h1>News</h1><img src=\"a.png\" alt=\"A\">")),
+            ("Runtime.evaluate", content("<p>Footer</p>")),
+        ]);
+        let mut server = McpServer::with_browser(Browser::with_cdp_client(mock.client()).unwrap());
+
+        let markdown = server.call_tool("chrome_content", &json!({})).await.unwrap();
+        assert!(markdown.contains("# News") && !markdown.contains("a.png"));
+        server.call_tool("chrome_content", &json!({ "selector": "footer" })).await.unwrap();
+
+        let calls = mock.calls();
+        let expression = |index: usize| calls[index].1.as_ref().unwrap()["expression"].as_str().unwrap().to_string();
+        assert!(expression(0).contains("document.querySelector('main') || document.querySelector('article') || document.body"));
+        assert!(expression(1).contains(r#"document.querySelector("footer")"#));
+    }
+
+    #[tokio::test]
+    async fn test_chrome_storage_listen_timeout_limit() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.addBinding", json!({})),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+            ("Runtime.removeBinding", json!({})),
+        ]);
+        let mut server = McpServer::with_browser(Browser::with_cdp_client(mock.client()).unwrap());
+        server.call_tool("chrome_storage", &json!({ "action": "listen", "timeout_ms": 60000 })).await.unwrap();
+
+        let calls = mock.calls();
+        let script = calls[1].1.as_ref().unwrap()["expression"].as_str().unwrap();
+        assert!(script.contains(&MAX_PAGE_WAIT_MS.to_string()));
+        assert!(!script.contains("60000"));
+    }
+
+    #[tokio::test]
+    async fn test_chrome_fuzz_argument_validation() {
+        let mut server = McpServer::new("localhost", 9222).unwrap();
+
+        let error = server.call_tool("chrome_fuzz", &json!({ "selector": "#q", "strategy": "custom" })).await.unwrap_err();
+        assert!(error.to_string().contains("Missing inputs parameter"));
+
+        let error = server.call_tool("chrome_fuzz", &json!({ "selector": "#q", "strategy": "sql", "iterations": 0 })).await.unwrap_err();
+        assert!(error.to_string().contains("iterations must be between 1 and 100"));
+
+        let error = server.call_tool("chrome_fuzz", &json!({ "selector": "#q", "strategy": "unicode" })).await.unwrap_err();
+        assert!(error.to_string().contains("unknown variant `unicode`"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_are_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.ndjson");
+        let mut server = McpServer::new("localhost", 9222)
+            .unwrap()
+            .with_tool_recording(SessionWriter::create(&path).unwrap());
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"chrome_nope","arguments":{"x":1}}}"#)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(response.error.is_some());
+
+        let session = crate::replay::ReplaySession::load(&path).unwrap();
+        assert_eq!(session.steps.len(), 1);
+        assert_eq!(session.steps[0].tool_name, "chrome_nope");
+        assert_eq!(session.steps[0].arguments, json!({ "x": 1 }));
+        assert!(session.steps[0].expected_result.as_deref().unwrap().contains("Unknown tool: chrome_nope"));
+    }
+
+    #[tokio::test]
+    async fn test_logging_set_and_get_level() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let (filter, handle) = reload::Layer::new(LevelFilter::INFO);
+        let _subscriber = Registry::default().with(filter);
+        let mut server = McpServer::new("localhost", 9222).unwrap().with_log_level(Level::INFO, handle.clone());
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":1,"method":"logging/setLevel","params":{"level":"warning"}}"#)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(response.error.is_none());
+        assert_eq!(handle.clone_current(), Some(LevelFilter::WARN));
+        assert_eq!(server.capabilities.logging.as_ref().unwrap().level.as_deref(), Some("warning"));
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":2,"method":"logging/getLevel"}"#)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.result, Some(json!({ "level": "warning" })));
+
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":3,"method":"logging/setLevel","params":{"level":"verbose"}}"#)
+            .await
+            .unwrap()
+            .unwrap();
+        let error = response.error.unwrap();
+        assert_eq!(error.code, -32602);
+        assert!(error.message.contains("Invalid log level: verbose"));
+        assert_eq!(handle.clone_current(), Some(LevelFilter::WARN));
+    }
+
+    #[test]
+    fn test_mcp_log_levels() {
+        assert_eq!(parse_mcp_log_level("notice"), Some(Level::INFO));
+        assert_eq!(parse_mcp_log_level("critical"), Some(Level::ERROR));
+        assert_eq!(parse_mcp_log_level("trace"), None);
+        assert_eq!(mcp_log_level_name(Level::TRACE), "debug");
+        assert_eq!(mcp_log_level_name(Level::WARN), "warning");
+    }
+
+    #[test]
+    fn test_chrome_pdf_text_tool_schema() {
+        let server = McpServer::new("localhost", 9222).unwrap();
+        let tools = server.get_available_tools();
+
+        let pdf_text_tool = tools.iter().find(|t| t.name == "chrome_pdf_text").unwrap();
+
+        let schema = &pdf_text_tool.input_schema;
+        assert_eq!(schema["properties"]["data"]["type"], "string");
+        assert_eq!(schema["properties"]["page_separator"]["type"], "string");
+        assert_eq!(schema["required"], json!(["data"]));
+    }
+
     #[test]
     fn test_initialize_response_format() {
         let result = McpServer::new("localhost", 9222);
@@ -1169,6 +3209,7 @@ mod tests {
                 code: -32603,
                 message: "Internal error".to_string(),
                 data: None,
+                trace: None,
             }),
         };
 
@@ -1197,7 +3238,40 @@ mod tests {
         }"#;
 
         let message: McpMessage = serde_json::from_str(message_json).unwrap();
-        assert!(message.id.is_none() || message.id == Some(json!(null)));
+        assert_eq!(message.id, Some(json!(null)));
+
+        let notification: McpMessage = serde_json::from_str(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).unwrap();
+        assert_eq!(notification.id, None);
+    }
+
+    #[test]
+    fn test_normalize_id() {
+        for id in [json!("req-abc-123"), json!("42"), json!(42), json!(-7), json!(1.5), json!(null)] {
+            assert_eq!(normalize_id(Some(id.clone())), Some(id));
+        }
+        assert_eq!(normalize_id(None), None);
+        assert_eq!(normalize_id(Some(json!({ "id": 1 }))), Some(json!(null)));
+        assert_eq!(normalize_id(Some(json!(true))), Some(json!(null)));
+    }
+
+    #[tokio::test]
+    async fn test_response_echoes_request_id() {
+        let mut server = McpServer::new("localhost", 9222).unwrap();
+
+        for id in [r#""req-abc-123""#, r#""42""#, "42", "null"] {
+            let request = format!(r#"{{"jsonrpc":"2.0","id":{},"method":"logging/getLevel"}}"#, id);
+            let response = server.handle_message(&request).await.unwrap().unwrap();
+            let serialized = serde_json::to_value(&response).unwrap();
+            assert_eq!(serialized["id"], serde_json::from_str::<Value>(id).unwrap(), "id {}", id);
+            assert!(serialized.as_object().unwrap().contains_key("id"));
+        }
+
+        let unknown = server.handle_message(r#"{"jsonrpc":"2.0","id":"x-1","method":"nope"}"#).await.unwrap().unwrap();
+        assert_eq!(unknown.id, Some(json!("x-1")));
+        assert_eq!(unknown.error.unwrap().code, -32601);
+
+        let notification = server.handle_message(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).await.unwrap();
+        assert!(notification.is_none());
     }
 
     #[test]
@@ -1,8 +1,13 @@
-//! Native input injection for macOS using Core Graphics
+//! Native input injection, using Core Graphics on macOS, `xdotool` (X11) or `ydotool`
+//! (Wayland) on Linux and `SendInput` on Windows
 //! This allows clicking anywhere on screen, including browser chrome, dialogs, etc.
 
 use crate::error::{ChromeMcpError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use tracing::debug;
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+use tracing::warn;
 
 #[cfg(target_os = "macos")]
 use core_graphics::{
@@ -11,15 +16,43 @@ use core_graphics::{
     event_source::{CGEventSource, CGEventSourceStateID},
 };
 
-/// Native input manager for macOS
+/// Element of the macOS accessibility hierarchy (as VoiceOver sees it), including
+/// browser chrome such as the address bar and toolbar buttons
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NativeAxElement {
+    /// `AXRole`, e.g. `AXButton` or `AXTextField`
+    pub role: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub value: Option<String>,
+    /// Screen frame as (x, y, width, height)
+    pub frame: Option<(f64, f64, f64, f64)>,
+    /// Every attribute the element reports, with its value as text
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// Native input manager for macOS, Linux and Windows
+#[derive(Clone)]
 pub struct NativeInputManager {
     #[cfg(target_os = "macos")]
     event_source: CGEventSource,
+    #[cfg(target_os = "linux")]
+    tool: linux::InputTool,
+    #[cfg(target_os = "linux")]
+    runner: linux::CommandRunner,
 }
 
 impl NativeInputManager {
-    /// Create a new native input manager
+    /// Create a new native input manager. On Linux this fails unless `xdotool`, or `ydotool`
+    /// under Wayland, is on `PATH`.
     pub fn new() -> Result<Self> {
+        Self::for_display(None)
+    }
+
+    /// Native input manager for the X display `display` (e.g. `:99`) rather than the one in
+    /// `DISPLAY`; other platforms have no X display and ignore it
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    pub fn for_display(display: Option<&str>) -> Result<Self> {
         #[cfg(target_os = "macos")]
         {
             let event_source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
@@ -28,13 +61,44 @@ impl NativeInputManager {
             Ok(Self { event_source })
         }
         
-        #[cfg(not(target_os = "macos"))]
+        #[cfg(target_os = "linux")]
+        {
+            let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+            let tool = linux::detect_tool(wayland, linux::is_on_path)?;
+            debug!("Using {} for native input", tool.program());
+            let display = display.map(|d| d.to_string());
+            let runner = move |program: &str, args: &[String]| linux::run_command(program, args, display.as_deref());
+            Ok(Self::with_command_runner(tool, std::sync::Arc::new(runner)))
+        }
+
+        #[cfg(target_os = "windows")]
         {
-            warn!("Native input is only supported on macOS");
+            Ok(Self {})
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+        {
+            warn!("Native input is only supported on macOS, Linux and Windows");
             Ok(Self {})
         }
     }
 
+    /// Native input manager sending its input through `runner` (e.g. one that records the
+    /// commands instead of running them) with the given tool's command line syntax
+    #[cfg(target_os = "linux")]
+    pub fn with_command_runner(tool: linux::InputTool, runner: linux::CommandRunner) -> Self {
+        Self { tool, runner }
+    }
+
+    /// Run the tool invocations performing `input`
+    #[cfg(target_os = "linux")]
+    fn run(&self, input: linux::Input) -> Result<()> {
+        for args in linux::invocations(self.tool, &input)? {
+            (self.runner)(self.tool.program(), &args)?;
+        }
+        Ok(())
+    }
+
     /// Click at screen coordinates
     pub fn click_at(&self, x: f64, y: f64) -> Result<()> {
         debug!("Native click at ({}, {})", x, y);
@@ -67,9 +131,19 @@ impl NativeInputManager {
             Ok(())
         }
         
-        #[cfg(not(target_os = "macos"))]
+        #[cfg(target_os = "linux")]
+        {
+            self.run(linux::Input::Click { x, y, button: linux::MouseButton::Left, count: 1 })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows::click_at(x, y)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
         {
-            Err(ChromeMcpError::native_input_error("Native input only supported on macOS"))
+            Err(ChromeMcpError::native_input_error("Native input only supported on macOS, Linux and Windows"))
         }
     }
 
@@ -102,9 +176,19 @@ impl NativeInputManager {
             Ok(())
         }
         
-        #[cfg(not(target_os = "macos"))]
+        #[cfg(target_os = "linux")]
         {
-            Err(ChromeMcpError::native_input_error("Native input only supported on macOS"))
+            self.run(linux::Input::Click { x, y, button: linux::MouseButton::Right, count: 1 })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows::right_click_at(x, y)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+        {
+            Err(ChromeMcpError::native_input_error("Native input only supported on macOS, Linux and Windows"))
         }
     }
 
@@ -166,9 +250,19 @@ impl NativeInputManager {
             Ok(())
         }
         
-        #[cfg(not(target_os = "macos"))]
+        #[cfg(target_os = "linux")]
+        {
+            self.run(linux::Input::Click { x, y, button: linux::MouseButton::Left, count: 2 })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows::double_click_at(x, y)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
         {
-            Err(ChromeMcpError::native_input_error("Native input only supported on macOS"))
+            Err(ChromeMcpError::native_input_error("Native input only supported on macOS, Linux and Windows"))
         }
     }
 
@@ -192,9 +286,19 @@ impl NativeInputManager {
             Ok(())
         }
         
-        #[cfg(not(target_os = "macos"))]
+        #[cfg(target_os = "linux")]
+        {
+            self.run(linux::Input::Move { x, y })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows::move_to(x, y)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
         {
-            Err(ChromeMcpError::native_input_error("Native input only supported on macOS"))
+            Err(ChromeMcpError::native_input_error("Native input only supported on macOS, Linux and Windows"))
         }
     }
 
@@ -224,9 +328,19 @@ impl NativeInputManager {
             Ok(())
         }
         
-        #[cfg(not(target_os = "macos"))]
+        #[cfg(target_os = "linux")]
+        {
+            self.run(linux::Input::Scroll { x, y, delta_x, delta_y })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows::scroll_at(x, y, delta_x, delta_y)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
         {
-            Err(ChromeMcpError::native_input_error("Native input only supported on macOS"))
+            Err(ChromeMcpError::native_input_error("Native input only supported on macOS, Linux and Windows"))
         }
     }
 
@@ -264,9 +378,19 @@ impl NativeInputManager {
             Ok(())
         }
         
-        #[cfg(not(target_os = "macos"))]
+        #[cfg(target_os = "linux")]
         {
-            Err(ChromeMcpError::native_input_error("Native input only supported on macOS"))
+            self.run(linux::Input::Type(text))
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows::type_text(text)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+        {
+            Err(ChromeMcpError::native_input_error("Native input only supported on macOS, Linux and Windows"))
         }
     }
 
@@ -295,9 +419,35 @@ impl NativeInputManager {
             Ok(())
         }
         
+        #[cfg(target_os = "linux")]
+        {
+            self.run(linux::Input::Key(key_code))
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            windows::press_key(key_code)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+        {
+            Err(ChromeMcpError::native_input_error("Native input only supported on macOS, Linux and Windows"))
+        }
+    }
+
+    /// Accessibility element at screen coordinates, from the macOS accessibility API.
+    /// Requires the Accessibility permission for the process running chrome-mcp.
+    pub fn get_native_accessibility_element(&self, x: f64, y: f64) -> Result<NativeAxElement> {
+        debug!("Native accessibility element at ({}, {})", x, y);
+
+        #[cfg(target_os = "macos")]
+        {
+            ax::element_at(x, y)
+        }
+
         #[cfg(not(target_os = "macos"))]
         {
-            Err(ChromeMcpError::native_input_error("Native input only supported on macOS"))
+            Err(ChromeMcpError::native_input_error("Native accessibility only supported on macOS"))
         }
     }
 
@@ -446,9 +596,692 @@ impl NativeKeycodesData {
 
 pub type NativeKeycodes = NativeKeycodesData;
 
-impl Default for NativeInputManager {
-    fn default() -> Self {
-        Self::new().expect("Failed to create native input manager")
+/// Bindings to the accessibility API of the ApplicationServices framework
+#[cfg(target_os = "macos")]
+mod ax {
+    use super::NativeAxElement;
+    use crate::error::{ChromeMcpError, Result};
+    use core_foundation::array::{CFArray, CFArrayRef};
+    use core_foundation::base::{CFRelease, CFType, CFTypeRef, TCFType};
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::number::CFNumber;
+    use core_foundation::string::{CFString, CFStringRef};
+    use core_graphics::geometry::{CGPoint, CGSize};
+    use std::os::raw::c_void;
+
+    type AXUIElementRef = *const c_void;
+    type AXError = i32;
+
+    const AX_ERROR_SUCCESS: AXError = 0;
+    const AX_ERROR_NO_VALUE: AXError = -25212;
+    const AX_VALUE_TYPE_CG_POINT: u32 = 1;
+    const AX_VALUE_TYPE_CG_SIZE: u32 = 2;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> u8;
+        fn AXUIElementCreateSystemWide() -> AXUIElementRef;
+        fn AXUIElementCopyElementAtPosition(application: AXUIElementRef, x: f32, y: f32, element: *mut AXUIElementRef) -> AXError;
+        fn AXUIElementCopyAttributeNames(element: AXUIElementRef, names: *mut CFArrayRef) -> AXError;
+        fn AXUIElementCopyAttributeValue(element: AXUIElementRef, attribute: CFStringRef, value: *mut CFTypeRef) -> AXError;
+        fn AXValueGetValue(value: CFTypeRef, value_type: u32, value_ptr: *mut c_void) -> u8;
+    }
+
+    /// Owned `AXUIElementRef`, released on drop
+    struct Element(AXUIElementRef);
+
+    impl Drop for Element {
+        fn drop(&mut self) {
+            unsafe { CFRelease(self.0) }
+        }
+    }
+
+    impl Element {
+        fn attribute(&self, name: &str) -> Option<CFType> {
+            let name = CFString::new(name);
+            let mut value: CFTypeRef = std::ptr::null();
+            let error = unsafe { AXUIElementCopyAttributeValue(self.0, name.as_concrete_TypeRef(), &mut value) };
+            (error == AX_ERROR_SUCCESS && !value.is_null()).then(|| unsafe { CFType::wrap_under_create_rule(value) })
+        }
+
+        fn attribute_names(&self) -> Vec<String> {
+            let mut names: CFArrayRef = std::ptr::null();
+            let error = unsafe { AXUIElementCopyAttributeNames(self.0, &mut names) };
+            if error != AX_ERROR_SUCCESS || names.is_null() {
+                return Vec::new();
+            }
+            let names: CFArray<CFString> = unsafe { CFArray::wrap_under_create_rule(names) };
+            names.iter().map(|name| name.to_string()).collect()
+        }
+
+        fn string_attribute(&self, name: &str) -> Option<String> {
+            self.attribute(name).map(|value| value_text(&value)).filter(|text| !text.is_empty())
+        }
+
+        fn frame(&self) -> Option<(f64, f64, f64, f64)> {
+            let mut position = CGPoint::new(0.0, 0.0);
+            let mut size = CGSize::new(0.0, 0.0);
+            let position_value = self.attribute("AXPosition")?;
+            let size_value = self.attribute("AXSize")?;
+            let read = unsafe {
+                AXValueGetValue(position_value.as_CFTypeRef(), AX_VALUE_TYPE_CG_POINT, &mut position as *mut CGPoint as *mut c_void) != 0
+                    && AXValueGetValue(size_value.as_CFTypeRef(), AX_VALUE_TYPE_CG_SIZE, &mut size as *mut CGSize as *mut c_void) != 0
+            };
+            read.then_some((position.x, position.y, size.width, size.height))
+        }
+    }
+
+    /// Text of an attribute value: strings, numbers and booleans as such, anything else
+    /// as its Core Foundation description
+    fn value_text(value: &CFType) -> String {
+        if let Some(string) = value.downcast::<CFString>() {
+            string.to_string()
+        } else if let Some(number) = value.downcast::<CFNumber>() {
+            number.to_f64().map(|n| n.to_string()).unwrap_or_default()
+        } else if let Some(boolean) = value.downcast::<CFBoolean>() {
+            bool::from(boolean).to_string()
+        } else {
+            format!("{:?}", value)
+        }
+    }
+
+    pub fn element_at(x: f64, y: f64) -> Result<NativeAxElement> {
+        if unsafe { AXIsProcessTrusted() } == 0 {
+            return Err(ChromeMcpError::native_input_error(
+                "Accessibility permission required: allow this process in System Settings → Privacy & Security → Accessibility",
+            ));
+        }
+
+        let system = Element(unsafe { AXUIElementCreateSystemWide() });
+        let mut element: AXUIElementRef = std::ptr::null();
+        let error = unsafe { AXUIElementCopyElementAtPosition(system.0, x as f32, y as f32, &mut element) };
+        if error == AX_ERROR_NO_VALUE || (error == AX_ERROR_SUCCESS && element.is_null()) {
+            return Err(ChromeMcpError::element_not_found(format!("No accessibility element at ({}, {})", x, y)));
+        }
+        if error != AX_ERROR_SUCCESS {
+            return Err(ChromeMcpError::native_input_error(format!("AXUIElementCopyElementAtPosition failed with error {}", error)));
+        }
+        let element = Element(element);
+
+        let attributes = element
+            .attribute_names()
+            .into_iter()
+            .filter_map(|name| element.attribute(&name).map(|value| (name, value_text(&value))))
+            .collect();
+
+        Ok(NativeAxElement {
+            role: element.string_attribute("AXRole"),
+            title: element.string_attribute("AXTitle"),
+            description: element.string_attribute("AXDescription"),
+            value: element.string_attribute("AXValue"),
+            frame: element.frame(),
+            attributes,
+        })
+    }
+}
+
+/// Command lines of `xdotool` and `ydotool`, the input tools native input runs on Linux
+#[cfg(target_os = "linux")]
+pub mod linux {
+    use super::NativeKeycodesData as Key;
+    use crate::error::{ChromeMcpError, Result};
+    use std::path::Path;
+    use std::process::Command;
+    use std::sync::Arc;
+
+    /// Runs one invocation of an input tool, e.g. `("xdotool", ["mousemove", "10", "20"])`
+    pub type CommandRunner = Arc<dyn Fn(&str, &[String]) -> Result<()> + Send + Sync>;
+
+    /// Delay between the clicks of a double click, in milliseconds
+    const DOUBLE_CLICK_DELAY_MS: u32 = 100;
+
+    /// Delay between typed characters, in milliseconds
+    const TYPING_DELAY_MS: u32 = 10;
+
+    /// Program injecting the input
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum InputTool {
+        /// X11 (and XWayland windows)
+        Xdotool,
+        /// Any display server, through the uinput kernel module; needs `ydotoold` running
+        Ydotool,
+    }
+
+    impl InputTool {
+        pub fn program(&self) -> &'static str {
+            match self {
+                InputTool::Xdotool => "xdotool",
+                InputTool::Ydotool => "ydotool",
+            }
+        }
+    }
+
+    // The server binary only clicks natively; the other input is there for library users
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum MouseButton {
+        Left,
+        Right,
+    }
+
+    /// One native input action
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Input<'a> {
+        Click { x: f64, y: f64, button: MouseButton, count: u32 },
+        Move { x: f64, y: f64 },
+        /// Positive deltas scroll right and down, in wheel clicks
+        Scroll { x: f64, y: f64, delta_x: i32, delta_y: i32 },
+        Type(&'a str),
+        /// macOS virtual key code, as in `NativeKeycodesData`
+        Key(u16),
+    }
+
+    /// `ydotool` under Wayland when installed, `xdotool` otherwise
+    pub fn detect_tool(wayland: bool, on_path: impl Fn(&str) -> bool) -> Result<InputTool> {
+        if wayland && on_path("ydotool") {
+            Ok(InputTool::Ydotool)
+        } else if on_path("xdotool") {
+            Ok(InputTool::Xdotool)
+        } else if wayland {
+            Err(ChromeMcpError::native_input_error(
+                "Native input on Wayland needs ydotool (with ydotoold running) or xdotool on PATH",
+            ))
+        } else {
+            Err(ChromeMcpError::native_input_error(
+                "Native input on Linux needs xdotool on PATH (e.g. apt install xdotool)",
+            ))
+        }
+    }
+
+    /// Whether `program` is an executable file in a `PATH` directory
+    pub fn is_on_path(program: &str) -> bool {
+        std::env::var_os("PATH")
+            .map(|path| std::env::split_paths(&path).any(|dir| is_executable(&dir.join(program))))
+            .unwrap_or(false)
+    }
+
+    fn is_executable(path: &Path) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata().map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+    }
+
+    /// Run an input tool, on the X `display` if given, and wait for it, failing with its
+    /// error output
+    pub fn run_command(program: &str, args: &[String], display: Option<&str>) -> Result<()> {
+        let mut command = Command::new(program);
+        if let Some(display) = display {
+            command.env("DISPLAY", display);
+        }
+        let output = command
+            .args(args)
+            .output()
+            .map_err(|e| ChromeMcpError::native_input_error(format!("Could not run {}: {}", program, e)))?;
+        if !output.status.success() {
+            return Err(ChromeMcpError::native_input_error(format!(
+                "{} {} failed ({}): {}",
+                program,
+                args.first().map(String::as_str).unwrap_or_default(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Arguments of the tool invocations performing `input`, in order
+    pub fn invocations(tool: InputTool, input: &Input) -> Result<Vec<Vec<String>>> {
+        let args = |args: &[&dyn ToString]| args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        let pixel = |value: f64| value.round() as i64;
+
+        let invocations = match (tool, input) {
+            (InputTool::Xdotool, Input::Click { x, y, button, count }) => {
+                let button = match button {
+                    MouseButton::Left => 1,
+                    MouseButton::Right => 3,
+                };
+                vec![args(&[&"mousemove", &pixel(*x), &pixel(*y), &"click", &"--repeat", count, &"--delay", &DOUBLE_CLICK_DELAY_MS, &button])]
+            }
+            (InputTool::Xdotool, Input::Move { x, y }) => vec![args(&[&"mousemove", &pixel(*x), &pixel(*y)])],
+            (InputTool::Xdotool, Input::Scroll { x, y, delta_x, delta_y }) => {
+                // Buttons 4 to 7 are the wheel: up, down, left, right
+                let mut command = args(&[&"mousemove", &pixel(*x), &pixel(*y)]);
+                for (delta, negative, positive) in [(delta_y, 4, 5), (delta_x, 6, 7)] {
+                    if *delta != 0 {
+                        let button = if *delta < 0 { negative } else { positive };
+                        command.extend(args(&[&"click", &"--repeat", &delta.unsigned_abs(), &button]));
+                    }
+                }
+                vec![command]
+            }
+            (InputTool::Xdotool, Input::Type(text)) => vec![args(&[&"type", &"--delay", &TYPING_DELAY_MS, &"--", text])],
+            (InputTool::Xdotool, Input::Key(key_code)) => vec![args(&[&"key", &key(*key_code)?.1])],
+            (InputTool::Ydotool, Input::Click { x, y, button, count }) => {
+                // Button code with the "down" and "up" flags set
+                let button = match button {
+                    MouseButton::Left => "0xC0",
+                    MouseButton::Right => "0xC1",
+                };
+                vec![
+                    args(&[&"mousemove", &"--absolute", &"-x", &pixel(*x), &"-y", &pixel(*y)]),
+                    args(&[&"click", &"--repeat", count, &"--next-delay", &DOUBLE_CLICK_DELAY_MS, &button]),
+                ]
+            }
+            (InputTool::Ydotool, Input::Move { x, y }) => {
+                vec![args(&[&"mousemove", &"--absolute", &"-x", &pixel(*x), &"-y", &pixel(*y)])]
+            }
+            (InputTool::Ydotool, Input::Scroll { x, y, delta_x, delta_y }) => vec![
+                args(&[&"mousemove", &"--absolute", &"-x", &pixel(*x), &"-y", &pixel(*y)]),
+                // The vertical wheel axis points up
+                args(&[&"mousemove", &"--wheel", &"-x", delta_x, &"-y", &-delta_y]),
+            ],
+            (InputTool::Ydotool, Input::Type(text)) => vec![args(&[&"type", &"--key-delay", &TYPING_DELAY_MS, &"--", text])],
+            (InputTool::Ydotool, Input::Key(key_code)) => {
+                let code = key(*key_code)?.2;
+                vec![args(&[&"key", &format!("{}:1", code), &format!("{}:0", code)])]
+            }
+        };
+        Ok(invocations)
+    }
+
+    /// X keysym name and Linux input event code of a macOS virtual key code
+    fn key(key_code: u16) -> Result<(u16, &'static str, u16)> {
+        KEYS.iter()
+            .find(|(code, _, _)| *code == key_code)
+            .copied()
+            .ok_or_else(|| ChromeMcpError::native_input_error(format!("No Linux key for key code {}", key_code)))
+    }
+
+    const KEYS: &[(u16, &str, u16)] = &[
+        (Key::A, "a", 30),
+        (Key::B, "b", 48),
+        (Key::C, "c", 46),
+        (Key::D, "d", 32),
+        (Key::E, "e", 18),
+        (Key::F, "f", 33),
+        (Key::G, "g", 34),
+        (Key::H, "h", 35),
+        (Key::I, "i", 23),
+        (Key::J, "j", 36),
+        (Key::K, "k", 37),
+        (Key::L, "l", 38),
+        (Key::M, "m", 50),
+        (Key::N, "n", 49),
+        (Key::O, "o", 24),
+        (Key::P, "p", 25),
+        (Key::Q, "q", 16),
+        (Key::R, "r", 19),
+        (Key::S, "s", 31),
+        (Key::T, "t", 20),
+        (Key::U, "u", 22),
+        (Key::V, "v", 47),
+        (Key::W, "w", 17),
+        (Key::X, "x", 45),
+        (Key::Y, "y", 21),
+        (Key::Z, "z", 44),
+        (Key::DIGIT_0, "0", 11),
+        (Key::DIGIT_1, "1", 2),
+        (Key::DIGIT_2, "2", 3),
+        (Key::DIGIT_3, "3", 4),
+        (Key::DIGIT_4, "4", 5),
+        (Key::DIGIT_5, "5", 6),
+        (Key::DIGIT_6, "6", 7),
+        (Key::DIGIT_7, "7", 8),
+        (Key::DIGIT_8, "8", 9),
+        (Key::DIGIT_9, "9", 10),
+        (Key::EQUAL, "equal", 13),
+        (Key::MINUS, "minus", 12),
+        (Key::LEFT_BRACKET, "bracketleft", 26),
+        (Key::RIGHT_BRACKET, "bracketright", 27),
+        (Key::QUOTE, "apostrophe", 40),
+        (Key::SEMICOLON, "semicolon", 39),
+        (Key::BACKSLASH, "backslash", 43),
+        (Key::COMMA, "comma", 51),
+        (Key::SLASH, "slash", 53),
+        (Key::PERIOD, "period", 52),
+        (Key::GRAVE, "grave", 41),
+        (Key::RETURN, "Return", 28),
+        (Key::TAB, "Tab", 15),
+        (Key::SPACE, "space", 57),
+        // The macOS delete key erases backwards
+        (Key::DELETE, "BackSpace", 14),
+        (Key::FORWARD_DELETE, "Delete", 111),
+        (Key::ESCAPE, "Escape", 1),
+        (Key::COMMAND, "Super_L", 125),
+        (Key::RIGHT_COMMAND, "Super_R", 126),
+        (Key::SHIFT, "Shift_L", 42),
+        (Key::RIGHT_SHIFT, "Shift_R", 54),
+        (Key::OPTION, "Alt_L", 56),
+        (Key::RIGHT_OPTION, "Alt_R", 100),
+        (Key::CONTROL, "Control_L", 29),
+        (Key::RIGHT_CONTROL, "Control_R", 97),
+        (Key::CAPS_LOCK, "Caps_Lock", 58),
+        (Key::HELP, "Help", 138),
+        (Key::HOME, "Home", 102),
+        (Key::END, "End", 107),
+        (Key::PAGE_UP, "Prior", 104),
+        (Key::PAGE_DOWN, "Next", 109),
+        (Key::LEFT_ARROW, "Left", 105),
+        (Key::RIGHT_ARROW, "Right", 106),
+        (Key::UP_ARROW, "Up", 103),
+        (Key::DOWN_ARROW, "Down", 108),
+        (Key::VOLUME_UP, "XF86AudioRaiseVolume", 115),
+        (Key::VOLUME_DOWN, "XF86AudioLowerVolume", 114),
+        (Key::MUTE, "XF86AudioMute", 113),
+        (Key::KEYPAD_0, "KP_0", 82),
+        (Key::KEYPAD_1, "KP_1", 79),
+        (Key::KEYPAD_2, "KP_2", 80),
+        (Key::KEYPAD_3, "KP_3", 81),
+        (Key::KEYPAD_4, "KP_4", 75),
+        (Key::KEYPAD_5, "KP_5", 76),
+        (Key::KEYPAD_6, "KP_6", 77),
+        (Key::KEYPAD_7, "KP_7", 71),
+        (Key::KEYPAD_8, "KP_8", 72),
+        (Key::KEYPAD_9, "KP_9", 73),
+        (Key::KEYPAD_DECIMAL, "KP_Decimal", 83),
+        (Key::KEYPAD_MULTIPLY, "KP_Multiply", 55),
+        (Key::KEYPAD_PLUS, "KP_Add", 78),
+        (Key::KEYPAD_MINUS, "KP_Subtract", 74),
+        (Key::KEYPAD_DIVIDE, "KP_Divide", 98),
+        (Key::KEYPAD_EQUALS, "KP_Equal", 117),
+        (Key::KEYPAD_ENTER, "KP_Enter", 96),
+        // Clear sits where Num Lock is on PC keypads
+        (Key::KEYPAD_CLEAR, "Num_Lock", 69),
+        (Key::F1, "F1", 59),
+        (Key::F2, "F2", 60),
+        (Key::F3, "F3", 61),
+        (Key::F4, "F4", 62),
+        (Key::F5, "F5", 63),
+        (Key::F6, "F6", 64),
+        (Key::F7, "F7", 65),
+        (Key::F8, "F8", 66),
+        (Key::F9, "F9", 67),
+        (Key::F10, "F10", 68),
+        (Key::F11, "F11", 87),
+        (Key::F12, "F12", 88),
+        (Key::F13, "F13", 183),
+        (Key::F14, "F14", 184),
+        (Key::F15, "F15", 185),
+        (Key::F16, "F16", 186),
+        (Key::F17, "F17", 187),
+        (Key::F18, "F18", 188),
+        (Key::F19, "F19", 189),
+        (Key::F20, "F20", 190),
+    ];
+}
+
+/// Windows virtual keys of the macOS key codes, and the `SendInput` calls native input makes
+/// on Windows. The translation is also compiled for tests, so it is tested on every platform.
+#[cfg(any(windows, test))]
+pub mod windows {
+    use super::NativeKeycodesData as Key;
+    #[cfg(target_os = "windows")]
+    pub use self::send_input::*;
+
+    /// Largest coordinate of `SendInput`'s absolute mouse positions
+    pub const ABSOLUTE_MAX: f64 = 65535.0;
+
+    /// Windows virtual key (`VK_*`)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct VirtualKey {
+        pub code: u16,
+        /// Sent with `KEYEVENTF_EXTENDEDKEY`, telling e.g. the arrow keys from the keypad
+        pub extended: bool,
+    }
+
+    /// Windows virtual key of a macOS virtual key code, as in `NativeKeycodesData`
+    pub fn virtual_key(key_code: u16) -> Option<VirtualKey> {
+        KEYS.iter()
+            .find(|(code, _, _)| *code == key_code)
+            .map(|&(_, code, extended)| VirtualKey { code, extended })
+    }
+
+    /// `SendInput` absolute position of a screen point, scaled from the `(left, top, width,
+    /// height)` virtual screen to `[0, 65535]` on both axes
+    pub fn to_absolute(x: f64, y: f64, screen: (f64, f64, f64, f64)) -> (i32, i32) {
+        let (left, top, width, height) = screen;
+        let scale = |value: f64, origin: f64, size: f64| {
+            let span = (size - 1.0).max(1.0);
+            ((value - origin) * ABSOLUTE_MAX / span).round().clamp(0.0, ABSOLUTE_MAX) as i32
+        };
+        (scale(x, left, width), scale(y, top, height))
+    }
+
+    const KEYS: &[(u16, u16, bool)] = &[
+        (Key::A, 0x41, false),
+        (Key::B, 0x42, false),
+        (Key::C, 0x43, false),
+        (Key::D, 0x44, false),
+        (Key::E, 0x45, false),
+        (Key::F, 0x46, false),
+        (Key::G, 0x47, false),
+        (Key::H, 0x48, false),
+        (Key::I, 0x49, false),
+        (Key::J, 0x4A, false),
+        (Key::K, 0x4B, false),
+        (Key::L, 0x4C, false),
+        (Key::M, 0x4D, false),
+        (Key::N, 0x4E, false),
+        (Key::O, 0x4F, false),
+        (Key::P, 0x50, false),
+        (Key::Q, 0x51, false),
+        (Key::R, 0x52, false),
+        (Key::S, 0x53, false),
+        (Key::T, 0x54, false),
+        (Key::U, 0x55, false),
+        (Key::V, 0x56, false),
+        (Key::W, 0x57, false),
+        (Key::X, 0x58, false),
+        (Key::Y, 0x59, false),
+        (Key::Z, 0x5A, false),
+        (Key::DIGIT_0, 0x30, false),
+        (Key::DIGIT_1, 0x31, false),
+        (Key::DIGIT_2, 0x32, false),
+        (Key::DIGIT_3, 0x33, false),
+        (Key::DIGIT_4, 0x34, false),
+        (Key::DIGIT_5, 0x35, false),
+        (Key::DIGIT_6, 0x36, false),
+        (Key::DIGIT_7, 0x37, false),
+        (Key::DIGIT_8, 0x38, false),
+        (Key::DIGIT_9, 0x39, false),
+        // US layout punctuation (VK_OEM_*)
+        (Key::EQUAL, 0xBB, false),
+        (Key::MINUS, 0xBD, false),
+        (Key::LEFT_BRACKET, 0xDB, false),
+        (Key::RIGHT_BRACKET, 0xDD, false),
+        (Key::QUOTE, 0xDE, false),
+        (Key::SEMICOLON, 0xBA, false),
+        (Key::BACKSLASH, 0xDC, false),
+        (Key::COMMA, 0xBC, false),
+        (Key::SLASH, 0xBF, false),
+        (Key::PERIOD, 0xBE, false),
+        (Key::GRAVE, 0xC0, false),
+        (Key::RETURN, 0x0D, false),
+        (Key::TAB, 0x09, false),
+        (Key::SPACE, 0x20, false),
+        // The macOS delete key erases backwards
+        (Key::DELETE, 0x08, false),
+        (Key::FORWARD_DELETE, 0x2E, true),
+        (Key::ESCAPE, 0x1B, false),
+        (Key::COMMAND, 0x5B, true),
+        (Key::RIGHT_COMMAND, 0x5C, true),
+        (Key::SHIFT, 0xA0, false),
+        (Key::RIGHT_SHIFT, 0xA1, false),
+        (Key::OPTION, 0xA4, false),
+        (Key::RIGHT_OPTION, 0xA5, true),
+        (Key::CONTROL, 0xA2, false),
+        (Key::RIGHT_CONTROL, 0xA3, true),
+        (Key::CAPS_LOCK, 0x14, false),
+        (Key::HELP, 0x2F, false),
+        (Key::HOME, 0x24, true),
+        (Key::END, 0x23, true),
+        (Key::PAGE_UP, 0x21, true),
+        (Key::PAGE_DOWN, 0x22, true),
+        (Key::LEFT_ARROW, 0x25, true),
+        (Key::UP_ARROW, 0x26, true),
+        (Key::RIGHT_ARROW, 0x27, true),
+        (Key::DOWN_ARROW, 0x28, true),
+        (Key::VOLUME_UP, 0xAF, true),
+        (Key::VOLUME_DOWN, 0xAE, true),
+        (Key::MUTE, 0xAD, true),
+        (Key::KEYPAD_0, 0x60, false),
+        (Key::KEYPAD_1, 0x61, false),
+        (Key::KEYPAD_2, 0x62, false),
+        (Key::KEYPAD_3, 0x63, false),
+        (Key::KEYPAD_4, 0x64, false),
+        (Key::KEYPAD_5, 0x65, false),
+        (Key::KEYPAD_6, 0x66, false),
+        (Key::KEYPAD_7, 0x67, false),
+        (Key::KEYPAD_8, 0x68, false),
+        (Key::KEYPAD_9, 0x69, false),
+        (Key::KEYPAD_DECIMAL, 0x6E, false),
+        (Key::KEYPAD_MULTIPLY, 0x6A, false),
+        (Key::KEYPAD_PLUS, 0x6B, false),
+        (Key::KEYPAD_MINUS, 0x6D, false),
+        (Key::KEYPAD_DIVIDE, 0x6F, true),
+        (Key::KEYPAD_ENTER, 0x0D, true),
+        // Clear sits where Num Lock is on PC keypads
+        (Key::KEYPAD_CLEAR, 0x90, true),
+        (Key::F1, 0x70, false),
+        (Key::F2, 0x71, false),
+        (Key::F3, 0x72, false),
+        (Key::F4, 0x73, false),
+        (Key::F5, 0x74, false),
+        (Key::F6, 0x75, false),
+        (Key::F7, 0x76, false),
+        (Key::F8, 0x77, false),
+        (Key::F9, 0x78, false),
+        (Key::F10, 0x79, false),
+        (Key::F11, 0x7A, false),
+        (Key::F12, 0x7B, false),
+        (Key::F13, 0x7C, false),
+        (Key::F14, 0x7D, false),
+        (Key::F15, 0x7E, false),
+        (Key::F16, 0x7F, false),
+        (Key::F17, 0x80, false),
+        (Key::F18, 0x81, false),
+        (Key::F19, 0x82, false),
+        (Key::F20, 0x83, false),
+    ];
+
+    #[cfg(target_os = "windows")]
+    mod send_input {
+        use super::{to_absolute, virtual_key};
+        use crate::error::{ChromeMcpError, Result};
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+            SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT, KEYEVENTF_EXTENDEDKEY,
+            KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_LEFTDOWN,
+            MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP,
+            MOUSEEVENTF_VIRTUALDESK, MOUSEEVENTF_WHEEL, MOUSEINPUT,
+        };
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN, WHEEL_DELTA,
+        };
+
+        pub fn click_at(x: f64, y: f64) -> Result<()> {
+            send(&click(x, y, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, 1))
+        }
+
+        pub fn right_click_at(x: f64, y: f64) -> Result<()> {
+            send(&click(x, y, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, 1))
+        }
+
+        pub fn double_click_at(x: f64, y: f64) -> Result<()> {
+            send(&click(x, y, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, 2))
+        }
+
+        pub fn move_to(x: f64, y: f64) -> Result<()> {
+            send(&[move_input(x, y)])
+        }
+
+        /// Positive deltas scroll right and down, in wheel clicks
+        pub fn scroll_at(x: f64, y: f64, delta_x: i32, delta_y: i32) -> Result<()> {
+            // The vertical wheel turns forward (up) for positive data
+            let mut inputs = vec![move_input(x, y)];
+            if delta_y != 0 {
+                inputs.push(mouse(0, 0, -delta_y * WHEEL_DELTA as i32, MOUSEEVENTF_WHEEL));
+            }
+            if delta_x != 0 {
+                inputs.push(mouse(0, 0, delta_x * WHEEL_DELTA as i32, MOUSEEVENTF_HWHEEL));
+            }
+            send(&inputs)
+        }
+
+        /// Type each UTF-16 unit as a Unicode key, so any text goes through whatever the layout
+        pub fn type_text(text: &str) -> Result<()> {
+            let inputs: Vec<INPUT> = text
+                .encode_utf16()
+                .flat_map(|unit| [key(0, unit, KEYEVENTF_UNICODE), key(0, unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP)])
+                .collect();
+            send(&inputs)
+        }
+
+        pub fn press_key(key_code: u16) -> Result<()> {
+            let virtual_key = virtual_key(key_code)
+                .ok_or_else(|| ChromeMcpError::native_input_error(format!("No Windows key for key code {}", key_code)))?;
+            let flags = if virtual_key.extended { KEYEVENTF_EXTENDEDKEY } else { 0 };
+            send(&[key(virtual_key.code, 0, flags), key(virtual_key.code, 0, flags | KEYEVENTF_KEYUP)])
+        }
+
+        fn click(x: f64, y: f64, down: u32, up: u32, count: usize) -> Vec<INPUT> {
+            let mut inputs = vec![move_input(x, y)];
+            for _ in 0..count {
+                inputs.push(mouse(0, 0, 0, down));
+                inputs.push(mouse(0, 0, 0, up));
+            }
+            inputs
+        }
+
+        /// Move to a point of the virtual screen, which spans every monitor
+        fn move_input(x: f64, y: f64) -> INPUT {
+            // SAFETY: GetSystemMetrics only reads system settings
+            let screen = unsafe {
+                (
+                    GetSystemMetrics(SM_XVIRTUALSCREEN) as f64,
+                    GetSystemMetrics(SM_YVIRTUALSCREEN) as f64,
+                    GetSystemMetrics(SM_CXVIRTUALSCREEN) as f64,
+                    GetSystemMetrics(SM_CYVIRTUALSCREEN) as f64,
+                )
+            };
+            let (dx, dy) = to_absolute(x, y, screen);
+            mouse(dx, dy, 0, MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_VIRTUALDESK)
+        }
+
+        fn mouse(dx: i32, dy: i32, data: i32, flags: u32) -> INPUT {
+            INPUT {
+                r#type: INPUT_MOUSE,
+                Anonymous: INPUT_0 {
+                    mi: MOUSEINPUT { dx, dy, mouseData: data as u32, dwFlags: flags, time: 0, dwExtraInfo: 0 },
+                },
+            }
+        }
+
+        fn key(virtual_key: u16, scan: u16, flags: u32) -> INPUT {
+            INPUT {
+                r#type: INPUT_KEYBOARD,
+                Anonymous: INPUT_0 {
+                    ki: KEYBDINPUT { wVk: virtual_key, wScan: scan, dwFlags: flags, time: 0, dwExtraInfo: 0 },
+                },
+            }
+        }
+
+        fn send(inputs: &[INPUT]) -> Result<()> {
+            if inputs.is_empty() {
+                return Ok(());
+            }
+            // SAFETY: the pointer and length describe `inputs`, which outlives the call
+            let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32) };
+            if sent as usize != inputs.len() {
+                // Windows drops input into windows of more privileged processes (UIPI)
+                return Err(ChromeMcpError::native_input_error(format!(
+                    "SendInput injected {} of {} events: {}",
+                    sent,
+                    inputs.len(),
+                    std::io::Error::last_os_error()
+                )));
+            }
+            Ok(())
+        }
     }
 }
 
@@ -457,17 +1290,12 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(not(target_os = "linux"))]
     fn test_native_input_manager_creation() {
         let result = NativeInputManager::new();
         assert!(result.is_ok());
     }
 
-    #[test]
-    fn test_native_input_manager_default() {
-        // Should not panic
-        let _manager = NativeInputManager::default();
-    }
-
     #[test]
     fn test_key_codes_constants() {
         // Test that key codes are defined and have reasonable values
@@ -646,7 +1474,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     fn test_click_fails_on_non_macos() {
         let manager = NativeInputManager::new().unwrap();
         let result = manager.click_at(100.0, 100.0);
@@ -661,10 +1489,10 @@ mod tests {
     }
 
     #[test]
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     fn test_key_press_fails_on_non_macos() {
         let manager = NativeInputManager::new().unwrap();
-        let result = manager.key_press(NativeKeycodesData::SPACE);
+        let result = manager.press_key(NativeKeycodesData::SPACE);
         assert!(result.is_err());
         
         match result.unwrap_err() {
@@ -677,6 +1505,17 @@ mod tests {
 
     #[test]
     #[cfg(not(target_os = "macos"))]
+    fn test_native_accessibility_fails_on_non_macos() {
+        #[cfg(target_os = "linux")]
+        let manager = recording_manager(linux::InputTool::Xdotool).0;
+        #[cfg(not(target_os = "linux"))]
+        let manager = NativeInputManager::new().unwrap();
+        let result = manager.get_native_accessibility_element(100.0, 100.0);
+        assert!(matches!(result, Err(ChromeMcpError::NativeInput(_))));
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     fn test_type_text_fails_on_non_macos() {
         let manager = NativeInputManager::new().unwrap();
         let result = manager.type_text("test");
@@ -690,6 +1529,140 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_windows_virtual_keys() {
+        use windows::{virtual_key, VirtualKey};
+        let code = |key_code: u16| virtual_key(key_code).map(|key| key.code);
+
+        // Letters and digits are their ASCII codes, whatever the macOS key order
+        assert_eq!(code(NativeKeycodesData::A), Some(b'A' as u16));
+        assert_eq!(code(NativeKeycodesData::Z), Some(b'Z' as u16));
+        assert_eq!(code(NativeKeycodesData::M), Some(b'M' as u16));
+        assert_eq!(code(NativeKeycodesData::DIGIT_0), Some(b'0' as u16));
+        assert_eq!(code(NativeKeycodesData::DIGIT_5), Some(b'5' as u16));
+        assert_eq!(code(NativeKeycodesData::DIGIT_9), Some(b'9' as u16));
+
+        assert_eq!(code(NativeKeycodesData::F1), Some(0x70));
+        assert_eq!(code(NativeKeycodesData::F3), Some(0x72));
+        assert_eq!(code(NativeKeycodesData::F12), Some(0x7B));
+        assert_eq!(code(NativeKeycodesData::F20), Some(0x83));
+
+        assert_eq!(code(NativeKeycodesData::SHIFT), Some(0xA0));
+        assert_eq!(code(NativeKeycodesData::CONTROL), Some(0xA2));
+        assert_eq!(code(NativeKeycodesData::OPTION), Some(0xA4));
+        assert_eq!(virtual_key(NativeKeycodesData::COMMAND), Some(VirtualKey { code: 0x5B, extended: true }));
+        assert_eq!(virtual_key(NativeKeycodesData::RIGHT_CONTROL), Some(VirtualKey { code: 0xA3, extended: true }));
+
+        assert_eq!(virtual_key(NativeKeycodesData::RETURN), Some(VirtualKey { code: 0x0D, extended: false }));
+        assert_eq!(virtual_key(NativeKeycodesData::KEYPAD_ENTER), Some(VirtualKey { code: 0x0D, extended: true }));
+        assert_eq!(virtual_key(NativeKeycodesData::LEFT_ARROW), Some(VirtualKey { code: 0x25, extended: true }));
+        assert_eq!(code(NativeKeycodesData::DELETE), Some(0x08));
+        assert_eq!(virtual_key(NativeKeycodesData::FUNCTION), None);
+    }
+
+    #[test]
+    fn test_windows_absolute_coordinates() {
+        use windows::to_absolute;
+
+        let screen = (0.0, 0.0, 1920.0, 1080.0);
+        assert_eq!(to_absolute(0.0, 0.0, screen), (0, 0));
+        assert_eq!(to_absolute(1919.0, 1079.0, screen), (65535, 65535));
+        assert_eq!(to_absolute(960.0, 540.0, screen), (32785, 32798));
+        assert_eq!(to_absolute(-50.0, 5000.0, screen), (0, 65535));
+
+        // A monitor left of the primary one starts the virtual screen at a negative x
+        let screen = (-1280.0, 0.0, 3200.0, 1080.0);
+        assert_eq!(to_absolute(-1280.0, 0.0, screen).0, 0);
+        assert_eq!(to_absolute(0.0, 0.0, screen).0, 26222);
+    }
+
+    /// Manager recording the tool invocations instead of running them
+    #[cfg(target_os = "linux")]
+    fn recording_manager(tool: linux::InputTool) -> (NativeInputManager, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        let commands = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = std::sync::Arc::clone(&commands);
+        let runner: linux::CommandRunner = std::sync::Arc::new(move |program: &str, args: &[String]| {
+            log.lock().unwrap().push(format!("{} {}", program, args.join(" ")));
+            Ok(())
+        });
+        (NativeInputManager::with_command_runner(tool, runner), commands)
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_detect_linux_input_tool() {
+        use linux::{detect_tool, InputTool};
+
+        assert_eq!(detect_tool(false, |program| program == "xdotool").unwrap(), InputTool::Xdotool);
+        assert_eq!(detect_tool(false, |_| true).unwrap(), InputTool::Xdotool);
+        assert_eq!(detect_tool(true, |_| true).unwrap(), InputTool::Ydotool);
+        // XWayland windows still take X11 input
+        assert_eq!(detect_tool(true, |program| program == "xdotool").unwrap(), InputTool::Xdotool);
+
+        let error = detect_tool(false, |_| false).unwrap_err();
+        assert!(matches!(&error, ChromeMcpError::NativeInput(message) if message.contains("xdotool")));
+        let error = detect_tool(true, |program| program == "ydotoold").unwrap_err();
+        assert!(matches!(&error, ChromeMcpError::NativeInput(message) if message.contains("ydotool")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_xdotool_commands() {
+        let (manager, commands) = recording_manager(linux::InputTool::Xdotool);
+        manager.click_at(100.4, 200.6).unwrap();
+        manager.right_click_at(10.0, 20.0).unwrap();
+        manager.double_click_at(10.0, 20.0).unwrap();
+        manager.move_to(5.0, 6.0).unwrap();
+        manager.scroll_at(5.0, 6.0, -1, 3).unwrap();
+        manager.type_text("it's -v").unwrap();
+        manager.press_key(NativeKeycodesData::RETURN).unwrap();
+        manager.press_key(NativeKeycodesData::DELETE).unwrap();
+
+        assert_eq!(*commands.lock().unwrap(), vec![
+            "xdotool mousemove 100 201 click --repeat 1 --delay 100 1",
+            "xdotool mousemove 10 20 click --repeat 1 --delay 100 3",
+            "xdotool mousemove 10 20 click --repeat 2 --delay 100 1",
+            "xdotool mousemove 5 6",
+            "xdotool mousemove 5 6 click --repeat 3 5 click --repeat 1 6",
+            "xdotool type --delay 10 -- it's -v",
+            "xdotool key Return",
+            "xdotool key BackSpace",
+        ]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_ydotool_commands() {
+        let (manager, commands) = recording_manager(linux::InputTool::Ydotool);
+        manager.double_click_at(10.0, 20.0).unwrap();
+        manager.scroll_at(5.0, 6.0, 0, 2).unwrap();
+        manager.type_text("hello").unwrap();
+        manager.press_key(NativeKeycodesData::ESCAPE).unwrap();
+
+        assert_eq!(*commands.lock().unwrap(), vec![
+            "ydotool mousemove --absolute -x 10 -y 20",
+            "ydotool click --repeat 2 --next-delay 100 0xC0",
+            "ydotool mousemove --absolute -x 5 -y 6",
+            "ydotool mousemove --wheel -x 0 -y -2",
+            "ydotool type --key-delay 10 -- hello",
+            "ydotool key 1:1 1:0",
+        ]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_linux_input_errors() {
+        let (manager, commands) = recording_manager(linux::InputTool::Xdotool);
+        let error = manager.press_key(NativeKeycodesData::FUNCTION).unwrap_err();
+        assert!(matches!(error, ChromeMcpError::NativeInput(_)));
+        assert!(commands.lock().unwrap().is_empty());
+
+        let error = linux::run_command("/nonexistent/xdotool", &["key".to_string()], None).unwrap_err();
+        assert!(matches!(&error, ChromeMcpError::NativeInput(message) if message.contains("Could not run")));
+        let error = linux::run_command("false", &["key".to_string()], Some(":99")).unwrap_err();
+        assert!(matches!(&error, ChromeMcpError::NativeInput(message) if message.contains("false key failed")));
+    }
+
     #[test]
     fn test_key_code_uniqueness() {
         // Test that key codes are unique (no duplicates)
@@ -0,0 +1,355 @@
+//! Proxy mode: routing tool calls to downstream chrome-mcp servers by tab URL
+
+use crate::error::{ChromeMcpError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tracing::{debug, info};
+
+/// How long connecting to an upstream may take
+const UPSTREAM_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long an upstream may take to answer a request, tool waits included
+const UPSTREAM_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Downstream chrome-mcp server (run with `--listen`) handling the tabs whose URL matches
+/// `tab_url_pattern`, a glob where `*` matches any characters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxyRoute {
+    pub tab_url_pattern: String,
+    pub upstream_host: String,
+    pub upstream_port: u16,
+    /// `--listen-token` of the upstream, sent with `initialize`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl ProxyRoute {
+    /// Whether `url` matches the route's pattern
+    pub fn matches(&self, url: &str) -> bool {
+        let pattern = format!("^{}$", regex::escape(&self.tab_url_pattern).replace(r"\*", ".*"));
+        Regex::new(&pattern).is_ok_and(|re| re.is_match(url))
+    }
+
+    /// `host:port` of the upstream server
+    pub fn upstream(&self) -> String {
+        format!("{}:{}", self.upstream_host, self.upstream_port)
+    }
+}
+
+/// Read the routes of a `--proxy-config` file, a JSON array of `ProxyRoute`s
+pub fn load_routes(path: &Path) -> Result<Vec<ProxyRoute>> {
+    let routes: Vec<ProxyRoute> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    if routes.is_empty() {
+        return Err(ChromeMcpError::invalid_operation(format!("No proxy routes in {}", path.display())));
+    }
+    Ok(routes)
+}
+
+/// Forwards tool calls to the upstream whose route matches the active tab URL
+///
+/// Before each call the active URL is asked from the upstream that handled the previous call,
+/// so that links, redirects and tab switches are followed; when it cannot tell, the last known
+/// URL is kept. Until a URL is known, calls go to the first route. Connections to upstreams are
+/// opened on first use and kept open.
+pub struct ProxyRouter {
+    routes: Vec<ProxyRoute>,
+    upstreams: HashMap<String, Upstream>,
+    active_url: Option<String>,
+    /// `host:port` of the upstream that handled the last call
+    active_upstream: Option<String>,
+}
+
+impl ProxyRouter {
+    pub fn new(routes: Vec<ProxyRoute>) -> Self {
+        Self {
+            routes,
+            upstreams: HashMap::new(),
+            active_url: None,
+            active_upstream: None,
+        }
+    }
+
+    /// Route for a tool call: `chrome_navigate` is routed by its target URL, other tools by
+    /// the active tab URL
+    pub fn route(&self, name: &str, arguments: &Value) -> Result<&ProxyRoute> {
+        let url = match name {
+            "chrome_navigate" => arguments.get("url").and_then(|u| u.as_str()),
+            _ => self.active_url.as_deref(),
+        };
+
+        match url {
+            Some(url) => self
+                .routes
+                .iter()
+                .find(|route| route.matches(url))
+                .ok_or_else(|| ChromeMcpError::invalid_operation(format!("No proxy route matches {}", url))),
+            None => self
+                .routes
+                .first()
+                .ok_or_else(|| ChromeMcpError::invalid_operation("No proxy routes configured")),
+        }
+    }
+
+    /// Forward a tool call to its upstream and return the upstream's result text
+    pub async fn call_tool(&mut self, name: &str, arguments: &Value) -> Result<String> {
+        if name != "chrome_navigate" {
+            self.resolve_active_url().await;
+        }
+        let route = self.route(name, arguments)?.clone();
+        let upstream = route.upstream();
+        debug!("Forwarding {} to {}", name, upstream);
+
+        if !self.upstreams.contains_key(&upstream) {
+            let connection = Upstream::connect(&route).await?;
+            self.upstreams.insert(upstream.clone(), connection);
+        }
+        let connection = self.upstreams.get_mut(&upstream).expect("upstream connected above");
+
+        let result = match connection
+            .request("tools/call", json!({ "name": name, "arguments": arguments }))
+            .await
+        {
+            Ok(result) => result,
+            Err(UpstreamError::Rpc(message)) => {
+                let message = message.strip_prefix("Tool execution failed: ").unwrap_or(&message);
+                return Err(ChromeMcpError::mcp_protocol_error(format!("{}: {}", upstream, message)));
+            }
+            Err(UpstreamError::Connection(e)) => {
+                // Reconnect on the next call
+                self.upstreams.remove(&upstream);
+                return Err(e.in_frame("proxy", "call_tool").with_context("upstream", upstream));
+            }
+        };
+
+        self.active_upstream = Some(upstream);
+        if name == "chrome_navigate" {
+            self.active_url = arguments.get("url").and_then(|u| u.as_str()).map(|u| u.to_string());
+        }
+
+        Ok(result_text(&result))
+    }
+
+    /// Ask the upstream that handled the last call for the URL of its current tab
+    async fn resolve_active_url(&mut self) {
+        let Some(connection) = self.active_upstream.as_ref().and_then(|upstream| self.upstreams.get_mut(upstream)) else {
+            return;
+        };
+        let url = match connection.request("tools/call", json!({ "name": "chrome_page_info", "arguments": {} })).await {
+            Ok(result) => serde_json::from_str::<Value>(&result_text(&result))
+                .ok()
+                .and_then(|info| info.get("url").and_then(|u| u.as_str()).map(|u| u.to_string())),
+            Err(UpstreamError::Rpc(message)) => {
+                debug!("Upstream cannot tell the active tab URL: {}", message);
+                None
+            }
+            Err(UpstreamError::Connection(e)) => {
+                debug!("Upstream cannot tell the active tab URL: {}", e);
+                if let Some(upstream) = self.active_upstream.take() {
+                    self.upstreams.remove(&upstream);
+                }
+                None
+            }
+        };
+        if let Some(url) = url.filter(|url| !url.is_empty()) {
+            self.active_url = Some(url);
+        }
+    }
+}
+
+/// Text content of a `tools/call` result
+fn result_text(result: &Value) -> String {
+    result
+        .get("content")
+        .and_then(|c| c.get(0))
+        .and_then(|c| c.get("text"))
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Failure of a request to an upstream server
+enum UpstreamError {
+    /// The upstream answered with a JSON-RPC error
+    Rpc(String),
+    /// The connection failed or the answer could not be read
+    Connection(ChromeMcpError),
+}
+
+impl<E: Into<ChromeMcpError>> From<E> for UpstreamError {
+    fn from(error: E) -> Self {
+        UpstreamError::Connection(error.into())
+    }
+}
+
+/// Newline-delimited JSON-RPC connection to an upstream chrome-mcp server
+struct Upstream {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+    next_id: u64,
+}
+
+impl Upstream {
+    /// Connect and initialize the upstream, which connects it to its Chrome
+    async fn connect(route: &ProxyRoute) -> Result<Self> {
+        info!("Connecting to upstream chrome-mcp server at {}", route.upstream());
+        let connect = TcpStream::connect((route.upstream_host.as_str(), route.upstream_port));
+        let stream = timeout(UPSTREAM_CONNECT_TIMEOUT, connect)
+            .await
+            .map_err(|_| ChromeMcpError::cdp_connection(format!("Timed out connecting to upstream {}", route.upstream())))?
+            .map_err(|e| ChromeMcpError::cdp_connection(format!("Cannot reach upstream {}: {}", route.upstream(), e)))?;
+        let (reader, writer) = stream.into_split();
+
+        let mut upstream = Self {
+            reader: BufReader::new(reader),
+            writer,
+            next_id: 1,
+        };
+        let params = match &route.token {
+            Some(token) => json!({ "token": token }),
+            None => json!({}),
+        };
+        match upstream.request("initialize", params).await {
+            Ok(_) => Ok(upstream),
+            Err(UpstreamError::Rpc(message)) => Err(ChromeMcpError::mcp_protocol_error(format!(
+                "{} failed to initialize: {}",
+                route.upstream(),
+                message
+            ))),
+            Err(UpstreamError::Connection(e)) => Err(e),
+        }
+    }
+
+    /// Send a request and wait for the response with the same id, for at most
+    /// `UPSTREAM_REQUEST_TIMEOUT`
+    async fn request(&mut self, method: &str, params: Value) -> std::result::Result<Value, UpstreamError> {
+        let limit = UPSTREAM_REQUEST_TIMEOUT.as_millis() as u64;
+        timeout(UPSTREAM_REQUEST_TIMEOUT, self.exchange(method, params))
+            .await
+            .unwrap_or(Err(UpstreamError::Connection(ChromeMcpError::Timeout { timeout: limit })))
+    }
+
+    async fn exchange(&mut self, method: &str, params: Value) -> std::result::Result<Value, UpstreamError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        self.writer.write_all(format!("{}\n", request).as_bytes()).await?;
+        self.writer.flush().await?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line).await? == 0 {
+                return Err(UpstreamError::Connection(ChromeMcpError::cdp_connection(
+                    "Upstream closed the connection",
+                )));
+            }
+            let response: Value = serde_json::from_str(line.trim())?;
+            // Skip notifications and answers to other requests
+            if response.get("id") != Some(&json!(id)) {
+                continue;
+            }
+            if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+                let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error");
+                return Err(UpstreamError::Rpc(message.to_string()));
+            }
+            return Ok(response.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(pattern: &str, port: u16) -> ProxyRoute {
+        ProxyRoute {
+            tab_url_pattern: pattern.to_string(),
+            upstream_host: "127.0.0.1".to_string(),
+            upstream_port: port,
+            token: None,
+        }
+    }
+
+    #[test]
+    fn test_route_matching() {
+        let admin = route("https://admin.example.com/*", 9001);
+        assert!(admin.matches("https://admin.example.com/users?page=2"));
+        assert!(!admin.matches("https://example.com/admin.example.com/"));
+        assert!(route("*", 1).matches("about:blank"));
+        assert!(route("https://*.example.com/*", 1).matches("https://shop.example.com/cart"));
+
+        let mut router = ProxyRouter::new(vec![admin, route("*", 9002)]);
+        assert_eq!(router.route("chrome_click", &json!({})).unwrap().upstream_port, 9001);
+        let navigate = json!({ "url": "https://example.com/" });
+        assert_eq!(router.route("chrome_navigate", &navigate).unwrap().upstream_port, 9002);
+
+        router.active_url = Some("https://example.com/".to_string());
+        assert_eq!(router.route("chrome_click", &json!({})).unwrap().upstream_port, 9002);
+
+        let strict = ProxyRouter::new(vec![route("https://admin.example.com/*", 9001)]);
+        let error = strict.route("chrome_navigate", &navigate).unwrap_err();
+        assert!(error.to_string().contains("No proxy route matches https://example.com/"));
+    }
+
+    /// Fake upstream answering `tools/call` with `name`, and `chrome_page_info` with `url`
+    async fn fake_upstream(name: &'static str, url: &'static str) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let request: Value = serde_json::from_str(&line).unwrap();
+                let text = match request["params"]["name"].as_str() {
+                    Some("chrome_page_info") => json!({ "url": url }).to_string(),
+                    _ => name.to_string(),
+                };
+                let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": { "content": [{ "type": "text", "text": text }] } });
+                writer.write_all(format!("{}\n", response).as_bytes()).await.unwrap();
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_resolved_tab_url() {
+        let admin = fake_upstream("admin", "https://admin.example.com/users").await;
+        // A link on the shop leads to the admin site
+        let shop = fake_upstream("shop", "https://admin.example.com/").await;
+
+        let mut router = ProxyRouter::new(vec![route("https://admin.example.com/*", admin), route("*", shop)]);
+        let navigate = json!({ "url": "https://shop.example.com/" });
+        assert_eq!(router.call_tool("chrome_navigate", &navigate).await.unwrap(), "shop");
+        assert_eq!(router.call_tool("chrome_click", &json!({})).await.unwrap(), "admin");
+        assert_eq!(router.active_url.as_deref(), Some("https://admin.example.com/"));
+        assert_eq!(router.call_tool("chrome_click", &json!({})).await.unwrap(), "admin");
+        assert_eq!(router.active_url.as_deref(), Some("https://admin.example.com/users"));
+    }
+
+    #[test]
+    fn test_load_routes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proxy.json");
+        std::fs::write(
+            &path,
+            r#"[{ "tab_url_pattern": "https://admin.example.com/*", "upstream_host": "chrome-admin", "upstream_port": 7000 }]"#,
+        )
+        .unwrap();
+        let routes = load_routes(&path).unwrap();
+        assert_eq!(routes[0].upstream(), "chrome-admin:7000");
+        assert_eq!(routes[0].token, None);
+
+        std::fs::write(&path, "[]").unwrap();
+        assert!(load_routes(&path).unwrap_err().to_string().contains("No proxy routes"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Replay of MCP tool calls recorded with `--record-session`, for regression testing
+
+use crate::cdp::CdpClientBackend;
+use crate::error::{ChromeMcpError, Result};
+use crate::mcp::McpServer;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::path::Path;
+
+/// One recorded tool call and a hash of the result it produced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub tool_name: String,
+    #[serde(default)]
+    pub arguments: Value,
+    pub expected_result_hash: String,
+    /// Recorded result text, used to show a diff when the hash no longer matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_result: Option<String>,
+}
+
+impl ReplayStep {
+    /// Record a tool call and its outcome
+    pub fn new(tool_name: &str, arguments: &Value, result: &Result<String>) -> Self {
+        let text = result_text(result);
+        Self {
+            tool_name: tool_name.to_string(),
+            arguments: arguments.clone(),
+            expected_result_hash: result_hash(&text),
+            expected_result: Some(text),
+        }
+    }
+}
+
+/// Tool calls of a recorded session, in order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplaySession {
+    pub steps: Vec<ReplayStep>,
+}
+
+impl ReplaySession {
+    /// Read the tool call lines of a session file, skipping the recorded CDP commands
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut steps = Vec::new();
+
+        for (index, line) in contents.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let invalid_line = |e: serde_json::Error| {
+                ChromeMcpError::invalid_operation(format!("Invalid recording line {}: {}", index + 1, e))
+            };
+            let value: Value = serde_json::from_str(line).map_err(invalid_line)?;
+            if value.get("tool_name").is_some() {
+                steps.push(serde_json::from_value(value).map_err(invalid_line)?);
+            }
+        }
+
+        Ok(Self { steps })
+    }
+}
+
+/// Result of replaying one step
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayOutcome {
+    pub tool_name: String,
+    pub result: String,
+    pub result_hash: String,
+    pub expected_result_hash: String,
+    pub expected_result: Option<String>,
+}
+
+impl ReplayOutcome {
+    /// Whether the replayed result hashes the same as the recorded one
+    pub fn matches(&self) -> bool {
+        self.result_hash == self.expected_result_hash
+    }
+
+    /// Line diff between the recorded and the replayed result
+    pub fn diff(&self) -> String {
+        match &self.expected_result {
+            Some(expected) => diff_lines(expected, &self.result),
+            None => format!(
+                "- (result with hash {})\n+ (result with hash {})\n",
+                self.expected_result_hash, self.result_hash
+            ),
+        }
+    }
+}
+
+/// Connect to Chrome and run every step of `session` through the server's tools
+pub async fn replay<B: CdpClientBackend>(server: &mut McpServer<B>, session: &ReplaySession) -> Result<Vec<ReplayOutcome>> {
+    server.connect().await?;
+
+    let mut outcomes = Vec::with_capacity(session.steps.len());
+    for step in &session.steps {
+        let result = result_text(&server.call_tool(&step.tool_name, &step.arguments).await);
+        outcomes.push(ReplayOutcome {
+            tool_name: step.tool_name.clone(),
+            result_hash: result_hash(&result),
+            result,
+            expected_result_hash: step.expected_result_hash.clone(),
+            expected_result: step.expected_result.clone(),
+        });
+    }
+
+    Ok(outcomes)
+}
+
+/// Text a tool call produced: its result, or its root error
+fn result_text(result: &Result<String>) -> String {
+    match result {
+        Ok(text) => text.clone(),
+        Err(e) => format!("error: {}", e.root_cause()),
+    }
+}
+
+/// Stable FNV-1a hash of a tool result, as 16 hex digits
+pub fn result_hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Compare two texts line by line, listing removed (`-`) and added (`+`) lines
+fn diff_lines(expected: &str, actual: &str) -> String {
+    // Compared as line slices, so a missing final newline does not count as a change
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    TextDiff::from_slices(&expected, &actual)
+        .iter_all_changes()
+        .filter_map(|change| {
+            let sign = match change.tag() {
+                ChangeTag::Delete => "-",
+                ChangeTag::Insert => "+",
+                ChangeTag::Equal => return None,
+            };
+            Some(format!("{} {}\n", sign, change.value()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browser::Browser;
+    use crate::testing::{evaluate_response, MockCdpClient};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_replay_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.ndjson");
+
+        let old_title = serde_json::to_string_pretty(&json!({ "type": "string", "value": "Old" })).unwrap();
+        let recorded = ReplayStep::new("chrome_evaluate", &json!({ "javascript": "document.title" }), &Ok(old_title));
+        let failed = ReplayStep::new("chrome_unknown", &json!({}), &Err(ChromeMcpError::mcp_protocol_error("Unknown tool: chrome_unknown")));
+        let lines = [
+            serde_json::to_string(&json!({ "method": "Runtime.evaluate", "params": null })).unwrap(),
+            serde_json::to_string(&recorded).unwrap(),
+            serde_json::to_string(&failed).unwrap(),
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let session = ReplaySession::load(&path).unwrap();
+        assert_eq!(session.steps, vec![recorded, failed]);
+
+        let mock = MockCdpClient::new(vec![("Runtime.evaluate", evaluate_response(json!("New")))]);
+        let mut server = McpServer::with_browser(Browser::with_cdp_client(mock.client()).unwrap());
+        let outcomes = replay(&mut server, &session).await.unwrap();
+
+        assert_eq!(outcomes.len(), 2);
+        assert!(!outcomes[0].matches());
+        assert!(outcomes[0].result.contains("\"New\""));
+        assert_eq!(outcomes[0].diff(), "-   \"value\": \"Old\"\n+   \"value\": \"New\"\n");
+        assert!(outcomes[1].matches());
+        assert_eq!(outcomes[1].diff(), "");
+    }
+
+    #[test]
+    fn test_result_hash_and_diff() {
+        assert_eq!(result_hash(""), "cbf29ce484222325");
+        assert_eq!(result_hash("a"), "af63dc4c8601ec8c");
+        assert_ne!(result_hash("{\"x\": 1}"), result_hash("{\"x\": 2}"));
+
+        assert_eq!(diff_lines("a\nb\nc", "a\nB\nc\nd"), "- b\n+ B\n+ d\n");
+        assert_eq!(diff_lines("same", "same"), "");
+        // Lines after an insertion are not reported as changed
+        assert_eq!(diff_lines("a\nb", "x\na\nb"), "+ x\n");
+    }
+}
@@ -1,17 +1,107 @@
-use crate::cdp::CdpClient;
+use crate::cdp::{CdpClient, CdpClientBackend, WebSocketBackend};
+use crate::cdp_types::{CaptureScreenshotRequest, ScreenshotClip};
 use crate::error::{ChromeMcpError, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use regex::Regex;
 use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, trace};
+use url::Url;
+
+/// Directory `auto_save` screenshots go to unless configured otherwise
+pub const DEFAULT_SCREENSHOT_DIR: &str = "screenshots";
+
+/// File name (without extension) of auto-saved screenshots unless a template is given
+pub const DEFAULT_SCREENSHOT_NAME_TEMPLATE: &str = "{timestamp}-{url_slug}-{counter}";
+
+/// Longest `{url_slug}` substituted into screenshot names
+const MAX_URL_SLUG_LEN: usize = 60;
 
 /// Screenshot manager for capturing browser content
-pub struct ScreenshotManager {
-    cdp: CdpClient,
+#[derive(Clone)]
+pub struct ScreenshotManager<B = WebSocketBackend> {
+    cdp: CdpClient<B>,
+    /// Where `auto_save` writes when the caller does not pick a directory
+    auto_save_dir: PathBuf,
+    /// Screenshots `auto_save` keeps in its directory, deleting the oldest beyond that
+    max_files: Option<usize>,
+    /// Screenshots auto-saved so far, for `{counter}`
+    saved_count: u64,
+    /// Parameters of the device metrics override in effect, set with `set_viewport_size`
+    viewport_override: Option<Value>,
 }
 
-impl ScreenshotManager {
-    pub fn new(cdp: CdpClient) -> Self {
-        Self { cdp }
+impl<B: CdpClientBackend> ScreenshotManager<B> {
+    pub fn new(cdp: CdpClient<B>) -> Self {
+        Self {
+            cdp,
+            auto_save_dir: PathBuf::from(DEFAULT_SCREENSHOT_DIR),
+            max_files: None,
+            saved_count: 0,
+            viewport_override: None,
+        }
+    }
+
+    /// Send commands through `cdp` from now on, e.g. after it connected to another tab.
+    /// Overrides belong to the previous CDP session, so none is in effect on the new one.
+    pub fn set_cdp_client(&mut self, cdp: CdpClient<B>) {
+        self.cdp = cdp;
+        self.viewport_override = None;
+    }
+
+    /// Default directory of `auto_save`, and how many screenshots to keep in it (all when `None`)
+    pub fn set_auto_save(&mut self, output_dir: impl Into<PathBuf>, max_files: Option<usize>) {
+        self.auto_save_dir = output_dir.into();
+        self.max_files = max_files;
+    }
+
+    /// Directory `auto_save` writes to by default
+    pub fn auto_save_dir(&self) -> &Path {
+        &self.auto_save_dir
+    }
+
+    /// Save a screenshot captured in `format` to `output_dir` under a name built from
+    /// `name_template`, returning the file path
+    ///
+    /// The template may contain `{timestamp}` (Unix milliseconds), `{tab_id}`, `{url_slug}` and
+    /// `{counter}`; the extension follows `format`. The name must stay inside `output_dir`, so
+    /// path separators and `..` are refused. Once the directory holds more than the configured
+    /// maximum of screenshots named after the template, the oldest of those are deleted.
+    pub async fn auto_save(&mut self, base64_data: &str, format: &str, output_dir: &str, name_template: &str) -> Result<String> {
+        let bytes = self.decode_screenshot(base64_data)?;
+
+        let url = self.cdp.evaluate_js("location.href").await?
+            .get("value")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or_default();
+        self.saved_count += 1;
+        let name = render_screenshot_name(
+            name_template,
+            timestamp_ms,
+            self.cdp.current_tab_id().unwrap_or("tab"),
+            &url,
+            self.saved_count,
+        );
+        if name.is_empty() || name.contains(['/', '\\', ':']) || name.contains("..") {
+            return Err(ChromeMcpError::invalid_operation(format!(
+                "Invalid screenshot name {:?}: it must not be empty or contain path separators or '..'", name
+            )));
+        }
+
+        std::fs::create_dir_all(output_dir)
+            .map_err(|e| ChromeMcpError::screenshot_error(format!("Failed to create {}: {}", output_dir, e)))?;
+        let path = Path::new(output_dir).join(format!("{}.{}", name, format));
+        std::fs::write(&path, bytes)
+            .map_err(|e| ChromeMcpError::screenshot_error(format!("Failed to write file: {}", e)))?;
+        debug!("Screenshot auto-saved to: {}", path.display());
+
+        if let Some(max_files) = self.max_files {
+            rotate_screenshots(Path::new(output_dir), name_template, max_files)?;
+        }
+        Ok(path.display().to_string())
     }
 
     /// Capture a full-page screenshot
@@ -58,6 +148,84 @@ impl ScreenshotManager {
         self.extract_screenshot_data(result)
     }
 
+    /// Capture a full-page screenshot as a series of viewport screenshots, scrolling down
+    /// `chunk_height` CSS pixels at a time, so very long pages never have to be rendered in
+    /// one piece. Chunks taller than the viewport are reduced to the viewport height. The
+    /// scroll position is restored afterwards.
+    pub async fn capture_full_page_chunked(&mut self, format: &str, quality: Option<u32>, chunk_height: u32) -> Result<Vec<String>> {
+        if chunk_height == 0 {
+            return Err(ChromeMcpError::invalid_operation("Chunk height must be positive"));
+        }
+
+        let metrics = self.cdp.evaluate_js(
+            "({ pageHeight: Math.max(document.documentElement.scrollHeight, document.body ? document.body.scrollHeight : 0), \
+                viewportHeight: window.innerHeight, scrollX: window.scrollX, scrollY: window.scrollY })"
+        ).await?;
+        let metric = |name: &str| metrics.get("value").and_then(|v| v.get(name)).and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let page_height = metric("pageHeight").ceil() as u32;
+        let viewport_height = (metric("viewportHeight") as u32).max(1);
+        let chunk_height = chunk_height.min(viewport_height);
+        debug!("Capturing {}px page in chunks of {}px", page_height, chunk_height);
+
+        let chunks = self.capture_chunks(format, quality, page_height, viewport_height, chunk_height).await;
+        self.cdp.evaluate_js(&format!("window.scrollTo({}, {})", metric("scrollX"), metric("scrollY"))).await?;
+        chunks
+    }
+
+    async fn capture_chunks(
+        &mut self,
+        format: &str,
+        quality: Option<u32>,
+        page_height: u32,
+        viewport_height: u32,
+        chunk_height: u32,
+    ) -> Result<Vec<String>> {
+        let mut chunks = Vec::new();
+        let mut top = 0;
+
+        while top < page_height {
+            // Near the bottom the page cannot scroll as far as asked: the chunk then starts
+            // lower in the viewport
+            let scrolled = self.cdp.evaluate_js(&format!("(window.scrollTo(0, {}), window.scrollY)", top)).await?;
+            let scroll_y = scrolled.get("value").and_then(|v| v.as_f64()).unwrap_or(top as f64) as u32;
+            let offset = top.saturating_sub(scroll_y);
+            let height = chunk_height.min(page_height - top).min(viewport_height - offset.min(viewport_height - 1));
+
+            let data = self.capture_with_options(format, quality, false).await?;
+            if offset == 0 && height == viewport_height {
+                chunks.push(data);
+            } else {
+                let bytes = self.decode_screenshot(&data)?;
+                chunks.push(BASE64.encode(crop_rows(&bytes, format, quality, offset, height, viewport_height)?));
+            }
+            top += height;
+        }
+
+        Ok(chunks)
+    }
+
+    /// Stack decoded screenshot chunks vertically into one PNG, as wide as the widest chunk
+    pub fn stitch_chunks(chunks: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+        let mut images = Vec::with_capacity(chunks.len());
+        for chunk in &chunks {
+            images.push(decode_image(chunk)?);
+        }
+        if images.is_empty() {
+            return Err(ChromeMcpError::screenshot_error("No screenshot chunks to stitch"));
+        }
+
+        let width = images.iter().map(|image| image.width()).max().unwrap_or(0);
+        let height = images.iter().map(|image| image.height()).sum();
+        let mut stitched = image::RgbaImage::new(width, height);
+        let mut top = 0;
+        for image in &images {
+            image::imageops::replace(&mut stitched, &image.to_rgba8(), 0, top as i64);
+            top += image.height();
+        }
+
+        encode_image(&image::DynamicImage::ImageRgba8(stitched), "png", None)
+    }
+
     /// Capture screenshot of a specific element
     pub async fn capture_element(&mut self, selector: &str) -> Result<String> {
         debug!("Capturing element screenshot for selector: {}", selector);
@@ -66,13 +234,22 @@ impl ScreenshotManager {
         let bounds = self.get_element_bounds(selector).await?;
         
         // Capture screenshot with the specific clip area
+        self.capture_region(bounds.x, bounds.y, bounds.width, bounds.height).await
+    }
+
+    /// Capture a rectangle of the page, in CSS pixels, as a PNG
+    pub async fn capture_region(&mut self, x: f64, y: f64, width: f64, height: f64) -> Result<String> {
+        if width <= 0.0 || height <= 0.0 {
+            return Err(ChromeMcpError::invalid_operation(format!("Invalid region size {}x{}", width, height)));
+        }
+
         let result = self.cdp.send_command("Page.captureScreenshot", Some(json!({
             "format": "png",
             "clip": {
-                "x": bounds.x,
-                "y": bounds.y,
-                "width": bounds.width,
-                "height": bounds.height,
+                "x": x,
+                "y": y,
+                "width": width,
+                "height": height,
                 "scale": 1.0
             }
         }))).await?;
@@ -80,6 +257,76 @@ impl ScreenshotManager {
         self.extract_screenshot_data(result)
     }
 
+    /// Capture the whole of an element, including the parts outside the viewport, as a PNG.
+    /// The page is scrolled over the element one viewport at a time and the clipped strips
+    /// are stitched together; the scroll position is restored afterwards.
+    pub async fn capture_element_full(&mut self, selector: &str) -> Result<String> {
+        debug!("Capturing full element screenshot for selector: {}", selector);
+
+        let script = format!(
+            r#"(() => {{
+                const el = document.querySelector({});
+                if (!el) return null;
+                const scroll = {{ x: window.scrollX, y: window.scrollY }};
+                el.scrollIntoView({{ block: 'start' }});
+                const rect = el.getBoundingClientRect();
+                return {{
+                    left: rect.left + window.scrollX,
+                    top: rect.top + window.scrollY,
+                    width: el.scrollWidth,
+                    height: el.scrollHeight,
+                    viewportHeight: window.innerHeight,
+                    scrollX: scroll.x,
+                    scrollY: scroll.y
+                }};
+            }})()"#,
+            serde_json::to_string(selector)?
+        );
+        let result = self.cdp.evaluate_js(&script).await?;
+        let Some(element) = result.get("value").filter(|v| !v.is_null()) else {
+            return Err(ChromeMcpError::element_not_found(format!("Element not found: {}", selector)));
+        };
+        let metric = |name: &str| element.get(name).and_then(|v| v.as_f64()).unwrap_or(0.0);
+        if metric("width") <= 0.0 || metric("height") <= 0.0 {
+            return Err(ChromeMcpError::screenshot_error(format!("Element has no size: {}", selector)));
+        }
+
+        let strips = self
+            .capture_element_strips(metric("left"), metric("top"), metric("width"), metric("height"), metric("viewportHeight").max(1.0))
+            .await;
+        self.cdp.evaluate_js(&format!("window.scrollTo({}, {})", metric("scrollX"), metric("scrollY"))).await?;
+
+        Ok(BASE64.encode(Self::stitch_chunks(strips?)?))
+    }
+
+    /// Decoded PNG strips, at most one viewport tall, covering the given page area
+    async fn capture_element_strips(&mut self, left: f64, top: f64, width: f64, height: f64, viewport_height: f64) -> Result<Vec<Vec<u8>>> {
+        let mut strips = Vec::new();
+        let mut offset = 0.0;
+
+        while offset < height {
+            let strip_height = viewport_height.min(height - offset);
+            self.cdp.evaluate_js(&format!("window.scrollTo(window.scrollX, {})", top + offset)).await?;
+
+            let screenshot = self.cdp.capture_screenshot_typed(CaptureScreenshotRequest {
+                format: Some("png".to_string()),
+                clip: Some(ScreenshotClip {
+                    x: left,
+                    y: top + offset,
+                    width,
+                    height: strip_height,
+                    scale: 1.0,
+                }),
+                capture_beyond_viewport: Some(false),
+                ..Default::default()
+            }).await?;
+            strips.push(self.decode_screenshot(&screenshot.data)?);
+            offset += strip_height;
+        }
+
+        Ok(strips)
+    }
+
     /// Get element bounds for clipping
     async fn get_element_bounds(&mut self, selector: &str) -> Result<ElementBounds> {
         // Get document root
@@ -220,16 +467,34 @@ impl ScreenshotManager {
     pub async fn set_viewport_size(&mut self, width: u32, height: u32) -> Result<()> {
         debug!("Setting viewport size to {}x{}", width, height);
         
-        self.cdp.send_command("Emulation.setDeviceMetricsOverride", Some(json!({
+        let metrics = json!({
             "width": width,
             "height": height,
             "deviceScaleFactor": 1.0,
             "mobile": false
-        }))).await?;
+        });
+        self.cdp.send_command("Emulation.setDeviceMetricsOverride", Some(metrics.clone())).await?;
+        self.viewport_override = Some(metrics);
 
         Ok(())
     }
 
+    /// Device metrics override in effect, to put back with `restore_viewport`
+    pub fn viewport_override(&self) -> Option<Value> {
+        self.viewport_override.clone()
+    }
+
+    /// Put back a device metrics override returned by `viewport_override`; with `None`, go
+    /// back to the browser window's own viewport size
+    pub async fn restore_viewport(&mut self, metrics: Option<Value>) -> Result<()> {
+        match &metrics {
+            Some(metrics) => self.cdp.send_command("Emulation.setDeviceMetricsOverride", Some(metrics.clone())).await?,
+            None => self.cdp.send_command("Emulation.clearDeviceMetricsOverride", None).await?,
+        };
+        self.viewport_override = metrics;
+        Ok(())
+    }
+
     /// Capture PDF of the page
     pub async fn capture_pdf(&mut self, options: Option<PdfOptions>) -> Result<String> {
         debug!("Capturing PDF with options: {:?}", options);
@@ -291,6 +556,80 @@ impl ScreenshotManager {
     }
 }
 
+/// Common responsive breakpoints (Tailwind's, plus a small phone), by name
+pub const BREAKPOINT_PRESETS: &[(&str, u32)] =
+    &[("xs", 360), ("sm", 640), ("md", 768), ("lg", 1024), ("xl", 1280), ("2xl", 1536)];
+
+/// Viewport width of a named breakpoint preset
+pub fn breakpoint_preset(name: &str) -> Option<u32> {
+    BREAKPOINT_PRESETS.iter().find(|(preset, _)| *preset == name).map(|(_, width)| *width)
+}
+
+/// Fill in the placeholders of an `auto_save` name template
+fn render_screenshot_name(template: &str, timestamp_ms: u128, tab_id: &str, url: &str, counter: u64) -> String {
+    template
+        .replace("{timestamp}", &timestamp_ms.to_string())
+        .replace("{tab_id}", &slugify(tab_id))
+        .replace("{url_slug}", &url_slug(url))
+        .replace("{counter}", &format!("{:04}", counter))
+}
+
+/// Host and path of `url` as lowercase words joined by dashes, e.g. `example-com-docs-intro`
+fn url_slug(url: &str) -> String {
+    let slug = match Url::parse(url) {
+        Ok(parsed) => slugify(&format!("{}{}", parsed.host_str().unwrap_or_default(), parsed.path())),
+        Err(_) => slugify(url),
+    };
+    match slug.char_indices().nth(MAX_URL_SLUG_LEN) {
+        Some((end, _)) => slug[..end].trim_end_matches('-').to_string(),
+        None if slug.is_empty() => "page".to_string(),
+        None => slug,
+    }
+}
+
+/// Lowercase ASCII letters and digits of `text`, with a single dash for every other run
+fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Pattern matching the PNG and JPEG file names `render_screenshot_name` can build from `template`
+fn screenshot_name_pattern(template: &str) -> Regex {
+    let name = regex::escape(template)
+        .replace(r"\{timestamp\}", r"\d+")
+        .replace(r"\{tab_id\}", "[a-z0-9-]*")
+        .replace(r"\{url_slug\}", "[a-z0-9-]+")
+        .replace(r"\{counter\}", r"\d{4,}");
+    Regex::new(&format!(r"^{}\.(png|jpeg|jpg)$", name)).expect("escaped template is a valid pattern")
+}
+
+/// Delete the oldest screenshots named after `name_template` (by modification time) in `dir`
+/// until at most `max_files` are left, returning how many were deleted. Other files, even
+/// images, are left alone.
+fn rotate_screenshots(dir: &Path, name_template: &str, max_files: usize) -> Result<usize> {
+    let pattern = screenshot_name_pattern(name_template);
+    let mut screenshots: Vec<(SystemTime, PathBuf)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| pattern.is_match(n)))
+        .filter_map(|path| Some((std::fs::metadata(&path).and_then(|m| m.modified()).ok()?, path)))
+        .collect();
+    if screenshots.len() <= max_files {
+        return Ok(0);
+    }
+
+    screenshots.sort();
+    let excess = screenshots.len() - max_files;
+    for (_, path) in &screenshots[..excess] {
+        debug!("Deleting old screenshot: {}", path.display());
+        std::fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
+
 /// Element bounds for clipping
 #[derive(Debug, Clone)]
 struct ElementBounds {
@@ -349,10 +688,43 @@ impl Default for PdfOptions {
     }
 }
 
+/// Rows `[top, top + height)` (CSS px) of a viewport screenshot `viewport_height` CSS px tall,
+/// re-encoded in `format`
+fn crop_rows(bytes: &[u8], format: &str, quality: Option<u32>, top: u32, height: u32, viewport_height: u32) -> Result<Vec<u8>> {
+    let image = decode_image(bytes)?;
+
+    // Screenshots are in device pixels
+    let scale = image.height() as f64 / viewport_height as f64;
+    let y = ((top as f64 * scale).round() as u32).min(image.height().saturating_sub(1));
+    let rows = ((height as f64 * scale).round() as u32).clamp(1, image.height() - y);
+
+    encode_image(&image.crop_imm(0, y, image.width(), rows), format, quality)
+}
+
+fn decode_image(bytes: &[u8]) -> Result<image::DynamicImage> {
+    image::load_from_memory(bytes)
+        .map_err(|e| ChromeMcpError::screenshot_error(format!("Could not decode screenshot: {}", e)))
+}
+
+fn encode_image(image: &image::DynamicImage, format: &str, quality: Option<u32>) -> Result<Vec<u8>> {
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    let result = if format.eq_ignore_ascii_case("jpeg") {
+        let quality = quality.unwrap_or(80).clamp(1, 100) as u8;
+        image
+            .to_rgb8()
+            .write_with_encoder(image::codecs::jpeg::JpegEncoder::new_with_quality(&mut encoded, quality))
+    } else {
+        image.write_to(&mut encoded, image::ImageFormat::Png)
+    };
+    result.map_err(|e| ChromeMcpError::screenshot_error(format!("Could not encode screenshot: {}", e)))?;
+    Ok(encoded.into_inner())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cdp::CdpClient;
+    use crate::testing::{evaluate_response, MockCdpClient};
     use serde_json::json;
 
     #[test]
@@ -363,6 +735,180 @@ mod tests {
         // The actual manager is wrapped and we can't easily inspect internal fields
     }
 
+    #[test]
+    fn test_breakpoint_presets() {
+        assert_eq!(breakpoint_preset("md"), Some(768));
+        assert_eq!(breakpoint_preset("2xl"), Some(1536));
+        assert_eq!(breakpoint_preset("xxl"), None);
+    }
+
+    #[tokio::test]
+    async fn test_capture_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Page.captureScreenshot", json!({ "data": "iVBORw0KGgo=" })),
+            ("Page.captureScreenshot", json!({})),
+        ]);
+        let mut manager = ScreenshotManager::new(mock.client());
+
+        let data = manager.capture_with_options("jpeg", Some(70), true).await.unwrap();
+        assert_eq!(manager.decode_screenshot(&data).unwrap(), b"\x89PNG\r\n\x1a\n");
+
+        let params = mock.calls()[0].1.clone().unwrap();
+        assert_eq!(params["format"], "jpeg");
+        assert_eq!(params["quality"], 70);
+        assert_eq!(params["captureBeyondViewport"], true);
+
+        assert!(manager.capture_viewport().await.is_err());
+    }
+
+    #[test]
+    fn test_render_screenshot_name() {
+        let name = render_screenshot_name(
+            "{timestamp}_{tab_id}_{url_slug}_{counter}",
+            1700000000000,
+            "ABC123",
+            "https://Example.com/docs/Getting_Started?x=1#top",
+            7,
+        );
+        assert_eq!(name, "1700000000000_abc123_example-com-docs-getting-started_0007");
+
+        assert_eq!(url_slug("about:blank"), "blank");
+        assert_eq!(url_slug(""), "page");
+        assert!(url_slug(&format!("https://example.com/{}", "a/".repeat(100))).len() <= MAX_URL_SLUG_LEN);
+    }
+
+    #[test]
+    fn test_rotate_screenshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let names = ["1000-example-com-0001.png", "1001-example-com-0002.jpeg", "1002-example-com-0003.png", "notes.txt", "holiday.png"];
+        for (index, name) in names.iter().enumerate() {
+            let path = dir.path().join(name);
+            std::fs::write(&path, b"").unwrap();
+            let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1000 + index as u64);
+            std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        }
+
+        assert_eq!(rotate_screenshots(dir.path(), DEFAULT_SCREENSHOT_NAME_TEMPLATE, 2).unwrap(), 1);
+        assert!(!dir.path().join(names[0]).exists());
+        assert!(dir.path().join(names[1]).exists() && dir.path().join(names[2]).exists());
+        // Files the template could not have produced are never deleted
+        assert!(dir.path().join("notes.txt").exists() && dir.path().join("holiday.png").exists());
+        assert_eq!(rotate_screenshots(dir.path(), DEFAULT_SCREENSHOT_NAME_TEMPLATE, 2).unwrap(), 0);
+        assert_eq!(rotate_screenshots(dir.path(), "shot-{counter}", 0).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_auto_save() {
+        let mock = MockCdpClient::new(vec![]);
+        for url in ["https://example.com/a", "https://example.com/b", "https://example.com/c", "https://example.com/d"] {
+            mock.push_response("Runtime.evaluate", evaluate_response(json!(url)));
+        }
+        let mut manager = ScreenshotManager::new(mock.client());
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().to_str().unwrap();
+        manager.set_auto_save(output_dir, Some(1));
+
+        let first = manager.auto_save("iVBORw0KGgo=", "png", output_dir, "{url_slug}-{counter}").await.unwrap();
+        assert!(first.ends_with("example-com-a-0001.png"));
+        assert_eq!(std::fs::read(&first).unwrap(), b"\x89PNG\r\n\x1a\n");
+
+        let second = manager.auto_save("iVBORw0KGgo=", "png", output_dir, "{url_slug}-{counter}").await.unwrap();
+        assert!(second.ends_with("example-com-b-0002.png"));
+        assert_eq!(std::fs::read_dir(output_dir).unwrap().count(), 1);
+
+        for template in ["../{url_slug}", "/tmp/{counter}"] {
+            let error = manager.auto_save("iVBORw0KGgo=", "png", output_dir, template).await.unwrap_err();
+            assert!(matches!(error, ChromeMcpError::InvalidOperation(_)), "{}", template);
+        }
+        assert_eq!(std::fs::read_dir(output_dir).unwrap().count(), 1);
+    }
+
+    /// PNG of a solid image with the given dimensions
+    fn solid_png(width: u32, height: u32, color: [u8; 4]) -> Vec<u8> {
+        let image = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(width, height, image::Rgba(color)));
+        encode_image(&image, "png", None).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_capture_full_page_chunked_with_mock_cdp() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let mut bottom = image::RgbaImage::from_pixel(40, 200, image::Rgba(red));
+        image::imageops::replace(&mut bottom, &image::RgbaImage::from_pixel(40, 100, image::Rgba(blue)), 0, 100);
+        let mut bottom_png = std::io::Cursor::new(Vec::new());
+        bottom.write_to(&mut bottom_png, image::ImageFormat::Png).unwrap();
+
+        // 250px page, 100px viewport at device scale factor 2; the last scroll stops at 150
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({ "pageHeight": 250, "viewportHeight": 100, "scrollX": 0, "scrollY": 30 }))),
+            ("Runtime.evaluate", evaluate_response(json!(0))),
+            ("Page.captureScreenshot", json!({ "data": BASE64.encode(solid_png(40, 200, red)) })),
+            ("Runtime.evaluate", evaluate_response(json!(100))),
+            ("Page.captureScreenshot", json!({ "data": BASE64.encode(solid_png(40, 200, red)) })),
+            ("Runtime.evaluate", evaluate_response(json!(150))),
+            ("Page.captureScreenshot", json!({ "data": BASE64.encode(bottom_png.into_inner()) })),
+            ("Runtime.evaluate", evaluate_response(Value::Null)),
+        ]);
+        let mut manager = ScreenshotManager::new(mock.client());
+
+        let chunks = manager.capture_full_page_chunked("png", None, 500).await.unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(mock.remaining(), 0);
+        let calls = mock.calls();
+        assert_eq!(calls[5].1.as_ref().unwrap()["expression"], "(window.scrollTo(0, 200), window.scrollY)");
+        assert_eq!(calls[7].1.as_ref().unwrap()["expression"], "window.scrollTo(0, 30)");
+
+        let decoded: Vec<Vec<u8>> = chunks.iter().map(|c| manager.decode_screenshot(c).unwrap()).collect();
+        let last = decode_image(&decoded[2]).unwrap().to_rgba8();
+        assert_eq!(last.dimensions(), (40, 100));
+        assert_eq!(last.get_pixel(0, 0).0, blue);
+
+        let stitched = decode_image(&ScreenshotManager::<MockCdpClient>::stitch_chunks(decoded).unwrap()).unwrap().to_rgba8();
+        assert_eq!(stitched.dimensions(), (40, 500));
+        assert_eq!(stitched.get_pixel(0, 399).0, red);
+        assert_eq!(stitched.get_pixel(0, 400).0, blue);
+
+        assert!(manager.capture_full_page_chunked("png", None, 0).await.is_err());
+        assert!(ScreenshotManager::<MockCdpClient>::stitch_chunks(vec![]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_capture_element_full_with_mock_cdp() {
+        let mock = MockCdpClient::new(vec![
+            ("Runtime.evaluate", evaluate_response(json!({
+                "left": 8, "top": 120, "width": 30, "height": 250, "viewportHeight": 100, "scrollX": 0, "scrollY": 40
+            }))),
+            ("Page.captureScreenshot", json!({ "data": BASE64.encode(solid_png(30, 100, [255, 0, 0, 255])) })),
+            ("Page.captureScreenshot", json!({ "data": BASE64.encode(solid_png(30, 100, [0, 255, 0, 255])) })),
+            ("Page.captureScreenshot", json!({ "data": BASE64.encode(solid_png(30, 50, [0, 0, 255, 255])) })),
+        ]);
+        // Three scrolls and the scroll restoration
+        for _ in 0..4 {
+            mock.push_response("Runtime.evaluate", evaluate_response(Value::Null));
+        }
+        let mut manager = ScreenshotManager::new(mock.client());
+
+        let png = manager.capture_element_full("#report").await.unwrap();
+        let image = decode_image(&manager.decode_screenshot(&png).unwrap()).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (30, 250));
+        assert_eq!(image.get_pixel(0, 150).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(0, 249).0, [0, 0, 255, 255]);
+
+        let calls = mock.calls();
+        let clips: Vec<Value> = calls
+            .iter()
+            .filter(|(method, _)| method == "Page.captureScreenshot")
+            .map(|(_, params)| params.as_ref().unwrap()["clip"].clone())
+            .collect();
+        assert_eq!(clips[2], json!({ "x": 8.0, "y": 320.0, "width": 30.0, "height": 50.0, "scale": 1.0 }));
+        assert_eq!(calls[5].1.as_ref().unwrap()["expression"], "window.scrollTo(window.scrollX, 320)");
+        assert_eq!(calls.last().unwrap().1.as_ref().unwrap()["expression"], "window.scrollTo(0, 40)");
+
+        let mock = MockCdpClient::new(vec![("Runtime.evaluate", evaluate_response(Value::Null))]);
+        let error = ScreenshotManager::new(mock.client()).capture_element_full("#missing").await.unwrap_err();
+        assert!(error.to_string().contains("Element not found: #missing"));
+    }
+
     #[test]
     fn test_pdf_options_default() {
         let options = PdfOptions::default();
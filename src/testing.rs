@@ -0,0 +1,484 @@
+use crate::cdp::{CdpClient, CdpClientBackend, EventSubscribers, FrameBytes, TabInfo, WebSocketBackend};
+use crate::error::{ChromeMcpError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Tab every `MockCdpClient` starts with
+pub const MOCK_TAB_ID: &str = "mock-tab";
+
+/// Command received by a `MockCdpClient`: method and params
+pub type MockCall = (String, Option<Value>);
+
+/// Canned answer to the next command whose method matches `method_pattern`
+#[derive(Debug, Clone)]
+struct ScriptedResponse {
+    method_pattern: String,
+    response: std::result::Result<Value, String>,
+    /// Events delivered just before the response, as Chrome sends them while handling the command
+    events: Vec<(String, Value)>,
+}
+
+/// CDP backend that answers commands from a queue of pre-scripted responses instead of Chrome
+///
+/// Each command takes the first queued response whose method pattern matches it; patterns are
+/// either exact method names or end in `*` (`"Runtime.*"`, `"*"`). Clones share the queue and
+/// the call log, so a test can keep a clone to inspect what the code under test sent.
+#[derive(Debug, Clone)]
+pub struct MockCdpClient {
+    responses: Arc<Mutex<VecDeque<ScriptedResponse>>>,
+    calls: Arc<Mutex<Vec<MockCall>>>,
+    tabs: Arc<Mutex<Vec<TabInfo>>>,
+    tab_id: Option<String>,
+    events: EventSubscribers,
+    frame_bytes: FrameBytes,
+}
+
+impl MockCdpClient {
+    /// Create a mock with one open tab and the given `(method_pattern, result)` queue
+    pub fn new(responses: Vec<(&str, Value)>) -> Self {
+        let mock = Self {
+            responses: Arc::new(Mutex::new(VecDeque::new())),
+            calls: Arc::new(Mutex::new(Vec::new())),
+            tabs: Arc::new(Mutex::new(vec![mock_tab(MOCK_TAB_ID, "about:blank")])),
+            tab_id: None,
+            events: EventSubscribers::default(),
+            frame_bytes: FrameBytes::default(),
+        };
+        for (method_pattern, response) in responses {
+            mock.push_response(method_pattern, response);
+        }
+        mock
+    }
+
+    /// Wrap the mock in a `CdpClient`
+    pub fn client(&self) -> CdpClient<MockCdpClient> {
+        CdpClient::with_backend(self.clone())
+    }
+
+    /// Deliver a CDP event to the subscribers of `method`, as Chrome would
+    #[cfg(test)]
+    pub fn emit_event(&self, method: &str, params: Value) {
+        self.events.dispatch(method, &params);
+    }
+
+    /// Queue the result of a command
+    pub fn push_response(&self, method_pattern: &str, response: Value) {
+        self.responses.lock().unwrap().push_back(ScriptedResponse {
+            method_pattern: method_pattern.to_string(),
+            response: Ok(response),
+            events: Vec::new(),
+        });
+    }
+
+    /// Queue the result of a command, delivering `(method, params)` events before it
+    #[cfg(test)]
+    pub fn push_response_with_events(&self, method_pattern: &str, response: Value, events: Vec<(&str, Value)>) {
+        self.responses.lock().unwrap().push_back(ScriptedResponse {
+            method_pattern: method_pattern.to_string(),
+            response: Ok(response),
+            events: events.into_iter().map(|(method, params)| (method.to_string(), params)).collect(),
+        });
+    }
+
+    /// Queue a CDP protocol error for a command
+    #[cfg(test)]
+    pub fn push_error(&self, method_pattern: &str, message: &str) {
+        self.push_error_code(method_pattern, -32000, message);
+    }
+
+    /// Queue a protocol error with a specific CDP error code (e.g. -32601 for unknown methods)
+    #[cfg(test)]
+    pub fn push_error_code(&self, method_pattern: &str, code: i64, message: &str) {
+        self.responses.lock().unwrap().push_back(ScriptedResponse {
+            method_pattern: method_pattern.to_string(),
+            response: Err(format!("CDP error {}: {}", code, message)),
+            events: Vec::new(),
+        });
+    }
+
+    /// Create a mock that replays a session written by `RecordingCdpClient`
+    pub fn from_recording(path: &Path) -> Result<Self> {
+        let mock = Self::new(Vec::new());
+        let contents = std::fs::read_to_string(path)?;
+
+        for (index, line) in contents.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let invalid_line = |e: serde_json::Error| {
+                ChromeMcpError::invalid_operation(format!("Invalid recording line {}: {}", index + 1, e))
+            };
+            let value: Value = serde_json::from_str(line).map_err(invalid_line)?;
+            // Tool call lines are replayed by `chrome-mcp replay --live`, not by the mock
+            if value.get("tool_name").is_some() {
+                continue;
+            }
+            let command: RecordedCommand = serde_json::from_value(value).map_err(invalid_line)?;
+            mock.responses.lock().unwrap().push_back(ScriptedResponse {
+                method_pattern: command.method,
+                response: match command.error {
+                    Some(error) => Err(error),
+                    None => Ok(command.result.unwrap_or(Value::Null)),
+                },
+                events: Vec::new(),
+            });
+        }
+
+        Ok(mock)
+    }
+
+    /// Commands sent so far, in order
+    #[cfg(test)]
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Number of scripted responses not consumed yet
+    #[cfg(test)]
+    pub fn remaining(&self) -> usize {
+        self.responses.lock().unwrap().len()
+    }
+}
+
+impl CdpClientBackend for MockCdpClient {
+    async fn send_command_inner(&mut self, method: &str, params: Option<Value>) -> Result<Value> {
+        // No frames here: count the size the command and its answer would have on the wire
+        self.frame_bytes.record_sent(serde_json::json!({ "method": method, "params": params }).to_string().len());
+        self.calls.lock().unwrap().push((method.to_string(), params));
+
+        let mut responses = self.responses.lock().unwrap();
+        let scripted = responses
+            .iter()
+            .position(|r| method_matches(&r.method_pattern, method))
+            .and_then(|index| responses.remove(index))
+            .ok_or_else(|| ChromeMcpError::cdp_protocol(format!("No scripted response for {}", method)))?;
+        drop(responses);
+
+        for (event, params) in &scripted.events {
+            self.frame_bytes.record_received(params.to_string().len());
+            self.events.dispatch(event, params);
+        }
+        match &scripted.response {
+            Ok(result) => self.frame_bytes.record_received(result.to_string().len()),
+            Err(message) => self.frame_bytes.record_received(message.len()),
+        }
+        scripted.response.map_err(ChromeMcpError::cdp_protocol)
+    }
+
+    /// Answered like any other command, from the same queue
+    async fn send_session_command_inner(&mut self, _session_id: &str, method: &str, params: Option<Value>) -> Result<Value> {
+        self.send_command_inner(method, params).await
+    }
+
+    async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
+        Ok(self.tabs.lock().unwrap().clone())
+    }
+
+    async fn create_tab(&self, url: Option<&str>) -> Result<TabInfo> {
+        let mut tabs = self.tabs.lock().unwrap();
+        let tab = mock_tab(&format!("mock-tab-{}", tabs.len() + 1), url.unwrap_or("about:blank"));
+        tabs.push(tab.clone());
+        Ok(tab)
+    }
+
+    async fn close_tab(&self, tab_id: &str) -> Result<()> {
+        let mut tabs = self.tabs.lock().unwrap();
+        let count = tabs.len();
+        tabs.retain(|t| t.id != tab_id);
+        if tabs.len() == count {
+            return Err(ChromeMcpError::tab_not_found(format!("Tab {} not found", tab_id)));
+        }
+        Ok(())
+    }
+
+    async fn connect_to_tab(&mut self, tab_id: &str) -> Result<()> {
+        if !self.tabs.lock().unwrap().iter().any(|t| t.id == tab_id) {
+            return Err(ChromeMcpError::tab_not_found(format!("Tab {} not found", tab_id)));
+        }
+        self.tab_id = Some(tab_id.to_string());
+        Ok(())
+    }
+
+    fn current_tab_id(&self) -> Option<&str> {
+        self.tab_id.as_deref()
+    }
+
+    fn events(&self) -> &EventSubscribers {
+        &self.events
+    }
+
+    fn frame_bytes(&self) -> &FrameBytes {
+        &self.frame_bytes
+    }
+}
+
+/// One line of a recorded CDP session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCommand {
+    pub method: String,
+    pub params: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Shared writer of a newline-delimited JSON session recording
+#[derive(Clone)]
+pub struct SessionWriter(Arc<Mutex<LineWriter<File>>>);
+
+impl SessionWriter {
+    /// Create the recording file, truncating it
+    pub fn create(path: &Path) -> Result<Self> {
+        Ok(Self(Arc::new(Mutex::new(LineWriter::new(File::create(path)?)))))
+    }
+
+    /// Append one JSON line
+    pub fn write<T: Serialize>(&self, line: &T) -> Result<()> {
+        let line = serde_json::to_string(line)?;
+        writeln!(self.0.lock().unwrap(), "{}", line)?;
+        Ok(())
+    }
+}
+
+/// CDP backend that forwards to another backend and records every command to a
+/// newline-delimited JSON file, which `MockCdpClient::from_recording` can replay
+#[derive(Clone)]
+pub struct RecordingCdpClient<B = WebSocketBackend> {
+    inner: B,
+    writer: SessionWriter,
+}
+
+impl<B: CdpClientBackend> RecordingCdpClient<B> {
+    /// Wrap a backend, truncating the recording file
+    pub fn create(inner: B, path: &Path) -> Result<Self> {
+        Ok(Self {
+            inner,
+            writer: SessionWriter::create(path)?,
+        })
+    }
+
+    /// Writer of the recording, for recording MCP tool calls into the same session
+    pub fn session_writer(&self) -> SessionWriter {
+        self.writer.clone()
+    }
+
+    fn record(&self, command: &RecordedCommand) -> Result<()> {
+        self.writer.write(command)
+    }
+
+    /// Record the outcome of a command
+    fn record_result(&self, method: &str, params: Option<Value>, result: &Result<Value>) -> Result<()> {
+        let (recorded_result, error) = match result {
+            Ok(value) => (Some(value.clone()), None),
+            // Replay turns errors back into protocol errors, so keep only their message
+            Err(e) => match e.root_cause() {
+                ChromeMcpError::CdpProtocol(message) => (None, Some(message.clone())),
+                other => (None, Some(other.to_string())),
+            },
+        };
+        self.record(&RecordedCommand {
+            method: method.to_string(),
+            params,
+            result: recorded_result,
+            error,
+        })
+    }
+}
+
+impl<B: CdpClientBackend> CdpClientBackend for RecordingCdpClient<B> {
+    async fn send_command_inner(&mut self, method: &str, params: Option<Value>) -> Result<Value> {
+        let result = self.inner.send_command_inner(method, params.clone()).await;
+        self.record_result(method, params, &result)?;
+        result
+    }
+
+    /// Recorded like any other command, so replay answers it from the same queue
+    async fn send_session_command_inner(&mut self, session_id: &str, method: &str, params: Option<Value>) -> Result<Value> {
+        let result = self.inner.send_session_command_inner(session_id, method, params.clone()).await;
+        self.record_result(method, params, &result)?;
+        result
+    }
+
+    async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
+        self.inner.list_tabs().await
+    }
+
+    async fn create_tab(&self, url: Option<&str>) -> Result<TabInfo> {
+        self.inner.create_tab(url).await
+    }
+
+    async fn close_tab(&self, tab_id: &str) -> Result<()> {
+        self.inner.close_tab(tab_id).await
+    }
+
+    async fn connect_to_tab(&mut self, tab_id: &str) -> Result<()> {
+        self.inner.connect_to_tab(tab_id).await
+    }
+
+    fn current_tab_id(&self) -> Option<&str> {
+        self.inner.current_tab_id()
+    }
+
+    fn events(&self) -> &EventSubscribers {
+        self.inner.events()
+    }
+
+    fn frame_bytes(&self) -> &FrameBytes {
+        self.inner.frame_bytes()
+    }
+}
+
+/// `Runtime.evaluate` result for an expression that returned `value`
+#[cfg(test)]
+pub fn evaluate_response(value: Value) -> Value {
+    serde_json::json!({ "result": { "type": json_type(&value), "value": value } })
+}
+
+#[cfg(test)]
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Null | Value::Array(_) | Value::Object(_) => "object",
+    }
+}
+
+fn mock_tab(id: &str, url: &str) -> TabInfo {
+    TabInfo {
+        id: id.to_string(),
+        title: String::new(),
+        url: url.to_string(),
+        description: String::new(),
+        websocket_debugger_url: None,
+        target_type: Some("page".to_string()),
+    }
+}
+
+fn method_matches(pattern: &str, method: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => method.starts_with(prefix),
+        None => pattern == method,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_mock_cdp_client_scripted_responses() {
+        let mock = MockCdpClient::new(vec![
+            ("Page.navigate", json!({ "frameId": "F1" })),
+            ("Runtime.*", evaluate_response(json!(42))),
+        ]);
+        mock.push_error("DOM.getDocument", "Not attached to an active page");
+        let mut cdp = mock.client();
+
+        // Responses are picked by method, not strictly in queue order
+        assert_eq!(cdp.evaluate_js("6 * 7").await.unwrap()["value"], 42);
+        assert_eq!(cdp.navigate("https://example.com").await.unwrap()["frameId"], "F1");
+
+        let error = cdp.send_command("DOM.getDocument", None).await.unwrap_err();
+        assert!(error.to_string().contains("Not attached to an active page"));
+        assert_eq!(error.trace()[0].operation, "send_command");
+
+        let error = cdp.send_command("Page.reload", None).await.unwrap_err();
+        assert!(error.to_string().contains("No scripted response for Page.reload"));
+
+        let calls = mock.calls();
+        assert_eq!(calls.len(), 4);
+        assert_eq!(calls[1].0, "Page.navigate");
+        assert_eq!(calls[1].1, Some(json!({ "url": "https://example.com" })));
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_incompatible_chrome_version() {
+        let mock = MockCdpClient::new(vec![
+            ("Browser.getVersion", json!({ "product": "HeadlessChrome/72.0.3626.121" })),
+        ]);
+        mock.push_error_code("Accessibility.getRootAXNode", -32601, "'Accessibility.getRootAXNode' wasn't found");
+        mock.push_error_code("Runtime.addBinding", -32601, "'Runtime.addBinding' wasn't found");
+        let mut cdp = mock.client();
+
+        let error = cdp.get_root_ax_node().await.unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            ChromeMcpError::IncompatibleChromeVersion { required: 91, actual: 72, .. }
+        ));
+        assert_eq!(error.trace()[0].context["method"], "Accessibility.getRootAXNode");
+
+        // Methods the connected Chrome supports keep their error; the version is fetched once
+        let error = cdp.send_command("Runtime.addBinding", Some(json!({ "name": "b" }))).await.unwrap_err();
+        assert!(matches!(error.root_cause(), ChromeMcpError::CdpProtocol(_)));
+        assert_eq!(cdp.version().await.unwrap().major, 72);
+        assert_eq!(mock.calls().iter().filter(|(method, _)| method == "Browser.getVersion").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_cdp_client_tabs() {
+        let mock = MockCdpClient::new(vec![]);
+        let mut cdp = mock.client();
+
+        assert!(cdp.connect_to_tab("missing").await.is_err());
+        cdp.connect_to_tab(MOCK_TAB_ID).await.unwrap();
+        assert_eq!(cdp.current_tab_id(), Some(MOCK_TAB_ID));
+
+        let tab = cdp.create_tab(Some("https://example.com")).await.unwrap();
+        assert_eq!(cdp.list_tabs().await.unwrap().len(), 2);
+        cdp.close_tab(&tab.id).await.unwrap();
+        assert_eq!(cdp.list_tabs().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.ndjson");
+
+        let live = MockCdpClient::new(vec![
+            ("Page.navigate", json!({ "frameId": "F1", "loaderId": "L1" })),
+            ("Runtime.evaluate", evaluate_response(json!("Example Domain"))),
+        ]);
+        live.push_error("DOM.querySelector", "Could not find node with given id");
+
+        let mut recording = CdpClient::with_backend(RecordingCdpClient::create(live, &path).unwrap());
+        recording.navigate("https://example.com").await.unwrap();
+        let title = recording.evaluate_js("document.title").await.unwrap();
+        let error = recording.send_command("DOM.querySelector", Some(json!({ "nodeId": 1, "selector": "#x" })))
+            .await
+            .unwrap_err();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<RecordedCommand> = contents.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].method, "Page.navigate");
+        assert_eq!(lines[0].params, Some(json!({ "url": "https://example.com" })));
+        assert!(lines[2].result.is_none());
+
+        let replay = MockCdpClient::from_recording(&path).unwrap();
+        let mut cdp = replay.client();
+        assert_eq!(cdp.navigate("https://example.com").await.unwrap()["frameId"], "F1");
+        assert_eq!(cdp.evaluate_js("document.title").await.unwrap(), title);
+        let replayed_error = cdp.send_command("DOM.querySelector", None).await.unwrap_err();
+        assert_eq!(replayed_error.to_string(), error.to_string());
+        assert_eq!(replay.remaining(), 0);
+
+        std::fs::write(&path, "{\"method\": \"Page.navigate\"}\nnot json\n").unwrap();
+        let error = MockCdpClient::from_recording(&path).unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_method_matches() {
+        assert!(method_matches("Page.navigate", "Page.navigate"));
+        assert!(!method_matches("Page.navigate", "Page.navigateToHistoryEntry"));
+        assert!(method_matches("Page.*", "Page.navigateToHistoryEntry"));
+        assert!(method_matches("*", "DOM.getDocument"));
+        assert!(!method_matches("DOM.*", "Page.navigate"));
+    }
+}
@@ -0,0 +1,1530 @@
+use crate::accessibility::TreeExportFormat;
+use crate::browser::{BrowserPermission, ResourceType, StorageType};
+use crate::error::{ChromeMcpError, Result};
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Typed tool arguments whose MCP `input_schema` is derived from the struct itself,
+/// so the advertised schema and the argument parsing cannot drift apart
+pub trait ToolParams: JsonSchema + DeserializeOwned {
+    /// JSON schema of the parameters, in the form used by `Tool::input_schema`
+    fn input_schema() -> Value {
+        let settings = SchemaSettings::draft07().with(|s| {
+            s.option_nullable = false;
+            s.option_add_null_type = false;
+            s.inline_subschemas = true;
+        });
+        let root = settings.into_generator().into_root_schema_for::<Self>();
+
+        let mut schema = serde_json::to_value(root.schema).unwrap_or_default();
+        if let Some(object) = schema.as_object_mut() {
+            // Tool name and description already identify the schema
+            object.remove("title");
+            object.remove("description");
+            // Clients expect a properties object even for tools without parameters
+            object.entry("properties").or_insert_with(|| Value::Object(Default::default()));
+        }
+        schema
+    }
+
+    /// Parse tool call arguments
+    fn from_arguments(arguments: &Value) -> Result<Self> {
+        serde_json::from_value(arguments.clone())
+            .map_err(|e| ChromeMcpError::mcp_protocol_error(format!("Invalid parameters: {}", e)))
+    }
+}
+
+impl<T: JsonSchema + DeserializeOwned> ToolParams for T {}
+
+/// `chrome_navigate` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NavigateParams {
+    /// The URL to navigate to
+    pub url: String,
+    /// Fail if the page throws more uncaught exceptions than the error budget (default 0) allows
+    #[serde(default)]
+    pub fail_on_js_errors: bool,
+    /// Scroll to this position once the page has loaded
+    pub restore_scroll: Option<ScrollPosition>,
+    /// Scroll the new page to the current page's scroll position (unless restore_scroll is given)
+    #[serde(default)]
+    pub keep_scroll: bool,
+}
+
+/// Page scroll offset in CSS pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// `chrome_reload` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReloadParams {
+    /// Bypass the browser cache, like a hard reload
+    #[serde(default)]
+    pub ignore_cache: bool,
+    /// JavaScript to run once in every frame of the reloaded page
+    pub inject_script: Option<String>,
+}
+
+/// `chrome_click` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClickParams {
+    /// CSS selector, text content, or accessibility label of element to click
+    pub target: String,
+    /// Click at a random point up to this many pixels from the element center (overrides --click-jitter)
+    pub jitter_px: Option<u32>,
+    /// Fail if the page throws more uncaught exceptions than the error budget (default 0) allows
+    #[serde(default)]
+    pub fail_on_js_errors: bool,
+}
+
+/// `chrome_type` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TypeParams {
+    /// Text to type
+    pub text: String,
+    /// Optional CSS selector to focus first
+    pub selector: Option<String>,
+    /// Type one character at a time, waiting at least this long between characters
+    pub min_delay_ms: Option<u64>,
+    /// Type one character at a time, waiting at most this long between characters
+    pub max_delay_ms: Option<u64>,
+    /// Fail if the page throws more uncaught exceptions than the error budget (default 0) allows
+    #[serde(default)]
+    pub fail_on_js_errors: bool,
+}
+
+/// Screenshot image format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpeg",
+        }
+    }
+}
+
+/// What `chrome_screenshot` captures: the page or an element, or one viewport screenshot
+/// per responsive breakpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenshotAction {
+    Capture,
+    Breakpoints,
+}
+
+/// `chrome_screenshot` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenshotParams {
+    /// Image format: png or jpeg
+    pub format: Option<ImageFormat>,
+    /// JPEG quality (1-100)
+    #[schemars(range(min = 1, max = 100))]
+    pub quality: Option<u32>,
+    /// Capture full page or just viewport
+    #[serde(default)]
+    pub full_page: bool,
+    /// Capture the full page as a list of viewport screenshots, for very long pages
+    #[serde(default)]
+    pub chunked: bool,
+    /// Height of each chunk in CSS pixels (default: viewport height)
+    #[schemars(range(min = 1))]
+    pub chunk_height: Option<u32>,
+    /// CSS selector of an element to capture instead of the page
+    pub element: Option<String>,
+    /// Capture the whole element, scrolling over the parts outside the viewport (PNG)
+    #[serde(default)]
+    pub full_element: bool,
+    /// capture (default) or breakpoints
+    pub action: Option<ScreenshotAction>,
+    /// Viewport widths to capture (for breakpoints action)
+    pub widths: Option<Vec<u32>>,
+    /// Named breakpoints to capture: xs (360), sm (640), md (768), lg (1024), xl (1280), 2xl (1536)
+    pub presets: Option<Vec<String>>,
+    /// Viewport height for the breakpoints action (default: current viewport height)
+    #[schemars(range(min = 1))]
+    pub height: Option<u32>,
+    /// Save the screenshot (viewport, or `element`) to the screenshot directory and return the file path instead of base64
+    #[serde(default)]
+    pub auto_save: bool,
+    /// File name for auto_save, with {timestamp}, {tab_id}, {url_slug} and {counter}
+    /// (default {timestamp}-{url_slug}-{counter})
+    pub name_template: Option<String>,
+}
+
+/// `chrome_evaluate` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EvaluateParams {
+    /// JavaScript code to execute
+    pub javascript: Option<String>,
+    /// JavaScript expressions to execute instead of `javascript`, returned as an array of results
+    pub batch: Option<Vec<String>>,
+    /// Milliseconds to wait between batch expressions, which then run one at a time (default 0)
+    pub delay_ms: Option<u64>,
+    /// Run batch expressions concurrently instead of one after the other
+    #[serde(default)]
+    pub parallel: bool,
+}
+
+/// `chrome_hover` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HoverParams {
+    /// CSS selector or text of element to hover over
+    pub target: String,
+}
+
+/// `chrome_right_click` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RightClickParams {
+    /// CSS selector or text of element to right-click
+    pub target: String,
+    /// Name of the item to select in the context menu the page opens
+    pub context_menu_item: Option<String>,
+}
+
+/// `chrome_double_click` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DoubleClickParams {
+    /// CSS selector or text of element to double-click
+    pub target: String,
+    /// Milliseconds between the two clicks (default 100)
+    pub delay_ms: Option<u64>,
+}
+
+/// `chrome_keyboard_shortcut` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KeyboardShortcutParams {
+    /// Modifiers (Ctrl, Cmd/Meta, Alt, Shift) and a key joined by `+`, e.g. `Ctrl+C`, `Cmd+Shift+T`, `Alt+F4` or `Enter`
+    pub shortcut: String,
+}
+
+/// `chrome_drag_and_drop` parameters: the source and the target are each given either as an
+/// element or as a point
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DragAndDropParams {
+    /// CSS selector or text of the element to drag
+    pub source_selector: Option<String>,
+    /// CSS selector or text of the element to drop onto
+    pub target_selector: Option<String>,
+    /// Viewport x coordinate to drag from, instead of source_selector
+    pub source_x: Option<f64>,
+    /// Viewport y coordinate to drag from, instead of source_selector
+    pub source_y: Option<f64>,
+    /// Viewport x coordinate to drop at, instead of target_selector
+    pub target_x: Option<f64>,
+    /// Viewport y coordinate to drop at, instead of target_selector
+    pub target_y: Option<f64>,
+    /// Duration of the drag in milliseconds (default 500)
+    pub duration_ms: Option<u64>,
+}
+
+/// `chrome_select` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SelectParams {
+    /// CSS selector of the select element
+    pub selector: String,
+    /// Value of the option to select
+    pub value: String,
+}
+
+/// `chrome_upload_file` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadFileParams {
+    /// CSS selector of the `<input type="file">` element
+    pub selector: String,
+    /// Absolute path of the local file to upload
+    pub file_path: Option<String>,
+    /// Absolute paths of several files to upload at once, for `multiple` inputs
+    pub files: Option<Vec<String>>,
+}
+
+/// `chrome_accessibility_tree` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AccessibilityTreeParams {
+    /// Return a text summary instead of full tree
+    #[serde(default)]
+    pub summary: bool,
+    /// Export format: json, text_outline, aria_roles, markdown or csv (overrides summary)
+    pub format: Option<TreeExportFormat>,
+    /// Only fetch nodes down to this depth (root = 0); faster on large pages
+    pub depth: Option<u32>,
+}
+
+/// `chrome_native_click` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NativeClickParams {
+    /// X coordinate on screen
+    pub x: f64,
+    /// Y coordinate on screen
+    pub y: f64,
+}
+
+/// `chrome_native_accessibility` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NativeAccessibilityParams {
+    /// X coordinate on screen
+    pub x: f64,
+    /// Y coordinate on screen
+    pub y: f64,
+}
+
+/// `chrome_find` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindParams {
+    /// Search query (text, role, or CSS selector)
+    pub query: String,
+    /// Maximum number of elements to return, most relevant first (default 10)
+    pub max_results: Option<usize>,
+    /// Add a `stable_selector` to each result, built from test ids, ARIA labels, ids or roles
+    /// rather than layout and styling classes
+    #[serde(default)]
+    pub generate_selector: bool,
+}
+
+/// `chrome_check_links` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckLinksParams {
+    /// Only check links inside the elements matching these CSS selectors (default: whole page)
+    pub selectors: Option<Vec<String>>,
+    /// Number of links checked at the same time (default 5)
+    #[schemars(range(min = 1))]
+    pub concurrent: Option<usize>,
+    /// Also check links to other origins, with HEAD requests
+    #[serde(default)]
+    pub include_external: bool,
+    /// Time allowed for each link in milliseconds (default 10000)
+    pub timeout_ms_per_link: Option<u64>,
+}
+
+/// `chrome_probe_ports` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProbePortsParams {
+    /// Host to probe from the page, e.g. 127.0.0.1 or an internal hostname
+    pub host: String,
+    /// Ports to probe
+    pub ports: Vec<u16>,
+    /// Time allowed for each port in milliseconds (default 2000)
+    pub timeout_ms: Option<u64>,
+}
+
+/// `chrome_menu` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MenuParams {
+    /// Element (CSS selector, text or role) that opens the menu when hovered
+    pub trigger: String,
+    /// Accessible names of the menu items to follow, outermost first; the last one is clicked
+    pub path: Vec<String>,
+}
+
+/// `chrome_sse` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SseParams {
+    /// URL of the event stream, relative to the page or absolute
+    pub url: String,
+    /// Event types to collect (default ["message"])
+    pub events: Option<Vec<String>>,
+    /// Stop after this many events (default 10)
+    #[schemars(range(min = 1))]
+    pub max_events: Option<usize>,
+    /// Stop after this many milliseconds (default 10000, at most 25000)
+    #[schemars(range(max = 25000))]
+    pub timeout_ms: Option<u64>,
+}
+
+/// `chrome_infinite_scroll` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct InfiniteScrollParams {
+    /// CSS selector of the sentinel element that triggers loading when it becomes visible
+    pub selector: String,
+    /// JavaScript to evaluate once the sentinel is visible, e.g. to click a "load more" button
+    pub on_intersect_js: Option<String>,
+    /// Time allowed for the sentinel to become visible in milliseconds (default 10000, at most 25000)
+    #[schemars(range(max = 25000))]
+    pub timeout_ms: Option<u64>,
+}
+
+/// `chrome_fetch` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FetchParams {
+    /// URL to request, relative to the page or absolute
+    pub url: String,
+    /// HTTP method (default GET)
+    pub method: Option<String>,
+    /// Request headers, e.g. Accept, Accept-Encoding, Cache-Control, If-None-Match
+    pub headers: Option<HashMap<String, String>>,
+    /// Request body
+    pub body: Option<String>,
+    /// Follow redirects (default true); otherwise a redirect is returned with status 0
+    pub follow_redirects: Option<bool>,
+}
+
+/// `chrome_content` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContentParams {
+    /// CSS selector of the element to convert (default the `<main>` element, else the first `<article>`, else the body)
+    pub selector: Option<String>,
+    /// Keep images as `![alt](src)`
+    #[serde(default)]
+    pub include_images: bool,
+}
+
+/// `chrome_ocr` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OcrParams {
+    /// CSS selector of the element to read, e.g. an image or a banner
+    pub selector: Option<String>,
+    /// Area of the page to read instead of an element
+    pub region: Option<Region>,
+}
+
+/// Rectangle of the page in CSS pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Region {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Parameters of tools that take none
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NoParams {}
+
+/// `chrome_tabs` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TabsAction {
+    List,
+    Create,
+    Switch,
+    Close,
+    Map,
+    Thumbnails,
+}
+
+/// `chrome_tabs` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TabsParams {
+    /// Action to perform
+    pub action: TabsAction,
+    /// Tab ID (for switch/close actions)
+    pub tab_id: Option<String>,
+    /// URL for new tab (create action)
+    pub url: Option<String>,
+    /// Tool to run on each tab (map action), e.g. chrome_screenshot
+    pub tool_name: Option<String>,
+    /// Arguments passed to the tool for each tab (map action)
+    pub tool_arguments: Option<serde_json::Map<String, Value>>,
+    /// Maximum thumbnail width in pixels (thumbnails action, default 320)
+    pub max_width: Option<u32>,
+    /// Maximum thumbnail height in pixels (thumbnails action, default 200)
+    pub max_height: Option<u32>,
+}
+
+/// Direction `chrome_scroll` moves a scroll-snap container in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapAction {
+    NextSnap,
+    PrevSnap,
+}
+
+/// `chrome_scroll` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollParams {
+    /// Horizontal scroll amount in pixels
+    pub x: Option<i32>,
+    /// Vertical scroll amount in pixels
+    pub y: Option<i32>,
+    /// CSS selector of element to scroll to
+    pub selector: Option<String>,
+    /// Move a scroll-snap container (carousel/slider) to its next or previous snap point
+    pub action: Option<SnapAction>,
+    /// CSS selector of the scroll-snap container (for next_snap/prev_snap)
+    pub container: Option<String>,
+}
+
+/// Condition `chrome_wait` waits for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WaitConditionKind {
+    ElementPresent,
+    ElementVisible,
+    ElementVisibleRatio,
+    ElementClickable,
+    TextPresent,
+    UrlMatches,
+    TitleContains,
+    TitleEquals,
+    TitleMatches,
+    TitleChangedFrom,
+    PageLoad,
+    NetworkIdle,
+    RequestCompleted,
+    RequestFailed,
+}
+
+/// `chrome_wait` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WaitParams {
+    /// Condition type
+    pub condition: WaitConditionKind,
+    /// Target for the condition (selector, text, URL pattern, title; for title_matches a prefix
+    /// or * and ? wildcard pattern; for title_changed_from the original title, default the
+    /// current one; for request_completed/request_failed a URL pattern with * and ? wildcards)
+    pub target: Option<String>,
+    /// HTTP method the request must use (for request_completed)
+    pub method: Option<String>,
+    /// Fraction of the element that must be in the viewport (for element_visible_ratio, default 0.5)
+    pub min_ratio: Option<f64>,
+    /// Requests that may stay in flight for network_idle, such as EventSource streams or long
+    /// polls (default 2, like networkidle2; 0 waits for every request)
+    pub max_inflight: Option<usize>,
+    /// Timeout in milliseconds (default 10000)
+    pub timeout: Option<u64>,
+}
+
+/// `chrome_cookies` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CookiesAction {
+    Get,
+    Set,
+    Clear,
+}
+
+/// `chrome_cookies` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CookiesParams {
+    /// Cookie action
+    pub action: CookiesAction,
+    /// Cookie name (for set action, or to only get cookies with this name)
+    pub name: Option<String>,
+    /// Get the cookies that apply to this URL (for get action, default the current page URL)
+    pub url: Option<String>,
+    /// Cookie value (for set action)
+    pub value: Option<String>,
+    /// Cookie domain (for set action, default localhost)
+    pub domain: Option<String>,
+    /// Cookie path (for set action, default /)
+    pub path: Option<String>,
+}
+
+/// `chrome_pdf` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PdfParams {
+    /// Landscape orientation
+    pub landscape: Option<bool>,
+    /// Include background graphics
+    pub print_background: Option<bool>,
+    /// Scale factor (0.1 to 2.0)
+    #[schemars(range(min = 0.1, max = 2.0))]
+    pub scale: Option<f64>,
+}
+
+/// `chrome_pdf_text` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PdfTextParams {
+    /// Base64-encoded PDF data
+    pub data: String,
+    /// Text inserted between pages (default: blank line)
+    pub page_separator: Option<String>,
+}
+
+/// `chrome_extract` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtractAction {
+    Table,
+    Tables,
+}
+
+/// `chrome_extract` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExtractParams {
+    /// What to extract
+    pub action: ExtractAction,
+    /// CSS selector of the table (for table action)
+    pub selector: Option<String>,
+}
+
+/// `chrome_mock` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MockAction {
+    Add,
+    AddFromFile,
+    Remove,
+    List,
+    Clear,
+}
+
+/// `chrome_mock` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MockParams {
+    /// Mock action
+    pub action: MockAction,
+    /// URL pattern with * and ? wildcards (for add/remove actions)
+    pub url_pattern: Option<String>,
+    /// HTTP status code (for add action, default 200)
+    pub status: Option<u16>,
+    /// Response body (for add action)
+    pub body: Option<String>,
+    /// Response Content-Type (for add action, default application/json)
+    pub content_type: Option<String>,
+    /// JSON fixture file mapping URL patterns to {status, headers, body} (for add_from_file action)
+    pub path: Option<String>,
+}
+
+/// `chrome_auth` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthAction {
+    SetBasicAuth,
+    ClearAuth,
+}
+
+/// `chrome_auth` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuthParams {
+    /// Store credentials or forget all stored credentials
+    pub action: AuthAction,
+    /// Username (for set_basic_auth)
+    pub username: Option<String>,
+    /// Password (for set_basic_auth)
+    pub password: Option<String>,
+    /// Only answer challenges from this host (defaults to any host)
+    pub host: Option<String>,
+    /// Only answer challenges for this realm (defaults to any realm)
+    pub realm: Option<String>,
+}
+
+/// `chrome_resources` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResourcesParams {
+    /// Only list resources of this type
+    pub type_filter: Option<ResourceType>,
+    /// Return the raw content of this resource instead of the list
+    pub url: Option<String>,
+}
+
+/// `chrome_webrtc` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebRtcAction {
+    DetectIps,
+    Disable,
+}
+
+/// `chrome_webrtc` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebRtcParams {
+    /// WebRTC action
+    pub action: WebRtcAction,
+}
+
+/// `chrome_locale` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LocaleAction {
+    SetTimezone,
+    SetLocale,
+    Reset,
+}
+
+/// `chrome_locale` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LocaleParams {
+    /// Locale action
+    pub action: LocaleAction,
+    /// IANA timezone, e.g. America/New_York (for set_timezone action)
+    pub timezone: Option<String>,
+    /// BCP 47 locale, e.g. de-DE (for set_locale action)
+    pub locale: Option<String>,
+}
+
+/// `chrome_geolocation` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GeolocationAction {
+    Set,
+    Clear,
+}
+
+/// `chrome_geolocation` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GeolocationParams {
+    /// Geolocation action
+    pub action: GeolocationAction,
+    /// Latitude in degrees, -90 to 90 (set)
+    pub latitude: Option<f64>,
+    /// Longitude in degrees, -180 to 180 (set)
+    pub longitude: Option<f64>,
+    /// Accuracy in meters (set, default 100)
+    pub accuracy: Option<f64>,
+}
+
+/// `chrome_find_text` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FindTextParams {
+    /// Text or regular expression to search for
+    pub pattern: String,
+    /// Match case exactly
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Treat pattern as a regular expression instead of literal text
+    #[serde(default)]
+    pub use_regex: bool,
+}
+
+/// `chrome_element_state` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ElementStateParams {
+    /// CSS selector of the element
+    pub selector: String,
+    /// Check or uncheck the checkbox/radio before reading its state
+    pub checked: Option<bool>,
+}
+
+/// `chrome_focus` and `chrome_blur` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FocusParams {
+    /// CSS selector of the element
+    pub selector: String,
+}
+
+/// `chrome_get_focused` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetFocusedParams {
+    /// Also return the focused node of the accessibility tree (default false)
+    pub include_accessibility: Option<bool>,
+}
+
+/// `chrome_dom_hash` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DomHashAction {
+    Compute,
+    AssertChanged,
+}
+
+/// `chrome_dom_hash` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DomHashParams {
+    /// Compute a hash, or check the DOM differs from baseline_hash
+    pub action: DomHashAction,
+    /// Only hash the element matching this selector (defaults to document.body)
+    pub selector: Option<String>,
+    /// Hash returned by a previous compute (for assert_changed)
+    pub baseline_hash: Option<String>,
+    /// Keep re-checking until the DOM changes or this timeout expires (for assert_changed)
+    pub timeout_ms: Option<u64>,
+}
+
+/// `chrome_dom_diff` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DomDiffAction {
+    Snapshot,
+    Diff,
+}
+
+/// `chrome_dom_diff` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DomDiffParams {
+    /// Take a snapshot, or diff the before and after snapshots (default diff)
+    pub action: Option<DomDiffAction>,
+    /// Only snapshot the element matching this selector (defaults to the whole document, for snapshot)
+    pub selector: Option<String>,
+    /// Label returned with the snapshot (for snapshot)
+    pub label: Option<String>,
+    /// Snapshot taken before the operation (for diff)
+    pub before: Option<String>,
+    /// Snapshot taken after the operation (for diff)
+    pub after: Option<String>,
+    /// Also return unchanged lines (for diff)
+    #[serde(default)]
+    pub include_equal: bool,
+}
+
+/// `chrome_element_bounds` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ElementBoundsParams {
+    /// CSS selector of the element
+    pub selector: String,
+}
+
+/// `chrome_get_text` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetTextParams {
+    /// CSS selector of the element
+    pub selector: String,
+    /// Include the text of hidden descendants (`textContent` instead of `innerText`, default false)
+    pub include_hidden: Option<bool>,
+    /// Return the text of every matching element as an array (default false)
+    pub all: Option<bool>,
+}
+
+/// `chrome_get_html` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetHtmlParams {
+    /// CSS selector of the element
+    pub selector: String,
+    /// Include the element's own tag (`outerHTML`) rather than only its content (`innerHTML`, default true)
+    pub outer: Option<bool>,
+}
+
+/// `chrome_get_attribute` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetAttributeParams {
+    /// CSS selector of the element
+    pub selector: String,
+    /// Attribute name, e.g. `href` or `data-id`
+    pub attribute: String,
+}
+
+/// Where `chrome_get_page_source` reads the page HTML from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSourceKind {
+    Dom,
+    Network,
+}
+
+/// `chrome_get_page_source` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PageSourceParams {
+    /// `dom` for the current DOM including JavaScript changes, `network` for the document as the server sent it (default dom)
+    pub source: Option<PageSourceKind>,
+}
+
+/// `chrome_track_position` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TrackPositionParams {
+    /// CSS selector of the element
+    pub selector: String,
+    /// Polling interval in milliseconds (default 16)
+    pub interval_ms: Option<u64>,
+    /// How long to track in milliseconds (default 1000)
+    pub duration_ms: Option<u64>,
+}
+
+/// `chrome_cpu_profile` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CpuProfileAction {
+    Start,
+    Stop,
+    Save,
+}
+
+/// `chrome_cpu_profile` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CpuProfileParams {
+    /// Start profiling, stop it, or save the last stopped profile to a file
+    pub action: CpuProfileAction,
+    /// Sampling interval in microseconds (for start, default 1000)
+    pub sample_interval_us: Option<u32>,
+    /// Output .cpuprofile path (for save)
+    pub filename: Option<String>,
+}
+
+/// `chrome_popup` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PopupAction {
+    Wait,
+    Switch,
+    Close,
+    ListPopups,
+}
+
+/// `chrome_popup` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PopupParams {
+    /// Popup action to perform
+    pub action: PopupAction,
+    /// Wildcard URL pattern of the popup, e.g. *accounts.google.com* (for wait, default *)
+    pub url_pattern: Option<String>,
+    /// How long to wait for the popup (for wait, default 10000)
+    pub timeout_ms: Option<u64>,
+    /// Switch to the popup once it appears (for wait)
+    #[serde(default)]
+    pub switch: bool,
+    /// Popup tab ID (for switch)
+    pub tab_id: Option<String>,
+}
+
+/// `chrome_extension_debug` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtensionDebugAction {
+    List,
+    Switch,
+    Evaluate,
+}
+
+/// `chrome_extension_debug` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExtensionDebugParams {
+    /// Extension DevTools action
+    pub action: ExtensionDebugAction,
+    /// Target ID from list (for switch, and for evaluate instead of the switched-to target)
+    pub target_id: Option<String>,
+    /// JavaScript to run in the extension context (for evaluate action)
+    pub javascript: Option<String>,
+}
+
+/// `chrome_form` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FormAction {
+    GetErrors,
+    Validate,
+    AuditLabels,
+}
+
+/// `chrome_form` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FormParams {
+    /// List validation errors, call reportValidity() on the form, or list inputs screen readers
+    /// cannot announce with a label
+    pub action: FormAction,
+    /// CSS selector of the form
+    pub form_selector: String,
+}
+
+/// `chrome_cors_test` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CorsTestParams {
+    /// Resource URL to test
+    pub url: String,
+    /// Requesting origin, e.g. https://app.example.com
+    pub origin: String,
+    /// HTTP method of the cross-origin request (default GET)
+    pub method: Option<String>,
+    /// Request header names the cross-origin request would send
+    pub headers: Option<Vec<String>>,
+}
+
+/// `chrome_svg` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum SvgAction {
+    #[serde(rename = "get_elements")]
+    Elements,
+    #[serde(rename = "get_text")]
+    Text,
+    #[serde(rename = "get_path_data")]
+    PathData,
+}
+
+/// `chrome_svg` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SvgParams {
+    /// What to extract from the SVG
+    pub action: SvgAction,
+    /// CSS selector of the <svg> element
+    pub selector: String,
+}
+
+/// `chrome_canvas` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CanvasAction {
+    ReadPixels,
+    DataUrl,
+    CompareRegion,
+}
+
+/// `chrome_canvas` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CanvasParams {
+    /// Canvas action to perform
+    pub action: CanvasAction,
+    /// CSS selector of the canvas
+    pub selector: String,
+    /// Region left edge in canvas pixels (default 0)
+    pub x: Option<u32>,
+    /// Region top edge in canvas pixels (default 0)
+    pub y: Option<u32>,
+    /// Region width (default 1)
+    pub width: Option<u32>,
+    /// Region height (default 1)
+    pub height: Option<u32>,
+    /// Expected hex color, e.g. #00ff00 or #00ff0080 (for compare_region)
+    pub expected_color: Option<String>,
+    /// Allowed per-channel difference, 0-255 (for compare_region, default 0)
+    pub tolerance: Option<u8>,
+}
+
+/// `chrome_console` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleAction {
+    Start,
+    Stop,
+    Get,
+}
+
+/// Level of a console message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleLevel {
+    Log,
+    Warn,
+    Error,
+    Debug,
+    Info,
+}
+
+impl ConsoleLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConsoleLevel::Log => "log",
+            ConsoleLevel::Warn => "warn",
+            ConsoleLevel::Error => "error",
+            ConsoleLevel::Debug => "debug",
+            ConsoleLevel::Info => "info",
+        }
+    }
+}
+
+/// `chrome_console` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConsoleParams {
+    /// Start watching, stop recording console output, or get what was recorded
+    pub action: ConsoleAction,
+    /// Only return console entries of this level (for get action)
+    pub level: Option<ConsoleLevel>,
+    /// Console entries to keep, dropping the oldest (for start action, default 1000)
+    pub max_entries: Option<usize>,
+}
+
+/// `chrome_timing` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TimingAction {
+    Navigation,
+    Resources,
+}
+
+/// `chrome_timing` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimingParams {
+    /// Timing of the main document load, or of every fetched resource
+    pub action: TimingAction,
+}
+
+/// `chrome_network` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkAction {
+    Start,
+    Stop,
+    Get,
+    GetResponse,
+}
+
+/// `chrome_network` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NetworkParams {
+    /// Start capturing requests, stop capturing, get the captured requests, or get one response with its body
+    pub action: NetworkAction,
+    /// Only return requests whose URL matches this regular expression (for get action)
+    pub url_pattern: Option<String>,
+    /// Fetch each response body once it finishes loading (for start action, default false)
+    pub capture_body: Option<bool>,
+    /// Requests to keep, dropping the oldest (for start action, default 1000)
+    pub max_entries: Option<usize>,
+    /// Largest response body to capture in bytes; larger ones are left to get_response (for start action, default 1048576)
+    pub max_body_bytes: Option<usize>,
+    /// CDP request ID of the captured request (for get_response action)
+    pub request_id: Option<String>,
+    /// Pretty-print the result JSON (default true)
+    pub pretty: Option<bool>,
+}
+
+/// `chrome_storage` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageAction {
+    Get,
+    Set,
+    Remove,
+    Clear,
+    Keys,
+    TriggerEvent,
+    Listen,
+}
+
+/// `chrome_storage` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StorageParams {
+    /// Storage action
+    pub action: StorageAction,
+    /// Storage key (for set, remove and trigger_event; for get, omit to read every entry)
+    pub key: Option<String>,
+    /// Value to store; anything but a string is stored as JSON (for set action)
+    pub value: Option<Value>,
+    /// Previous value, omit for a newly added key (for trigger_event action)
+    pub old_value: Option<String>,
+    /// New value, omit for a removed key (for trigger_event action)
+    pub new_value: Option<String>,
+    /// Storage area (for every action but listen, default local)
+    pub storage_type: Option<StorageType>,
+    /// How long to listen for storage events (for listen action, default 5000, at most 25000)
+    #[schemars(range(max = 25000))]
+    pub timeout_ms: Option<u64>,
+}
+
+/// `chrome_mark` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkAction {
+    Add,
+    GetMarks,
+}
+
+/// `chrome_mark` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MarkParams {
+    /// Add a mark, or get all performance marks
+    pub action: MarkAction,
+    /// Mark label (for add action)
+    pub label: Option<String>,
+}
+
+/// Kind of inputs `chrome_fuzz` tries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FuzzStrategyKind {
+    Random,
+    Boundary,
+    Sql,
+    Xss,
+    Custom,
+}
+
+/// `chrome_fuzz` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FuzzParams {
+    /// CSS selector of the input or textarea to fuzz
+    pub selector: String,
+    /// Kind of inputs to try
+    pub strategy: FuzzStrategyKind,
+    /// Number of inputs to submit (default 10)
+    #[schemars(range(min = 1, max = 100))]
+    pub iterations: Option<u32>,
+    /// Inputs to try (for custom strategy)
+    pub inputs: Option<Vec<String>>,
+}
+
+/// `chrome_audit` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    RenderBlocking,
+    JsBundles,
+}
+
+/// `chrome_audit` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditParams {
+    /// Audit to run
+    pub action: AuditAction,
+    /// Also add `defer` to the blocking head scripts in the live DOM (for render_blocking action)
+    #[serde(default)]
+    pub fix: bool,
+}
+
+/// `chrome_download` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadAction {
+    SetDirectory,
+    Progress,
+    Wait,
+}
+
+/// `chrome_download` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DownloadParams {
+    /// Download action
+    pub action: DownloadAction,
+    /// Download directory (for set_directory action)
+    pub path: Option<String>,
+    /// File name pattern with * and ? wildcards (for progress/wait actions)
+    pub pattern: Option<String>,
+    /// How long to wait for the download to complete (for wait action, default 30000)
+    pub timeout_ms: Option<u64>,
+}
+
+/// `chrome_fullscreen` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FullscreenAction {
+    Enter,
+    Exit,
+    Status,
+}
+
+/// `chrome_fullscreen` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FullscreenParams {
+    /// Fullscreen action
+    pub action: FullscreenAction,
+    /// CSS selector of the element to make fullscreen (for enter action, defaults to the whole page)
+    pub selector: Option<String>,
+}
+
+/// `chrome_css_vars` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CssVarsAction {
+    List,
+    Set,
+}
+
+/// `chrome_css_vars` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CssVarsParams {
+    /// CSS variable action
+    pub action: CssVarsAction,
+    /// CSS selector of the element to read values from or set the variable on (defaults to the
+    /// root element)
+    pub selector: Option<String>,
+    /// Custom property name, starting with -- (for set action)
+    pub variable: Option<String>,
+    /// New value (for set action)
+    pub value: Option<String>,
+}
+
+/// `chrome_permissions` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionsAction {
+    Query,
+    QueryAll,
+    Grant,
+    Deny,
+}
+
+/// `chrome_permissions` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PermissionsParams {
+    /// Permission action
+    pub action: PermissionsAction,
+    /// Permission (for query, grant and deny actions)
+    pub permission: Option<BrowserPermission>,
+}
+
+/// `chrome_broadcast` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BroadcastAction {
+    Listen,
+    Post,
+}
+
+/// `chrome_broadcast` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BroadcastParams {
+    /// BroadcastChannel action
+    pub action: BroadcastAction,
+    /// Channel name
+    pub channel: String,
+    /// Message to post, any JSON value (for post action)
+    pub message: Option<Value>,
+    /// Stop listening after this many messages (for listen action, default 10)
+    pub max_messages: Option<usize>,
+    /// How long to listen for messages (for listen action, default 5000, at most 25000)
+    #[schemars(range(max = 25000))]
+    pub timeout_ms: Option<u64>,
+}
+
+/// `chrome_wasm` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmAction {
+    List,
+    CallExport,
+}
+
+/// `chrome_wasm` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WasmParams {
+    /// WebAssembly action (modules are tracked from the first call on; reload the page to include
+    /// the ones loaded at startup)
+    pub action: WasmAction,
+    /// Name of the exported function (for call_export action)
+    pub function: Option<String>,
+    /// Arguments of the function (for call_export action)
+    pub args: Option<Vec<f64>>,
+    /// URL the module was loaded from, when several modules export the function (for
+    /// call_export action)
+    pub module: Option<String>,
+}
+
+/// `chrome_status` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusAction {
+    Metrics,
+    ResetMetrics,
+}
+
+/// `chrome_status` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StatusParams {
+    /// Status action
+    pub action: StatusAction,
+}
+
+/// `chrome_clipboard` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardAction {
+    Read,
+    ReadImage,
+    ReadItems,
+    Write,
+}
+
+/// `chrome_clipboard` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClipboardParams {
+    /// Clipboard action
+    pub action: ClipboardAction,
+    /// Text to put on the clipboard (write)
+    pub text: Option<String>,
+}
+
+/// `chrome_history` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryAction {
+    Back,
+    Forward,
+    Go,
+    Get,
+}
+
+/// `chrome_history` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryParams {
+    /// History action
+    pub action: HistoryAction,
+    /// Entries to move, negative to go back (go)
+    pub delta: Option<i32>,
+}
+
+/// `chrome_frame` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameAction {
+    Switch,
+    Main,
+}
+
+/// `chrome_frame` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FrameParams {
+    /// Frame action
+    pub action: FrameAction,
+    /// CSS selector of the iframe element in the top-level document (switch)
+    pub selector: Option<String>,
+}
+
+/// `chrome_session` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionAction {
+    Export,
+    Import,
+    SaveFile,
+    LoadFile,
+}
+
+/// `chrome_session` parameters
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SessionParams {
+    /// Session action
+    pub action: SessionAction,
+    /// Session state previously returned by export (for import action)
+    pub state: Option<serde_json::Map<String, Value>>,
+    /// File path (for save_file/load_file actions)
+    pub path: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Build arguments with a sample value for every property of a schema
+    fn example_arguments(schema: &Value) -> Value {
+        let mut arguments = serde_json::Map::new();
+        for (name, property) in schema["properties"].as_object().unwrap() {
+            let value = if let Some(options) = property.get("enum") {
+                options[0].clone()
+            } else {
+                match property["type"].as_str() {
+                    Some("string") => json!("example"),
+                    Some("integer") => json!(1),
+                    Some("number") => json!(1.5),
+                    Some("boolean") => json!(true),
+                    Some("object") if property.get("properties").is_none() => json!({ "example": "example" }),
+                    Some("object") => example_arguments(property),
+                    Some("array") if property["items"]["type"] == "integer" => json!([1]),
+                    Some("array") if property["items"]["type"] == "number" => json!([1.5]),
+                    Some("array") => json!(["example"]),
+                    // Any JSON value
+                    None if property.get("type").is_none() => json!("example"),
+                    other => panic!("Unexpected type {:?} for {}", other, name),
+                }
+            };
+            arguments.insert(name.clone(), value);
+        }
+        Value::Object(arguments)
+    }
+
+    /// Every schema property must round-trip through the struct, and vice versa
+    fn assert_schema_matches<T: ToolParams + Serialize>() {
+        let schema = T::input_schema();
+        let arguments = example_arguments(&schema);
+
+        let params = T::from_arguments(&arguments).unwrap();
+        let serialized = serde_json::to_value(&params).unwrap();
+
+        let mut schema_fields: Vec<&String> = schema["properties"].as_object().unwrap().keys().collect();
+        let mut struct_fields: Vec<&String> = serialized.as_object().unwrap().keys().collect();
+        schema_fields.sort();
+        struct_fields.sort();
+        assert_eq!(schema_fields, struct_fields);
+
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let mut missing = arguments.clone();
+            missing.as_object_mut().unwrap().remove(required.as_str().unwrap());
+            assert!(T::from_arguments(&missing).is_err(), "{} should be required", required);
+        }
+    }
+
+    #[test]
+    fn test_schemas_match_param_structs() {
+        assert_schema_matches::<NavigateParams>();
+        assert_schema_matches::<ReloadParams>();
+        assert_schema_matches::<ClickParams>();
+        assert_schema_matches::<TypeParams>();
+        assert_schema_matches::<ScreenshotParams>();
+        assert_schema_matches::<EvaluateParams>();
+        assert_schema_matches::<HoverParams>();
+        assert_schema_matches::<GetTextParams>();
+        assert_schema_matches::<GetHtmlParams>();
+        assert_schema_matches::<GetAttributeParams>();
+        assert_schema_matches::<PageSourceParams>();
+        assert_schema_matches::<FocusParams>();
+        assert_schema_matches::<GetFocusedParams>();
+        assert_schema_matches::<RightClickParams>();
+        assert_schema_matches::<DoubleClickParams>();
+        assert_schema_matches::<KeyboardShortcutParams>();
+        assert_schema_matches::<DragAndDropParams>();
+        assert_schema_matches::<SelectParams>();
+        assert_schema_matches::<UploadFileParams>();
+        assert_schema_matches::<AccessibilityTreeParams>();
+        assert_schema_matches::<NativeClickParams>();
+        assert_schema_matches::<NativeAccessibilityParams>();
+        assert_schema_matches::<FindParams>();
+        assert_schema_matches::<ContentParams>();
+        assert_schema_matches::<CheckLinksParams>();
+        assert_schema_matches::<ProbePortsParams>();
+        assert_schema_matches::<FetchParams>();
+        assert_schema_matches::<MenuParams>();
+        assert_schema_matches::<SseParams>();
+        assert_schema_matches::<InfiniteScrollParams>();
+        assert_schema_matches::<OcrParams>();
+        assert_schema_matches::<NoParams>();
+        assert_schema_matches::<TabsParams>();
+        assert_schema_matches::<ScrollParams>();
+        assert_schema_matches::<WaitParams>();
+        assert_schema_matches::<CookiesParams>();
+        assert_schema_matches::<PdfParams>();
+        assert_schema_matches::<PdfTextParams>();
+        assert_schema_matches::<ExtractParams>();
+        assert_schema_matches::<MockParams>();
+        assert_schema_matches::<AuthParams>();
+        assert_schema_matches::<ResourcesParams>();
+        assert_schema_matches::<WebRtcParams>();
+        assert_schema_matches::<LocaleParams>();
+        assert_schema_matches::<GeolocationParams>();
+        assert_schema_matches::<FindTextParams>();
+        assert_schema_matches::<ElementStateParams>();
+        assert_schema_matches::<DomHashParams>();
+        assert_schema_matches::<DomDiffParams>();
+        assert_schema_matches::<ElementBoundsParams>();
+        assert_schema_matches::<TrackPositionParams>();
+        assert_schema_matches::<CpuProfileParams>();
+        assert_schema_matches::<PopupParams>();
+        assert_schema_matches::<ExtensionDebugParams>();
+        assert_schema_matches::<FormParams>();
+        assert_schema_matches::<CorsTestParams>();
+        assert_schema_matches::<SvgParams>();
+        assert_schema_matches::<CanvasParams>();
+        assert_schema_matches::<ConsoleParams>();
+        assert_schema_matches::<TimingParams>();
+        assert_schema_matches::<NetworkParams>();
+        assert_schema_matches::<StorageParams>();
+        assert_schema_matches::<MarkParams>();
+        assert_schema_matches::<FuzzParams>();
+        assert_schema_matches::<AuditParams>();
+        assert_schema_matches::<DownloadParams>();
+        assert_schema_matches::<FullscreenParams>();
+        assert_schema_matches::<CssVarsParams>();
+        assert_schema_matches::<PermissionsParams>();
+        assert_schema_matches::<BroadcastParams>();
+        assert_schema_matches::<WasmParams>();
+        assert_schema_matches::<StatusParams>();
+        assert_schema_matches::<ClipboardParams>();
+        assert_schema_matches::<HistoryParams>();
+        assert_schema_matches::<FrameParams>();
+        assert_schema_matches::<SessionParams>();
+    }
+
+    #[test]
+    fn test_navigate_params_schema() {
+        let schema = NavigateParams::input_schema();
+
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["url"]));
+        assert_eq!(schema["properties"]["url"]["description"], "The URL to navigate to");
+        assert_eq!(schema["properties"]["fail_on_js_errors"]["default"], false);
+        assert!(schema.get("title").is_none());
+    }
+
+    #[test]
+    fn test_from_arguments_errors() {
+        let error = NavigateParams::from_arguments(&json!({})).unwrap_err();
+        assert!(error.to_string().contains("missing field `url`"));
+
+        let params = ScreenshotParams::from_arguments(&json!({ "format": "jpeg", "quality": 80 })).unwrap();
+        assert_eq!(params.format, Some(ImageFormat::Jpeg));
+        assert!(!params.full_page);
+        assert!(ScreenshotParams::from_arguments(&json!({ "format": "gif" })).is_err());
+
+        let params = ResourcesParams::from_arguments(&json!({ "type_filter": "XHR" })).unwrap();
+        assert_eq!(params.type_filter, Some(ResourceType::Xhr));
+        assert!(ResourcesParams::from_arguments(&json!({ "type_filter": "Bogus" })).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::browser::Browser;
+use crate::cdp::WebSocketBackend;
+use crate::error::{ChromeMcpError, Result};
+use crate::mcp::Tool;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Future returned by `AsyncToolHandler::call`
+pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = Result<String>> + 'a>>;
+
+/// Implementation of an MCP tool, called with the `tools/call` arguments
+pub trait AsyncToolHandler<B = WebSocketBackend> {
+    /// Run the tool and return the text content of its result
+    fn call<'a>(&'a self, arguments: &'a Value, browser: &'a mut Browser<B>) -> ToolFuture<'a>;
+}
+
+/// Tools served by `McpServer`, in the order they are listed by `tools/list`
+pub struct ToolRegistry<B = WebSocketBackend> {
+    tools: Vec<(Tool, Box<dyn AsyncToolHandler<B>>)>,
+}
+
+impl<B> Default for ToolRegistry<B> {
+    fn default() -> Self {
+        Self { tools: Vec::new() }
+    }
+}
+
+impl<B> ToolRegistry<B> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tool; names must be unique
+    pub fn register(&mut self, tool: Tool, handler: Box<dyn AsyncToolHandler<B>>) -> Result<()> {
+        if self.tools.iter().any(|(t, _)| t.name == tool.name) {
+            return Err(ChromeMcpError::invalid_operation(format!("Tool already registered: {}", tool.name)));
+        }
+        self.tools.push((tool, handler));
+        Ok(())
+    }
+
+    /// Definitions of all registered tools
+    pub fn tools(&self) -> Vec<Tool> {
+        self.tools.iter().map(|(tool, _)| tool.clone()).collect()
+    }
+
+    /// Handler of the tool with the given name
+    pub fn handler(&self, name: &str) -> Option<&dyn AsyncToolHandler<B>> {
+        self.tools.iter().find(|(tool, _)| tool.name == name).map(|(_, handler)| handler.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Echo;
+
+    impl AsyncToolHandler for Echo {
+        fn call<'a>(&'a self, arguments: &'a Value, _browser: &'a mut Browser) -> ToolFuture<'a> {
+            Box::pin(async move { Ok(arguments.to_string()) })
+        }
+    }
+
+    fn tool(name: &str) -> Tool {
+        Tool {
+            name: name.to_string(),
+            description: "Echo the arguments".to_string(),
+            input_schema: json!({ "type": "object" }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_registry() {
+        let mut registry: ToolRegistry = ToolRegistry::new();
+        registry.register(tool("echo"), Box::new(Echo)).unwrap();
+        registry.register(tool("echo_again"), Box::new(Echo)).unwrap();
+
+        let error = registry.register(tool("echo"), Box::new(Echo)).unwrap_err();
+        assert!(error.to_string().contains("Tool already registered: echo"));
+
+        let names: Vec<String> = registry.tools().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["echo", "echo_again"]);
+
+        let mut browser = Browser::new("localhost", 9222).unwrap();
+        let handler = registry.handler("echo").unwrap();
+        assert_eq!(handler.call(&json!({ "a": 1 }), &mut browser).await.unwrap(), r#"{"a":1}"#);
+        assert!(registry.handler("missing").is_none());
+    }
+}